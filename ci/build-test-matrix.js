@@ -130,6 +130,17 @@ const FULL_MATRIX = [
     "filter": "linux-riscv64",
     "isa": "riscv64",
   },
+  {
+    "os": ubuntu,
+    "target": "loongarch64-unknown-linux-gnu",
+    "gcc_package": "gcc-loongarch64-linux-gnu",
+    "gcc": "loongarch64-linux-gnu-gcc",
+    "qemu": "qemu-loongarch64 -cpu la464 -L /usr/loongarch64-linux-gnu",
+    "qemu_target": "loongarch64-linux-user",
+    "name": "Test Linux loongarch64",
+    "filter": "linux-loongarch64",
+    "isa": "loongarch64",
+  },
   {
     "name": "Tests on i686-unknown-linux-gnu",
     "os": ubuntu,
@@ -8,17 +8,19 @@
 set -ex
 
 compile() {
-  cargo run --no-default-features --features compile,pulley,wat,gc-drc,component-model \
+  cargo run --no-default-features --features compile,pulley,wat,gc-drc,component-model,threads \
     compile --target pulley64 $1 \
     -o ${1%.wat}.cwasm \
     -O memory-reservation=$((1 << 20)) \
     -O memory-guard-size=0 \
     -O signals-based-traps=n \
-    -W function-references
+    -W function-references \
+    "${@:2}"
 }
 
 compile ./tests/all/pulley_provenance_test.wat
 compile ./tests/all/pulley_provenance_test_component.wat
+compile ./tests/all/pulley_provenance_test_threads.wat -W threads
 
 MIRIFLAGS="$MIRIFLAGS -Zmiri-disable-isolation -Zmiri-permissive-provenance" \
   cargo miri test --test all -- \
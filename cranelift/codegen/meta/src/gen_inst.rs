@@ -158,6 +158,8 @@ fn gen_arguments_method(formats: &[Rc<InstructionFormat>], fmt: &mut Formatter,
 /// - `pub fn arguments_mut(&mut self, &pool) -> &mut [Value]`
 /// - `pub fn eq(&self, &other: Self, &pool) -> bool`
 /// - `pub fn hash<H: Hasher>(&self, state: &mut H, &pool)`
+/// - `pub fn deep_clone(&self, &mut pool) -> Self`
+/// - `pub fn map(&self, mapper: impl InstructionMapper) -> Self`
 fn gen_instruction_data_impl(formats: &[Rc<InstructionFormat>], fmt: &mut Formatter) {
     fmt.add_block("impl InstructionData",|fmt| {
         fmt.doc_comment("Get the opcode of this instruction.");
@@ -414,6 +416,91 @@ fn gen_instruction_data_impl(formats: &[Rc<InstructionFormat>], fmt: &mut Format
                 }
             });
                     });
+        fmt.empty_line();
+
+        fmt.doc_comment(r#"
+            Map some functions, described by the given `InstructionMapper`, over each of the
+            entities within this instruction, producing a new `InstructionData`.
+        "#);
+        fmt.add_block("pub fn map(&self, mut mapper: impl crate::ir::instructions::InstructionMapper) -> Self", |fmt| {
+            fmt.add_block("match *self",|fmt| {
+                for format in formats {
+                    let name = format!("Self::{}", format.name);
+                    let mut members = vec!["opcode"];
+
+                    if format.has_value_list {
+                        members.push("args");
+                    } else if format.num_value_operands == 1 {
+                        members.push("arg");
+                    } else if format.num_value_operands > 0 {
+                        members.push("args");
+                    }
+
+                    match format.num_block_operands {
+                        0 => {}
+                        1 => {
+                            members.push("destination");
+                        }
+                        _ => {
+                            members.push("blocks");
+                        }
+                    };
+
+                    for field in &format.imm_fields {
+                        members.push(field.member);
+                    }
+                    let members = members.join(", ");
+
+                    fmt.add_block(&format!("{name}{{{members}}} => "),|fmt| {
+                        fmt.add_block(&format!("Self::{}", format.name), |fmt| {
+                            fmtln!(fmt, "opcode,");
+
+                            if format.has_value_list {
+                                fmtln!(fmt, "args: mapper.map_value_list(args),");
+                            } else if format.num_value_operands == 1 {
+                                fmtln!(fmt, "arg: mapper.map_value(arg),");
+                            } else if format.num_value_operands > 0 {
+                                let maps = (0..format.num_value_operands)
+                                    .map(|i| format!("mapper.map_value(args[{i}])"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                fmtln!(fmt, "args: [{maps}],");
+                            }
+
+                            match format.num_block_operands {
+                                0 => {}
+                                1 => {
+                                    fmtln!(fmt, "destination: mapper.map_block_call(destination),");
+                                }
+                                2 => {
+                                    fmtln!(fmt, "blocks: [mapper.map_block_call(blocks[0]), mapper.map_block_call(blocks[1])],");
+                                }
+                                _ => panic!("Too many block targets in instruction"),
+                            }
+
+                            for field in &format.imm_fields {
+                                let map = match field.kind.rust_type {
+                                    "ir::StackSlot" => Some("map_stack_slot"),
+                                    "ir::DynamicStackSlot" => Some("map_dynamic_stack_slot"),
+                                    "ir::GlobalValue" => Some("map_global_value"),
+                                    "ir::SigRef" => Some("map_sig_ref"),
+                                    "ir::FuncRef" => Some("map_func_ref"),
+                                    "ir::JumpTable" => Some("map_jump_table"),
+                                    "ir::ExceptionTable" => Some("map_exception_table"),
+                                    "ir::Constant" => Some("map_constant"),
+                                    "ir::Immediate" => Some("map_immediate"),
+                                    _ => None,
+                                };
+                                match map {
+                                    Some(map) => fmtln!(fmt, "{}: mapper.{map}({}),", field.member, field.member),
+                                    None => fmtln!(fmt, "{},", field.member),
+                                }
+                            }
+                        });
+                    });
+                }
+            });
+        });
             });
 }
 
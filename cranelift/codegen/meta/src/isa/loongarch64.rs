@@ -0,0 +1,37 @@
+use crate::cdsl::isa::TargetIsa;
+use crate::cdsl::settings::SettingGroupBuilder;
+
+pub(crate) fn define() -> TargetIsa {
+    let mut setting = SettingGroupBuilder::new("loongarch64");
+
+    // We target a minimum of LA64 with the base integer and floating-point
+    // instruction sets, which every LoongArch server and desktop part
+    // implements. Everything else is an optional extension.
+
+    setting.add_bool(
+        "has_lsx",
+        "has extension LSX?",
+        "LSX: 128-bit SIMD instructions",
+        false,
+    );
+    setting.add_bool(
+        "has_lasx",
+        "has extension LASX?",
+        "LASX: 256-bit SIMD instructions",
+        false,
+    );
+    setting.add_bool(
+        "has_lam_bh",
+        "has extension LAM-BH?",
+        "LAM-BH: byte and halfword atomic swap and add instructions",
+        false,
+    );
+    setting.add_bool(
+        "has_lamcas",
+        "has extension LAMCAS?",
+        "LAMCAS: atomic compare-and-swap instructions",
+        false,
+    );
+
+    TargetIsa::new("loongarch64", setting.build())
+}
@@ -3,6 +3,7 @@ use crate::cdsl::isa::TargetIsa;
 use std::fmt;
 
 mod arm64;
+mod loongarch64;
 mod pulley;
 mod riscv64;
 mod s390x;
@@ -15,6 +16,7 @@ pub enum Isa {
     Arm64,
     S390x,
     Riscv64,
+    LoongArch64,
     Pulley32,
     Pulley64,
 }
@@ -35,6 +37,7 @@ impl Isa {
             "s390x" => Some(Isa::S390x),
             x if ["x86_64", "i386", "i586", "i686"].contains(&x) => Some(Isa::X86),
             "riscv64" | "riscv64gc" | "riscv64imac" => Some(Isa::Riscv64),
+            "loongarch64" => Some(Isa::LoongArch64),
             "pulley32" => Some(Isa::Pulley32),
             "pulley64" => Some(Isa::Pulley64),
             _ => None,
@@ -48,6 +51,7 @@ impl Isa {
             Isa::Arm64,
             Isa::S390x,
             Isa::Riscv64,
+            Isa::LoongArch64,
             Isa::Pulley32,
             Isa::Pulley64,
         ]
@@ -62,6 +66,7 @@ impl fmt::Display for Isa {
             Isa::Arm64 => write!(f, "arm64"),
             Isa::S390x => write!(f, "s390x"),
             Isa::Riscv64 => write!(f, "riscv64"),
+            Isa::LoongArch64 => write!(f, "loongarch64"),
             Isa::Pulley32 => write!(f, "pulley32"),
             Isa::Pulley64 => write!(f, "pulley64"),
         }
@@ -75,6 +80,7 @@ pub(crate) fn define(isas: &[Isa]) -> Vec<TargetIsa> {
             Isa::Arm64 => arm64::define(),
             Isa::S390x => s390x::define(),
             Isa::Riscv64 => riscv64::define(),
+            Isa::LoongArch64 => loongarch64::define(),
             Isa::Pulley32 | Isa::Pulley64 => pulley::define(),
         })
         .collect()
@@ -76,6 +76,10 @@ pub fn get_isle_compilations(
     let src_isa_aarch64 = codegen_crate_dir.join("src").join("isa").join("aarch64");
     let src_isa_s390x = codegen_crate_dir.join("src").join("isa").join("s390x");
     let src_isa_risc_v = codegen_crate_dir.join("src").join("isa").join("riscv64");
+    let src_isa_loongarch64 = codegen_crate_dir
+        .join("src")
+        .join("isa")
+        .join("loongarch64");
     #[cfg(feature = "pulley")]
     let src_isa_pulley_shared = codegen_crate_dir
         .join("src")
@@ -174,6 +178,18 @@ pub fn get_isle_compilations(
                 ],
                 untracked_inputs: vec![numerics_isle.clone(), clif_lower_isle.clone()],
             },
+            // The LoongArch instruction selector.
+            IsleCompilation {
+                name: "loongarch64".to_string(),
+                output: gen_dir.join("isle_loongarch64.rs"),
+                inputs: vec![
+                    prelude_isle.clone(),
+                    prelude_lower_isle.clone(),
+                    src_isa_loongarch64.join("inst.isle"),
+                    src_isa_loongarch64.join("lower.isle"),
+                ],
+                untracked_inputs: vec![numerics_isle.clone(), clif_lower_isle.clone()],
+            },
             // The Pulley instruction selector.
             #[cfg(feature = "pulley")]
             IsleCompilation {
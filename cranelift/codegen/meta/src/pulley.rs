@@ -26,6 +26,34 @@ macro_rules! define {
 const OPS: &[Inst<'_>] = pulley_interpreter::for_each_op!(define);
 const EXTENDED_OPS: &[Inst<'_>] = pulley_interpreter::for_each_extended_op!(define);
 
+/// A superinstruction along with the `lower` rules which select it, each of
+/// which is a priority, a pattern and then any other clauses of the rule.
+struct Superinstruction<'a> {
+    snake_name: &'a str,
+    fields: &'a [&'a str],
+    lower: &'a [(i32, &'a str, &'a [&'a str])],
+}
+
+macro_rules! define_superinstructions {
+    (
+        $(
+            $( #[$attr:meta] )*
+            $snake_name:ident = $name:ident { $( $field:ident : $field_ty:ident ),* }
+                => [ $($sequence:tt)* ]
+                lower [ $( $prio:literal => $pattern:literal $( , $clause:literal )* ; )* ];
+        )*
+    ) => {
+        &[$(Superinstruction {
+            snake_name: stringify!($snake_name),
+            fields: &[$( stringify!($field) ),*],
+            lower: &[$( ($prio, $pattern, &[$( $clause ),*]) ),*],
+        }),*]
+    };
+}
+
+const SUPERINSTRUCTIONS: &[Superinstruction<'_>] =
+    pulley_interpreter::for_each_superinstruction!(define_superinstructions);
+
 enum Operand<'a> {
     Normal {
         name: &'a str,
@@ -85,6 +113,27 @@ impl Inst<'_> {
             })
     }
 
+    /// Whether this instruction is a load (`true`) or a store (`false`)
+    /// and the number of bytes it accesses, as encoded in its name (e.g.
+    /// `xload16le_u32_o32` or `vload8x8_s_z`).
+    fn mem_access(&self) -> Option<(bool, u32)> {
+        let (is_load, rest) = if let Some((_, rest)) = self.snake_name.split_once("load") {
+            (true, rest)
+        } else {
+            (false, self.snake_name.split_once("store")?.1)
+        };
+        let digits = |s: &str| -> Option<(u32, usize)> {
+            let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            Some((s[..len].parse().ok()?, len))
+        };
+        let (bits, len) = digits(rest)?;
+        let lanes = match rest[len..].strip_prefix("x") {
+            Some(rest) => digits(rest)?.0,
+            None => 1,
+        };
+        Some((is_load, bits * lanes / 8))
+    }
+
     fn skip(&self) -> bool {
         match self.name {
             // Skip instructions related to control-flow as those require
@@ -304,6 +353,63 @@ pub fn generate_rust(filename: &str, out_dir: &Path) -> Result<(), Error> {
     rust.push_str("}\n");
     rust.push_str("}\n");
 
+    // Generate a description of the memory accessed by loads and stores, for
+    // proof-carrying code.
+    rust.push_str("pub fn mem_access(inst: &RawInst) -> Option<RawMemAccess> {\n");
+    rust.push_str("match inst {\n");
+    for inst @ Inst { name, .. } in OPS.iter().chain(EXTENDED_OPS) {
+        if inst.skip() {
+            continue;
+        }
+        let Some((is_load, bytes)) = inst.mem_access() else {
+            continue;
+        };
+        let Some((addr, addr_ty)) = inst.fields.iter().find(|(_, ty)| ty.starts_with("Addr"))
+        else {
+            continue;
+        };
+        let kind = addr_ty.strip_prefix("Addr").unwrap();
+        let (reg, reg_ty) = inst
+            .fields
+            .iter()
+            .find(|(name, _)| *name == if is_load { "dst" } else { "src" })
+            .unwrap();
+        let mut bindings = vec![];
+        // Plain `o32` accesses are never checked, so their address
+        // isn't needed.
+        let addr = if kind == "O32" {
+            "RawAddr::O32".to_string()
+        } else {
+            bindings.push(*addr);
+            format!("RawAddr::{kind}(*{addr})")
+        };
+        let reg = if !reg_ty.contains("XReg") {
+            "None".to_string()
+        } else {
+            bindings.push(*reg);
+            if is_load {
+                format!("Some(*{reg}.to_reg())")
+            } else {
+                format!("Some(**{reg})")
+            }
+        };
+        bindings.push("..");
+        let bindings = bindings.join(", ");
+        rust.push_str(&format!(
+            "
+        RawInst::{name} {{ {bindings} }} => Some(RawMemAccess {{
+            addr: {addr},
+            bytes: {bytes},
+            is_load: {is_load},
+            reg: {reg},
+        }}),
+        "
+        ));
+    }
+    rust.push_str("_ => None,\n");
+    rust.push_str("}\n");
+    rust.push_str("}\n");
+
     std::fs::write(out_dir.join(filename), rust)?;
     Ok(())
 }
@@ -420,6 +526,29 @@ pub fn generate_isle(filename: &str, out_dir: &Path) -> Result<(), Error> {
         isle.push_str(&rule);
     }
 
+    // Generate the `lower` rules for superinstructions, which pass all of the
+    // fields bound by the pattern other than destinations to the constructor.
+    for Superinstruction {
+        snake_name,
+        fields,
+        lower,
+    } in SUPERINSTRUCTIONS
+    {
+        let args = fields
+            .iter()
+            .filter(|name| !name.starts_with("dst"))
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        for (prio, pattern, clauses) in lower.iter() {
+            isle.push_str(&format!("(rule {prio} (lower {pattern})\n"));
+            for clause in clauses.iter() {
+                isle.push_str(&format!("  {clause}\n"));
+            }
+            isle.push_str(&format!("  (pulley_{snake_name} {args}))\n"));
+        }
+    }
+
     std::fs::write(out_dir.join(filename), isle)?;
     Ok(())
 }
@@ -38,12 +38,16 @@ pub(crate) fn define() -> SettingGroup {
 
             - `backtracking`: A backtracking allocator with range splitting; more expensive
                               but generates better code.
-
-            Note that the `single_pass` option is currently disabled because it does not
-            have adequate support for the kinds of allocations required by exception
-            handling (https://github.com/bytecodealliance/regalloc2/issues/217).
+            - `single_pass`: A single-pass algorithm that yields quick compilation but
+                             results in code with more register spills and moves.
+
+            Note that the `single_pass` allocator does not yet have adequate support for
+            the kinds of allocations required by exception handling
+            (https://github.com/bytecodealliance/regalloc2/issues/217), so functions
+            which contain exception-handling calls are always allocated with the
+            `backtracking` algorithm.
         "#,
-        vec!["backtracking"],
+        vec!["backtracking", "single_pass"],
     );
 
     settings.add_enum(
@@ -69,6 +73,34 @@ pub(crate) fn define() -> SettingGroup {
         true,
     );
 
+    settings.add_bool(
+        "enable_loop_optimizations",
+        "Hoist invariant loads out of loops, unroll small loops and strength-reduce induction variables.",
+        r#"
+            This enables loop-invariant code motion of loads that alias analysis proves aren't
+            written to in the loop, full unrolling of loops with a small constant trip count, and
+            strength reduction of multiplications by induction variables. Only effective when
+            `opt_level` is `speed` or `speed_and_size`.
+        "#,
+        false,
+    );
+
+    settings.add_bool(
+        "enable_slp_vectorization",
+        "Vectorize groups of scalar stores to adjacent addresses.",
+        r#"
+            This enables a superword-level parallelism pass, which replaces groups of stores
+            of scalars to adjacent addresses, and the isomorphic computations of the stored
+            values, with 128-bit vector operations. Only effective when `opt_level` is `speed`
+            or `speed_and_size`, on x86_64 and aarch64.
+
+            A vectorized group of memory accesses traps as a whole: when one of the scalar
+            accesses would have trapped, the others may or may not have happened before the
+            trap.
+        "#,
+        false,
+    );
+
     settings.add_bool(
         "enable_verifier",
         "Run the Cranelift IR verifier at strategic times during compilation.",
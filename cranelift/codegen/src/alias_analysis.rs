@@ -84,7 +84,7 @@ pub struct LastStores {
 }
 
 impl LastStores {
-    fn update(&mut self, func: &Function, inst: Inst) {
+    pub(crate) fn update(&mut self, func: &Function, inst: Inst) {
         let opcode = func.dfg.insts[inst].opcode();
         if has_memory_fence_semantics(opcode) {
             self.heap = inst.into();
@@ -108,7 +108,7 @@ impl LastStores {
         }
     }
 
-    fn get_last_store(&self, func: &Function, inst: Inst) -> PackedOption<Inst> {
+    pub(crate) fn get_last_store(&self, func: &Function, inst: Inst) -> PackedOption<Inst> {
         if let Some(memflags) = func.dfg.insts[inst].memflags() {
             match memflags.alias_region() {
                 None => self.other,
@@ -115,6 +115,24 @@ pub enum Reloc {
     /// <https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-elf.adoc#pc-relative-symbol-addresses>
     RiscvGotHi20,
 
+    /// LoongArch call: a `pcaddu18i` + `jirl` pair forming a 38-bit
+    /// PC-relative function call.
+    ///
+    /// This is the `R_LARCH_CALL36` relocation from the LoongArch ELF psABI document.
+    /// <https://loongson.github.io/LoongArch-Documentation/LoongArch-ELF-ABI-EN.html>
+    LoongArchCall36,
+
+    /// LoongArch GOT PC-relative high 20 bits, for `pcalau12i`.
+    ///
+    /// This is the `R_LARCH_GOT_PC_HI20` relocation from the LoongArch ELF psABI document.
+    LoongArchGotPcHi20,
+
+    /// LoongArch GOT PC-relative low 12 bits, for the `ld.d` following a
+    /// `pcalau12i`.
+    ///
+    /// This is the `R_LARCH_GOT_PC_LO12` relocation from the LoongArch ELF psABI document.
+    LoongArchGotPcLo12,
+
     /// s390x TLS GD64 - 64-bit offset of tls_index for GD symbol in GOT
     S390xTlsGd64,
     /// s390x TLS GDCall - marker to enable optimization of TLS calls
@@ -155,6 +173,9 @@ impl fmt::Display for Reloc {
             Self::Aarch64TlsDescCall => write!(f, "Aarch64TlsDescCall"),
             Self::Aarch64AdrGotPage21 => write!(f, "Aarch64AdrGotPage21"),
             Self::Aarch64Ld64GotLo12Nc => write!(f, "Aarch64AdrGotLo12Nc"),
+            Self::LoongArchCall36 => write!(f, "LoongArchCall36"),
+            Self::LoongArchGotPcHi20 => write!(f, "LoongArchGotPcHi20"),
+            Self::LoongArchGotPcLo12 => write!(f, "LoongArchGotPcLo12"),
             Self::S390xTlsGd64 => write!(f, "TlsGd64"),
             Self::S390xTlsGdCall => write!(f, "TlsGdCall"),
             Self::PulleyCallIndirectHost => write!(f, "PulleyCallIndirectHost"),
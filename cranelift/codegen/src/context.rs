@@ -14,19 +14,23 @@ use crate::dominator_tree::DominatorTree;
 use crate::dominator_tree::DominatorTreePreorder;
 use crate::egraph::EgraphPass;
 use crate::flowgraph::ControlFlowGraph;
+use crate::inline::{Inline, do_inlining};
 use crate::ir::Function;
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::loop_analysis::LoopAnalysis;
+use crate::loop_opts::do_loop_opts;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
 use crate::remove_constant_phis::do_remove_constant_phis;
 use crate::result::{CodegenResult, CompileResult};
 use crate::settings::{FlagsOrIsa, OptLevel};
+use crate::slp::do_slp_vectorization;
 use crate::trace;
 use crate::unreachable_code::eliminate_unreachable_code;
 use crate::verifier::{VerifierErrors, VerifierResult, verify_context};
 use crate::{CompileError, timing};
+use alloc::boxed::Box;
 #[cfg(feature = "souper-harvest")]
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -58,6 +62,10 @@ pub struct Context {
 
     /// Flag: do we want a disassembly with the CompiledCode?
     pub want_disasm: bool,
+
+    /// Provider of callee bodies for the inlining pass, if inlining is
+    /// enabled. See `set_inliner`.
+    inliner: Option<Box<dyn Inline + Send>>,
 }
 
 impl Context {
@@ -82,6 +90,7 @@ impl Context {
             loop_analysis: LoopAnalysis::new(),
             compiled_code: None,
             want_disasm: false,
+            inliner: None,
         }
     }
 
@@ -93,6 +102,7 @@ impl Context {
         self.loop_analysis.clear();
         self.compiled_code = None;
         self.want_disasm = false;
+        self.inliner = None;
     }
 
     /// Returns the compilation result for this function, available after any `compile` function
@@ -113,6 +123,15 @@ impl Context {
         self.want_disasm = val;
     }
 
+    /// Set the provider of callee bodies used to inline calls when
+    /// optimizing this function.
+    ///
+    /// The inliner is used by the next `optimize` (or `compile`) at any
+    /// optimization level other than `none`, and is cleared afterwards.
+    pub fn set_inliner(&mut self, inliner: impl Inline + Send + 'static) {
+        self.inliner = Some(Box::new(inliner));
+    }
+
     /// Compile the function, and emit machine code into a `Vec<u8>`.
     #[deprecated = "use Context::compile"]
     pub fn compile_and_emit(
@@ -173,6 +192,8 @@ impl Context {
             self.func.display()
         );
 
+        self.run_inliner(isa)?;
+
         if isa.flags().enable_nan_canonicalization() {
             self.canonicalize_nans(isa)?;
         }
@@ -186,8 +207,20 @@ impl Context {
 
         self.func.dfg.resolve_all_aliases();
 
+        if matches!(opt_level, OptLevel::Speed | OptLevel::SpeedAndSize)
+            && isa.flags().enable_loop_optimizations()
+        {
+            self.optimize_loops(isa)?;
+        }
+
         if opt_level != OptLevel::None {
             self.egraph_pass(isa, ctrl_plane)?;
+
+            // Run after the egraph pass, so that equal addresses are the same
+            // values.
+            if isa.flags().enable_slp_vectorization() {
+                self.slp_vectorize(isa)?;
+            }
         }
 
         Ok(())
@@ -276,6 +309,71 @@ impl Context {
         Ok(())
     }
 
+    /// Run the loop optimizations on the function: hoisting of invariant
+    /// loads, unrolling and strength reduction of induction variables.
+    pub fn optimize_loops<'a, FOI: Into<FlagsOrIsa<'a>>>(
+        &mut self,
+        fisa: FOI,
+    ) -> CodegenResult<()> {
+        let fisa = fisa.into();
+        if do_loop_opts(
+            &mut self.func,
+            &mut self.cfg,
+            &mut self.domtree,
+            &mut self.loop_analysis,
+            fisa.flags,
+        ) {
+            self.flowgraph();
+            // Fully unrolled loops pass constants to their former headers.
+            self.remove_constant_phis(fisa)?;
+            self.func.dfg.resolve_all_aliases();
+        }
+        Ok(())
+    }
+
+    /// Vectorize groups of scalar stores to adjacent addresses, and the
+    /// computations of the stored values, on targets with 128-bit vectors.
+    pub fn slp_vectorize(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        // The vectorized code doesn't carry proof-carrying code facts.
+        if isa.flags().enable_pcc()
+            || !matches!(
+                isa.triple().architecture,
+                Architecture::X86_64 | Architecture::Aarch64(_)
+            )
+        {
+            return Ok(());
+        }
+        if do_slp_vectorization(&mut self.func) {
+            self.verify_if(isa)?;
+        }
+        Ok(())
+    }
+
+    /// Inline calls in the function, using `inliner` to provide the bodies
+    /// of callees. Returns whether any call was inlined.
+    pub fn inline<'a, FOI: Into<FlagsOrIsa<'a>>>(
+        &mut self,
+        fisa: FOI,
+        inliner: impl Inline,
+    ) -> CodegenResult<bool> {
+        let inlined = do_inlining(&mut self.func, inliner)?;
+        if inlined {
+            self.verify_if(fisa)?;
+        }
+        Ok(inlined)
+    }
+
+    /// Run the inliner set with `set_inliner`, if any, unless optimizations
+    /// are disabled.
+    pub(crate) fn run_inliner(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        if let Some(inliner) = self.inliner.take() {
+            if isa.flags().opt_level() != OptLevel::None {
+                self.inline(isa, inliner)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Perform NaN canonicalizing rewrites on the function.
     pub fn canonicalize_nans(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
         // Currently only RiscV64 is the only arch that may not have vector support.
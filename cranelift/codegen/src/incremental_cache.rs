@@ -43,6 +43,15 @@ impl Context {
         cache_store: &mut dyn CacheKvStore,
         ctrl_plane: &mut ControlPlane,
     ) -> CompileResult<'_, (&CompiledCode, bool)> {
+        // Inlining changes the function, so it must happen before the cache
+        // key is computed.
+        if let Err(inner) = self.run_inliner(isa) {
+            return Err(CompileError {
+                inner,
+                func: &self.func,
+            });
+        }
+
         let cache_key_hash = {
             let _tt = timing::try_incremental_cache();
 
@@ -0,0 +1,781 @@
+//! Function inlining.
+//!
+//! Cranelift compiles one function at a time and knows nothing about the other
+//! functions in a module, so the bodies of callees are supplied by the embedder
+//! through the [`Inline`] trait. The inlining pass walks the direct `call`
+//! instructions in a caller, asks the embedder for each callee's body, and
+//! splices a copy of that body into the caller in place of the call when the
+//! [`InlineHeuristics`] allow it.
+//!
+//! Splicing a callee at a call site works as follows:
+//!
+//! * The block containing the call is split just after the call. The new
+//!   "return block" takes one parameter per call result, and the call's
+//!   results become aliases of those parameters.
+//!
+//! * Every entity the callee refers to (blocks, values, stack slots, global
+//!   values, signatures, function references, jump tables, constants, ...) is
+//!   copied into the caller, and the callee's instructions are copied with
+//!   their entities remapped. The callee's entry block parameters are replaced
+//!   with the call's arguments.
+//!
+//! * The call itself is replaced with a jump to the copy of the callee's entry
+//!   block, and each `return` in the callee becomes a jump to the return
+//!   block.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::ir::instructions::InstructionMapper;
+use crate::ir::{
+    self, ArgumentPurpose, Block, BlockArg, BlockCall, ExternalName, Function, GlobalValueData,
+    Inst, InstBuilder, InstructionData, Opcode, UserStackMapEntry, Value, ValueList,
+};
+use crate::result::CodegenResult;
+use crate::timing;
+use crate::trace;
+use crate::traversals::Dfs;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use cranelift_entity::packed_option::PackedOption;
+use cranelift_entity::{EntityRef, SecondaryMap};
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+/// A trait for providing the bodies of callees to the inlining pass.
+///
+/// Implementations of this trait are consulted once for each direct call in
+/// the function being compiled, and decide whether the call should be inlined
+/// and, if so, what body the callee has.
+pub trait Inline {
+    /// Decide whether to inline the call `call_inst` to `callee` in `caller`.
+    ///
+    /// `call_args` are the arguments of the call, which may be used to
+    /// specialize the decision (for example, when some arguments are
+    /// constants).
+    ///
+    /// Returning [`InlineCommand::Inline`] is a request rather than an order:
+    /// the call is still kept when the callee is too large for the
+    /// [`InlineHeuristics`] returned by [`Inline::heuristics`], or when the
+    /// callee uses features that cannot be inlined, such as exception-handling
+    /// calls, tail calls, or direct access to its own stack frame.
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> InlineCommand<'_>;
+
+    /// The heuristics limiting how much inlining is done.
+    fn heuristics(&self) -> InlineHeuristics {
+        InlineHeuristics::default()
+    }
+}
+
+impl<T> Inline for &mut T
+where
+    T: Inline + ?Sized,
+{
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        (**self).inline(caller, call_inst, callee, call_args)
+    }
+
+    fn heuristics(&self) -> InlineHeuristics {
+        (**self).heuristics()
+    }
+}
+
+impl<T> Inline for alloc::boxed::Box<T>
+where
+    T: Inline + ?Sized,
+{
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        (**self).inline(caller, call_inst, callee, call_args)
+    }
+
+    fn heuristics(&self) -> InlineHeuristics {
+        (**self).heuristics()
+    }
+}
+
+/// The result of an [`Inline::inline`] query.
+pub enum InlineCommand<'a> {
+    /// Keep the call as it is.
+    KeepCall,
+
+    /// Inline the given body of the callee in place of the call.
+    Inline {
+        /// The body of the callee.
+        callee: Cow<'a, ir::Function>,
+
+        /// Whether the calls within the inlined body should themselves be
+        /// considered for inlining.
+        visit_callee: bool,
+    },
+}
+
+/// Heuristics deciding whether a call is inlined.
+///
+/// Sizes are measured in CLIF instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InlineHeuristics {
+    /// The largest callee that is inlined at any call site.
+    pub max_callee_size: usize,
+
+    /// The largest callee that is inlined when the caller contains only one
+    /// call to it. Inlining such callees does not duplicate any code within
+    /// the caller, so a larger limit is used.
+    pub max_single_call_callee_size: usize,
+
+    /// Inlining stops once the caller would grow beyond this size.
+    pub max_caller_size: usize,
+
+    /// The maximum nesting of inlined calls within inlined calls.
+    pub max_depth: usize,
+}
+
+impl Default for InlineHeuristics {
+    fn default() -> Self {
+        Self {
+            max_callee_size: 32,
+            max_single_call_callee_size: 256,
+            max_caller_size: 10_000,
+            max_depth: 4,
+        }
+    }
+}
+
+impl InlineHeuristics {
+    /// Should a callee of `callee_size` instructions be inlined into a caller
+    /// of `caller_size` instructions, which contains `call_sites` calls to it,
+    /// at nesting `depth`?
+    pub fn should_inline(
+        &self,
+        depth: usize,
+        caller_size: usize,
+        callee_size: usize,
+        call_sites: usize,
+    ) -> bool {
+        depth < self.max_depth
+            && caller_size.saturating_add(callee_size) <= self.max_caller_size
+            && (callee_size <= self.max_callee_size
+                || (call_sites <= 1 && callee_size <= self.max_single_call_callee_size))
+    }
+}
+
+/// Perform inlining on `func`, returning whether any call was inlined.
+pub(crate) fn do_inlining(func: &mut Function, mut inliner: impl Inline) -> CodegenResult<bool> {
+    let _tt = timing::inline();
+
+    let heuristics = inliner.heuristics();
+
+    // Count the direct calls to each callee, keyed by name because the same
+    // callee may be imported more than once.
+    let mut call_sites: FxHashMap<ExternalName, usize> = FxHashMap::default();
+    let mut worklist = Vec::new();
+    let mut size = 0;
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            size += 1;
+            if let InstructionData::Call {
+                opcode: Opcode::Call,
+                func_ref,
+                ..
+            } = func.dfg.insts[inst]
+            {
+                let name = func.dfg.ext_funcs[func_ref].name.clone();
+                *call_sites.entry(name).or_default() += 1;
+                worklist.push((inst, 0));
+            }
+        }
+    }
+
+    // Visit calls in layout order.
+    worklist.reverse();
+
+    let mut inlined_any = false;
+    while let Some((call_inst, depth)) = worklist.pop() {
+        let InstructionData::Call {
+            opcode: Opcode::Call,
+            func_ref,
+            args,
+        } = func.dfg.insts[call_inst]
+        else {
+            continue;
+        };
+        let call_args: SmallVec<[Value; 8]> = args
+            .as_slice(&func.dfg.value_lists)
+            .iter()
+            .map(|arg| func.dfg.resolve_aliases(*arg))
+            .collect();
+        let num_call_sites = call_sites
+            .get(&func.dfg.ext_funcs[func_ref].name)
+            .copied()
+            .unwrap_or(1);
+
+        let (callee, visit_callee) = match inliner.inline(func, call_inst, func_ref, &call_args) {
+            InlineCommand::KeepCall => continue,
+            InlineCommand::Inline {
+                callee,
+                visit_callee,
+            } => (callee, visit_callee),
+        };
+
+        let callee_size = function_size(&callee);
+        if !heuristics.should_inline(depth, size, callee_size, num_call_sites) {
+            trace!(
+                "not inlining {} into {}: callee has {callee_size} instructions",
+                callee.name, func.name
+            );
+            continue;
+        }
+        if let Err(reason) = check_inlinable(func, call_inst, &call_args, &callee) {
+            trace!("not inlining {} into {}: {reason}", callee.name, func.name);
+            continue;
+        }
+
+        trace!("inlining {} into {} at {call_inst}", callee.name, func.name);
+        let new_calls = inline_one(func, call_inst, &call_args, &callee);
+        inlined_any = true;
+        size += callee_size;
+
+        if visit_callee {
+            worklist.extend(new_calls.into_iter().rev().map(|inst| (inst, depth + 1)));
+        }
+    }
+
+    Ok(inlined_any)
+}
+
+/// The number of instructions in `func`'s layout.
+fn function_size(func: &Function) -> usize {
+    func.layout
+        .blocks()
+        .map(|block| func.layout.block_insts(block).count())
+        .sum()
+}
+
+/// Check that `callee` can be inlined at the call `call_inst` in `caller`,
+/// returning the reason it cannot otherwise.
+fn check_inlinable(
+    caller: &Function,
+    call_inst: Inst,
+    call_args: &[Value],
+    callee: &Function,
+) -> Result<(), &'static str> {
+    let entry = callee.layout.entry_block().ok_or("callee has no body")?;
+
+    let params = callee.dfg.block_params(entry);
+    if params.len() != call_args.len()
+        || params
+            .iter()
+            .zip(call_args)
+            .any(|(p, a)| callee.dfg.value_type(*p) != caller.dfg.value_type(*a))
+    {
+        return Err("callee parameters do not match the call's arguments");
+    }
+    let results = caller.dfg.inst_results(call_inst);
+    if results.len() != callee.signature.returns.len()
+        || results
+            .iter()
+            .zip(&callee.signature.returns)
+            .any(|(r, abi)| caller.dfg.value_type(*r) != abi.value_type)
+    {
+        return Err("callee returns do not match the call's results");
+    }
+
+    // Facts refer to memory types and global values of the function they
+    // were stated in; rather than remapping them, leave such callees alone.
+    if !callee.memory_types.is_empty()
+        || callee.global_value_facts.values().any(Option::is_some)
+        || callee.dfg.facts.values().any(Option::is_some)
+    {
+        return Err("callee has proof-carrying code facts");
+    }
+
+    // A `vmctx` global value in the callee refers to the callee's own
+    // `vmctx` parameter, which is only the same as the caller's when the
+    // caller passes its own `vmctx` along.
+    if callee
+        .global_values
+        .values()
+        .any(|gv| matches!(gv, GlobalValueData::VMContext))
+    {
+        let callee_vmctx = callee
+            .signature
+            .special_param_index(ArgumentPurpose::VMContext)
+            .map(|i| call_args[i]);
+        let caller_vmctx = caller
+            .special_param(ArgumentPurpose::VMContext)
+            .map(|v| caller.dfg.resolve_aliases(v));
+        if callee_vmctx.is_none() || callee_vmctx != caller_vmctx {
+            return Err("callee's vmctx differs from the caller's");
+        }
+    }
+
+    for block in callee.layout.blocks() {
+        for inst in callee.layout.block_insts(block) {
+            match callee.dfg.insts[inst].opcode() {
+                // Exceptional control flow would need to be rewired to the
+                // caller's handlers.
+                Opcode::TryCall | Opcode::TryCallIndirect => {
+                    return Err("callee contains exception-handling calls");
+                }
+                // A tail call from the callee is not a tail call from the
+                // caller.
+                Opcode::ReturnCall | Opcode::ReturnCallIndirect => {
+                    return Err("callee contains tail calls");
+                }
+                // These observe the callee's own frame.
+                Opcode::GetFramePointer | Opcode::GetStackPointer | Opcode::GetReturnAddress => {
+                    return Err("callee inspects its own frame");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Splice a copy of `callee` into `func` in place of `call_inst`, returning the
+/// copied call instructions.
+fn inline_one(
+    func: &mut Function,
+    call_inst: Inst,
+    call_args: &[Value],
+    callee: &Function,
+) -> Vec<Inst> {
+    let call_block = func.layout.inst_block(call_inst).unwrap();
+    let call_srcloc = func.srcloc(call_inst);
+    let call_stack_map: SmallVec<[UserStackMapEntry; 4]> = func
+        .dfg
+        .user_stack_map_entries(call_inst)
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default();
+
+    // Split the caller's block after the call, and turn the call's results
+    // into parameters of the new return block.
+    let return_block = func.dfg.make_block();
+    let next_inst = func
+        .layout
+        .next_inst(call_inst)
+        .expect("calls are not terminators");
+    func.layout.split_block(return_block, next_inst);
+    if func.layout.is_cold(call_block) {
+        func.layout.set_cold(return_block);
+    }
+    let results: SmallVec<[Value; 4]> = func.dfg.inst_results(call_inst).iter().copied().collect();
+    func.dfg.detach_inst_results(call_inst);
+    for result in results {
+        let ty = func.dfg.value_type(result);
+        let param = func.dfg.append_block_param(return_block, ty);
+        func.dfg.change_to_alias(result, param);
+    }
+
+    let mut mapper = EntityMapper::new(func, callee);
+
+    // Create the callee's reachable blocks in the caller, in the callee's
+    // layout order, right before the return block. The entry block's
+    // parameters are the call's arguments.
+    let entry = callee.layout.entry_block().unwrap();
+    let mut dfs = Dfs::new();
+    let mut blocks: Vec<Block> = dfs.post_order_iter(callee).collect();
+    blocks.reverse();
+    for &block in &blocks {
+        let new_block = mapper.caller.dfg.make_block();
+        mapper.blocks[block] = new_block.into();
+        if block == entry {
+            for (param, arg) in callee.dfg.block_params(block).iter().zip(call_args) {
+                mapper.values[*param] = (*arg).into();
+            }
+        } else {
+            for param in callee.dfg.block_params(block) {
+                let ty = callee.dfg.value_type(*param);
+                let new_param = mapper.caller.dfg.append_block_param(new_block, ty);
+                mapper.values[*param] = new_param.into();
+            }
+        }
+    }
+    let mut after = call_block;
+    for block in callee.layout.blocks() {
+        if let Some(new_block) = mapper.blocks[block].expand() {
+            mapper.caller.layout.insert_block_after(new_block, after);
+            if callee.layout.is_cold(block) {
+                mapper.caller.layout.set_cold(new_block);
+            }
+            after = new_block;
+        }
+    }
+
+    // Replace the call with a jump into the callee's body.
+    let new_entry = mapper.blocks[entry].unwrap();
+    mapper.caller.dfg.replace(call_inst).jump(new_entry, &[]);
+
+    // Copy the callee's instructions in reverse post-order, so that every
+    // value is defined before it is used.
+    let mut new_calls = Vec::new();
+    for &block in &blocks {
+        let new_block = mapper.blocks[block].unwrap();
+        for inst in callee.layout.block_insts(block) {
+            let opcode = callee.dfg.insts[inst].opcode();
+            let new_inst = if opcode == Opcode::Return {
+                let args: SmallVec<[BlockArg; 4]> = callee
+                    .dfg
+                    .inst_args(inst)
+                    .iter()
+                    .map(|arg| BlockArg::Value(mapper.map_value(*arg)))
+                    .collect();
+                let mut pos = FuncCursor::new(mapper.caller).at_bottom(new_block);
+                pos.ins().jump(return_block, &args)
+            } else {
+                let data = callee.dfg.insts[inst].map(&mut mapper);
+                let new_inst = mapper.caller.dfg.make_inst(data);
+                let ctrl_typevar = callee.dfg.ctrl_typevar(inst);
+                mapper.caller.dfg.make_inst_results(new_inst, ctrl_typevar);
+                for (result, new_result) in callee
+                    .dfg
+                    .inst_results(inst)
+                    .iter()
+                    .zip(mapper.caller.dfg.inst_results(new_inst))
+                {
+                    mapper.values[*result] = (*new_result).into();
+                }
+                mapper.caller.layout.append_inst(new_inst, new_block);
+                new_inst
+            };
+
+            let srcloc = match callee.srcloc(inst) {
+                srcloc if srcloc.is_default() => call_srcloc,
+                srcloc => srcloc,
+            };
+            if !srcloc.is_default() {
+                mapper.caller.set_srcloc(new_inst, srcloc);
+            }
+
+            if opcode.is_call() {
+                // Values the caller kept live across the inlined call must
+                // stay visible to the GC at calls within the callee too.
+                if let Some(entries) = callee.dfg.user_stack_map_entries(inst) {
+                    for entry in entries {
+                        let entry = UserStackMapEntry {
+                            slot: mapper.map_stack_slot(entry.slot),
+                            ..entry.clone()
+                        };
+                        mapper
+                            .caller
+                            .dfg
+                            .append_user_stack_map_entry(new_inst, entry);
+                    }
+                }
+                for entry in &call_stack_map {
+                    mapper
+                        .caller
+                        .dfg
+                        .append_user_stack_map_entry(new_inst, entry.clone());
+                }
+                new_calls.push(new_inst);
+            }
+        }
+    }
+
+    new_calls
+}
+
+/// Maps the entities of a callee to the entities of the caller it is being
+/// inlined into, creating the latter on first use.
+struct EntityMapper<'a> {
+    caller: &'a mut Function,
+    callee: &'a Function,
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    values: SecondaryMap<Value, PackedOption<Value>>,
+    stack_slots: SecondaryMap<ir::StackSlot, PackedOption<ir::StackSlot>>,
+    dynamic_stack_slots: SecondaryMap<ir::DynamicStackSlot, PackedOption<ir::DynamicStackSlot>>,
+    dynamic_types: SecondaryMap<ir::DynamicType, PackedOption<ir::DynamicType>>,
+    global_values: SecondaryMap<ir::GlobalValue, PackedOption<ir::GlobalValue>>,
+    sig_refs: SecondaryMap<ir::SigRef, PackedOption<ir::SigRef>>,
+    func_refs: SecondaryMap<ir::FuncRef, PackedOption<ir::FuncRef>>,
+    jump_tables: SecondaryMap<ir::JumpTable, PackedOption<ir::JumpTable>>,
+    exception_tables: SecondaryMap<ir::ExceptionTable, PackedOption<ir::ExceptionTable>>,
+    constants: SecondaryMap<ir::Constant, PackedOption<ir::Constant>>,
+    immediates: SecondaryMap<ir::Immediate, PackedOption<ir::Immediate>>,
+}
+
+impl<'a> EntityMapper<'a> {
+    fn new(caller: &'a mut Function, callee: &'a Function) -> Self {
+        Self {
+            caller,
+            callee,
+            blocks: SecondaryMap::new(),
+            values: SecondaryMap::new(),
+            stack_slots: SecondaryMap::new(),
+            dynamic_stack_slots: SecondaryMap::new(),
+            dynamic_types: SecondaryMap::new(),
+            global_values: SecondaryMap::new(),
+            sig_refs: SecondaryMap::new(),
+            func_refs: SecondaryMap::new(),
+            jump_tables: SecondaryMap::new(),
+            exception_tables: SecondaryMap::new(),
+            constants: SecondaryMap::new(),
+            immediates: SecondaryMap::new(),
+        }
+    }
+
+    /// Get the mapping of `entity` in `map`, creating it with `make` if
+    /// there is none yet.
+    fn get_or_make<E: EntityRef>(
+        &mut self,
+        map: fn(&mut Self) -> &mut SecondaryMap<E, PackedOption<E>>,
+        entity: E,
+        make: impl FnOnce(&mut Self) -> E,
+    ) -> E
+    where
+        E: cranelift_entity::packed_option::ReservedValue,
+    {
+        if let Some(new) = map(self)[entity].expand() {
+            return new;
+        }
+        let new = make(self);
+        map(self)[entity] = new.into();
+        new
+    }
+
+    fn map_external_name(&mut self, name: &ExternalName) -> ExternalName {
+        match name {
+            ExternalName::User(name_ref) => {
+                let name = self.callee.params.user_named_funcs()[*name_ref].clone();
+                ExternalName::User(self.caller.params.ensure_user_func_name(name))
+            }
+            name => name.clone(),
+        }
+    }
+
+    fn map_dynamic_type(&mut self, dynamic_type: ir::DynamicType) -> ir::DynamicType {
+        self.get_or_make(
+            |m| &mut m.dynamic_types,
+            dynamic_type,
+            |m| {
+                let mut data = m.callee.dfg.dynamic_types[dynamic_type].clone();
+                data.dynamic_scale = m.map_global_value(data.dynamic_scale);
+                m.caller.dfg.make_dynamic_ty(data)
+            },
+        )
+    }
+}
+
+impl InstructionMapper for EntityMapper<'_> {
+    fn map_value(&mut self, value: Value) -> Value {
+        let value = self.callee.dfg.resolve_aliases(value);
+        self.values[value]
+            .expand()
+            .expect("values are defined before they are used")
+    }
+
+    fn map_value_list(&mut self, value_list: ValueList) -> ValueList {
+        let mut new_list = ValueList::new();
+        for value in value_list.as_slice(&self.callee.dfg.value_lists) {
+            let value = self.map_value(*value);
+            new_list.push(value, &mut self.caller.dfg.value_lists);
+        }
+        new_list
+    }
+
+    fn map_global_value(&mut self, global_value: ir::GlobalValue) -> ir::GlobalValue {
+        self.get_or_make(
+            |m| &mut m.global_values,
+            global_value,
+            |m| {
+                let data = match m.callee.global_values[global_value].clone() {
+                    GlobalValueData::VMContext => {
+                        // `check_inlinable` made sure the callee's `vmctx` is
+                        // the caller's.
+                        if let Some(gv) = m
+                            .caller
+                            .global_values
+                            .iter()
+                            .find(|(_, data)| matches!(data, GlobalValueData::VMContext))
+                            .map(|(gv, _)| gv)
+                        {
+                            return gv;
+                        }
+                        GlobalValueData::VMContext
+                    }
+                    GlobalValueData::Load {
+                        base,
+                        offset,
+                        global_type,
+                        flags,
+                    } => GlobalValueData::Load {
+                        base: m.map_global_value(base),
+                        offset,
+                        global_type,
+                        flags,
+                    },
+                    GlobalValueData::IAddImm {
+                        base,
+                        offset,
+                        global_type,
+                    } => GlobalValueData::IAddImm {
+                        base: m.map_global_value(base),
+                        offset,
+                        global_type,
+                    },
+                    GlobalValueData::Symbol {
+                        name,
+                        offset,
+                        colocated,
+                        tls,
+                    } => GlobalValueData::Symbol {
+                        name: m.map_external_name(&name),
+                        offset,
+                        colocated,
+                        tls,
+                    },
+                    data @ GlobalValueData::DynScaleTargetConst { .. } => data,
+                };
+                m.caller.create_global_value(data)
+            },
+        )
+    }
+
+    fn map_jump_table(&mut self, jump_table: ir::JumpTable) -> ir::JumpTable {
+        self.get_or_make(
+            |m| &mut m.jump_tables,
+            jump_table,
+            |m| {
+                let data = &m.callee.dfg.jump_tables[jump_table];
+                let default = m.map_block_call(data.default_block());
+                let table: SmallVec<[BlockCall; 16]> = data
+                    .as_slice()
+                    .iter()
+                    .map(|call| m.map_block_call(*call))
+                    .collect();
+                m.caller
+                    .create_jump_table(ir::JumpTableData::new(default, &table))
+            },
+        )
+    }
+
+    fn map_exception_table(&mut self, exception_table: ir::ExceptionTable) -> ir::ExceptionTable {
+        self.get_or_make(
+            |m| &mut m.exception_tables,
+            exception_table,
+            |m| {
+                let data = &m.callee.dfg.exception_tables[exception_table];
+                let sig = m.map_sig_ref(data.signature());
+                let normal_return = m.map_block_call(*data.normal_return());
+                let catches: SmallVec<[_; 4]> = data
+                    .catches()
+                    .map(|(tag, call)| (tag, m.map_block_call(*call)))
+                    .collect();
+                m.caller
+                    .dfg
+                    .exception_tables
+                    .push(ir::ExceptionTableData::new(sig, normal_return, catches))
+            },
+        )
+    }
+
+    fn map_block_call(&mut self, block_call: BlockCall) -> BlockCall {
+        let block = block_call.block(&self.callee.dfg.value_lists);
+        let block = self.blocks[block]
+            .expand()
+            .expect("branch targets are reachable");
+        let args: SmallVec<[BlockArg; 8]> = block_call
+            .args(&self.callee.dfg.value_lists)
+            .map(|arg| match arg {
+                BlockArg::Value(value) => BlockArg::Value(self.map_value(value)),
+                arg => arg,
+            })
+            .collect();
+        BlockCall::new(block, args.into_iter(), &mut self.caller.dfg.value_lists)
+    }
+
+    fn map_func_ref(&mut self, func_ref: ir::FuncRef) -> ir::FuncRef {
+        self.get_or_make(
+            |m| &mut m.func_refs,
+            func_ref,
+            |m| {
+                let data = &m.callee.dfg.ext_funcs[func_ref];
+                let data = ir::ExtFuncData {
+                    name: m.map_external_name(&data.name),
+                    signature: m.map_sig_ref(data.signature),
+                    colocated: data.colocated,
+                };
+                m.caller.import_function(data)
+            },
+        )
+    }
+
+    fn map_sig_ref(&mut self, sig_ref: ir::SigRef) -> ir::SigRef {
+        self.get_or_make(
+            |m| &mut m.sig_refs,
+            sig_ref,
+            |m| {
+                let signature = m.callee.dfg.signatures[sig_ref].clone();
+                m.caller.import_signature(signature)
+            },
+        )
+    }
+
+    fn map_stack_slot(&mut self, stack_slot: ir::StackSlot) -> ir::StackSlot {
+        self.get_or_make(
+            |m| &mut m.stack_slots,
+            stack_slot,
+            |m| {
+                let data = m.callee.sized_stack_slots[stack_slot].clone();
+                m.caller.create_sized_stack_slot(data)
+            },
+        )
+    }
+
+    fn map_dynamic_stack_slot(
+        &mut self,
+        dynamic_stack_slot: ir::DynamicStackSlot,
+    ) -> ir::DynamicStackSlot {
+        self.get_or_make(
+            |m| &mut m.dynamic_stack_slots,
+            dynamic_stack_slot,
+            |m| {
+                let data = &m.callee.dynamic_stack_slots[dynamic_stack_slot];
+                let kind = data.kind;
+                let dyn_ty = m.map_dynamic_type(data.dyn_ty);
+                m.caller
+                    .create_dynamic_stack_slot(ir::DynamicStackSlotData::new(kind, dyn_ty))
+            },
+        )
+    }
+
+    fn map_constant(&mut self, constant: ir::Constant) -> ir::Constant {
+        self.get_or_make(
+            |m| &mut m.constants,
+            constant,
+            |m| {
+                let data = m.callee.dfg.constants.get(constant).clone();
+                m.caller.dfg.constants.insert(data)
+            },
+        )
+    }
+
+    fn map_immediate(&mut self, immediate: ir::Immediate) -> ir::Immediate {
+        self.get_or_make(
+            |m| &mut m.immediates,
+            immediate,
+            |m| {
+                let data = m.callee.dfg.immediates[immediate].clone();
+                m.caller.dfg.immediates.push(data)
+            },
+        )
+    }
+}
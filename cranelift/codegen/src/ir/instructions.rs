@@ -18,8 +18,8 @@ use serde_derive::{Deserialize, Serialize};
 use crate::bitset::ScalarBitSet;
 use crate::entity;
 use crate::ir::{
-    self, Block, ExceptionTable, ExceptionTables, FuncRef, MemFlags, SigRef, StackSlot, Type,
-    Value,
+    self, Block, DynamicStackSlot, ExceptionTable, ExceptionTables, FuncRef, MemFlags, SigRef,
+    StackSlot, Type, Value,
     condcodes::{FloatCC, IntCC},
     trapcode::TrapCode,
     types,
@@ -565,6 +565,19 @@ impl InstructionData {
         }
     }
 
+    /// If this instruction references a dynamic stack slot, return it
+    pub fn dynamic_stack_slot(&self) -> Option<DynamicStackSlot> {
+        match self {
+            &InstructionData::DynamicStackStore {
+                dynamic_stack_slot, ..
+            }
+            | &InstructionData::DynamicStackLoad {
+                dynamic_stack_slot, ..
+            } => Some(dynamic_stack_slot),
+            _ => None,
+        }
+    }
+
     /// Return information about a call instruction.
     ///
     /// Any instruction that can call another function reveals its call signature here.
@@ -659,6 +672,100 @@ impl InstructionData {
     }
 }
 
+/// A trait for mapping the entities within an instruction to new
+/// entities, e.g. when copying an instruction from one function into
+/// another. See `InstructionData::map`.
+pub trait InstructionMapper {
+    /// Map a value to a new value.
+    fn map_value(&mut self, value: Value) -> Value;
+
+    /// Map a value list to a new value list.
+    fn map_value_list(&mut self, value_list: ValueList) -> ValueList;
+
+    /// Map a global value to a new global value.
+    fn map_global_value(&mut self, global_value: ir::GlobalValue) -> ir::GlobalValue;
+
+    /// Map a jump table to a new jump table.
+    fn map_jump_table(&mut self, jump_table: ir::JumpTable) -> ir::JumpTable;
+
+    /// Map an exception table to a new exception table.
+    fn map_exception_table(&mut self, exception_table: ExceptionTable) -> ExceptionTable;
+
+    /// Map a block call to a new block call.
+    fn map_block_call(&mut self, block_call: BlockCall) -> BlockCall;
+
+    /// Map a function reference to a new function reference.
+    fn map_func_ref(&mut self, func_ref: FuncRef) -> FuncRef;
+
+    /// Map a signature reference to a new signature reference.
+    fn map_sig_ref(&mut self, sig_ref: SigRef) -> SigRef;
+
+    /// Map a stack slot to a new stack slot.
+    fn map_stack_slot(&mut self, stack_slot: StackSlot) -> StackSlot;
+
+    /// Map a dynamic stack slot to a new dynamic stack slot.
+    fn map_dynamic_stack_slot(&mut self, dynamic_stack_slot: DynamicStackSlot) -> DynamicStackSlot;
+
+    /// Map a constant to a new constant.
+    fn map_constant(&mut self, constant: ir::Constant) -> ir::Constant;
+
+    /// Map an immediate to a new immediate.
+    fn map_immediate(&mut self, immediate: ir::Immediate) -> ir::Immediate;
+}
+
+impl<T> InstructionMapper for &mut T
+where
+    T: InstructionMapper + ?Sized,
+{
+    fn map_value(&mut self, value: Value) -> Value {
+        (**self).map_value(value)
+    }
+
+    fn map_value_list(&mut self, value_list: ValueList) -> ValueList {
+        (**self).map_value_list(value_list)
+    }
+
+    fn map_global_value(&mut self, global_value: ir::GlobalValue) -> ir::GlobalValue {
+        (**self).map_global_value(global_value)
+    }
+
+    fn map_jump_table(&mut self, jump_table: ir::JumpTable) -> ir::JumpTable {
+        (**self).map_jump_table(jump_table)
+    }
+
+    fn map_exception_table(&mut self, exception_table: ExceptionTable) -> ExceptionTable {
+        (**self).map_exception_table(exception_table)
+    }
+
+    fn map_block_call(&mut self, block_call: BlockCall) -> BlockCall {
+        (**self).map_block_call(block_call)
+    }
+
+    fn map_func_ref(&mut self, func_ref: FuncRef) -> FuncRef {
+        (**self).map_func_ref(func_ref)
+    }
+
+    fn map_sig_ref(&mut self, sig_ref: SigRef) -> SigRef {
+        (**self).map_sig_ref(sig_ref)
+    }
+
+    fn map_stack_slot(&mut self, stack_slot: StackSlot) -> StackSlot {
+        (**self).map_stack_slot(stack_slot)
+    }
+
+    fn map_dynamic_stack_slot(&mut self, dynamic_stack_slot: DynamicStackSlot) -> DynamicStackSlot {
+        (**self).map_dynamic_stack_slot(dynamic_stack_slot)
+    }
+
+    fn map_constant(&mut self, constant: ir::Constant) -> ir::Constant {
+        (**self).map_constant(constant)
+    }
+
+    fn map_immediate(&mut self, immediate: ir::Immediate) -> ir::Immediate {
+        (**self).map_immediate(immediate)
+    }
+}
+
 /// Information about call instructions.
 pub enum CallInfo<'a> {
     /// This is not a call instruction.
@@ -674,6 +674,31 @@ impl Fact {
                 }
             }
 
+            // We can't express both a static and a symbolic range in
+            // one fact, so keep the symbolic one: it is what bounds
+            // checks reason about. (Either is sound to keep, since
+            // the kept fact is still checked against the definition.)
+            (
+                Fact::Range {
+                    bit_width: bw_static,
+                    ..
+                },
+                dynamic @ Fact::DynamicRange {
+                    bit_width: bw_dynamic,
+                    ..
+                },
+            )
+            | (
+                dynamic @ Fact::DynamicRange {
+                    bit_width: bw_dynamic,
+                    ..
+                },
+                Fact::Range {
+                    bit_width: bw_static,
+                    ..
+                },
+            ) if bw_static == bw_dynamic => dynamic.clone(),
+
             (
                 Fact::Mem {
                     ty: ty_lhs,
@@ -721,6 +746,16 @@ impl Fact {
                 }
             }
 
+            // As with ranges above, prefer the symbolic pointer fact.
+            (
+                Fact::Mem { ty: ty_static, .. },
+                dynamic @ Fact::DynamicMem { ty: ty_dynamic, .. },
+            )
+            | (
+                dynamic @ Fact::DynamicMem { ty: ty_dynamic, .. },
+                Fact::Mem { ty: ty_static, .. },
+            ) if ty_static == ty_dynamic => dynamic.clone(),
+
             _ => Fact::Conflict,
         }
     }
@@ -817,6 +852,25 @@ impl<'a> FactContext<'a> {
                 bw_lhs == bw_rhs && Expr::le(max_lhs, max_rhs) && Expr::le(min_rhs, min_lhs)
             }
 
+            // A static range subsumes a dynamic range whose bounds
+            // are constant expressions containing it.
+            (
+                Fact::Range {
+                    bit_width: bw_lhs,
+                    min: min_lhs,
+                    max: max_lhs,
+                },
+                Fact::DynamicRange {
+                    bit_width: bw_rhs,
+                    min: min_rhs,
+                    max: max_rhs,
+                },
+            ) if min_rhs.base == BaseExpr::None && max_rhs.base == BaseExpr::None => {
+                bw_lhs == bw_rhs
+                    && i64::try_from(*max_lhs).is_ok_and(|max| max <= max_rhs.offset)
+                    && i64::try_from(*min_lhs).is_ok_and(|min| min >= min_rhs.offset)
+            }
+
             (
                 Fact::Mem {
                     ty: ty_lhs,
@@ -857,6 +911,52 @@ impl<'a> FactContext<'a> {
                     && (*nullable_lhs || !*nullable_rhs)
             }
 
+            // A symbolic range or pointer whose bounds are statically
+            // known subsumes a static one containing those bounds.
+            (
+                Fact::DynamicRange {
+                    bit_width: bw_lhs,
+                    min: min_lhs,
+                    max: max_lhs,
+                },
+                Fact::Range {
+                    bit_width: bw_rhs,
+                    min: min_rhs,
+                    max: max_rhs,
+                },
+            ) => {
+                bw_lhs >= bw_rhs
+                    && self
+                        .expr_bounds(min_lhs)
+                        .is_some_and(|(min, _)| min >= *min_rhs)
+                    && self
+                        .expr_bounds(max_lhs)
+                        .is_some_and(|(_, max)| max <= *max_rhs)
+            }
+            (
+                Fact::DynamicMem {
+                    ty: ty_lhs,
+                    min: min_lhs,
+                    max: max_lhs,
+                    nullable: nullable_lhs,
+                },
+                Fact::Mem {
+                    ty: ty_rhs,
+                    min_offset: min_offset_rhs,
+                    max_offset: max_offset_rhs,
+                    nullable: nullable_rhs,
+                },
+            ) => {
+                ty_lhs == ty_rhs
+                    && self
+                        .expr_bounds(min_lhs)
+                        .is_some_and(|(min, _)| min >= *min_offset_rhs)
+                    && self
+                        .expr_bounds(max_lhs)
+                        .is_some_and(|(_, max)| max <= *max_offset_rhs)
+                    && (*nullable_lhs || !*nullable_rhs)
+            }
+
             // Constant zero subsumes nullable DynamicMem pointers.
             (
                 Fact::Range {
@@ -1381,10 +1481,47 @@ impl<'a> FactContext<'a> {
                 }
                 _ => bail!(OutOfBounds),
             },
+            Fact::DynamicMem { ty, min, max, .. } => match &self.function.memory_types[*ty] {
+                ir::MemoryTypeData::Memory { size: mem_size } => {
+                    self.check_symbolic_address(min, max, size, *mem_size)
+                }
+                _ => bail!(OutOfBounds),
+            },
             _ => bail!(OutOfBounds),
         }
     }
 
+    /// Check an access of `size` bytes at a symbolic range of offsets
+    /// into a memory of static size `mem_size`.
+    fn check_symbolic_address(
+        &self,
+        min: &Expr,
+        max: &Expr,
+        size: u32,
+        mem_size: u64,
+    ) -> PccResult<Option<(ir::MemoryType, u64)>> {
+        ensure!(self.expr_bounds(min).is_some(), OutOfBounds);
+        let (_, max) = self.expr_bounds(max).ok_or(PccError::OutOfBounds)?;
+        let end_offset = max.checked_add(u64::from(size)).ok_or(PccError::Overflow)?;
+        ensure!(end_offset <= mem_size, OutOfBounds);
+        Ok(None)
+    }
+
+    /// Static bounds on the value of an expression, if known: either
+    /// a constant, or an SSA value (bounded by the width of its type)
+    /// plus a non-negative offset.
+    fn expr_bounds(&self, expr: &Expr) -> Option<(u64, u64)> {
+        let offset = u64::try_from(expr.offset).ok()?;
+        match expr.base {
+            BaseExpr::None => Some((offset, offset)),
+            BaseExpr::Value(value) => {
+                let bits = u16::try_from(self.function.dfg.value_type(value).bits()).ok()?;
+                Some((offset, max_value_for_width(bits).checked_add(offset)?))
+            }
+            _ => None,
+        }
+    }
+
     /// Get the access struct field, if any, by a pointer with the
     /// given fact and an access of the given type.
     pub fn struct_field<'b>(
@@ -1450,11 +1587,17 @@ impl<'a> FactContext<'a> {
         rhs: &Fact,
         kind: InequalityKind,
     ) -> Fact {
+        // A constant on the right-hand side is a symbol with no base.
+        let rhs_symbol = rhs.as_symbol().cloned().or_else(|| {
+            rhs.as_const(self.pointer_width)
+                .and_then(|k| i64::try_from(k).ok())
+                .map(Expr::constant)
+        });
         let result = match (
             lhs.as_symbol(),
             lhs.as_const(self.pointer_width)
                 .and_then(|k| i64::try_from(k).ok()),
-            rhs.as_symbol(),
+            rhs_symbol.as_ref(),
             fact,
         ) {
             (
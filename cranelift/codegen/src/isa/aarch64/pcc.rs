@@ -254,6 +254,20 @@ pub(crate) fn check(
             Ok(())
         }
 
+        Inst::AluRRImm12 {
+            alu_op: ALUOp::SubS,
+            size,
+            rd,
+            rn,
+            imm12,
+        } if rd.to_reg() == zero_reg() => {
+            // Compare with an immediate.
+            let rn = get_fact_or_default(vcode, rn, size.bits().into());
+            let rm = Fact::constant(size.bits().into(), imm12.value().into());
+            state.cmp_flags = Some((rn, rm));
+            Ok(())
+        }
+
         Inst::AluRRImmLogic {
             alu_op: ALUOp::Orr,
             size,
@@ -360,6 +374,36 @@ pub(crate) fn check(
             })
         }
 
+        Inst::CSel { rd, cond, rn, rm }
+            if (cond == Cond::Lo || cond == Cond::Ls) && cmp_flags.is_some() =>
+        {
+            let (cmp_lhs, cmp_rhs) = cmp_flags.unwrap();
+            trace!("CSel: cmp {cond:?} ({cmp_lhs:?}, {cmp_rhs:?})");
+
+            check_output(ctx, vcode, rd, &[], |vcode| {
+                // The mirror image of the above: the true side knows
+                // rhs > lhs (Lo) or rhs >= lhs (Ls).
+                let rn = get_fact_or_default(vcode, rn, 64);
+                let rhs_kind = match cond {
+                    Cond::Lo => InequalityKind::Strict,
+                    Cond::Ls => InequalityKind::Loose,
+                    _ => unreachable!(),
+                };
+                let rn = ctx.apply_inequality(&rn, &cmp_rhs, &cmp_lhs, rhs_kind);
+                // False side: lhs >= rhs (Lo) or lhs > rhs (Ls).
+                let rm = get_fact_or_default(vcode, rm, 64);
+                let lhs_kind = match cond {
+                    Cond::Lo => InequalityKind::Loose,
+                    Cond::Ls => InequalityKind::Strict,
+                    _ => unreachable!(),
+                };
+                let rm = ctx.apply_inequality(&rm, &cmp_lhs, &cmp_rhs, lhs_kind);
+                let union = ctx.union(&rn, &rm);
+                // Union the two facts.
+                clamp_range(ctx, 64, 64, union)
+            })
+        }
+
         _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
 
         _ => Ok(()),
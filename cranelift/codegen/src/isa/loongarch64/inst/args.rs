@@ -0,0 +1,690 @@
+//! LoongArch64 ISA definitions: instruction arguments.
+
+use super::*;
+use crate::ir::condcodes::CondCode;
+
+use crate::isa::loongarch64::lower::isle::generated_code::{
+    AluOPRR, AluOPRRI, AluOPRRR, AluOPRRShift, AluOPRRUI, AtomicOP, FpuCond, FpuConvOP, FpuOPRR,
+    FpuOPRRR, FpuOPRRRR, FpuWidth,
+};
+
+use std::fmt::Result;
+
+/// An addressing mode specified for a load/store operation.
+#[derive(Clone, Debug, Copy)]
+pub enum AMode {
+    /// Arbitrary offset from a register. Converted to generation of large
+    /// offsets with multiple instructions as necessary during code emission.
+    RegOffset(Reg, i64),
+    /// Offset from the stack pointer.
+    SPOffset(i64),
+
+    /// Offset from the frame pointer.
+    FPOffset(i64),
+
+    /// Offset into the slot area of the stack, which lies just above the
+    /// outgoing argument area that's setup by the function prologue.
+    /// At emission time, this is converted to `SPOffset` with a fixup added to
+    /// the offset constant.
+    SlotOffset(i64),
+
+    /// Offset into the argument area.
+    IncomingArg(i64),
+
+    /// A reference to a label.
+    Label(MachLabel),
+}
+
+impl AMode {
+    /// Add the registers referenced by this AMode to `collector`.
+    pub(crate) fn get_operands(&mut self, collector: &mut impl OperandVisitor) {
+        match self {
+            AMode::RegOffset(reg, ..) => collector.reg_use(reg),
+            // Registers used in these modes aren't allocatable.
+            AMode::SPOffset(..)
+            | AMode::FPOffset(..)
+            | AMode::SlotOffset(..)
+            | AMode::IncomingArg(..)
+            | AMode::Label(..) => {}
+        }
+    }
+
+    pub(crate) fn get_base_register(&self) -> Option<Reg> {
+        match self {
+            &AMode::RegOffset(reg, ..) => Some(reg),
+            &AMode::SPOffset(..) => Some(stack_reg()),
+            &AMode::FPOffset(..) => Some(fp_reg()),
+            &AMode::SlotOffset(..) => Some(stack_reg()),
+            &AMode::IncomingArg(..) => Some(stack_reg()),
+            AMode::Label(..) => None,
+        }
+    }
+
+    pub(crate) fn get_offset_with_state(&self, state: &EmitState) -> i64 {
+        match self {
+            &AMode::SlotOffset(offset) => {
+                offset + i64::from(state.frame_layout().outgoing_args_size)
+            }
+
+            // Compute the offset into the incoming argument area relative to SP
+            &AMode::IncomingArg(offset) => {
+                let frame_layout = state.frame_layout();
+                let sp_offset = frame_layout.tail_args_size
+                    + frame_layout.setup_area_size
+                    + frame_layout.clobber_size
+                    + frame_layout.fixed_frame_storage_size
+                    + frame_layout.outgoing_args_size;
+                i64::from(sp_offset) - offset
+            }
+
+            &AMode::RegOffset(_, offset) => offset,
+            &AMode::SPOffset(offset) => offset,
+            &AMode::FPOffset(offset) => offset,
+            &AMode::Label(_) => 0,
+        }
+    }
+}
+
+impl Display for AMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            &AMode::RegOffset(r, offset, ..) => {
+                write!(f, "{}({})", offset, reg_name(r))
+            }
+            &AMode::SPOffset(offset, ..) => {
+                write!(f, "{offset}(sp)")
+            }
+            &AMode::SlotOffset(offset, ..) => {
+                write!(f, "{offset}(slot)")
+            }
+            &AMode::IncomingArg(offset) => {
+                write!(f, "-{offset}(incoming_arg)")
+            }
+            &AMode::FPOffset(offset, ..) => {
+                write!(f, "{offset}(fp)")
+            }
+            &AMode::Label(label) => {
+                write!(f, "[label{}]", label.as_u32())
+            }
+        }
+    }
+}
+
+impl From<StackAMode> for AMode {
+    fn from(stack: StackAMode) -> AMode {
+        match stack {
+            StackAMode::IncomingArg(offset, stack_args_size) => {
+                AMode::IncomingArg(i64::from(stack_args_size) - offset)
+            }
+            StackAMode::OutgoingArg(offset) => AMode::SPOffset(offset),
+            StackAMode::Slot(offset) => AMode::SlotOffset(offset),
+        }
+    }
+}
+
+/// A comparison of two integer registers, as used by the conditional branch
+/// instructions.
+#[derive(Clone, Copy, Debug)]
+pub struct IntegerCompare {
+    pub(crate) kind: IntCC,
+    pub(crate) rs1: Reg,
+    pub(crate) rs2: Reg,
+}
+
+impl IntegerCompare {
+    /// The opcode of the branch implementing this comparison, and whether the
+    /// operands must be swapped.
+    pub(crate) fn branch_opcode(&self) -> (u32, bool) {
+        match self.kind {
+            IntCC::Equal => (0x5800_0000, false),
+            IntCC::NotEqual => (0x5C00_0000, false),
+            IntCC::SignedLessThan => (0x6000_0000, false),
+            IntCC::SignedGreaterThanOrEqual => (0x6400_0000, false),
+            IntCC::SignedGreaterThan => (0x6000_0000, true),
+            IntCC::SignedLessThanOrEqual => (0x6400_0000, true),
+            IntCC::UnsignedLessThan => (0x6800_0000, false),
+            IntCC::UnsignedGreaterThanOrEqual => (0x6C00_0000, false),
+            IntCC::UnsignedGreaterThan => (0x6800_0000, true),
+            IntCC::UnsignedLessThanOrEqual => (0x6C00_0000, true),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn op_name(&self) -> &'static str {
+        match self.kind {
+            IntCC::Equal => "beq",
+            IntCC::NotEqual => "bne",
+            IntCC::SignedLessThan => "blt",
+            IntCC::SignedGreaterThanOrEqual => "bge",
+            IntCC::SignedGreaterThan => "bgt",
+            IntCC::SignedLessThanOrEqual => "ble",
+            IntCC::UnsignedLessThan => "bltu",
+            IntCC::UnsignedGreaterThanOrEqual => "bgeu",
+            IntCC::UnsignedGreaterThan => "bgtu",
+            IntCC::UnsignedLessThanOrEqual => "bleu",
+        }
+    }
+
+    /// Encodes the branch with a zero offset; the offset is filled in by a
+    /// `LabelUse::B16` fixup.
+    pub(crate) fn emit(self) -> u32 {
+        let (opcode, reverse) = self.branch_opcode();
+        let (rs1, rs2) = if reverse {
+            (self.rs2, self.rs1)
+        } else {
+            (self.rs1, self.rs2)
+        };
+        opcode | reg_to_gpr_num(rs1) << 5 | reg_to_gpr_num(rs2)
+    }
+
+    pub(crate) fn inverse(self) -> Self {
+        Self {
+            kind: self.kind.complement(),
+            ..self
+        }
+    }
+
+    pub(crate) fn regs(&self) -> [Reg; 2] {
+        [self.rs1, self.rs2]
+    }
+}
+
+impl AluOPRRR {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::AddW => "add.w",
+            Self::AddD => "add.d",
+            Self::SubW => "sub.w",
+            Self::SubD => "sub.d",
+            Self::Slt => "slt",
+            Self::Sltu => "sltu",
+            Self::Maskeqz => "maskeqz",
+            Self::Masknez => "masknez",
+            Self::Nor => "nor",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+            Self::Orn => "orn",
+            Self::Andn => "andn",
+            Self::SllW => "sll.w",
+            Self::SrlW => "srl.w",
+            Self::SraW => "sra.w",
+            Self::SllD => "sll.d",
+            Self::SrlD => "srl.d",
+            Self::SraD => "sra.d",
+            Self::RotrW => "rotr.w",
+            Self::RotrD => "rotr.d",
+            Self::MulW => "mul.w",
+            Self::MulhW => "mulh.w",
+            Self::MulhWu => "mulh.wu",
+            Self::MulD => "mul.d",
+            Self::MulhD => "mulh.d",
+            Self::MulhDu => "mulh.du",
+            Self::DivW => "div.w",
+            Self::ModW => "mod.w",
+            Self::DivWu => "div.wu",
+            Self::ModWu => "mod.wu",
+            Self::DivD => "div.d",
+            Self::ModD => "mod.d",
+            Self::DivDu => "div.du",
+            Self::ModDu => "mod.du",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::AddW => 0x0010_0000,
+            Self::AddD => 0x0010_8000,
+            Self::SubW => 0x0011_0000,
+            Self::SubD => 0x0011_8000,
+            Self::Slt => 0x0012_0000,
+            Self::Sltu => 0x0012_8000,
+            Self::Maskeqz => 0x0013_0000,
+            Self::Masknez => 0x0013_8000,
+            Self::Nor => 0x0014_0000,
+            Self::And => 0x0014_8000,
+            Self::Or => 0x0015_0000,
+            Self::Xor => 0x0015_8000,
+            Self::Orn => 0x0016_0000,
+            Self::Andn => 0x0016_8000,
+            Self::SllW => 0x0017_0000,
+            Self::SrlW => 0x0017_8000,
+            Self::SraW => 0x0018_0000,
+            Self::SllD => 0x0018_8000,
+            Self::SrlD => 0x0019_0000,
+            Self::SraD => 0x0019_8000,
+            Self::RotrW => 0x001B_0000,
+            Self::RotrD => 0x001B_8000,
+            Self::MulW => 0x001C_0000,
+            Self::MulhW => 0x001C_8000,
+            Self::MulhWu => 0x001D_0000,
+            Self::MulD => 0x001D_8000,
+            Self::MulhD => 0x001E_0000,
+            Self::MulhDu => 0x001E_8000,
+            Self::DivW => 0x0020_0000,
+            Self::ModW => 0x0020_8000,
+            Self::DivWu => 0x0021_0000,
+            Self::ModWu => 0x0021_8000,
+            Self::DivD => 0x0022_0000,
+            Self::ModD => 0x0022_8000,
+            Self::DivDu => 0x0023_0000,
+            Self::ModDu => 0x0023_8000,
+        }
+    }
+}
+
+impl AluOPRRI {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Slti => "slti",
+            Self::Sltui => "sltui",
+            Self::AddiW => "addi.w",
+            Self::AddiD => "addi.d",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::Slti => 0x0200_0000,
+            Self::Sltui => 0x0240_0000,
+            Self::AddiW => 0x0280_0000,
+            Self::AddiD => 0x02C0_0000,
+        }
+    }
+}
+
+impl AluOPRRUI {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Andi => "andi",
+            Self::Ori => "ori",
+            Self::Xori => "xori",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::Andi => 0x0340_0000,
+            Self::Ori => 0x0380_0000,
+            Self::Xori => 0x03C0_0000,
+        }
+    }
+}
+
+impl AluOPRRShift {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::SlliW => "slli.w",
+            Self::SlliD => "slli.d",
+            Self::SrliW => "srli.w",
+            Self::SrliD => "srli.d",
+            Self::SraiW => "srai.w",
+            Self::SraiD => "srai.d",
+            Self::RotriW => "rotri.w",
+            Self::RotriD => "rotri.d",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::SlliW => 0x0040_8000,
+            Self::SlliD => 0x0041_0000,
+            Self::SrliW => 0x0044_8000,
+            Self::SrliD => 0x0045_0000,
+            Self::SraiW => 0x0048_8000,
+            Self::SraiD => 0x0049_0000,
+            Self::RotriW => 0x004C_8000,
+            Self::RotriD => 0x004D_0000,
+        }
+    }
+
+    /// The width of the shift amount field.
+    pub(crate) fn shamt_mask(self) -> u32 {
+        match self {
+            Self::SlliW | Self::SrliW | Self::SraiW | Self::RotriW => 0x1f,
+            Self::SlliD | Self::SrliD | Self::SraiD | Self::RotriD => 0x3f,
+        }
+    }
+}
+
+impl AluOPRR {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::ClzW => "clz.w",
+            Self::CtzW => "ctz.w",
+            Self::ClzD => "clz.d",
+            Self::CtzD => "ctz.d",
+            Self::Revb2H => "revb.2h",
+            Self::Revb2W => "revb.2w",
+            Self::RevbD => "revb.d",
+            Self::Bitrev4B => "bitrev.4b",
+            Self::BitrevW => "bitrev.w",
+            Self::BitrevD => "bitrev.d",
+            Self::ExtWH => "ext.w.h",
+            Self::ExtWB => "ext.w.b",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::ClzW => 0x1400,
+            Self::CtzW => 0x1C00,
+            Self::ClzD => 0x2400,
+            Self::CtzD => 0x2C00,
+            Self::Revb2H => 0x3000,
+            Self::Revb2W => 0x3800,
+            Self::RevbD => 0x3C00,
+            Self::Bitrev4B => 0x4800,
+            Self::BitrevW => 0x5000,
+            Self::BitrevD => 0x5400,
+            Self::ExtWH => 0x5800,
+            Self::ExtWB => 0x5C00,
+        }
+    }
+}
+
+impl FpuWidth {
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            Self::S => "s",
+            Self::D => "d",
+        }
+    }
+}
+
+impl FpuOPRR {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Fabs => "fabs",
+            Self::Fneg => "fneg",
+            Self::Fsqrt => "fsqrt",
+            Self::Fmov => "fmov",
+        }
+    }
+
+    pub(crate) fn opcode(self, width: FpuWidth) -> u32 {
+        let single = match self {
+            Self::Fabs => 0x0114_0400,
+            Self::Fneg => 0x0114_1400,
+            Self::Fsqrt => 0x0114_4400,
+            Self::Fmov => 0x0114_9400,
+        };
+        match width {
+            FpuWidth::S => single,
+            FpuWidth::D => single + 0x400,
+        }
+    }
+}
+
+impl FpuOPRRR {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Fadd => "fadd",
+            Self::Fsub => "fsub",
+            Self::Fmul => "fmul",
+            Self::Fdiv => "fdiv",
+            Self::Fmax => "fmax",
+            Self::Fmin => "fmin",
+            Self::Fcopysign => "fcopysign",
+        }
+    }
+
+    pub(crate) fn opcode(self, width: FpuWidth) -> u32 {
+        let single = match self {
+            Self::Fadd => 0x0100_8000,
+            Self::Fsub => 0x0102_8000,
+            Self::Fmul => 0x0104_8000,
+            Self::Fdiv => 0x0106_8000,
+            Self::Fmax => 0x0108_8000,
+            Self::Fmin => 0x010A_8000,
+            Self::Fcopysign => 0x0112_8000,
+        };
+        match width {
+            FpuWidth::S => single,
+            FpuWidth::D => single + 0x8000,
+        }
+    }
+}
+
+impl FpuOPRRRR {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Fmadd => "fmadd",
+            Self::Fmsub => "fmsub",
+            Self::Fnmadd => "fnmadd",
+            Self::Fnmsub => "fnmsub",
+        }
+    }
+
+    pub(crate) fn opcode(self, width: FpuWidth) -> u32 {
+        let single = match self {
+            Self::Fmadd => 0x0810_0000,
+            Self::Fmsub => 0x0850_0000,
+            Self::Fnmadd => 0x0890_0000,
+            Self::Fnmsub => 0x08D0_0000,
+        };
+        match width {
+            FpuWidth::S => single,
+            FpuWidth::D => single + 0x0010_0000,
+        }
+    }
+}
+
+impl FpuConvOP {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::MovgrfrW => "movgr2fr.w",
+            Self::MovgrfrD => "movgr2fr.d",
+            Self::MovfrgrS => "movfr2gr.s",
+            Self::MovfrgrD => "movfr2gr.d",
+            Self::FcvtSD => "fcvt.s.d",
+            Self::FcvtDS => "fcvt.d.s",
+            Self::FtintrmLS => "ftintrm.l.s",
+            Self::FtintrmLD => "ftintrm.l.d",
+            Self::FtintrpLS => "ftintrp.l.s",
+            Self::FtintrpLD => "ftintrp.l.d",
+            Self::FtintrzLS => "ftintrz.l.s",
+            Self::FtintrzLD => "ftintrz.l.d",
+            Self::FtintrneLS => "ftintrne.l.s",
+            Self::FtintrneLD => "ftintrne.l.d",
+            Self::FfintSL => "ffint.s.l",
+            Self::FfintDL => "ffint.d.l",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::MovgrfrW => 0x0114_A400,
+            Self::MovgrfrD => 0x0114_A800,
+            Self::MovfrgrS => 0x0114_B400,
+            Self::MovfrgrD => 0x0114_B800,
+            Self::FcvtSD => 0x0119_1800,
+            Self::FcvtDS => 0x0119_2400,
+            Self::FtintrmLS => 0x011A_2400,
+            Self::FtintrmLD => 0x011A_2800,
+            Self::FtintrpLS => 0x011A_6400,
+            Self::FtintrpLD => 0x011A_6800,
+            Self::FtintrzLS => 0x011A_A400,
+            Self::FtintrzLD => 0x011A_A800,
+            Self::FtintrneLS => 0x011A_E400,
+            Self::FtintrneLD => 0x011A_E800,
+            Self::FfintSL => 0x011D_1800,
+            Self::FfintDL => 0x011D_2800,
+        }
+    }
+
+    /// The register class of the source operand.
+    pub(crate) fn src_class(self) -> RegClass {
+        match self {
+            Self::MovgrfrW | Self::MovgrfrD => RegClass::Int,
+            _ => RegClass::Float,
+        }
+    }
+
+    /// The register class of the destination operand.
+    pub(crate) fn dst_class(self) -> RegClass {
+        match self {
+            Self::MovfrgrS | Self::MovfrgrD => RegClass::Int,
+            _ => RegClass::Float,
+        }
+    }
+}
+
+impl FpuCond {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::Ceq => "ceq",
+            Self::Clt => "clt",
+            Self::Cle => "cle",
+            Self::Cun => "cun",
+            Self::Cueq => "cueq",
+            Self::Cult => "cult",
+            Self::Cule => "cule",
+            Self::Cne => "cne",
+            Self::Cor => "cor",
+            Self::Cune => "cune",
+        }
+    }
+
+    /// The condition field of the quiet `fcmp` encoding.
+    pub(crate) fn bits(self) -> u32 {
+        match self {
+            Self::Ceq => 0x04,
+            Self::Clt => 0x02,
+            Self::Cle => 0x06,
+            Self::Cun => 0x08,
+            Self::Cueq => 0x0C,
+            Self::Cult => 0x0A,
+            Self::Cule => 0x0E,
+            Self::Cne => 0x10,
+            Self::Cor => 0x14,
+            Self::Cune => 0x18,
+        }
+    }
+}
+
+impl LoadOP {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::LdB => "ld.b",
+            Self::LdH => "ld.h",
+            Self::LdW => "ld.w",
+            Self::LdD => "ld.d",
+            Self::LdBu => "ld.bu",
+            Self::LdHu => "ld.hu",
+            Self::LdWu => "ld.wu",
+            Self::FldS => "fld.s",
+            Self::FldD => "fld.d",
+        }
+    }
+
+    pub(crate) fn from_type(ty: Type) -> Self {
+        match ty {
+            F32 => Self::FldS,
+            F64 => Self::FldD,
+            I8 => Self::LdB,
+            I16 => Self::LdH,
+            I32 => Self::LdW,
+            I64 => Self::LdD,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::LdB => 0x2800_0000,
+            Self::LdH => 0x2840_0000,
+            Self::LdW => 0x2880_0000,
+            Self::LdD => 0x28C0_0000,
+            Self::LdBu => 0x2A00_0000,
+            Self::LdHu => 0x2A40_0000,
+            Self::LdWu => 0x2A80_0000,
+            Self::FldS => 0x2B00_0000,
+            Self::FldD => 0x2B80_0000,
+        }
+    }
+}
+
+impl StoreOP {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::StB => "st.b",
+            Self::StH => "st.h",
+            Self::StW => "st.w",
+            Self::StD => "st.d",
+            Self::FstS => "fst.s",
+            Self::FstD => "fst.d",
+        }
+    }
+
+    pub(crate) fn from_type(ty: Type) -> Self {
+        match ty {
+            F32 => Self::FstS,
+            F64 => Self::FstD,
+            I8 => Self::StB,
+            I16 => Self::StH,
+            I32 => Self::StW,
+            I64 => Self::StD,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::StB => 0x2900_0000,
+            Self::StH => 0x2940_0000,
+            Self::StW => 0x2980_0000,
+            Self::StD => 0x29C0_0000,
+            Self::FstS => 0x2B40_0000,
+            Self::FstD => 0x2BC0_0000,
+        }
+    }
+}
+
+impl AtomicOP {
+    pub(crate) fn op_name(self) -> &'static str {
+        match self {
+            Self::AmswapW => "amswap_db.w",
+            Self::AmswapD => "amswap_db.d",
+            Self::AmaddW => "amadd_db.w",
+            Self::AmaddD => "amadd_db.d",
+            Self::AmandW => "amand_db.w",
+            Self::AmandD => "amand_db.d",
+            Self::AmorW => "amor_db.w",
+            Self::AmorD => "amor_db.d",
+            Self::AmxorW => "amxor_db.w",
+            Self::AmxorD => "amxor_db.d",
+            Self::AmmaxW => "ammax_db.w",
+            Self::AmmaxD => "ammax_db.d",
+            Self::AmminW => "ammin_db.w",
+            Self::AmminD => "ammin_db.d",
+            Self::AmmaxWu => "ammax_db.wu",
+            Self::AmmaxDu => "ammax_db.du",
+            Self::AmminWu => "ammin_db.wu",
+            Self::AmminDu => "ammin_db.du",
+        }
+    }
+
+    pub(crate) fn opcode(self) -> u32 {
+        match self {
+            Self::AmswapW => 0x3869_0000,
+            Self::AmswapD => 0x3869_8000,
+            Self::AmaddW => 0x386A_0000,
+            Self::AmaddD => 0x386A_8000,
+            Self::AmandW => 0x386B_0000,
+            Self::AmandD => 0x386B_8000,
+            Self::AmorW => 0x386C_0000,
+            Self::AmorD => 0x386C_8000,
+            Self::AmxorW => 0x386D_0000,
+            Self::AmxorD => 0x386D_8000,
+            Self::AmmaxW => 0x386E_0000,
+            Self::AmmaxD => 0x386E_8000,
+            Self::AmminW => 0x386F_0000,
+            Self::AmminD => 0x386F_8000,
+            Self::AmmaxWu => 0x3870_0000,
+            Self::AmmaxDu => 0x3870_8000,
+            Self::AmminWu => 0x3871_0000,
+            Self::AmminDu => 0x3871_8000,
+        }
+    }
+}
@@ -0,0 +1,1485 @@
+//! LoongArch64 ISA: binary code emission.
+
+use crate::ir::{self, AtomicRmwOp};
+use crate::isa::loongarch64::abi::LoongArch64MachineDeps;
+use crate::isa::loongarch64::inst::*;
+use cranelift_control::ControlPlane;
+
+pub struct EmitInfo {
+    shared_flag: settings::Flags,
+    #[expect(dead_code, reason = "no ISA extensions influence encoding yet")]
+    isa_flags: super::super::loongarch_settings::Flags,
+}
+
+impl EmitInfo {
+    pub(crate) fn new(
+        shared_flag: settings::Flags,
+        isa_flags: super::super::loongarch_settings::Flags,
+    ) -> Self {
+        Self {
+            shared_flag,
+            isa_flags,
+        }
+    }
+}
+
+pub(crate) fn reg_to_gpr_num(m: Reg) -> u32 {
+    u32::from(m.to_real_reg().unwrap().hw_enc() & 31)
+}
+
+/// State carried between emissions of a sequence of instructions.
+#[derive(Default, Clone, Debug)]
+pub struct EmitState {
+    /// The user stack map for the upcoming instruction, as provided to
+    /// `pre_safepoint()`.
+    user_stack_map: Option<ir::UserStackMap>,
+
+    /// Only used during fuzz-testing. Otherwise, it is a zero-sized struct and
+    /// optimized away at compiletime. See [cranelift_control].
+    ctrl_plane: ControlPlane,
+
+    frame_layout: FrameLayout,
+}
+
+impl EmitState {
+    fn take_stack_map(&mut self) -> Option<ir::UserStackMap> {
+        self.user_stack_map.take()
+    }
+}
+
+impl MachInstEmitState<Inst> for EmitState {
+    fn new(abi: &Callee<LoongArch64MachineDeps>, ctrl_plane: ControlPlane) -> Self {
+        EmitState {
+            user_stack_map: None,
+            ctrl_plane,
+            frame_layout: abi.frame_layout().clone(),
+        }
+    }
+
+    fn pre_safepoint(&mut self, user_stack_map: Option<ir::UserStackMap>) {
+        self.user_stack_map = user_stack_map;
+    }
+
+    fn ctrl_plane_mut(&mut self) -> &mut ControlPlane {
+        &mut self.ctrl_plane
+    }
+
+    fn take_ctrl_plane(self) -> ControlPlane {
+        self.ctrl_plane
+    }
+
+    fn on_new_block(&mut self) {}
+
+    fn frame_layout(&self) -> &FrameLayout {
+        &self.frame_layout
+    }
+}
+
+/// `op rd, rj, rk`
+fn enc_rrr(op: u32, rd: Writable<Reg>, rj: Reg, rk: Reg) -> u32 {
+    op | reg_to_gpr_num(rk) << 10 | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+/// `op rd, rj, imm12`, used by both the signed and unsigned 12-bit forms.
+fn enc_rri12(op: u32, rd: Writable<Reg>, rj: Reg, imm12: u32) -> u32 {
+    op | (imm12 & 0xFFF) << 10 | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+/// `op rd, si20`, used by `lu12i.w`, `lu32i.d`, `pcaddi` and `pcalau12i`.
+fn enc_ri20(op: u32, rd: Writable<Reg>, si20: u32) -> u32 {
+    op | (si20 & 0xF_FFFF) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+/// `op rd, rj, 0` for the `ll`/`sc` family.
+fn enc_llsc(op: u32, rd: Writable<Reg>, rj: Reg) -> u32 {
+    op | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+const LL_W: u32 = 0x2000_0000;
+const SC_W: u32 = 0x2100_0000;
+const LL_D: u32 = 0x2200_0000;
+const SC_D: u32 = 0x2300_0000;
+
+const LU12I_W: u32 = 0x1400_0000;
+const LU32I_D: u32 = 0x1600_0000;
+const LU52I_D: u32 = 0x0300_0000;
+const PCADDI: u32 = 0x1800_0000;
+const PCALAU12I: u32 = 0x1A00_0000;
+const B: u32 = 0x5000_0000;
+
+impl Inst {
+    fn alu_rrr(alu_op: AluOPRRR, rd: Writable<Reg>, rj: Reg, rk: Reg) -> Inst {
+        Inst::AluRRR { alu_op, rd, rj, rk }
+    }
+
+    fn alu_rri(alu_op: AluOPRRI, rd: Writable<Reg>, rj: Reg, imm: i16) -> Inst {
+        Inst::AluRRImm12 {
+            alu_op,
+            rd,
+            rj,
+            imm12: Imm12::from_i16(imm),
+        }
+    }
+
+    fn alu_rrui(alu_op: AluOPRRUI, rd: Writable<Reg>, rj: Reg, imm: u64) -> Inst {
+        Inst::AluRRUImm12 {
+            alu_op,
+            rd,
+            rj,
+            imm12: UImm12::maybe_from_u64(imm).unwrap(),
+        }
+    }
+
+    fn alu_shift(alu_op: AluOPRRShift, rd: Writable<Reg>, rj: Reg, shamt: u64) -> Inst {
+        Inst::AluRRShift {
+            alu_op,
+            rd,
+            rj,
+            shamt: UImm6::maybe_from_u64(shamt).unwrap(),
+        }
+    }
+
+    /// Emit a sequence of instructions.
+    fn emit_all(
+        insts: &[Inst],
+        sink: &mut MachBuffer<Inst>,
+        emit_info: &EmitInfo,
+        state: &mut EmitState,
+    ) {
+        for inst in insts {
+            inst.emit(sink, emit_info, state);
+        }
+    }
+
+    /// Compute `rd = op(a, b)` for an atomic read-modify-write.
+    ///
+    /// `t0` is clobbered by the min/max operations, which compare `a` and `b`
+    /// with `slt` or `sltu` depending on `signed`.
+    fn atomic_op_insts(
+        op: AtomicRmwOp,
+        rd: Writable<Reg>,
+        a: Reg,
+        b: Reg,
+        t0: Writable<Reg>,
+        signed: bool,
+    ) -> SmallInstVec<Inst> {
+        let mut insts = SmallInstVec::new();
+        match op {
+            AtomicRmwOp::Add => insts.push(Inst::alu_rrr(AluOPRRR::AddD, rd, a, b)),
+            AtomicRmwOp::Sub => insts.push(Inst::alu_rrr(AluOPRRR::SubD, rd, a, b)),
+            AtomicRmwOp::And => insts.push(Inst::alu_rrr(AluOPRRR::And, rd, a, b)),
+            AtomicRmwOp::Or => insts.push(Inst::alu_rrr(AluOPRRR::Or, rd, a, b)),
+            AtomicRmwOp::Xor => insts.push(Inst::alu_rrr(AluOPRRR::Xor, rd, a, b)),
+            AtomicRmwOp::Nand => {
+                insts.push(Inst::alu_rrr(AluOPRRR::And, rd, a, b));
+                insts.push(Inst::alu_rrr(AluOPRRR::Nor, rd, rd.to_reg(), zero_reg()));
+            }
+            AtomicRmwOp::Xchg => insts.push(Inst::alu_rrr(AluOPRRR::Or, rd, b, zero_reg())),
+            AtomicRmwOp::Smin | AtomicRmwOp::Smax | AtomicRmwOp::Umin | AtomicRmwOp::Umax => {
+                let cmp = if signed {
+                    AluOPRRR::Slt
+                } else {
+                    AluOPRRR::Sltu
+                };
+                // t0 = a < b
+                insts.push(Inst::alu_rrr(cmp, t0, a, b));
+                // max: rd = t0 ? b : a
+                // min: rd = t0 ? a : b
+                let (keep_a, keep_b) = match op {
+                    AtomicRmwOp::Smax | AtomicRmwOp::Umax => (AluOPRRR::Masknez, AluOPRRR::Maskeqz),
+                    _ => (AluOPRRR::Maskeqz, AluOPRRR::Masknez),
+                };
+                insts.push(Inst::alu_rrr(keep_a, rd, a, t0.to_reg()));
+                insts.push(Inst::alu_rrr(keep_b, t0, b, t0.to_reg()));
+                insts.push(Inst::alu_rrr(AluOPRRR::Or, rd, rd.to_reg(), t0.to_reg()));
+            }
+        }
+        insts
+    }
+
+    /// Split an 8 or 16-bit atomic access into its containing aligned word.
+    ///
+    /// Leaves the bit offset of the field in `shift`, the address of the word
+    /// in `spilltmp2` and the field mask, shifted into place, in `spilltmp`.
+    fn atomic_narrow_setup(ty: Type, addr: Reg, shift: Writable<Reg>) -> SmallInstVec<Inst> {
+        let mut insts = SmallInstVec::new();
+        insts.extend(Inst::atomic_narrow_shift(addr, shift));
+        insts.push(Inst::alu_shift(
+            AluOPRRShift::SrliD,
+            writable_spilltmp_reg2(),
+            addr,
+            2,
+        ));
+        insts.push(Inst::alu_shift(
+            AluOPRRShift::SlliD,
+            writable_spilltmp_reg2(),
+            spilltmp_reg2(),
+            2,
+        ));
+        insts.push(Inst::alu_rri(
+            AluOPRRI::AddiW,
+            writable_spilltmp_reg(),
+            zero_reg(),
+            -1,
+        ));
+        insts.push(Inst::Bstrpick {
+            rd: writable_spilltmp_reg(),
+            rj: spilltmp_reg(),
+            msb: (ty.bits() - 1) as u8,
+            lsb: 0,
+        });
+        insts.push(Inst::alu_rrr(
+            AluOPRRR::SllD,
+            writable_spilltmp_reg(),
+            spilltmp_reg(),
+            shift.to_reg(),
+        ));
+        insts
+    }
+
+    /// `shift = (addr & 3) * 8`
+    fn atomic_narrow_shift(addr: Reg, shift: Writable<Reg>) -> [Inst; 2] {
+        [
+            Inst::alu_rrui(AluOPRRUI::Andi, shift, addr, 3),
+            Inst::alu_shift(AluOPRRShift::SlliD, shift, shift.to_reg(), 3),
+        ]
+    }
+
+    /// Insert the field of `new` selected by the mask in `spilltmp` into
+    /// `old`, leaving the result in `new`.
+    fn atomic_narrow_merge(new: Writable<Reg>, old: Reg) -> [Inst; 3] {
+        [
+            Inst::alu_rrr(AluOPRRR::Xor, new, new.to_reg(), old),
+            Inst::alu_rrr(AluOPRRR::And, new, new.to_reg(), spilltmp_reg()),
+            Inst::alu_rrr(AluOPRRR::Xor, new, new.to_reg(), old),
+        ]
+    }
+
+    /// Move the field at bit offset `shift` of `dst` down into the low bits
+    /// and zero-extend it.
+    fn atomic_narrow_extract(ty: Type, dst: Writable<Reg>, shift: Reg) -> [Inst; 2] {
+        [
+            Inst::alu_rrr(AluOPRRR::SrlD, dst, dst.to_reg(), shift),
+            Inst::Bstrpick {
+                rd: dst,
+                rj: dst.to_reg(),
+                msb: (ty.bits() - 1) as u8,
+                lsb: 0,
+            },
+        ]
+    }
+}
+
+impl MachInstEmit for Inst {
+    type State = EmitState;
+    type Info = EmitInfo;
+
+    fn emit(&self, sink: &mut MachBuffer<Inst>, emit_info: &Self::Info, state: &mut EmitState) {
+        // N.B.: we *must* not exceed the "worst-case size" used to compute
+        // where to insert islands, except when islands are explicitly triggered
+        // (with an `EmitIsland`). We check this in debug builds. This is `mut`
+        // to allow disabling the check for `BrTable`, which emits its own
+        // island.
+        let mut start_off = sink.cur_offset();
+
+        self.emit_inner(sink, emit_info, state, &mut start_off);
+
+        // We exclude br_table, call, return_call and try_call from
+        // these checks since they emit their own islands, and thus
+        // are allowed to exceed the worst case size.
+        let emits_own_island = match self {
+            Inst::BrTable { .. }
+            | Inst::ReturnCall { .. }
+            | Inst::ReturnCallInd { .. }
+            | Inst::Call { .. }
+            | Inst::CallInd { .. }
+            | Inst::EmitIsland { .. } => true,
+            _ => false,
+        };
+        if !emits_own_island {
+            let end_off = sink.cur_offset();
+            assert!(
+                (end_off - start_off) <= Inst::worst_case_size(),
+                "Inst:{:?} length:{} worst_case_size:{}",
+                self,
+                end_off - start_off,
+                Inst::worst_case_size()
+            );
+        }
+    }
+
+    fn pretty_print_inst(&self, state: &mut Self::State) -> String {
+        self.print_with_state(state)
+    }
+}
+
+impl Inst {
+    fn emit_inner(
+        &self,
+        sink: &mut MachBuffer<Inst>,
+        emit_info: &EmitInfo,
+        state: &mut EmitState,
+        start_off: &mut u32,
+    ) {
+        match self {
+            &Inst::Nop0 => {
+                // do nothing
+            }
+            // Addi r0, r0, 0 is not a nop on LoongArch, `andi r0, r0, 0` is.
+            &Inst::Nop4 => {
+                sink.put4(0x0340_0000);
+            }
+            &Inst::LoadConst64 { rd, imm } => {
+                let simm = imm as i64;
+                if let Some(imm12) = Imm12::maybe_from_i64(simm) {
+                    sink.put4(enc_rri12(
+                        AluOPRRI::AddiD.opcode(),
+                        rd,
+                        zero_reg(),
+                        imm12.bits(),
+                    ));
+                    return;
+                }
+                if imm <= 0xFFF {
+                    sink.put4(enc_rri12(
+                        AluOPRRUI::Ori.opcode(),
+                        rd,
+                        zero_reg(),
+                        imm as u32,
+                    ));
+                    return;
+                }
+
+                if imm & ((1 << 52) - 1) == 0 {
+                    sink.put4(enc_rri12(LU52I_D, rd, zero_reg(), (imm >> 52) as u32));
+                    return;
+                }
+
+                // Build bits 31:0 with `lu12i.w` + `ori`, which sign-extends
+                // bit 31, then fix up bits 51:32 with `lu32i.d` (which
+                // sign-extends bit 51) and bits 63:52 with `lu52i.d` as
+                // needed.
+                let lo12 = (imm & 0xFFF) as u32;
+                let hi20 = ((imm >> 12) & 0xF_FFFF) as u32;
+                let mut cur = if hi20 == 0 {
+                    sink.put4(enc_rri12(AluOPRRUI::Ori.opcode(), rd, zero_reg(), lo12));
+                    u64::from(lo12)
+                } else {
+                    sink.put4(enc_ri20(LU12I_W, rd, hi20));
+                    if lo12 != 0 {
+                        sink.put4(enc_rri12(AluOPRRUI::Ori.opcode(), rd, rd.to_reg(), lo12));
+                    }
+                    imm as u32 as i32 as i64 as u64
+                };
+                if cur != imm {
+                    let mid20 = ((imm >> 32) & 0xF_FFFF) as u32;
+                    sink.put4(enc_ri20(LU32I_D, rd, mid20));
+                    cur = (cur & 0xFFFF_FFFF) | ((i64::from((mid20 << 12) as i32) as u64) << 20);
+                }
+                if cur != imm {
+                    let top12 = (imm >> 52) as u32;
+                    sink.put4(enc_rri12(LU52I_D, rd, rd.to_reg(), top12));
+                }
+            }
+            &Inst::AluRRR { alu_op, rd, rj, rk } => {
+                sink.put4(enc_rrr(alu_op.opcode(), rd, rj, rk));
+            }
+            &Inst::AluRRImm12 {
+                alu_op,
+                rd,
+                rj,
+                imm12,
+            } => {
+                sink.put4(enc_rri12(alu_op.opcode(), rd, rj, imm12.bits()));
+            }
+            &Inst::AluRRUImm12 {
+                alu_op,
+                rd,
+                rj,
+                imm12,
+            } => {
+                sink.put4(enc_rri12(alu_op.opcode(), rd, rj, imm12.bits()));
+            }
+            &Inst::AluRRShift {
+                alu_op,
+                rd,
+                rj,
+                shamt,
+            } => {
+                let shamt = shamt.bits() & alu_op.shamt_mask();
+                sink.put4(
+                    alu_op.opcode()
+                        | shamt << 10
+                        | reg_to_gpr_num(rj) << 5
+                        | reg_to_gpr_num(rd.to_reg()),
+                );
+            }
+            &Inst::AluRR { alu_op, rd, rj } => {
+                sink.put4(alu_op.opcode() | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg()));
+            }
+            &Inst::Bstrpick { rd, rj, msb, lsb } => {
+                debug_assert!(msb < 64 && lsb <= msb);
+                sink.put4(
+                    0x00C0_0000
+                        | u32::from(msb) << 16
+                        | u32::from(lsb) << 10
+                        | reg_to_gpr_num(rj) << 5
+                        | reg_to_gpr_num(rd.to_reg()),
+                );
+            }
+            &Inst::Alsl { rd, rj, rk, shamt } => {
+                debug_assert!((1..=4).contains(&shamt));
+                sink.put4(0x002C_0000 | u32::from(shamt - 1) << 15 | enc_rrr(0, rd, rj, rk));
+            }
+            &Inst::FpuRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+            } => {
+                sink.put4(
+                    fpu_op.opcode(width) | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg()),
+                );
+            }
+            &Inst::FpuRRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+                rk,
+            } => {
+                sink.put4(enc_rrr(fpu_op.opcode(width), rd, rj, rk));
+            }
+            &Inst::FpuRRRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+                rk,
+                ra,
+            } => {
+                sink.put4(fpu_op.opcode(width) | reg_to_gpr_num(ra) << 15 | enc_rrr(0, rd, rj, rk));
+            }
+            &Inst::FpuConv { op, rd, rj } => {
+                debug_assert_eq!(rj.class(), op.src_class());
+                debug_assert_eq!(rd.to_reg().class(), op.dst_class());
+                sink.put4(op.opcode() | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg()));
+            }
+            &Inst::FpuCmp {
+                cond,
+                width,
+                rd,
+                rj,
+                rk,
+            } => {
+                let op = match width {
+                    FpuWidth::S => 0x0C10_0000,
+                    FpuWidth::D => 0x0C20_0000,
+                };
+                // fcmp.cond.{s,d} $fcc0, rj, rk
+                sink.put4(
+                    op | cond.bits() << 15 | reg_to_gpr_num(rk) << 10 | reg_to_gpr_num(rj) << 5,
+                );
+                // movcf2gr rd, $fcc0
+                sink.put4(0x0114_DC00 | reg_to_gpr_num(rd.to_reg()));
+            }
+            &Inst::FpuSel { rd, c, x, y } => {
+                // movgr2cf $fcc0, c
+                sink.put4(0x0114_D800 | reg_to_gpr_num(c) << 5);
+                // fsel rd, y, x, $fcc0
+                sink.put4(0x0D00_0000 | enc_rrr(0, rd, y, x));
+            }
+            &Inst::Load {
+                rd,
+                op,
+                flags,
+                from,
+            } => {
+                let (base, imm12) = Inst::emit_amode(from, sink, emit_info, state);
+
+                if let Some(trap_code) = flags.trap_code() {
+                    // Register the offset at which the actual load instruction starts.
+                    sink.add_trap(trap_code);
+                }
+
+                sink.put4(enc_rri12(op.opcode(), rd, base, imm12.bits()));
+            }
+            &Inst::Store { op, src, flags, to } => {
+                let (base, imm12) = Inst::emit_amode(to, sink, emit_info, state);
+
+                if let Some(trap_code) = flags.trap_code() {
+                    // Register the offset at which the actual store instruction starts.
+                    sink.add_trap(trap_code);
+                }
+
+                sink.put4(
+                    op.opcode()
+                        | imm12.bits() << 10
+                        | reg_to_gpr_num(base) << 5
+                        | reg_to_gpr_num(src),
+                );
+            }
+            &Inst::Args { .. } | &Inst::Rets { .. } => {
+                // Nothing: this is a pseudoinstruction that serves
+                // only to constrain registers at a certain point.
+            }
+            &Inst::Ret {} => {
+                // jirl zero, ra, 0
+                sink.put4(enc_jirl(writable_zero_reg(), link_reg(), 0));
+            }
+
+            &Inst::Extend {
+                rd,
+                rn,
+                signed,
+                from_bits,
+                to_bits: _to_bits,
+            } => {
+                let inst = match (signed, from_bits) {
+                    (true, 8) => Inst::AluRR {
+                        alu_op: AluOPRR::ExtWB,
+                        rd,
+                        rj: rn,
+                    },
+                    (true, 16) => Inst::AluRR {
+                        alu_op: AluOPRR::ExtWH,
+                        rd,
+                        rj: rn,
+                    },
+                    // `addi.w` sign-extends its 32-bit result.
+                    (true, 32) => Inst::alu_rri(AluOPRRI::AddiW, rd, rn, 0),
+                    (false, _) => Inst::Bstrpick {
+                        rd,
+                        rj: rn,
+                        msb: from_bits - 1,
+                        lsb: 0,
+                    },
+                    _ => unreachable!("unsupported extension from {from_bits} bits"),
+                };
+                inst.emit(sink, emit_info, state);
+            }
+
+            &Inst::Call { ref info } => {
+                sink.add_reloc(Reloc::LoongArchCall36, &info.dest, 0);
+                sink.put4(enc_pcaddu18i(writable_link_reg(), 0));
+                sink.put4(enc_jirl(writable_link_reg(), link_reg(), 0));
+
+                if let Some(s) = state.take_stack_map() {
+                    let offset = sink.cur_offset();
+                    sink.push_user_stack_map(state, offset, s);
+                }
+
+                if let Some(try_call) = info.try_call_info.as_ref() {
+                    sink.add_call_site(&try_call.exception_dests);
+                } else {
+                    sink.add_call_site(&[]);
+                }
+
+                let callee_pop_size = i32::try_from(info.callee_pop_size).unwrap();
+                if callee_pop_size > 0 {
+                    for inst in LoongArch64MachineDeps::gen_sp_reg_adjust(-callee_pop_size) {
+                        inst.emit(sink, emit_info, state);
+                    }
+                }
+
+                // Load any stack-carried return values.
+                info.emit_retval_loads::<LoongArch64MachineDeps, _, _>(
+                    state.frame_layout().stackslots_size,
+                    |inst| inst.emit(sink, emit_info, state),
+                    |needed_space| Some(Inst::EmitIsland { needed_space }),
+                );
+
+                // If this is a try-call, jump to the continuation
+                // (normal-return) block.
+                if let Some(try_call) = info.try_call_info.as_ref() {
+                    let jmp = Inst::Jump {
+                        label: try_call.continuation,
+                    };
+                    jmp.emit(sink, emit_info, state);
+                }
+
+                *start_off = sink.cur_offset();
+            }
+            &Inst::CallInd { ref info } => {
+                sink.put4(enc_jirl(writable_link_reg(), info.dest, 0));
+
+                if let Some(s) = state.take_stack_map() {
+                    let offset = sink.cur_offset();
+                    sink.push_user_stack_map(state, offset, s);
+                }
+
+                if let Some(try_call) = info.try_call_info.as_ref() {
+                    sink.add_call_site(&try_call.exception_dests);
+                } else {
+                    sink.add_call_site(&[]);
+                }
+
+                let callee_pop_size = i32::try_from(info.callee_pop_size).unwrap();
+                if callee_pop_size > 0 {
+                    for inst in LoongArch64MachineDeps::gen_sp_reg_adjust(-callee_pop_size) {
+                        inst.emit(sink, emit_info, state);
+                    }
+                }
+
+                // Load any stack-carried return values.
+                info.emit_retval_loads::<LoongArch64MachineDeps, _, _>(
+                    state.frame_layout().stackslots_size,
+                    |inst| inst.emit(sink, emit_info, state),
+                    |needed_space| Some(Inst::EmitIsland { needed_space }),
+                );
+
+                // If this is a try-call, jump to the continuation
+                // (normal-return) block.
+                if let Some(try_call) = info.try_call_info.as_ref() {
+                    let jmp = Inst::Jump {
+                        label: try_call.continuation,
+                    };
+                    jmp.emit(sink, emit_info, state);
+                }
+
+                *start_off = sink.cur_offset();
+            }
+
+            &Inst::ReturnCall { ref info } => {
+                emit_return_call_common_sequence(sink, emit_info, state, info);
+
+                sink.add_call_site(&[]);
+                sink.add_reloc(Reloc::LoongArchCall36, &info.dest, 0);
+                sink.put4(enc_pcaddu18i(writable_spilltmp_reg(), 0));
+                sink.put4(enc_jirl(writable_zero_reg(), spilltmp_reg(), 0));
+            }
+
+            &Inst::ReturnCallInd { ref info } => {
+                emit_return_call_common_sequence(sink, emit_info, state, &info);
+
+                sink.put4(enc_jirl(writable_zero_reg(), info.dest, 0));
+            }
+            &Inst::Jump { label } => {
+                sink.use_label_at_offset(*start_off, label, LabelUse::B26);
+                sink.add_uncond_branch(*start_off, *start_off + 4, label);
+                sink.put4(B);
+            }
+            &Inst::CondBr {
+                taken,
+                not_taken,
+                kind,
+            } => {
+                match taken {
+                    CondBrTarget::Label(label) => {
+                        let code = kind.emit();
+                        let code_inverse = kind.inverse().emit().to_le_bytes();
+                        sink.use_label_at_offset(*start_off, label, LabelUse::B16);
+                        sink.add_cond_branch(*start_off, *start_off + 4, label, &code_inverse);
+                        sink.put4(code);
+                    }
+                    CondBrTarget::Fallthrough => panic!("Cannot fallthrough in taken target"),
+                }
+
+                match not_taken {
+                    CondBrTarget::Label(label) => {
+                        Inst::gen_jump(label).emit(sink, emit_info, state)
+                    }
+                    CondBrTarget::Fallthrough => {}
+                };
+            }
+
+            &Inst::Mov { rd, rm, ty } => {
+                debug_assert_eq!(rd.to_reg().class(), rm.class());
+                if rd.to_reg() == rm {
+                    return;
+                }
+
+                match rm.class() {
+                    RegClass::Int => Inst::alu_rrr(AluOPRRR::Or, rd, rm, zero_reg()),
+                    RegClass::Float => Inst::FpuRR {
+                        fpu_op: FpuOPRR::Fmov,
+                        width: if ty == F32 { FpuWidth::S } else { FpuWidth::D },
+                        rd,
+                        rj: rm,
+                    },
+                    RegClass::Vector => unreachable!("no vector registers"),
+                }
+                .emit(sink, emit_info, state);
+            }
+
+            &Inst::MovFromPReg { rd, rm } => {
+                Inst::gen_move(rd, Reg::from(rm), I64).emit(sink, emit_info, state);
+            }
+
+            &Inst::BrTable {
+                index,
+                tmp1,
+                tmp2,
+                ref targets,
+            } => {
+                // The default target is passed in as the 0th element of `targets`
+                // separate it here for clarity.
+                let default_target = targets[0];
+                let targets = &targets[1..];
+
+                // We are going to potentially emit a large amount of
+                // instructions, so ensure that we emit an island now if we
+                // need one. The bounds check and target computation are at
+                // most 9 instructions, followed by one `b` per entry.
+                let distance = ((9 + targets.len()) * Inst::INSTRUCTION_SIZE as usize) as u32;
+                if sink.island_needed(distance) {
+                    let jump_around_label = sink.get_label();
+                    Inst::gen_jump(jump_around_label).emit(sink, emit_info, state);
+                    sink.emit_island(distance + 4, &mut state.ctrl_plane);
+                    sink.bind_label(jump_around_label, &mut state.ctrl_plane);
+                }
+
+                // Build the following sequence:
+                //
+                //     bstrpick.d tmp1, index, 31, 0
+                //     li         spilltmp, n_labels
+                //     bltu       tmp1, spilltmp, compute_target
+                //     b          default_block
+                // compute_target:
+                //     pcaddi     tmp2, 3
+                //     alsl.d     tmp2, tmp1, tmp2, 2
+                //     jirl       zero, tmp2, 0
+                // jump_table:
+                //     ; This repeats for each entry in the jumptable
+                //     b          block_target
+                //
+                // None of these branches are registered with the branch
+                // optimizer, as the table layout must not change.
+
+                // Zero-extend the index, as its top 32 bits are undefined.
+                Inst::Bstrpick {
+                    rd: tmp1,
+                    rj: index,
+                    msb: 31,
+                    lsb: 0,
+                }
+                .emit(sink, emit_info, state);
+
+                Inst::load_constant_u64(writable_spilltmp_reg(), targets.len() as u64)
+                    .iter()
+                    .for_each(|i| i.emit(sink, emit_info, state));
+
+                let label_compute_target = sink.get_label();
+                let bounds_check = IntegerCompare {
+                    kind: IntCC::UnsignedLessThan,
+                    rs1: tmp1.to_reg(),
+                    rs2: spilltmp_reg(),
+                };
+                sink.use_label_at_offset(sink.cur_offset(), label_compute_target, LabelUse::B16);
+                sink.put4(bounds_check.emit());
+
+                sink.use_label_at_offset(sink.cur_offset(), default_target, LabelUse::B26);
+                sink.put4(B);
+
+                sink.bind_label(label_compute_target, &mut state.ctrl_plane);
+                sink.put4(enc_ri20(PCADDI, tmp2, 3));
+                Inst::Alsl {
+                    rd: tmp2,
+                    rj: tmp1.to_reg(),
+                    rk: tmp2.to_reg(),
+                    shamt: 2,
+                }
+                .emit(sink, emit_info, state);
+                sink.put4(enc_jirl(writable_zero_reg(), tmp2.to_reg(), 0));
+
+                for target in targets.iter() {
+                    sink.use_label_at_offset(sink.cur_offset(), *target, LabelUse::B26);
+                    sink.put4(B);
+                }
+
+                // We've just emitted an island that is safe up to *here*.
+                // Mark it as such so that we don't needlessly emit additional islands.
+                *start_off = sink.cur_offset();
+            }
+
+            &Inst::Fence => {
+                // dbar 0
+                sink.put4(0x3872_0000);
+            }
+            &Inst::Break => {
+                // break 0
+                sink.put4(0x002A_0000);
+            }
+            &Inst::AtomicRmw { op, rd, addr, src } => {
+                sink.put4(enc_rrr(op.opcode(), rd, addr, src));
+            }
+            &Inst::AtomicRmwLoop {
+                op,
+                ty,
+                dst,
+                addr,
+                x,
+                t0,
+                t1,
+                t2,
+                t3,
+            } => {
+                let is_min_max = matches!(
+                    op,
+                    AtomicRmwOp::Smin | AtomicRmwOp::Smax | AtomicRmwOp::Umin | AtomicRmwOp::Umax
+                );
+                let signed = matches!(op, AtomicRmwOp::Smin | AtomicRmwOp::Smax);
+                let loop_start = sink.get_label();
+
+                Inst::Fence.emit(sink, emit_info, state);
+
+                if ty.bits() >= 32 {
+                    // 32 and 64-bit operations work directly on the memory
+                    // location:
+                    //
+                    // loop:
+                    //     ll.{w,d}  dst, addr, 0
+                    //     <t2 = op(dst, x)>
+                    //     sc.{w,d}  t2, addr, 0
+                    //     beq       t2, zero, loop
+                    //
+                    // `ll.w` sign-extends the loaded value, so 32-bit min/max
+                    // compare operands extended to match.
+                    let (ll, sc) = if ty == I64 {
+                        (LL_D, SC_D)
+                    } else {
+                        (LL_W, SC_W)
+                    };
+                    let (a, b) = match (ty, is_min_max, signed) {
+                        (I32, true, true) => {
+                            Inst::alu_rri(AluOPRRI::AddiW, t1, x, 0).emit(sink, emit_info, state);
+                            (dst.to_reg(), t1.to_reg())
+                        }
+                        (I32, true, false) => {
+                            Inst::Bstrpick {
+                                rd: t1,
+                                rj: x,
+                                msb: 31,
+                                lsb: 0,
+                            }
+                            .emit(sink, emit_info, state);
+                            (t3.to_reg(), t1.to_reg())
+                        }
+                        _ => (dst.to_reg(), x),
+                    };
+
+                    sink.bind_label(loop_start, &mut state.ctrl_plane);
+                    sink.put4(enc_llsc(ll, dst, addr));
+                    if a == t3.to_reg() {
+                        Inst::Bstrpick {
+                            rd: t3,
+                            rj: dst.to_reg(),
+                            msb: 31,
+                            lsb: 0,
+                        }
+                        .emit(sink, emit_info, state);
+                    }
+                    let insts = Inst::atomic_op_insts(op, t2, a, b, t0, signed);
+                    Inst::emit_all(&insts, sink, emit_info, state);
+                    sink.put4(enc_llsc(sc, t2, addr));
+                } else {
+                    // 8 and 16-bit operations work on the containing aligned
+                    // word, with the field at bit offset `t0` and the
+                    // in-place field mask in `spilltmp`:
+                    //
+                    //     <setup>
+                    //     sll.d     t1, x, t0
+                    // loop:
+                    //     ll.w      dst, spilltmp2, 0
+                    //     <t2 = op(dst, t1)>
+                    //     <t2 = dst with the field replaced by t2's>
+                    //     sc.w      t2, spilltmp2, 0
+                    //     beq       t2, zero, loop
+                    //     srl.d     dst, dst, t0
+                    //
+                    // Min/max operate on the isolated field. Signed
+                    // comparisons flip the field's sign bit, held in t3, to
+                    // turn them into unsigned ones.
+                    let mut insts = Inst::atomic_narrow_setup(ty, addr, t0);
+                    insts.push(Inst::alu_rrr(AluOPRRR::SllD, t1, x, t0.to_reg()));
+                    if is_min_max {
+                        insts.push(Inst::alu_rrr(
+                            AluOPRRR::And,
+                            t1,
+                            t1.to_reg(),
+                            spilltmp_reg(),
+                        ));
+                    }
+                    if signed {
+                        insts.push(Inst::alu_shift(AluOPRRShift::SrliD, t3, spilltmp_reg(), 1));
+                        insts.push(Inst::alu_rrr(
+                            AluOPRRR::Xor,
+                            t3,
+                            t3.to_reg(),
+                            spilltmp_reg(),
+                        ));
+                        insts.push(Inst::alu_rrr(
+                            AluOPRRR::And,
+                            t3,
+                            t3.to_reg(),
+                            spilltmp_reg(),
+                        ));
+                        insts.push(Inst::alu_rrr(AluOPRRR::Xor, t1, t1.to_reg(), t3.to_reg()));
+                    }
+                    Inst::emit_all(&insts, sink, emit_info, state);
+
+                    sink.bind_label(loop_start, &mut state.ctrl_plane);
+                    sink.put4(enc_llsc(LL_W, dst, spilltmp_reg2()));
+                    let mut insts = SmallInstVec::new();
+                    if is_min_max {
+                        insts.push(Inst::alu_rrr(
+                            AluOPRRR::And,
+                            t2,
+                            dst.to_reg(),
+                            spilltmp_reg(),
+                        ));
+                        if signed {
+                            insts.push(Inst::alu_rrr(AluOPRRR::Xor, t2, t2.to_reg(), t3.to_reg()));
+                        }
+                        insts.extend(Inst::atomic_op_insts(
+                            op,
+                            t2,
+                            t2.to_reg(),
+                            t1.to_reg(),
+                            t0,
+                            false,
+                        ));
+                        if signed {
+                            insts.push(Inst::alu_rrr(AluOPRRR::Xor, t2, t2.to_reg(), t3.to_reg()));
+                        }
+                    } else {
+                        insts.extend(Inst::atomic_op_insts(
+                            op,
+                            t2,
+                            dst.to_reg(),
+                            t1.to_reg(),
+                            t0,
+                            false,
+                        ));
+                    }
+                    insts.extend(Inst::atomic_narrow_merge(t2, dst.to_reg()));
+                    Inst::emit_all(&insts, sink, emit_info, state);
+                    sink.put4(enc_llsc(SC_W, t2, spilltmp_reg2()));
+                }
+
+                Inst::CondBr {
+                    taken: CondBrTarget::Label(loop_start),
+                    not_taken: CondBrTarget::Fallthrough,
+                    kind: IntegerCompare {
+                        kind: IntCC::Equal,
+                        rs1: t2.to_reg(),
+                        rs2: zero_reg(),
+                    },
+                }
+                .emit(sink, emit_info, state);
+
+                if ty.bits() < 32 {
+                    // The min/max selection clobbered the shift amount.
+                    if is_min_max {
+                        Inst::emit_all(
+                            &Inst::atomic_narrow_shift(addr, t0),
+                            sink,
+                            emit_info,
+                            state,
+                        );
+                    }
+                    Inst::emit_all(
+                        &Inst::atomic_narrow_extract(ty, dst, t0.to_reg()),
+                        sink,
+                        emit_info,
+                        state,
+                    );
+                }
+
+                Inst::Fence.emit(sink, emit_info, state);
+            }
+            &Inst::AtomicCas {
+                ty,
+                dst,
+                addr,
+                e,
+                v,
+                t0,
+                t1,
+                t2,
+            } => {
+                let loop_start = sink.get_label();
+                let fail = sink.get_label();
+
+                Inst::Fence.emit(sink, emit_info, state);
+
+                if ty.bits() >= 32 {
+                    // loop:
+                    //     ll.{w,d}  dst, addr, 0
+                    //     bne       dst, t1, fail
+                    //     move      t2, v
+                    //     sc.{w,d}  t2, addr, 0
+                    //     beq       t2, zero, loop
+                    // fail:
+                    let (ll, sc) = if ty == I64 {
+                        (LL_D, SC_D)
+                    } else {
+                        (LL_W, SC_W)
+                    };
+                    // `ll.w` sign-extends, so compare against a sign-extended
+                    // expected value.
+                    if ty == I32 {
+                        Inst::alu_rri(AluOPRRI::AddiW, t1, e, 0)
+                    } else {
+                        Inst::gen_move(t1, e, I64)
+                    }
+                    .emit(sink, emit_info, state);
+
+                    sink.bind_label(loop_start, &mut state.ctrl_plane);
+                    sink.put4(enc_llsc(ll, dst, addr));
+                    Inst::CondBr {
+                        taken: CondBrTarget::Label(fail),
+                        not_taken: CondBrTarget::Fallthrough,
+                        kind: IntegerCompare {
+                            kind: IntCC::NotEqual,
+                            rs1: dst.to_reg(),
+                            rs2: t1.to_reg(),
+                        },
+                    }
+                    .emit(sink, emit_info, state);
+                    Inst::alu_rrr(AluOPRRR::Or, t2, v, zero_reg()).emit(sink, emit_info, state);
+                    sink.put4(enc_llsc(sc, t2, addr));
+                } else {
+                    // The narrow variant compares and replaces just the field
+                    // within the containing aligned word.
+                    let mut insts = Inst::atomic_narrow_setup(ty, addr, t0);
+                    insts.push(Inst::alu_rrr(AluOPRRR::SllD, t1, e, t0.to_reg()));
+                    insts.push(Inst::alu_rrr(
+                        AluOPRRR::And,
+                        t1,
+                        t1.to_reg(),
+                        spilltmp_reg(),
+                    ));
+                    Inst::emit_all(&insts, sink, emit_info, state);
+
+                    sink.bind_label(loop_start, &mut state.ctrl_plane);
+                    sink.put4(enc_llsc(LL_W, dst, spilltmp_reg2()));
+                    Inst::alu_rrr(AluOPRRR::And, t2, dst.to_reg(), spilltmp_reg())
+                        .emit(sink, emit_info, state);
+                    Inst::CondBr {
+                        taken: CondBrTarget::Label(fail),
+                        not_taken: CondBrTarget::Fallthrough,
+                        kind: IntegerCompare {
+                            kind: IntCC::NotEqual,
+                            rs1: t2.to_reg(),
+                            rs2: t1.to_reg(),
+                        },
+                    }
+                    .emit(sink, emit_info, state);
+                    let mut insts = SmallInstVec::new();
+                    insts.push(Inst::alu_rrr(AluOPRRR::SllD, t2, v, t0.to_reg()));
+                    insts.extend(Inst::atomic_narrow_merge(t2, dst.to_reg()));
+                    Inst::emit_all(&insts, sink, emit_info, state);
+                    sink.put4(enc_llsc(SC_W, t2, spilltmp_reg2()));
+                }
+
+                Inst::CondBr {
+                    taken: CondBrTarget::Label(loop_start),
+                    not_taken: CondBrTarget::Fallthrough,
+                    kind: IntegerCompare {
+                        kind: IntCC::Equal,
+                        rs1: t2.to_reg(),
+                        rs2: zero_reg(),
+                    },
+                }
+                .emit(sink, emit_info, state);
+
+                sink.bind_label(fail, &mut state.ctrl_plane);
+                if ty.bits() < 32 {
+                    Inst::emit_all(
+                        &Inst::atomic_narrow_extract(ty, dst, t0.to_reg()),
+                        sink,
+                        emit_info,
+                        state,
+                    );
+                }
+                Inst::Fence.emit(sink, emit_info, state);
+            }
+
+            &Inst::Select {
+                ref dst,
+                condition,
+                ref x,
+                ref y,
+            } => {
+                // The general form for this select is the following:
+                //
+                //     mv rd, x
+                //     b{cond} rcond, label_end
+                //     mv rd, y
+                // label_end:
+                //     ... etc
+                //
+                // If the destination register is the same as `y` we invert
+                // the condition to avoid the first move.
+                let label_end = sink.get_label();
+
+                let xregs = x.regs();
+                let yregs = y.regs();
+                let dstregs: Vec<Reg> = dst.regs().into_iter().map(|r| r.to_reg()).collect();
+                let condregs = condition.regs();
+
+                // We are going to write to the destination register before evaluating
+                // the condition, so we need to make sure that the destination register
+                // is not one of the condition registers.
+                debug_assert_ne!(dstregs, condregs);
+
+                let (uncond_move, cond_move, condition) = if yregs == dstregs {
+                    (yregs, xregs, condition.inverse())
+                } else {
+                    (xregs, yregs, condition)
+                };
+
+                for i in gen_moves(dst.regs(), uncond_move) {
+                    i.emit(sink, emit_info, state);
+                }
+
+                // If the condition passes we skip over the conditional move
+                Inst::CondBr {
+                    taken: CondBrTarget::Label(label_end),
+                    not_taken: CondBrTarget::Fallthrough,
+                    kind: condition,
+                }
+                .emit(sink, emit_info, state);
+
+                // Move the conditional value to the destination register.
+                for i in gen_moves(dst.regs(), cond_move) {
+                    i.emit(sink, emit_info, state);
+                }
+
+                sink.bind_label(label_end, &mut state.ctrl_plane);
+            }
+
+            &Inst::LoadExtName {
+                rd,
+                ref name,
+                offset,
+            } => {
+                if emit_info.shared_flag.is_pic() {
+                    // Load the address from the GOT:
+                    //
+                    //   pcalau12i rd, 0          # R_LARCH_GOT_PC_HI20 (symbol)
+                    //   ld.d      rd, rd, 0      # R_LARCH_GOT_PC_LO12 (symbol)
+                    sink.add_reloc(Reloc::LoongArchGotPcHi20, &**name, 0);
+                    sink.put4(enc_ri20(PCALAU12I, rd, 0));
+                    sink.add_reloc(Reloc::LoongArchGotPcLo12, &**name, 0);
+                    sink.put4(enc_rri12(LoadOP::LdD.opcode(), rd, rd.to_reg(), 0));
+
+                    if offset != 0 {
+                        Inst::emit_add_offset(rd, offset, sink, emit_info, state);
+                    }
+                } else {
+                    // In the non PIC sequence we relocate the absolute address into
+                    // a preallocated space, load it into a register and jump over it.
+                    //
+                    // Emit the following code:
+                    //   ld.d rd, label_data
+                    //   b label_end
+                    // label_data:
+                    //   <8 byte space>           # ABS8
+                    // label_end:
+
+                    let label_data = sink.get_label();
+                    let label_end = sink.get_label();
+
+                    // Load the value from a label
+                    Inst::Load {
+                        rd,
+                        op: LoadOP::LdD,
+                        flags: MemFlags::trusted(),
+                        from: AMode::Label(label_data),
+                    }
+                    .emit(sink, emit_info, state);
+
+                    // Jump over the data
+                    Inst::gen_jump(label_end).emit(sink, emit_info, state);
+
+                    sink.bind_label(label_data, &mut state.ctrl_plane);
+                    sink.add_reloc(Reloc::Abs8, name.as_ref(), offset);
+                    sink.put8(0);
+
+                    sink.bind_label(label_end, &mut state.ctrl_plane);
+                }
+            }
+
+            &Inst::LoadAddr { rd, mem } => {
+                let base = mem.get_base_register();
+                let offset = mem.get_offset_with_state(state);
+
+                match (mem, base, Imm12::maybe_from_i64(offset)) {
+                    (_, Some(rj), Some(imm12)) => {
+                        Inst::AluRRImm12 {
+                            alu_op: AluOPRRI::AddiD,
+                            rd,
+                            rj,
+                            imm12,
+                        }
+                        .emit(sink, emit_info, state);
+                    }
+                    (_, Some(rj), None) => {
+                        let mut insts =
+                            Inst::load_constant_u64(writable_spilltmp_reg2(), offset as u64);
+                        insts.push(Inst::alu_rrr(AluOPRRR::AddD, rd, rj, spilltmp_reg2()));
+                        Inst::emit_all(&insts, sink, emit_info, state);
+                    }
+                    (AMode::Label(label), None, _) => {
+                        sink.use_label_at_offset(sink.cur_offset(), label, LabelUse::PCAdd20);
+                        sink.put4(enc_ri20(PCADDI, rd, 0));
+                    }
+                    (amode, _, _) => {
+                        unimplemented!("LoadAddr: {:?}", amode);
+                    }
+                }
+            }
+
+            &Inst::TrapIf {
+                rj,
+                rk,
+                cc,
+                trap_code,
+            } => {
+                let label_end = sink.get_label();
+                let cond = IntegerCompare {
+                    kind: cc,
+                    rs1: rj,
+                    rs2: rk,
+                };
+
+                // Jump over the trap if we the condition is false.
+                Inst::CondBr {
+                    taken: CondBrTarget::Label(label_end),
+                    not_taken: CondBrTarget::Fallthrough,
+                    kind: cond.inverse(),
+                }
+                .emit(sink, emit_info, state);
+                Inst::Udf { trap_code }.emit(sink, emit_info, state);
+
+                sink.bind_label(label_end, &mut state.ctrl_plane);
+            }
+            &Inst::Udf { trap_code } => {
+                sink.add_trap(trap_code);
+                sink.put_data(Inst::TRAP_OPCODE);
+            }
+            &Inst::Unwind { ref inst } => {
+                sink.add_unwind(inst.clone());
+            }
+            &Inst::DummyUse { .. } => {
+                // This has already been handled by Inst::allocate.
+            }
+            &Inst::StackProbeLoop {
+                guard_size,
+                probe_count,
+                tmp: guard_size_tmp,
+            } => {
+                let step = writable_spilltmp_reg();
+                Inst::load_constant_u64(step, (guard_size as u64) * (probe_count as u64))
+                    .iter()
+                    .for_each(|i| i.emit(sink, emit_info, state));
+                Inst::load_constant_u64(guard_size_tmp, guard_size as u64)
+                    .iter()
+                    .for_each(|i| i.emit(sink, emit_info, state));
+
+                let loop_start = sink.get_label();
+                let label_done = sink.get_label();
+                sink.bind_label(loop_start, &mut state.ctrl_plane);
+                Inst::CondBr {
+                    taken: CondBrTarget::Label(label_done),
+                    not_taken: CondBrTarget::Fallthrough,
+                    kind: IntegerCompare {
+                        kind: IntCC::UnsignedLessThanOrEqual,
+                        rs1: step.to_reg(),
+                        rs2: guard_size_tmp.to_reg(),
+                    },
+                }
+                .emit(sink, emit_info, state);
+                // compute address.
+                Inst::alu_rrr(
+                    AluOPRRR::SubD,
+                    writable_spilltmp_reg2(),
+                    stack_reg(),
+                    step.to_reg(),
+                )
+                .emit(sink, emit_info, state);
+                Inst::Store {
+                    to: AMode::RegOffset(spilltmp_reg2(), 0),
+                    op: StoreOP::StB,
+                    flags: MemFlags::new(),
+                    src: zero_reg(),
+                }
+                .emit(sink, emit_info, state);
+                // reset step.
+                Inst::alu_rrr(AluOPRRR::SubD, step, step.to_reg(), guard_size_tmp.to_reg())
+                    .emit(sink, emit_info, state);
+                Inst::gen_jump(loop_start).emit(sink, emit_info, state);
+                sink.bind_label(label_done, &mut state.ctrl_plane);
+            }
+
+            &Inst::EmitIsland { needed_space } => {
+                if sink.island_needed(needed_space) {
+                    let jump_around_label = sink.get_label();
+                    Inst::gen_jump(jump_around_label).emit(sink, emit_info, state);
+                    sink.emit_island(needed_space + 4, &mut state.ctrl_plane);
+                    sink.bind_label(jump_around_label, &mut state.ctrl_plane);
+                }
+            }
+        }
+    }
+
+    /// Emit whatever is needed to address `amode` with a base register and a
+    /// 12-bit offset, which are returned.
+    fn emit_amode(
+        amode: AMode,
+        sink: &mut MachBuffer<Inst>,
+        emit_info: &EmitInfo,
+        state: &mut EmitState,
+    ) -> (Reg, Imm12) {
+        let base = amode.get_base_register();
+        let offset = amode.get_offset_with_state(state);
+
+        match (base, Imm12::maybe_from_i64(offset)) {
+            (Some(base), Some(imm12)) => (base, imm12),
+            // Labels, constants and large offsets compute the address into
+            // `spilltmp` first.
+            _ => {
+                Inst::LoadAddr {
+                    rd: writable_spilltmp_reg(),
+                    mem: amode,
+                }
+                .emit(sink, emit_info, state);
+                (spilltmp_reg(), Imm12::ZERO)
+            }
+        }
+    }
+
+    /// `rd = rd + offset`
+    fn emit_add_offset(
+        rd: Writable<Reg>,
+        offset: i64,
+        sink: &mut MachBuffer<Inst>,
+        emit_info: &EmitInfo,
+        state: &mut EmitState,
+    ) {
+        if let Some(imm12) = Imm12::maybe_from_i64(offset) {
+            Inst::AluRRImm12 {
+                alu_op: AluOPRRI::AddiD,
+                rd,
+                rj: rd.to_reg(),
+                imm12,
+            }
+            .emit(sink, emit_info, state);
+        } else {
+            let mut insts = Inst::load_constant_u64(writable_spilltmp_reg(), offset as u64);
+            insts.push(Inst::alu_rrr(
+                AluOPRRR::AddD,
+                rd,
+                rd.to_reg(),
+                spilltmp_reg(),
+            ));
+            Inst::emit_all(&insts, sink, emit_info, state);
+        }
+    }
+}
+
+fn emit_return_call_common_sequence<T>(
+    sink: &mut MachBuffer<Inst>,
+    emit_info: &EmitInfo,
+    state: &mut EmitState,
+    info: &ReturnCallInfo<T>,
+) {
+    // The return call sequence can potentially emit a lot of instructions, so
+    // lets emit an island here if we need it.
+    //
+    // It is difficult to calculate exactly how many instructions are going to be emitted, so
+    // we calculate it by emitting it into a disposable buffer, and then checking how many instructions
+    // were actually emitted.
+    let mut buffer = MachBuffer::new();
+    let mut fake_emit_state = state.clone();
+
+    return_call_emit_impl(&mut buffer, emit_info, &mut fake_emit_state, info);
+
+    // Finalize the buffer and get the number of bytes emitted.
+    let buffer = buffer.finish(&Default::default(), &mut Default::default());
+    let length = buffer.data().len() as u32;
+
+    // And now emit the island inline with this instruction.
+    if sink.island_needed(length) {
+        let jump_around_label = sink.get_label();
+        Inst::gen_jump(jump_around_label).emit(sink, emit_info, state);
+        sink.emit_island(length + 4, &mut state.ctrl_plane);
+        sink.bind_label(jump_around_label, &mut state.ctrl_plane);
+    }
+
+    // Now that we're done, emit the *actual* return sequence.
+    return_call_emit_impl(sink, emit_info, state, info);
+}
+
+/// This should not be called directly, Instead prefer to call [emit_return_call_common_sequence].
+fn return_call_emit_impl<T>(
+    sink: &mut MachBuffer<Inst>,
+    emit_info: &EmitInfo,
+    state: &mut EmitState,
+    info: &ReturnCallInfo<T>,
+) {
+    let sp_to_fp_offset = {
+        let frame_layout = state.frame_layout();
+        i64::from(
+            frame_layout.clobber_size
+                + frame_layout.fixed_frame_storage_size
+                + frame_layout.outgoing_args_size,
+        )
+    };
+
+    let mut clobber_offset = sp_to_fp_offset - 8;
+    for reg in state.frame_layout().clobbered_callee_saves.clone() {
+        let rreg = reg.to_reg();
+        let ty = match rreg.class() {
+            RegClass::Int => I64,
+            RegClass::Float => F64,
+            RegClass::Vector => unreachable!("no vector registers"),
+        };
+
+        Inst::gen_load(
+            reg.map(Reg::from),
+            AMode::SPOffset(clobber_offset),
+            ty,
+            MemFlags::trusted(),
+        )
+        .emit(sink, emit_info, state);
+
+        clobber_offset -= 8
+    }
+
+    // Restore the link register and frame pointer
+    let setup_area_size = i64::from(state.frame_layout().setup_area_size);
+    if setup_area_size > 0 {
+        Inst::gen_load(
+            writable_link_reg(),
+            AMode::SPOffset(sp_to_fp_offset + 8),
+            I64,
+            MemFlags::trusted(),
+        )
+        .emit(sink, emit_info, state);
+
+        Inst::gen_load(
+            writable_fp_reg(),
+            AMode::SPOffset(sp_to_fp_offset),
+            I64,
+            MemFlags::trusted(),
+        )
+        .emit(sink, emit_info, state);
+    }
+
+    // If we over-allocated the incoming args area in the prologue, resize down to what the callee
+    // is expecting.
+    let incoming_args_diff =
+        i64::from(state.frame_layout().tail_args_size - info.new_stack_arg_size);
+
+    // Increment SP all at once
+    let sp_increment = sp_to_fp_offset + setup_area_size + incoming_args_diff;
+    if sp_increment > 0 {
+        for inst in LoongArch64MachineDeps::gen_sp_reg_adjust(i32::try_from(sp_increment).unwrap())
+        {
+            inst.emit(sink, emit_info, state);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use crate::ir::{AtomicRmwOp, TrapCode};
+use crate::isa::loongarch64::inst::*;
+use crate::isa::loongarch64::lower::isle::generated_code::{
+    AtomicOP, FpuConvOP, FpuOPRRR, FpuOPRRRR,
+};
+use std::borrow::Cow;
+
+fn a1() -> Reg {
+    x_reg(5)
+}
+
+fn a2() -> Reg {
+    x_reg(6)
+}
+
+fn fa0() -> Reg {
+    f_reg(0)
+}
+
+fn fa1() -> Reg {
+    f_reg(1)
+}
+
+fn fa2() -> Reg {
+    f_reg(2)
+}
+
+#[test]
+fn test_loongarch64_binemit() {
+    struct TestUnit {
+        inst: Inst,
+        assembly: &'static str,
+        code: TestEncoding,
+    }
+
+    struct TestEncoding(Cow<'static, str>);
+
+    impl From<&'static str> for TestEncoding {
+        fn from(value: &'static str) -> Self {
+            Self(value.into())
+        }
+    }
+
+    impl From<u32> for TestEncoding {
+        fn from(value: u32) -> Self {
+            let value = value.swap_bytes();
+            let value = format!("{value:08X}");
+            Self(value.into())
+        }
+    }
+
+    impl TestUnit {
+        fn new(inst: Inst, assembly: &'static str, code: impl Into<TestEncoding>) -> Self {
+            let code = code.into();
+            Self {
+                inst,
+                assembly,
+                code,
+            }
+        }
+    }
+
+    let mut insns = Vec::<TestUnit>::with_capacity(100);
+
+    insns.push(TestUnit::new(Inst::Ret {}, "ret", 0x4c000020));
+    insns.push(TestUnit::new(Inst::Nop4, "##fixed 4-size nop", 0x03400000));
+    insns.push(TestUnit::new(Inst::Fence, "dbar 0", 0x38720000));
+    insns.push(TestUnit::new(Inst::Break, "break 0", 0x002a0000));
+
+    insns.push(TestUnit::new(
+        Inst::Mov {
+            rd: writable_a0(),
+            rm: a1(),
+            ty: I64,
+        },
+        "move a0,a1",
+        0x001500a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Mov {
+            rd: Writable::from_reg(fa0()),
+            rm: fa1(),
+            ty: F64,
+        },
+        "fmov.d fa0,fa1",
+        0x01149820,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRR {
+            alu_op: AluOPRRR::AddD,
+            rd: writable_a0(),
+            rj: a1(),
+            rk: a2(),
+        },
+        "add.d a0,a1,a2",
+        0x001098a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Masknez,
+            rd: writable_a0(),
+            rj: a1(),
+            rk: a2(),
+        },
+        "masknez a0,a1,a2",
+        0x001398a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRR {
+            alu_op: AluOPRRR::ModDu,
+            rd: writable_a0(),
+            rj: a1(),
+            rk: a2(),
+        },
+        "mod.du a0,a1,a2",
+        0x002398a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::AddiD,
+            rd: writable_stack_reg(),
+            rj: stack_reg(),
+            imm12: Imm12::from_i16(-16),
+        },
+        "addi.d sp,sp,-16",
+        0x02ffc063,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::AddiW,
+            rd: writable_a0(),
+            rj: a1(),
+            imm12: Imm12::ZERO,
+        },
+        "sext.w a0,a1",
+        0x028000a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRUImm12 {
+            alu_op: AluOPRRUI::Ori,
+            rd: writable_a0(),
+            rj: a1(),
+            imm12: UImm12::maybe_from_u64(0xfff).unwrap(),
+        },
+        "ori a0,a1,4095",
+        0x03bffca4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRShift {
+            alu_op: AluOPRRShift::SlliD,
+            rd: writable_a0(),
+            rj: a1(),
+            shamt: UImm6::maybe_from_u64(3).unwrap(),
+        },
+        "slli.d a0,a1,3",
+        0x00410ca4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRRShift {
+            alu_op: AluOPRRShift::SraiW,
+            rd: writable_a0(),
+            rj: a1(),
+            shamt: UImm6::maybe_from_u64(31).unwrap(),
+        },
+        "srai.w a0,a1,31",
+        0x0048fca4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AluRR {
+            alu_op: AluOPRR::ExtWB,
+            rd: writable_a0(),
+            rj: a1(),
+        },
+        "ext.w.b a0,a1",
+        0x00005ca4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Bstrpick {
+            rd: writable_a0(),
+            rj: a1(),
+            msb: 31,
+            lsb: 0,
+        },
+        "bstrpick.d a0,a1,31,0",
+        0x00df00a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Alsl {
+            rd: writable_a0(),
+            rj: a1(),
+            rk: a2(),
+            shamt: 3,
+        },
+        "alsl.d a0,a1,a2,3",
+        0x002d18a4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::LoadConst64 {
+            rd: writable_a0(),
+            imm: 0x12345678,
+        },
+        "li.d a0,0x12345678",
+        "A468241484E09903",
+    ));
+    insns.push(TestUnit::new(
+        Inst::LoadConst64 {
+            rd: writable_a0(),
+            imm: 0x1234_0000_0000_0000,
+        },
+        "li.d a0,0x1234000000000000",
+        "0400800304008016848C0403",
+    ));
+    insns.push(TestUnit::new(
+        Inst::LoadConst64 {
+            rd: writable_a0(),
+            imm: 0x1230_0000_0000_0000,
+        },
+        "li.d a0,0x1230000000000000",
+        0x03048c04,
+    ));
+    insns.push(TestUnit::new(
+        Inst::LoadConst64 {
+            rd: writable_a0(),
+            imm: 0xffff_ffff_ffff_f000,
+        },
+        "li.d a0,0xfffffffffffff000",
+        0x15ffffe4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Load {
+            rd: writable_link_reg(),
+            op: LoadOP::LdD,
+            flags: MemFlags::new(),
+            from: AMode::SPOffset(8),
+        },
+        "ld.d ra,8(sp)",
+        0x28c02061,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Load {
+            rd: writable_a0(),
+            op: LoadOP::LdBu,
+            flags: MemFlags::new(),
+            from: AMode::RegOffset(a1(), -1),
+        },
+        "ld.bu a0,-1(a1)",
+        0x2a3ffca4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Store {
+            to: AMode::SPOffset(8),
+            op: StoreOP::StD,
+            flags: MemFlags::new(),
+            src: link_reg(),
+        },
+        "st.d ra,8(sp)",
+        0x29c02061,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Store {
+            to: AMode::RegOffset(a1(), 0x1000),
+            op: StoreOP::FstD,
+            flags: MemFlags::new(),
+            src: fa0(),
+        },
+        "fst.d fa0,4096(a1)",
+        // lu12i.w t7, 1; add.d t8, a1, t7; fst.d fa0, t8, 0
+        "33000014B4CC10008002C02B",
+    ));
+    insns.push(TestUnit::new(
+        Inst::FpuRRR {
+            fpu_op: FpuOPRRR::Fadd,
+            width: FpuWidth::D,
+            rd: Writable::from_reg(fa0()),
+            rj: fa1(),
+            rk: fa2(),
+        },
+        "fadd.d fa0,fa1,fa2",
+        0x01010820,
+    ));
+    insns.push(TestUnit::new(
+        Inst::FpuRRRR {
+            fpu_op: FpuOPRRRR::Fmadd,
+            width: FpuWidth::S,
+            rd: Writable::from_reg(fa0()),
+            rj: fa1(),
+            rk: fa2(),
+            ra: fa0(),
+        },
+        "fmadd.s fa0,fa1,fa2,fa0",
+        0x08100820,
+    ));
+    insns.push(TestUnit::new(
+        Inst::FpuConv {
+            op: FpuConvOP::FtintrzLD,
+            rd: Writable::from_reg(fa0()),
+            rj: fa1(),
+        },
+        "ftintrz.l.d fa0,fa1",
+        0x011aa820,
+    ));
+    insns.push(TestUnit::new(
+        Inst::FpuConv {
+            op: FpuConvOP::MovfrgrD,
+            rd: writable_a0(),
+            rj: fa1(),
+        },
+        "movfr2gr.d a0,fa1",
+        0x0114b824,
+    ));
+    insns.push(TestUnit::new(
+        Inst::AtomicRmw {
+            op: AtomicOP::AmswapD,
+            rd: writable_a0(),
+            addr: a2(),
+            src: a1(),
+        },
+        "amswap_db.d a0,a1,a2",
+        0x386994c4,
+    ));
+    insns.push(TestUnit::new(
+        Inst::Udf {
+            trap_code: TrapCode::STACK_OVERFLOW,
+        },
+        "udf##trap_code=stk_ovf",
+        0x38600400,
+    ));
+
+    let (flags, isa_flags) = make_test_flags();
+    let emit_info = EmitInfo::new(flags, isa_flags);
+
+    for unit in insns.iter() {
+        println!("LoongArch64: {:?}, {}", unit.inst, unit.assembly);
+        // Check the printed text is as expected.
+        let actual_printing = unit.inst.print_with_state(&mut EmitState::default());
+        assert_eq!(unit.assembly, actual_printing);
+        let mut buffer = MachBuffer::new();
+        unit.inst
+            .emit(&mut buffer, &emit_info, &mut Default::default());
+        let buffer = buffer.finish(&Default::default(), &mut Default::default());
+        let actual_encoding = buffer.stringify_code_bytes();
+
+        assert_eq!(actual_encoding, unit.code.0);
+    }
+}
+
+fn make_test_flags() -> (settings::Flags, super::super::loongarch_settings::Flags) {
+    let b = settings::builder();
+    let flags = settings::Flags::new(b.clone());
+    let b2 = super::super::loongarch_settings::builder();
+    let isa_flags = super::super::loongarch_settings::Flags::new(&flags, &b2);
+    (flags, isa_flags)
+}
+
+#[test]
+fn loongarch64_worst_case_instruction_size() {
+    let (flags, isa_flags) = make_test_flags();
+    let emit_info = EmitInfo::new(flags, isa_flags);
+
+    // These are all candidate instructions with potential to generate a lot of bytes.
+    let mut candidates: Vec<MInst> = vec![];
+
+    candidates.push(Inst::LoadConst64 {
+        rd: writable_a0(),
+        imm: 0x1234_5678_9abc_def0,
+    });
+
+    for ty in [I8, I16, I32, I64] {
+        candidates.push(Inst::AtomicCas {
+            ty,
+            dst: writable_a0(),
+            addr: a1(),
+            e: a2(),
+            v: a2(),
+            t0: writable_a0(),
+            t1: writable_a0(),
+            t2: writable_a0(),
+        });
+
+        candidates.extend(AtomicRmwOp::all().iter().map(|op| Inst::AtomicRmwLoop {
+            op: *op,
+            ty,
+            dst: writable_a0(),
+            addr: a1(),
+            x: a2(),
+            t0: writable_a0(),
+            t1: writable_a0(),
+            t2: writable_a0(),
+            t3: writable_a0(),
+        }));
+    }
+
+    // Return Call Indirect and BrTable are the largest instructions possible. However they
+    // emit their own island, so we don't account them here.
+
+    let mut max: (u32, MInst) = (0, Inst::Nop0);
+    for i in candidates {
+        let mut buffer = MachBuffer::new();
+        let mut emit_state = Default::default();
+        i.emit(&mut buffer, &emit_info, &mut emit_state);
+        let buffer = buffer.finish(&Default::default(), &mut Default::default());
+        let length = buffer.data().len() as u32;
+        if length > max.0 {
+            let length = buffer.data().len() as u32;
+            max = (length, i.clone());
+        }
+        println!("insn:{i:?}  length: {length}");
+    }
+    println!("calculate max size is {} , inst is {:?}", max.0, max.1);
+    assert!(max.0 <= Inst::worst_case_size());
+}
@@ -0,0 +1,137 @@
+//! LoongArch64 ISA definitions: immediate constants.
+
+use std::fmt::{Display, Formatter, Result};
+
+/// A signed 12-bit immediate, as used by `addi`, `slti` and the
+/// base+offset load and store instructions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Imm12 {
+    /// 16-bit container where the low 12 bits are the data payload.
+    ///
+    /// Acquiring the underlying value requires sign-extending the 12th bit.
+    bits: u16,
+}
+
+impl Imm12 {
+    pub(crate) const ZERO: Self = Self { bits: 0 };
+
+    pub fn maybe_from_u64(val: u64) -> Option<Imm12> {
+        Self::maybe_from_i64(val as i64)
+    }
+
+    pub fn maybe_from_i64(val: i64) -> Option<Imm12> {
+        if val >= -2048 && val <= 2047 {
+            Some(Imm12 {
+                bits: val as u16 & 0xfff,
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn from_i16(bits: i16) -> Self {
+        assert!(bits >= -2048 && bits <= 2047);
+        Self {
+            bits: (bits & 0xfff) as u16,
+        }
+    }
+
+    #[inline]
+    pub fn as_i16(self) -> i16 {
+        (self.bits << 4) as i16 >> 4
+    }
+
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.bits.into()
+    }
+}
+
+impl From<Imm12> for i64 {
+    fn from(imm12: Imm12) -> i64 {
+        imm12.as_i16().into()
+    }
+}
+
+impl Display for Imm12 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.as_i16())
+    }
+}
+
+/// An unsigned 12-bit immediate, as used by `andi`, `ori` and `xori`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UImm12 {
+    bits: u16,
+}
+
+impl UImm12 {
+    pub fn maybe_from_u64(val: u64) -> Option<UImm12> {
+        if val <= 0xfff {
+            Some(UImm12 { bits: val as u16 })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.bits.into()
+    }
+}
+
+impl Display for UImm12 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.bits)
+    }
+}
+
+/// An unsigned shift amount, for the `*i.w` (5-bit) and `*i.d` (6-bit) shift
+/// and rotate instructions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UImm6 {
+    bits: u8,
+}
+
+impl UImm6 {
+    pub fn maybe_from_u64(val: u64) -> Option<UImm6> {
+        if val < 64 {
+            Some(UImm6 { bits: val as u8 })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.bits.into()
+    }
+}
+
+impl Display for UImm6 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.bits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn imm12_round_trip() {
+        for v in [-2048i64, -1, 0, 1, 2047] {
+            let imm = Imm12::maybe_from_i64(v).unwrap();
+            assert_eq!(i64::from(imm), v);
+        }
+        assert!(Imm12::maybe_from_i64(2048).is_none());
+        assert!(Imm12::maybe_from_i64(-2049).is_none());
+    }
+
+    #[test]
+    fn uimm12_range() {
+        assert_eq!(UImm12::maybe_from_u64(0xfff).unwrap().bits(), 0xfff);
+        assert!(UImm12::maybe_from_u64(0x1000).is_none());
+    }
+}
@@ -0,0 +1,1162 @@
+//! This module defines loongarch64-specific machine instruction types.
+
+use crate::binemit::{Addend, CodeOffset, Reloc};
+pub use crate::ir::condcodes::IntCC;
+use crate::ir::types::{F32, F64, I8, I16, I32, I64, I128};
+
+pub use crate::ir::{ExternalName, MemFlags, Type};
+use crate::isa::FunctionAlignment;
+use crate::machinst::*;
+use crate::{CodegenError, CodegenResult, settings};
+
+use alloc::vec::Vec;
+use regalloc2::RegClass;
+use std::fmt::Write;
+use std::string::{String, ToString};
+
+pub mod regs;
+pub use self::regs::*;
+pub mod imms;
+pub use self::imms::*;
+pub mod args;
+pub use self::args::*;
+pub mod emit;
+pub use self::emit::*;
+pub mod unwind;
+
+use crate::isa::loongarch64::abi::LoongArch64MachineDeps;
+
+#[cfg(test)]
+mod emit_tests;
+
+use std::fmt::{Display, Formatter};
+
+//=============================================================================
+// Instructions (top level): definition
+
+use crate::isa::loongarch64::lower::isle::generated_code::MInst;
+pub use crate::isa::loongarch64::lower::isle::generated_code::{
+    AluOPRR, AluOPRRI, AluOPRRR, AluOPRRShift, AluOPRRUI, FpuOPRR, FpuWidth, LoadOP, MInst as Inst,
+    StoreOP,
+};
+
+/// Additional information for `return_call[_ind]` instructions, left out of
+/// line to lower the size of the `Inst` enum.
+#[derive(Clone, Debug)]
+pub struct ReturnCallInfo<T> {
+    pub dest: T,
+    pub uses: CallArgList,
+    pub new_stack_arg_size: u32,
+}
+
+/// A conditional branch target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CondBrTarget {
+    /// An unresolved reference to a Label, as passed into
+    /// `lower_branch_group()`.
+    Label(MachLabel),
+    /// No jump; fall through to the next instruction.
+    Fallthrough,
+}
+
+impl CondBrTarget {
+    /// Return the target's label, if it is a label-based target.
+    pub(crate) fn as_label(self) -> Option<MachLabel> {
+        match self {
+            CondBrTarget::Label(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_fallthrough(&self) -> bool {
+        self == &CondBrTarget::Fallthrough
+    }
+}
+
+impl Display for CondBrTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CondBrTarget::Label(l) => write!(f, "{}", l.to_string()),
+            CondBrTarget::Fallthrough => write!(f, "0"),
+        }
+    }
+}
+
+/// `pcaddu18i rd, si20`: `rd = pc + (si20 << 18)`.
+pub(crate) fn enc_pcaddu18i(rd: Writable<Reg>, si20: u32) -> u32 {
+    0x1E00_0000 | (si20 & 0xF_FFFF) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+/// `jirl rd, rj, offs16`: jump to `rj + (offs16 << 2)`, writing the return
+/// address to `rd`.
+pub(crate) fn enc_jirl(rd: Writable<Reg>, rj: Reg, offs16: u32) -> u32 {
+    0x4C00_0000 | (offs16 & 0xFFFF) << 10 | reg_to_gpr_num(rj) << 5 | reg_to_gpr_num(rd.to_reg())
+}
+
+/// rd and src must have the same length.
+pub(crate) fn gen_moves(rd: &[Writable<Reg>], src: &[Reg]) -> SmallInstVec<Inst> {
+    assert!(rd.len() == src.len());
+    assert!(rd.len() > 0);
+    let mut insts = SmallInstVec::new();
+    for (dst, src) in rd.iter().zip(src.iter()) {
+        let ty = Inst::canonical_type_for_rc(dst.to_reg().class());
+        insts.push(Inst::gen_move(*dst, *src, ty));
+    }
+    insts
+}
+
+impl Inst {
+    /// All LoongArch instructions are 4 bytes long.
+    const INSTRUCTION_SIZE: i32 = 4;
+
+    pub fn load_constant_u64(rd: Writable<Reg>, value: u64) -> SmallInstVec<Inst> {
+        let mut insts = SmallInstVec::new();
+        insts.push(Inst::LoadConst64 { rd, imm: value });
+        insts
+    }
+
+    /// Generic constructor for a load (zero-extending where appropriate).
+    pub fn gen_load(into_reg: Writable<Reg>, mem: AMode, ty: Type, flags: MemFlags) -> Inst {
+        Inst::Load {
+            rd: into_reg,
+            op: LoadOP::from_type(ty),
+            from: mem,
+            flags,
+        }
+    }
+
+    /// Generic constructor for a store.
+    pub fn gen_store(mem: AMode, from_reg: Reg, ty: Type, flags: MemFlags) -> Inst {
+        Inst::Store {
+            src: from_reg,
+            op: StoreOP::from_type(ty),
+            to: mem,
+            flags,
+        }
+    }
+}
+
+//=============================================================================
+
+fn loongarch64_get_operands(inst: &mut Inst, collector: &mut impl OperandVisitor) {
+    match inst {
+        Inst::Nop0 | Inst::Nop4 => {}
+        Inst::BrTable {
+            index, tmp1, tmp2, ..
+        } => {
+            collector.reg_use(index);
+            collector.reg_early_def(tmp1);
+            collector.reg_early_def(tmp2);
+        }
+        Inst::LoadConst64 { rd, .. } => collector.reg_def(rd),
+        Inst::AluRRR { rd, rj, rk, .. } => {
+            collector.reg_use(rj);
+            collector.reg_use(rk);
+            collector.reg_def(rd);
+        }
+        Inst::AluRRImm12 { rd, rj, .. }
+        | Inst::AluRRUImm12 { rd, rj, .. }
+        | Inst::AluRRShift { rd, rj, .. }
+        | Inst::AluRR { rd, rj, .. }
+        | Inst::Bstrpick { rd, rj, .. } => {
+            collector.reg_use(rj);
+            collector.reg_def(rd);
+        }
+        Inst::Alsl { rd, rj, rk, .. } => {
+            collector.reg_use(rj);
+            collector.reg_use(rk);
+            collector.reg_def(rd);
+        }
+        Inst::FpuRR { rd, rj, .. } | Inst::FpuConv { rd, rj, .. } => {
+            collector.reg_use(rj);
+            collector.reg_def(rd);
+        }
+        Inst::FpuRRR { rd, rj, rk, .. } | Inst::FpuCmp { rd, rj, rk, .. } => {
+            collector.reg_use(rj);
+            collector.reg_use(rk);
+            collector.reg_def(rd);
+        }
+        Inst::FpuRRRR { rd, rj, rk, ra, .. } => {
+            collector.reg_use(rj);
+            collector.reg_use(rk);
+            collector.reg_use(ra);
+            collector.reg_def(rd);
+        }
+        Inst::FpuSel { rd, c, x, y } => {
+            collector.reg_use(c);
+            collector.reg_use(x);
+            collector.reg_use(y);
+            collector.reg_def(rd);
+        }
+        Inst::Load { rd, from, .. } => {
+            from.get_operands(collector);
+            collector.reg_def(rd);
+        }
+        Inst::Store { to, src, .. } => {
+            to.get_operands(collector);
+            collector.reg_use(src);
+        }
+
+        Inst::Args { args } => {
+            for ArgPair { vreg, preg } in args {
+                collector.reg_fixed_def(vreg, *preg);
+            }
+        }
+        Inst::Rets { rets } => {
+            for RetPair { vreg, preg } in rets {
+                collector.reg_fixed_use(vreg, *preg);
+            }
+        }
+        Inst::Ret { .. } => {}
+
+        Inst::Extend { rd, rn, .. } => {
+            collector.reg_use(rn);
+            collector.reg_def(rd);
+        }
+        Inst::Call { info, .. } => {
+            let CallInfo { uses, defs, .. } = &mut **info;
+            for CallArgPair { vreg, preg } in uses {
+                collector.reg_fixed_use(vreg, *preg);
+            }
+            for CallRetPair { vreg, location } in defs {
+                match location {
+                    RetLocation::Reg(preg, ..) => collector.reg_fixed_def(vreg, *preg),
+                    RetLocation::Stack(..) => collector.any_def(vreg),
+                }
+            }
+            collector.reg_clobbers(info.clobbers);
+        }
+        Inst::CallInd { info } => {
+            let CallInfo {
+                dest, uses, defs, ..
+            } = &mut **info;
+            collector.reg_use(dest);
+            for CallArgPair { vreg, preg } in uses {
+                collector.reg_fixed_use(vreg, *preg);
+            }
+            for CallRetPair { vreg, location } in defs {
+                match location {
+                    RetLocation::Reg(preg, ..) => collector.reg_fixed_def(vreg, *preg),
+                    RetLocation::Stack(..) => collector.any_def(vreg),
+                }
+            }
+            collector.reg_clobbers(info.clobbers);
+        }
+        Inst::ReturnCall { info } => {
+            for CallArgPair { vreg, preg } in &mut info.uses {
+                collector.reg_fixed_use(vreg, *preg);
+            }
+        }
+        Inst::ReturnCallInd { info } => {
+            // TODO(https://github.com/bytecodealliance/regalloc2/issues/145):
+            // This shouldn't be a fixed register constraint. `t0` is neither
+            // an argument register nor restored by the epilogue.
+            collector.reg_fixed_use(&mut info.dest, x_reg(12));
+
+            for CallArgPair { vreg, preg } in &mut info.uses {
+                collector.reg_fixed_use(vreg, *preg);
+            }
+        }
+        Inst::Jump { .. } => {}
+        Inst::CondBr {
+            kind: IntegerCompare { rs1, rs2, .. },
+            ..
+        } => {
+            collector.reg_use(rs1);
+            collector.reg_use(rs2);
+        }
+        Inst::LoadExtName { rd, .. } => {
+            collector.reg_def(rd);
+        }
+        Inst::LoadAddr { rd, mem } => {
+            mem.get_operands(collector);
+            collector.reg_early_def(rd);
+        }
+
+        Inst::Mov { rd, rm, .. } => {
+            collector.reg_use(rm);
+            collector.reg_def(rd);
+        }
+        Inst::MovFromPReg { rd, rm } => {
+            debug_assert!([px_reg(3), px_reg(22)].contains(rm));
+            collector.reg_def(rd);
+        }
+        Inst::Fence | Inst::Break | Inst::Udf { .. } => {}
+        Inst::AtomicRmw { rd, addr, src, .. } => {
+            // The `am*` instructions raise an exception if the destination
+            // aliases either source.
+            collector.reg_use(addr);
+            collector.reg_use(src);
+            collector.reg_early_def(rd);
+        }
+        Inst::AtomicRmwLoop {
+            dst,
+            addr,
+            x,
+            t0,
+            t1,
+            t2,
+            t3,
+            ..
+        } => {
+            collector.reg_use(addr);
+            collector.reg_use(x);
+            collector.reg_early_def(t0);
+            collector.reg_early_def(t1);
+            collector.reg_early_def(t2);
+            collector.reg_early_def(t3);
+            collector.reg_early_def(dst);
+        }
+        Inst::AtomicCas {
+            dst,
+            addr,
+            e,
+            v,
+            t0,
+            t1,
+            t2,
+            ..
+        } => {
+            collector.reg_use(addr);
+            collector.reg_use(e);
+            collector.reg_use(v);
+            collector.reg_early_def(t0);
+            collector.reg_early_def(t1);
+            collector.reg_early_def(t2);
+            collector.reg_early_def(dst);
+        }
+        Inst::Select {
+            dst,
+            condition: IntegerCompare { rs1, rs2, .. },
+            x,
+            y,
+            ..
+        } => {
+            // Mark the condition registers as late use so that they don't overlap with the destination
+            // register. We may potentially write to the destination register before evaluating the
+            // condition.
+            collector.reg_late_use(rs1);
+            collector.reg_late_use(rs2);
+
+            for reg in x.regs_mut() {
+                collector.reg_use(reg);
+            }
+            for reg in y.regs_mut() {
+                collector.reg_use(reg);
+            }
+
+            // If there's more than one destination register then use
+            // `reg_early_def` to prevent destination registers from overlapping
+            // with any operands. See the riscv64 backend for the details.
+            match dst.regs_mut() {
+                [reg] => collector.reg_def(reg),
+                regs => {
+                    for d in regs {
+                        collector.reg_early_def(d);
+                    }
+                }
+            }
+        }
+        Inst::TrapIf { rj, rk, .. } => {
+            collector.reg_use(rj);
+            collector.reg_use(rk);
+        }
+        Inst::Unwind { .. } => {}
+        Inst::DummyUse { reg } => {
+            collector.reg_use(reg);
+        }
+        Inst::StackProbeLoop { tmp, .. } => {
+            collector.reg_early_def(tmp);
+        }
+        Inst::EmitIsland { .. } => {}
+    }
+}
+
+impl MachInst for Inst {
+    type LabelUse = LabelUse;
+    type ABIMachineSpec = LoongArch64MachineDeps;
+
+    // `amswap.w $zero, $ra, $zero`: AM* instructions whose destination
+    // aliases a source are invalid and raise an instruction non-existent
+    // exception (SIGILL).
+    const TRAP_OPCODE: &'static [u8] = &0x3860_0400u32.to_le_bytes();
+
+    fn gen_dummy_use(reg: Reg) -> Self {
+        Inst::DummyUse { reg }
+    }
+
+    fn canonical_type_for_rc(rc: RegClass) -> Type {
+        match rc {
+            regalloc2::RegClass::Int => I64,
+            regalloc2::RegClass::Float => F64,
+            regalloc2::RegClass::Vector => unreachable!(),
+        }
+    }
+
+    fn is_safepoint(&self) -> bool {
+        match self {
+            Inst::Call { .. } | Inst::CallInd { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn get_operands(&mut self, collector: &mut impl OperandVisitor) {
+        loongarch64_get_operands(self, collector);
+    }
+
+    fn is_move(&self) -> Option<(Writable<Reg>, Reg)> {
+        match self {
+            Inst::Mov { rd, rm, .. } => Some((*rd, *rm)),
+            _ => None,
+        }
+    }
+
+    fn is_included_in_clobbers(&self) -> bool {
+        match self {
+            &Inst::Args { .. } => false,
+            _ => true,
+        }
+    }
+
+    fn is_trap(&self) -> bool {
+        match self {
+            Self::Udf { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn is_args(&self) -> bool {
+        match self {
+            Self::Args { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn is_term(&self) -> MachTerminator {
+        match self {
+            &Inst::Jump { .. } => MachTerminator::Branch,
+            &Inst::CondBr { .. } => MachTerminator::Branch,
+            &Inst::Rets { .. } => MachTerminator::Ret,
+            &Inst::BrTable { .. } => MachTerminator::Branch,
+            &Inst::ReturnCall { .. } | &Inst::ReturnCallInd { .. } => MachTerminator::RetCall,
+            &Inst::Call { ref info } if info.try_call_info.is_some() => MachTerminator::Branch,
+            &Inst::CallInd { ref info } if info.try_call_info.is_some() => MachTerminator::Branch,
+            _ => MachTerminator::None,
+        }
+    }
+
+    fn is_mem_access(&self) -> bool {
+        match self {
+            &Inst::Load { .. }
+            | &Inst::Store { .. }
+            | &Inst::AtomicRmw { .. }
+            | &Inst::AtomicRmwLoop { .. }
+            | &Inst::AtomicCas { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn gen_move(to_reg: Writable<Reg>, from_reg: Reg, ty: Type) -> Inst {
+        Inst::Mov {
+            rd: to_reg,
+            rm: from_reg,
+            ty,
+        }
+    }
+
+    fn gen_nop(preferred_size: usize) -> Inst {
+        if preferred_size == 0 {
+            return Inst::Nop0;
+        }
+        // We can't give a NOP (or any insn) < 4 bytes.
+        assert!(preferred_size >= 4);
+        Inst::Nop4
+    }
+
+    fn rc_for_type(ty: Type) -> CodegenResult<(&'static [RegClass], &'static [Type])> {
+        match ty {
+            I8 => Ok((&[RegClass::Int], &[I8])),
+            I16 => Ok((&[RegClass::Int], &[I16])),
+            I32 => Ok((&[RegClass::Int], &[I32])),
+            I64 => Ok((&[RegClass::Int], &[I64])),
+            F32 => Ok((&[RegClass::Float], &[F32])),
+            F64 => Ok((&[RegClass::Float], &[F64])),
+            I128 => Ok((&[RegClass::Int, RegClass::Int], &[I64, I64])),
+            _ => Err(CodegenError::Unsupported(format!(
+                "Unexpected SSA-value type: {ty}"
+            ))),
+        }
+    }
+
+    fn gen_jump(target: MachLabel) -> Inst {
+        Inst::Jump { label: target }
+    }
+
+    fn worst_case_size() -> CodeOffset {
+        // Our worst case size is determined by the loongarch64_worst_case_instruction_size test
+        128
+    }
+
+    fn ref_type_regclass(_settings: &settings::Flags) -> RegClass {
+        RegClass::Int
+    }
+
+    fn function_alignment() -> FunctionAlignment {
+        FunctionAlignment {
+            minimum: 4,
+            preferred: 4,
+        }
+    }
+}
+
+//=============================================================================
+// Pretty-printing of instructions.
+pub fn reg_name(reg: Reg) -> String {
+    match reg.to_real_reg() {
+        Some(real) => match real.class() {
+            RegClass::Int => match real.hw_enc() {
+                0 => "zero".into(),
+                1 => "ra".into(),
+                2 => "tp".into(),
+                3 => "sp".into(),
+                4..=11 => format!("a{}", real.hw_enc() - 4),
+                12..=20 => format!("t{}", real.hw_enc() - 12),
+                21 => "r21".into(),
+                22 => "fp".into(),
+                23..=31 => format!("s{}", real.hw_enc() - 23),
+                _ => unreachable!(),
+            },
+            RegClass::Float => match real.hw_enc() {
+                0..=7 => format!("fa{}", real.hw_enc()),
+                8..=23 => format!("ft{}", real.hw_enc() - 8),
+                24..=31 => format!("fs{}", real.hw_enc() - 24),
+                _ => unreachable!(),
+            },
+            RegClass::Vector => unreachable!(),
+        },
+        None => {
+            format!("{reg:?}")
+        }
+    }
+}
+
+fn pretty_print_try_call(info: &TryCallInfo) -> String {
+    let dests = info
+        .exception_dests
+        .iter()
+        .map(|(tag, label)| format!("{tag:?}: {label:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("; b {:?}; catch [{dests}]", info.continuation)
+}
+
+impl Inst {
+    fn print_with_state(&self, _state: &mut EmitState) -> String {
+        let format_reg = |reg: Reg| -> String { reg_name(reg) };
+
+        let format_regs = |regs: &[Reg]| -> String {
+            let mut x = if regs.len() > 1 {
+                String::from("[")
+            } else {
+                String::default()
+            };
+            regs.iter().for_each(|i| {
+                x.push_str(format_reg(*i).as_str());
+                if *i != *regs.last().unwrap() {
+                    x.push_str(",");
+                }
+            });
+            if regs.len() > 1 {
+                x.push_str("]");
+            }
+            x
+        };
+        let format_labels = |labels: &[MachLabel]| -> String {
+            if labels.len() == 0 {
+                return String::from("[_]");
+            }
+            let mut x = String::from("[");
+            labels.iter().for_each(|l| {
+                x.push_str(
+                    format!(
+                        "{:?}{}",
+                        l,
+                        if l != labels.last().unwrap() { "," } else { "" },
+                    )
+                    .as_str(),
+                );
+            });
+            x.push_str("]");
+            x
+        };
+
+        match self {
+            &Inst::Nop0 => {
+                format!("##zero length nop")
+            }
+            &Inst::Nop4 => {
+                format!("##fixed 4-size nop")
+            }
+            &Inst::StackProbeLoop {
+                guard_size,
+                probe_count,
+                tmp,
+            } => {
+                let tmp = format_reg(tmp.to_reg());
+                format!(
+                    "inline_stack_probe##guard_size={guard_size} probe_count={probe_count} tmp={tmp}"
+                )
+            }
+            &Inst::DummyUse { reg } => {
+                let reg = format_reg(reg);
+                format!("dummy_use {reg}")
+            }
+            &Inst::LoadConst64 { rd, imm } => {
+                let rd = format_reg(rd.to_reg());
+                format!("li.d {rd},{imm:#x}")
+            }
+            &Inst::AluRRR { alu_op, rd, rj, rk } => {
+                let rd = format_reg(rd.to_reg());
+                let rj_s = format_reg(rj);
+                let rk_s = format_reg(rk);
+                match alu_op {
+                    AluOPRRR::Or if rk == zero_reg() => format!("move {rd},{rj_s}"),
+                    AluOPRRR::SubD if rj == zero_reg() => format!("neg.d {rd},{rk_s}"),
+                    AluOPRRR::Nor if rk == zero_reg() => format!("not {rd},{rj_s}"),
+                    _ => format!("{} {rd},{rj_s},{rk_s}", alu_op.op_name()),
+                }
+            }
+            &Inst::AluRRImm12 {
+                alu_op,
+                rd,
+                rj,
+                imm12,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj_s = format_reg(rj);
+                match alu_op {
+                    AluOPRRI::AddiD if rj == zero_reg() => format!("li.d {rd},{imm12}"),
+                    AluOPRRI::AddiW if imm12.as_i16() == 0 => format!("sext.w {rd},{rj_s}"),
+                    _ => format!("{} {rd},{rj_s},{imm12}", alu_op.op_name()),
+                }
+            }
+            &Inst::AluRRUImm12 {
+                alu_op,
+                rd,
+                rj,
+                imm12,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("{} {rd},{rj},{imm12}", alu_op.op_name())
+            }
+            &Inst::AluRRShift {
+                alu_op,
+                rd,
+                rj,
+                shamt,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("{} {rd},{rj},{shamt}", alu_op.op_name())
+            }
+            &Inst::AluRR { alu_op, rd, rj } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("{} {rd},{rj}", alu_op.op_name())
+            }
+            &Inst::Bstrpick { rd, rj, msb, lsb } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("bstrpick.d {rd},{rj},{msb},{lsb}")
+            }
+            &Inst::Alsl { rd, rj, rk, shamt } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                let rk = format_reg(rk);
+                format!("alsl.d {rd},{rj},{rk},{shamt}")
+            }
+            &Inst::FpuRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("{}.{} {rd},{rj}", fpu_op.op_name(), width.suffix())
+            }
+            &Inst::FpuRRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+                rk,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                let rk = format_reg(rk);
+                format!("{}.{} {rd},{rj},{rk}", fpu_op.op_name(), width.suffix())
+            }
+            &Inst::FpuRRRR {
+                fpu_op,
+                width,
+                rd,
+                rj,
+                rk,
+                ra,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                let rk = format_reg(rk);
+                let ra = format_reg(ra);
+                format!(
+                    "{}.{} {rd},{rj},{rk},{ra}",
+                    fpu_op.op_name(),
+                    width.suffix()
+                )
+            }
+            &Inst::FpuConv { op, rd, rj } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                format!("{} {rd},{rj}", op.op_name())
+            }
+            &Inst::FpuCmp {
+                cond,
+                width,
+                rd,
+                rj,
+                rk,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                let rj = format_reg(rj);
+                let rk = format_reg(rk);
+                format!(
+                    "fcmp.{}.{} $fcc0,{rj},{rk}; movcf2gr {rd},$fcc0",
+                    cond.op_name(),
+                    width.suffix()
+                )
+            }
+            &Inst::FpuSel { rd, c, x, y } => {
+                let rd = format_reg(rd.to_reg());
+                let c = format_reg(c);
+                let x = format_reg(x);
+                let y = format_reg(y);
+                format!("movgr2cf $fcc0,{c}; fsel {rd},{y},{x},$fcc0")
+            }
+            &Inst::Load {
+                rd,
+                op,
+                from,
+                flags: _flags,
+            } => {
+                let base = from.to_string();
+                let rd = format_reg(rd.to_reg());
+                format!("{} {},{}", op.op_name(), rd, base)
+            }
+            &Inst::Store {
+                to,
+                src,
+                op,
+                flags: _flags,
+            } => {
+                let base = to.to_string();
+                let src = format_reg(src);
+                format!("{} {},{}", op.op_name(), src, base)
+            }
+            &Inst::Args { ref args } => {
+                let mut s = "args".to_string();
+                for arg in args {
+                    let preg = format_reg(arg.preg);
+                    let def = format_reg(arg.vreg.to_reg());
+                    write!(&mut s, " {def}={preg}").unwrap();
+                }
+                s
+            }
+            &Inst::Rets { ref rets } => {
+                let mut s = "rets".to_string();
+                for ret in rets {
+                    let preg = format_reg(ret.preg);
+                    let vreg = format_reg(ret.vreg);
+                    write!(&mut s, " {vreg}={preg}").unwrap();
+                }
+                s
+            }
+            &Inst::Ret {} => "ret".to_string(),
+
+            &MInst::Extend {
+                rd,
+                rn,
+                signed,
+                from_bits,
+                ..
+            } => {
+                let rn = format_reg(rn);
+                let rd = format_reg(rd.to_reg());
+                match (signed, from_bits) {
+                    (true, 8) => format!("ext.w.b {rd},{rn}"),
+                    (true, 16) => format!("ext.w.h {rd},{rn}"),
+                    (true, _) => format!("sext.w {rd},{rn}"),
+                    (false, bits) => format!("bstrpick.d {rd},{rn},{},0", bits - 1),
+                }
+            }
+            &MInst::Call { ref info } => {
+                let try_call = info
+                    .try_call_info
+                    .as_ref()
+                    .map(|tci| pretty_print_try_call(tci))
+                    .unwrap_or_default();
+                format!("call {}{try_call}", info.dest.display(None))
+            }
+            &MInst::CallInd { ref info } => {
+                let rd = format_reg(info.dest);
+                let try_call = info
+                    .try_call_info
+                    .as_ref()
+                    .map(|tci| pretty_print_try_call(tci))
+                    .unwrap_or_default();
+                format!("callind {rd}{try_call}")
+            }
+            &MInst::ReturnCall { ref info } => {
+                let mut s = format!(
+                    "return_call {:?} new_stack_arg_size:{}",
+                    info.dest, info.new_stack_arg_size
+                );
+                for ret in &info.uses {
+                    let preg = format_reg(ret.preg);
+                    let vreg = format_reg(ret.vreg);
+                    write!(&mut s, " {vreg}={preg}").unwrap();
+                }
+                s
+            }
+            &MInst::ReturnCallInd { ref info } => {
+                let callee = format_reg(info.dest);
+                let mut s = format!(
+                    "return_call_ind {callee} new_stack_arg_size:{}",
+                    info.new_stack_arg_size
+                );
+                for ret in &info.uses {
+                    let preg = format_reg(ret.preg);
+                    let vreg = format_reg(ret.vreg);
+                    write!(&mut s, " {vreg}={preg}").unwrap();
+                }
+                s
+            }
+            &MInst::TrapIf {
+                rj,
+                rk,
+                cc,
+                trap_code,
+            } => {
+                let rj = format_reg(rj);
+                let rk = format_reg(rk);
+                format!("trap_if {trap_code}##({rj} {cc} {rk})")
+            }
+            &MInst::Jump { label } => {
+                format!("b {}", label.to_string())
+            }
+            &MInst::CondBr {
+                taken,
+                not_taken,
+                kind,
+                ..
+            } => {
+                let rs1 = format_reg(kind.rs1);
+                let rs2 = format_reg(kind.rs2);
+                if not_taken.is_fallthrough() && taken.as_label().is_none() {
+                    format!("{} {},{},0", kind.op_name(), rs1, rs2)
+                } else {
+                    format!(
+                        "{} {},{},taken({}),not_taken({})",
+                        kind.op_name(),
+                        rs1,
+                        rs2,
+                        taken,
+                        not_taken
+                    )
+                }
+            }
+            &MInst::AtomicRmw { op, rd, addr, src } => {
+                let addr = format_reg(addr);
+                let src = format_reg(src);
+                let rd = format_reg(rd.to_reg());
+                format!("{} {rd},{src},{addr}", op.op_name())
+            }
+            &MInst::AtomicRmwLoop {
+                op,
+                ty,
+                dst,
+                addr,
+                x,
+                t0,
+                t1,
+                t2,
+                t3,
+            } => {
+                let addr = format_reg(addr);
+                let x = format_reg(x);
+                let dst = format_reg(dst.to_reg());
+                let t0 = format_reg(t0.to_reg());
+                let t1 = format_reg(t1.to_reg());
+                let t2 = format_reg(t2.to_reg());
+                let t3 = format_reg(t3.to_reg());
+                format!("atomic_rmw.{ty} {op} {dst},{x},({addr})##t0={t0} t1={t1} t2={t2} t3={t3}")
+            }
+            &MInst::AtomicCas {
+                ty,
+                dst,
+                addr,
+                e,
+                v,
+                t0,
+                t1,
+                t2,
+            } => {
+                let addr = format_reg(addr);
+                let e = format_reg(e);
+                let v = format_reg(v);
+                let dst = format_reg(dst.to_reg());
+                let t0 = format_reg(t0.to_reg());
+                let t1 = format_reg(t1.to_reg());
+                let t2 = format_reg(t2.to_reg());
+                format!("atomic_cas.{ty} {dst},{e},{v},({addr})##t0={t0} t1={t1} t2={t2}")
+            }
+            &MInst::LoadExtName {
+                rd,
+                ref name,
+                offset,
+            } => {
+                let rd = format_reg(rd.to_reg());
+                format!("load_sym {},{}{:+}", rd, name.display(None), offset)
+            }
+            &MInst::LoadAddr { ref rd, ref mem } => {
+                let rs = mem.to_string();
+                let rd = format_reg(rd.to_reg());
+                format!("load_addr {rd},{rs}")
+            }
+            &MInst::Mov { rd, rm, ty } => {
+                let rm = format_reg(rm);
+                let rd = format_reg(rd.to_reg());
+
+                let op = match ty {
+                    F32 => "fmov.s",
+                    F64 => "fmov.d",
+                    _ => "move",
+                };
+
+                format!("{op} {rd},{rm}")
+            }
+            &MInst::MovFromPReg { rd, rm } => {
+                let rd = format_reg(rd.to_reg());
+                debug_assert!([px_reg(3), px_reg(22)].contains(&rm));
+                let rm = reg_name(Reg::from(rm));
+                format!("move {rd},{rm}")
+            }
+            &MInst::Fence => "dbar 0".to_string(),
+            &MInst::Break => "break 0".to_string(),
+            &MInst::Select {
+                ref dst,
+                condition,
+                ref x,
+                ref y,
+            } => {
+                let c_rs1 = format_reg(condition.rs1);
+                let c_rs2 = format_reg(condition.rs2);
+                let x = format_regs(x.regs());
+                let y = format_regs(y.regs());
+                let dst = dst.map(|r| r.to_reg());
+                let dst = format_regs(dst.regs());
+                format!(
+                    "select {},{},{}##condition=({} {} {})",
+                    dst,
+                    x,
+                    y,
+                    c_rs1,
+                    condition.kind.to_static_str(),
+                    c_rs2
+                )
+            }
+            &MInst::BrTable {
+                index,
+                tmp1,
+                tmp2,
+                ref targets,
+            } => {
+                format!(
+                    "{} {},{}##tmp1={},tmp2={}",
+                    "br_table",
+                    format_reg(index),
+                    format_labels(&targets[..]),
+                    format_reg(tmp1.to_reg()),
+                    format_reg(tmp2.to_reg()),
+                )
+            }
+            &MInst::Unwind { ref inst } => {
+                format!("unwind {inst:?}")
+            }
+            &MInst::Udf { trap_code } => format!("udf##trap_code={trap_code}"),
+            &MInst::EmitIsland { needed_space } => {
+                format!("emit_island {needed_space}")
+            }
+        }
+    }
+}
+
+/// Different forms of label references for different instruction formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelUse {
+    /// 16-bit branch offset used by the two-register conditional branches
+    /// (`beq`, `bne`, `blt`, ...). The offset is in units of 4 bytes, giving
+    /// a range of +/-128KiB.
+    B16,
+
+    /// 26-bit branch offset used by `b` and `bl`, giving a range of +/-128MiB.
+    B26,
+
+    /// 20-bit PC-relative offset used by `pcaddi`, giving a range of +/-2MiB.
+    PCAdd20,
+
+    /// A `pcaddu18i` + `jirl` pair, giving a range of +/-128GiB. This is the
+    /// shape of the `R_LARCH_CALL36` relocation.
+    Call36,
+}
+
+impl MachInstLabelUse for LabelUse {
+    /// Alignment for veneer code. Every LoongArch instruction must be
+    /// 4-byte-aligned.
+    const ALIGN: CodeOffset = 4;
+
+    fn max_pos_range(self) -> CodeOffset {
+        match self {
+            LabelUse::B16 => ((1 << 15) - 1) * 4,
+            LabelUse::B26 => ((1 << 25) - 1) * 4,
+            LabelUse::PCAdd20 => ((1 << 19) - 1) * 4,
+            // The full range doesn't fit in a `CodeOffset`, which is fine as
+            // code sections are limited well below that anyway.
+            LabelUse::Call36 => i32::MAX as CodeOffset,
+        }
+    }
+
+    fn max_neg_range(self) -> CodeOffset {
+        match self {
+            LabelUse::Call36 => i32::MAX as CodeOffset,
+            _ => self.max_pos_range() + 4,
+        }
+    }
+
+    fn patch_size(self) -> CodeOffset {
+        match self {
+            LabelUse::B16 | LabelUse::B26 | LabelUse::PCAdd20 => 4,
+            LabelUse::Call36 => 8,
+        }
+    }
+
+    fn patch(self, buffer: &mut [u8], use_offset: CodeOffset, label_offset: CodeOffset) {
+        assert!(use_offset % 4 == 0);
+        assert!(label_offset % 4 == 0);
+        let offset = (label_offset as i64) - (use_offset as i64);
+
+        assert!(
+            offset >= -(self.max_neg_range() as i64) && offset <= (self.max_pos_range() as i64),
+            "{self:?} offset '{offset}' use_offset:'{use_offset}' label_offset:'{label_offset}'  must not exceed max range.",
+        );
+        self.patch_raw_offset(buffer, offset);
+    }
+
+    fn supports_veneer(self) -> bool {
+        match self {
+            Self::B16 | Self::B26 => true,
+            _ => false,
+        }
+    }
+
+    fn veneer_size(self) -> CodeOffset {
+        match self {
+            Self::B16 | Self::B26 => 8,
+            _ => unreachable!(),
+        }
+    }
+
+    fn worst_case_veneer_size() -> CodeOffset {
+        8
+    }
+
+    fn generate_veneer(
+        self,
+        buffer: &mut [u8],
+        veneer_offset: CodeOffset,
+    ) -> (CodeOffset, LabelUse) {
+        let base = writable_spilltmp_reg();
+        buffer[0..4].clone_from_slice(&enc_pcaddu18i(base, 0).to_le_bytes());
+        buffer[4..8]
+            .clone_from_slice(&enc_jirl(writable_zero_reg(), base.to_reg(), 0).to_le_bytes());
+        (veneer_offset, Self::Call36)
+    }
+
+    fn from_reloc(reloc: Reloc, addend: Addend) -> Option<LabelUse> {
+        match (reloc, addend) {
+            (Reloc::LoongArchCall36, _) => Some(Self::Call36),
+            _ => None,
+        }
+    }
+}
+
+impl LabelUse {
+    fn patch_raw_offset(self, buffer: &mut [u8], offset: i64) {
+        let insn = u32::from_le_bytes(buffer[..4].try_into().unwrap());
+
+        match self {
+            LabelUse::B16 => {
+                let offs = (offset >> 2) as u32;
+                let v = (offs & 0xFFFF) << 10;
+                buffer[0..4].clone_from_slice(&u32::to_le_bytes(insn | v));
+            }
+            LabelUse::B26 => {
+                let offs = (offset >> 2) as u32;
+                let v = (offs & 0xFFFF) << 10 | (offs >> 16) & 0x3FF;
+                buffer[0..4].clone_from_slice(&u32::to_le_bytes(insn | v));
+            }
+            LabelUse::PCAdd20 => {
+                let offs = (offset >> 2) as u32;
+                let v = (offs & 0xF_FFFF) << 5;
+                buffer[0..4].clone_from_slice(&u32::to_le_bytes(insn | v));
+            }
+            LabelUse::Call36 => {
+                let insn2 = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+                // `jirl` sign-extends its offset, so round the high part such
+                // that the low part lands in [-2^17, 2^17).
+                let hi = (offset + (1 << 17)) >> 18;
+                let lo = (offset - (hi << 18)) >> 2;
+                let hi = (hi as u32 & 0xF_FFFF) << 5;
+                let lo = (lo as u32 & 0xFFFF) << 10;
+                buffer[0..4].clone_from_slice(&u32::to_le_bytes(insn | hi));
+                buffer[4..8].clone_from_slice(&u32::to_le_bytes(insn2 | lo));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn label_use_max_range() {
+        assert!(LabelUse::B16.max_neg_range() == LabelUse::B16.max_pos_range() + 4);
+        assert!(LabelUse::B26.max_neg_range() == LabelUse::B26.max_pos_range() + 4);
+        assert!(LabelUse::B16.max_pos_range() == ((1 << 15) - 1) * 4);
+    }
+
+    #[test]
+    fn call36_patch() {
+        // A forward call to an offset whose low part would be negative.
+        let mut buffer = [0u8; 8];
+        buffer[0..4].clone_from_slice(&enc_pcaddu18i(writable_link_reg(), 0).to_le_bytes());
+        buffer[4..8].clone_from_slice(&enc_jirl(writable_link_reg(), link_reg(), 0).to_le_bytes());
+        LabelUse::Call36.patch(&mut buffer, 0, 0x3_fffc);
+        let hi = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
+        let lo = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+        let hi = i64::from(((hi >> 5) & 0xF_FFFF) as i32) << 18;
+        let lo = i64::from((((lo >> 10) & 0xFFFF) as u16) as i16) << 2;
+        assert_eq!(hi + lo, 0x3_fffc);
+    }
+}
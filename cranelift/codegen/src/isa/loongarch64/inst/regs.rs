@@ -0,0 +1,131 @@
+//! LoongArch64 ISA definitions: registers.
+//!
+//! The general-purpose registers follow the LP64 naming of the LoongArch ELF
+//! psABI:
+//!
+//! * `r0` (`zero`) is hardwired to zero.
+//! * `r1` (`ra`) is the return address.
+//! * `r2` (`tp`) is the thread pointer and is never allocated.
+//! * `r3` (`sp`) is the stack pointer.
+//! * `r4`-`r11` (`a0`-`a7`) are argument registers.
+//! * `r12`-`r20` (`t0`-`t8`) are temporaries.
+//! * `r21` is reserved by the ABI and is never allocated.
+//! * `r22` (`fp`) is the frame pointer.
+//! * `r23`-`r31` (`s0`-`s8`) are callee-saved.
+
+use crate::machinst::{Reg, Writable};
+
+use regalloc2::{PReg, RegClass, VReg};
+
+// first argument of function call
+#[inline]
+pub const fn a0() -> Reg {
+    x_reg(4)
+}
+
+// second argument of function call
+#[inline]
+pub const fn a1() -> Reg {
+    x_reg(5)
+}
+
+#[inline]
+pub fn writable_a0() -> Writable<Reg> {
+    Writable::from_reg(a0())
+}
+
+/// Get a reference to the zero-register.
+#[inline]
+pub const fn zero_reg() -> Reg {
+    x_reg(0)
+}
+
+/// Get a writable reference to the zero-register (this discards a result).
+#[inline]
+pub fn writable_zero_reg() -> Writable<Reg> {
+    Writable::from_reg(zero_reg())
+}
+
+/// Get a reference to the link register (r1).
+#[inline]
+pub const fn link_reg() -> Reg {
+    x_reg(1)
+}
+
+/// Get a writable reference to the link register.
+#[inline]
+pub fn writable_link_reg() -> Writable<Reg> {
+    Writable::from_reg(link_reg())
+}
+
+/// Get a reference to the stack-pointer register (r3).
+#[inline]
+pub const fn stack_reg() -> Reg {
+    x_reg(3)
+}
+
+/// Get a writable reference to the stack-pointer register.
+#[inline]
+pub fn writable_stack_reg() -> Writable<Reg> {
+    Writable::from_reg(stack_reg())
+}
+
+/// Get a reference to the frame pointer (r22).
+#[inline]
+pub const fn fp_reg() -> Reg {
+    x_reg(22)
+}
+
+/// Get a writable reference to the frame pointer.
+#[inline]
+pub fn writable_fp_reg() -> Writable<Reg> {
+    Writable::from_reg(fp_reg())
+}
+
+/// Get a reference to the first temporary, sometimes "spill temporary",
+/// register. This register is used in various ways as a temporary and is
+/// never handed out by the register allocator.
+#[inline]
+pub const fn spilltmp_reg() -> Reg {
+    x_reg(20)
+}
+
+/// Get a writable reference to the spilltmp reg.
+#[inline]
+pub fn writable_spilltmp_reg() -> Writable<Reg> {
+    Writable::from_reg(spilltmp_reg())
+}
+
+/// Get a reference to the second spill temporary register.
+#[inline]
+pub const fn spilltmp_reg2() -> Reg {
+    x_reg(19)
+}
+
+/// Get a writable reference to the spilltmp2 reg.
+#[inline]
+pub fn writable_spilltmp_reg2() -> Writable<Reg> {
+    Writable::from_reg(spilltmp_reg2())
+}
+
+#[inline]
+pub const fn x_reg(enc: usize) -> Reg {
+    let p_reg = PReg::new(enc, RegClass::Int);
+    let v_reg = VReg::new(p_reg.index(), p_reg.class());
+    Reg::from_virtual_reg(v_reg)
+}
+
+pub const fn px_reg(enc: usize) -> PReg {
+    PReg::new(enc, RegClass::Int)
+}
+
+#[inline]
+pub const fn f_reg(enc: usize) -> Reg {
+    let p_reg = PReg::new(enc, RegClass::Float);
+    let v_reg = VReg::new(p_reg.index(), p_reg.class());
+    Reg::from_virtual_reg(v_reg)
+}
+
+pub const fn pf_reg(enc: usize) -> PReg {
+    PReg::new(enc, RegClass::Float)
+}
@@ -0,0 +1,31 @@
+//! Lowering rules for LoongArch64.
+use crate::ir::Inst as IRInst;
+use crate::isa::loongarch64::LoongArch64Backend;
+use crate::isa::loongarch64::inst::*;
+use crate::machinst::lower::*;
+use crate::machinst::*;
+pub mod isle;
+
+//=============================================================================
+// Lowering-backend trait implementation.
+
+impl LowerBackend for LoongArch64Backend {
+    type MInst = Inst;
+
+    fn lower(&self, ctx: &mut Lower<Inst>, ir_inst: IRInst) -> Option<InstOutput> {
+        isle::lower(ctx, self, ir_inst)
+    }
+
+    fn lower_branch(
+        &self,
+        ctx: &mut Lower<Inst>,
+        ir_inst: IRInst,
+        targets: &[MachLabel],
+    ) -> Option<()> {
+        isle::lower_branch(ctx, self, ir_inst, targets)
+    }
+
+    // Proof-carrying code is not yet supported on this backend, so the
+    // default `check_fact` (which rejects every fact) is used.
+    type FactFlowState = ();
+}
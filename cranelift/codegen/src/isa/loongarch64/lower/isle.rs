@@ -0,0 +1,361 @@
+//! ISLE integration glue code for loongarch64 lowering.
+
+// Pull in the ISLE generated code.
+#[allow(unused)]
+pub mod generated_code;
+use generated_code::MInst;
+
+// Types that the generated ISLE code uses via `use super::*`.
+use self::generated_code::FpuWidth;
+use crate::isa::loongarch64::LoongArch64Backend;
+use crate::machinst::Reg;
+use crate::machinst::{CallInfo, MachInst, isle::*};
+use crate::machinst::{VCodeConstant, VCodeConstantData};
+use crate::{
+    ir::{
+        AtomicRmwOp, BlockCall, ExternalName, Inst, InstructionData, MemFlags, Opcode, TrapCode,
+        Value, ValueList, condcodes::FloatCC, immediates::*, types::*,
+    },
+    isa::loongarch64::inst::*,
+    machinst::{ArgPair, CallArgList, CallRetList, InstOutput},
+};
+use regalloc2::PReg;
+use std::boxed::Box;
+use std::vec::Vec;
+use wasmtime_math::{f32_cvt_to_int_bounds, f64_cvt_to_int_bounds};
+
+type BoxCallInfo = Box<CallInfo<ExternalName>>;
+type BoxCallIndInfo = Box<CallInfo<Reg>>;
+type BoxReturnCallInfo = Box<ReturnCallInfo<ExternalName>>;
+type BoxReturnCallIndInfo = Box<ReturnCallInfo<Reg>>;
+type BoxExternalName = Box<ExternalName>;
+type VecMachLabel = Vec<MachLabel>;
+type VecArgPair = Vec<ArgPair>;
+
+pub(crate) struct LA64IsleContext<'a, 'b, I, B>
+where
+    I: VCodeInst,
+    B: LowerBackend,
+{
+    pub lower_ctx: &'a mut Lower<'b, I>,
+    pub backend: &'a B,
+}
+
+impl<'a, 'b> LA64IsleContext<'a, 'b, MInst, LoongArch64Backend> {
+    fn new(lower_ctx: &'a mut Lower<'b, MInst>, backend: &'a LoongArch64Backend) -> Self {
+        Self { lower_ctx, backend }
+    }
+
+    pub(crate) fn dfg(&self) -> &crate::ir::DataFlowGraph {
+        &self.lower_ctx.f.dfg
+    }
+}
+
+impl generated_code::Context for LA64IsleContext<'_, '_, MInst, LoongArch64Backend> {
+    isle_lower_prelude_methods!();
+
+    fn emit(&mut self, arg0: &MInst) -> Unit {
+        self.lower_ctx.emit(arg0.clone());
+    }
+
+    fn gen_call_info(
+        &mut self,
+        sig: Sig,
+        dest: ExternalName,
+        uses: CallArgList,
+        defs: CallRetList,
+        try_call_info: Option<TryCallInfo>,
+    ) -> BoxCallInfo {
+        let stack_ret_space = self.lower_ctx.sigs()[sig].sized_stack_ret_space();
+        let stack_arg_space = self.lower_ctx.sigs()[sig].sized_stack_arg_space();
+        self.lower_ctx
+            .abi_mut()
+            .accumulate_outgoing_args_size(stack_ret_space + stack_arg_space);
+
+        Box::new(
+            self.lower_ctx
+                .gen_call_info(sig, dest, uses, defs, try_call_info),
+        )
+    }
+
+    fn gen_call_ind_info(
+        &mut self,
+        sig: Sig,
+        dest: Reg,
+        uses: CallArgList,
+        defs: CallRetList,
+        try_call_info: Option<TryCallInfo>,
+    ) -> BoxCallIndInfo {
+        let stack_ret_space = self.lower_ctx.sigs()[sig].sized_stack_ret_space();
+        let stack_arg_space = self.lower_ctx.sigs()[sig].sized_stack_arg_space();
+        self.lower_ctx
+            .abi_mut()
+            .accumulate_outgoing_args_size(stack_ret_space + stack_arg_space);
+
+        Box::new(
+            self.lower_ctx
+                .gen_call_info(sig, dest, uses, defs, try_call_info),
+        )
+    }
+
+    fn gen_return_call_info(
+        &mut self,
+        sig: Sig,
+        dest: ExternalName,
+        uses: CallArgList,
+    ) -> BoxReturnCallInfo {
+        let new_stack_arg_size = self.lower_ctx.sigs()[sig].sized_stack_arg_space();
+        self.lower_ctx
+            .abi_mut()
+            .accumulate_tail_args_size(new_stack_arg_size);
+
+        Box::new(ReturnCallInfo {
+            dest,
+            uses,
+            new_stack_arg_size,
+        })
+    }
+
+    fn gen_return_call_ind_info(
+        &mut self,
+        sig: Sig,
+        dest: Reg,
+        uses: CallArgList,
+    ) -> BoxReturnCallIndInfo {
+        let new_stack_arg_size = self.lower_ctx.sigs()[sig].sized_stack_arg_space();
+        self.lower_ctx
+            .abi_mut()
+            .accumulate_tail_args_size(new_stack_arg_size);
+
+        Box::new(ReturnCallInfo {
+            dest,
+            uses,
+            new_stack_arg_size,
+        })
+    }
+
+    fn ty_supported_float(&mut self, ty: Type) -> Option<Type> {
+        match ty {
+            F32 | F64 => Some(ty),
+            _ => None,
+        }
+    }
+
+    fn ty_reg_pair(&mut self, ty: Type) -> Option<Type> {
+        match ty {
+            I128 => Some(ty),
+            _ => None,
+        }
+    }
+
+    fn fpu_width_from_ty(&mut self, ty: Type) -> FpuWidth {
+        match ty {
+            F32 => FpuWidth::S,
+            F64 => FpuWidth::D,
+            _ => panic!("unexpected float type {ty}"),
+        }
+    }
+
+    fn load_ra(&mut self) -> Reg {
+        if self.backend.flags.preserve_frame_pointers() {
+            let tmp = self.temp_writable_reg(I64);
+            self.emit(&MInst::Load {
+                rd: tmp,
+                op: LoadOP::LdD,
+                flags: MemFlags::trusted(),
+                from: AMode::FPOffset(8),
+            });
+            tmp.to_reg()
+        } else {
+            link_reg()
+        }
+    }
+
+    fn label_to_br_target(&mut self, label: MachLabel) -> CondBrTarget {
+        CondBrTarget::Label(label)
+    }
+
+    fn imm12_from_i64(&mut self, val: i64) -> Option<Imm12> {
+        Imm12::maybe_from_i64(val)
+    }
+
+    fn uimm12_from_u64(&mut self, val: u64) -> Option<UImm12> {
+        UImm12::maybe_from_u64(val)
+    }
+
+    fn imm12_const(&mut self, val: i32) -> Imm12 {
+        Imm12::maybe_from_i64(i64::from(val)).unwrap()
+    }
+
+    fn uimm12_const(&mut self, val: u32) -> UImm12 {
+        UImm12::maybe_from_u64(u64::from(val)).unwrap()
+    }
+
+    fn uimm6(&mut self, val: u8) -> UImm6 {
+        UImm6::maybe_from_u64(u64::from(val)).unwrap()
+    }
+
+    fn shift_imm(&mut self, ty: Type, val: u64) -> UImm6 {
+        UImm6::maybe_from_u64(val & u64::from(ty.bits() - 1)).unwrap()
+    }
+
+    fn alsl_shamt(&mut self, val: u64) -> Option<u8> {
+        match val {
+            1..=4 => Some(val as u8),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn zero_reg(&mut self) -> Reg {
+        zero_reg()
+    }
+
+    fn is_zero_reg(&mut self, reg: Reg) -> Option<()> {
+        if reg == zero_reg() { Some(()) } else { None }
+    }
+
+    fn gen_reg_offset_amode(&mut self, base: Reg, offset: i64) -> AMode {
+        AMode::RegOffset(base, offset)
+    }
+
+    fn gen_sp_offset_amode(&mut self, offset: i64) -> AMode {
+        AMode::SPOffset(offset)
+    }
+
+    fn gen_fp_offset_amode(&mut self, offset: i64) -> AMode {
+        AMode::FPOffset(offset)
+    }
+
+    fn gen_stack_slot_amode(&mut self, ss: StackSlot, offset: i64) -> AMode {
+        // Offset from beginning of stackslot area.
+        let stack_off = self.lower_ctx.abi().sized_stackslot_offsets()[ss] as i64;
+        let sp_off: i64 = stack_off + offset;
+        AMode::SlotOffset(sp_off)
+    }
+
+    fn valid_atomic_transaction(&mut self, ty: Type) -> Option<Type> {
+        if ty.is_int() && ty.bits() <= 64 {
+            Some(ty)
+        } else {
+            None
+        }
+    }
+
+    fn load_op(&mut self, ty: Type) -> LoadOP {
+        LoadOP::from_type(ty)
+    }
+
+    fn store_op(&mut self, ty: Type) -> StoreOP {
+        StoreOP::from_type(ty)
+    }
+
+    fn load_ext_name(&mut self, name: ExternalName, offset: i64) -> Reg {
+        let tmp = self.temp_writable_reg(I64);
+        self.emit(&MInst::LoadExtName {
+            rd: tmp,
+            name: Box::new(name),
+            offset,
+        });
+        tmp.to_reg()
+    }
+
+    fn gen_stack_addr(&mut self, slot: StackSlot, offset: Offset32) -> Reg {
+        let result = self.temp_writable_reg(I64);
+        let i = self
+            .lower_ctx
+            .abi()
+            .sized_stackslot_addr(slot, i64::from(offset) as u32, result);
+        self.emit(&i);
+        result.to_reg()
+    }
+
+    fn lower_br_table(&mut self, index: Reg, targets: &[MachLabel]) -> Unit {
+        let tmp1 = self.temp_writable_reg(I64);
+        let tmp2 = self.temp_writable_reg(I64);
+        self.emit(&MInst::BrTable {
+            index,
+            tmp1,
+            tmp2,
+            targets: targets.to_vec(),
+        });
+    }
+
+    fn fp_reg(&mut self) -> PReg {
+        px_reg(22)
+    }
+
+    fn sp_reg(&mut self) -> PReg {
+        px_reg(3)
+    }
+
+    #[inline]
+    fn int_compare(&mut self, kind: &IntCC, rs1: Reg, rs2: Reg) -> IntegerCompare {
+        IntegerCompare {
+            kind: *kind,
+            rs1,
+            rs2,
+        }
+    }
+
+    fn fcvt_smin_bound(&mut self, float: Type, int: Type, saturating: bool) -> u64 {
+        assert!(!saturating);
+        match float {
+            F32 => f32_cvt_to_int_bounds(true, int.bits()).0.to_bits().into(),
+            F64 => f64_cvt_to_int_bounds(true, int.bits()).0.to_bits(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn fcvt_smax_bound(&mut self, float: Type, int: Type, saturating: bool) -> u64 {
+        assert!(!saturating);
+        match float {
+            F32 => f32_cvt_to_int_bounds(true, int.bits()).1.to_bits().into(),
+            F64 => f64_cvt_to_int_bounds(true, int.bits()).1.to_bits(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn fcvt_umax_bound(&mut self, float: Type, int: Type, saturating: bool) -> u64 {
+        assert!(!saturating);
+        match float {
+            F32 => f32_cvt_to_int_bounds(false, int.bits()).1.to_bits().into(),
+            F64 => f64_cvt_to_int_bounds(false, int.bits()).1.to_bits(),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn fcvt_umin_bound(&mut self, float: Type, saturating: bool) -> u64 {
+        assert!(!saturating);
+        match float {
+            F32 => (-1.0f32).to_bits().into(),
+            F64 => (-1.0f64).to_bits(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+/// The main entry point for lowering with ISLE.
+pub(crate) fn lower(
+    lower_ctx: &mut Lower<MInst>,
+    backend: &LoongArch64Backend,
+    inst: Inst,
+) -> Option<InstOutput> {
+    // TODO: reuse the ISLE context across lowerings so we can reuse its
+    // internal heap allocations.
+    let mut isle_ctx = LA64IsleContext::new(lower_ctx, backend);
+    generated_code::constructor_lower(&mut isle_ctx, inst)
+}
+
+/// The main entry point for branch lowering with ISLE.
+pub(crate) fn lower_branch(
+    lower_ctx: &mut Lower<MInst>,
+    backend: &LoongArch64Backend,
+    branch: Inst,
+    targets: &[MachLabel],
+) -> Option<()> {
+    // TODO: reuse the ISLE context across lowerings so we can reuse its
+    // internal heap allocations.
+    let mut isle_ctx = LA64IsleContext::new(lower_ctx, backend);
+    generated_code::constructor_lower_branch(&mut isle_ctx, branch, targets)
+}
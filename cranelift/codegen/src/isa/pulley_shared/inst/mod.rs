@@ -33,6 +33,35 @@ impl From<RawInst> for Inst {
     }
 }
 
+/// The addressing mode of a raw Pulley load or store.
+#[derive(Clone, Copy, Debug)]
+pub enum RawAddr {
+    /// A plain host access; its address is not tracked since such
+    /// accesses are never checked.
+    O32,
+    Z(AddrZ),
+    G32(AddrG32),
+    G32Bne(AddrG32Bne),
+}
+
+/// The memory accessed by a raw Pulley load or store.
+#[derive(Clone, Copy, Debug)]
+pub struct RawMemAccess {
+    pub addr: RawAddr,
+    pub bytes: u32,
+    pub is_load: bool,
+    /// The integer register loaded into or stored from, if any.
+    pub reg: Option<Reg>,
+}
+
+impl RawInst {
+    /// Returns the memory accessed by this instruction, if it is a load or
+    /// store.
+    pub fn mem_access(&self) -> Option<RawMemAccess> {
+        generated::mem_access(self)
+    }
+}
+
 use super::PulleyTargetKind;
 
 mod generated {
@@ -2,9 +2,12 @@
 
 pub mod isle;
 
-use super::{PulleyBackend, PulleyTargetKind, inst::*};
+use super::{PulleyBackend, PulleyTargetKind, inst::*, pcc};
 use crate::{
-    ir,
+    ir::{
+        self,
+        pcc::{FactContext, PccResult},
+    },
     machinst::{lower::*, *},
 };
 
@@ -32,5 +35,15 @@ where
         None
     }
 
-    type FactFlowState = ();
+    fn check_fact(
+        &self,
+        ctx: &FactContext<'_>,
+        vcode: &mut VCode<Self::MInst>,
+        inst: InsnIndex,
+        state: &mut pcc::FactFlowState,
+    ) -> PccResult<()> {
+        pcc::check(ctx, vcode, inst, state)
+    }
+
+    type FactFlowState = pcc::FactFlowState;
 }
@@ -266,6 +266,10 @@ where
         flags.trap_code().is_none()
     }
 
+    fn memflags_checked(&mut self, flags: MemFlags) -> bool {
+        flags.checked()
+    }
+
     fn memflags_is_wasm(&mut self, flags: MemFlags) -> bool {
         flags.trap_code() == Some(TrapCode::HEAP_OUT_OF_BOUNDS)
             && self.endianness(flags) == Endianness::Little
@@ -3,6 +3,7 @@
 mod abi;
 mod inst;
 mod lower;
+mod pcc;
 mod settings;
 
 use self::inst::EmitInfo;
@@ -0,0 +1,508 @@
+//! Proof-carrying code checking for Pulley VCode.
+
+use super::PulleyTargetKind;
+use super::inst::*;
+use crate::ir::condcodes::IntCC;
+use crate::ir::pcc::*;
+use crate::ir::types::*;
+use crate::ir::{MemFlags, Type};
+use crate::machinst::pcc::*;
+use crate::machinst::{InsnIndex, Reg, RegClass, VCode, VCodeInst, Writable};
+use crate::trace;
+use std::collections::HashMap;
+
+/// Flow-state between facts.
+///
+/// Pulley has no condition flags: comparisons produce a `0`/`1`
+/// value in an ordinary register, which typically carries no fact of
+/// its own. We track which comparison each such register holds
+/// within a block so that selects on it can be checked. Since vregs
+/// are in SSA form, an entry remains valid for the rest of the block.
+#[derive(Clone, Debug, Default)]
+pub struct FactFlowState {
+    flags: HashMap<Reg, (IntCC, Fact, Fact)>,
+}
+
+impl FactFlowState {
+    /// The comparison whose outcome `reg` holds as a `0`/`1` flag,
+    /// either as tracked within this block or as stated by a
+    /// (checked) fact on the register itself.
+    fn flag<I: VCodeInst>(&self, vcode: &VCode<I>, reg: Reg) -> Option<(IntCC, Fact, Fact)> {
+        if let Some(flag) = self.flags.get(&reg) {
+            return Some(flag.clone());
+        }
+        match vcode.vreg_fact(reg.into()) {
+            Some(Fact::Compare { kind, lhs, rhs }) => {
+                Some((*kind, exact_fact(lhs), exact_fact(rhs)))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn xreg(reg: WritableXReg) -> Writable<Reg> {
+    reg.map(|r| *r)
+}
+
+pub(crate) fn check<P: PulleyTargetKind>(
+    ctx: &FactContext,
+    vcode: &mut VCode<InstAndKind<P>>,
+    inst_idx: InsnIndex,
+    state: &mut FactFlowState,
+) -> PccResult<()> {
+    let bits = u16::from(P::pointer_width().bits());
+    let inst: &Inst = &vcode[inst_idx];
+    trace!("Checking facts on inst: {:?}", inst);
+
+    match *inst {
+        Inst::Args { .. } => {
+            // Defs on the args have "axiomatic facts": we trust the
+            // ABI code to pass through the values unharmed, so the
+            // facts given to us in the CLIF should still be true.
+            Ok(())
+        }
+
+        Inst::XLoad {
+            dst,
+            ref mem,
+            ty,
+            flags,
+        } => {
+            let result_fact = vcode.vreg_fact(dst.to_reg().to_reg().into());
+            let from_bits = u16::try_from(ty.bits()).unwrap();
+            check_amode(
+                ctx,
+                flags,
+                mem,
+                vcode,
+                bits,
+                ty,
+                LoadOrStore::Load {
+                    result_fact,
+                    from_bits,
+                    to_bits: from_bits,
+                },
+            )
+        }
+        Inst::XStore {
+            ref mem,
+            src,
+            ty,
+            flags,
+        } => check_amode(
+            ctx,
+            flags,
+            mem,
+            vcode,
+            bits,
+            ty,
+            LoadOrStore::Store {
+                stored_fact: vcode.vreg_fact(src.to_reg().into()),
+            },
+        ),
+        Inst::FLoad {
+            ref mem, ty, flags, ..
+        }
+        | Inst::VLoad {
+            ref mem, ty, flags, ..
+        } => check_amode(
+            ctx,
+            flags,
+            mem,
+            vcode,
+            bits,
+            ty,
+            LoadOrStore::Load {
+                result_fact: None,
+                from_bits: u16::try_from(ty.bits()).unwrap(),
+                to_bits: u16::try_from(ty.bits()).unwrap(),
+            },
+        ),
+        Inst::FStore {
+            ref mem, ty, flags, ..
+        }
+        | Inst::VStore {
+            ref mem, ty, flags, ..
+        } => check_amode(
+            ctx,
+            flags,
+            mem,
+            vcode,
+            bits,
+            ty,
+            LoadOrStore::Store { stored_fact: None },
+        ),
+
+        Inst::LoadAddr {
+            dst,
+            mem: Amode::RegOffset { base, offset },
+        } => {
+            let base = get_fact_or_default(vcode, *base, bits);
+            check_output(ctx, vcode, xreg(dst), &[], |_vcode| {
+                Ok(ctx.offset(&base, bits, offset.into()))
+            })
+        }
+
+        Inst::Raw { ref raw } => check_raw(ctx, vcode, inst_idx, raw.clone(), bits, state),
+
+        _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
+
+        _ => Ok(()),
+    }
+}
+
+fn check_raw<P: PulleyTargetKind>(
+    ctx: &FactContext,
+    vcode: &mut VCode<InstAndKind<P>>,
+    inst_idx: InsnIndex,
+    raw: RawInst,
+    bits: u16,
+    state: &mut FactFlowState,
+) -> PccResult<()> {
+    if let Some(access) = raw.mem_access() {
+        return check_raw_access(ctx, vcode, bits, &access);
+    }
+
+    match raw {
+        RawInst::Xmov { dst, src } => {
+            check_unop(ctx, vcode, 64, xreg(dst), *src, |src| Ok(Some(src.clone())))
+        }
+
+        RawInst::Xzero { dst } => check_constant(ctx, vcode, xreg(dst), 64, 0),
+        RawInst::Xone { dst } => check_constant(ctx, vcode, xreg(dst), 64, 1),
+        RawInst::Xconst8 { dst, imm } => {
+            check_constant(ctx, vcode, xreg(dst), 64, i64::from(imm) as u64)
+        }
+        RawInst::Xconst16 { dst, imm } => {
+            check_constant(ctx, vcode, xreg(dst), 64, i64::from(imm) as u64)
+        }
+        RawInst::Xconst32 { dst, imm } => {
+            check_constant(ctx, vcode, xreg(dst), 64, i64::from(imm) as u64)
+        }
+        RawInst::Xconst64 { dst, imm } => check_constant(ctx, vcode, xreg(dst), 64, imm as u64),
+
+        // 32-bit operations leave the upper half of the destination
+        // unspecified, so their results only have 32-bit facts.
+        RawInst::Xadd32 { dst, src1, src2 } => {
+            check_binop(ctx, vcode, 32, xreg(dst), *src1, *src2, |src1, src2| {
+                clamp_range(ctx, 32, 32, ctx.add(src1, src2, 32))
+            })
+        }
+        RawInst::Xadd32U8 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 32, xreg(dst), *src1, |src1| {
+                let src2 = Fact::constant(32, src2.into());
+                clamp_range(ctx, 32, 32, ctx.add(src1, &src2, 32))
+            })
+        }
+        RawInst::Xadd32U32 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 32, xreg(dst), *src1, |src1| {
+                let src2 = Fact::constant(32, src2.into());
+                clamp_range(ctx, 32, 32, ctx.add(src1, &src2, 32))
+            })
+        }
+        RawInst::Xshl32U6 { dst, src1, src2 } => {
+            let amount = u8::from(src2);
+            check_unop(ctx, vcode, 32, xreg(dst), *src1, |src1| {
+                clamp_range(ctx, 32, 32, ctx.shl(src1, 32, amount.into()))
+            })
+        }
+        RawInst::Xadd64 { dst, src1, src2 } => {
+            check_binop(ctx, vcode, 64, xreg(dst), *src1, *src2, |src1, src2| {
+                clamp_range(ctx, 64, 64, ctx.add(src1, src2, 64))
+            })
+        }
+        RawInst::Xadd64U8 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 64, xreg(dst), *src1, |src1| {
+                Ok(ctx.offset(src1, 64, src2.into()))
+            })
+        }
+        RawInst::Xadd64U32 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 64, xreg(dst), *src1, |src1| {
+                Ok(ctx.offset(src1, 64, src2.into()))
+            })
+        }
+        RawInst::Xsub64U8 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 64, xreg(dst), *src1, |src1| {
+                Ok(ctx.offset(src1, 64, -i64::from(src2)))
+            })
+        }
+        RawInst::Xsub64U32 { dst, src1, src2 } => {
+            check_unop(ctx, vcode, 64, xreg(dst), *src1, |src1| {
+                Ok(ctx.offset(src1, 64, -i64::from(src2)))
+            })
+        }
+        RawInst::Xsub64 { dst, src1, src2 } => {
+            check_binop(ctx, vcode, 64, xreg(dst), *src1, *src2, |src1, src2| {
+                // Only subtraction of a known constant is supported.
+                let Some(k) = src2.as_const(64).and_then(|k| i64::try_from(k).ok()) else {
+                    return Ok(None);
+                };
+                Ok(ctx.offset(src1, 64, -k))
+            })
+        }
+
+        RawInst::Xshl64U6 { dst, src1, src2 } => {
+            let amount = u8::from(src2);
+            check_unop(ctx, vcode, 64, xreg(dst), *src1, |src1| {
+                clamp_range(ctx, 64, 64, ctx.shl(src1, 64, amount.into()))
+            })
+        }
+        RawInst::Xshladd32 {
+            dst,
+            src1,
+            src2,
+            shift,
+        } => {
+            let amount = u8::from(shift);
+            check_binop(ctx, vcode, 32, xreg(dst), *src1, *src2, |src1, src2| {
+                let Some(shifted) = clamp_range(ctx, 32, 32, ctx.shl(src1, 32, amount.into()))?
+                else {
+                    return Ok(None);
+                };
+                clamp_range(ctx, 32, 32, ctx.add(&shifted, src2, 32))
+            })
+        }
+        RawInst::Xshladd64 {
+            dst,
+            src1,
+            src2,
+            shift,
+        } => {
+            let amount = u8::from(shift);
+            check_binop(ctx, vcode, 64, xreg(dst), *src1, *src2, |src1, src2| {
+                let Some(shifted) = clamp_range(ctx, 64, 64, ctx.shl(src1, 64, amount.into()))?
+                else {
+                    return Ok(None);
+                };
+                clamp_range(ctx, 64, 64, ctx.add(&shifted, src2, 64))
+            })
+        }
+        RawInst::Zext32 { dst, src } => check_unop(ctx, vcode, 64, xreg(dst), *src, |src| {
+            clamp_range(ctx, 64, 64, ctx.uextend(src, 32, 64))
+        }),
+
+        RawInst::Xult64 { dst, src1, src2 } | RawInst::Xulteq64 { dst, src1, src2 } => {
+            let kind = match raw {
+                RawInst::Xult64 { .. } => IntCC::UnsignedLessThan,
+                _ => IntCC::UnsignedLessThanOrEqual,
+            };
+            let lhs = get_fact_or_default(vcode, *src1, 64);
+            let rhs = get_fact_or_default(vcode, *src2, 64);
+            check_compare_output(vcode, xreg(dst), (kind, &lhs, &rhs))?;
+            state
+                .flags
+                .insert(*dst.to_reg(), (kind, lhs.clone(), rhs.clone()));
+            Ok(())
+        }
+
+        RawInst::XSelect64 {
+            dst,
+            cond,
+            if_nonzero,
+            if_zero,
+        } => {
+            // Without a known comparison in `cond`, the select
+            // tells us nothing beyond the union of its inputs.
+            let (kind, lhs, rhs) = state.flag(vcode, *cond).unwrap_or((
+                IntCC::NotEqual,
+                get_fact_or_default(vcode, *cond, 64),
+                Fact::constant(64, 0),
+            ));
+            trace!("XSelect64: {kind:?} {lhs:?} {rhs:?}");
+            let if_nonzero = get_fact_or_default(vcode, *if_nonzero, 64);
+            let if_zero = get_fact_or_default(vcode, *if_zero, 64);
+            check_select(
+                ctx,
+                vcode,
+                xreg(dst),
+                (kind, &lhs, &rhs),
+                &if_nonzero,
+                &if_zero,
+            )
+        }
+
+        _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
+
+        _ => Ok(()),
+    }
+}
+
+/// Check a load or store through a pseudo-instruction's `Amode`.
+fn check_amode<'a, P: PulleyTargetKind>(
+    ctx: &FactContext,
+    flags: MemFlags,
+    mem: &Amode,
+    vcode: &VCode<InstAndKind<P>>,
+    bits: u16,
+    ty: Type,
+    op: LoadOrStore<'a>,
+) -> PccResult<()> {
+    if !flags.checked() {
+        return Ok(());
+    }
+
+    trace!("check_amode: {:?}", mem);
+
+    match *mem {
+        Amode::RegOffset { base, offset } => {
+            let base = get_fact_or_default(vcode, *base, bits);
+            let addr = fail_if_missing(ctx.offset(&base, bits, offset.into()))?;
+            check_access(ctx, &addr, ty, op)
+        }
+        Amode::SpOffset { .. } | Amode::Stack { .. } => {
+            // We trust ABI code (for now!) and no lowering rules
+            // lower input value accesses directly to these.
+            Ok(())
+        }
+    }
+}
+
+/// Check a raw load or store, with one of Pulley's addressing modes.
+///
+/// Raw loads and stores carry no `MemFlags`, so we can't tell from
+/// the instruction whether its access is meant to be checked. Checked
+/// host accesses are lowered to the `XLoad`/`XStore` pseudo-insts
+/// instead, so plain (`o32`) accesses are never checked. The
+/// bounds-checked (`g32`, `g32bne`) and null-trapping (`z`) modes are
+/// only used for accesses to Wasm linear memory, which always carry
+/// facts under PCC, so those are always checked.
+fn check_raw_access<P: PulleyTargetKind>(
+    ctx: &FactContext,
+    vcode: &VCode<InstAndKind<P>>,
+    bits: u16,
+    access: &RawMemAccess,
+) -> PccResult<()> {
+    trace!("check_raw_access: {:?}", access);
+
+    let addr = match access.addr {
+        RawAddr::O32 => return Ok(()),
+        RawAddr::Z(AddrZ::Base { addr, offset }) => {
+            let base = get_fact_or_default(vcode, *addr, bits);
+            fail_if_missing(ctx.offset(&base, bits, offset.into()))?
+        }
+        RawAddr::G32(AddrG32::RegisterBound {
+            host_heap_base,
+            host_heap_bound,
+            wasm_addr: _,
+            offset,
+        }) => {
+            let bound = get_fact_or_default(vcode, *host_heap_bound, bits);
+            bounded_addr(
+                ctx,
+                vcode,
+                bits,
+                host_heap_base,
+                &bound,
+                offset.into(),
+                access,
+            )?
+        }
+        RawAddr::G32Bne(AddrG32Bne::BoundNe {
+            host_heap_base,
+            host_heap_bound_addr,
+            host_heap_bound_offset,
+            wasm_addr: _,
+            offset,
+        }) => {
+            let bound_addr = get_fact_or_default(vcode, *host_heap_bound_addr, bits);
+            let bound_addr =
+                fail_if_missing(ctx.offset(&bound_addr, bits, host_heap_bound_offset.into()))?;
+            let bound_ty = Type::int(bits).unwrap();
+            let bound = ctx
+                .load(&bound_addr, bound_ty)?
+                .cloned()
+                .ok_or(PccError::MissingFact)?;
+            bounded_addr(
+                ctx,
+                vcode,
+                bits,
+                host_heap_base,
+                &bound,
+                offset.into(),
+                access,
+            )?
+        }
+    };
+
+    let ty = raw_access_ty(access);
+    let op = match access.reg {
+        Some(reg) if access.is_load => LoadOrStore::Load {
+            result_fact: vcode.vreg_fact(reg.into()),
+            from_bits: u16::try_from(ty.bits()).unwrap(),
+            to_bits: u16::try_from(ty.bits()).unwrap(),
+        },
+        Some(reg) => LoadOrStore::Store {
+            stored_fact: vcode.vreg_fact(reg.into()),
+        },
+        None if access.is_load => LoadOrStore::Load {
+            result_fact: None,
+            from_bits: u16::try_from(ty.bits()).unwrap(),
+            to_bits: u16::try_from(ty.bits()).unwrap(),
+        },
+        None => LoadOrStore::Store { stored_fact: None },
+    };
+    check_access(ctx, &addr, ty, op)
+}
+
+/// The address accessed by a bounds-checked (`g32`/`g32bne`) access:
+/// the access traps unless `zext32(wasm_addr) <= bound - offset -
+/// size`, so the index into the heap is within that range.
+fn bounded_addr<P: PulleyTargetKind>(
+    ctx: &FactContext,
+    vcode: &VCode<InstAndKind<P>>,
+    bits: u16,
+    base: XReg,
+    bound: &Fact,
+    offset: i64,
+    access: &RawMemAccess,
+) -> PccResult<Fact> {
+    let bound = exact_expr(bound).ok_or(PccError::UnsupportedFact)?;
+    let max = Expr::offset(&bound, -offset - i64::from(access.bytes)).ok_or(PccError::Overflow)?;
+    let index = Fact::DynamicRange {
+        bit_width: bits,
+        min: Expr::constant(0),
+        max,
+    };
+    let base = get_fact_or_default(vcode, *base, bits);
+    let addr = fail_if_missing(ctx.add(&base, &index, bits))?;
+    fail_if_missing(ctx.offset(&addr, bits, offset))
+}
+
+/// The type of a raw access: an integer of the access's size when
+/// it loads into or stores from an integer register, or otherwise
+/// the float or vector type of that size.
+fn raw_access_ty(access: &RawMemAccess) -> Type {
+    match (access.reg.map(|r| r.class()), access.bytes) {
+        (Some(RegClass::Int), bytes) => {
+            Type::int_with_byte_size(bytes.try_into().unwrap()).unwrap()
+        }
+        (_, 4) => F32,
+        (_, 8) => F64,
+        (_, 16) => I8X16,
+        (_, bytes) => Type::int_with_byte_size(bytes.try_into().unwrap()).unwrap(),
+    }
+}
+
+fn check_access<'a>(
+    ctx: &FactContext,
+    addr: &Fact,
+    ty: Type,
+    op: LoadOrStore<'a>,
+) -> PccResult<()> {
+    match op {
+        LoadOrStore::Load {
+            result_fact,
+            from_bits,
+            to_bits,
+        } => {
+            let loaded_fact = clamp_range(ctx, to_bits, from_bits, ctx.load(addr, ty)?.cloned())?;
+            trace!("checking a load: loaded_fact = {loaded_fact:?} result_fact = {result_fact:?}");
+            if ctx.subsumes_fact_optionals(loaded_fact.as_ref(), result_fact) {
+                Ok(())
+            } else {
+                Err(PccError::UnsupportedFact)
+            }
+        }
+        LoadOrStore::Store { stored_fact } => ctx.store(addr, ty, stored_fact),
+    }
+}
@@ -1,7 +1,9 @@
 //! Lowering rules for Riscv64.
 use crate::ir::Inst as IRInst;
+use crate::ir::pcc::{FactContext, PccResult};
 use crate::isa::riscv64::Riscv64Backend;
 use crate::isa::riscv64::inst::*;
+use crate::isa::riscv64::pcc;
 use crate::machinst::lower::*;
 use crate::machinst::*;
 pub mod isle;
@@ -31,5 +33,15 @@ impl LowerBackend for Riscv64Backend {
         None
     }
 
-    type FactFlowState = ();
+    fn check_fact(
+        &self,
+        ctx: &FactContext<'_>,
+        vcode: &mut VCode<Self::MInst>,
+        inst: InsnIndex,
+        state: &mut pcc::FactFlowState,
+    ) -> PccResult<()> {
+        pcc::check(ctx, vcode, inst, state)
+    }
+
+    type FactFlowState = pcc::FactFlowState;
 }
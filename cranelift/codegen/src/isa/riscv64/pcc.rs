@@ -0,0 +1,567 @@
+//! Proof-carrying code checking for RISC-V 64 VCode.
+
+use crate::ir::MemFlags;
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::pcc::*;
+use crate::ir::types::*;
+use crate::isa::riscv64::inst::regs::zero_reg;
+use crate::isa::riscv64::inst::*;
+use crate::isa::riscv64::lower::isle::generated_code::VecAMode;
+use crate::machinst::pcc::*;
+use crate::machinst::{InsnIndex, Reg, RegClass, VCode, Writable};
+use crate::trace;
+use std::collections::HashMap;
+
+/// A comparison whose result lives in a register: `lhs <kind> rhs`,
+/// where `lhs` and `rhs` are the facts on the compared values.
+#[derive(Clone, Debug)]
+struct Comparison {
+    kind: IntCC,
+    lhs: Fact,
+    rhs: Fact,
+}
+
+impl Comparison {
+    fn as_tuple(&self) -> (IntCC, &Fact, &Fact) {
+        (self.kind, &self.lhs, &self.rhs)
+    }
+
+    /// The comparison with the opposite outcome.
+    fn inverse(&self) -> Self {
+        Comparison {
+            kind: self.kind.complement(),
+            lhs: self.lhs.clone(),
+            rhs: self.rhs.clone(),
+        }
+    }
+}
+
+/// What we know about a register's value beyond its fact, derived
+/// from the instruction sequence that defined it.
+#[derive(Clone, Debug)]
+enum Derived {
+    /// The value is `1` if the comparison holds and `0` otherwise.
+    Flag(Comparison),
+    /// The value is all ones if the comparison holds and `0`
+    /// otherwise.
+    Mask(Comparison),
+    /// The value is the given register shifted left by the given
+    /// amount.
+    Shl(Reg, u8),
+}
+
+/// Flow-state between facts.
+///
+/// RISC-V has no condition flags, so comparisons, and the masks
+/// built from them for Spectre guards, are ordinary register values
+/// that typically carry no facts of their own. We track how such
+/// registers were computed within a block so that the uses of them
+/// can be checked. Since vregs are in SSA form, a register's entry
+/// remains valid for the rest of the block.
+#[derive(Clone, Debug, Default)]
+pub struct FactFlowState {
+    derived: HashMap<Reg, Derived>,
+}
+
+impl FactFlowState {
+    fn get(&self, reg: Reg) -> Option<&Derived> {
+        self.derived.get(&reg)
+    }
+
+    fn set(&mut self, reg: Writable<Reg>, derived: Derived) {
+        self.derived.insert(reg.to_reg(), derived);
+    }
+
+    /// The comparison whose outcome `reg` holds as a `0`/`1` flag,
+    /// either as tracked within this block or as stated by a
+    /// (checked) fact on the register itself.
+    fn flag(&self, vcode: &VCode<Inst>, reg: Reg) -> Option<Comparison> {
+        match self.get(reg) {
+            Some(Derived::Flag(cmp)) => Some(cmp.clone()),
+            _ => match vcode.vreg_fact(reg.into()) {
+                Some(Fact::Compare { kind, lhs, rhs }) => Some(Comparison {
+                    kind: *kind,
+                    lhs: exact_fact(lhs),
+                    rhs: exact_fact(rhs),
+                }),
+                _ => None,
+            },
+        }
+    }
+
+    fn mask(&self, reg: Reg) -> Option<Comparison> {
+        match self.get(reg) {
+            Some(Derived::Mask(cmp)) => Some(cmp.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn reg_fact(vcode: &VCode<Inst>, reg: Reg) -> Fact {
+    if reg == zero_reg() {
+        Fact::constant(64, 0)
+    } else {
+        get_fact_or_default(vcode, reg, 64)
+    }
+}
+
+pub(crate) fn check(
+    ctx: &FactContext,
+    vcode: &mut VCode<Inst>,
+    inst_idx: InsnIndex,
+    state: &mut FactFlowState,
+) -> PccResult<()> {
+    let inst = &vcode[inst_idx];
+    trace!("Checking facts on inst: {:?}", inst);
+
+    match *inst {
+        Inst::Args { .. } => {
+            // Defs on the args have "axiomatic facts": we trust the
+            // ABI code to pass through the values unharmed, so the
+            // facts given to us in the CLIF should still be true.
+            Ok(())
+        }
+
+        Inst::Load {
+            rd,
+            op,
+            flags,
+            ref from,
+        } => {
+            let access_ty = load_op_ty(op);
+            let result_fact = if rd.to_reg().class() == RegClass::Int {
+                vcode.vreg_fact(rd.to_reg().into())
+            } else {
+                None
+            };
+            let bits = u16::try_from(access_ty.bits()).unwrap();
+            check_addr(
+                ctx,
+                flags,
+                from,
+                vcode,
+                access_ty,
+                LoadOrStore::Load {
+                    result_fact,
+                    from_bits: bits,
+                    to_bits: bits,
+                },
+            )
+        }
+        Inst::Store {
+            ref to,
+            op,
+            flags,
+            src,
+        } => {
+            let access_ty = store_op_ty(op);
+            let stored_fact = if src.class() == RegClass::Int {
+                vcode.vreg_fact(src.into())
+            } else {
+                None
+            };
+            check_addr(
+                ctx,
+                flags,
+                to,
+                vcode,
+                access_ty,
+                LoadOrStore::Store { stored_fact },
+            )
+        }
+        Inst::VecLoad {
+            ref from, flags, ..
+        } => check_vec_addr(ctx, flags, from, vcode),
+        Inst::VecStore { ref to, flags, .. } => check_vec_addr(ctx, flags, to, vcode),
+
+        Inst::Lui { rd, ref imm } => {
+            // `lui` sign-extends its 32-bit result to 64 bits.
+            let constant = i64::from(imm.as_i32()) << 12;
+            check_constant(ctx, vcode, rd, 64, constant as u64)
+        }
+        Inst::LoadInlineConst { rd, ty, imm } if ty == I64 => {
+            check_constant(ctx, vcode, rd, 64, imm)
+        }
+        Inst::AluRRImm12 {
+            alu_op: op @ (AluOPRRI::Addi | AluOPRRI::Addiw | AluOPRRI::Slli | AluOPRRI::Srli),
+            rd,
+            rs,
+            imm12,
+        } if reg_fact(vcode, rs).as_const(64).is_some() => {
+            // Constants too wide for a single instruction are built
+            // up with `lui`/`addi`/shift sequences.
+            let input = reg_fact(vcode, rs).as_const(64).unwrap();
+            let imm = imm12.as_i16();
+            let constant = match op {
+                AluOPRRI::Addi => input.wrapping_add(imm as u64),
+                AluOPRRI::Addiw => i64::from((input as i32).wrapping_add(imm.into())) as u64,
+                AluOPRRI::Slli => input << (imm & 63),
+                AluOPRRI::Srli => input >> (imm & 63),
+                _ => unreachable!(),
+            };
+            check_constant(ctx, vcode, rd, 64, constant)
+        }
+
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Add,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+            clamp_range(ctx, 64, 64, ctx.add(rs1, rs2, 64))
+        }),
+        // The `*w` operations sign-extend their 32-bit result, so
+        // only the low 32 bits have a fact.
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Addw,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 32, rd, rs1, rs2, |rs1, rs2| {
+            clamp_range(ctx, 32, 32, ctx.add(rs1, rs2, 32))
+        }),
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Sub,
+            rd,
+            rs1,
+            rs2,
+        } if rs1 == zero_reg() => {
+            // `neg`: turns a `0`/`1` comparison flag into a mask.
+            if let Some(cmp) = state.flag(vcode, rs2) {
+                state.set(rd, Derived::Mask(cmp));
+            }
+            check_output(ctx, vcode, rd, &[], |_vcode| clamp_range(ctx, 64, 64, None))
+        }
+        Inst::AluRRR {
+            alu_op: AluOPRRR::Sub,
+            rd,
+            rs1,
+            rs2,
+        } => check_binop(ctx, vcode, 64, rd, rs1, rs2, |rs1, rs2| {
+            if let Some(k) = rs2.as_const(64) {
+                clamp_range(ctx, 64, 64, ctx.offset(rs1, 64, -(k as i64)))
+            } else {
+                clamp_range(ctx, 64, 64, None)
+            }
+        }),
+        Inst::AluRRR {
+            alu_op:
+                op @ (AluOPRRR::Adduw
+                | AluOPRRR::Sh1add
+                | AluOPRRR::Sh1adduw
+                | AluOPRRR::Sh2add
+                | AluOPRRR::Sh2adduw
+                | AluOPRRR::Sh3add
+                | AluOPRRR::Sh3adduw),
+            rd,
+            rs1,
+            rs2,
+        } => {
+            // Zba address generation: `rs2 + (zext?(rs1) << shift)`.
+            let (zext, shift) = match op {
+                AluOPRRR::Adduw => (true, 0),
+                AluOPRRR::Sh1add => (false, 1),
+                AluOPRRR::Sh1adduw => (true, 1),
+                AluOPRRR::Sh2add => (false, 2),
+                AluOPRRR::Sh2adduw => (true, 2),
+                AluOPRRR::Sh3add => (false, 3),
+                AluOPRRR::Sh3adduw => (true, 3),
+                _ => unreachable!(),
+            };
+            check_output(ctx, vcode, rd, &[], |vcode| {
+                let rs1 = reg_fact(vcode, rs1);
+                let rs2 = reg_fact(vcode, rs2);
+                let rs1 = if zext {
+                    fail_if_missing(ctx.uextend(&rs1, 32, 64))?
+                } else {
+                    rs1
+                };
+                let rs1 = fail_if_missing(ctx.shl(&rs1, 64, shift))?;
+                clamp_range(ctx, 64, 64, ctx.add(&rs2, &rs1, 64))
+            })
+        }
+        Inst::AluRRR {
+            alu_op: op @ (AluOPRRR::SltU | AluOPRRR::Sgtu),
+            rd,
+            rs1,
+            rs2,
+        } => {
+            let kind = match op {
+                AluOPRRR::SltU => IntCC::UnsignedLessThan,
+                AluOPRRR::Sgtu => IntCC::UnsignedGreaterThan,
+                _ => unreachable!(),
+            };
+            let cmp = Comparison {
+                kind,
+                lhs: reg_fact(vcode, rs1),
+                rhs: reg_fact(vcode, rs2),
+            };
+            check_compare_output(vcode, rd, cmp.as_tuple())?;
+            state.set(rd, Derived::Flag(cmp));
+            Ok(())
+        }
+        Inst::AluRRR {
+            alu_op: op @ (AluOPRRR::And | AluOPRRR::Andn),
+            rd,
+            rs1,
+            rs2,
+        } if state.mask(rs2).is_some() => {
+            // Masking a value with a comparison mask, as done by
+            // Spectre guards: the result is either the value or zero.
+            let cmp = state.mask(rs2).unwrap();
+            let (if_true, if_false) = match op {
+                AluOPRRR::And => (reg_fact(vcode, rs1), Fact::constant(64, 0)),
+                AluOPRRR::Andn => (Fact::constant(64, 0), reg_fact(vcode, rs1)),
+                _ => unreachable!(),
+            };
+            check_select(ctx, vcode, rd, cmp.as_tuple(), &if_true, &if_false)
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Xori,
+            rd,
+            rs,
+            imm12,
+        } if imm12.as_i16() == 1 && state.flag(vcode, rs).is_some() => {
+            // Inverting a comparison flag.
+            let cmp = state.flag(vcode, rs).unwrap().inverse();
+            check_compare_output(vcode, rd, cmp.as_tuple())?;
+            state.set(rd, Derived::Flag(cmp));
+            Ok(())
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Xori,
+            rd,
+            rs,
+            imm12,
+        } if imm12.as_i16() == -1 && state.mask(rs).is_some() => {
+            // Inverting a comparison mask.
+            let cmp = state.mask(rs).unwrap().inverse();
+            state.set(rd, Derived::Mask(cmp));
+            check_output(ctx, vcode, rd, &[], |_vcode| clamp_range(ctx, 64, 64, None))
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Addi,
+            rd,
+            rs,
+            imm12,
+        } => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, ctx.offset(rs, 64, imm12.as_i16().into()))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Addiw,
+            rd,
+            rs,
+            imm12,
+        } if imm12.as_i16() >= 0 => check_unop(ctx, vcode, 32, rd, rs, |rs| {
+            let imm = Fact::constant(32, imm12.as_i16() as u64);
+            clamp_range(ctx, 32, 32, ctx.add(rs, &imm, 32))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Slliw,
+            rd,
+            rs,
+            imm12,
+        } => check_unop(ctx, vcode, 32, rd, rs, |rs| {
+            clamp_range(ctx, 32, 32, ctx.shl(rs, 32, imm12.as_i16() as u16))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Slli,
+            rd,
+            rs,
+            imm12,
+        } => {
+            let amount = imm12.as_i16() as u8;
+            state.set(rd, Derived::Shl(rs, amount));
+            check_output(ctx, vcode, rd, &[], |vcode| {
+                let rs = reg_fact(vcode, rs);
+                clamp_range(ctx, 64, 64, ctx.shl(&rs, 64, amount.into()))
+            })
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Srli,
+            rd,
+            rs,
+            imm12,
+        } => {
+            let amount = imm12.as_i16() as u8;
+            check_output(ctx, vcode, rd, &[], |vcode| match state.get(rs) {
+                // `slli` followed by `srli` of the same amount is a
+                // zero-extension.
+                Some(Derived::Shl(src, shl_amount)) if *shl_amount == amount && amount > 0 => {
+                    let src = reg_fact(vcode, *src);
+                    clamp_range(ctx, 64, 64, ctx.uextend(&src, u16::from(64 - amount), 64))
+                }
+                _ => clamp_range(ctx, 64, 64, None),
+            })
+        }
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::SlliUw,
+            rd,
+            rs,
+            imm12,
+        } => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            let rs = fail_if_missing(ctx.uextend(rs, 32, 64))?;
+            clamp_range(ctx, 64, 64, ctx.shl(&rs, 64, imm12.as_i16() as u16))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Andi,
+            rd,
+            imm12,
+            ..
+        } if imm12.as_i16() >= 0 => check_output(ctx, vcode, rd, &[], |_vcode| {
+            // Masking with a non-negative immediate bounds the result
+            // by that immediate.
+            Ok(Some(Fact::Range {
+                bit_width: 64,
+                min: 0,
+                max: imm12.as_i16() as u64,
+            }))
+        }),
+        Inst::AluRRImm12 {
+            alu_op: AluOPRRI::Zexth,
+            rd,
+            rs,
+            ..
+        } => check_unop(ctx, vcode, 64, rd, rs, |rs| {
+            clamp_range(ctx, 64, 64, ctx.uextend(rs, 16, 64))
+        }),
+
+        Inst::Extend {
+            rd,
+            rn,
+            signed: false,
+            from_bits,
+            to_bits,
+        } => check_unop(ctx, vcode, 64, rd, rn, |rn| {
+            clamp_range(
+                ctx,
+                64,
+                to_bits.into(),
+                ctx.uextend(rn, from_bits.into(), to_bits.into()),
+            )
+        }),
+
+        Inst::Select {
+            ref dst,
+            ref condition,
+            ref x,
+            ref y,
+        } if dst.len() == 1 => {
+            let rd = dst.regs()[0];
+            let (x, y) = (x.regs()[0], y.regs()[0]);
+            let lhs = reg_fact(vcode, condition.rs1);
+            let rhs = reg_fact(vcode, condition.rs2);
+            // Selecting on a comparison flag being non-zero is
+            // selecting on the comparison itself.
+            let cmp = match condition.kind {
+                IntCC::NotEqual if condition.rs2 == zero_reg() => state.flag(vcode, condition.rs1),
+                IntCC::Equal if condition.rs2 == zero_reg() => {
+                    state.flag(vcode, condition.rs1).map(|cmp| cmp.inverse())
+                }
+                _ => None,
+            };
+            let cmp = cmp.unwrap_or(Comparison {
+                kind: condition.kind,
+                lhs,
+                rhs,
+            });
+            trace!("Select: {cmp:?}");
+            let (x, y) = (reg_fact(vcode, x), reg_fact(vcode, y));
+            check_select(ctx, vcode, rd, cmp.as_tuple(), &x, &y)
+        }
+
+        _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
+
+        _ => Ok(()),
+    }
+}
+
+fn load_op_ty(op: LoadOP) -> Type {
+    match op {
+        LoadOP::Lb | LoadOP::Lbu => I8,
+        LoadOP::Lh | LoadOP::Lhu => I16,
+        LoadOP::Lw | LoadOP::Lwu => I32,
+        LoadOP::Ld => I64,
+        LoadOP::Flh => F16,
+        LoadOP::Flw => F32,
+        LoadOP::Fld => F64,
+    }
+}
+
+fn store_op_ty(op: StoreOP) -> Type {
+    match op {
+        StoreOP::Sb => I8,
+        StoreOP::Sh => I16,
+        StoreOP::Sw => I32,
+        StoreOP::Sd => I64,
+        StoreOP::Fsh => F16,
+        StoreOP::Fsw => F32,
+        StoreOP::Fsd => F64,
+    }
+}
+
+fn check_addr<'a>(
+    ctx: &FactContext,
+    flags: MemFlags,
+    addr: &AMode,
+    vcode: &VCode<Inst>,
+    ty: Type,
+    op: LoadOrStore<'a>,
+) -> PccResult<()> {
+    if !flags.checked() {
+        return Ok(());
+    }
+
+    trace!("check_addr: {:?}", addr);
+
+    match *addr {
+        AMode::RegOffset(base, offset) => {
+            let base = reg_fact(vcode, base);
+            let addr = fail_if_missing(ctx.offset(&base, 64, offset))?;
+            match op {
+                LoadOrStore::Load {
+                    result_fact,
+                    from_bits,
+                    to_bits,
+                } => {
+                    let loaded_fact =
+                        clamp_range(ctx, to_bits, from_bits, ctx.load(&addr, ty)?.cloned())?;
+                    trace!(
+                        "checking a load: loaded_fact = {loaded_fact:?} result_fact = {result_fact:?}"
+                    );
+                    if ctx.subsumes_fact_optionals(loaded_fact.as_ref(), result_fact) {
+                        Ok(())
+                    } else {
+                        Err(PccError::UnsupportedFact)
+                    }
+                }
+                LoadOrStore::Store { stored_fact } => ctx.store(&addr, ty, stored_fact),
+            }
+        }
+        AMode::Const(..) | AMode::Label(..) => {
+            // Always accept: labels and constants must be within the
+            // generated code (else they won't be resolved).
+            Ok(())
+        }
+        AMode::SPOffset(..)
+        | AMode::FPOffset(..)
+        | AMode::SlotOffset(..)
+        | AMode::IncomingArg(..) => {
+            // We trust ABI code (for now!) and no lowering rules
+            // lower input value accesses directly to these.
+            Ok(())
+        }
+    }
+}
+
+fn check_vec_addr(
+    _ctx: &FactContext,
+    flags: MemFlags,
+    _addr: &VecAMode,
+    _vcode: &VCode<Inst>,
+) -> PccResult<()> {
+    if !flags.checked() {
+        return Ok(());
+    }
+    Err(PccError::UnimplementedInst)
+}
@@ -1,9 +1,11 @@
 //! Lowering rules for S390x.
 
 use crate::ir::Inst as IRInst;
+use crate::ir::pcc::{FactContext, PccResult};
 use crate::isa::s390x::S390xBackend;
 use crate::isa::s390x::inst::Inst;
-use crate::machinst::{InstOutput, Lower, LowerBackend, MachLabel};
+use crate::isa::s390x::pcc;
+use crate::machinst::{InsnIndex, InstOutput, Lower, LowerBackend, MachLabel, VCode};
 
 pub mod isle;
 
@@ -26,5 +28,15 @@ impl LowerBackend for S390xBackend {
         isle::lower_branch(ctx, self, ir_inst, targets)
     }
 
-    type FactFlowState = ();
+    fn check_fact(
+        &self,
+        ctx: &FactContext<'_>,
+        vcode: &mut VCode<Self::MInst>,
+        inst: InsnIndex,
+        state: &mut pcc::FactFlowState,
+    ) -> PccResult<()> {
+        pcc::check(ctx, vcode, inst, state)
+    }
+
+    type FactFlowState = pcc::FactFlowState;
 }
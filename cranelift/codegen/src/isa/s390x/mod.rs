@@ -22,6 +22,7 @@ use target_lexicon::{Architecture, Triple};
 mod abi;
 pub(crate) mod inst;
 mod lower;
+mod pcc;
 mod settings;
 
 use self::inst::EmitInfo;
@@ -0,0 +1,518 @@
+//! Proof-carrying code checking for s390x VCode.
+
+use crate::ir::condcodes::IntCC;
+use crate::ir::pcc::*;
+use crate::ir::types::*;
+use crate::isa::s390x::inst::regs::zero_reg;
+use crate::isa::s390x::inst::{ALUOp, CmpOp, Cond, Inst, MemArg, ShiftOp};
+use crate::machinst::pcc::*;
+use crate::machinst::{InsnIndex, Reg, VCode};
+use crate::trace;
+
+/// Flow-state between facts.
+#[derive(Clone, Debug, Default)]
+pub struct FactFlowState {
+    /// The facts on the operands of the last unsigned comparison, if
+    /// it was the immediately preceding instruction.
+    cmp_flags: Option<(Fact, Fact)>,
+}
+
+/// The unsigned comparison that a condition-code mask tests for
+/// after an unsigned (logical) compare: CC 0 means equal, CC 1 low
+/// and CC 2 high.
+fn unsigned_cond(cond: Cond) -> Option<IntCC> {
+    match cond.bits() {
+        0b0010 => Some(IntCC::UnsignedGreaterThan),
+        0b1010 => Some(IntCC::UnsignedGreaterThanOrEqual),
+        0b0100 => Some(IntCC::UnsignedLessThan),
+        0b1100 => Some(IntCC::UnsignedLessThanOrEqual),
+        _ => None,
+    }
+}
+
+/// The type of a vector lane of `size` bits.
+fn lane_ty(size: u32) -> Type {
+    Type::int(u16::try_from(size).unwrap()).unwrap()
+}
+
+fn reg_fact(vcode: &VCode<Inst>, reg: Reg) -> Fact {
+    get_fact_or_default(vcode, reg, 64)
+}
+
+pub(crate) fn check(
+    ctx: &FactContext,
+    vcode: &mut VCode<Inst>,
+    inst_idx: InsnIndex,
+    state: &mut FactFlowState,
+) -> PccResult<()> {
+    let inst = &vcode[inst_idx];
+    trace!("Checking facts on inst: {:?}", inst);
+
+    // As on AArch64, we only persist flag state for one instruction.
+    let cmp_flags = state.cmp_flags.take();
+    trace!(" * with cmp_flags = {cmp_flags:?}");
+
+    match *inst {
+        Inst::Args { .. } => {
+            // Defs on the args have "axiomatic facts": we trust the
+            // ABI code to pass through the values unharmed, so the
+            // facts given to us in the CLIF should still be true.
+            Ok(())
+        }
+
+        Inst::Load32 { rd, ref mem } => check_load(ctx, vcode, Some(rd.to_reg()), mem, I32),
+        Inst::Load32ZExt8 { rd, ref mem } | Inst::Load32SExt8 { rd, ref mem } => {
+            check_load(ctx, vcode, Some(rd.to_reg()), mem, I8)
+        }
+        Inst::Load32ZExt16 { rd, ref mem } | Inst::Load32SExt16 { rd, ref mem } => {
+            check_load(ctx, vcode, Some(rd.to_reg()), mem, I16)
+        }
+        Inst::Load64 { rd, ref mem } => check_load(ctx, vcode, Some(rd.to_reg()), mem, I64),
+        Inst::Load64ZExt8 { rd, ref mem } | Inst::Load64SExt8 { rd, ref mem } => {
+            check_load(ctx, vcode, Some(rd.to_reg()), mem, I8)
+        }
+        Inst::Load64ZExt16 { rd, ref mem } | Inst::Load64SExt16 { rd, ref mem } => {
+            check_load(ctx, vcode, Some(rd.to_reg()), mem, I16)
+        }
+        Inst::Load64ZExt32 { rd, ref mem } | Inst::Load64SExt32 { rd, ref mem } => {
+            check_load(ctx, vcode, Some(rd.to_reg()), mem, I32)
+        }
+        Inst::LoadRev16 { rd, ref mem }
+        | Inst::LoadRev32 { rd, ref mem }
+        | Inst::LoadRev64 { rd, ref mem } => {
+            // A byte-reversed load does not produce the value stored
+            // in memory, so it can't be given that value's fact.
+            if has_fact(vcode, rd.to_reg()) {
+                return Err(PccError::UnsupportedFact);
+            }
+            let ty = match *inst {
+                Inst::LoadRev16 { .. } => I16,
+                Inst::LoadRev32 { .. } => I32,
+                _ => I64,
+            };
+            check_load(ctx, vcode, None, mem, ty)
+        }
+        Inst::VecLoad { ref mem, .. }
+        | Inst::VecLoadRev { ref mem, .. }
+        | Inst::VecLoadByte16Rev { ref mem, .. }
+        | Inst::VecLoadByte32Rev { ref mem, .. }
+        | Inst::VecLoadByte64Rev { ref mem, .. }
+        | Inst::VecLoadElt16Rev { ref mem, .. }
+        | Inst::VecLoadElt32Rev { ref mem, .. }
+        | Inst::VecLoadElt64Rev { ref mem, .. } => check_load(ctx, vcode, None, mem, I8X16),
+        Inst::VecLoadReplicate { size, ref mem, .. }
+        | Inst::VecLoadReplicateRev { size, ref mem, .. }
+        | Inst::VecLoadLane { size, ref mem, .. }
+        | Inst::VecLoadLaneUndef { size, ref mem, .. }
+        | Inst::VecLoadLaneRev { size, ref mem, .. }
+        | Inst::VecLoadLaneRevUndef { size, ref mem, .. } => {
+            check_load(ctx, vcode, None, mem, lane_ty(size))
+        }
+
+        Inst::Store8 { rd, ref mem } => check_store(ctx, vcode, Some(rd), mem, I8),
+        Inst::Store16 { rd, ref mem } => check_store(ctx, vcode, Some(rd), mem, I16),
+        Inst::Store32 { rd, ref mem } => check_store(ctx, vcode, Some(rd), mem, I32),
+        Inst::Store64 { rd, ref mem } => check_store(ctx, vcode, Some(rd), mem, I64),
+        Inst::StoreImm8 { ref mem, .. } => check_store(ctx, vcode, None, mem, I8),
+        Inst::StoreImm16 { ref mem, .. } => check_store(ctx, vcode, None, mem, I16),
+        Inst::StoreImm32SExt16 { ref mem, .. } => check_store(ctx, vcode, None, mem, I32),
+        Inst::StoreImm64SExt16 { ref mem, .. } => check_store(ctx, vcode, None, mem, I64),
+        Inst::StoreRev16 { ref mem, .. } => check_store(ctx, vcode, None, mem, I16),
+        Inst::StoreRev32 { ref mem, .. } => check_store(ctx, vcode, None, mem, I32),
+        Inst::StoreRev64 { ref mem, .. } => check_store(ctx, vcode, None, mem, I64),
+        Inst::VecStore { ref mem, .. }
+        | Inst::VecStoreRev { ref mem, .. }
+        | Inst::VecStoreByte16Rev { ref mem, .. }
+        | Inst::VecStoreByte32Rev { ref mem, .. }
+        | Inst::VecStoreByte64Rev { ref mem, .. }
+        | Inst::VecStoreElt16Rev { ref mem, .. }
+        | Inst::VecStoreElt32Rev { ref mem, .. }
+        | Inst::VecStoreElt64Rev { ref mem, .. } => check_store(ctx, vcode, None, mem, I8X16),
+        Inst::VecStoreLane { size, ref mem, .. } | Inst::VecStoreLaneRev { size, ref mem, .. } => {
+            check_store(ctx, vcode, None, mem, lane_ty(size))
+        }
+
+        Inst::Mov64 { rd, rm } => check_unop(ctx, vcode, 64, rd, rm, |rm| {
+            clamp_range(ctx, 64, 64, Some(rm.clone()))
+        }),
+        Inst::Mov64SImm16 { rd, imm } => check_constant(ctx, vcode, rd, 64, imm as i64 as u64),
+        Inst::Mov64SImm32 { rd, imm } => check_constant(ctx, vcode, rd, 64, imm as i64 as u64),
+        // 32-bit operations leave the high half of the register
+        // unchanged, so their results only have 32-bit facts.
+        Inst::Mov32Imm { rd, imm } => check_constant(ctx, vcode, rd, 32, imm.into()),
+        Inst::Mov32SImm16 { rd, imm } => {
+            check_constant(ctx, vcode, rd, 32, u64::from(imm as i32 as u32))
+        }
+        Inst::Mov64UImm16Shifted { rd, imm } => {
+            let constant = u64::from(imm.bits) << (imm.shift * 16);
+            check_constant(ctx, vcode, rd, 64, constant)
+        }
+        Inst::Mov64UImm32Shifted { rd, imm } => {
+            let constant = u64::from(imm.bits) << (imm.shift * 32);
+            check_constant(ctx, vcode, rd, 64, constant)
+        }
+        Inst::Insert64UImm16Shifted { rd, ri, imm } => {
+            let mask = 0xffff << (imm.shift * 16);
+            let constant = u64::from(imm.bits) << (imm.shift * 16);
+            check_insert(ctx, vcode, rd, ri, mask, constant)
+        }
+        Inst::Insert64UImm32Shifted { rd, ri, imm } => {
+            let mask = 0xffff_ffff << (imm.shift * 32);
+            let constant = u64::from(imm.bits) << (imm.shift * 32);
+            check_insert(ctx, vcode, rd, ri, mask, constant)
+        }
+
+        Inst::AluRRR {
+            alu_op: ALUOp::Add64 | ALUOp::AddLogical64,
+            rd,
+            rn,
+            rm,
+        }
+        | Inst::AluRR {
+            alu_op: ALUOp::Add64 | ALUOp::AddLogical64,
+            rd,
+            ri: rn,
+            rm,
+        } => check_binop(ctx, vcode, 64, rd, rn, rm, |rn, rm| {
+            clamp_range(ctx, 64, 64, ctx.add(rn, rm, 64))
+        }),
+        Inst::AluRRR {
+            alu_op: ALUOp::Add32 | ALUOp::AddLogical32,
+            rd,
+            rn,
+            rm,
+        }
+        | Inst::AluRR {
+            alu_op: ALUOp::Add32 | ALUOp::AddLogical32,
+            rd,
+            ri: rn,
+            rm,
+        } => check_binop(ctx, vcode, 32, rd, rn, rm, |rn, rm| {
+            clamp_range(ctx, 32, 32, ctx.add(rn, rm, 32))
+        }),
+        Inst::AluRR {
+            alu_op: ALUOp::AddLogical64Ext32,
+            rd,
+            ri,
+            rm,
+        } => check_binop(ctx, vcode, 64, rd, ri, rm, |ri, rm| {
+            let rm = fail_if_missing(ctx.uextend(rm, 32, 64))?;
+            clamp_range(ctx, 64, 64, ctx.add(ri, &rm, 64))
+        }),
+        Inst::AluRX {
+            alu_op: ALUOp::Add64 | ALUOp::AddLogical64,
+            rd,
+            ri,
+            ref mem,
+        } => {
+            let loaded = load_fact(ctx, vcode, mem, I64)?;
+            check_output(ctx, vcode, rd, &[ri], |vcode| {
+                let ri = reg_fact(vcode, ri);
+                clamp_range(ctx, 64, 64, ctx.add(&ri, &loaded, 64))
+            })
+        }
+        Inst::AluRRSImm16 {
+            alu_op: ALUOp::Add64,
+            rd,
+            rn,
+            imm,
+        }
+        | Inst::AluRSImm16 {
+            alu_op: ALUOp::Add64,
+            rd,
+            ri: rn,
+            imm,
+        } => check_unop(ctx, vcode, 64, rd, rn, |rn| {
+            clamp_range(ctx, 64, 64, ctx.offset(rn, 64, imm.into()))
+        }),
+        Inst::AluRRSImm16 {
+            alu_op: ALUOp::Add32,
+            rd,
+            rn,
+            imm,
+        }
+        | Inst::AluRSImm16 {
+            alu_op: ALUOp::Add32,
+            rd,
+            ri: rn,
+            imm,
+        } if imm >= 0 => check_unop(ctx, vcode, 32, rd, rn, |rn| {
+            let imm = Fact::constant(32, imm as u64);
+            clamp_range(ctx, 32, 32, ctx.add(rn, &imm, 32))
+        }),
+        Inst::AluRSImm32 {
+            alu_op: ALUOp::Add64,
+            rd,
+            ri,
+            imm,
+        } => check_unop(ctx, vcode, 64, rd, ri, |ri| {
+            clamp_range(ctx, 64, 64, ctx.offset(ri, 64, imm.into()))
+        }),
+        Inst::AluRUImm32 {
+            alu_op: op @ (ALUOp::AddLogical64 | ALUOp::SubLogical64),
+            rd,
+            ri,
+            imm,
+        } => check_unop(ctx, vcode, 64, rd, ri, |ri| {
+            let imm = i64::from(imm);
+            let offset = if op == ALUOp::AddLogical64 { imm } else { -imm };
+            clamp_range(ctx, 64, 64, ctx.offset(ri, 64, offset))
+        }),
+        Inst::AluRRR {
+            alu_op: ALUOp::Sub64 | ALUOp::SubLogical64,
+            rd,
+            rn,
+            rm,
+        }
+        | Inst::AluRR {
+            alu_op: ALUOp::Sub64 | ALUOp::SubLogical64,
+            rd,
+            ri: rn,
+            rm,
+        } => check_binop(ctx, vcode, 64, rd, rn, rm, |rn, rm| {
+            match rm.as_const(64).and_then(|k| i64::try_from(k).ok()) {
+                Some(k) => clamp_range(ctx, 64, 64, ctx.offset(rn, 64, -k)),
+                None => clamp_range(ctx, 64, 64, None),
+            }
+        }),
+
+        Inst::ShiftRR {
+            shift_op: ShiftOp::LShL64,
+            rd,
+            rn,
+            shift_imm,
+            shift_reg,
+        } if shift_reg == zero_reg() => check_unop(ctx, vcode, 64, rd, rn, |rn| {
+            clamp_range(ctx, 64, 64, ctx.shl(rn, 64, shift_imm.into()))
+        }),
+
+        Inst::ShiftRR {
+            shift_op: ShiftOp::LShL32,
+            rd,
+            rn,
+            shift_imm,
+            shift_reg,
+        } if shift_reg == zero_reg() => check_unop(ctx, vcode, 32, rd, rn, |rn| {
+            clamp_range(ctx, 32, 32, ctx.shl(rn, 32, shift_imm.into()))
+        }),
+
+        Inst::Extend {
+            rd,
+            rn,
+            signed: false,
+            from_bits,
+            to_bits,
+        } => check_unop(ctx, vcode, 64, rd, rn, |rn| {
+            clamp_range(
+                ctx,
+                64,
+                to_bits.into(),
+                ctx.uextend(rn, from_bits.into(), to_bits.into()),
+            )
+        }),
+
+        Inst::LoadAddr { rd, ref mem } => {
+            let addr = address_fact(ctx, vcode, mem)?;
+            check_output(ctx, vcode, rd, &[], |_vcode| clamp_range(ctx, 64, 64, addr))
+        }
+
+        Inst::CmpRR {
+            op: CmpOp::CmpL64,
+            rn,
+            rm,
+        } => {
+            state.cmp_flags = Some((reg_fact(vcode, rn), reg_fact(vcode, rm)));
+            Ok(())
+        }
+        Inst::CmpRR {
+            op: CmpOp::CmpL64Ext32,
+            rn,
+            rm,
+        } => {
+            let rm = fail_if_missing(ctx.uextend(&reg_fact(vcode, rm), 32, 64))?;
+            state.cmp_flags = Some((reg_fact(vcode, rn), rm));
+            Ok(())
+        }
+        Inst::CmpRUImm32 {
+            op: CmpOp::CmpL64,
+            rn,
+            imm,
+        } => {
+            state.cmp_flags = Some((reg_fact(vcode, rn), Fact::constant(64, imm.into())));
+            Ok(())
+        }
+        Inst::CmpRX {
+            op: CmpOp::CmpL64,
+            rn,
+            ref mem,
+        } => {
+            let loaded = load_fact(ctx, vcode, mem, I64)?;
+            state.cmp_flags = Some((reg_fact(vcode, rn), loaded));
+            Ok(())
+        }
+
+        // Note that a conditional move replaces `ri` with `rm` (or
+        // `imm`) when the condition holds.
+        Inst::CMov64 { rd, cond, ri, rm }
+            if cmp_flags.is_some() && unsigned_cond(cond).is_some() =>
+        {
+            let (cmp_lhs, cmp_rhs) = cmp_flags.unwrap();
+            let kind = unsigned_cond(cond).unwrap();
+            trace!("CMov64: cmp {kind:?} ({cmp_lhs:?}, {cmp_rhs:?})");
+            let (rm, ri) = (reg_fact(vcode, rm), reg_fact(vcode, ri));
+            check_select(ctx, vcode, rd, (kind, &cmp_lhs, &cmp_rhs), &rm, &ri)
+        }
+        Inst::CMov64SImm16 { rd, cond, ri, imm }
+            if cmp_flags.is_some() && unsigned_cond(cond).is_some() =>
+        {
+            let (cmp_lhs, cmp_rhs) = cmp_flags.unwrap();
+            let kind = unsigned_cond(cond).unwrap();
+            trace!("CMov64SImm16: cmp {kind:?} ({cmp_lhs:?}, {cmp_rhs:?})");
+            let imm = Fact::constant(64, imm as i64 as u64);
+            let ri = reg_fact(vcode, ri);
+            check_select(ctx, vcode, rd, (kind, &cmp_lhs, &cmp_rhs), &imm, &ri)
+        }
+
+        _ if vcode.inst_defines_facts(inst_idx) => Err(PccError::UnsupportedFact),
+
+        _ => Ok(()),
+    }
+}
+
+/// Check an insert of `constant` into the bits of `ri` selected by
+/// `mask`, as used to build up wide constants.
+fn check_insert(
+    ctx: &FactContext,
+    vcode: &mut VCode<Inst>,
+    rd: crate::machinst::Writable<Reg>,
+    ri: Reg,
+    mask: u64,
+    constant: u64,
+) -> PccResult<()> {
+    let input = reg_fact(vcode, ri);
+    if let Some(input_constant) = input.as_const(64) {
+        let constant = (input_constant & !mask) | constant;
+        check_constant(ctx, vcode, rd, 64, constant)
+    } else {
+        check_output(ctx, vcode, rd, &[], |_vcode| {
+            Ok(Some(Fact::max_range_for_width(64)))
+        })
+    }
+}
+
+/// The fact on the address computed by `mem`, or `None` if `mem`
+/// isn't a register-based address.
+fn address_fact(ctx: &FactContext, vcode: &VCode<Inst>, mem: &MemArg) -> PccResult<Option<Fact>> {
+    let (base, index, disp) = match *mem {
+        MemArg::BXD12 {
+            base, index, disp, ..
+        } => (base, index, i64::from(disp.bits())),
+        MemArg::BXD20 {
+            base, index, disp, ..
+        } => {
+            // Sign-extend the 20-bit displacement.
+            let disp = ((disp.bits() << 12) as i32) >> 12;
+            (base, index, i64::from(disp))
+        }
+        MemArg::RegOffset { reg, off, .. } => (reg, zero_reg(), off),
+        _ => return Ok(None),
+    };
+    // Register 0 as a base or index means "none".
+    let addr = match (base == zero_reg(), index == zero_reg()) {
+        (false, true) => reg_fact(vcode, base),
+        (true, false) => reg_fact(vcode, index),
+        (false, false) => {
+            fail_if_missing(ctx.add(&reg_fact(vcode, base), &reg_fact(vcode, index), 64))?
+        }
+        (true, true) => Fact::constant(64, 0),
+    };
+    fail_if_missing(ctx.offset(&addr, 64, disp)).map(Some)
+}
+
+/// Check a load of `ty` from `mem` and return the fact on the loaded
+/// value.
+fn load_fact(ctx: &FactContext, vcode: &VCode<Inst>, mem: &MemArg, ty: Type) -> PccResult<Fact> {
+    let bits = u16::try_from(ty.bits()).unwrap();
+    let default = Fact::max_range_for_width(bits);
+    if !mem.get_flags().checked() {
+        return Ok(default);
+    }
+    trace!("load_fact: {:?}", mem);
+    match address_fact(ctx, vcode, mem)? {
+        Some(addr) => {
+            Ok(clamp_range(ctx, 64, bits, ctx.load(&addr, ty)?.cloned())?.unwrap_or(default))
+        }
+        // Always accept: see `check_addr`.
+        None => Ok(default),
+    }
+}
+
+fn check_load(
+    ctx: &FactContext,
+    vcode: &VCode<Inst>,
+    rd: Option<Reg>,
+    mem: &MemArg,
+    ty: Type,
+) -> PccResult<()> {
+    let result_fact = rd.and_then(|rd| vcode.vreg_fact(rd.into()));
+    let bits = u16::try_from(ty.bits()).unwrap();
+    check_addr(
+        ctx,
+        mem,
+        vcode,
+        ty,
+        LoadOrStore::Load {
+            result_fact,
+            from_bits: bits,
+            to_bits: bits,
+        },
+    )
+}
+
+fn check_store(
+    ctx: &FactContext,
+    vcode: &VCode<Inst>,
+    rd: Option<Reg>,
+    mem: &MemArg,
+    ty: Type,
+) -> PccResult<()> {
+    let stored_fact = rd.and_then(|rd| vcode.vreg_fact(rd.into()));
+    check_addr(ctx, mem, vcode, ty, LoadOrStore::Store { stored_fact })
+}
+
+fn check_addr<'a>(
+    ctx: &FactContext,
+    mem: &MemArg,
+    vcode: &VCode<Inst>,
+    ty: Type,
+    op: LoadOrStore<'a>,
+) -> PccResult<()> {
+    if !mem.get_flags().checked() {
+        return Ok(());
+    }
+
+    trace!("check_addr: {:?}", mem);
+
+    let Some(addr) = address_fact(ctx, vcode, mem)? else {
+        // Always accept: labels, constants and symbols must be
+        // within the generated code or resolved by the linker, and we
+        // trust ABI code (for now!) for the stack-based modes.
+        return Ok(());
+    };
+    match op {
+        LoadOrStore::Load {
+            result_fact,
+            from_bits,
+            to_bits,
+        } => {
+            let loaded_fact = clamp_range(ctx, to_bits, from_bits, ctx.load(&addr, ty)?.cloned())?;
+            trace!("checking a load: loaded_fact = {loaded_fact:?} result_fact = {result_fact:?}");
+            if ctx.subsumes_fact_optionals(loaded_fact.as_ref(), result_fact) {
+                Ok(())
+            } else {
+                Err(PccError::UnsupportedFact)
+            }
+        }
+        LoadOrStore::Store { stored_fact } => ctx.store(&addr, ty, stored_fact),
+    }
+}
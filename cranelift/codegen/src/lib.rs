@@ -50,6 +50,7 @@ pub mod data_value;
 pub mod dbg;
 pub mod dominator_tree;
 pub mod flowgraph;
+pub mod inline;
 pub mod ir;
 pub mod isa;
 pub mod loop_analysis;
@@ -79,12 +80,14 @@ mod egraph;
 mod inst_predicates;
 mod isle_prelude;
 mod legalizer;
+mod loop_opts;
 mod nan_canonicalization;
 mod opts;
 mod ranges;
 mod remove_constant_phis;
 mod result;
 mod scoped_hash_map;
+mod slp;
 mod take_and_replace;
 mod unreachable_code;
 mod value_label;
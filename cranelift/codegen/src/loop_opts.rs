@@ -0,0 +1,140 @@
+//! Loop optimizations run ahead of the egraph pass.
+//!
+//! The egraph pass already hoists pure values out of loops while elaborating,
+//! but it leaves the side-effecting skeleton, and the shape of the CFG, as it
+//! found them. The passes here fill in the rest:
+//!
+//! - `licm` hoists loads out of loops when no instruction in the loop may
+//!   write the memory they read, according to the abstract state categories
+//!   of alias analysis.
+//!
+//! - `unroll` unrolls single-block loops with a constant trip count, fully if
+//!   they are small enough and otherwise by a factor dividing the trip count,
+//!   so that no exit test is needed in all but the last copy of the body.
+//!
+//! - `strength_reduce` replaces multiplications of induction variables with
+//!   new induction variables that are incremented by the product of the step
+//!   instead.
+//!
+//! They are only run with the `enable_loop_optimizations` setting and when
+//! optimizing for speed; unrolling, which grows code, only at
+//! `opt_level=speed`.
+
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{Block, Function, InstructionData, Opcode, Value, ValueDef};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::settings::{Flags, OptLevel};
+use crate::timing;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+mod licm;
+mod strength_reduce;
+mod unroll;
+
+/// Run the loop optimizations on `func`, returning whether it changed.
+///
+/// The control flow graph and dominator tree must be valid on entry. They
+/// are not necessarily valid anymore on exit if `func` changed.
+pub(crate) fn do_loop_opts(
+    func: &mut Function,
+    cfg: &mut ControlFlowGraph,
+    domtree: &mut DominatorTree,
+    loop_analysis: &mut LoopAnalysis,
+    flags: &Flags,
+) -> bool {
+    let _tt = timing::loop_opts();
+
+    // The loop optimizations don't maintain proof-carrying code facts.
+    if flags.enable_pcc() {
+        return false;
+    }
+
+    loop_analysis.compute(func, cfg, domtree);
+    let mut changed = licm::hoist_invariant_loads(func, cfg, domtree, loop_analysis);
+
+    if flags.opt_level() == OptLevel::Speed && unroll::unroll_loops(func, cfg) {
+        changed = true;
+        cfg.compute(func);
+        domtree.compute(func, cfg);
+        loop_analysis.compute(func, cfg, domtree);
+    }
+
+    if strength_reduce::reduce_induction_variables(func, cfg, domtree, loop_analysis) {
+        changed = true;
+        func.dfg.resolve_all_aliases();
+    }
+
+    changed
+}
+
+/// The loops of the function, innermost first.
+fn loops_innermost_first(loop_analysis: &LoopAnalysis) -> Vec<Loop> {
+    let mut loops: Vec<Loop> = loop_analysis.loops().collect();
+    loops.sort_by_key(|&lp| Reverse(loop_analysis.loop_level(loop_analysis.loop_header(lp))));
+    loops
+}
+
+/// The blocks of `lp`, in reverse post-order.
+fn loop_blocks(domtree: &DominatorTree, loop_analysis: &LoopAnalysis, lp: Loop) -> Vec<Block> {
+    domtree
+        .cfg_rpo()
+        .copied()
+        .filter(|&block| loop_analysis.is_in_loop(block, lp))
+        .collect()
+}
+
+/// The preheader of `lp`: the only block outside of the loop that branches to
+/// its header, if it does so with an unconditional jump.
+///
+/// The preheader dominates the loop, and code placed at its end runs exactly
+/// once on every entry to the loop.
+fn preheader(
+    func: &Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+) -> Option<Block> {
+    let header = loop_analysis.loop_header(lp);
+    let mut entries = cfg
+        .pred_iter(header)
+        .filter(|pred| !loop_analysis.is_in_loop(pred.block, lp));
+    let entry = entries.next()?;
+    if entries.next().is_some() {
+        return None;
+    }
+    match func.dfg.insts[entry.inst] {
+        InstructionData::Jump { .. } => Some(entry.block),
+        _ => None,
+    }
+}
+
+/// Is `value` defined outside of `lp`?
+fn is_defined_outside(
+    func: &Function,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    value: Value,
+) -> bool {
+    let block = match func.dfg.value_def(value) {
+        ValueDef::Result(inst, _) => func.layout.inst_block(inst),
+        ValueDef::Param(block, _) => Some(block),
+        ValueDef::Union(..) => None,
+    };
+    block.is_some_and(|block| !loop_analysis.is_in_loop(block, lp))
+}
+
+/// The constant `value` is defined as, if it is the result of an `iconst`.
+fn iconst_value(func: &Function, value: Value) -> Option<i64> {
+    match func.dfg.value_def(value) {
+        ValueDef::Result(inst, _) => match func.dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => Some(imm.bits()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
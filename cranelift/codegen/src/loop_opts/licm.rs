@@ -0,0 +1,124 @@
+//! Hoisting of loop-invariant loads and traps.
+//!
+//! A load is moved to the preheader of its loop when its address is defined
+//! outside of the loop and nothing in the loop may write its category of
+//! abstract state. That alone makes it produce the same value on every
+//! iteration, but not that it may run at the preheader: the load must also
+//! either be `can_move`, or run on every entry to the loop before anything
+//! with a side effect, i.e. be in the header with only pure instructions
+//! before it. In the latter case the load traps, if it does, just as it would
+//! have on the first iteration.
+//!
+//! The same goes for instructions whose only side effect is to trap, such as
+//! the bounds checks of accesses at a loop-invariant address: when they run
+//! first in the loop, trapping in the preheader instead is indistinguishable,
+//! and if they don't trap there they won't on later iterations either.
+//!
+//! Pure instructions whose arguments are all defined outside of the loop are
+//! moved along, so that the addresses they compute are available in the
+//! preheader. The egraph pass places pure instructions itself, so this
+//! doesn't otherwise affect them.
+
+use super::{is_defined_outside, loop_blocks, loops_innermost_first, preheader};
+use crate::alias_analysis::LastStores;
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::ControlFlowGraph;
+use crate::inst_predicates::is_pure_for_egraph;
+use crate::ir::{Block, Function, Inst, InstructionData, Opcode};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::trace;
+
+/// Hoist the invariant loads of every loop with a preheader into it,
+/// returning whether anything was hoisted.
+///
+/// Inner loops are visited first, so that loads hoisted into a preheader
+/// inside of an enclosing loop can then be hoisted out of that loop too.
+pub(crate) fn hoist_invariant_loads(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+) -> bool {
+    let mut changed = false;
+    for lp in loops_innermost_first(loop_analysis) {
+        if let Some(preheader) = preheader(func, cfg, loop_analysis, lp) {
+            changed |= hoist_from_loop(func, domtree, loop_analysis, lp, preheader);
+        }
+    }
+    changed
+}
+
+fn hoist_from_loop(
+    func: &mut Function,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    preheader: Block,
+) -> bool {
+    let header = loop_analysis.loop_header(lp);
+    let blocks = loop_blocks(domtree, loop_analysis, lp);
+
+    // The categories of abstract state written in the loop. Unlike alias
+    // analysis, which must not move accesses across them, traps don't count:
+    // they don't write memory.
+    let mut stores = LastStores::default();
+    for &block in &blocks {
+        for inst in func.layout.block_insts(block) {
+            if !func.dfg.insts[inst].opcode().can_trap() {
+                stores.update(func, inst);
+            }
+        }
+    }
+
+    let dest = func.layout.last_inst(preheader).unwrap();
+    let mut changed = false;
+    // Whether everything in the header so far either was hoisted or is pure.
+    let mut in_header_prefix = true;
+    for &block in &blocks {
+        let mut next = func.layout.first_inst(block);
+        while let Some(inst) = next {
+            next = func.layout.next_inst(inst);
+            let args_invariant = func
+                .dfg
+                .inst_args(inst)
+                .iter()
+                .all(|&arg| is_defined_outside(func, loop_analysis, lp, arg));
+            let pure = is_pure_for_egraph(func, inst);
+            let hoist = args_invariant
+                && (pure || is_hoistable(func, inst, &stores, block == header && in_header_prefix));
+            if hoist {
+                trace!("hoisting {} out of {}", func.dfg.display_inst(inst), header);
+                func.layout.remove_inst(inst);
+                func.layout.insert_inst(inst, dest);
+                changed = true;
+            } else if !pure {
+                in_header_prefix = false;
+            }
+        }
+        in_header_prefix = false;
+    }
+    changed
+}
+
+/// Can the impure `inst`, whose arguments are loop-invariant, be hoisted to
+/// the preheader of a loop which writes `stores`? `runs_first` is whether it
+/// runs on every entry to the loop before any side effect.
+fn is_hoistable(func: &Function, inst: Inst, stores: &LastStores, runs_first: bool) -> bool {
+    match func.dfg.insts[inst] {
+        InstructionData::Load { flags, .. } => {
+            stores.get_last_store(func, inst).is_none() && (flags.can_move() || runs_first)
+        }
+        data => runs_first && only_traps(data.opcode()),
+    }
+}
+
+/// Is trapping the only side effect of `opcode`?
+fn only_traps(opcode: Opcode) -> bool {
+    opcode.can_trap()
+        && !opcode.can_load()
+        && !opcode.can_store()
+        && !opcode.is_call()
+        && !opcode.is_branch()
+        && !opcode.is_terminator()
+        && !opcode.other_side_effects()
+}
@@ -0,0 +1,287 @@
+//! Strength reduction of induction variables.
+//!
+//! A basic induction variable is a parameter of a loop's header which every
+//! branch back to the header increments by a loop-invariant step. A product
+//! `imul iv, k` of one with a loop-invariant `k` is itself an induction
+//! variable, starting at `init * k` and stepping by `step * k`, so it is
+//! replaced with a new header parameter, computed with an addition per
+//! iteration instead of a multiplication. Wrapping multiplication distributes
+//! over wrapping addition, so this holds whatever the values involved.
+//!
+//! Products by powers of two are left alone: they are shifts, as cheap as the
+//! addition, and often folded into addressing modes.
+
+use super::{iconst_value, is_defined_outside, loop_blocks, loops_innermost_first, preheader};
+use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{
+    Block, BlockArg, Function, Inst, InstBuilder, InstructionData, Opcode, Type, Value,
+};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::trace;
+use alloc::vec::Vec;
+use core::iter;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+/// Strength-reduce the products of induction variables in every loop with a
+/// preheader, returning whether any was.
+pub(crate) fn reduce_induction_variables(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+) -> bool {
+    let mut changed = false;
+    for lp in loops_innermost_first(loop_analysis) {
+        if let Some(preheader) = preheader(func, cfg, loop_analysis, lp) {
+            changed |= reduce_loop(func, cfg, domtree, loop_analysis, lp, preheader);
+        }
+    }
+    changed
+}
+
+/// A loop-invariant operand: either defined outside of the loop, or a
+/// constant, which may be rematerialized in the preheader.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Invariant {
+    Value(Value),
+    Const(i64),
+}
+
+impl Invariant {
+    fn new(func: &Function, loop_analysis: &LoopAnalysis, lp: Loop, value: Value) -> Option<Self> {
+        if let Some(imm) = iconst_value(func, value) {
+            Some(Self::Const(imm))
+        } else if is_defined_outside(func, loop_analysis, lp, value) {
+            Some(Self::Value(value))
+        } else {
+            None
+        }
+    }
+
+    /// Materialize the operand at `pos`, in the preheader.
+    fn materialize(self, pos: &mut FuncCursor, ty: Type) -> Value {
+        match self {
+            Self::Value(value) => value,
+            Self::Const(imm) => pos.ins().iconst(ty, imm),
+        }
+    }
+
+    /// Materialize the product of two operands at `pos`.
+    fn materialize_product(self, other: Self, pos: &mut FuncCursor, ty: Type) -> Value {
+        match (self, other) {
+            (Self::Const(x), Self::Const(y)) => pos.ins().iconst(ty, x.wrapping_mul(y)),
+            _ => {
+                let x = self.materialize(pos, ty);
+                let y = other.materialize(pos, ty);
+                pos.ins().imul(x, y)
+            }
+        }
+    }
+}
+
+/// A basic induction variable.
+struct InductionVariable {
+    /// The value passed to the header on entry to the loop.
+    init: Value,
+    /// The step along each branch back to the header, in the order of the
+    /// loop's latches and their destinations.
+    steps: SmallVec<[Invariant; 4]>,
+}
+
+fn reduce_loop(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    preheader: Block,
+) -> bool {
+    let header = loop_analysis.loop_header(lp);
+    let entry = func.layout.last_inst(preheader).unwrap();
+
+    // The branches back to the header, which must all be able to take new
+    // arguments.
+    let mut latches: SmallVec<[Inst; 4]> = cfg
+        .pred_iter(header)
+        .filter(|pred| loop_analysis.is_in_loop(pred.block, lp))
+        .map(|pred| pred.inst)
+        .collect();
+    latches.sort();
+    latches.dedup();
+    if latches.iter().any(|&inst| {
+        !matches!(
+            func.dfg.insts[inst],
+            InstructionData::Jump { .. } | InstructionData::Brif { .. }
+        )
+    }) {
+        return false;
+    }
+
+    let entry_args = header_args(func, entry, header);
+    let latch_args: Vec<SmallVec<[BlockArg; 8]>> = latches
+        .iter()
+        .flat_map(|&latch| header_args(func, latch, header))
+        .collect();
+    let mut ivs: FxHashMap<Value, InductionVariable> = FxHashMap::default();
+    for (index, &param) in func.dfg.block_params(header).iter().enumerate() {
+        let ty = func.dfg.value_type(param);
+        if !ty.is_int() || ty.bits() > 64 {
+            continue;
+        }
+        let BlockArg::Value(init) = entry_args[0][index] else {
+            continue;
+        };
+        let steps = latch_args
+            .iter()
+            .map(|args| match args[index] {
+                BlockArg::Value(next) => step(func, loop_analysis, lp, param, next),
+                _ => None,
+            })
+            .collect::<Option<_>>();
+        if let Some(steps) = steps {
+            ivs.insert(param, InductionVariable { init, steps });
+        }
+    }
+    if ivs.is_empty() {
+        return false;
+    }
+
+    // The products of induction variables to reduce.
+    let mut products: Vec<(Inst, Value, Invariant)> = Vec::new();
+    for block in loop_blocks(domtree, loop_analysis, lp) {
+        for inst in func.layout.block_insts(block) {
+            let InstructionData::Binary {
+                opcode: Opcode::Imul,
+                args: [x, y],
+            } = func.dfg.insts[inst]
+            else {
+                continue;
+            };
+            let (iv, k) = if ivs.contains_key(&x) {
+                (x, y)
+            } else if ivs.contains_key(&y) {
+                (y, x)
+            } else {
+                continue;
+            };
+            match Invariant::new(func, loop_analysis, lp, k) {
+                Some(Invariant::Const(k)) if k == 0 || (k as u64).is_power_of_two() => {}
+                Some(k) => products.push((inst, iv, k)),
+                None => {}
+            }
+        }
+    }
+    if products.is_empty() {
+        return false;
+    }
+
+    let mut reduced: FxHashMap<(Value, Invariant), Value> = FxHashMap::default();
+    for (inst, iv, k) in products {
+        let reduced_iv = *reduced.entry((iv, k)).or_insert_with(|| {
+            add_induction_variable(func, header, entry, &latches, iv, &ivs[&iv], k)
+        });
+        trace!(
+            "strength-reducing {} to {}",
+            func.dfg.display_inst(inst),
+            reduced_iv
+        );
+        let result = func.dfg.first_result(inst);
+        func.layout.remove_inst(inst);
+        func.dfg.clear_results(inst);
+        func.dfg.change_to_alias(result, reduced_iv);
+    }
+    true
+}
+
+/// The step of the induction variable `param` along a branch back to the
+/// header passing it `next`, if `next` is `iadd param, step`.
+fn step(
+    func: &Function,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    param: Value,
+    next: Value,
+) -> Option<Invariant> {
+    let inst = func.dfg.value_def(next).inst()?;
+    match func.dfg.insts[inst] {
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [x, y],
+        } if x == param => Invariant::new(func, loop_analysis, lp, y),
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [x, y],
+        } if y == param => Invariant::new(func, loop_analysis, lp, x),
+        _ => None,
+    }
+}
+
+/// The arguments of each of the destinations of the branch `inst` that are
+/// `header`.
+fn header_args(func: &Function, inst: Inst, header: Block) -> Vec<SmallVec<[BlockArg; 8]>> {
+    func.dfg.insts[inst]
+        .branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables)
+        .iter()
+        .filter(|call| call.block(&func.dfg.value_lists) == header)
+        .map(|call| call.args(&func.dfg.value_lists).collect())
+        .collect()
+}
+
+/// Add a parameter to `header` that is always `iv * k`. The loop is entered
+/// through the jump `entry`, and branches back to the header at `latches`.
+fn add_induction_variable(
+    func: &mut Function,
+    header: Block,
+    entry: Inst,
+    latches: &[Inst],
+    iv: Value,
+    iv_data: &InductionVariable,
+    k: Invariant,
+) -> Value {
+    let ty = func.dfg.value_type(iv);
+    let reduced_iv = func.dfg.append_block_param(header, ty);
+
+    // The initial value and the steps are computed in the preheader.
+    let mut pos = FuncCursor::new(func).at_inst(entry);
+    let k_value = k.materialize(&mut pos, ty);
+    let init = pos.ins().imul(iv_data.init, k_value);
+    let steps: SmallVec<[Value; 4]> = iv_data
+        .steps
+        .iter()
+        .map(|step| step.materialize_product(k, &mut pos, ty))
+        .collect();
+    append_header_args(func, entry, header, &mut iter::once(init));
+
+    // The next values are computed right before each branch back.
+    let mut steps = steps.into_iter();
+    for &latch in latches {
+        let mut pos = FuncCursor::new(func).at_inst(latch);
+        let nexts: SmallVec<[Value; 2]> = header_args(pos.func, latch, header)
+            .iter()
+            .map(|_| pos.ins().iadd(reduced_iv, steps.next().unwrap()))
+            .collect();
+        append_header_args(func, latch, header, &mut nexts.into_iter());
+    }
+    reduced_iv
+}
+
+/// Append a value from `args` to each of the destinations of the branch
+/// `inst` that are `header`.
+fn append_header_args(
+    func: &mut Function,
+    inst: Inst,
+    header: Block,
+    args: &mut impl Iterator<Item = Value>,
+) {
+    let dfg = &mut func.dfg;
+    for call in
+        dfg.insts[inst].branch_destination_mut(&mut dfg.jump_tables, &mut dfg.exception_tables)
+    {
+        if call.block(&dfg.value_lists) == header {
+            call.append_argument(args.next().unwrap(), &mut dfg.value_lists);
+        }
+    }
+}
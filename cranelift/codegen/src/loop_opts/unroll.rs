@@ -0,0 +1,321 @@
+//! Unrolling of single-block loops with a constant trip count.
+//!
+//! A loop consisting of a single block whose branch back to itself is taken
+//! depending on a comparison between an induction variable and a constant
+//! runs the same number of times on every entry, which is computed by
+//! stepping through the iterations. Knowing it, the body is copied so that
+//! each iteration of the unrolled loop runs `factor` iterations of the
+//! original one, with no exit test but in the last copy: when `factor` divides
+//! the trip count the loop can't exit anywhere else. When `factor` is the
+//! trip count itself, the loop is unrolled fully and the last exit test goes
+//! away too.
+//!
+//! The copies are placed in the loop's block, before the original
+//! instructions, which become the last copy. Values defined in the loop and
+//! used after it thus remain those of the last iteration, except for the
+//! block parameters, whose uses outside of the loop are rewritten.
+
+use super::iconst_value;
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::immediates::Imm64;
+use crate::ir::{
+    Block, BlockArg, Function, Inst, InstructionData, Opcode, UserStackMapEntry, Value, ValueDef,
+};
+use crate::trace;
+use alloc::vec::Vec;
+use core::iter;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+/// The largest trip count that is searched for.
+const MAX_TRIP_COUNT: u32 = 4096;
+
+/// The maximum number of instructions of a fully unrolled loop.
+const MAX_FULLY_UNROLLED_SIZE: usize = 256;
+
+/// The maximum number of instructions of a partially unrolled loop.
+const MAX_PARTIALLY_UNROLLED_SIZE: usize = 128;
+
+/// The unroll factors tried, in order, when a loop can't be fully unrolled.
+const PARTIAL_UNROLL_FACTORS: [u32; 3] = [8, 4, 2];
+
+/// Unroll the single-block loops of `func` with a constant trip count,
+/// returning whether any was.
+pub(crate) fn unroll_loops(func: &mut Function, cfg: &ControlFlowGraph) -> bool {
+    let mut changed = false;
+    let blocks: Vec<Block> = func.layout.blocks().collect();
+    for block in blocks {
+        let Some(trip_count) = trip_count(func, cfg, block) else {
+            continue;
+        };
+        let size = func.layout.block_insts(block).count() - 1;
+        let factor = if trip_count as usize * size <= MAX_FULLY_UNROLLED_SIZE {
+            trip_count
+        } else if let Some(factor) = PARTIAL_UNROLL_FACTORS.into_iter().find(|&factor| {
+            trip_count % factor == 0 && factor as usize * size <= MAX_PARTIALLY_UNROLLED_SIZE
+        }) {
+            factor
+        } else {
+            continue;
+        };
+        trace!("unrolling {block} by {factor} (trip count {trip_count})");
+        unroll(func, block, factor, factor == trip_count);
+        changed = true;
+    }
+    changed
+}
+
+/// The shape of a single-block loop: `block` ends with a `brif` whose
+/// destination at `back` is `block` itself and whose other destination is
+/// outside of the loop.
+struct SelfLoop {
+    terminator: Inst,
+    back: usize,
+}
+
+impl SelfLoop {
+    fn new(func: &Function, block: Block) -> Option<Self> {
+        let terminator = func.layout.last_inst(block)?;
+        let InstructionData::Brif { blocks, .. } = func.dfg.insts[terminator] else {
+            return None;
+        };
+        let pool = &func.dfg.value_lists;
+        let back = match (
+            blocks[0].block(pool) == block,
+            blocks[1].block(pool) == block,
+        ) {
+            (true, false) => 0,
+            (false, true) => 1,
+            _ => return None,
+        };
+        Some(Self { terminator, back })
+    }
+
+    /// The arguments passed back to the loop's block.
+    fn back_args(&self, func: &Function) -> Option<SmallVec<[Value; 8]>> {
+        let InstructionData::Brif { blocks, .. } = func.dfg.insts[self.terminator] else {
+            unreachable!()
+        };
+        blocks[self.back]
+            .args(&func.dfg.value_lists)
+            .map(|arg| arg.as_value())
+            .collect()
+    }
+}
+
+/// The number of times the body of `block` runs on each entry, if it is a
+/// single-block loop whose exit depends on a comparison between a constant
+/// and an induction variable with constant initial value and step.
+fn trip_count(func: &Function, cfg: &ControlFlowGraph, block: Block) -> Option<u32> {
+    let self_loop = SelfLoop::new(func, block)?;
+
+    // The loop must be entered from a single other block.
+    let mut preds = cfg.pred_iter(block);
+    let (first, second) = (preds.next()?, preds.next()?);
+    if preds.next().is_some() {
+        return None;
+    }
+    let entry = if first.block == block {
+        second
+    } else if second.block == block {
+        first
+    } else {
+        return None;
+    };
+    let mut entry_calls = func.dfg.insts[entry.inst]
+        .branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables)
+        .iter()
+        .filter(|call| call.block(&func.dfg.value_lists) == block);
+    let entry_call = entry_calls.next()?;
+    if entry_calls.next().is_some() {
+        return None;
+    }
+
+    // The exit condition: `icmp cond, x, y`, possibly extended, where one of
+    // `x` and `y` is a constant and the other an induction variable, before or
+    // after its increment.
+    let InstructionData::Brif { arg, .. } = func.dfg.insts[self_loop.terminator] else {
+        unreachable!()
+    };
+    let mut cond_inst = func.dfg.value_def(arg).inst()?;
+    if let InstructionData::Unary {
+        opcode: Opcode::Uextend | Opcode::Sextend,
+        arg,
+    } = func.dfg.insts[cond_inst]
+    {
+        cond_inst = func.dfg.value_def(arg).inst()?;
+    }
+    let InstructionData::IntCompare {
+        opcode: Opcode::Icmp,
+        cond,
+        args: [x, y],
+    } = func.dfg.insts[cond_inst]
+    else {
+        return None;
+    };
+    let (iv, limit, cond) = match (iconst_value(func, x), iconst_value(func, y)) {
+        (None, Some(limit)) => (x, limit, cond),
+        (Some(limit), None) => (y, limit, cond.swap_args()),
+        _ => return None,
+    };
+
+    let back_args = self_loop.back_args(func)?;
+    let params = func.dfg.block_params(block);
+    let (index, param, next, step) = params.iter().enumerate().find_map(|(index, &param)| {
+        let next = back_args[index];
+        let step = increment(func, param, next)?;
+        (iv == param || iv == next).then_some((index, param, next, step))
+    })?;
+    let init = match entry_call.args(&func.dfg.value_lists).nth(index)? {
+        BlockArg::Value(init) => iconst_value(func, init)?,
+        _ => return None,
+    };
+
+    let ty = func.dfg.value_type(param);
+    if !ty.is_int() || ty.bits() > 64 {
+        return None;
+    }
+    let bits = ty.bits();
+    let mut value = Imm64::new(init).zero_extend_from_width(bits);
+    for trip_count in 1..=MAX_TRIP_COUNT {
+        let incremented = Imm64::new(value.bits().wrapping_add(step)).zero_extend_from_width(bits);
+        let tested = if iv == next { incremented } else { value };
+        let taken = compare(cond, bits, tested, Imm64::new(limit));
+        if taken != (self_loop.back == 0) {
+            return Some(trip_count);
+        }
+        value = incremented;
+    }
+    None
+}
+
+/// The constant `next` adds to `param`, if it is defined as `iadd param, k`.
+fn increment(func: &Function, param: Value, next: Value) -> Option<i64> {
+    let ValueDef::Result(inst, _) = func.dfg.value_def(next) else {
+        return None;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [x, y],
+        } if x == param => iconst_value(func, y),
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [x, y],
+        } if y == param => iconst_value(func, x),
+        _ => None,
+    }
+}
+
+/// Evaluate `icmp cond, x, y` on `bits`-wide integers.
+fn compare(cond: IntCC, bits: u32, x: Imm64, y: Imm64) -> bool {
+    let (ux, uy) = (
+        x.zero_extend_from_width(bits).bits() as u64,
+        y.zero_extend_from_width(bits).bits() as u64,
+    );
+    let (sx, sy) = (
+        x.sign_extend_from_width(bits).bits(),
+        y.sign_extend_from_width(bits).bits(),
+    );
+    match cond {
+        IntCC::Equal => ux == uy,
+        IntCC::NotEqual => ux != uy,
+        IntCC::SignedLessThan => sx < sy,
+        IntCC::SignedGreaterThanOrEqual => sx >= sy,
+        IntCC::SignedGreaterThan => sx > sy,
+        IntCC::SignedLessThanOrEqual => sx <= sy,
+        IntCC::UnsignedLessThan => ux < uy,
+        IntCC::UnsignedGreaterThanOrEqual => ux >= uy,
+        IntCC::UnsignedGreaterThan => ux > uy,
+        IntCC::UnsignedLessThanOrEqual => ux <= uy,
+    }
+}
+
+/// Unroll the single-block loop `block` by `factor`, which must divide its
+/// trip count, or be it if `full`.
+fn unroll(func: &mut Function, block: Block, factor: u32, full: bool) {
+    let self_loop = SelfLoop::new(func, block).unwrap();
+    let terminator = self_loop.terminator;
+    let back_args = self_loop.back_args(func).unwrap();
+    let params: SmallVec<[Value; 8]> = func.dfg.block_params(block).into();
+    let body: SmallVec<[Inst; 32]> = func
+        .layout
+        .block_insts(block)
+        .take_while(|&inst| inst != terminator)
+        .collect();
+    let first = func.layout.first_inst(block).unwrap();
+
+    // The values of the parameters at the start of the current copy.
+    let mut start = params.clone();
+    let mut map = FxHashMap::default();
+    for _ in 1..factor {
+        map.clear();
+        map.extend(params.iter().copied().zip(start.iter().copied()));
+        for &inst in &body {
+            let copy = func.dfg.clone_inst(inst);
+            func.dfg
+                .map_inst_values(copy, |arg| map.get(&arg).copied().unwrap_or(arg));
+            for (&result, &copied) in func
+                .dfg
+                .inst_results(inst)
+                .iter()
+                .zip(func.dfg.inst_results(copy))
+            {
+                map.insert(result, copied);
+            }
+            func.layout.insert_inst(copy, first);
+
+            let srcloc = func.srcloc(inst);
+            if !srcloc.is_default() {
+                func.set_srcloc(copy, srcloc);
+            }
+            let stack_map: SmallVec<[UserStackMapEntry; 4]> = func
+                .dfg
+                .user_stack_map_entries(inst)
+                .map(|entries| entries.iter().cloned().collect())
+                .unwrap_or_default();
+            for entry in stack_map {
+                func.dfg.append_user_stack_map_entry(copy, entry);
+            }
+        }
+        start = back_args
+            .iter()
+            .map(|arg| map.get(arg).copied().unwrap_or(*arg))
+            .collect();
+    }
+
+    // The original instructions are the last copy; the parameters they use
+    // are now the values passed on by the previous copy, both in the loop and
+    // after it.
+    map.clear();
+    map.extend(params.iter().copied().zip(start.iter().copied()));
+    let map_params = |func: &mut Function, inst| {
+        func.dfg
+            .map_inst_values(inst, |arg| map.get(&arg).copied().unwrap_or(arg));
+    };
+    for &inst in body.iter().chain(iter::once(&terminator)) {
+        map_params(func, inst);
+    }
+    if factor > 1 {
+        let outside: Vec<Inst> = func
+            .layout
+            .blocks()
+            .filter(|&other| other != block)
+            .flat_map(|other| func.layout.block_insts(other))
+            .collect();
+        for inst in outside {
+            map_params(func, inst);
+        }
+    }
+
+    if full {
+        let InstructionData::Brif { blocks, .. } = func.dfg.insts[terminator] else {
+            unreachable!()
+        };
+        func.dfg.insts[terminator] = InstructionData::Jump {
+            opcode: Opcode::Jump,
+            destination: blocks[1 - self_loop.back],
+        };
+    }
+}
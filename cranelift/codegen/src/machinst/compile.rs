@@ -66,8 +66,14 @@ pub fn compile<B: LowerBackend + TargetIsa>(
 
         options.algorithm = match b.flags().regalloc_algorithm() {
             RegallocAlgorithm::Backtracking => Algorithm::Ion,
-            // Note: single-pass is currently disabled
-            // (https://github.com/bytecodealliance/regalloc2/issues/217).
+            // The single-pass allocator can't yet handle the register
+            // constraints of exception-handling calls
+            // (https://github.com/bytecodealliance/regalloc2/issues/217), so
+            // fall back to the backtracking allocator for such functions.
+            RegallocAlgorithm::SinglePass if f.dfg.exception_tables.is_empty() => {
+                Algorithm::Fastalloc
+            }
+            RegallocAlgorithm::SinglePass => Algorithm::Ion,
         };
 
         regalloc2::run(&vcode, vcode.abi.machine_env(), &options)
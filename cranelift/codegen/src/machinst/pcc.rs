@@ -1,6 +1,7 @@
 //! Common helpers for ISA-specific proof-carrying-code implementations.
 
-use crate::ir::pcc::{Fact, FactContext, PccError, PccResult};
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::pcc::{Expr, Fact, FactContext, InequalityKind, PccError, PccResult};
 use crate::machinst::{Reg, VCode, VCodeInst, Writable};
 use crate::trace;
 
@@ -130,6 +131,96 @@ pub(crate) fn check_binop<I: VCodeInst, F: FnOnce(&Fact, &Fact) -> PccResult<Opt
     })
 }
 
+/// Refine `fact`, the fact on one input of a select, given that the
+/// comparison `lhs <kind> rhs` is known to hold on that side of the
+/// select. Only unsigned comparisons tell us anything.
+pub(crate) fn apply_compare(
+    ctx: &FactContext,
+    fact: &Fact,
+    kind: IntCC,
+    lhs: &Fact,
+    rhs: &Fact,
+) -> Fact {
+    match kind {
+        IntCC::UnsignedGreaterThan => ctx.apply_inequality(fact, lhs, rhs, InequalityKind::Strict),
+        IntCC::UnsignedGreaterThanOrEqual => {
+            ctx.apply_inequality(fact, lhs, rhs, InequalityKind::Loose)
+        }
+        IntCC::UnsignedLessThan => ctx.apply_inequality(fact, rhs, lhs, InequalityKind::Strict),
+        IntCC::UnsignedLessThanOrEqual => {
+            ctx.apply_inequality(fact, rhs, lhs, InequalityKind::Loose)
+        }
+        _ => fact.clone(),
+    }
+}
+
+/// Check the output of a select between `if_true` and `if_false` on
+/// the comparison `lhs <kind> rhs`.
+pub(crate) fn check_select<I: VCodeInst>(
+    ctx: &FactContext,
+    vcode: &mut VCode<I>,
+    out: Writable<Reg>,
+    (kind, lhs, rhs): (IntCC, &Fact, &Fact),
+    if_true: &Fact,
+    if_false: &Fact,
+) -> PccResult<()> {
+    check_output(ctx, vcode, out, &[], |_vcode| {
+        let if_true = apply_compare(ctx, if_true, kind, lhs, rhs);
+        let if_false = apply_compare(ctx, if_false, kind.complement(), lhs, rhs);
+        clamp_range(ctx, 64, 64, ctx.union(&if_true, &if_false))
+    })
+}
+
+/// A fact stating that a 64-bit value is exactly `expr`.
+pub(crate) fn exact_fact(expr: &Expr) -> Fact {
+    Fact::DynamicRange {
+        bit_width: 64,
+        min: expr.clone(),
+        max: expr.clone(),
+    }
+}
+
+/// The symbolic expression for the exact value described by a fact,
+/// if any.
+pub(crate) fn exact_expr(fact: &Fact) -> Option<Expr> {
+    fact.as_symbol().cloned().or_else(|| {
+        fact.as_const(64)
+            .and_then(|k| i64::try_from(k).ok())
+            .map(Expr::constant)
+    })
+}
+
+/// Check a stated `Compare` fact, if any, on the output of an
+/// instruction computing the comparison `lhs <kind> rhs` into a
+/// register.
+pub(crate) fn check_compare_output<I: VCodeInst>(
+    vcode: &VCode<I>,
+    out: Writable<Reg>,
+    (kind, lhs, rhs): (IntCC, &Fact, &Fact),
+) -> PccResult<()> {
+    let Some(fact) = vcode.vreg_fact(out.to_reg().into()) else {
+        return Ok(());
+    };
+    let Fact::Compare {
+        kind: stated,
+        lhs: stated_lhs,
+        rhs: stated_rhs,
+    } = fact
+    else {
+        return Err(PccError::UnsupportedFact);
+    };
+    let (Some(lhs), Some(rhs)) = (exact_expr(lhs), exact_expr(rhs)) else {
+        return Err(PccError::UnsupportedFact);
+    };
+    let matches = (*stated == kind && *stated_lhs == lhs && *stated_rhs == rhs)
+        || (*stated == kind.swap_args() && *stated_lhs == rhs && *stated_rhs == lhs);
+    if matches {
+        Ok(())
+    } else {
+        Err(PccError::UnsupportedFact)
+    }
+}
+
 pub(crate) fn check_constant<I: VCodeInst>(
     ctx: &FactContext,
     vcode: &mut VCode<I>,
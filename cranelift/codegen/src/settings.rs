@@ -521,6 +521,8 @@ log2_min_function_alignment = 0
 regalloc_checker = false
 regalloc_verbose_logs = false
 enable_alias_analysis = true
+enable_loop_optimizations = false
+enable_slp_vectorization = false
 enable_verifier = true
 enable_pcc = false
 is_pic = false
@@ -0,0 +1,647 @@
+//! Superword-level parallelism (SLP) vectorization.
+//!
+//! Within a block, groups of stores of scalars to adjacent addresses are
+//! replaced with a single store of a 128-bit vector when the stored values
+//! can themselves be computed lane-wise: by isomorphic arithmetic on other
+//! such groups of values, by loads of scalars from adjacent addresses, or as
+//! constants or copies of the same value. This turns scalar code, such as the
+//! body of an unrolled loop, into SIMD code.
+//!
+//! The vector instructions are inserted just before the last store of the
+//! group, so the loads and stores that are vectorized move there. This is
+//! only done when it can't change the values read or written: nothing in
+//! between may write memory but the group's own stores, and the accesses
+//! whose order changes must be at different constant offsets from the same
+//! address, so as to provably not overlap.
+//!
+//! A vectorized group of accesses traps as a whole: when one of the scalar
+//! accesses would have trapped, the others may or may not have happened
+//! before the trap. This is why the pass is opt-in, with the
+//! `enable_slp_vectorization` setting.
+//!
+//! The pass runs after the egraph pass, so that equal addresses are the same
+//! values. The scalar instructions it makes unused are removed, as nothing
+//! else would remove the loads among them, which may trap.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::inst_predicates::is_pure_for_egraph;
+use crate::ir::immediates::Offset32;
+use crate::ir::{
+    Block, ConstantData, Endianness, Function, Inst, InstBuilder, InstructionData, MemFlags,
+    Opcode, Type, Value, ValueDef, types,
+};
+use crate::timing;
+use crate::trace;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use cranelift_entity::SecondaryMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+/// The width of the vectors built, in bits.
+const VECTOR_BITS: u32 = 128;
+
+/// The maximum depth of the trees of vectorized values.
+const MAX_DEPTH: usize = 8;
+
+/// The maximum number of nodes of a tree of vectorized values.
+const MAX_NODES: usize = 64;
+
+/// Vectorize the groups of adjacent scalar stores of `func`, returning
+/// whether any was.
+pub(crate) fn do_slp_vectorization(func: &mut Function) -> bool {
+    let _tt = timing::slp_vectorization();
+
+    let mut vectorized = Vectorized::default();
+    let blocks: Vec<_> = func.layout.blocks().collect();
+    for block in blocks {
+        let mut insts: Vec<Inst> = func.layout.block_insts(block).collect();
+        for group in store_groups(func, &insts) {
+            if vectorize_group(func, &insts, &group, &mut vectorized) {
+                insts = func.layout.block_insts(block).collect();
+            }
+        }
+    }
+
+    if vectorized.stores == 0 {
+        return false;
+    }
+    trace!(
+        "vectorized {} groups of stores; function is now:\n{}",
+        vectorized.stores,
+        func.display()
+    );
+    remove_unused(func, &vectorized);
+    true
+}
+
+/// What was vectorized so far.
+#[derive(Default)]
+struct Vectorized {
+    /// The number of groups of stores.
+    stores: usize,
+    /// The scalar loads whose results went into vectors.
+    loads: FxHashSet<Inst>,
+    /// The instructions whose results may now be unused.
+    maybe_unused: Vec<Inst>,
+}
+
+/// A scalar load or store, of `ty` at `base + offset`.
+#[derive(Clone, Copy)]
+struct Access {
+    inst: Inst,
+    ty: Type,
+    flags: MemFlags,
+    base: Value,
+    offset: i64,
+}
+
+impl Access {
+    /// The access `inst` makes, if it is a plain scalar load or store of a
+    /// type that fits in a vector lane.
+    fn new(func: &Function, inst: Inst) -> Option<Self> {
+        let (ty, flags, addr, offset) = match func.dfg.insts[inst] {
+            InstructionData::Load {
+                opcode: Opcode::Load,
+                arg,
+                flags,
+                offset,
+            } => (func.dfg.ctrl_typevar(inst), flags, arg, offset),
+            InstructionData::Store {
+                opcode: Opcode::Store,
+                args: [data, addr],
+                flags,
+                offset,
+            } => (func.dfg.value_type(data), flags, addr, offset),
+            _ => return None,
+        };
+        if lanes(ty).is_none() || flags.explicit_endianness() == Some(Endianness::Big) {
+            return None;
+        }
+        let (base, displacement) = split_address(func, addr);
+        Some(Self {
+            inst,
+            ty,
+            flags,
+            base,
+            offset: displacement.checked_add(i64::from(offset))?,
+        })
+    }
+
+    fn size(&self) -> i64 {
+        i64::from(self.ty.bytes())
+    }
+
+    /// Do `self` and `other` provably access disjoint ranges of memory?
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.base == other.base
+            && (self.offset + self.size() <= other.offset
+                || other.offset + other.size() <= self.offset)
+    }
+
+    /// Can `self` and `other` be lanes `i` and `i + 1` of the same vector
+    /// access?
+    fn precedes(&self, other: &Self) -> bool {
+        self.base == other.base
+            && self.ty == other.ty
+            && self.flags == other.flags
+            && self.offset.checked_add(self.size()) == Some(other.offset)
+    }
+}
+
+/// The number of lanes of the vectors of `ty`, if it is a supported lane type.
+fn lanes(ty: Type) -> Option<u32> {
+    match ty {
+        types::I8 | types::I16 | types::I32 | types::I64 | types::F32 | types::F64 => {
+            Some(VECTOR_BITS / ty.bits())
+        }
+        _ => None,
+    }
+}
+
+/// Split `addr` into a base and a constant displacement.
+fn split_address(func: &Function, addr: Value) -> (Value, i64) {
+    let ValueDef::Result(inst, _) = func.dfg.value_def(addr) else {
+        return (addr, 0);
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args: [x, y],
+        } => match (iconst_value(func, x), iconst_value(func, y)) {
+            (None, Some(k)) => (x, k),
+            (Some(k), None) => (y, k),
+            _ => (addr, 0),
+        },
+        _ => (addr, 0),
+    }
+}
+
+/// The value of the integer constant `value`, if it is one.
+fn iconst_value(func: &Function, value: Value) -> Option<i64> {
+    match func.dfg.value_def(value) {
+        ValueDef::Result(inst, _) => match func.dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => Some(imm.bits()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The groups of stores of `insts` to vectorize, each in lane order: as many
+/// stores as there are lanes, of the same type at adjacent addresses.
+fn store_groups(func: &Function, insts: &[Inst]) -> Vec<Vec<Access>> {
+    // Split the stores of each type to each base into segments in which no
+    // address is stored to twice, so that a group can't mix stores to the
+    // same addresses made at different times.
+    let mut segments: Vec<Vec<Access>> = Vec::new();
+    let mut current: FxHashMap<(Value, Type), (Vec<Access>, FxHashSet<i64>)> = FxHashMap::default();
+    for &inst in insts {
+        if func.dfg.insts[inst].opcode() != Opcode::Store {
+            continue;
+        }
+        let Some(store) = Access::new(func, inst) else {
+            continue;
+        };
+        let (segment, offsets) = current.entry((store.base, store.ty)).or_default();
+        if !offsets.insert(store.offset) {
+            segments.push(mem::take(segment));
+            offsets.clear();
+            offsets.insert(store.offset);
+        }
+        segment.push(store);
+    }
+    segments.extend(current.into_values().map(|(segment, _)| segment));
+
+    let mut groups = Vec::new();
+    for mut stores in segments {
+        stores.sort_by_key(|store| store.offset);
+        let mut i = 0;
+        while i < stores.len() {
+            let lanes = lanes(stores[i].ty).unwrap() as usize;
+            let run = stores[i..].len().min(lanes);
+            let adjacent = (1..run)
+                .take_while(|&j| stores[i + j - 1].precedes(&stores[i + j]))
+                .count()
+                + 1;
+            if adjacent == lanes {
+                groups.push(stores[i..i + lanes].to_vec());
+                i += lanes;
+            } else {
+                i += adjacent;
+            }
+        }
+    }
+    groups
+}
+
+/// A node of a tree of vectorized values.
+enum Node {
+    /// The same value in all lanes.
+    Splat(Value),
+    /// Constant lanes, by their bits.
+    Const(SmallVec<[u64; 16]>),
+    /// Loads of adjacent scalars.
+    Load(SmallVec<[Access; 16]>),
+    /// A lane-wise operation on two vectors.
+    Binary(Opcode, usize, usize),
+    /// A lane-wise shift of a vector by the same amount in all lanes.
+    Shift(Opcode, usize, Value),
+}
+
+/// The tree of the values stored by a group of stores.
+#[derive(Default)]
+struct Tree {
+    nodes: Vec<Node>,
+    /// The nodes computing each vector of lanes built so far.
+    built: FxHashMap<SmallVec<[Value; 16]>, usize>,
+    /// The scalar instructions computing lanes of the tree.
+    scalars: Vec<Inst>,
+}
+
+impl Tree {
+    /// Add the node computing `lanes`, values used in `block`, to the tree,
+    /// returning its index.
+    fn build(
+        &mut self,
+        func: &Function,
+        block: Block,
+        lanes: &[Value],
+        depth: usize,
+    ) -> Option<usize> {
+        if let Some(&index) = self.built.get(lanes) {
+            return Some(index);
+        }
+        if depth > MAX_DEPTH || self.nodes.len() >= MAX_NODES {
+            return None;
+        }
+        let node = if lanes.iter().all(|&lane| lane == lanes[0]) {
+            Node::Splat(lanes[0])
+        } else if let Some(bits) = lanes
+            .iter()
+            .map(|&lane| constant_bits(func, lane))
+            .collect::<Option<_>>()
+        {
+            Node::Const(bits)
+        } else {
+            let insts = lanes
+                .iter()
+                .map(|&lane| match func.dfg.value_def(lane) {
+                    ValueDef::Result(inst, 0) if func.layout.inst_block(inst) == Some(block) => {
+                        Some(inst)
+                    }
+                    _ => None,
+                })
+                .collect::<Option<SmallVec<[Inst; 16]>>>()?;
+            let node = self.build_insts(func, block, &insts, depth)?;
+            self.scalars.extend_from_slice(&insts);
+            node
+        };
+        self.nodes.push(node);
+        self.built.insert(lanes.into(), self.nodes.len() - 1);
+        Some(self.nodes.len() - 1)
+    }
+
+    /// The node computing the results of `insts`, all in `block`.
+    fn build_insts(
+        &mut self,
+        func: &Function,
+        block: Block,
+        insts: &[Inst],
+        depth: usize,
+    ) -> Option<Node> {
+        let ty = func.dfg.value_type(func.dfg.first_result(insts[0]));
+        match func.dfg.insts[insts[0]] {
+            InstructionData::Load { .. } => {
+                let loads = insts
+                    .iter()
+                    .map(|&inst| Access::new(func, inst))
+                    .collect::<Option<SmallVec<[Access; 16]>>>()?;
+                loads
+                    .windows(2)
+                    .all(|pair| pair[0].precedes(&pair[1]))
+                    .then_some(Node::Load(loads))
+            }
+            InstructionData::Binary { opcode, args } => {
+                let mut operands: [SmallVec<[Value; 16]>; 2] = Default::default();
+                for &inst in insts {
+                    match func.dfg.insts[inst] {
+                        InstructionData::Binary {
+                            opcode: other,
+                            args,
+                        } if other == opcode && func.dfg.ctrl_typevar(inst) == ty => {
+                            operands[0].push(args[0]);
+                            operands[1].push(args[1]);
+                        }
+                        _ => return None,
+                    }
+                }
+                if is_shift(opcode) {
+                    // The amounts are the same value, or constants with the
+                    // same value, as legalization leaves them.
+                    let amount = args[1];
+                    let bits = constant_bits(func, amount);
+                    if operands[1].iter().any(|&other| {
+                        other != amount && (bits.is_none() || constant_bits(func, other) != bits)
+                    }) {
+                        return None;
+                    }
+                    let x = self.build(func, block, &operands[0], depth + 1)?;
+                    Some(Node::Shift(opcode, x, amount))
+                } else if is_lane_wise(opcode, ty) {
+                    let x = self.build(func, block, &operands[0], depth + 1)?;
+                    let y = self.build(func, block, &operands[1], depth + 1)?;
+                    Some(Node::Binary(opcode, x, y))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The loads of the tree.
+    fn loads(&self) -> impl Iterator<Item = &Access> {
+        self.nodes.iter().flat_map(|node| match node {
+            Node::Load(loads) => &loads[..],
+            _ => &[],
+        })
+    }
+
+    /// Can the loads of the tree and `stores` all move to the last of
+    /// `stores` without changing what they read or write? `insts` are the
+    /// instructions of the block, at `positions`.
+    fn can_move_accesses(
+        &self,
+        func: &Function,
+        insts: &[Inst],
+        positions: &SecondaryMap<Inst, usize>,
+        stores: &[Access],
+    ) -> bool {
+        let position = |access: &Access| positions[access.inst];
+        let last = stores.iter().map(position).max().unwrap();
+        let first = stores
+            .iter()
+            .chain(self.loads())
+            .map(position)
+            .min()
+            .unwrap();
+        let is_member = |inst| stores.iter().chain(self.loads()).any(|a| a.inst == inst);
+
+        for &inst in &insts[first..last] {
+            if is_member(inst) {
+                continue;
+            }
+            let opcode = func.dfg.insts[inst].opcode();
+            if opcode.can_load() {
+                // The stores that were before the load move after it.
+                let Some(load) = Access::new(func, inst) else {
+                    return false;
+                };
+                if stores
+                    .iter()
+                    .any(|store| position(store) < position(&load) && !store.is_disjoint(&load))
+                {
+                    return false;
+                }
+            } else if has_effects(opcode) {
+                return false;
+            }
+        }
+
+        // The loads that were after a store move before it.
+        self.loads().all(|load| {
+            stores
+                .iter()
+                .all(|store| position(store) > position(load) || store.is_disjoint(load))
+        })
+    }
+
+    /// Insert the vector instructions computing node `index`, with lanes of
+    /// `ty`, at `pos`, unless they are among the `emitted` ones already.
+    fn emit(
+        &self,
+        pos: &mut FuncCursor,
+        index: usize,
+        ty: Type,
+        emitted: &mut Vec<Option<Value>>,
+    ) -> Value {
+        if let Some(value) = emitted[index] {
+            return value;
+        }
+        let vector = ty.by(lanes(ty).unwrap()).unwrap();
+        let value = match &self.nodes[index] {
+            Node::Splat(value) => pos.ins().splat(vector, *value),
+            Node::Const(lanes) => {
+                let bytes: Vec<u8> = lanes
+                    .iter()
+                    .flat_map(|&lane| lane.to_le_bytes()[..ty.bytes() as usize].to_vec())
+                    .collect();
+                let constant = pos.func.dfg.constants.insert(ConstantData::from(bytes));
+                pos.ins().vconst(vector, constant)
+            }
+            Node::Load(loads) => {
+                let (addr, offset) = address(pos.func, loads[0].inst);
+                pos.ins()
+                    .load(vector, unaligned(loads[0].flags), addr, offset)
+            }
+            Node::Binary(opcode, x, y) => {
+                let x = self.emit(pos, *x, ty, emitted);
+                let y = self.emit(pos, *y, ty, emitted);
+                let (inst, dfg) = pos.ins().Binary(*opcode, vector, x, y);
+                dfg.first_result(inst)
+            }
+            Node::Shift(opcode, x, amount) => {
+                let x = self.emit(pos, *x, ty, emitted);
+                let (inst, dfg) = pos.ins().Binary(*opcode, vector, x, *amount);
+                dfg.first_result(inst)
+            }
+        };
+        emitted[index] = Some(value);
+        value
+    }
+}
+
+/// Vectorize `stores`, a group of stores of the block whose instructions
+/// are `insts`, if possible. Returns whether it was.
+fn vectorize_group(
+    func: &mut Function,
+    insts: &[Inst],
+    stores: &[Access],
+    vectorized: &mut Vectorized,
+) -> bool {
+    let mut positions = SecondaryMap::new();
+    for (position, &inst) in insts.iter().enumerate() {
+        positions[inst] = position;
+    }
+    let block = func.layout.inst_block(insts[0]).unwrap();
+    let data: SmallVec<[Value; 16]> = stores
+        .iter()
+        .map(|store| func.dfg.inst_args(store.inst)[0])
+        .collect();
+    let mut tree = Tree::default();
+    let Some(root) = tree.build(func, block, &data, 0) else {
+        return false;
+    };
+    if !tree.can_move_accesses(func, insts, &positions, stores) {
+        return false;
+    }
+    trace!(
+        "vectorizing stores {:?}",
+        stores.iter().map(|store| store.inst).collect::<Vec<_>>()
+    );
+
+    let last = stores
+        .iter()
+        .map(|store| store.inst)
+        .max_by_key(|&inst| positions[inst])
+        .unwrap();
+    let (addr, offset) = address(func, stores[0].inst);
+    let flags = unaligned(stores[0].flags);
+    let srcloc = func.srcloc(last);
+    let mut pos = FuncCursor::new(func).at_inst(last).with_srcloc(srcloc);
+    let vector = tree.emit(
+        &mut pos,
+        root,
+        stores[0].ty,
+        &mut vec![None; tree.nodes.len()],
+    );
+    pos.ins().store(flags, vector, addr, offset);
+    for store in stores {
+        for &arg in pos.func.dfg.inst_args(store.inst) {
+            if let ValueDef::Result(def, _) = pos.func.dfg.value_def(arg) {
+                vectorized.maybe_unused.push(def);
+            }
+        }
+        pos.func.layout.remove_inst(store.inst);
+    }
+
+    vectorized.stores += 1;
+    vectorized.loads.extend(tree.loads().map(|load| load.inst));
+    vectorized.maybe_unused.extend(tree.scalars);
+    true
+}
+
+/// The address operand and offset immediate of the load or store `inst`.
+fn address(func: &Function, inst: Inst) -> (Value, Offset32) {
+    match func.dfg.insts[inst] {
+        InstructionData::Load { arg, offset, .. } => (arg, offset),
+        InstructionData::Store {
+            args: [_, addr],
+            offset,
+            ..
+        } => (addr, offset),
+        _ => unreachable!(),
+    }
+}
+
+/// `flags` without the `aligned` flag, which the wider vector accesses can't
+/// inherit from scalar ones.
+fn unaligned(flags: MemFlags) -> MemFlags {
+    let mut result = MemFlags::new()
+        .with_trap_code(flags.trap_code())
+        .with_alias_region(flags.alias_region());
+    if let Some(endianness) = flags.explicit_endianness() {
+        result.set_endianness(endianness);
+    }
+    if flags.readonly() {
+        result.set_readonly();
+    }
+    if flags.can_move() {
+        result.set_can_move();
+    }
+    if flags.checked() {
+        result.set_checked();
+    }
+    result
+}
+
+/// The bits of `value`, if it is a constant.
+fn constant_bits(func: &Function, value: Value) -> Option<u64> {
+    let ValueDef::Result(inst, _) = func.dfg.value_def(value) else {
+        return None;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => Some(imm.bits() as u64),
+        InstructionData::UnaryIeee32 {
+            opcode: Opcode::F32const,
+            imm,
+        } => Some(u64::from(imm.bits())),
+        InstructionData::UnaryIeee64 {
+            opcode: Opcode::F64const,
+            imm,
+        } => Some(imm.bits()),
+        _ => None,
+    }
+}
+
+/// Is `opcode` a shift, whose amount is the same for all lanes of a vector?
+fn is_shift(opcode: Opcode) -> bool {
+    matches!(opcode, Opcode::Ishl | Opcode::Ushr | Opcode::Sshr)
+}
+
+/// Does `opcode` have a lane-wise vector counterpart for lanes of `ty` that
+/// is worth using?
+fn is_lane_wise(opcode: Opcode, ty: Type) -> bool {
+    match opcode {
+        Opcode::Iadd | Opcode::Isub | Opcode::Band | Opcode::Bor | Opcode::Bxor => ty.is_int(),
+        // There are no vector multiplications of bytes, and those of 64-bit
+        // integers are slow.
+        Opcode::Imul => ty == types::I16 || ty == types::I32,
+        Opcode::Fadd | Opcode::Fsub | Opcode::Fmul | Opcode::Fdiv => ty.is_float(),
+        _ => false,
+    }
+}
+
+/// Does `opcode` have any effect beyond computing its results?
+fn has_effects(opcode: Opcode) -> bool {
+    opcode.can_store()
+        || opcode.can_trap()
+        || opcode.is_call()
+        || opcode.is_branch()
+        || opcode.is_terminator()
+        || opcode.other_side_effects()
+}
+
+/// Remove the pure instructions and loads whose results went into vectors
+/// that are now unused, starting with the `maybe_unused` ones.
+fn remove_unused(func: &mut Function, vectorized: &Vectorized) {
+    let mut uses = SecondaryMap::<Value, u32>::new();
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            for value in func.dfg.inst_values(inst) {
+                uses[value] += 1;
+            }
+        }
+    }
+
+    let mut worklist = vectorized.maybe_unused.clone();
+    while let Some(inst) = worklist.pop() {
+        if func.layout.inst_block(inst).is_none()
+            || !(vectorized.loads.contains(&inst) || is_pure_for_egraph(func, inst))
+            || func
+                .dfg
+                .inst_results(inst)
+                .iter()
+                .any(|&result| uses[result] > 0)
+        {
+            continue;
+        }
+        for value in func.dfg.inst_values(inst) {
+            uses[value] -= 1;
+            if let ValueDef::Result(def, _) = func.dfg.value_def(value) {
+                if uses[value] == 0 {
+                    worklist.push(def);
+                }
+            }
+        }
+        func.layout.remove_inst(inst);
+    }
+}
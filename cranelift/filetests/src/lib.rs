@@ -21,6 +21,7 @@ mod test_alias_analysis;
 mod test_cat;
 mod test_compile;
 mod test_domtree;
+mod test_inline;
 mod test_interpret;
 mod test_legalizer;
 mod test_optimize;
@@ -89,6 +90,7 @@ fn new_subtest(parsed: &TestCommand) -> anyhow::Result<Box<dyn subtest::SubTest>
         "cat" => test_cat::subtest(parsed),
         "compile" => test_compile::subtest(parsed),
         "domtree" => test_domtree::subtest(parsed),
+        "inline" => test_inline::subtest(parsed),
         "interpret" => test_interpret::subtest(parsed),
         "legalizer" => test_legalizer::subtest(parsed),
         "optimize" => test_optimize::subtest(parsed),
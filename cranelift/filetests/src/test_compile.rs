@@ -82,22 +82,26 @@ impl SubTest for TestCompile {
                         pulley_interpreter::disas::Disassembler::new(compiled_code.buffer.data());
                     disas.hexdump(false).offsets(false);
                     pulley_interpreter::decode::Decoder::decode_all(&mut disas)?;
-                    disas.disas().to_string()
+                    Some(disas.disas().to_string())
                 }
+                // Capstone has no LoongArch support, so only the VCode is
+                // checked for this target.
+                target_lexicon::Architecture::LoongArch64 => None,
                 _ => {
                     let cs = isa
                         .to_capstone()
                         .map_err(|e| anyhow::format_err!("{}", e))?;
-                    compiled_code.disassemble(Some(&params), &cs)?
+                    Some(compiled_code.disassemble(Some(&params), &cs)?)
                 }
             };
 
-            let actual = Vec::from_iter(
-                std::iter::once("VCode:")
-                    .chain(compiled_code.vcode.as_ref().unwrap().lines())
-                    .chain(["", "Disassembled:"])
-                    .chain(dis.lines()),
+            let mut actual = Vec::from_iter(
+                std::iter::once("VCode:").chain(compiled_code.vcode.as_ref().unwrap().lines()),
             );
+            if let Some(dis) = &dis {
+                actual.extend(["", "Disassembled:"]);
+                actual.extend(dis.lines());
+            }
 
             check_precise_output(&actual, context)
         } else {
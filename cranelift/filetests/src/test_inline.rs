@@ -0,0 +1,136 @@
+//! Test command for testing the inlining pass.
+//!
+//! The `inline` test command runs the inlining pass on each function, using
+//! the other functions in the same test file as the bodies of callees. The
+//! output for filecheck purposes is the resulting CLIF.
+
+use crate::runone::FileUpdate;
+use crate::subtest::{Context, SubTest, check_precise_output, run_filecheck};
+use anyhow::{Context as _, Result};
+use cranelift_codegen::inline::{Inline, InlineCommand};
+use cranelift_codegen::ir::{self, ExternalName, Function, UserFuncName};
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::settings::Flags;
+use cranelift_reader::{Details, TestCommand, TestFile, TestOption};
+use log::info;
+use std::borrow::Cow;
+
+struct TestInline {
+    /// Flag indicating that the text expectation, comments after the function,
+    /// must be a precise 100% match on the compiled output of the function.
+    /// This test assertion is also automatically-update-able to allow tweaking
+    /// the code generator and easily updating all affected tests.
+    precise_output: bool,
+}
+
+pub fn subtest(parsed: &TestCommand) -> Result<Box<dyn SubTest>> {
+    assert_eq!(parsed.command, "inline");
+    let mut test = TestInline {
+        precise_output: false,
+    };
+    for option in parsed.options.iter() {
+        match option {
+            TestOption::Flag("precise-output") => test.precise_output = true,
+            _ => anyhow::bail!("unknown option on {}", parsed),
+        }
+    }
+    Ok(Box::new(test))
+}
+
+impl SubTest for TestInline {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    fn run_target<'a>(
+        &self,
+        testfile: &TestFile,
+        file_update: &mut FileUpdate,
+        file_path: &'a str,
+        flags: &'a Flags,
+        isa: Option<&'a dyn TargetIsa>,
+    ) -> Result<()> {
+        for (func, details) in &testfile.functions {
+            info!("Test: {}({})", self.name(), func.name);
+
+            let context = Context {
+                preamble_comments: &testfile.preamble_comments,
+                details,
+                flags,
+                isa,
+                file_path,
+                file_update,
+            };
+
+            let inliner = FileInliner {
+                functions: &testfile.functions,
+            };
+            self.run_with_inliner(func.clone(), &context, inliner)
+                .context(self.name())?;
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, _func: Cow<Function>, _context: &Context) -> Result<()> {
+        unreachable!()
+    }
+}
+
+impl TestInline {
+    fn run_with_inliner(
+        &self,
+        func: Function,
+        context: &Context,
+        inliner: FileInliner<'_>,
+    ) -> Result<()> {
+        let mut comp_ctx = cranelift_codegen::Context::for_function(func);
+
+        comp_ctx
+            .inline(context.flags_or_isa(), inliner)
+            .map_err(|e| crate::pretty_anyhow_error(&comp_ctx.func, e))?;
+
+        let clif = format!("{:?}", comp_ctx.func);
+
+        if self.precise_output {
+            let actual: Vec<_> = clif.lines().collect();
+            check_precise_output(&actual, context)
+        } else {
+            run_filecheck(&clif, context)
+        }
+    }
+}
+
+/// An inliner providing the bodies of the functions defined in a test file.
+struct FileInliner<'a> {
+    functions: &'a [(Function, Details<'a>)],
+}
+
+impl Inline for FileInliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &Function,
+        _call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        _call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        let name = match &caller.dfg.ext_funcs[callee].name {
+            ExternalName::User(name) => {
+                UserFuncName::User(caller.params.user_named_funcs()[*name].clone())
+            }
+            ExternalName::TestCase(name) => UserFuncName::Testcase(name.clone()),
+            _ => return InlineCommand::KeepCall,
+        };
+        match self.functions.iter().find(|(func, _)| func.name == name) {
+            Some((func, _)) => InlineCommand::Inline {
+                callee: Cow::Borrowed(func),
+                visit_callee: true,
+            },
+            None => InlineCommand::KeepCall,
+        }
+    }
+}
@@ -796,6 +796,14 @@ impl<'a> FunctionBuilder<'a> {
                 .has_any_predecessors(self.position.unwrap())
     }
 
+    /// Returns the branch instructions that are predecessors of `block`, or `None` if
+    /// [`seal_block`](Self::seal_block) hasn't been called on `block` yet and so more
+    /// predecessors may still be declared.
+    pub fn sealed_predecessors(&self, block: Block) -> Option<&[Inst]> {
+        let ssa = &self.func_ctx.ssa;
+        ssa.is_sealed(block).then(|| ssa.predecessors(block))
+    }
+
     /// Returns `true` if and only if no instructions have been added since the last call to
     /// [`switch_to_block`](Self::switch_to_block).
     fn is_pristine(&self, block: Block) -> bool {
@@ -581,7 +581,7 @@ impl SSABuilder {
     }
 
     /// Returns the list of `Block`s that have been declared as predecessors of the argument.
-    fn predecessors(&self, block: Block) -> &[Inst] {
+    pub fn predecessors(&self, block: Block) -> &[Inst] {
         self.ssa_blocks[block]
             .predecessors
             .as_slice(&self.inst_pool)
@@ -1,13 +1,41 @@
 use super::HashMap;
 use crate::frontend::FunctionBuilder;
 use alloc::vec::Vec;
+use core::cmp::Reverse;
 use cranelift_codegen::ir::condcodes::IntCC;
 use cranelift_codegen::ir::*;
 
 type EntryIndex = u128;
 
+/// The minimum number of cases for a jump table to be used for them.
+const MIN_JUMP_TABLE_CASES: usize = 4;
+
+/// The minimum percentage of the entries of a jump table that must have a
+/// case, the others jumping to the default block.
+const MIN_JUMP_TABLE_DENSITY: u128 = 40;
+
+/// The minimum number of cases for bit tests to be used for them.
+const MIN_BIT_TEST_CASES: usize = 3;
+
+/// The maximum number of blocks that bit tests can jump to, as each of them
+/// needs a test of its own.
+const MAX_BIT_TEST_BLOCKS: usize = 3;
+
+/// The maximum number of entries covered by bit tests: the number of bits of
+/// the mask that is tested.
+const MAX_BIT_TEST_ENTRIES: u128 = 64;
+
 /// Unlike with `br_table`, `Switch` cases may be sparse or non-0-based.
-/// They emit efficient code using branches, jump tables, or a combination of both.
+/// They emit efficient code using branches, jump tables, bit tests, or a
+/// combination of them.
+///
+/// The cases are grouped into clusters which are each lowered to a single
+/// range check, a jump table if the cluster is dense enough, or bit tests if
+/// the cluster is small enough and only jumps to a few blocks. A binary search
+/// then finds the cluster of the switched value. If cases were set with
+/// [`Switch::set_entry_with_weight`], that search is balanced by weight rather
+/// than by number of cases, and the clusters most likely to match are tested
+/// first.
 ///
 /// # Example
 ///
@@ -41,6 +69,7 @@ type EntryIndex = u128;
 #[derive(Debug, Default)]
 pub struct Switch {
     cases: HashMap<EntryIndex, Block>,
+    weights: HashMap<EntryIndex, u64>,
 }
 
 impl Switch {
@@ -48,6 +77,7 @@ impl Switch {
     pub fn new() -> Self {
         Self {
             cases: HashMap::new(),
+            weights: HashMap::new(),
         }
     }
 
@@ -57,83 +87,105 @@ impl Switch {
         assert!(prev.is_none(), "Tried to set the same entry {index} twice");
     }
 
+    /// Set a switch entry along with its weight: how often the switched value
+    /// is expected to be `index`, relative to the weights of other entries,
+    /// for example as counted by a profile.
+    ///
+    /// Entries set with [`Switch::set_entry`] have a weight of 1.
+    pub fn set_entry_with_weight(&mut self, index: EntryIndex, block: Block, weight: u64) {
+        self.set_entry(index, block);
+        self.weights.insert(index, weight);
+    }
+
     /// Get a reference to all existing entries
     pub fn entries(&self) -> &HashMap<EntryIndex, Block> {
         &self.cases
     }
 
-    /// Turn the `cases` `HashMap` into a list of `ContiguousCaseRange`s.
+    /// Group the `cases` into a list of `Cluster`s.
     ///
     /// # Postconditions
     ///
     /// * Every entry will be represented.
-    /// * The `ContiguousCaseRange`s will not overlap.
-    /// * Between two `ContiguousCaseRange`s there will be at least one entry index.
-    /// * No `ContiguousCaseRange`s will be empty.
-    fn collect_contiguous_case_ranges(self) -> Vec<ContiguousCaseRange> {
-        log::trace!("build_contiguous_case_ranges before: {:#?}", self.cases);
-        let mut cases = self.cases.into_iter().collect::<Vec<(_, _)>>();
-        cases.sort_by_key(|&(index, _)| index);
-
-        let mut contiguous_case_ranges: Vec<ContiguousCaseRange> = vec![];
-        let mut last_index = None;
-        for (index, block) in cases {
-            match last_index {
-                None => contiguous_case_ranges.push(ContiguousCaseRange::new(index)),
-                Some(last_index) => {
-                    if index > last_index + 1 {
-                        contiguous_case_ranges.push(ContiguousCaseRange::new(index));
-                    }
-                }
-            }
-            contiguous_case_ranges
-                .last_mut()
-                .unwrap()
-                .blocks
-                .push(block);
-            last_index = Some(index);
+    /// * The `Cluster`s will not overlap and are sorted by entry index.
+    /// * No `Cluster`s will be empty.
+    fn collect_clusters(self) -> Vec<Cluster> {
+        log::trace!("collect_clusters before: {:#?}", self.cases);
+        let mut cases = self
+            .cases
+            .iter()
+            .map(|(&index, &block)| Case {
+                index,
+                block,
+                weight: self.weights.get(&index).copied().unwrap_or(1),
+            })
+            .collect::<Vec<_>>();
+        cases.sort_by_key(|case| case.index);
+
+        // Greedily form the cluster that covers the most cases starting at
+        // the first case not covered yet, preferring the cheapest lowering.
+        let mut clusters = Vec::new();
+        let mut rest = &cases[..];
+        while !rest.is_empty() {
+            let range = Cluster::range_len(rest);
+            let contiguous = Cluster::contiguous_len(rest);
+            let bit_tests = Cluster::bit_tests_len(rest);
+            let jump_table = Cluster::jump_table_len(rest);
+            let cluster = if bit_tests >= MIN_BIT_TEST_CASES
+                && bit_tests > contiguous
+                && bit_tests >= jump_table
+            {
+                Cluster::bit_tests(&rest[..bit_tests])
+            } else if jump_table >= MIN_JUMP_TABLE_CASES && jump_table > range {
+                Cluster::jump_table(&rest[..jump_table])
+            } else if contiguous > range {
+                // A jump table without holes is cheaper than a branch per
+                // case, however small it is.
+                Cluster::jump_table(&rest[..contiguous])
+            } else {
+                Cluster::range(&rest[..range])
+            };
+            rest = &rest[cluster.num_cases..];
+            clusters.push(cluster);
         }
 
-        log::trace!(
-            "build_contiguous_case_ranges after: {:#?}",
-            contiguous_case_ranges
-        );
+        log::trace!("collect_clusters after: {clusters:#?}");
 
-        contiguous_case_ranges
+        clusters
     }
 
-    /// Binary search for the right `ContiguousCaseRange`.
+    /// Binary search for the right `Cluster`.
     fn build_search_tree<'a>(
         bx: &mut FunctionBuilder,
         val: Value,
         otherwise: Block,
-        contiguous_case_ranges: &'a [ContiguousCaseRange],
+        clusters: &'a [Cluster],
     ) {
         // If no switch cases were added to begin with, we can just emit `jump otherwise`.
-        if contiguous_case_ranges.is_empty() {
+        if clusters.is_empty() {
             bx.ins().jump(otherwise, &[]);
             return;
         }
 
         // Avoid allocation in the common case
-        if contiguous_case_ranges.len() <= 3 {
-            Self::build_search_branches(bx, val, otherwise, contiguous_case_ranges);
+        if clusters.len() <= 3 {
+            Self::build_search_branches(bx, val, otherwise, clusters);
             return;
         }
 
         let mut stack = Vec::new();
-        stack.push((None, contiguous_case_ranges));
+        stack.push((None, clusters));
 
-        while let Some((block, contiguous_case_ranges)) = stack.pop() {
+        while let Some((block, clusters)) = stack.pop() {
             if let Some(block) = block {
                 bx.switch_to_block(block);
             }
 
-            if contiguous_case_ranges.len() <= 3 {
-                Self::build_search_branches(bx, val, otherwise, contiguous_case_ranges);
+            if clusters.len() <= 3 {
+                Self::build_search_branches(bx, val, otherwise, clusters);
             } else {
-                let split_point = contiguous_case_ranges.len() / 2;
-                let (left, right) = contiguous_case_ranges.split_at(split_point);
+                let split_point = Self::split_point(clusters);
+                let (left, right) = clusters.split_at(split_point);
 
                 let left_block = bx.create_block();
                 let right_block = bx.create_block();
@@ -153,44 +205,55 @@ impl Switch {
         }
     }
 
-    /// Linear search for the right `ContiguousCaseRange`.
+    /// Returns where to split `clusters` so that both sides weigh about the
+    /// same, which without weights means that they have about as many cases.
+    fn split_point(clusters: &[Cluster]) -> usize {
+        let total: u128 = clusters.iter().map(|c| u128::from(c.weight)).sum();
+        let mut left = 0;
+        let mut best = (u128::MAX, clusters.len() / 2);
+        for (i, cluster) in clusters[..clusters.len() - 1].iter().enumerate() {
+            left += u128::from(cluster.weight);
+            let imbalance = (2 * left).abs_diff(total);
+            if imbalance < best.0 {
+                best = (imbalance, i + 1);
+            }
+        }
+        best.1
+    }
+
+    /// Linear search for the right `Cluster`.
     fn build_search_branches<'a>(
         bx: &mut FunctionBuilder,
         val: Value,
         otherwise: Block,
-        contiguous_case_ranges: &'a [ContiguousCaseRange],
+        clusters: &'a [Cluster],
     ) {
-        for (ix, range) in contiguous_case_ranges.iter().enumerate().rev() {
-            let alternate = if ix == 0 {
+        // Test the clusters that are most likely to match first, and those
+        // with higher entry indices first when they're equally likely.
+        let mut clusters = clusters.iter().rev().collect::<Vec<_>>();
+        clusters.sort_by_key(|cluster| Reverse(cluster.weight));
+
+        for (ix, cluster) in clusters.iter().enumerate() {
+            let alternate = if ix == clusters.len() - 1 {
                 otherwise
             } else {
                 bx.create_block()
             };
 
-            if range.first_index == 0 {
-                assert_eq!(alternate, otherwise);
-
-                if let Some(block) = range.single_block() {
-                    bx.ins().brif(val, otherwise, &[], block, &[]);
-                } else {
-                    Self::build_jump_table(bx, val, otherwise, 0, &range.blocks);
+            match &cluster.kind {
+                ClusterKind::Range(block) => {
+                    Self::build_range(bx, val, otherwise, alternate, cluster, *block)
                 }
-            } else {
-                if let Some(block) = range.single_block() {
-                    let is_good_val = icmp_imm_u128(bx, IntCC::Equal, val, range.first_index);
-                    bx.ins().brif(is_good_val, block, &[], alternate, &[]);
-                } else {
-                    let is_good_val = icmp_imm_u128(
-                        bx,
-                        IntCC::UnsignedGreaterThanOrEqual,
-                        val,
-                        range.first_index,
-                    );
-                    let jt_block = bx.create_block();
-                    bx.ins().brif(is_good_val, jt_block, &[], alternate, &[]);
-                    bx.seal_block(jt_block);
-                    bx.switch_to_block(jt_block);
-                    Self::build_jump_table(bx, val, otherwise, range.first_index, &range.blocks);
+                ClusterKind::JumpTable(blocks) => Self::build_jump_table(
+                    bx,
+                    val,
+                    otherwise,
+                    alternate,
+                    cluster.first_index,
+                    blocks,
+                ),
+                ClusterKind::BitTests(tests) => {
+                    Self::build_bit_tests(bx, val, otherwise, alternate, cluster, tests)
                 }
             }
 
@@ -201,12 +264,38 @@ impl Switch {
         }
     }
 
+    fn build_range(
+        bx: &mut FunctionBuilder,
+        val: Value,
+        otherwise: Block,
+        alternate: Block,
+        cluster: &Cluster,
+        block: Block,
+    ) {
+        if cluster.first_index != cluster.last_index {
+            let offset = isub_imm_u128(bx, val, cluster.first_index);
+            let is_good_val = icmp_imm_u128(
+                bx,
+                IntCC::UnsignedLessThanOrEqual,
+                offset,
+                cluster.last_index - cluster.first_index,
+            );
+            bx.ins().brif(is_good_val, block, &[], alternate, &[]);
+        } else if cluster.first_index == 0 && alternate == otherwise {
+            bx.ins().brif(val, otherwise, &[], block, &[]);
+        } else {
+            let is_good_val = icmp_imm_u128(bx, IntCC::Equal, val, cluster.first_index);
+            bx.ins().brif(is_good_val, block, &[], alternate, &[]);
+        }
+    }
+
     fn build_jump_table(
         bx: &mut FunctionBuilder,
         val: Value,
         otherwise: Block,
+        alternate: Block,
         first_index: EntryIndex,
-        blocks: &[Block],
+        blocks: &[Option<Block>],
     ) {
         // There are currently no 128bit systems supported by rustc, but once we do ensure that
         // we don't silently ignore a part of the jump table for 128bit integers on 128bit systems.
@@ -215,28 +304,20 @@ impl Switch {
             "Jump tables bigger than 2^32-1 are not yet supported"
         );
 
+        // The entries without a case jump to `otherwise`, and the values
+        // outside of the table to `alternate`.
         let jt_data = JumpTableData::new(
-            bx.func.dfg.block_call(otherwise, &[]),
+            bx.func.dfg.block_call(alternate, &[]),
             &blocks
                 .iter()
-                .map(|block| bx.func.dfg.block_call(*block, &[]))
+                .map(|block| bx.func.dfg.block_call(block.unwrap_or(otherwise), &[]))
                 .collect::<Vec<_>>(),
         );
         let jump_table = bx.create_jump_table(jt_data);
 
-        let discr = if first_index == 0 {
-            val
-        } else {
-            if let Ok(first_index) = u64::try_from(first_index) {
-                bx.ins().iadd_imm(val, (first_index as i64).wrapping_neg())
-            } else {
-                let (lsb, msb) = (first_index as u64, (first_index >> 64) as u64);
-                let lsb = bx.ins().iconst(types::I64, lsb as i64);
-                let msb = bx.ins().iconst(types::I64, msb as i64);
-                let index = bx.ins().iconcat(lsb, msb);
-                bx.ins().isub(val, index)
-            }
-        };
+        // Values below `first_index` wrap around to indices past the end of
+        // the table, so they don't need a check of their own.
+        let discr = isub_imm_u128(bx, val, first_index);
 
         let discr = match bx.func.dfg.value_type(discr).bits() {
             bits if bits > 32 => {
@@ -246,7 +327,7 @@ impl Switch {
                     bx.ins()
                         .icmp_imm(IntCC::UnsignedGreaterThan, discr, u32::MAX as i64);
                 bx.ins()
-                    .brif(bigger_than_u32, otherwise, &[], new_block, &[]);
+                    .brif(bigger_than_u32, alternate, &[], new_block, &[]);
                 bx.seal_block(new_block);
                 bx.switch_to_block(new_block);
 
@@ -260,6 +341,55 @@ impl Switch {
         bx.ins().br_table(discr, jump_table);
     }
 
+    fn build_bit_tests(
+        bx: &mut FunctionBuilder,
+        val: Value,
+        otherwise: Block,
+        alternate: Block,
+        cluster: &Cluster,
+        tests: &[BitTest],
+    ) {
+        let span = cluster.last_index - cluster.first_index;
+        let offset = isub_imm_u128(bx, val, cluster.first_index);
+        let in_range = icmp_imm_u128(bx, IntCC::UnsignedLessThanOrEqual, offset, span);
+        let tests_block = bx.create_block();
+        bx.ins().brif(in_range, tests_block, &[], alternate, &[]);
+        bx.seal_block(tests_block);
+        bx.switch_to_block(tests_block);
+
+        // Test the bit of `offset` in the mask of the entries of each block.
+        let offset = match bx.func.dfg.value_type(offset).bits() {
+            bits if bits < 64 => bx.ins().uextend(types::I64, offset),
+            64 => offset,
+            _ => bx.ins().ireduce(types::I64, offset),
+        };
+        let one = bx.ins().iconst(types::I64, 1);
+        let bit = bx.ins().ishl(one, offset);
+
+        // If every entry has a case then the last test can be skipped.
+        let all_entries = u64::MAX >> (63 - span);
+        let has_holes = tests.iter().fold(0, |mask, test| mask | test.mask) != all_entries;
+        for (ix, test) in tests.iter().enumerate() {
+            if ix == tests.len() - 1 && !has_holes {
+                bx.ins().jump(test.block, &[]);
+                break;
+            }
+
+            let next = if ix == tests.len() - 1 {
+                otherwise
+            } else {
+                bx.create_block()
+            };
+            let is_good_val = bx.ins().band_imm(bit, test.mask as i64);
+            bx.ins().brif(is_good_val, test.block, &[], next, &[]);
+
+            if next != otherwise {
+                bx.seal_block(next);
+                bx.switch_to_block(next);
+            }
+        }
+    }
+
     /// Build the switch
     ///
     /// # Arguments
@@ -276,11 +406,18 @@ impl Switch {
             panic!("The index type {val_ty} does not fit the maximum switch entry of {max}");
         }
 
-        let contiguous_case_ranges = self.collect_contiguous_case_ranges();
-        Self::build_search_tree(bx, val, otherwise, &contiguous_case_ranges);
+        let clusters = self.collect_clusters();
+        Self::build_search_tree(bx, val, otherwise, &clusters);
     }
 }
 
+fn iconst_u128(bx: &mut FunctionBuilder, x: u128) -> Value {
+    let (lsb, msb) = (x as u64, (x >> 64) as u64);
+    let lsb = bx.ins().iconst(types::I64, lsb as i64);
+    let msb = bx.ins().iconst(types::I64, msb as i64);
+    bx.ins().iconcat(lsb, msb)
+}
+
 fn icmp_imm_u128(bx: &mut FunctionBuilder, cond: IntCC, x: Value, y: u128) -> Value {
     if bx.func.dfg.value_type(x) != types::I128 {
         assert!(u64::try_from(y).is_ok());
@@ -288,48 +425,197 @@ fn icmp_imm_u128(bx: &mut FunctionBuilder, cond: IntCC, x: Value, y: u128) -> Va
     } else if let Ok(index) = i64::try_from(y) {
         bx.ins().icmp_imm(cond, x, index)
     } else {
-        let (lsb, msb) = (y as u64, (y >> 64) as u64);
-        let lsb = bx.ins().iconst(types::I64, lsb as i64);
-        let msb = bx.ins().iconst(types::I64, msb as i64);
-        let index = bx.ins().iconcat(lsb, msb);
+        let index = iconst_u128(bx, y);
         bx.ins().icmp(cond, x, index)
     }
 }
 
-/// This represents a contiguous range of cases to switch on.
+/// Subtracts `y` from `x`, wrapping around.
+fn isub_imm_u128(bx: &mut FunctionBuilder, x: Value, y: u128) -> Value {
+    if y == 0 {
+        x
+    } else if bx.func.dfg.value_type(x) != types::I128 {
+        let y = u64::try_from(y).unwrap();
+        bx.ins().iadd_imm(x, (y as i64).wrapping_neg())
+    } else if let Ok(y) = i64::try_from(y) {
+        // The immediate is sign-extended to 128 bits, so this only works for
+        // values that fit in an `i64`.
+        bx.ins().iadd_imm(x, -y)
+    } else {
+        let y = iconst_u128(bx, y);
+        bx.ins().isub(x, y)
+    }
+}
+
+/// A case of a switch.
+#[derive(Clone, Copy, Debug)]
+struct Case {
+    index: EntryIndex,
+    block: Block,
+    weight: u64,
+}
+
+/// This represents a cluster of cases to switch on which is lowered as a
+/// whole.
 ///
-/// For example 10 => block1, 11 => block2, 12 => block7 will be represented as:
+/// For example 10 => block1, 11 => block2, 13 => block7, 14 => block2 could be
+/// represented as:
 ///
 /// ```plain
-/// ContiguousCaseRange {
+/// Cluster {
 ///     first_index: 10,
-///     blocks: vec![Block::from_u32(1), Block::from_u32(2), Block::from_u32(7)]
+///     last_index: 14,
+///     num_cases: 4,
+///     weight: 4,
+///     kind: ClusterKind::JumpTable(vec![
+///         Some(Block::from_u32(1)),
+///         Some(Block::from_u32(2)),
+///         None,
+///         Some(Block::from_u32(7)),
+///         Some(Block::from_u32(2)),
+///     ]),
 /// }
 /// ```
 #[derive(Debug)]
-struct ContiguousCaseRange {
+struct Cluster {
     /// The entry index of the first case. Eg. 10 when the entry indexes are 10, 11, 12 and 13.
     first_index: EntryIndex,
 
-    /// The blocks to jump to sorted in ascending order of entry index.
-    blocks: Vec<Block>,
+    /// The entry index of the last case. Eg. 13 when the entry indexes are 10, 11, 12 and 13.
+    last_index: EntryIndex,
+
+    /// The number of cases in this cluster.
+    num_cases: usize,
+
+    /// The sum of the weights of the cases in this cluster.
+    weight: u64,
+
+    /// How this cluster is lowered.
+    kind: ClusterKind,
 }
 
-impl ContiguousCaseRange {
-    fn new(first_index: EntryIndex) -> Self {
+#[derive(Debug)]
+enum ClusterKind {
+    /// Every entry of the cluster jumps to the same block.
+    Range(Block),
+
+    /// A jump table with the block of each entry, in ascending order of
+    /// entry index, or `None` for the entries that have no case.
+    JumpTable(Vec<Option<Block>>),
+
+    /// A test of a bit mask of the entries for each block, the most likely
+    /// block first.
+    BitTests(Vec<BitTest>),
+}
+
+/// The entries that jump to `block` in a cluster lowered to bit tests: bit `i`
+/// of `mask` is set if entry `first_index + i` jumps to `block`.
+#[derive(Debug)]
+struct BitTest {
+    block: Block,
+    mask: u64,
+    weight: u64,
+}
+
+impl Cluster {
+    fn new(cases: &[Case], kind: ClusterKind) -> Self {
         Self {
-            first_index,
-            blocks: Vec::new(),
+            first_index: cases[0].index,
+            last_index: cases[cases.len() - 1].index,
+            num_cases: cases.len(),
+            weight: cases
+                .iter()
+                .fold(0, |weight: u64, case| weight.saturating_add(case.weight)),
+            kind,
         }
     }
 
-    /// Returns `Some` block when there is only a single block in this range.
-    fn single_block(&self) -> Option<Block> {
-        if self.blocks.len() == 1 {
-            Some(self.blocks[0])
-        } else {
-            None
+    /// The number of leading `cases` with contiguous entry indices that jump
+    /// to the same block.
+    fn range_len(cases: &[Case]) -> usize {
+        cases
+            .windows(2)
+            .take_while(|w| w[1].index == w[0].index + 1 && w[1].block == w[0].block)
+            .count()
+            + 1
+    }
+
+    /// The number of leading `cases` with contiguous entry indices.
+    fn contiguous_len(cases: &[Case]) -> usize {
+        cases
+            .windows(2)
+            .take_while(|w| w[1].index == w[0].index + 1)
+            .count()
+            + 1
+    }
+
+    /// The number of leading `cases` that bit tests can cover.
+    fn bit_tests_len(cases: &[Case]) -> usize {
+        let mut blocks = Vec::with_capacity(MAX_BIT_TEST_BLOCKS);
+        for (i, case) in cases.iter().enumerate() {
+            if case.index - cases[0].index >= MAX_BIT_TEST_ENTRIES {
+                return i;
+            }
+            if !blocks.contains(&case.block) {
+                if blocks.len() == MAX_BIT_TEST_BLOCKS {
+                    return i;
+                }
+                blocks.push(case.block);
+            }
+        }
+        cases.len()
+    }
+
+    /// The number of leading `cases` that a dense enough jump table can
+    /// cover.
+    fn jump_table_len(cases: &[Case]) -> usize {
+        let max_entries = cases.len() as u128 * 100 / MIN_JUMP_TABLE_DENSITY;
+        let mut len = 1;
+        for (i, case) in cases.iter().enumerate().skip(1) {
+            let entries = case.index - cases[0].index + 1;
+            // No table ending at a later case can be dense enough either.
+            if entries > max_entries {
+                break;
+            }
+            if entries * MIN_JUMP_TABLE_DENSITY <= (i as u128 + 1) * 100 {
+                len = i + 1;
+            }
         }
+        len
+    }
+
+    fn range(cases: &[Case]) -> Self {
+        Self::new(cases, ClusterKind::Range(cases[0].block))
+    }
+
+    fn jump_table(cases: &[Case]) -> Self {
+        let first_index = cases[0].index;
+        let num_entries = usize::try_from(cases[cases.len() - 1].index - first_index + 1).unwrap();
+        let mut blocks = vec![None; num_entries];
+        for case in cases {
+            blocks[usize::try_from(case.index - first_index).unwrap()] = Some(case.block);
+        }
+        Self::new(cases, ClusterKind::JumpTable(blocks))
+    }
+
+    fn bit_tests(cases: &[Case]) -> Self {
+        let mut tests: Vec<BitTest> = Vec::with_capacity(MAX_BIT_TEST_BLOCKS);
+        for case in cases {
+            let bit = 1 << (case.index - cases[0].index);
+            match tests.iter_mut().find(|test| test.block == case.block) {
+                Some(test) => {
+                    test.mask |= bit;
+                    test.weight = test.weight.saturating_add(case.weight);
+                }
+                None => tests.push(BitTest {
+                    block: case.block,
+                    mask: bit,
+                    weight: case.weight,
+                }),
+            }
+        }
+        tests.sort_by_key(|test| (Reverse(test.weight), Reverse(test.mask.count_ones())));
+        Self::new(cases, ClusterKind::BitTests(tests))
     }
 }
 
@@ -337,7 +623,7 @@ impl ContiguousCaseRange {
 mod tests {
     use super::*;
     use crate::frontend::FunctionBuilderContext;
-    use alloc::string::ToString;
+    use alloc::string::{String, ToString};
 
     macro_rules! setup {
         ($default:expr, [$($index:expr,)*]) => {{
@@ -432,29 +718,8 @@ block3:
             func,
             "block0:
     v0 = iconst.i8 0
-    v1 = icmp_imm uge v0, 7  ; v0 = 0
-    brif v1, block9, block8
-
-block9:
-    v2 = icmp_imm.i8 uge v0, 10  ; v0 = 0
-    brif v2, block11, block10
-
-block11:
-    v3 = iadd_imm.i8 v0, -10  ; v0 = 0
-    v4 = uextend.i32 v3
-    br_table v4, block0, [block5, block6, block7]
-
-block10:
-    v5 = icmp_imm.i8 eq v0, 7  ; v0 = 0
-    brif v5, block4, block0
-
-block8:
-    v6 = icmp_imm.i8 eq v0, 5  ; v0 = 0
-    brif v6, block3, block12
-
-block12:
-    v7 = uextend.i32 v0  ; v0 = 0
-    br_table v7, block0, [block1, block2]"
+    v1 = uextend.i32 v0  ; v0 = 0
+    br_table v1, block0, [block1, block2, block0, block0, block0, block3, block0, block4, block0, block0, block5, block6, block7]"
         );
     }
 
@@ -497,12 +762,12 @@ block3:
             func,
             "block0:
     v0 = iconst.i8 0
-    v1 = icmp_imm eq v0, -1  ; v0 = 0
-    brif v1, block1, block4
+    v1 = uextend.i32 v0  ; v0 = 0
+    br_table v1, block4, [block2, block3]
 
 block4:
-    v2 = uextend.i32 v0  ; v0 = 0
-    br_table v2, block0, [block2, block3]"
+    v2 = icmp_imm.i8 eq v0, -1  ; v0 = 0
+    brif v2, block1, block0"
         );
     }
 
@@ -673,4 +938,151 @@ block4:
     brif.i128 v1, block3, block2"
         );
     }
+
+    fn emit_switch(ty: Type, entries: &[(EntryIndex, u32, Option<u64>)]) -> String {
+        let mut func = Function::new();
+        let mut func_ctx = FunctionBuilderContext::new();
+        {
+            let mut bx = FunctionBuilder::new(&mut func, &mut func_ctx);
+            let block0 = bx.create_block();
+            bx.switch_to_block(block0);
+            let val = bx.ins().iconst(types::I64, 0);
+            let val = match ty.bits() {
+                bits if bits < 64 => bx.ins().ireduce(ty, val),
+                64 => val,
+                _ => bx.ins().uextend(ty, val),
+            };
+            let mut switch = Switch::new();
+            for &(index, block, weight) in entries {
+                while bx.func.dfg.num_blocks() <= block as usize {
+                    bx.create_block();
+                }
+                let block = Block::from_u32(block);
+                match weight {
+                    Some(weight) => switch.set_entry_with_weight(index, block, weight),
+                    None => switch.set_entry(index, block),
+                }
+            }
+            let otherwise = bx.create_block();
+            switch.emit(&mut bx, val, otherwise);
+        }
+        func.to_string()
+            .trim_start_matches("function u0:0() fast {\n")
+            .trim_end_matches("\n}\n")
+            .to_string()
+    }
+
+    #[test]
+    fn switch_bit_tests() {
+        let func = emit_switch(
+            types::I32,
+            &[
+                (10, 1, None),
+                (13, 2, None),
+                (20, 1, None),
+                (33, 2, None),
+                (40, 1, None),
+                (50, 3, None),
+                (60, 2, None),
+            ],
+        );
+        assert_eq_output!(
+            func,
+            "block0:
+    v0 = iconst.i64 0
+    v1 = ireduce.i32 v0  ; v0 = 0
+    v2 = iadd_imm v1, -10
+    v3 = icmp_imm ule v2, 50
+    brif v3, block5, block4
+
+block5:
+    v4 = uextend.i64 v2
+    v5 = iconst.i64 1
+    v6 = ishl v5, v4  ; v5 = 1
+    v7 = band_imm v6, 0x4000_0401
+    brif v7, block1, block6
+
+block6:
+    v8 = band_imm.i64 v6, 0x0004_0000_0080_0008
+    brif v8, block2, block7
+
+block7:
+    v9 = band_imm.i64 v6, 0x0100_0000_0000
+    brif v9, block3, block4"
+        );
+    }
+
+    #[test]
+    fn switch_sparse_jump_table() {
+        let func = emit_switch(
+            types::I128,
+            &[
+                (1 << 100, 1, None),
+                ((1 << 100) + 2, 2, None),
+                ((1 << 100) + 3, 3, None),
+                ((1 << 100) + 5, 4, None),
+            ],
+        );
+        assert_eq_output!(
+            func,
+            "block0:
+    v0 = iconst.i64 0
+    v1 = uextend.i128 v0  ; v0 = 0
+    v2 = iconst.i64 0
+    v3 = iconst.i64 0x0010_0000_0000
+    v4 = iconcat v2, v3  ; v2 = 0, v3 = 0x0010_0000_0000
+    v5 = isub v1, v4
+    v6 = icmp_imm ugt v5, 0xffff_ffff
+    brif v6, block5, block6
+
+block6:
+    v7 = ireduce.i32 v5
+    br_table v7, block5, [block1, block5, block2, block3, block5, block4]"
+        );
+    }
+
+    #[test]
+    fn switch_weights() {
+        let func = emit_switch(
+            types::I32,
+            &[
+                (0, 1, Some(1)),
+                (100, 2, Some(1)),
+                (200, 3, Some(1)),
+                (300, 4, Some(1)),
+                (400, 5, Some(1000)),
+            ],
+        );
+        assert_eq_output!(
+            func,
+            "block0:
+    v0 = iconst.i64 0
+    v1 = ireduce.i32 v0  ; v0 = 0
+    v2 = icmp_imm uge v1, 400
+    brif v2, block8, block7
+
+block8:
+    v3 = icmp_imm.i32 eq v1, 400
+    brif v3, block5, block6
+
+block7:
+    v4 = icmp_imm.i32 uge v1, 200
+    brif v4, block10, block9
+
+block10:
+    v5 = icmp_imm.i32 eq v1, 300
+    brif v5, block4, block11
+
+block11:
+    v6 = icmp_imm.i32 eq v1, 200
+    brif v6, block3, block6
+
+block9:
+    v7 = icmp_imm.i32 eq v1, 100
+    brif v7, block2, block12
+
+block12:
+    brif.i32 v1, block6, block1"
+        );
+    }
 }
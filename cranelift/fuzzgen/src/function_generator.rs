@@ -9,9 +9,10 @@ use cranelift::codegen::ir::instructions::{InstructionFormat, ResolvedConstraint
 use cranelift::codegen::ir::stackslot::StackSize;
 
 use cranelift::codegen::ir::{
-    AliasRegion, AtomicRmwOp, Block, BlockArg, ConstantData, Endianness, ExternalName, FuncRef,
-    Function, LibCall, Opcode, SigRef, Signature, StackSlot, UserExternalName, UserFuncName, Value,
-    types::*,
+    AliasRegion, AtomicRmwOp, Block, BlockArg, ConstantData, DynamicStackSlot,
+    DynamicStackSlotData, DynamicTypeData, Endianness, ExternalName, FuncRef, Function,
+    GlobalValueData, LibCall, Opcode, SigRef, Signature, StackSlot, UserExternalName, UserFuncName,
+    Value, types::*,
 };
 use cranelift::codegen::isa::CallConv;
 use cranelift::frontend::{FunctionBuilder, FunctionBuilderContext, Switch, Variable};
@@ -126,10 +127,14 @@ fn insert_call(
 fn insert_stack_load(
     fgen: &mut FunctionGenerator,
     builder: &mut FunctionBuilder,
-    _opcode: Opcode,
-    _args: &[Type],
+    opcode: Opcode,
+    args: &[Type],
     rets: &[Type],
 ) -> Result<()> {
+    if opcode == Opcode::StackAddr {
+        return insert_stack_addr(fgen, builder, args, rets);
+    }
+
     let typevar = rets[0];
     let type_size = typevar.bytes();
     let (slot, slot_size, _align, category) = fgen.stack_slot_with_size(type_size)?;
@@ -176,6 +181,82 @@ fn insert_stack_store(
     Ok(())
 }
 
+fn insert_stack_addr(
+    fgen: &mut FunctionGenerator,
+    builder: &mut FunctionBuilder,
+    _args: &[Type],
+    rets: &[Type],
+) -> Result<()> {
+    let addr_ty = rets[0];
+    let type_size = addr_ty.bytes();
+    let (slot, slot_size, _align, category) = fgen.stack_slot_with_size(type_size)?;
+    let offset = fgen.u.int_in_range(0..=(slot_size - type_size))? as i32;
+
+    // Addresses are never stored in variables (see `generate_load_store_address`), so we
+    // immediately load a value through the address instead.
+    let addr = builder.ins().stack_addr(addr_ty, slot, offset);
+    let mut flags = MemFlags::new();
+    flags.set_notrap();
+    category.update_memflags(&mut flags);
+
+    let val = builder.ins().load(addr_ty, flags, addr, 0);
+    let var = fgen.get_variable_of_type(addr_ty)?;
+    builder.def_var(var, val);
+
+    Ok(())
+}
+
+fn insert_dynamic_stack_load(
+    fgen: &mut FunctionGenerator,
+    builder: &mut FunctionBuilder,
+    opcode: Opcode,
+    _args: &[Type],
+    rets: &[Type],
+) -> Result<()> {
+    // Dynamic vectors can't be stored in variables, so these instructions are modeled in
+    // terms of the base vector type of the slot. See `OPCODE_SIGNATURES`.
+    let (res, vector_ty) = match opcode {
+        Opcode::DynamicStackLoad => {
+            let vector_ty = rets[0];
+            let (slot, dyn_ty) = fgen.dynamic_stack_slot_with_base(vector_ty)?;
+            let val = builder.ins().dynamic_stack_load(dyn_ty, slot);
+            (builder.ins().extract_vector(val, 0), vector_ty)
+        }
+        Opcode::DynamicStackAddr => {
+            // Like `stack_addr`, immediately load the first vector through the address.
+            let addr_ty = rets[0];
+            let &(slot, vector_ty, _dyn_ty) = fgen.u.choose(&fgen.resources.dynamic_stack_slots)?;
+            let addr = builder.ins().dynamic_stack_addr(addr_ty, slot);
+            let mut flags = MemFlags::new();
+            flags.set_notrap();
+            (builder.ins().load(vector_ty, flags, addr, 0), vector_ty)
+        }
+        _ => unreachable!(),
+    };
+
+    let var = fgen.get_variable_of_type(vector_ty)?;
+    builder.def_var(var, res);
+    Ok(())
+}
+
+fn insert_dynamic_stack_store(
+    fgen: &mut FunctionGenerator,
+    builder: &mut FunctionBuilder,
+    _opcode: Opcode,
+    args: &[Type],
+    _rets: &[Type],
+) -> Result<()> {
+    // The stored dynamic vector is a splat of a lane, see `insert_dynamic_stack_load`.
+    let lane_ty = args[0];
+    let vector_ty = lane_ty.by(128 / lane_ty.bits()).unwrap();
+    let (slot, dyn_ty) = fgen.dynamic_stack_slot_with_base(vector_ty)?;
+
+    let lane = builder.use_var(fgen.get_variable_of_type(lane_ty)?);
+    let val = builder.ins().splat(dyn_ty, lane);
+    builder.ins().dynamic_stack_store(val, slot);
+    Ok(())
+}
+
 fn insert_cmp(
     fgen: &mut FunctionGenerator,
     builder: &mut FunctionBuilder,
@@ -516,6 +597,13 @@ fn valid_for_target(triple: &Triple, op: Opcode, args: &[Type], rets: &[Type]) -
         // all in fuzzgen just yet.
         Opcode::StackSwitch => return false,
 
+        // Dynamic vectors are only supported on AArch64 for now.
+        Opcode::DynamicStackLoad | Opcode::DynamicStackStore | Opcode::DynamicStackAddr
+            if !matches!(triple.architecture, Architecture::Aarch64(_)) =>
+        {
+            return false;
+        }
+
         _ => {}
     }
 
@@ -789,6 +877,9 @@ fn valid_for_target(triple: &Triple, op: Opcode, args: &[Type], rets: &[Type]) -
 
 type OpcodeSignature = (Opcode, Vec<Type>, Vec<Type>);
 
+/// Base vector types of the dynamic vectors used by `DynamicStackLoad` and `DynamicStackStore`.
+const DYNAMIC_VECTOR_BASE_TYPES: &[Type] = &[I8X16, I16X8, I32X4, I64X2, F32X4, F64X2];
+
 static OPCODE_SIGNATURES: LazyLock<Vec<OpcodeSignature>> = LazyLock::new(|| {
     let types = &[
         I8, I16, I32, I64, I128, // Scalar Integers
@@ -829,6 +920,10 @@ static OPCODE_SIGNATURES: LazyLock<Vec<OpcodeSignature>> = LazyLock::new(|| {
                 // uses dynamic vectors.
                 Opcode::ExtractVector => false,
 
+                // Dynamic vectors can't be stored in variables, so the signatures of these are
+                // added separately below.
+                Opcode::DynamicStackLoad | Opcode::DynamicStackStore => false,
+
                 _ => true,
             }
         })
@@ -885,6 +980,14 @@ static OPCODE_SIGNATURES: LazyLock<Vec<OpcodeSignature>> = LazyLock::new(|| {
                 argss.into_iter().map(move |args| (*op, args, rets.clone()))
             })
         })
+        // Dynamic stack loads are modeled as producing their base vector type and stores as
+        // taking a lane to splat into the slot.
+        .chain(DYNAMIC_VECTOR_BASE_TYPES.iter().flat_map(|&ty| {
+            [
+                (Opcode::DynamicStackLoad, vec![], vec![ty]),
+                (Opcode::DynamicStackStore, vec![ty.lane_type()], vec![]),
+            ]
+        }))
         .filter(|(op, args, rets)| {
             // These op/signature combinations need to be vetted
             exceptions!(
@@ -899,16 +1002,8 @@ static OPCODE_SIGNATURES: LazyLock<Vec<OpcodeSignature>> = LazyLock::new(|| {
                 (Opcode::FuncAddr),
                 (Opcode::X86Pshufb),
                 (Opcode::AvgRound),
-                (Opcode::Uload8x8),
-                (Opcode::Sload8x8),
-                (Opcode::Uload16x4),
-                (Opcode::Sload16x4),
-                (Opcode::Uload32x2),
-                (Opcode::Sload32x2),
-                (Opcode::StackAddr),
-                (Opcode::DynamicStackLoad),
-                (Opcode::DynamicStackStore),
-                (Opcode::DynamicStackAddr),
+                // Only 64-bit targets are fuzzed at the moment.
+                (Opcode::StackAddr | Opcode::DynamicStackAddr, &[], &[I32]),
                 (Opcode::GlobalValue),
                 (Opcode::SymbolValue),
                 (Opcode::TlsValue),
@@ -1079,8 +1174,8 @@ fn inserter_for_format(fmt: InstructionFormat) -> OpcodeInserter {
         InstructionFormat::Call => insert_call,
         InstructionFormat::CallIndirect => insert_call,
         InstructionFormat::CondTrap => todo!(),
-        InstructionFormat::DynamicStackLoad => todo!(),
-        InstructionFormat::DynamicStackStore => todo!(),
+        InstructionFormat::DynamicStackLoad => insert_dynamic_stack_load,
+        InstructionFormat::DynamicStackStore => insert_dynamic_stack_store,
         InstructionFormat::FloatCompare => insert_cmp,
         InstructionFormat::FuncAddr => todo!(),
         InstructionFormat::IntAddTrap => todo!(),
@@ -1202,6 +1297,8 @@ struct Resources {
     /// We use this invariant when searching for stack slots with a given size.
     /// See [FunctionGenerator::stack_slot_with_size]
     stack_slots: Vec<(StackSlot, StackSize, StackAlignment, AACategory)>,
+    /// Dynamic stack slots along with their base vector type and dynamic vector type.
+    dynamic_stack_slots: Vec<(DynamicStackSlot, Type, Type)>,
     usercalls: Vec<(UserExternalName, Signature)>,
     libcalls: Vec<LibCall>,
 }
@@ -1295,6 +1392,16 @@ where
         Ok(*self.u.choose(&self.resources.stack_slots[first..])?)
     }
 
+    /// Finds the dynamic stack slot whose dynamic vector type has `base` as its base vector type.
+    fn dynamic_stack_slot_with_base(&mut self, base: Type) -> Result<(DynamicStackSlot, Type)> {
+        self.resources
+            .dynamic_stack_slots
+            .iter()
+            .find(|&&(_slot, vector_ty, _dyn_ty)| vector_ty == base)
+            .map(|&(slot, _vector_ty, dyn_ty)| (slot, dyn_ty))
+            .ok_or_else(|| arbitrary::Error::IncorrectFormat.into())
+    }
+
     /// Generates an address that should allow for a store or a load.
     ///
     /// Addresses aren't generated like other values. They are never stored in variables so that
@@ -1641,6 +1748,28 @@ where
             .stack_slots
             .sort_unstable_by_key(|&(_slot, bytes, _align, _category)| bytes);
 
+        // Dynamic vectors are only supported on AArch64 for now. Each base vector type gets a
+        // slot so that any dynamic stack access can find one.
+        if matches!(self.isa.triple().architecture, Architecture::Aarch64(_)) {
+            for &vector_ty in DYNAMIC_VECTOR_BASE_TYPES {
+                let scale = builder.create_global_value(GlobalValueData::DynScaleTargetConst {
+                    vector_type: vector_ty,
+                });
+                let dt = builder
+                    .func
+                    .dfg
+                    .make_dynamic_ty(DynamicTypeData::new(vector_ty, scale));
+                let dyn_ty = builder.func.get_concrete_dynamic_ty(dt).unwrap();
+
+                let ss_data = DynamicStackSlotData::new(StackSlotKind::ExplicitDynamicSlot, dt);
+                let slot = builder.create_dynamic_stack_slot(ss_data);
+
+                self.resources
+                    .dynamic_stack_slots
+                    .push((slot, vector_ty, dyn_ty));
+            }
+        }
+
         Ok(())
     }
 
@@ -1678,6 +1807,16 @@ where
                 size -= filled;
             }
         }
+
+        for &(slot, vector_ty, dyn_ty) in self.resources.dynamic_stack_slots.iter() {
+            let zero = match vector_ty.lane_type() {
+                F32 => builder.ins().f32const(0.0),
+                F64 => builder.ins().f64const(0.0),
+                ty => builder.ins().iconst(ty, 0),
+            };
+            let val = builder.ins().splat(dyn_ty, zero);
+            builder.ins().dynamic_stack_store(val, slot);
+        }
         Ok(())
     }
 
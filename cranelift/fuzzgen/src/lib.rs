@@ -1,6 +1,6 @@
 use crate::config::Config;
 use crate::function_generator::FunctionGenerator;
-use crate::settings::{Flags, OptLevel};
+use crate::settings::{Flags, OptLevel, RegallocAlgorithm};
 use anyhow::Result;
 use arbitrary::{Arbitrary, Unstructured};
 use cranelift::codegen::Context;
@@ -177,6 +177,11 @@ where
         let opt = self.u.choose(OptLevel::all())?;
         builder.set("opt_level", &format!("{opt}")[..]).unwrap();
 
+        let regalloc = self.u.choose(RegallocAlgorithm::all())?;
+        builder
+            .set("regalloc_algorithm", &format!("{regalloc}")[..])
+            .unwrap();
+
         // Boolean flags
         // TODO: enable_pinned_reg does not work with our current trampolines. See: #4376
         // TODO: is_pic has issues:
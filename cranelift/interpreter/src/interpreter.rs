@@ -11,8 +11,8 @@ use crate::step::{ControlFlow, CraneliftTrap, StepError, step};
 use crate::value::{DataValueExt, ValueError};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::{
-    ArgumentPurpose, Block, Endianness, ExternalName, FuncRef, Function, GlobalValue,
-    GlobalValueData, LibCall, MemFlags, StackSlot, Type,
+    ArgumentPurpose, Block, DynamicStackSlot, Endianness, ExternalName, FuncRef, Function,
+    GlobalValue, GlobalValueData, LibCall, MemFlags, StackSlot, Type,
 };
 use log::trace;
 use smallvec::SmallVec;
@@ -237,6 +237,28 @@ impl<'a> InterpreterState<'a> {
     }
 }
 
+/// The scaling factor applied to the base vector type of every dynamic vector type.
+///
+/// The interpreter models dynamic vectors as a single copy of their base vector type, which
+/// matches the native backends that all use 128-bit dynamic vectors.
+pub(crate) const DYNAMIC_VECTOR_SCALE: u32 = 1;
+
+/// Number of bytes occupied by a dynamic stack slot.
+fn dynamic_stack_slot_size(function: &Function, slot: DynamicStackSlot) -> u32 {
+    let dyn_ty = function.dynamic_stack_slots[slot].dyn_ty;
+    function.dfg.dynamic_types[dyn_ty].base_vector_ty.bytes() * DYNAMIC_VECTOR_SCALE
+}
+
+/// Number of bytes of stack space that a frame for this function requires.
+fn frame_size(function: &Function) -> usize {
+    let dynamic_size: u32 = function
+        .dynamic_stack_slots
+        .keys()
+        .map(|slot| dynamic_stack_slot_size(function, slot))
+        .sum();
+    (function.fixed_stack_size() + dynamic_size) as usize
+}
+
 impl<'a> State<'a> for InterpreterState<'a> {
     fn get_function(&self, func_ref: FuncRef) -> Option<&'a Function> {
         self.functions
@@ -252,12 +274,12 @@ impl<'a> State<'a> for InterpreterState<'a> {
 
     fn push_frame(&mut self, function: &'a Function) {
         if let Some(frame) = self.frame_stack.iter().last() {
-            self.frame_offset += frame.function().fixed_stack_size() as usize;
+            self.frame_offset += frame_size(frame.function());
         }
 
         // Grow the stack by the space necessary for this frame
         self.stack
-            .extend(iter::repeat(0).take(function.fixed_stack_size() as usize));
+            .extend(iter::repeat(0).take(frame_size(function)));
 
         self.frame_stack.push(Frame::new(function));
     }
@@ -265,11 +287,11 @@ impl<'a> State<'a> for InterpreterState<'a> {
         if let Some(frame) = self.frame_stack.pop() {
             // Shorten the stack after exiting the frame
             self.stack
-                .truncate(self.stack.len() - frame.function().fixed_stack_size() as usize);
+                .truncate(self.stack.len() - frame_size(frame.function()));
 
             // Reset frame_offset to the start of this function
             if let Some(frame) = self.frame_stack.iter().last() {
-                self.frame_offset -= frame.function().fixed_stack_size() as usize;
+                self.frame_offset -= frame_size(frame.function());
             }
         }
     }
@@ -318,6 +340,34 @@ impl<'a> State<'a> for InterpreterState<'a> {
         Address::from_parts(size, AddressRegion::Stack, 0, final_offset)
     }
 
+    fn dynamic_stack_address(
+        &self,
+        size: AddressSize,
+        slot: DynamicStackSlot,
+    ) -> Result<Address, MemoryError> {
+        let func = self.get_current_function();
+
+        // Dynamic stack slots are laid out after all of the sized stack slots.
+        let slot_offset: u64 = func
+            .dynamic_stack_slots
+            .keys()
+            .filter(|k| k < &slot)
+            .map(|k| dynamic_stack_slot_size(func, k) as u64)
+            .sum();
+
+        let final_offset =
+            self.frame_offset as u64 + u64::from(func.fixed_stack_size()) + slot_offset;
+        Address::from_parts(size, AddressRegion::Stack, 0, final_offset)
+    }
+
+    fn frame_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        Address::from_parts(size, AddressRegion::Stack, 0, self.frame_offset as u64)
+    }
+
+    fn stack_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        Address::from_parts(size, AddressRegion::Stack, 0, self.stack.len() as u64)
+    }
+
     fn checked_load(
         &self,
         addr: Address,
@@ -525,7 +575,9 @@ impl<'a> State<'a> for InterpreterState<'a> {
                         action_stack.push(ResolveAction::Resolve(base));
                     }
                     GlobalValueData::Symbol { .. } => unimplemented!(),
-                    GlobalValueData::DynScaleTargetConst { .. } => unimplemented!(),
+                    GlobalValueData::DynScaleTargetConst { .. } => {
+                        current_val = DataValue::I64(DYNAMIC_VECTOR_SCALE as i64);
+                    }
                 },
                 Some(ResolveAction::Add(dv)) => {
                     current_val = current_val
@@ -1059,4 +1111,108 @@ mod tests {
         // Ensure that the correct trap was propagated.
         assert_eq!(trap, ControlFlow::Trap(CraneliftTrap::HeapMisaligned));
     }
+
+    // The frame pointer marks the start of the current frame and the stack pointer its end, with
+    // dynamic stack slots placed after all of the sized stack slots.
+    #[test]
+    fn frame_and_stack_pointers() {
+        let code = "
+        function %callee() -> i64, i64 {
+        block0:
+            v0 = get_frame_pointer.i64
+            v1 = get_stack_pointer.i64
+            return v0, v1
+        }
+
+        function %caller() -> i64, i64, i64, i64, i64, i64 {
+            fn0 = %callee() -> i64, i64
+            gv0 = dyn_scale_target_const.i32x4
+            dt0 = i32x4*gv0
+            ss0 = explicit_slot 8
+            dss0 = explicit_dynamic_slot dt0
+
+        block0:
+            v0 = get_frame_pointer.i64
+            v1 = get_stack_pointer.i64
+            v2 = stack_addr.i64 ss0
+            v3 = dynamic_stack_addr.i64 dss0
+            v4, v5 = call fn0()
+            return v0, v1, v2, v3, v4, v5
+        }";
+
+        let mut env = FunctionStore::default();
+        let funcs = parse_functions(code).unwrap().to_vec();
+        funcs.iter().for_each(|f| env.add(f.name.to_string(), f));
+
+        let state = InterpreterState::default().with_function_store(env);
+        let result = Interpreter::new(state)
+            .call_by_name("%caller", &[])
+            .unwrap();
+
+        let stack_addr = |offset| {
+            let addr = Address::from_parts(AddressSize::_64, AddressRegion::Stack, 0, offset);
+            DataValue::try_from(addr.unwrap()).unwrap()
+        };
+        assert_eq!(
+            result,
+            ControlFlow::Return(smallvec![
+                stack_addr(0),
+                stack_addr(24),
+                stack_addr(0),
+                stack_addr(8),
+                stack_addr(24),
+                stack_addr(24),
+            ])
+        );
+    }
+
+    #[test]
+    fn dynamic_stack_slots_multi_functions() {
+        let code = "
+        function %callee(i32) -> i32x4 {
+            gv0 = dyn_scale_target_const.i32x4
+            dt0 = i32x4*gv0
+            dss0 = explicit_dynamic_slot dt0
+
+        block0(v0: i32):
+            v1 = splat.dt0 v0
+            dynamic_stack_store v1, dss0
+            v2 = dynamic_stack_load.dt0 dss0
+            v3 = iadd v2, v2
+            v4 = extract_vector v3, 0
+            return v4
+        }
+
+        function %caller(i32, i32) -> i32x4 {
+            fn0 = %callee(i32) -> i32x4
+            gv0 = dyn_scale_target_const.i32x4
+            dt0 = i32x4*gv0
+            dss0 = explicit_dynamic_slot dt0
+
+        block0(v0: i32, v1: i32):
+            v2 = splat.dt0 v0
+            dynamic_stack_store v2, dss0
+            v3 = call fn0(v1)
+            v4 = dynamic_stack_addr.i64 dss0
+            v5 = load.i32x4 v4
+            v6 = iadd v3, v5
+            return v6
+        }";
+
+        let mut env = FunctionStore::default();
+        let funcs = parse_functions(code).unwrap().to_vec();
+        funcs.iter().for_each(|f| env.add(f.name.to_string(), f));
+
+        let state = InterpreterState::default().with_function_store(env);
+        let result = Interpreter::new(state)
+            .call_by_name("%caller", &[DataValue::I32(1), DataValue::I32(2)])
+            .unwrap();
+
+        assert_eq!(
+            result,
+            ControlFlow::Return(smallvec![DataValue::V128([
+                5, 0, 0, 0, 5, 0, 0, 0, 5, 0, 0, 0, 5, 0, 0, 0
+            ])])
+        );
+    }
 }
@@ -1,6 +1,19 @@
 //! Cranelift IR interpreter.
 //!
 //! This module is a project for interpreting Cranelift IR.
+//!
+//! Almost every CLIF opcode can be interpreted. The exceptions, which return
+//! [`step::StepError::UnsupportedInstruction`], are:
+//!
+//! * `try_call` and `try_call_indirect`, since exception handling isn't
+//!   modeled.
+//! * `stack_switch`, since there is only ever one stack.
+//! * `get_return_address`, since interpreted calls have no native return
+//!   address.
+//!
+//! Dynamic vector types are modeled with a scale of 1, i.e. a dynamic vector
+//! is always exactly as large as its base vector type. This matches the native
+//! backends, but it means `extract_vector` only accepts index 0.
 
 pub mod address;
 pub mod environment;
@@ -3,13 +3,14 @@
 use crate::address::{Address, AddressSize};
 use crate::frame::Frame;
 use crate::instruction::InstructionContext;
+use crate::interpreter::DYNAMIC_VECTOR_SCALE;
 use crate::state::{InterpreterFunctionRef, MemoryError, State};
 use crate::value::{DataValueExt, ValueConversionKind, ValueError, ValueResult};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
 use cranelift_codegen::ir::{
     AbiParam, AtomicRmwOp, Block, BlockArg, BlockCall, Endianness, ExternalName, FuncRef, Function,
-    InstructionData, MemFlags, Opcode, TrapCode, Type, Value as ValueRef, types,
+    InstructionData, KnownSymbol, MemFlags, Opcode, TrapCode, Type, Value as ValueRef, types,
 };
 use log::trace;
 use smallvec::{SmallVec, smallvec};
@@ -65,6 +66,12 @@ where
 {
     let inst = inst_context.data();
     let ctrl_ty = inst_context.controlling_type().unwrap();
+    // Dynamic vectors are modeled as their base vector type, see `DYNAMIC_VECTOR_SCALE`.
+    let ctrl_ty = if ctrl_ty.is_dynamic_vector() {
+        ctrl_ty.dynamic_to_vector().unwrap()
+    } else {
+        ctrl_ty
+    };
     trace!(
         "Step: {}{}",
         inst.opcode(),
@@ -229,7 +236,6 @@ where
     // instruction's results.
     let unary =
         |op: fn(DataValue) -> ValueResult<DataValue>, arg: DataValue| -> ValueResult<ControlFlow> {
-            let ctrl_ty = inst_context.controlling_type().unwrap();
             let res = unary_arith(arg, ctrl_ty, op)?;
             Ok(assign(res))
         };
@@ -240,7 +246,6 @@ where
                   left: DataValue,
                   right: DataValue|
      -> ValueResult<ControlFlow> {
-        let ctrl_ty = inst_context.controlling_type().unwrap();
         let res = binary_arith(left, right, ctrl_ty, op)?;
         Ok(assign(res))
     };
@@ -250,7 +255,6 @@ where
                            left: DataValue,
                            right: DataValue|
      -> ValueResult<ControlFlow> {
-        let ctrl_ty = inst_context.controlling_type().unwrap();
         let res = binary_arith(left, right, ctrl_ty, op);
         assign_or_trap(res)
     };
@@ -413,7 +417,10 @@ where
                     InterpreterFunctionRef::Function(function)
                 }
                 ExternalName::LibCall(libcall) => InterpreterFunctionRef::LibCall(libcall),
-                ExternalName::KnownSymbol(_) => unimplemented!(),
+                // Known symbols refer to data, such as the GOT, and are never callable.
+                ExternalName::KnownSymbol(symbol) => {
+                    return Err(StepError::UncallableSymbol(symbol));
+                }
             };
 
             let make_control_flow = match inst.opcode() {
@@ -479,7 +486,6 @@ where
         | Opcode::Sload16x4
         | Opcode::Uload32x2
         | Opcode::Sload32x2 => {
-            let ctrl_ty = inst_context.controlling_type().unwrap();
             let (load_ty, kind) = match inst.opcode() {
                 Opcode::Load => (ctrl_ty, None),
                 Opcode::Uload8 => (types::I8, Some(ValueConversionKind::ZeroExtend(ctrl_ty))),
@@ -488,12 +494,31 @@ where
                 Opcode::Sload16 => (types::I16, Some(ValueConversionKind::SignExtend(ctrl_ty))),
                 Opcode::Uload32 => (types::I32, Some(ValueConversionKind::ZeroExtend(ctrl_ty))),
                 Opcode::Sload32 => (types::I32, Some(ValueConversionKind::SignExtend(ctrl_ty))),
-                Opcode::Uload8x8
-                | Opcode::Sload8x8
-                | Opcode::Uload16x4
-                | Opcode::Sload16x4
-                | Opcode::Uload32x2
-                | Opcode::Sload32x2 => unimplemented!(),
+                // The extending vector loads read 64 bits and widen each lane.
+                Opcode::Uload8x8 => (
+                    types::I8X8,
+                    Some(ValueConversionKind::ZeroExtend(types::I16)),
+                ),
+                Opcode::Sload8x8 => (
+                    types::I8X8,
+                    Some(ValueConversionKind::SignExtend(types::I16)),
+                ),
+                Opcode::Uload16x4 => (
+                    types::I16X4,
+                    Some(ValueConversionKind::ZeroExtend(types::I32)),
+                ),
+                Opcode::Sload16x4 => (
+                    types::I16X4,
+                    Some(ValueConversionKind::SignExtend(types::I32)),
+                ),
+                Opcode::Uload32x2 => (
+                    types::I32X2,
+                    Some(ValueConversionKind::ZeroExtend(types::I64)),
+                ),
+                Opcode::Sload32x2 => (
+                    types::I32X2,
+                    Some(ValueConversionKind::SignExtend(types::I64)),
+                ),
                 _ => unreachable!(),
             };
 
@@ -504,10 +529,22 @@ where
                     .and_then(|addr| state.checked_load(addr, load_ty, mem_flags)),
             );
 
+            let extend = |loaded: DataValue, c: &ValueConversionKind| {
+                if load_ty.is_vector() {
+                    let lanes = extractlanes(&loaded, load_ty)?
+                        .into_iter()
+                        .map(|lane| lane.convert(c.clone()))
+                        .collect::<ValueResult<SimdVec<_>>>()?;
+                    vectorizelanes(&lanes, load_ty.double_width().unwrap())
+                } else {
+                    loaded.convert(c.clone())
+                }
+            };
+
             match (loaded, kind) {
                 (ControlFlow::Assign(ret), Some(c)) => ControlFlow::Assign(
                     ret.into_iter()
-                        .map(|loaded| loaded.convert(c.clone()))
+                        .map(|loaded| extend(loaded, &c))
                         .collect::<ValueResult<SmallVec<[DataValue; 1]>>>()?,
                 ),
                 (cf, _) => cf,
@@ -535,7 +572,7 @@ where
             )
         }
         Opcode::StackLoad => {
-            let load_ty = inst_context.controlling_type().unwrap();
+            let load_ty = ctrl_ty;
             let slot = inst.stack_slot().unwrap();
             let offset = sum_unsigned(imm(), args())? as u64;
             let mem_flags = MemFlags::new();
@@ -568,9 +605,35 @@ where
                 })
             })
         }
-        Opcode::DynamicStackAddr => unimplemented!("DynamicStackSlot"),
-        Opcode::DynamicStackLoad => unimplemented!("DynamicStackLoad"),
-        Opcode::DynamicStackStore => unimplemented!("DynamicStackStore"),
+        Opcode::DynamicStackAddr => {
+            let slot = inst.dynamic_stack_slot().unwrap();
+            assign_or_memtrap({
+                AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                    let addr = state.dynamic_stack_address(addr_size, slot)?;
+                    let dv = DataValue::try_from(addr)?;
+                    Ok(dv)
+                })
+            })
+        }
+        Opcode::DynamicStackLoad => {
+            let slot = inst.dynamic_stack_slot().unwrap();
+            let mem_flags = MemFlags::new();
+            assign_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_load(addr, ctrl_ty, mem_flags))
+            })
+        }
+        Opcode::DynamicStackStore => {
+            let arg = arg(0);
+            let slot = inst.dynamic_stack_slot().unwrap();
+            let mem_flags = MemFlags::new();
+            continue_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_store(addr, arg, mem_flags))
+            })
+        }
         Opcode::GlobalValue | Opcode::SymbolValue | Opcode::TlsValue => {
             if let InstructionData::UnaryGlobalValue { global_value, .. } = inst {
                 assign_or_memtrap(state.resolve_global_value(global_value))
@@ -888,20 +951,32 @@ where
         Opcode::Fneg => unary(DataValueExt::neg, arg(0))?,
         Opcode::Fabs => unary(DataValueExt::abs, arg(0))?,
         Opcode::Fcopysign => binary(DataValueExt::copysign, arg(0), arg(1))?,
-        Opcode::Fmin => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
-            (a, b) => a.smin(b)?,
-        }),
-        Opcode::Fmax => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
-            (a, b) => a.smax(b)?,
-        }),
+        Opcode::Fmin => binary(
+            |a, b| {
+                Ok(match (a, b) {
+                    (a, _) if a.is_nan()? => a,
+                    (_, b) if b.is_nan()? => b,
+                    (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
+                    (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
+                    (a, b) => a.smin(b)?,
+                })
+            },
+            arg(0),
+            arg(1),
+        )?,
+        Opcode::Fmax => binary(
+            |a, b| {
+                Ok(match (a, b) {
+                    (a, _) if a.is_nan()? => a,
+                    (_, b) if b.is_nan()? => b,
+                    (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
+                    (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
+                    (a, b) => a.smax(b)?,
+                })
+            },
+            arg(0),
+            arg(1),
+        )?,
         Opcode::Ceil => unary(DataValueExt::ceil, arg(0))?,
         Opcode::Floor => unary(DataValueExt::floor, arg(0))?,
         Opcode::Trunc => unary(DataValueExt::trunc, arg(0))?,
@@ -1275,7 +1350,7 @@ where
             // actually need to emit a fence here.
             ControlFlow::Continue
         }
-        Opcode::SqmulRoundSat => {
+        Opcode::SqmulRoundSat | Opcode::X86Pmulhrsw => {
             let lane_type = ctrl_ty.lane_type();
             let double_width = ctrl_ty.double_width().unwrap().lane_type();
             let arg0 = extractlanes(&arg(0), ctrl_ty)?;
@@ -1294,9 +1369,14 @@ where
                         (x * y + (1 << (lane_type.bits() - 2))) >> (lane_type.bits() - 1),
                         double_width,
                     )?;
-                    // check bounds, saturate, and truncate to correct width.
-                    let z = DataValueExt::smin(z, max.clone())?;
-                    let z = DataValueExt::smax(z, min.clone())?;
+                    // check bounds, saturate, and truncate to correct width. `x86_pmulhrsw`
+                    // doesn't saturate and wraps around when both lanes are `MIN` instead.
+                    let z = if inst.opcode() == Opcode::SqmulRoundSat {
+                        let z = DataValueExt::smin(z, max.clone())?;
+                        DataValueExt::smax(z, min.clone())?
+                    } else {
+                        z
+                    };
                     let z = z.convert(ValueConversionKind::Truncate(lane_type))?;
                     Ok(z)
                 })
@@ -1307,20 +1387,101 @@ where
             assign(binary_pairwise(arg(0), arg(1), ctrl_ty, DataValueExt::add)?)
         }
         Opcode::ExtractVector => {
-            unimplemented!("ExtractVector not supported");
-        }
-        Opcode::GetFramePointer => unimplemented!("GetFramePointer"),
-        Opcode::GetStackPointer => unimplemented!("GetStackPointer"),
-        Opcode::GetReturnAddress => unimplemented!("GetReturnAddress"),
-        Opcode::X86Pshufb => unimplemented!("X86Pshufb"),
-        Opcode::X86Blendv => unimplemented!("X86Blendv"),
-        Opcode::X86Pmulhrsw => unimplemented!("X86Pmulhrsw"),
-        Opcode::X86Pmaddubsw => unimplemented!("X86Pmaddubsw"),
-        Opcode::X86Cvtt2dq => unimplemented!("X86Cvtt2dq"),
-        Opcode::StackSwitch => unimplemented!("StackSwitch"),
-
-        Opcode::TryCall => unimplemented!("TryCall"),
-        Opcode::TryCallIndirect => unimplemented!("TryCallIndirect"),
+            // Each dynamic vector holds `DYNAMIC_VECTOR_SCALE` copies of its base vector type.
+            // That scale is always 1, so the only valid index is 0 and it extracts the whole
+            // vector. Larger scales would need `DataValue`s wider than 128 bits.
+            let index = imm().into_int_unsigned()? as u32;
+            if index >= DYNAMIC_VECTOR_SCALE {
+                return Err(StepError::ValueError(ValueError::InvalidValue(ctrl_ty)));
+            }
+            assign(arg(0))
+        }
+        Opcode::GetFramePointer => assign_or_memtrap({
+            AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                let addr = state.frame_pointer(addr_size)?;
+                let dv = DataValue::try_from(addr)?;
+                Ok(dv)
+            })
+        }),
+        Opcode::GetStackPointer => assign_or_memtrap({
+            AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                let addr = state.stack_pointer(addr_size)?;
+                let dv = DataValue::try_from(addr)?;
+                Ok(dv)
+            })
+        }),
+        Opcode::X86Pshufb => {
+            let x = DataValueExt::into_array(&arg(0))?;
+            let s = DataValueExt::into_array(&arg(1))?;
+            let mut new = [0u8; 16];
+            for i in 0..new.len() {
+                if s[i] & 0x80 == 0 {
+                    new[i] = x[(s[i] & 0xf) as usize];
+                } // else leave as 0
+            }
+            assign(DataValueExt::vector(new, types::I8X16)?)
+        }
+        Opcode::X86Blendv => {
+            let c = extractlanes(&arg(0), ctrl_ty.as_int())?;
+            let x = extractlanes(&arg(1), ctrl_ty)?;
+            let y = extractlanes(&arg(2), ctrl_ty)?;
+            let new_vec = c
+                .into_iter()
+                .zip(x)
+                .zip(y)
+                .map(|((c, x), y)| Ok(if c.into_int_signed()? < 0 { x } else { y }))
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, ctrl_ty)?)
+        }
+        Opcode::X86Pmaddubsw => {
+            let x = DataValueExt::into_array(&arg(0))?;
+            let y = DataValueExt::into_array(&arg(1))?;
+            let new_vec = x
+                .chunks(2)
+                .zip(y.chunks(2))
+                .map(|(x, y)| {
+                    // Signed bytes from `x` are multiplied with unsigned bytes from `y`.
+                    let sum: i32 = x
+                        .iter()
+                        .zip(y)
+                        .map(|(&x, &y)| i32::from(x as i8) * i32::from(y))
+                        .sum();
+                    let sum = sum.clamp(i16::MIN.into(), i16::MAX.into());
+                    DataValueExt::int(sum.into(), types::I16)
+                })
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, types::I16X8)?)
+        }
+        Opcode::X86Cvtt2dq => {
+            let in_ty = inst_context.type_of(inst_context.args()[0]).unwrap();
+            let cvt = |x: DataValue| -> ValueResult<DataValue> {
+                let x = x.into_float()?;
+                // NaN and out-of-bounds lanes produce `INT_MIN`, which is then sign extended
+                // to the width of the result lane.
+                let x = if x > -2147483649.0 && x < 2147483648.0 {
+                    x as i32
+                } else {
+                    i32::MIN
+                };
+                DataValueExt::int(x.into(), ctrl_ty.lane_type())
+            };
+            let x = extractlanes(&arg(0), in_ty)?;
+            assign(vectorizelanes(
+                &x.into_iter()
+                    .map(cvt)
+                    .collect::<ValueResult<SimdVec<DataValue>>>()?,
+                ctrl_ty,
+            )?)
+        }
+
+        // There is no native return address or stack to switch in the interpreter, and
+        // exception handling isn't modeled, so these report an error instead of executing.
+        Opcode::GetReturnAddress
+        | Opcode::StackSwitch
+        | Opcode::TryCall
+        | Opcode::TryCallIndirect => {
+            return Err(StepError::UnsupportedInstruction(inst.opcode()));
+        }
     })
 }
 
@@ -1334,6 +1495,10 @@ pub enum StepError {
     ValueError(#[from] ValueError),
     #[error("failed to access memory")]
     MemoryError(#[from] MemoryError),
+    #[error("unable to call the following symbol: {0}")]
+    UncallableSymbol(KnownSymbol),
+    #[error("the interpreter does not support the following instruction: {0}")]
+    UnsupportedInstruction(Opcode),
 }
 
 /// Enumerate the ways in which the control flow can change based on a single step in a Cranelift
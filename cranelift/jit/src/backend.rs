@@ -3,6 +3,7 @@
 use crate::{
     compiled_blob::CompiledBlob,
     memory::{BranchProtection, JITMemoryProvider, SystemMemoryProvider},
+    plt::PltEntry,
 };
 use cranelift_codegen::binemit::Reloc;
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
@@ -19,7 +20,8 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::io::Write;
-use std::ptr;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::AtomicPtr;
 use target_lexicon::PointerWidth;
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
@@ -32,6 +34,7 @@ pub struct JITBuilder {
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     memory: Option<Box<dyn JITMemoryProvider>>,
+    hotswap_enabled: bool,
 }
 
 impl JITBuilder {
@@ -96,6 +99,7 @@ impl JITBuilder {
             lookup_symbols,
             libcall_names,
             memory: None,
+            hotswap_enabled: false,
         }
     }
 
@@ -154,6 +158,18 @@ impl JITBuilder {
         self.memory = Some(provider);
         self
     }
+
+    /// Enable or disable hotswap support.
+    ///
+    /// When enabled, all references to functions defined in the module go through a PLT entry,
+    /// which allows redefining functions after they have been finalized. See
+    /// [`JITModule::prepare_for_function_redefine`] for more information.
+    ///
+    /// Hotswapping is currently supported on x86_64 and aarch64.
+    pub fn hotswap(&mut self, enabled: bool) -> &mut Self {
+        self.hotswap_enabled = enabled;
+        self
+    }
 }
 
 /// A wrapper that impls Send for the contents.
@@ -179,6 +195,9 @@ pub struct JITModule {
     code_ranges: Vec<(usize, usize, FuncId)>,
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
+    hotswap_enabled: bool,
+    function_plt_entries: SecondaryMap<FuncId, Option<PltEntry>>,
+    superseded_functions: Vec<(FuncId, CompiledBlob)>,
 }
 
 impl JITModule {
@@ -217,7 +236,12 @@ impl JITModule {
                 let (name, linkage) = if ModuleDeclarations::is_function(name) {
                     let func_id = FuncId::from_name(name);
                     match &self.compiled_functions[func_id] {
-                        Some(compiled) => return compiled.ptr,
+                        Some(compiled) => {
+                            return match &self.function_plt_entries[func_id] {
+                                Some(plt_entry) => plt_entry.stub,
+                                None => compiled.ptr,
+                            };
+                        }
                         None => {
                             let decl = self.declarations.get_function_decl(func_id);
                             (&decl.name, decl.linkage)
@@ -257,15 +281,23 @@ impl JITModule {
     ///
     /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
     /// some way of deallocating this individual function is used.
+    ///
+    /// When hotswapping is enabled, this is the address of the function's PLT entry, which always
+    /// jumps to the most recently finalized definition of the function.
     pub fn get_finalized_function(&self, func_id: FuncId) -> *const u8 {
         let info = &self.compiled_functions[func_id];
         assert!(
             !self.functions_to_finalize.iter().any(|x| *x == func_id),
             "function not yet finalized"
         );
-        info.as_ref()
+        let ptr = info
+            .as_ref()
             .expect("function must be compiled before it can be finalized")
-            .ptr
+            .ptr;
+        match &self.function_plt_entries[func_id] {
+            Some(plt_entry) => plt_entry.stub,
+            None => ptr,
+        }
     }
 
     /// Returns the address and size of a finalized data object.
@@ -311,7 +343,8 @@ impl JITModule {
     ///
     /// Returns ModuleError in case of allocation or syscall failure
     pub fn finalize_definitions(&mut self) -> ModuleResult<()> {
-        for func in std::mem::take(&mut self.functions_to_finalize) {
+        let functions_to_finalize = std::mem::take(&mut self.functions_to_finalize);
+        for &func in &functions_to_finalize {
             let decl = self.declarations.get_function_decl(func);
             assert!(decl.linkage.is_definable());
             let func = self.compiled_functions[func]
@@ -340,6 +373,103 @@ impl JITModule {
         };
         self.memory.finalize(branch_protection)?;
 
+        // The new definitions are executable now, so redirect all callers to them.
+        for func in functions_to_finalize {
+            if let Some(plt_entry) = &self.function_plt_entries[func] {
+                plt_entry.update(self.compiled_functions[func].as_ref().unwrap().ptr);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Allow a single future `define_function` on a previously defined function. This allows for
+    /// hot code swapping of functions.
+    ///
+    /// Code calling the function or taking its address doesn't need to be redefined: it refers to
+    /// the function's PLT entry, which starts jumping to the new definition once that has been
+    /// finalized by [`JITModule::finalize_definitions`]. Until then the previous definition
+    /// remains in use.
+    ///
+    /// The memory of the previous definition is kept until
+    /// [`JITModule::free_superseded_functions`] is called.
+    ///
+    /// Panics if hotswapping wasn't enabled with [`JITBuilder::hotswap`].
+    pub fn prepare_for_function_redefine(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        assert!(self.hotswap_enabled, "Hotswap support is not enabled");
+        let decl = self.declarations.get_function_decl(func_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+
+        let Some(compiled) = self.compiled_functions[func_id].take() else {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "Tried to redefine not yet defined function {}",
+                decl.linkage_name(func_id)
+            )));
+        };
+        self.functions_to_finalize.retain(|&id| id != func_id);
+        self.code_ranges.retain(|&(_, _, id)| id != func_id);
+        self.superseded_functions.push((func_id, compiled));
+
+        Ok(())
+    }
+
+    /// Free the memory of function definitions which were replaced using
+    /// [`JITModule::prepare_for_function_redefine`] and whose replacement has been finalized.
+    ///
+    /// Definitions that may still be called through the function's PLT entry are kept.
+    ///
+    /// # Safety
+    ///
+    /// None of the replaced definitions may be executing, and pointers to them obtained before
+    /// the function was redefined must not be used afterwards.
+    pub unsafe fn free_superseded_functions(&mut self) {
+        let superseded_functions = std::mem::take(&mut self.superseded_functions);
+        for (func_id, compiled) in superseded_functions {
+            let plt_entry = self.function_plt_entries[func_id].as_ref().unwrap();
+            if plt_entry.target() == compiled.ptr as *const u8 {
+                self.superseded_functions.push((func_id, compiled));
+            } else {
+                self.memory.free_readexec(compiled.ptr, compiled.size);
+            }
+        }
+    }
+
+    /// Allocate the PLT and GOT entry of a function which may be redefined later.
+    fn new_func_plt_entry(&mut self, id: FuncId) -> ModuleResult<()> {
+        let got = self
+            .memory
+            .allocate_readwrite(
+                size_of::<AtomicPtr<u8>>(),
+                align_of::<AtomicPtr<u8>>() as u64,
+            )
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc function GOT entry",
+                err: e,
+            })?
+            .cast::<AtomicPtr<u8>>();
+        unsafe { got.write(AtomicPtr::new(ptr::null_mut())) };
+
+        let code = PltEntry::code(self.isa.triple().architecture, got);
+        let align = (self.isa.function_alignment().minimum as u64).max(16);
+        let stub = self
+            .memory
+            .allocate_readexec(code.len(), align)
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc function PLT entry",
+                err: e,
+            })?;
+        unsafe { ptr::copy_nonoverlapping(code.as_ptr(), stub, code.len()) };
+
+        let decl = self.declarations.get_function_decl(id);
+        self.record_function_for_perf(stub, code.len(), &format!("{}@plt", decl.linkage_name(id)));
+        self.function_plt_entries[id] = Some(PltEntry {
+            stub,
+            got: NonNull::new(got).unwrap(),
+        });
         Ok(())
     }
 
@@ -349,6 +479,13 @@ impl JITModule {
             !builder.isa.flags().is_pic(),
             "cranelift-jit needs is_pic=false"
         );
+        if builder.hotswap_enabled {
+            let arch = builder.isa.triple().architecture;
+            assert!(
+                PltEntry::is_supported(arch),
+                "cranelift-jit hotswapping is not supported on {arch}"
+            );
+        }
 
         let memory = builder
             .memory
@@ -365,6 +502,9 @@ impl JITModule {
             code_ranges: Vec::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
+            hotswap_enabled: builder.hotswap_enabled,
+            function_plt_entries: SecondaryMap::new(),
+            superseded_functions: Vec::new(),
         }
     }
 
@@ -423,14 +563,21 @@ impl Module for JITModule {
         linkage: Linkage,
         signature: &ir::Signature,
     ) -> ModuleResult<FuncId> {
-        let (id, _linkage) = self
+        let (id, linkage) = self
             .declarations
             .declare_function(name, linkage, signature)?;
+        if self.hotswap_enabled && linkage.is_definable() && self.function_plt_entries[id].is_none()
+        {
+            self.new_func_plt_entry(id)?;
+        }
         Ok(id)
     }
 
     fn declare_anonymous_function(&mut self, signature: &ir::Signature) -> ModuleResult<FuncId> {
         let id = self.declarations.declare_anonymous_function(signature)?;
+        if self.hotswap_enabled {
+            self.new_func_plt_entry(id)?;
+        }
         Ok(id)
     }
 
@@ -474,10 +621,32 @@ impl Module for JITModule {
             ));
         }
 
-        // work around borrow-checker to allow reuse of ctx below
-        let res = ctx.compile(self.isa(), ctrl_plane)?;
-        let alignment = res.buffer.alignment as u64;
-        let compiled_code = ctx.compiled_code().unwrap();
+        ctx.compile(self.isa(), ctrl_plane)?;
+        self.define_compiled_function(id, ctx)
+    }
+
+    fn define_compiled_function(
+        &mut self,
+        id: FuncId,
+        ctx: &cranelift_codegen::Context,
+    ) -> ModuleResult<()> {
+        let decl = self.declarations.get_function_decl(id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(id).into_owned(),
+            ));
+        }
+
+        if !self.compiled_functions[id].is_none() {
+            return Err(ModuleError::DuplicateDefinition(
+                decl.linkage_name(id).into_owned(),
+            ));
+        }
+
+        let compiled_code = ctx
+            .compiled_code()
+            .expect("function must be compiled before it is defined");
+        let alignment = compiled_code.buffer.alignment as u64;
 
         let size = compiled_code.code_info().total_size as usize;
         let align = alignment
@@ -130,6 +130,32 @@ impl CompiledBlob {
                         modify_inst32(jalr_addr, |jalr| (jalr & 0xFFFFF) | (lo12 << 20));
                     }
                 }
+                Reloc::LoongArchCall36 => {
+                    // A R_LARCH_CALL36 relocation expects a pcaddu18i+jirl
+                    // instruction pair. The `pcaddu18i` supplies bits 37:18 of
+                    // the offset and the `jirl` the remaining bits 17:2, both
+                    // as signed values.
+                    let base = get_address(name);
+                    let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
+                    let pcrel = i64::try_from((what as isize) - (at as isize)).unwrap();
+                    assert!(
+                        (-(1 << 37)..(1 << 37)).contains(&pcrel),
+                        "call36 target out of range"
+                    );
+
+                    let hi20 = ((pcrel + 0x2_0000) >> 18) as u32 & 0xF_FFFF;
+                    let lo16 = ((pcrel & 0x3_FFFF) >> 2) as u32 & 0xFFFF;
+
+                    unsafe {
+                        let pcaddu18i_addr = at as *mut u32;
+                        modify_inst32(pcaddu18i_addr, |inst| {
+                            (inst & !(0xF_FFFF << 5)) | (hi20 << 5)
+                        });
+
+                        let jirl_addr = at.offset(4) as *mut u32;
+                        modify_inst32(jirl_addr, |inst| (inst & !(0xFFFF << 10)) | (lo16 << 10));
+                    }
+                }
                 _ => unimplemented!(),
             }
         }
@@ -9,6 +9,7 @@
 mod backend;
 mod compiled_blob;
 mod memory;
+mod plt;
 
 pub use crate::backend::{JITBuilder, JITModule};
 pub use crate::memory::{
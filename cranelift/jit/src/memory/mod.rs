@@ -25,6 +25,16 @@ pub trait JITMemoryProvider {
     /// Allocate memory that will be read-only once finalized.
     fn allocate_readonly(&mut self, size: usize, align: u64) -> io::Result<*mut u8>;
 
+    /// Free a single allocation previously returned by
+    /// [`JITMemoryProvider::allocate_readexec`].
+    ///
+    /// Providers that can't free individual allocations may ignore this, in which case the
+    /// memory is only freed by [`JITMemoryProvider::free_memory`]. This is what the default
+    /// implementation does.
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, size: usize) {
+        let _ = (ptr, size);
+    }
+
     /// Free the memory region.
     unsafe fn free_memory(&mut self);
     /// Finalize the memory region and apply memory protections.
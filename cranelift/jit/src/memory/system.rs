@@ -19,6 +19,8 @@ struct PtrLen {
 
     ptr: *mut u8,
     len: usize,
+    /// The number of bytes allocated from this region which haven't been freed.
+    live: usize,
 }
 
 impl PtrLen {
@@ -30,6 +32,7 @@ impl PtrLen {
 
             ptr: ptr::null_mut(),
             len: 0,
+            live: 0,
         }
     }
 
@@ -45,6 +48,7 @@ impl PtrLen {
                 ptr: mmap.as_mut_ptr(),
                 map: Some(mmap),
                 len: alloc_size,
+                live: 0,
             }
         })
     }
@@ -62,6 +66,7 @@ impl PtrLen {
             Ok(Self {
                 ptr,
                 len: alloc_size,
+                live: 0,
             })
         } else {
             Err(io::Error::from(io::ErrorKind::OutOfMemory))
@@ -87,6 +92,7 @@ impl PtrLen {
             Ok(Self {
                 ptr: ptr as *mut u8,
                 len: region::page::ceil(size as *const ()) as usize,
+                live: 0,
             })
         } else {
             Err(io::Error::last_os_error())
@@ -110,7 +116,24 @@ impl Drop for PtrLen {
     }
 }
 
-// TODO: add a `Drop` impl for `cfg(target_os = "windows")`
+#[cfg(target_os = "windows")]
+impl Drop for PtrLen {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            use windows_sys::Win32::System::Memory::{MEM_RELEASE, VirtualFree};
+
+            // `MEM_RELEASE` frees the whole region reserved by `VirtualAlloc`
+            // and requires a size of zero.
+            let ret = unsafe { VirtualFree(self.ptr.cast(), 0, MEM_RELEASE) };
+            assert_ne!(
+                ret,
+                0,
+                "unable to free memory: {}",
+                io::Error::last_os_error()
+            );
+        }
+    }
+}
 
 /// JIT memory manager. This manages pages of suitably aligned and
 /// accessible memory. Memory will be leaked by default to have
@@ -136,8 +159,12 @@ impl Memory {
     }
 
     fn finish_current(&mut self) {
-        self.allocations
-            .push(mem::replace(&mut self.current, PtrLen::new()));
+        let current = mem::replace(&mut self.current, PtrLen::new());
+        // Regions whose allocations have all been freed already are dropped
+        // here rather than kept around until the `Memory` itself is dropped.
+        if current.live != 0 {
+            self.allocations.push(current);
+        }
         self.position = 0;
     }
 
@@ -152,6 +179,7 @@ impl Memory {
             // TODO: Ensure overflow is not possible.
             let ptr = unsafe { self.current.ptr.add(self.position) };
             self.position += size;
+            self.current.live += size;
             return Ok(ptr);
         }
 
@@ -160,6 +188,7 @@ impl Memory {
         // TODO: Allocate more at a time.
         self.current = PtrLen::with_size(size)?;
         self.position = size;
+        self.current.live = size;
 
         Ok(self.current.ptr)
     }
@@ -211,6 +240,35 @@ impl Memory {
         return iter.filter(|&PtrLen { len, .. }| *len != 0);
     }
 
+    /// Frees a single allocation of `size` bytes at `ptr`.
+    ///
+    /// The memory region containing the allocation is freed once all allocations in it have been
+    /// freed, or reused for new allocations if it's the region currently being allocated from.
+    pub(crate) unsafe fn free(&mut self, ptr: *mut u8, size: usize) {
+        let contains = |alloc: &PtrLen| alloc.ptr <= ptr && ptr < alloc.ptr.wrapping_add(alloc.len);
+        if contains(&self.current) {
+            self.current.live -= size;
+            // Nothing in the current region is in use anymore and it hasn't been
+            // protected yet, so start handing it out again from the beginning.
+            if self.current.live == 0 {
+                self.position = 0;
+            }
+            return;
+        }
+        let idx = self
+            .allocations
+            .iter()
+            .position(contains)
+            .expect("freed memory wasn't allocated by this `Memory`");
+        self.allocations[idx].live -= size;
+        if self.allocations[idx].live == 0 {
+            self.allocations.remove(idx);
+            if idx < self.already_protected {
+                self.already_protected -= 1;
+            }
+        }
+    }
+
     /// Frees all allocated memory regions that would be leaked otherwise.
     /// Likely to invalidate existing function pointers, causing unsafety.
     pub(crate) unsafe fn free_memory(&mut self) {
@@ -258,6 +316,10 @@ impl JITMemoryProvider for SystemMemoryProvider {
         self.writable.free_memory();
     }
 
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, size: usize) {
+        self.code.free(ptr, size);
+    }
+
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()> {
         self.readonly.set_readonly()?;
         self.code.set_readable_and_executable(branch_protection)
@@ -275,3 +337,40 @@ impl JITMemoryProvider for SystemMemoryProvider {
         self.readonly.allocate(size, align)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_regions_are_not_kept() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(64, 16).unwrap();
+        memory.set_readonly().unwrap();
+
+        for _ in 0..100 {
+            let ptr = memory.allocate(64, 16).unwrap();
+            memory.set_readonly().unwrap();
+            unsafe { memory.free(ptr, 64) };
+        }
+        assert_eq!(memory.allocations.len(), 1);
+        assert_eq!(memory.already_protected, 1);
+
+        unsafe { memory.free(first, 64) };
+        assert!(memory.allocations.is_empty());
+        assert_eq!(memory.already_protected, 0);
+    }
+
+    #[test]
+    fn current_region_is_reused_once_freed() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(64, 16).unwrap();
+        unsafe { memory.free(first, 64) };
+        for _ in 0..100 {
+            let ptr = memory.allocate(64, 16).unwrap();
+            assert_eq!(ptr, first);
+            unsafe { memory.free(ptr, 64) };
+        }
+        assert!(memory.allocations.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! PLT and GOT entries used by `JITModule` to support redefining functions.
+//!
+//! When hotswapping is enabled, every definable function gets a GOT entry holding the address of
+//! its current definition and a PLT entry, a small piece of code which jumps to the address
+//! stored in the GOT entry. All references to the function resolve to the PLT entry, so
+//! redefining a function only requires updating its GOT entry.
+
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use target_lexicon::Architecture;
+
+/// The PLT and GOT entry of a single function.
+#[derive(Clone)]
+pub(crate) struct PltEntry {
+    /// The code jumping to the address stored in `got`.
+    pub(crate) stub: *const u8,
+    /// The address of the current definition of the function.
+    pub(crate) got: NonNull<AtomicPtr<u8>>,
+}
+
+impl PltEntry {
+    /// Whether PLT entries can be generated for `arch`.
+    pub(crate) fn is_supported(arch: Architecture) -> bool {
+        matches!(arch, Architecture::X86_64 | Architecture::Aarch64(_))
+    }
+
+    /// Returns the code of a PLT entry jumping to the address stored at `got`.
+    pub(crate) fn code(arch: Architecture, got: *const AtomicPtr<u8>) -> Vec<u8> {
+        let got = (got as u64).to_le_bytes();
+        let mut code = Vec::new();
+        match arch {
+            Architecture::X86_64 => {
+                // movabs r11, got
+                code.extend_from_slice(&[0x49, 0xbb]);
+                code.extend_from_slice(&got);
+                // jmp qword ptr [r11]
+                code.extend_from_slice(&[0x41, 0xff, 0x23]);
+                // Pad to 16 bytes with int3.
+                code.resize(16, 0xcc);
+            }
+            Architecture::Aarch64(_) => {
+                for inst in [
+                    // bti c; this is a nop when BTI isn't enabled.
+                    0xd503245f_u32,
+                    // ldr x16, #12
+                    0x58000070,
+                    // ldr x16, [x16]
+                    0xf9400210,
+                    // br x16
+                    0xd61f0200,
+                ] {
+                    code.extend_from_slice(&inst.to_le_bytes());
+                }
+                code.extend_from_slice(&got);
+            }
+            _ => panic!("PLT entries are not supported on {arch}"),
+        }
+        code
+    }
+
+    /// Make the PLT entry jump to `target` from now on.
+    pub(crate) fn update(&self, target: *const u8) {
+        unsafe { self.got.as_ref() }.store(target as *mut u8, Ordering::Release);
+    }
+
+    /// Returns the address the PLT entry currently jumps to.
+    pub(crate) fn target(&self) -> *const u8 {
+        unsafe { self.got.as_ref() }.load(Ordering::Acquire)
+    }
+}
@@ -215,3 +215,199 @@ fn empty_data_object() {
     data.define(Box::new([]));
     module.define_data(data_id, &data).unwrap();
 }
+
+fn define_add_const(module: &mut JITModule, name: &str, n: i64) -> (FuncId, Context) {
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: module.isa().default_call_conv(),
+    };
+    let func_id = module.declare_function(name, Linkage::Local, &sig).unwrap();
+
+    let mut ctx = module.make_context();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let res = bcx.ins().iadd_imm(x, n);
+        bcx.ins().return_(&[res]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    (func_id, ctx)
+}
+
+#[test]
+fn define_functions_in_parallel() {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let mut funcs = (0..64)
+        .map(|i| define_add_const(&mut module, &format!("add{i}"), i))
+        .collect::<Vec<_>>();
+    module.define_functions(&mut funcs).unwrap();
+    module.finalize_definitions().unwrap();
+
+    for (i, (func_id, ctx)) in funcs.iter().enumerate() {
+        assert!(ctx.compiled_code().is_some());
+        let ptr = module.get_finalized_function(*func_id);
+        let f: extern "C" fn(i64) -> i64 = unsafe { std::mem::transmute(ptr) };
+        assert_eq!(f(100), 100 + i as i64);
+    }
+}
+
+#[test]
+fn define_functions_rejects_duplicates() {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let (func_id, mut ctx) = define_add_const(&mut module, "add", 1);
+    let mut funcs = vec![(func_id, Context::for_function(ctx.func.clone()))];
+    module.define_function(func_id, &mut ctx).unwrap();
+
+    let err = module.define_functions(&mut funcs).err().unwrap();
+    assert!(matches!(err, ModuleError::DuplicateDefinition(_)), "{err}");
+}
+
+fn hotswap_module() -> Option<JITModule> {
+    if !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        return None;
+    }
+    let mut builder = JITBuilder::with_isa(isa()?, default_libcall_names());
+    builder.hotswap(true);
+    Some(JITModule::new(builder))
+}
+
+fn define_const(module: &mut JITModule, func_id: FuncId, n: i64) {
+    let mut ctx = module.make_context();
+    ctx.func.signature.returns.push(AbiParam::new(types::I64));
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let res = bcx.ins().iconst(types::I64, n);
+        bcx.ins().return_(&[res]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+}
+
+#[test]
+fn hotswap_redefine_function() {
+    let Some(mut module) = hotswap_module() else {
+        return;
+    };
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let callee = module
+        .declare_function("callee", Linkage::Local, &sig)
+        .unwrap();
+    let caller = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+
+    let mut ctx = module.make_context();
+    ctx.func.signature = sig.clone();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let callee_ref = module.declare_func_in_func(callee, bcx.func);
+        let call = bcx.ins().call(callee_ref, &[]);
+        let res = bcx.inst_results(call)[0];
+        let res = bcx.ins().iadd_imm(res, 1);
+        bcx.ins().return_(&[res]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(caller, &mut ctx).unwrap();
+    define_const(&mut module, callee, 1);
+    module.finalize_definitions().unwrap();
+
+    let caller_fn: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(caller)) };
+    let callee_fn: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(callee)) };
+    assert_eq!(caller_fn(), 2);
+    assert_eq!(callee_fn(), 1);
+
+    // Without preparing for redefinition, redefining is an error.
+    let mut ctx = module.make_context();
+    ctx.func.signature = sig.clone();
+    let err = module.define_function(callee, &mut ctx).unwrap_err();
+    assert!(matches!(err, ModuleError::DuplicateDefinition(_)), "{err}");
+
+    module.prepare_for_function_redefine(callee).unwrap();
+    define_const(&mut module, callee, 41);
+    // The previous definition stays in use until the new one is finalized.
+    assert_eq!(caller_fn(), 2);
+    module.finalize_definitions().unwrap();
+    assert_eq!(caller_fn(), 42);
+    assert_eq!(callee_fn(), 41);
+
+    unsafe { module.free_superseded_functions() };
+    module.prepare_for_function_redefine(callee).unwrap();
+    define_const(&mut module, callee, 99);
+    module.finalize_definitions().unwrap();
+    unsafe { module.free_superseded_functions() };
+    assert_eq!(caller_fn(), 100);
+    assert_eq!(callee_fn(), 99);
+}
+
+#[test]
+fn hotswap_redefine_function_repeatedly() {
+    let Some(mut module) = hotswap_module() else {
+        return;
+    };
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let func_id = module.declare_function("f", Linkage::Local, &sig).unwrap();
+    define_const(&mut module, func_id, 0);
+    module.finalize_definitions().unwrap();
+    let f: extern "C" fn() -> i64 =
+        unsafe { std::mem::transmute(module.get_finalized_function(func_id)) };
+
+    // Each redefinition frees the code of the previous one, so this shouldn't
+    // keep accumulating memory.
+    for i in 1..1000 {
+        module.prepare_for_function_redefine(func_id).unwrap();
+        define_const(&mut module, func_id, i);
+        module.finalize_definitions().unwrap();
+        unsafe { module.free_superseded_functions() };
+        assert_eq!(f(), i);
+    }
+}
+
+#[test]
+fn hotswap_redefine_undefined_function() {
+    let Some(mut module) = hotswap_module() else {
+        return;
+    };
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let func_id = module.declare_function("f", Linkage::Local, &sig).unwrap();
+    assert!(module.prepare_for_function_redefine(func_id).is_err());
+
+    let import = module
+        .declare_function("malloc", Linkage::Import, &sig)
+        .unwrap();
+    let err = module.prepare_for_function_redefine(import).unwrap_err();
+    assert!(
+        matches!(err, ModuleError::InvalidImportDefinition(_)),
+        "{err}"
+    );
+}
@@ -0,0 +1,253 @@
+//! Source-level debug information for the functions of a module.
+//!
+//! A [`DebugContext`] describes the source files, types and variables of a program so that
+//! backends supporting it, such as `cranelift-object`, can emit debug information for the
+//! compiled code.
+//!
+//! Instructions are mapped back to the source through the [`ir::SourceLoc`]s returned by
+//! [`DebugContext::add_location`], which are attached while building a function (for example
+//! with `FunctionBuilder::set_srcloc`). Variables are tracked through [`ir::ValueLabel`]s (for
+//! example with `FunctionBuilder::set_val_label`); this requires calling
+//! [`ir::Function::collect_debug_info`] before building the function.
+
+use crate::module::FuncId;
+use cranelift_codegen::entity::{PrimaryMap, SecondaryMap, entity_impl};
+use cranelift_codegen::ir;
+use std::string::String;
+use std::vec::Vec;
+
+/// The `DW_LANG_C99` language code, used by default for compilation units.
+const DW_LANG_C99: u16 = 0x000c;
+
+/// A source file registered with a [`DebugContext`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DebugFileId(u32);
+entity_impl!(DebugFileId, "file");
+
+/// A type registered with a [`DebugContext`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DebugTypeId(u32);
+entity_impl!(DebugTypeId, "type");
+
+/// A position in a source file.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SourcePosition {
+    /// The file containing this position.
+    pub file: DebugFileId,
+    /// The line number, starting at 1.
+    pub line: u32,
+    /// The column number, starting at 1, or 0 if unknown.
+    pub column: u32,
+}
+
+/// How the bits of a [`DebugType::Base`] are interpreted.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BaseTypeEncoding {
+    /// A two's complement signed integer.
+    Signed,
+    /// An unsigned integer.
+    Unsigned,
+    /// An IEEE 754 floating point number.
+    Float,
+    /// A boolean, where zero is false and any other value is true.
+    Boolean,
+    /// A machine address.
+    Address,
+}
+
+/// The source-level type of a variable.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DebugType {
+    /// A primitive type, such as an integer or a float.
+    Base {
+        /// The name of the type in the source language.
+        name: String,
+        /// The size of the type in bytes.
+        size: u8,
+        /// How values of this type are encoded.
+        encoding: BaseTypeEncoding,
+    },
+    /// A pointer to a value of another type, or an untyped pointer.
+    Pointer {
+        /// The type being pointed to.
+        pointee: Option<DebugTypeId>,
+    },
+}
+
+/// A source-level variable of a function.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DebugVariable {
+    /// The name of the variable in the source language.
+    pub name: String,
+    /// The type of the variable.
+    pub ty: DebugTypeId,
+    /// The label attached to the values holding this variable.
+    pub label: ir::ValueLabel,
+    /// Whether this variable is a parameter of the function.
+    pub parameter: bool,
+    /// Where the variable is declared.
+    pub location: Option<ir::SourceLoc>,
+}
+
+/// Source-level description of a function.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FunctionDebugInfo {
+    /// The name of the function in the source language. The linkage name of the function is
+    /// used if this is empty.
+    pub name: String,
+    /// Where the function is declared.
+    pub location: Option<ir::SourceLoc>,
+    /// The type returned by the function, if any.
+    pub return_type: Option<DebugTypeId>,
+    /// The variables of the function, including its parameters.
+    pub variables: Vec<DebugVariable>,
+}
+
+/// Debug information describing the source of a module.
+#[derive(Clone, Debug)]
+pub struct DebugContext {
+    name: String,
+    comp_dir: String,
+    producer: String,
+    language: u16,
+    files: PrimaryMap<DebugFileId, String>,
+    positions: Vec<SourcePosition>,
+    types: PrimaryMap<DebugTypeId, DebugType>,
+    functions: SecondaryMap<FuncId, Option<FunctionDebugInfo>>,
+}
+
+impl DebugContext {
+    /// Create a new `DebugContext` for a compilation unit whose main source file is `name`.
+    ///
+    /// Relative file paths are resolved against the compilation directory `comp_dir`. The main
+    /// source file is registered as the first file of the context.
+    pub fn new(name: impl Into<String>, comp_dir: impl Into<String>) -> Self {
+        let name = name.into();
+        let mut files = PrimaryMap::new();
+        files.push(name.clone());
+        Self {
+            name,
+            comp_dir: comp_dir.into(),
+            producer: String::from("cranelift"),
+            language: DW_LANG_C99,
+            files,
+            positions: Vec::new(),
+            types: PrimaryMap::new(),
+            functions: SecondaryMap::new(),
+        }
+    }
+
+    /// Set the name of the compiler producing this compilation unit.
+    pub fn set_producer(&mut self, producer: impl Into<String>) -> &mut Self {
+        self.producer = producer.into();
+        self
+    }
+
+    /// Set the source language of this compilation unit, as a `DW_LANG_*` constant.
+    ///
+    /// Defaults to `DW_LANG_C99`.
+    pub fn set_language(&mut self, language: u16) -> &mut Self {
+        self.language = language;
+        self
+    }
+
+    /// The name of the main source file of this compilation unit.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The compilation directory of this compilation unit.
+    pub fn comp_dir(&self) -> &str {
+        &self.comp_dir
+    }
+
+    /// The name of the compiler producing this compilation unit.
+    pub fn producer(&self) -> &str {
+        &self.producer
+    }
+
+    /// The source language of this compilation unit, as a `DW_LANG_*` constant.
+    pub fn language(&self) -> u16 {
+        self.language
+    }
+
+    /// The file registered by [`DebugContext::new`] for the main source file.
+    pub fn main_file(&self) -> DebugFileId {
+        DebugFileId::from_u32(0)
+    }
+
+    /// Register a source file, returning its identifier.
+    pub fn add_file(&mut self, path: impl Into<String>) -> DebugFileId {
+        self.files.push(path.into())
+    }
+
+    /// All registered source files.
+    pub fn files(&self) -> &PrimaryMap<DebugFileId, String> {
+        &self.files
+    }
+
+    /// Register a position in a source file, returning the [`ir::SourceLoc`] to attach to the
+    /// instructions generated for it.
+    pub fn add_location(&mut self, file: DebugFileId, line: u32, column: u32) -> ir::SourceLoc {
+        let index = u32::try_from(self.positions.len()).unwrap();
+        // `u32::MAX` is reserved for the default source location.
+        assert!(index < u32::MAX, "too many source locations");
+        self.positions.push(SourcePosition { file, line, column });
+        ir::SourceLoc::new(index)
+    }
+
+    /// Look up the source position of a location returned by [`DebugContext::add_location`].
+    pub fn position(&self, loc: ir::SourceLoc) -> Option<&SourcePosition> {
+        if loc.is_default() {
+            return None;
+        }
+        self.positions.get(loc.bits() as usize)
+    }
+
+    /// Register a type, returning its identifier.
+    pub fn add_type(&mut self, ty: DebugType) -> DebugTypeId {
+        self.types.push(ty)
+    }
+
+    /// All registered types.
+    pub fn types(&self) -> &PrimaryMap<DebugTypeId, DebugType> {
+        &self.types
+    }
+
+    /// Describe the source-level function `func`, replacing any previous description.
+    pub fn define_function(&mut self, func: FuncId, info: FunctionDebugInfo) {
+        self.functions[func] = Some(info);
+    }
+
+    /// The description of the function `func`, if one was given.
+    pub fn function(&self, func: FuncId) -> Option<&FunctionDebugInfo> {
+        self.functions.get(func).and_then(|info| info.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locations() {
+        let mut debug = DebugContext::new("main.src", "/tmp");
+        let other = debug.add_file("other.src");
+        assert_eq!(debug.files()[debug.main_file()], "main.src");
+        assert_eq!(debug.files()[other], "other.src");
+
+        let a = debug.add_location(debug.main_file(), 3, 1);
+        let b = debug.add_location(other, 7, 0);
+        assert_ne!(a, b);
+        assert_eq!(
+            debug.position(b),
+            Some(&SourcePosition {
+                file: other,
+                line: 7,
+                column: 0
+            })
+        );
+        assert_eq!(debug.position(ir::SourceLoc::default()), None);
+        assert_eq!(debug.position(ir::SourceLoc::new(2)), None);
+    }
+}
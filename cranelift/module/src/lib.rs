@@ -21,10 +21,15 @@ use std::string::String;
 use cranelift_codegen::ir;
 
 mod data_context;
+mod debug;
 mod module;
 mod traps;
 
 pub use crate::data_context::{DataDescription, Init};
+pub use crate::debug::{
+    BaseTypeEncoding, DebugContext, DebugFileId, DebugType, DebugTypeId, DebugVariable,
+    FunctionDebugInfo, SourcePosition,
+};
 pub use crate::module::{
     DataDeclaration, DataId, FuncId, FuncOrDataId, FunctionDeclaration, Linkage, Module,
     ModuleDeclarations, ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
@@ -20,6 +20,7 @@ use cranelift_control::ControlPlane;
 use std::borrow::{Cow, ToOwned};
 use std::boxed::Box;
 use std::string::String;
+use std::vec::Vec;
 
 /// A module relocation.
 #[derive(Clone)]
@@ -979,6 +980,90 @@ pub trait Module {
         relocs: &[ModuleReloc],
     ) -> ModuleResult<()>;
 
+    /// Define a function whose body has already been compiled in the given `Context`.
+    ///
+    /// The `Context` must have been compiled for this module's target ISA, for example with
+    /// [`Context::compile`], before calling this function.
+    ///
+    /// The default implementation forwards the compiled code to [`define_function_bytes`].
+    ///
+    /// [`define_function_bytes`]: Self::define_function_bytes
+    fn define_compiled_function(&mut self, func: FuncId, ctx: &Context) -> ModuleResult<()> {
+        let compiled_code = ctx
+            .compiled_code()
+            .expect("function must be compiled before it is defined");
+        let relocs = compiled_code
+            .buffer
+            .relocs()
+            .iter()
+            .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &ctx.func, func))
+            .collect::<Vec<_>>();
+        self.define_function_bytes(
+            func,
+            compiled_code.buffer.alignment as u64,
+            compiled_code.buffer.data(),
+            &relocs,
+        )
+    }
+
+    /// Define many functions at once, producing each function body from its `Context`.
+    ///
+    /// Functions are compiled independently of each other, in parallel when the
+    /// `parallel-compilation` feature of this crate is enabled. They are then defined in the
+    /// order in which they appear in `funcs`, so the result is deterministic regardless of how
+    /// many threads took part in compilation.
+    ///
+    /// If a function has a linkage which can't be defined, appears in `funcs` more than once or
+    /// fails to compile, an error is returned and none of the functions are defined. When several
+    /// functions fail to compile, the error of the first such function in `funcs` is returned.
+    ///
+    /// Errors which the module only detects while defining the compiled functions, for example
+    /// because a function was already defined by an earlier call, leave the functions before the
+    /// failing one in `funcs` defined, and the functions after it undefined.
+    ///
+    /// Note: After calling this function each `Context` will contain its compiled function.
+    fn define_functions(&mut self, funcs: &mut [(FuncId, Context)]) -> ModuleResult<()> {
+        let decls = self.declarations();
+        for (func, _) in funcs.iter() {
+            let decl = decls.get_function_decl(*func);
+            if !decl.linkage.is_definable() {
+                return Err(ModuleError::InvalidImportDefinition(
+                    decl.linkage_name(*func).into_owned(),
+                ));
+            }
+        }
+        let mut ids = funcs.iter().map(|(func, _)| *func).collect::<Vec<_>>();
+        ids.sort_unstable();
+        if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(ModuleError::DuplicateDefinition(
+                decls
+                    .get_function_decl(pair[0])
+                    .linkage_name(pair[0])
+                    .into_owned(),
+            ));
+        }
+
+        let isa = self.isa();
+        let compile = |(_, ctx): &mut (FuncId, Context)| -> ModuleResult<()> {
+            ctx.compile(isa, &mut ControlPlane::default())?;
+            Ok(())
+        };
+
+        #[cfg(feature = "parallel-compilation")]
+        let results = {
+            use rayon::prelude::*;
+            funcs.par_iter_mut().map(compile).collect::<Vec<_>>()
+        };
+        #[cfg(not(feature = "parallel-compilation"))]
+        let results = funcs.iter_mut().map(compile).collect::<Vec<_>>();
+        results.into_iter().collect::<ModuleResult<()>>()?;
+
+        for (func, ctx) in funcs.iter() {
+            self.define_compiled_function(*func, ctx)?;
+        }
+        Ok(())
+    }
+
     /// Define a data object, producing the data contents from the given `DataContext`.
     fn define_data(&mut self, data_id: DataId, data: &DataDescription) -> ModuleResult<()>;
 }
@@ -1082,6 +1167,14 @@ impl<M: Module + ?Sized> Module for &mut M {
         (**self).define_function_bytes(func_id, alignment, bytes, relocs)
     }
 
+    fn define_compiled_function(&mut self, func: FuncId, ctx: &Context) -> ModuleResult<()> {
+        (**self).define_compiled_function(func, ctx)
+    }
+
+    fn define_functions(&mut self, funcs: &mut [(FuncId, Context)]) -> ModuleResult<()> {
+        (**self).define_functions(funcs)
+    }
+
     fn define_data(&mut self, data_id: DataId, data: &DataDescription) -> ModuleResult<()> {
         (**self).define_data(data_id, data)
     }
@@ -1186,6 +1279,14 @@ impl<M: Module + ?Sized> Module for Box<M> {
         (**self).define_function_bytes(func_id, alignment, bytes, relocs)
     }
 
+    fn define_compiled_function(&mut self, func: FuncId, ctx: &Context) -> ModuleResult<()> {
+        (**self).define_compiled_function(func, ctx)
+    }
+
+    fn define_functions(&mut self, funcs: &mut [(FuncId, Context)]) -> ModuleResult<()> {
+        (**self).define_functions(funcs)
+    }
+
     fn define_data(&mut self, data_id: DataId, data: &DataDescription) -> ModuleResult<()> {
         (**self).define_data(data_id, data)
     }
@@ -152,6 +152,19 @@ pub fn infer_native_flags(isa_builder: &mut dyn Configurable) -> Result<(), &'st
         let _ = riscv::cpuinfo_detect(isa_builder);
     }
 
+    #[cfg(all(target_arch = "loongarch64", target_os = "linux"))]
+    {
+        let v = unsafe { libc::getauxval(libc::AT_HWCAP) };
+        const HWCAP_LOONGARCH_LSX: libc::c_ulong = 1 << 4;
+        const HWCAP_LOONGARCH_LASX: libc::c_ulong = 1 << 5;
+        if (v & HWCAP_LOONGARCH_LSX) != 0 {
+            isa_builder.enable("has_lsx").unwrap();
+        }
+        if (v & HWCAP_LOONGARCH_LASX) != 0 {
+            isa_builder.enable("has_lasx").unwrap();
+        }
+    }
+
     // On all other architectures (e.g. wasm32) we won't infer any native flags,
     // but still need to use the `isa_builder` to avoid compiler warnings.
     let _ = isa_builder;
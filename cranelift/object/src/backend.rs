@@ -1,5 +1,6 @@
 //! Defines `ObjectModule`.
 
+use crate::debug::{FunctionDebugRecord, emit_dwarf};
 use anyhow::anyhow;
 use cranelift_codegen::binemit::{Addend, CodeOffset, Reloc};
 use cranelift_codegen::entity::SecondaryMap;
@@ -7,8 +8,8 @@ use cranelift_codegen::ir;
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
 use cranelift_control::ControlPlane;
 use cranelift_module::{
-    DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
-    ModuleReloc, ModuleRelocTarget, ModuleResult,
+    DataDescription, DataId, DebugContext, FuncId, Init, Linkage, Module, ModuleDeclarations,
+    ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
 use log::info;
 use object::write::{
@@ -34,6 +35,7 @@ pub struct ObjectBuilder {
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     per_function_section: bool,
     per_data_object_section: bool,
+    debug_info: bool,
 }
 
 impl ObjectBuilder {
@@ -103,6 +105,22 @@ impl ObjectBuilder {
                 object::Architecture::Riscv64
             }
             target_lexicon::Architecture::S390x => object::Architecture::S390x,
+            target_lexicon::Architecture::LoongArch64 => {
+                if binary_format != object::BinaryFormat::Elf {
+                    return Err(ModuleError::Backend(anyhow!(
+                        "binary format {:?} is not supported for loongarch64",
+                        binary_format,
+                    )));
+                }
+
+                file_flags = object::FileFlags::Elf {
+                    os_abi: object::elf::ELFOSABI_NONE,
+                    abi_version: 0,
+                    e_flags: object::elf::EF_LARCH_ABI_DOUBLE_FLOAT
+                        | object::elf::EF_LARCH_OBJABI_V1,
+                };
+                object::Architecture::LoongArch64
+            }
             architecture => {
                 return Err(ModuleError::Backend(anyhow!(
                     "target architecture {:?} is unsupported",
@@ -124,6 +142,7 @@ impl ObjectBuilder {
             libcall_names,
             per_function_section: false,
             per_data_object_section: false,
+            debug_info: false,
         })
     }
 
@@ -138,6 +157,13 @@ impl ObjectBuilder {
         self.per_data_object_section = per_data_object_section;
         self
     }
+
+    /// Set if the information needed by [`ObjectModule::emit_debug_info`] should be recorded
+    /// for every defined function.
+    pub fn debug_info(&mut self, debug_info: bool) -> &mut Self {
+        self.debug_info = debug_info;
+        self
+    }
 }
 
 /// An `ObjectModule` implements `Module` and emits ".o" files using the `object` library.
@@ -156,6 +182,7 @@ pub struct ObjectModule {
     known_labels: HashMap<(FuncId, CodeOffset), SymbolId>,
     per_function_section: bool,
     per_data_object_section: bool,
+    debug_records: Option<Vec<FunctionDebugRecord>>,
 }
 
 impl ObjectModule {
@@ -178,6 +205,7 @@ impl ObjectModule {
             known_labels: HashMap::new(),
             per_function_section: builder.per_function_section,
             per_data_object_section: builder.per_data_object_section,
+            debug_records: builder.debug_info.then(Vec::new),
         }
     }
 }
@@ -342,18 +370,39 @@ impl Module for ObjectModule {
     ) -> ModuleResult<()> {
         info!("defining function {}: {}", func_id, ctx.func.display());
 
-        let res = ctx.compile(self.isa(), ctrl_plane)?;
-        let alignment = res.buffer.alignment as u64;
+        ctx.compile(self.isa(), ctrl_plane)?;
+        self.define_compiled_function(func_id, ctx)
+    }
 
-        let buffer = &ctx.compiled_code().unwrap().buffer;
-        let relocs = buffer
+    fn define_compiled_function(
+        &mut self,
+        func_id: FuncId,
+        ctx: &cranelift_codegen::Context,
+    ) -> ModuleResult<()> {
+        let compiled_code = ctx
+            .compiled_code()
+            .expect("function must be compiled before it is defined");
+        let relocs = compiled_code
+            .buffer
             .relocs()
             .iter()
             .map(|reloc| {
-                self.process_reloc(&ModuleReloc::from_mach_reloc(&reloc, &ctx.func, func_id))
+                self.process_reloc(&ModuleReloc::from_mach_reloc(reloc, &ctx.func, func_id))
             })
-            .collect::<Vec<_>>();
-        self.define_function_inner(func_id, alignment, buffer.data(), relocs)
+            .collect();
+        self.define_function_inner(
+            func_id,
+            compiled_code.buffer.alignment as u64,
+            compiled_code.buffer.data(),
+            relocs,
+        )?;
+
+        if self.debug_records.is_some() {
+            let (symbol, _) = self.functions[func_id].unwrap();
+            let record = FunctionDebugRecord::new(&*self.isa, func_id, symbol, compiled_code)?;
+            self.debug_records.as_mut().unwrap().push(record);
+        }
+        Ok(())
     }
 
     fn define_function_bytes(
@@ -518,6 +567,27 @@ impl ObjectModule {
         Ok(())
     }
 
+    /// Add DWARF sections describing the functions defined so far to the object.
+    ///
+    /// The source locations, variables and types of the functions are looked up in `debug`.
+    /// This requires [`ObjectBuilder::debug_info`] to be enabled and is only supported for ELF
+    /// objects. It should be called once, after all functions have been defined.
+    pub fn emit_debug_info(&mut self, debug: &DebugContext) -> ModuleResult<()> {
+        let Some(records) = &self.debug_records else {
+            return Err(ModuleError::Backend(anyhow!(
+                "debug information was not enabled in the `ObjectBuilder`"
+            )));
+        };
+        let declarations = &self.declarations;
+        emit_dwarf(&mut self.object, &*self.isa, debug, records, |func_id| {
+            let decl = declarations.get_function_decl(func_id);
+            (
+                decl.linkage_name(func_id).into_owned(),
+                decl.linkage != Linkage::Local,
+            )
+        })
+    }
+
     /// Finalize all relocations and output an object.
     pub fn finish(mut self) -> ObjectProduct {
         let symbol_relocs = mem::take(&mut self.relocs);
@@ -877,6 +947,36 @@ impl ObjectModule {
                     r_type: object::elf::R_RISCV_GOT_HI20,
                 }
             }
+            Reloc::LoongArchCall36 => {
+                assert_eq!(
+                    self.object.format(),
+                    object::BinaryFormat::Elf,
+                    "LoongArchCall36 is not supported for this file format"
+                );
+                RelocationFlags::Elf {
+                    r_type: object::elf::R_LARCH_CALL36,
+                }
+            }
+            Reloc::LoongArchGotPcHi20 => {
+                assert_eq!(
+                    self.object.format(),
+                    object::BinaryFormat::Elf,
+                    "LoongArchGotPcHi20 is not supported for this file format"
+                );
+                RelocationFlags::Elf {
+                    r_type: object::elf::R_LARCH_GOT_PC_HI20,
+                }
+            }
+            Reloc::LoongArchGotPcLo12 => {
+                assert_eq!(
+                    self.object.format(),
+                    object::BinaryFormat::Elf,
+                    "LoongArchGotPcLo12 is not supported for this file format"
+                );
+                RelocationFlags::Elf {
+                    r_type: object::elf::R_LARCH_GOT_PC_LO12,
+                }
+            }
             // FIXME
             reloc => unimplemented!("{:?}", reloc),
         };
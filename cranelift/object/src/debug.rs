@@ -0,0 +1,506 @@
+//! Emission of DWARF debug information for the functions of an `ObjectModule`.
+
+use anyhow::anyhow;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::isa::unwind::{CfaUnwindInfo, UnwindInfo};
+use cranelift_codegen::{CompiledCode, LabelValueLoc, ValueLabelsRanges, ir};
+use cranelift_module::{
+    BaseTypeEncoding, DebugContext, DebugFileId, DebugType, DebugTypeId, DebugVariable, FuncId,
+    ModuleError, ModuleResult, SourcePosition,
+};
+use gimli::write::{
+    Address, AttributeValue, DwarfUnit, EndianVec, Expression, FileId, FrameTable, LineProgram,
+    LineString, Location, LocationList, Range, RangeList, RelocateWriter, Relocation,
+    RelocationTarget, Sections, UnitEntryId,
+};
+use gimli::{Encoding, Format, LineEncoding, RunTimeEndian, constants};
+use object::write::{Object, SectionId, StandardSegment, SymbolId};
+use object::{RelocationEncoding, RelocationFlags, RelocationKind, SectionKind};
+use std::collections::HashMap;
+
+/// The information about a compiled function needed to describe it in the debug information.
+pub(crate) struct FunctionDebugRecord {
+    func_id: FuncId,
+    symbol: SymbolId,
+    size: u32,
+    srclocs: Vec<(u32, u32, ir::SourceLoc)>,
+    value_labels: ValueLabelsRanges,
+    unwind_info: Option<CfaUnwindInfo>,
+}
+
+impl FunctionDebugRecord {
+    pub(crate) fn new(
+        isa: &dyn TargetIsa,
+        func_id: FuncId,
+        symbol: SymbolId,
+        compiled_code: &CompiledCode,
+    ) -> ModuleResult<Self> {
+        let srclocs = compiled_code
+            .buffer
+            .get_srclocs_sorted()
+            .iter()
+            .map(|srcloc| (srcloc.start, srcloc.end, srcloc.loc))
+            .collect();
+        let unwind_info = match compiled_code.create_unwind_info(isa)? {
+            Some(UnwindInfo::SystemV(info)) => Some(info),
+            _ => None,
+        };
+        Ok(Self {
+            func_id,
+            symbol,
+            size: compiled_code.code_info().total_size,
+            srclocs,
+            value_labels: compiled_code.value_labels_ranges.clone(),
+            unwind_info,
+        })
+    }
+}
+
+/// A section writer recording the relocations gimli requests.
+#[derive(Clone)]
+struct DebugSectionWriter {
+    writer: EndianVec<RunTimeEndian>,
+    relocs: Vec<Relocation>,
+}
+
+impl RelocateWriter for DebugSectionWriter {
+    type Writer = EndianVec<RunTimeEndian>;
+
+    fn writer(&self) -> &Self::Writer {
+        &self.writer
+    }
+
+    fn writer_mut(&mut self) -> &mut Self::Writer {
+        &mut self.writer
+    }
+
+    fn relocate(&mut self, relocation: Relocation) {
+        self.relocs.push(relocation);
+    }
+}
+
+fn gimli_error(err: gimli::write::Error) -> ModuleError {
+    ModuleError::Backend(anyhow!("failed to write debug information: {err}"))
+}
+
+/// Builds the DWARF compilation unit describing the recorded functions.
+struct DwarfBuilder<'a> {
+    isa: &'a dyn TargetIsa,
+    debug: &'a DebugContext,
+    dwarf: DwarfUnit,
+    files: HashMap<DebugFileId, FileId>,
+    types: HashMap<DebugTypeId, UnitEntryId>,
+}
+
+impl<'a> DwarfBuilder<'a> {
+    fn new(isa: &'a dyn TargetIsa, debug: &'a DebugContext) -> Self {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: isa.pointer_bytes(),
+        };
+        let mut dwarf = DwarfUnit::new(encoding);
+
+        let comp_dir = LineString::String(debug.comp_dir().as_bytes().to_vec());
+        let comp_name = LineString::String(debug.name().as_bytes().to_vec());
+        let mut line_program =
+            LineProgram::new(encoding, LineEncoding::default(), comp_dir, comp_name, None);
+        let dir = line_program.default_directory();
+        let files = debug
+            .files()
+            .iter()
+            .map(|(id, path)| {
+                let name = LineString::String(path.as_bytes().to_vec());
+                (id, line_program.add_file(name, dir, None))
+            })
+            .collect();
+        dwarf.unit.line_program = line_program;
+
+        let root = dwarf.unit.root();
+        let producer = dwarf.strings.add(debug.producer());
+        let name = dwarf.strings.add(debug.name());
+        let comp_dir = dwarf.strings.add(debug.comp_dir());
+        let entry = dwarf.unit.get_mut(root);
+        entry.set(
+            constants::DW_AT_producer,
+            AttributeValue::StringRef(producer),
+        );
+        entry.set(
+            constants::DW_AT_language,
+            AttributeValue::Language(constants::DwLang(debug.language())),
+        );
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(
+            constants::DW_AT_comp_dir,
+            AttributeValue::StringRef(comp_dir),
+        );
+
+        Self {
+            isa,
+            debug,
+            dwarf,
+            files,
+            types: HashMap::new(),
+        }
+    }
+
+    fn add_types(&mut self) {
+        let root = self.dwarf.unit.root();
+        for (id, ty) in self.debug.types().iter() {
+            let entry_id = match ty {
+                DebugType::Base {
+                    name,
+                    size,
+                    encoding,
+                } => {
+                    let entry_id = self.dwarf.unit.add(root, constants::DW_TAG_base_type);
+                    let name = self.dwarf.strings.add(name.as_str());
+                    let encoding = match encoding {
+                        BaseTypeEncoding::Signed => constants::DW_ATE_signed,
+                        BaseTypeEncoding::Unsigned => constants::DW_ATE_unsigned,
+                        BaseTypeEncoding::Float => constants::DW_ATE_float,
+                        BaseTypeEncoding::Boolean => constants::DW_ATE_boolean,
+                        BaseTypeEncoding::Address => constants::DW_ATE_address,
+                    };
+                    let entry = self.dwarf.unit.get_mut(entry_id);
+                    entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+                    entry.set(constants::DW_AT_byte_size, AttributeValue::Data1(*size));
+                    entry.set(
+                        constants::DW_AT_encoding,
+                        AttributeValue::Encoding(encoding),
+                    );
+                    entry_id
+                }
+                DebugType::Pointer { .. } => {
+                    let entry_id = self.dwarf.unit.add(root, constants::DW_TAG_pointer_type);
+                    let entry = self.dwarf.unit.get_mut(entry_id);
+                    entry.set(
+                        constants::DW_AT_byte_size,
+                        AttributeValue::Data1(self.isa.pointer_bytes()),
+                    );
+                    entry_id
+                }
+            };
+            self.types.insert(id, entry_id);
+        }
+
+        // Pointers may refer to types registered after them, so they are resolved once every
+        // type has an entry.
+        for (id, ty) in self.debug.types().iter() {
+            if let DebugType::Pointer {
+                pointee: Some(pointee),
+            } = ty
+            {
+                let pointee = self.types[pointee];
+                self.dwarf
+                    .unit
+                    .get_mut(self.types[&id])
+                    .set(constants::DW_AT_type, AttributeValue::UnitRef(pointee));
+            }
+        }
+    }
+
+    fn position(&self, loc: Option<ir::SourceLoc>) -> Option<&'a SourcePosition> {
+        loc.and_then(|loc| self.debug.position(loc))
+    }
+
+    fn set_decl(&mut self, entry_id: UnitEntryId, position: Option<&SourcePosition>) {
+        if let Some(position) = position {
+            let file = self.files[&position.file];
+            let entry = self.dwarf.unit.get_mut(entry_id);
+            entry.set(
+                constants::DW_AT_decl_file,
+                AttributeValue::FileIndex(Some(file)),
+            );
+            entry.set(
+                constants::DW_AT_decl_line,
+                AttributeValue::Udata(position.line.into()),
+            );
+        }
+    }
+
+    fn add_line_sequence(&mut self, symbol: usize, record: &FunctionDebugRecord) {
+        let decl = self
+            .debug
+            .function(record.func_id)
+            .and_then(|info| self.position(info.location));
+        let line_program = &mut self.dwarf.unit.line_program;
+        line_program.begin_sequence(Some(Address::Symbol { symbol, addend: 0 }));
+
+        // The prologue is attributed to the declaration of the function so that debuggers place
+        // breakpoints on the function after it.
+        let mut current = None;
+        let mut next_offset = 0;
+        let rows = record
+            .srclocs
+            .iter()
+            .flat_map(|&(start, end, loc)| {
+                let gap = (start > next_offset).then_some((next_offset, None));
+                next_offset = end;
+                gap.into_iter()
+                    .chain(Some((start, self.debug.position(loc))))
+            })
+            .collect::<Vec<_>>();
+        for (offset, position) in rows {
+            let position = match position {
+                Some(position) => Some(position),
+                None if offset == 0 => decl,
+                None => None,
+            };
+            let key = position.map(|p| (p.file, p.line, p.column));
+            if current == Some(key) {
+                continue;
+            }
+            current = Some(key);
+
+            let row = line_program.row();
+            row.address_offset = offset.into();
+            match position {
+                Some(position) => {
+                    row.file = self.files[&position.file];
+                    row.line = position.line.into();
+                    row.column = position.column.into();
+                }
+                None => {
+                    row.line = 0;
+                    row.column = 0;
+                }
+            }
+            line_program.generate_row();
+        }
+        line_program.end_sequence(record.size.into());
+    }
+
+    fn variable_location(
+        &mut self,
+        symbol: usize,
+        record: &FunctionDebugRecord,
+        variable: &DebugVariable,
+    ) -> Option<AttributeValue> {
+        let ranges = record.value_labels.get(&variable.label)?;
+        let locations = ranges
+            .iter()
+            .filter_map(|range| {
+                let mut expr = Expression::new();
+                match range.loc {
+                    LabelValueLoc::Reg(reg) => {
+                        let reg = self.isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+                        expr.op_reg(gimli::Register(reg));
+                    }
+                    LabelValueLoc::CFAOffset(offset) => expr.op_fbreg(offset),
+                }
+                Some(Location::StartEnd {
+                    begin: Address::Symbol {
+                        symbol,
+                        addend: range.start.into(),
+                    },
+                    end: Address::Symbol {
+                        symbol,
+                        addend: range.end.into(),
+                    },
+                    data: expr,
+                })
+            })
+            .collect::<Vec<_>>();
+        if locations.is_empty() {
+            return None;
+        }
+        let list = self.dwarf.unit.locations.add(LocationList(locations));
+        Some(AttributeValue::LocationListRef(list))
+    }
+
+    fn add_subprogram(
+        &mut self,
+        symbol: usize,
+        linkage_name: &str,
+        external: bool,
+        record: &FunctionDebugRecord,
+    ) {
+        let root = self.dwarf.unit.root();
+        let entry_id = self.dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let info = self.debug.function(record.func_id);
+
+        let name = info
+            .map(|info| info.name.as_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(linkage_name);
+        let linkage = (name != linkage_name).then(|| self.dwarf.strings.add(linkage_name));
+        let name = self.dwarf.strings.add(name);
+        let mut frame_base = Expression::new();
+        frame_base.op(constants::DW_OP_call_frame_cfa);
+
+        let entry = self.dwarf.unit.get_mut(entry_id);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        if let Some(linkage) = linkage {
+            entry.set(
+                constants::DW_AT_linkage_name,
+                AttributeValue::StringRef(linkage),
+            );
+        }
+        entry.set(constants::DW_AT_external, AttributeValue::Flag(external));
+        entry.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Symbol { symbol, addend: 0 }),
+        );
+        entry.set(
+            constants::DW_AT_high_pc,
+            AttributeValue::Udata(record.size.into()),
+        );
+        entry.set(
+            constants::DW_AT_frame_base,
+            AttributeValue::Exprloc(frame_base),
+        );
+
+        let Some(info) = info else {
+            return;
+        };
+        if let Some(ty) = info.return_type {
+            let ty = self.types[&ty];
+            self.dwarf
+                .unit
+                .get_mut(entry_id)
+                .set(constants::DW_AT_type, AttributeValue::UnitRef(ty));
+        }
+        self.set_decl(entry_id, self.position(info.location));
+
+        for variable in &info.variables {
+            let tag = if variable.parameter {
+                constants::DW_TAG_formal_parameter
+            } else {
+                constants::DW_TAG_variable
+            };
+            let var_id = self.dwarf.unit.add(entry_id, tag);
+            let name = self.dwarf.strings.add(variable.name.as_str());
+            let ty = self.types[&variable.ty];
+            let location = self.variable_location(symbol, record, variable);
+            let entry = self.dwarf.unit.get_mut(var_id);
+            entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(ty));
+            if let Some(location) = location {
+                entry.set(constants::DW_AT_location, location);
+            }
+            self.set_decl(var_id, self.position(variable.location));
+        }
+    }
+}
+
+/// Describes `records` in DWARF sections added to `object`.
+///
+/// `names` provides the linkage name of each function, and whether it is visible outside of
+/// the object.
+pub(crate) fn emit_dwarf(
+    object: &mut Object<'static>,
+    isa: &dyn TargetIsa,
+    debug: &DebugContext,
+    records: &[FunctionDebugRecord],
+    names: impl Fn(FuncId) -> (String, bool),
+) -> ModuleResult<()> {
+    if object.format() != object::BinaryFormat::Elf {
+        return Err(ModuleError::Backend(anyhow!(
+            "debug information is only supported for ELF objects"
+        )));
+    }
+
+    let mut builder = DwarfBuilder::new(isa, debug);
+    builder.add_types();
+
+    // gimli refers to the functions through their index in `records`.
+    let mut ranges = Vec::new();
+    for (symbol, record) in records.iter().enumerate() {
+        let (linkage_name, external) = names(record.func_id);
+        builder.add_line_sequence(symbol, record);
+        builder.add_subprogram(symbol, &linkage_name, external, record);
+        ranges.push(Range::StartLength {
+            begin: Address::Symbol { symbol, addend: 0 },
+            length: record.size.into(),
+        });
+    }
+    let ranges = builder.dwarf.unit.ranges.add(RangeList(ranges));
+    let root = builder.dwarf.unit.root();
+    let entry = builder.dwarf.unit.get_mut(root);
+    entry.set(
+        constants::DW_AT_low_pc,
+        AttributeValue::Address(Address::Constant(0)),
+    );
+    entry.set(
+        constants::DW_AT_ranges,
+        AttributeValue::RangeListRef(ranges),
+    );
+
+    let endian = match isa.endianness() {
+        ir::Endianness::Little => RunTimeEndian::Little,
+        ir::Endianness::Big => RunTimeEndian::Big,
+    };
+    let mut sections = Sections::new(DebugSectionWriter {
+        writer: EndianVec::new(endian),
+        relocs: Vec::new(),
+    });
+    builder.dwarf.write(&mut sections).map_err(gimli_error)?;
+
+    if let Some(cie) = isa.create_systemv_cie() {
+        let mut frames = FrameTable::default();
+        let cie = frames.add_cie(cie);
+        for (symbol, record) in records.iter().enumerate() {
+            if let Some(info) = &record.unwind_info {
+                frames.add_fde(cie, info.to_fde(Address::Symbol { symbol, addend: 0 }));
+            }
+        }
+        frames
+            .write_debug_frame(&mut sections.debug_frame)
+            .map_err(gimli_error)?;
+    }
+
+    // Add all sections first so that relocations between them can be resolved.
+    let mut section_ids = HashMap::new();
+    let mut section_relocs = Vec::new();
+    sections.for_each_mut(|id, section| -> ModuleResult<()> {
+        let data = section.writer.take();
+        if data.is_empty() {
+            return Ok(());
+        }
+        let section_id = object.add_section(
+            object.segment_name(StandardSegment::Debug).to_vec(),
+            id.name().as_bytes().to_vec(),
+            SectionKind::Debug,
+        );
+        object.set_section_data(section_id, data, 1);
+        section_ids.insert(id, section_id);
+        section_relocs.push((section_id, std::mem::take(&mut section.relocs)));
+        Ok(())
+    })?;
+
+    for (section_id, relocs) in section_relocs {
+        for reloc in relocs {
+            add_relocation(object, &section_ids, records, section_id, reloc)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_relocation(
+    object: &mut Object<'static>,
+    section_ids: &HashMap<gimli::SectionId, SectionId>,
+    records: &[FunctionDebugRecord],
+    section_id: SectionId,
+    reloc: Relocation,
+) -> ModuleResult<()> {
+    let symbol = match reloc.target {
+        RelocationTarget::Symbol(index) => records[index].symbol,
+        RelocationTarget::Section(id) => object.section_symbol(section_ids[&id]),
+    };
+    object
+        .add_relocation(
+            section_id,
+            object::write::Relocation {
+                offset: reloc.offset as u64,
+                symbol,
+                addend: reloc.addend,
+                flags: RelocationFlags::Generic {
+                    kind: RelocationKind::Absolute,
+                    encoding: RelocationEncoding::Generic,
+                    size: reloc.size * 8,
+                },
+            },
+        )
+        .map_err(|err| ModuleError::Backend(anyhow!("{err}")))
+}
@@ -239,3 +239,144 @@ fn reject_nul_byte_symbol_for_data() {
         )
         .unwrap();
 }
+
+fn object_module() -> ObjectModule {
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    ObjectModule::new(ObjectBuilder::new(isa, "foo", default_libcall_names()).unwrap())
+}
+
+/// Declares a function `name` multiplying its argument by `factor`, returning
+/// the context with its body. If `valid` is false the body fails to verify.
+fn declare_mul_function(
+    module: &mut ObjectModule,
+    name: &str,
+    linkage: Linkage,
+    factor: i64,
+    valid: bool,
+) -> (FuncId, Context) {
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: CallConv::SystemV,
+    };
+    let func_id = module.declare_function(name, linkage, &sig).unwrap();
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let res = bcx.ins().imul_imm(x, factor);
+        if valid {
+            bcx.ins().return_(&[res]);
+        } else {
+            bcx.ins().return_(&[]);
+        }
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    (func_id, ctx)
+}
+
+// This is compiled with the `parallel-compilation` feature of
+// `cranelift-module`, so functions are compiled on multiple threads here.
+#[test]
+fn define_functions_matches_define_function() {
+    fn build(define_all: bool) -> Vec<u8> {
+        let mut module = object_module();
+        let mut funcs = (0..64)
+            .map(|i| declare_mul_function(&mut module, &format!("f{i}"), Linkage::Export, i, true))
+            .collect::<Vec<_>>();
+
+        if define_all {
+            module.define_functions(&mut funcs).unwrap();
+        } else {
+            for (func_id, ctx) in funcs.iter_mut() {
+                module.define_function(*func_id, ctx).unwrap();
+            }
+        }
+        module.finish().emit().unwrap()
+    }
+
+    assert_eq!(build(true), build(false));
+}
+
+#[test]
+fn define_functions_compile_error_defines_nothing() {
+    let mut module = object_module();
+    let mut funcs = (0..16)
+        .map(|i| {
+            let valid = i != 5 && i != 11;
+            declare_mul_function(&mut module, &format!("f{i}"), Linkage::Export, i, valid)
+        })
+        .collect::<Vec<_>>();
+
+    let err = module.define_functions(&mut funcs).unwrap_err();
+    assert!(matches!(err, ModuleError::Compilation(_)), "{err:?}");
+
+    // None of the functions were defined, so the valid ones can still be.
+    for (i, (func_id, ctx)) in funcs.iter_mut().enumerate() {
+        if i != 5 && i != 11 {
+            module.define_function(*func_id, ctx).unwrap();
+        }
+    }
+}
+
+#[test]
+fn define_functions_invalid_batch_defines_nothing() {
+    let mut module = object_module();
+    let mut funcs = vec![
+        declare_mul_function(&mut module, "f0", Linkage::Export, 0, true),
+        declare_mul_function(&mut module, "import", Linkage::Import, 1, true),
+    ];
+    let err = module.define_functions(&mut funcs).unwrap_err();
+    assert!(
+        matches!(err, ModuleError::InvalidImportDefinition(ref name) if name == "import"),
+        "{err:?}"
+    );
+
+    let mut funcs = vec![
+        declare_mul_function(&mut module, "f0", Linkage::Export, 0, true),
+        declare_mul_function(&mut module, "f0", Linkage::Export, 0, true),
+    ];
+    let err = module.define_functions(&mut funcs).unwrap_err();
+    assert!(
+        matches!(err, ModuleError::DuplicateDefinition(ref name) if name == "f0"),
+        "{err:?}"
+    );
+
+    let (func_id, mut ctx) = declare_mul_function(&mut module, "f0", Linkage::Export, 0, true);
+    module.define_function(func_id, &mut ctx).unwrap();
+}
+
+#[test]
+fn define_functions_already_defined_leaves_earlier_functions_defined() {
+    let mut module = object_module();
+    let mut funcs = (0..3)
+        .map(|i| declare_mul_function(&mut module, &format!("f{i}"), Linkage::Export, i, true))
+        .collect::<Vec<_>>();
+    let (f1, mut f1_ctx) = declare_mul_function(&mut module, "f1", Linkage::Export, 1, true);
+    module.define_function(f1, &mut f1_ctx).unwrap();
+
+    let err = module.define_functions(&mut funcs).unwrap_err();
+    assert!(
+        matches!(err, ModuleError::DuplicateDefinition(ref name) if name == "f1"),
+        "{err:?}"
+    );
+
+    // `f0` comes before `f1` so it was defined, while `f2` wasn't.
+    let (f0, f0_ctx) = &mut funcs[0];
+    assert!(matches!(
+        module.define_function(*f0, f0_ctx),
+        Err(ModuleError::DuplicateDefinition(_))
+    ));
+    let (f2, f2_ctx) = &mut funcs[2];
+    module.define_function(*f2, f2_ctx).unwrap();
+}
@@ -0,0 +1,135 @@
+use cranelift_codegen::Context;
+use cranelift_codegen::ir::*;
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::settings;
+use cranelift_entity::EntityRef;
+use cranelift_frontend::*;
+use cranelift_module::*;
+use cranelift_object::object::{Object, ObjectSection, ObjectSymbol, RelocationTarget};
+use cranelift_object::*;
+
+fn isa() -> cranelift_codegen::isa::OwnedTargetIsa {
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap()
+}
+
+fn define_add(module: &mut ObjectModule, debug: &mut DebugContext) -> FuncId {
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I32), AbiParam::new(types::I32)],
+        returns: vec![AbiParam::new(types::I32)],
+        call_conv: CallConv::SystemV,
+    };
+    let func_id = module
+        .declare_function("add_numbers", Linkage::Export, &sig)
+        .unwrap();
+
+    let int = debug.add_type(DebugType::Base {
+        name: "int".into(),
+        size: 4,
+        encoding: BaseTypeEncoding::Signed,
+    });
+    let file = debug.main_file();
+    let decl = debug.add_location(file, 1, 1);
+    let body = debug.add_location(file, 2, 5);
+    let ret = debug.add_location(file, 3, 5);
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        bcx.seal_block(block);
+        let a = bcx.block_params(block)[0];
+        let b = bcx.block_params(block)[1];
+        bcx.set_val_label(a, ValueLabel::new(0));
+        bcx.set_val_label(b, ValueLabel::new(1));
+        bcx.set_srcloc(body);
+        let sum = bcx.ins().iadd(a, b);
+        bcx.set_srcloc(ret);
+        bcx.ins().return_(&[sum]);
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+
+    let param = |name: &str, label| DebugVariable {
+        name: name.into(),
+        ty: int,
+        label: ValueLabel::new(label),
+        parameter: true,
+        location: Some(decl),
+    };
+    debug.define_function(
+        func_id,
+        FunctionDebugInfo {
+            name: "add".into(),
+            location: Some(decl),
+            return_type: Some(int),
+            variables: vec![param("a", 0), param("b", 1)],
+        },
+    );
+    func_id
+}
+
+#[test]
+fn emit_debug_info() {
+    let mut builder = ObjectBuilder::new(isa(), "add", default_libcall_names()).unwrap();
+    builder.debug_info(true);
+    let mut module = ObjectModule::new(builder);
+    let mut debug = DebugContext::new("add.src", "/src");
+    define_add(&mut module, &mut debug);
+    module.emit_debug_info(&debug).unwrap();
+    let bytes = module.finish().emit().unwrap();
+
+    let file = object::File::parse(&*bytes).unwrap();
+    for name in [
+        ".debug_info",
+        ".debug_abbrev",
+        ".debug_line",
+        ".debug_loc",
+        ".debug_frame",
+    ] {
+        assert!(file.section_by_name(name).is_some(), "missing {name}");
+    }
+
+    let strings = file.section_by_name(".debug_str").unwrap();
+    let strings = strings.data().unwrap();
+    for name in [
+        "add.src",
+        "/src",
+        "cranelift",
+        "add",
+        "add_numbers",
+        "int",
+        "a",
+        "b",
+    ] {
+        assert!(
+            strings.split(|&b| b == 0).any(|s| s == name.as_bytes()),
+            "missing string {name:?}"
+        );
+    }
+
+    // Code addresses in the debug information are relocated against the function symbol.
+    let symbol = file.symbol_by_name("add_numbers").unwrap().index();
+    let info = file.section_by_name(".debug_info").unwrap();
+    assert!(
+        info.relocations()
+            .any(|(_, reloc)| reloc.target() == RelocationTarget::Symbol(symbol))
+    );
+}
+
+#[test]
+fn debug_info_must_be_enabled() {
+    let builder = ObjectBuilder::new(isa(), "add", default_libcall_names()).unwrap();
+    let mut module = ObjectModule::new(builder);
+    let mut debug = DebugContext::new("add.src", "/src");
+    define_add(&mut module, &mut debug);
+    assert!(module.emit_debug_info(&debug).is_err());
+}
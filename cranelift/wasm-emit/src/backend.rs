@@ -0,0 +1,636 @@
+//! Defines `WasmModule`.
+
+use crate::translate::{
+    Callee, FuncType, Inst, STACK_POINTER_GLOBAL, TranslatedFunction, translate_function,
+};
+use anyhow::anyhow;
+use cranelift_codegen::binemit::Reloc;
+use cranelift_codegen::entity::SecondaryMap;
+use cranelift_codegen::ir::{self, types};
+use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
+use cranelift_control::ControlPlane;
+use cranelift_module::{
+    DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
+    ModuleReloc, ModuleRelocTarget, ModuleResult,
+};
+use log::info;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use target_lexicon::PointerWidth;
+use wasm_encoder::{
+    CodeSection, ConstExpr, DataSection, ElementSection, Elements, EntityType, ExportKind,
+    ExportSection, Function, FunctionSection, GlobalSection, GlobalType, ImportSection,
+    Instruction, MemorySection, MemoryType, RefType, TableSection, TableType, TypeSection, ValType,
+};
+
+/// The address of the first data object in linear memory.
+///
+/// Nothing is placed below it, so that null pointers never point to valid data.
+const DATA_START: u64 = 16;
+
+/// The default size of the shadow stack holding stack slots.
+const DEFAULT_STACK_SIZE: u64 = 1 << 20;
+
+/// A builder for `WasmModule`.
+pub struct WasmBuilder {
+    isa: OwnedTargetIsa,
+    libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    import_module: String,
+    stack_size: u64,
+}
+
+impl WasmBuilder {
+    /// Create a new `WasmBuilder` using the given Cranelift target, that can be passed to
+    /// [`WasmModule::new`].
+    ///
+    /// The target only determines the pointer width of the generated module: targets with 32-bit
+    /// pointers produce modules using a 32-bit linear memory and targets with 64-bit pointers
+    /// produce modules using a 64-bit linear memory. The target's settings, like the optimization
+    /// level, are used when optimizing functions before translating them to Wasm.
+    ///
+    /// The `libcall_names` function provides a way to translate `cranelift_codegen`'s
+    /// [`ir::LibCall`] enum to the names of imported functions. If you don't know what to use for
+    /// this argument, use [`cranelift_module::default_libcall_names`].
+    pub fn new(
+        isa: OwnedTargetIsa,
+        libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    ) -> ModuleResult<Self> {
+        match isa.triple().pointer_width() {
+            Ok(PointerWidth::U32 | PointerWidth::U64) => {}
+            Ok(PointerWidth::U16) | Err(()) => {
+                return Err(ModuleError::Backend(anyhow!(
+                    "the Wasm backend requires a target with 32-bit or 64-bit pointers",
+                )));
+            }
+        }
+        Ok(Self {
+            isa,
+            libcall_names,
+            import_module: "env".to_owned(),
+            stack_size: DEFAULT_STACK_SIZE,
+        })
+    }
+
+    /// Set the name of the module from which undefined functions and libcalls are imported.
+    ///
+    /// Defaults to `env`.
+    pub fn import_module(&mut self, name: impl Into<String>) -> &mut Self {
+        self.import_module = name.into();
+        self
+    }
+
+    /// Set the size in bytes of the shadow stack in linear memory holding stack slots.
+    ///
+    /// Defaults to 1 MiB.
+    pub fn stack_size(&mut self, bytes: u64) -> &mut Self {
+        self.stack_size = bytes;
+        self
+    }
+}
+
+/// A function defined in a `WasmModule`.
+enum Definition {
+    Translated(TranslatedFunction),
+    /// A raw function body, as passed to `define_function_bytes`.
+    Bytes(Vec<u8>),
+}
+
+/// A data object defined in a `WasmModule`.
+struct DataObject {
+    init: Init,
+    align: u64,
+    relocs: Vec<ModuleReloc>,
+}
+
+/// A `WasmModule` implements `Module` and emits WebAssembly modules using the `wasm-encoder`
+/// library.
+///
+/// Functions are optimized as usual and then translated to Wasm instead of being lowered to
+/// machine code. Data objects are placed in the module's linear memory, which is exported as
+/// `memory`, followed by a shadow stack holding stack slots. Function pointers are indices into
+/// the function table, which is exported as `__indirect_function_table`.
+///
+/// Functions which are not defined when the module is finished, as well as libcalls, are imported
+/// from the module configured with [`WasmBuilder::import_module`]. Exported functions are
+/// exported under their name, and exported data objects as immutable globals holding their
+/// address.
+///
+/// See the `WasmBuilder` for a convenient way to construct `WasmModule` instances.
+pub struct WasmModule {
+    isa: OwnedTargetIsa,
+    declarations: ModuleDeclarations,
+    functions: BTreeMap<FuncId, Definition>,
+    data_objects: BTreeMap<DataId, DataObject>,
+    libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    import_module: String,
+    stack_size: u64,
+}
+
+impl WasmModule {
+    /// Create a new `WasmModule` using the given Cranelift target.
+    pub fn new(builder: WasmBuilder) -> Self {
+        Self {
+            isa: builder.isa,
+            declarations: ModuleDeclarations::default(),
+            functions: BTreeMap::new(),
+            data_objects: BTreeMap::new(),
+            libcall_names: builder.libcall_names,
+            import_module: builder.import_module,
+            stack_size: builder.stack_size,
+        }
+    }
+
+    fn check_function_definable(&self, func_id: FuncId) -> ModuleResult<()> {
+        let decl = self.declarations.get_function_decl(func_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+        if self.functions.contains_key(&func_id) {
+            return Err(ModuleError::DuplicateDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Module for WasmModule {
+    fn isa(&self) -> &dyn TargetIsa {
+        &*self.isa
+    }
+
+    fn declarations(&self) -> &ModuleDeclarations {
+        &self.declarations
+    }
+
+    fn declare_function(
+        &mut self,
+        name: &str,
+        linkage: Linkage,
+        signature: &ir::Signature,
+    ) -> ModuleResult<FuncId> {
+        let (id, _linkage) = self
+            .declarations
+            .declare_function(name, linkage, signature)?;
+        Ok(id)
+    }
+
+    fn declare_anonymous_function(&mut self, signature: &ir::Signature) -> ModuleResult<FuncId> {
+        self.declarations.declare_anonymous_function(signature)
+    }
+
+    fn declare_data(
+        &mut self,
+        name: &str,
+        linkage: Linkage,
+        writable: bool,
+        tls: bool,
+    ) -> ModuleResult<DataId> {
+        let (id, _linkage) = self
+            .declarations
+            .declare_data(name, linkage, writable, tls)?;
+        Ok(id)
+    }
+
+    fn declare_anonymous_data(&mut self, writable: bool, tls: bool) -> ModuleResult<DataId> {
+        self.declarations.declare_anonymous_data(writable, tls)
+    }
+
+    fn define_function_with_control_plane(
+        &mut self,
+        func_id: FuncId,
+        ctx: &mut cranelift_codegen::Context,
+        ctrl_plane: &mut ControlPlane,
+    ) -> ModuleResult<()> {
+        info!("defining function {}: {}", func_id, ctx.func.display());
+
+        self.check_function_definable(func_id)?;
+
+        ctx.optimize(self.isa(), ctrl_plane)?;
+        ctx.compute_cfg();
+        ctx.compute_domtree();
+        let translated =
+            translate_function(&ctx.func, &ctx.cfg, &ctx.domtree, self.isa.pointer_type())?;
+        self.functions
+            .insert(func_id, Definition::Translated(translated));
+        Ok(())
+    }
+
+    /// Define a function from the encoding of a Wasm function body, including its locals.
+    ///
+    /// The body can't refer to other functions or data objects, as Wasm function bodies have no
+    /// relocations, so `relocs` must be empty.
+    fn define_function_bytes(
+        &mut self,
+        func_id: FuncId,
+        _alignment: u64,
+        bytes: &[u8],
+        relocs: &[ModuleReloc],
+    ) -> ModuleResult<()> {
+        self.check_function_definable(func_id)?;
+        if !relocs.is_empty() {
+            return Err(ModuleError::Backend(anyhow!(
+                "relocations are not supported by the Wasm backend",
+            )));
+        }
+        self.functions
+            .insert(func_id, Definition::Bytes(bytes.to_vec()));
+        Ok(())
+    }
+
+    /// Functions compiled to machine code can't be part of a Wasm module, so this always returns
+    /// an error. Use [`Module::define_function`] instead.
+    fn define_compiled_function(
+        &mut self,
+        func_id: FuncId,
+        _ctx: &cranelift_codegen::Context,
+    ) -> ModuleResult<()> {
+        let decl = self.declarations.get_function_decl(func_id);
+        Err(ModuleError::Backend(anyhow!(
+            "can't define {} from machine code in a Wasm module",
+            decl.linkage_name(func_id),
+        )))
+    }
+
+    /// Translation to Wasm doesn't go through [`cranelift_codegen::Context::compile`], so this
+    /// defines the functions one by one in order.
+    fn define_functions(
+        &mut self,
+        funcs: &mut [(FuncId, cranelift_codegen::Context)],
+    ) -> ModuleResult<()> {
+        for (func, ctx) in funcs.iter_mut() {
+            self.define_function(*func, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn define_data(&mut self, data_id: DataId, data: &DataDescription) -> ModuleResult<()> {
+        let decl = self.declarations.get_data_decl(data_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(data_id).into_owned(),
+            ));
+        }
+        if self.data_objects.contains_key(&data_id) {
+            return Err(ModuleError::DuplicateDefinition(
+                decl.linkage_name(data_id).into_owned(),
+            ));
+        }
+        if decl.tls {
+            return Err(ModuleError::Backend(anyhow!(
+                "TLS data objects are not supported by the Wasm backend",
+            )));
+        }
+        if data.custom_segment_section.is_some() {
+            return Err(ModuleError::Backend(anyhow!(
+                "custom sections are not supported by the Wasm backend",
+            )));
+        }
+        if let Init::Uninitialized = data.init {
+            panic!("data is not initialized yet");
+        }
+
+        let pointer_reloc = match self.isa.pointer_type() {
+            types::I32 => Reloc::Abs4,
+            _ => Reloc::Abs8,
+        };
+        self.data_objects.insert(
+            data_id,
+            DataObject {
+                init: data.init.clone(),
+                align: data.align.unwrap_or(1),
+                relocs: data.all_relocs(pointer_reloc).collect(),
+            },
+        );
+        Ok(())
+    }
+}
+
+/// The function and type index spaces of the module being emitted.
+struct Indices {
+    functions: SecondaryMap<FuncId, u32>,
+    libcalls: HashMap<ir::LibCall, u32>,
+    types: Vec<FuncType>,
+    type_indices: HashMap<FuncType, u32>,
+}
+
+impl Indices {
+    fn ty(&mut self, ty: &FuncType) -> u32 {
+        if let Some(&index) = self.type_indices.get(ty) {
+            return index;
+        }
+        let index = u32::try_from(self.types.len()).unwrap();
+        self.types.push(ty.clone());
+        self.type_indices.insert(ty.clone(), index);
+        index
+    }
+
+    fn callee(&self, callee: &Callee) -> u32 {
+        match callee {
+            Callee::Func(id) => self.functions[*id],
+            Callee::LibCall(libcall, _) => self.libcalls[libcall],
+        }
+    }
+}
+
+impl WasmModule {
+    /// Finalize all relocations and output a Wasm module.
+    pub fn finish(self) -> ModuleResult<WasmProduct> {
+        let pointer_type = self.isa.pointer_type();
+        let memory64 = pointer_type == types::I64;
+        let pointer_val_type = if memory64 { ValType::I64 } else { ValType::I32 };
+        let pointer_const = |value: u64| {
+            if memory64 {
+                ConstExpr::i64_const(value as i64)
+            } else {
+                ConstExpr::i32_const(value as i32)
+            }
+        };
+
+        let mut indices = Indices {
+            functions: SecondaryMap::new(),
+            libcalls: HashMap::new(),
+            types: Vec::new(),
+            type_indices: HashMap::new(),
+        };
+
+        // Imports come first in the function index space, followed by the defined functions.
+        let mut imports = ImportSection::new();
+        let mut num_functions = 0;
+        for (id, decl) in self.declarations.get_functions() {
+            if !self.functions.contains_key(&id) {
+                let ty = FuncType::from_signature(&decl.signature)?;
+                imports.import(
+                    &self.import_module,
+                    &decl.linkage_name(id),
+                    EntityType::Function(indices.ty(&ty)),
+                );
+                indices.functions[id] = num_functions;
+                num_functions += 1;
+            }
+        }
+        for definition in self.functions.values() {
+            let Definition::Translated(translated) = definition else {
+                continue;
+            };
+            for inst in &translated.body {
+                let (Inst::Call(Callee::LibCall(libcall, ty))
+                | Inst::ReturnCall(Callee::LibCall(libcall, ty))) = inst
+                else {
+                    continue;
+                };
+                if indices.libcalls.contains_key(libcall) {
+                    continue;
+                }
+                let type_index = indices.ty(ty);
+                imports.import(
+                    &self.import_module,
+                    &(self.libcall_names)(*libcall),
+                    EntityType::Function(type_index),
+                );
+                indices.libcalls.insert(*libcall, num_functions);
+                num_functions += 1;
+            }
+        }
+        let mut function_section = FunctionSection::new();
+        for (id, decl) in self.declarations.get_functions() {
+            if self.functions.contains_key(&id) {
+                let ty = FuncType::from_signature(&decl.signature)?;
+                function_section.function(indices.ty(&ty));
+                indices.functions[id] = num_functions;
+                num_functions += 1;
+            }
+        }
+
+        // Every function is in the function table, at its function index plus one so that null
+        // function pointers are never valid.
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: RefType::FUNCREF,
+            table64: false,
+            minimum: u64::from(num_functions) + 1,
+            maximum: Some(u64::from(num_functions) + 1),
+            shared: false,
+        });
+        let mut elements = ElementSection::new();
+        let all_functions = (0..num_functions).collect::<Vec<_>>();
+        elements.active(
+            None,
+            &ConstExpr::i32_const(1),
+            Elements::Functions(Cow::Owned(all_functions)),
+        );
+
+        // Lay out the data objects in linear memory, followed by the shadow stack.
+        let mut data_addresses = SecondaryMap::<DataId, Option<u64>>::new();
+        let mut end = DATA_START;
+        for (id, decl) in self.declarations.get_data_objects() {
+            match self.data_objects.get(&id) {
+                Some(data) => {
+                    let address = end.next_multiple_of(data.align);
+                    data_addresses[id] = Some(address);
+                    end = address + data.init.size() as u64;
+                }
+                None => {
+                    return Err(ModuleError::Backend(anyhow!(
+                        "{} is not defined, but importing data objects is not supported by the \
+                         Wasm backend",
+                        decl.linkage_name(id),
+                    )));
+                }
+            }
+        }
+        let stack_top = end.next_multiple_of(16) + self.stack_size.next_multiple_of(16);
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: stack_top.div_ceil(1 << 16),
+            maximum: None,
+            memory64,
+            shared: false,
+            page_size_log2: None,
+        });
+
+        let mut globals = GlobalSection::new();
+        globals.global(
+            GlobalType {
+                val_type: pointer_val_type,
+                mutable: true,
+                shared: false,
+            },
+            &pointer_const(stack_top),
+        );
+        let mut num_globals = STACK_POINTER_GLOBAL + 1;
+
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("__indirect_function_table", ExportKind::Table, 0);
+        for (id, decl) in self.declarations.get_functions() {
+            if decl.linkage == Linkage::Export && self.functions.contains_key(&id) {
+                exports.export(
+                    &decl.linkage_name(id),
+                    ExportKind::Func,
+                    indices.functions[id],
+                );
+            }
+        }
+        for (id, decl) in self.declarations.get_data_objects() {
+            if decl.linkage == Linkage::Export {
+                globals.global(
+                    GlobalType {
+                        val_type: pointer_val_type,
+                        mutable: false,
+                        shared: false,
+                    },
+                    &pointer_const(data_addresses[id].unwrap()),
+                );
+                exports.export(&decl.linkage_name(id), ExportKind::Global, num_globals);
+                num_globals += 1;
+            }
+        }
+
+        let mut code = CodeSection::new();
+        for definition in self.functions.values() {
+            let translated = match definition {
+                Definition::Translated(translated) => translated,
+                Definition::Bytes(bytes) => {
+                    code.raw(bytes);
+                    continue;
+                }
+            };
+            let mut function = Function::new_with_locals_types(translated.locals.iter().copied());
+            for inst in &translated.body {
+                let pointer = |value: u64| {
+                    if memory64 {
+                        Instruction::I64Const(value as i64)
+                    } else {
+                        Instruction::I32Const(value as i32)
+                    }
+                };
+                match inst {
+                    Inst::Wasm(inst) => function.instruction(inst),
+                    Inst::Call(callee) => {
+                        function.instruction(&Instruction::Call(indices.callee(callee)))
+                    }
+                    Inst::ReturnCall(callee) => {
+                        function.instruction(&Instruction::ReturnCall(indices.callee(callee)))
+                    }
+                    Inst::CallIndirect(ty) => function.instruction(&Instruction::CallIndirect {
+                        type_index: indices.ty(ty),
+                        table_index: 0,
+                    }),
+                    Inst::ReturnCallIndirect(ty) => {
+                        function.instruction(&Instruction::ReturnCallIndirect {
+                            type_index: indices.ty(ty),
+                            table_index: 0,
+                        })
+                    }
+                    Inst::FuncAddr(id) => {
+                        function.instruction(&pointer(u64::from(indices.functions[*id]) + 1))
+                    }
+                    Inst::DataAddr(id) => {
+                        function.instruction(&pointer(data_addresses[*id].unwrap()))
+                    }
+                };
+            }
+            code.function(&function);
+        }
+
+        let mut data_section = DataSection::new();
+        for (&id, data) in &self.data_objects {
+            let Init::Bytes { contents } = &data.init else {
+                // Linear memory is zero-initialized.
+                continue;
+            };
+            let mut contents = contents.to_vec();
+            for reloc in &data.relocs {
+                let value = match reloc.name {
+                    ModuleRelocTarget::User { namespace: 0, .. } => {
+                        let func = FuncId::from_name(&reloc.name);
+                        u64::from(indices.functions[func]) + 1
+                    }
+                    ModuleRelocTarget::User { namespace: 1, .. } => {
+                        let data = DataId::from_name(&reloc.name);
+                        data_addresses[data]
+                            .unwrap()
+                            .wrapping_add(reloc.addend as u64)
+                    }
+                    ref name => {
+                        return Err(ModuleError::Backend(anyhow!(
+                            "relocation to {name} is not supported by the Wasm backend",
+                        )));
+                    }
+                };
+                let offset = reloc.offset as usize;
+                match reloc.kind {
+                    Reloc::Abs4 => {
+                        contents[offset..offset + 4].copy_from_slice(&(value as u32).to_le_bytes())
+                    }
+                    Reloc::Abs8 => {
+                        contents[offset..offset + 8].copy_from_slice(&value.to_le_bytes())
+                    }
+                    kind => unreachable!("unexpected data relocation {kind}"),
+                }
+            }
+            data_section.active(0, &pointer_const(data_addresses[id].unwrap()), contents);
+        }
+
+        let mut types = TypeSection::new();
+        for ty in &indices.types {
+            types
+                .ty()
+                .function(ty.params.iter().copied(), ty.results.iter().copied());
+        }
+
+        let mut module = wasm_encoder::Module::new();
+        module.section(&types);
+        module.section(&imports);
+        module.section(&function_section);
+        module.section(&tables);
+        module.section(&memories);
+        module.section(&globals);
+        module.section(&exports);
+        module.section(&elements);
+        module.section(&code);
+        module.section(&data_section);
+
+        Ok(WasmProduct {
+            module,
+            functions: indices.functions,
+            data_addresses,
+        })
+    }
+}
+
+/// This is the output of `WasmModule`'s
+/// [`finish`](../struct.WasmModule.html#method.finish) function.
+/// It contains the generated `wasm_encoder::Module` and other information produced during
+/// compilation.
+pub struct WasmProduct {
+    /// Wasm module.
+    pub module: wasm_encoder::Module,
+    functions: SecondaryMap<FuncId, u32>,
+    data_addresses: SecondaryMap<DataId, Option<u64>>,
+}
+
+impl WasmProduct {
+    /// Return the index of the given function in the module's function index space.
+    ///
+    /// Function pointers are indices into the module's function table, which is this index
+    /// plus one.
+    #[inline]
+    pub fn func_index(&self, id: FuncId) -> u32 {
+        self.functions[id]
+    }
+
+    /// Return the address of the given data object in the module's linear memory.
+    #[inline]
+    pub fn data_address(&self, id: DataId) -> u64 {
+        self.data_addresses[id].unwrap()
+    }
+
+    /// Write the Wasm module to a byte vector.
+    pub fn emit(self) -> Vec<u8> {
+        self.module.finish()
+    }
+}
@@ -0,0 +1,18 @@
+//! Top-level lib.rs for `cranelift_wasm_emit`.
+//!
+//! This crate provides a `cranelift_module::Module` implementation which translates Cranelift IR
+//! to WebAssembly instead of compiling it to machine code. This re-exports `wasm_encoder` so you
+//! don't have to explicitly keep the versions in sync.
+
+#![deny(missing_docs)]
+
+mod backend;
+mod structure;
+mod translate;
+
+pub use crate::backend::{WasmBuilder, WasmModule, WasmProduct};
+
+/// Version number of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub use wasm_encoder;
@@ -0,0 +1,132 @@
+//! Analysis of the control-flow graph used to recover structured control flow.
+//!
+//! WebAssembly only has structured control flow: `block`, `loop` and `if`, with branches to the
+//! end of enclosing blocks or to the start of enclosing loops. The translation follows Norman
+//! Ramsey's "Beyond Relooper" (ICFP 2022): the dominator tree of a reducible CFG is walked,
+//! emitting a `loop` around each loop header and a `block` in front of each merge node, that is
+//! each block with more than one forward edge into it. All other blocks have a single forward
+//! predecessor and are emitted inline at the branch to them.
+//!
+//! Irreducible control flow can't be expressed this way, so functions with irreducible CFGs are
+//! instead translated to a loop dispatching to the next block based on a label local.
+
+use cranelift_codegen::dominator_tree::{DominatorTree, DominatorTreePreorder};
+use cranelift_codegen::flowgraph::ControlFlowGraph;
+use cranelift_codegen::ir::{Block, BlockCall, Function, InstructionData};
+use cranelift_entity::SecondaryMap;
+
+/// Returns the control-flow edges out of `block`.
+///
+/// Each conditional branch contributes two edges, even if both point to the same block. Jump
+/// table entries using the same block and arguments are only counted once, as they share a single
+/// edge in the translated code.
+pub(crate) fn edges(func: &Function, block: Block) -> Vec<BlockCall> {
+    let Some(inst) = func.layout.last_inst(block) else {
+        return Vec::new();
+    };
+    let pool = &func.dfg.value_lists;
+    let data = &func.dfg.insts[inst];
+    let destinations = data.branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables);
+    if !matches!(data, InstructionData::BranchTable { .. }) {
+        return destinations.to_vec();
+    }
+    let mut edges = Vec::<BlockCall>::new();
+    for call in destinations {
+        let is_new = !edges
+            .iter()
+            .any(|e| e.block(pool) == call.block(pool) && e.args(pool).eq(call.args(pool)));
+        if is_new {
+            edges.push(*call);
+        }
+    }
+    edges
+}
+
+/// The structure of a function's control-flow graph.
+pub(crate) struct Structure {
+    /// The position of each reachable block in reverse post-order.
+    rpo: SecondaryMap<Block, u32>,
+    /// The number of forward edges into each block.
+    forward_edges: SecondaryMap<Block, u32>,
+    /// Whether a block is the target of a back edge.
+    loop_header: SecondaryMap<Block, bool>,
+    /// Whether all back edges go to a block dominating their source.
+    reducible: bool,
+    /// All reachable blocks in reverse post-order.
+    blocks: Vec<Block>,
+    preorder: DominatorTreePreorder,
+}
+
+impl Structure {
+    /// Analyze the control-flow graph of `func`.
+    pub(crate) fn new(func: &Function, cfg: &ControlFlowGraph, domtree: &DominatorTree) -> Self {
+        debug_assert!(cfg.is_valid() && domtree.is_valid());
+
+        let blocks = domtree.cfg_rpo().copied().collect::<Vec<_>>();
+        let mut rpo = SecondaryMap::with_default(u32::MAX);
+        for (i, &block) in blocks.iter().enumerate() {
+            rpo[block] = u32::try_from(i).unwrap();
+        }
+
+        let mut forward_edges = SecondaryMap::new();
+        let mut loop_header = SecondaryMap::new();
+        let mut reducible = true;
+        for &block in &blocks {
+            for call in edges(func, block) {
+                let target = call.block(&func.dfg.value_lists);
+                if rpo[target] > rpo[block] {
+                    forward_edges[target] += 1;
+                } else {
+                    loop_header[target] = true;
+                    reducible &= domtree.dominates(target, block, &func.layout);
+                }
+            }
+        }
+
+        let mut preorder = DominatorTreePreorder::new();
+        preorder.compute(domtree);
+
+        Self {
+            rpo,
+            forward_edges,
+            loop_header,
+            reducible,
+            blocks,
+            preorder,
+        }
+    }
+
+    /// Can this function be translated with structured control flow only?
+    pub(crate) fn is_reducible(&self) -> bool {
+        self.reducible
+    }
+
+    /// All reachable blocks in reverse post-order.
+    pub(crate) fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Does the edge from `from` to `to` go backwards, to an enclosing loop header?
+    pub(crate) fn is_backward(&self, from: Block, to: Block) -> bool {
+        self.rpo[to] <= self.rpo[from]
+    }
+
+    /// Is `block` the target of a back edge?
+    pub(crate) fn is_loop_header(&self, block: Block) -> bool {
+        self.loop_header[block]
+    }
+
+    /// Does `block` have more than one forward edge into it?
+    pub(crate) fn is_merge_node(&self, block: Block) -> bool {
+        self.forward_edges[block] > 1
+    }
+
+    /// Returns the children of `block` in the dominator tree that are merge nodes, in reverse
+    /// post-order.
+    pub(crate) fn merge_children(&self, block: Block) -> Vec<Block> {
+        self.preorder
+            .children(block)
+            .filter(|&child| self.is_merge_node(child))
+            .collect()
+    }
+}
@@ -0,0 +1,1288 @@
+//! Translation of Cranelift IR functions to WebAssembly function bodies.
+//!
+//! Every CLIF value, including block parameters, is stored in its own Wasm local. Values of type
+//! `i8` and `i16` are stored in `i32` locals and are always kept zero-extended, so operations
+//! whose result depends on the upper bits, like signed division or comparisons, extend their
+//! operands as needed.
+
+use crate::structure::{Structure, edges};
+use cranelift_codegen::dominator_tree::DominatorTree;
+use cranelift_codegen::flowgraph::ControlFlowGraph;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{
+    self, Block, BlockArg, BlockCall, Endianness, ExternalName, Function, GlobalValueData,
+    InstructionData, MemFlags, Opcode, Type, Value, types,
+};
+use cranelift_codegen::{CodegenError, CodegenResult};
+use cranelift_entity::SecondaryMap;
+use cranelift_module::{DataId, FuncId, ModuleRelocTarget};
+use std::borrow::Cow;
+use std::fmt::Display;
+use wasm_encoder::{BlockType, Ieee32, Ieee64, Instruction, MemArg, ValType};
+
+/// The index of the global holding the stack pointer of the shadow stack in linear memory.
+pub(crate) const STACK_POINTER_GLOBAL: u32 = 0;
+
+/// The type of a Wasm function.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct FuncType {
+    pub(crate) params: Vec<ValType>,
+    pub(crate) results: Vec<ValType>,
+}
+
+impl FuncType {
+    /// Returns the Wasm type of functions with the given signature.
+    pub(crate) fn from_signature(sig: &ir::Signature) -> CodegenResult<Self> {
+        Ok(Self {
+            params: sig
+                .params
+                .iter()
+                .map(|p| val_type(p.value_type))
+                .collect::<CodegenResult<_>>()?,
+            results: sig
+                .returns
+                .iter()
+                .map(|p| val_type(p.value_type))
+                .collect::<CodegenResult<_>>()?,
+        })
+    }
+}
+
+/// A function called by a translated function.
+#[derive(Clone, Debug)]
+pub(crate) enum Callee {
+    /// A function declared in the module.
+    Func(FuncId),
+    /// A libcall, which is imported with the given type.
+    LibCall(ir::LibCall, FuncType),
+}
+
+/// A Wasm instruction in a translated function.
+///
+/// Besides plain Wasm instructions this refers to module-level entities, whose index or address is
+/// only known once all functions and data objects of the module have been defined.
+pub(crate) enum Inst {
+    Wasm(Instruction<'static>),
+    Call(Callee),
+    ReturnCall(Callee),
+    CallIndirect(FuncType),
+    ReturnCallIndirect(FuncType),
+    /// Push the address of a function, which is its index in the function table.
+    FuncAddr(FuncId),
+    /// Push the address of a data object in linear memory.
+    DataAddr(DataId),
+}
+
+/// A function translated to Wasm.
+pub(crate) struct TranslatedFunction {
+    /// The locals of the function, excluding its parameters.
+    pub(crate) locals: Vec<ValType>,
+    pub(crate) body: Vec<Inst>,
+}
+
+/// Translate `func` to Wasm.
+///
+/// `cfg` and `domtree` must be valid for `func`. Pointers have type `pointer_type`, which must be
+/// either `i32` or `i64`.
+pub(crate) fn translate_function(
+    func: &Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    pointer_type: Type,
+) -> CodegenResult<TranslatedFunction> {
+    let structure = Structure::new(func, cfg, domtree);
+    let ty = FuncType::from_signature(&func.signature)?;
+    let mut translator = Translator {
+        func,
+        structure: &structure,
+        pointer_type,
+        num_params: u32::try_from(ty.params.len()).unwrap(),
+        locals: Vec::new(),
+        value_locals: SecondaryMap::new(),
+        scratch_locals: Vec::new(),
+        frame: None,
+        dispatch: None,
+        scopes: Vec::new(),
+        body: Vec::new(),
+    };
+    translator.prologue()?;
+    if structure.is_reducible() {
+        let entry = func.layout.entry_block().unwrap();
+        translator.do_tree(entry)?;
+    } else {
+        translator.dispatch_loop()?;
+    }
+    // All paths through the body end in a branch, return or trap, but a validator doesn't know
+    // that the end of the last construct is unreachable.
+    translator.emit(Instruction::Unreachable);
+    translator.emit(Instruction::End);
+    debug_assert!(translator.scopes.is_empty());
+
+    Ok(TranslatedFunction {
+        locals: translator.locals,
+        body: translator.body,
+    })
+}
+
+fn unsupported(what: impl Display) -> CodegenError {
+    CodegenError::Unsupported(format!("{what} is not supported by the Wasm backend"))
+}
+
+/// Returns the Wasm type used to represent values of type `ty`.
+fn val_type(ty: Type) -> CodegenResult<ValType> {
+    match ty {
+        types::I8 | types::I16 | types::I32 => Ok(ValType::I32),
+        types::I64 => Ok(ValType::I64),
+        types::F32 => Ok(ValType::F32),
+        types::F64 => Ok(ValType::F64),
+        _ => Err(unsupported(format_args!("type {ty}"))),
+    }
+}
+
+/// Picks the 32-bit or 64-bit variant of an instruction.
+macro_rules! pick {
+    ($is64:expr, $i32:ident, $i64:ident) => {
+        if $is64 {
+            Instruction::$i64
+        } else {
+            Instruction::$i32
+        }
+    };
+}
+
+/// A structured control-flow construct enclosing the code being emitted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// A `loop` headed by the given block.
+    Loop(Block),
+    /// A `block` followed by the given block.
+    Block(Block),
+    /// The loop dispatching to all blocks of a function with irreducible control flow.
+    Dispatch,
+    /// Any other construct, which is never branched to.
+    Other,
+}
+
+/// The frame of a function in the shadow stack, holding its stack slots.
+struct Frame {
+    /// The local holding the address of the frame.
+    base: u32,
+    size: u32,
+    offsets: SecondaryMap<ir::StackSlot, u32>,
+}
+
+/// The state of the translation of an irreducible function.
+struct Dispatch {
+    /// The local holding the index of the next block to execute.
+    label: u32,
+    index: SecondaryMap<Block, u32>,
+}
+
+struct Translator<'a> {
+    func: &'a Function,
+    structure: &'a Structure,
+    pointer_type: Type,
+    num_params: u32,
+    locals: Vec<ValType>,
+    value_locals: SecondaryMap<Value, Option<u32>>,
+    scratch_locals: Vec<(ValType, u32)>,
+    frame: Option<Frame>,
+    dispatch: Option<Dispatch>,
+    scopes: Vec<Scope>,
+    body: Vec<Inst>,
+}
+
+impl<'a> Translator<'a> {
+    fn emit(&mut self, inst: Instruction<'static>) {
+        self.body.push(Inst::Wasm(inst));
+    }
+
+    fn new_local(&mut self, ty: ValType) -> u32 {
+        self.locals.push(ty);
+        self.num_params + u32::try_from(self.locals.len() - 1).unwrap()
+    }
+
+    /// Returns the `n`th scratch local of type `ty`.
+    fn scratch_local(&mut self, ty: ValType, n: usize) -> u32 {
+        let existing = self
+            .scratch_locals
+            .iter()
+            .filter(|(t, _)| *t == ty)
+            .nth(n)
+            .map(|(_, local)| *local);
+        match existing {
+            Some(local) => local,
+            None => {
+                let local = self.new_local(ty);
+                self.scratch_locals.push((ty, local));
+                self.scratch_local(ty, n)
+            }
+        }
+    }
+
+    fn local(&mut self, value: Value) -> CodegenResult<u32> {
+        let value = self.func.dfg.resolve_aliases(value);
+        if let Some(local) = self.value_locals[value] {
+            return Ok(local);
+        }
+        let local = self.new_local(val_type(self.func.dfg.value_type(value))?);
+        self.value_locals[value] = Some(local);
+        Ok(local)
+    }
+
+    fn get(&mut self, value: Value) -> CodegenResult<()> {
+        let local = self.local(value)?;
+        self.emit(Instruction::LocalGet(local));
+        Ok(())
+    }
+
+    /// Push `value`, sign-extending it to 32 bits if it's a small integer.
+    fn get_signed(&mut self, value: Value) -> CodegenResult<()> {
+        self.get(value)?;
+        match self.func.dfg.value_type(value) {
+            types::I8 => self.emit(Instruction::I32Extend8S),
+            types::I16 => self.emit(Instruction::I32Extend16S),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, value: Value) -> CodegenResult<()> {
+        let local = self.local(value)?;
+        self.emit(Instruction::LocalSet(local));
+        Ok(())
+    }
+
+    /// Zero-extend the small integer of type `ty` on top of the stack.
+    fn narrow(&mut self, ty: Type) {
+        match ty {
+            types::I8 => {
+                self.emit(Instruction::I32Const(0xff));
+                self.emit(Instruction::I32And);
+            }
+            types::I16 => {
+                self.emit(Instruction::I32Const(0xffff));
+                self.emit(Instruction::I32And);
+            }
+            _ => {}
+        }
+    }
+
+    /// Push `value` as an `i32` which is non-zero if and only if `value` is.
+    fn get_condition(&mut self, value: Value) -> CodegenResult<()> {
+        self.get(value)?;
+        if self.func.dfg.value_type(value) == types::I64 {
+            self.emit(Instruction::I64Const(0));
+            self.emit(Instruction::I64Ne);
+        }
+        Ok(())
+    }
+
+    fn pointer_const(&mut self, value: i64) {
+        if self.pointer_type == types::I64 {
+            self.emit(Instruction::I64Const(value));
+        } else {
+            self.emit(Instruction::I32Const(value as i32));
+        }
+    }
+
+    fn pointer_add(&mut self) {
+        self.emit(pick!(self.pointer_type == types::I64, I32Add, I64Add));
+    }
+
+    /// Convert the pointer on top of the stack to an index into the function table.
+    fn pointer_to_table_index(&mut self) {
+        if self.pointer_type == types::I64 {
+            self.emit(Instruction::I32WrapI64);
+        }
+    }
+
+    /// Emit the code at the start of the function.
+    fn prologue(&mut self) -> CodegenResult<()> {
+        let func = self.func;
+        let entry = func.layout.entry_block().unwrap();
+        for (i, &param) in func.dfg.block_params(entry).iter().enumerate() {
+            self.value_locals[param] = Some(u32::try_from(i).unwrap());
+        }
+
+        // Callers outside of the module may pass small integers with arbitrary upper bits.
+        for &param in func.dfg.block_params(entry) {
+            let ty = func.dfg.value_type(param);
+            if ty == types::I8 || ty == types::I16 {
+                self.get(param)?;
+                self.narrow(ty);
+                self.set(param)?;
+            }
+        }
+
+        if !func.dynamic_stack_slots.is_empty() {
+            return Err(unsupported("dynamic stack slots"));
+        }
+        if func.sized_stack_slots.is_empty() {
+            return Ok(());
+        }
+        let mut offsets = SecondaryMap::new();
+        let mut size = 0u32;
+        for (slot, data) in func.sized_stack_slots.iter() {
+            if data.align_shift > 4 {
+                return Err(unsupported("stack slot alignment above 16 bytes"));
+            }
+            let align = 1u32 << data.align_shift;
+            size = size.next_multiple_of(align);
+            offsets[slot] = size;
+            size = size
+                .checked_add(data.size)
+                .ok_or_else(|| CodegenError::ImplLimitExceeded)?;
+        }
+        let size = size.next_multiple_of(16);
+        let base = self.new_local(val_type(self.pointer_type)?);
+        self.emit(Instruction::GlobalGet(STACK_POINTER_GLOBAL));
+        self.pointer_const(size.into());
+        self.emit(pick!(self.pointer_type == types::I64, I32Sub, I64Sub));
+        self.emit(Instruction::LocalTee(base));
+        self.emit(Instruction::GlobalSet(STACK_POINTER_GLOBAL));
+        self.frame = Some(Frame {
+            base,
+            size,
+            offsets,
+        });
+        Ok(())
+    }
+
+    /// Emit the code leaving the function, before a return or tail call.
+    fn epilogue(&mut self) {
+        if let Some(frame) = &self.frame {
+            let (base, size) = (frame.base, frame.size);
+            self.emit(Instruction::LocalGet(base));
+            self.pointer_const(size.into());
+            self.pointer_add();
+            self.emit(Instruction::GlobalSet(STACK_POINTER_GLOBAL));
+        }
+    }
+
+    /// Emit `block` and all blocks it immediately dominates.
+    fn do_tree(&mut self, block: Block) -> CodegenResult<()> {
+        let merge_children = self.structure.merge_children(block);
+        if self.structure.is_loop_header(block) {
+            self.emit(Instruction::Loop(BlockType::Empty));
+            self.scopes.push(Scope::Loop(block));
+            self.node_within(block, &merge_children)?;
+            self.scopes.pop();
+            self.emit(Instruction::End);
+            Ok(())
+        } else {
+            self.node_within(block, &merge_children)
+        }
+    }
+
+    /// Emit `block` followed by `merge_children`, each of which is preceded by the end of a
+    /// Wasm block so that `block` and earlier merge children can branch to it.
+    fn node_within(&mut self, block: Block, merge_children: &[Block]) -> CodegenResult<()> {
+        match merge_children.split_last() {
+            Some((&last, rest)) => {
+                self.emit(Instruction::Block(BlockType::Empty));
+                self.scopes.push(Scope::Block(last));
+                self.node_within(block, rest)?;
+                self.scopes.pop();
+                self.emit(Instruction::End);
+                self.do_tree(last)
+            }
+            None => self.translate_block(block),
+        }
+    }
+
+    /// Emit a loop executing the block whose index is stored in a local, for functions whose
+    /// control flow can't be structured.
+    fn dispatch_loop(&mut self) -> CodegenResult<()> {
+        let blocks = self.structure.blocks().to_vec();
+        // Locals are zero-initialized, so the entry block, which comes first in reverse
+        // post-order, is executed first.
+        let label = self.new_local(ValType::I32);
+        let mut index = SecondaryMap::new();
+        for (i, &block) in blocks.iter().enumerate() {
+            index[block] = u32::try_from(i).unwrap();
+        }
+        self.dispatch = Some(Dispatch { label, index });
+
+        self.emit(Instruction::Loop(BlockType::Empty));
+        self.scopes.push(Scope::Dispatch);
+        for _ in &blocks {
+            self.emit(Instruction::Block(BlockType::Empty));
+            self.scopes.push(Scope::Other);
+        }
+        self.emit(Instruction::LocalGet(label));
+        let targets = (0..u32::try_from(blocks.len()).unwrap()).collect::<Vec<_>>();
+        self.emit(Instruction::BrTable(Cow::Owned(targets), 0));
+        for block in blocks {
+            self.emit(Instruction::End);
+            self.scopes.pop();
+            self.translate_block(block)?;
+        }
+        self.scopes.pop();
+        self.emit(Instruction::End);
+        Ok(())
+    }
+
+    /// Emit a branch to the enclosing `scope`.
+    fn branch_to(&mut self, scope: Scope) {
+        let position = self
+            .scopes
+            .iter()
+            .rposition(|s| *s == scope)
+            .expect("branch target must be in scope");
+        let depth = self.scopes.len() - 1 - position;
+        self.emit(Instruction::Br(u32::try_from(depth).unwrap()));
+    }
+
+    /// Emit the control-flow edge from `from` given by `call`.
+    fn do_edge(&mut self, from: Block, call: BlockCall) -> CodegenResult<()> {
+        let func = self.func;
+        let pool = &func.dfg.value_lists;
+        let target = call.block(pool);
+
+        // Read all arguments before assigning any parameter, as parameters may be used as
+        // arguments.
+        let mut count = 0;
+        for arg in call.args(pool) {
+            match arg {
+                BlockArg::Value(value) => self.get(value)?,
+                _ => return Err(unsupported("exception handling")),
+            }
+            count += 1;
+        }
+        debug_assert_eq!(count, func.dfg.block_params(target).len());
+        for &param in func.dfg.block_params(target).iter().rev() {
+            self.set(param)?;
+        }
+
+        if let Some(dispatch) = &self.dispatch {
+            let (label, index) = (dispatch.label, dispatch.index[target]);
+            self.emit(Instruction::I32Const(index as i32));
+            self.emit(Instruction::LocalSet(label));
+            self.branch_to(Scope::Dispatch);
+            Ok(())
+        } else if self.structure.is_backward(from, target) {
+            self.branch_to(Scope::Loop(target));
+            Ok(())
+        } else if self.structure.is_merge_node(target) {
+            self.branch_to(Scope::Block(target));
+            Ok(())
+        } else {
+            self.do_tree(target)
+        }
+    }
+
+    fn translate_block(&mut self, block: Block) -> CodegenResult<()> {
+        let func = self.func;
+        for inst in func.layout.block_insts(block) {
+            if func.dfg.insts[inst].opcode().is_terminator() {
+                self.translate_terminator(block, inst)?;
+            } else {
+                self.translate_inst(inst)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn translate_terminator(&mut self, block: Block, inst: ir::Inst) -> CodegenResult<()> {
+        let func = self.func;
+        let dfg = &func.dfg;
+        match dfg.insts[inst] {
+            InstructionData::Jump { destination, .. } => self.do_edge(block, destination),
+            InstructionData::Brif {
+                arg,
+                blocks: [then_block, else_block],
+                ..
+            } => {
+                self.get_condition(arg)?;
+                self.emit(Instruction::If(BlockType::Empty));
+                self.scopes.push(Scope::Other);
+                self.do_edge(block, then_block)?;
+                self.emit(Instruction::Else);
+                self.do_edge(block, else_block)?;
+                self.scopes.pop();
+                self.emit(Instruction::End);
+                Ok(())
+            }
+            InstructionData::BranchTable { arg, table, .. } => {
+                // Every distinct edge gets its own Wasm block, the end of which is followed by the
+                // code for the edge.
+                let edges = edges(func, block);
+                let pool = &dfg.value_lists;
+                let edge_index = |call: &BlockCall| {
+                    let index = edges
+                        .iter()
+                        .position(|e| {
+                            e.block(pool) == call.block(pool) && e.args(pool).eq(call.args(pool))
+                        })
+                        .unwrap();
+                    u32::try_from(index).unwrap()
+                };
+                let table = &dfg.jump_tables[table];
+                let targets = table.as_slice().iter().map(edge_index).collect::<Vec<_>>();
+                let default = edge_index(&table.default_block());
+
+                for _ in &edges {
+                    self.emit(Instruction::Block(BlockType::Empty));
+                    self.scopes.push(Scope::Other);
+                }
+                self.get(arg)?;
+                self.emit(Instruction::BrTable(Cow::Owned(targets), default));
+                for edge in edges {
+                    self.emit(Instruction::End);
+                    self.scopes.pop();
+                    self.do_edge(block, edge)?;
+                }
+                Ok(())
+            }
+            InstructionData::MultiAry {
+                opcode: Opcode::Return,
+                ..
+            } => {
+                for &arg in dfg.inst_args(inst) {
+                    self.get(arg)?;
+                }
+                self.epilogue();
+                self.emit(Instruction::Return);
+                Ok(())
+            }
+            InstructionData::Call {
+                opcode: Opcode::ReturnCall,
+                func_ref,
+                ..
+            } => {
+                let callee = self.callee(func_ref)?;
+                for &arg in dfg.inst_args(inst) {
+                    self.get(arg)?;
+                }
+                self.epilogue();
+                self.body.push(Inst::ReturnCall(callee));
+                Ok(())
+            }
+            InstructionData::CallIndirect {
+                opcode: Opcode::ReturnCallIndirect,
+                sig_ref,
+                ..
+            } => {
+                let ty = FuncType::from_signature(&dfg.signatures[sig_ref])?;
+                for &arg in dfg.inst_variable_args(inst) {
+                    self.get(arg)?;
+                }
+                self.get(dfg.inst_fixed_args(inst)[0])?;
+                self.pointer_to_table_index();
+                self.epilogue();
+                self.body.push(Inst::ReturnCallIndirect(ty));
+                Ok(())
+            }
+            InstructionData::Trap {
+                opcode: Opcode::Trap,
+                ..
+            } => {
+                self.emit(Instruction::Unreachable);
+                Ok(())
+            }
+            ref data => Err(unsupported(data.opcode())),
+        }
+    }
+
+    fn callee(&self, func_ref: ir::FuncRef) -> CodegenResult<Callee> {
+        let func = self.func;
+        let ext_func = &func.dfg.ext_funcs[func_ref];
+        match ext_func.name {
+            ExternalName::User(name) => {
+                let name = &func.params.user_named_funcs()[name];
+                if name.namespace != 0 {
+                    return Err(unsupported("calling a data object"));
+                }
+                Ok(Callee::Func(FuncId::from_name(&ModuleRelocTarget::user(
+                    name.namespace,
+                    name.index,
+                ))))
+            }
+            ExternalName::LibCall(libcall) => Ok(Callee::LibCall(
+                libcall,
+                FuncType::from_signature(&func.dfg.signatures[ext_func.signature])?,
+            )),
+            ref name => Err(unsupported(format_args!(
+                "calling {}",
+                name.display(Some(&func.params))
+            ))),
+        }
+    }
+
+    /// Push the address of the symbol `name`.
+    fn symbol_address(&mut self, name: &ExternalName) -> CodegenResult<()> {
+        let ExternalName::User(name) = name else {
+            return Err(unsupported(format_args!(
+                "the address of {}",
+                name.display(Some(&self.func.params))
+            )));
+        };
+        let name = &self.func.params.user_named_funcs()[*name];
+        let target = ModuleRelocTarget::user(name.namespace, name.index);
+        self.body.push(match name.namespace {
+            0 => Inst::FuncAddr(FuncId::from_name(&target)),
+            1 => Inst::DataAddr(DataId::from_name(&target)),
+            _ => return Err(unsupported(format_args!("the address of {target}"))),
+        });
+        Ok(())
+    }
+
+    /// Store the results of `inst` from the stack.
+    fn set_results(&mut self, inst: ir::Inst) -> CodegenResult<()> {
+        let dfg = &self.func.dfg;
+        for &result in dfg.inst_results(inst).iter().rev() {
+            self.set(result)?;
+        }
+        // The callee may not be a translated function and return small integers with arbitrary
+        // upper bits.
+        for &result in dfg.inst_results(inst) {
+            let ty = dfg.value_type(result);
+            if ty == types::I8 || ty == types::I16 {
+                self.get(result)?;
+                self.narrow(ty);
+                self.set(result)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the address of a memory access to `addr + offset`, returning the static offset
+    /// of the access.
+    fn address(&mut self, addr: Value, offset: i32) -> CodegenResult<u64> {
+        self.get(addr)?;
+        if offset < 0 {
+            self.pointer_const(offset.into());
+            self.pointer_add();
+            Ok(0)
+        } else {
+            Ok(offset as u64)
+        }
+    }
+
+    fn mem_arg(flags: MemFlags, offset: u64, bytes: u32) -> CodegenResult<MemArg> {
+        if flags.explicit_endianness() == Some(Endianness::Big) {
+            return Err(unsupported("big-endian memory access"));
+        }
+        Ok(MemArg {
+            offset,
+            align: if flags.aligned() {
+                bytes.trailing_zeros()
+            } else {
+                0
+            },
+            memory_index: 0,
+        })
+    }
+
+    fn translate_inst(&mut self, inst: ir::Inst) -> CodegenResult<()> {
+        let func = self.func;
+        let dfg = &func.dfg;
+        let data = &dfg.insts[inst];
+        let opcode = data.opcode();
+        let args = dfg.inst_args(inst);
+        let result = dfg.inst_results(inst).first().copied();
+        let ty = result.map_or(types::INVALID, |r| dfg.value_type(r));
+
+        match *data {
+            InstructionData::NullAry {
+                opcode: Opcode::Nop | Opcode::Debugtrap,
+            } => return Ok(()),
+            InstructionData::UnaryImm { imm, .. } => {
+                let imm = imm.bits();
+                match ty {
+                    types::I8 => self.emit(Instruction::I32Const((imm & 0xff) as i32)),
+                    types::I16 => self.emit(Instruction::I32Const((imm & 0xffff) as i32)),
+                    types::I32 => self.emit(Instruction::I32Const(imm as i32)),
+                    types::I64 => self.emit(Instruction::I64Const(imm)),
+                    _ => return Err(unsupported(format_args!("iconst.{ty}"))),
+                }
+            }
+            InstructionData::UnaryIeee32 { imm, .. } => {
+                self.emit(Instruction::F32Const(Ieee32::from(f32::from_bits(
+                    imm.bits(),
+                ))));
+            }
+            InstructionData::UnaryIeee64 { imm, .. } => {
+                self.emit(Instruction::F64Const(Ieee64::from(f64::from_bits(
+                    imm.bits(),
+                ))));
+            }
+            InstructionData::CondTrap { arg, .. } => {
+                self.get_condition(arg)?;
+                if opcode == Opcode::Trapz {
+                    self.emit(Instruction::I32Eqz);
+                }
+                self.emit(Instruction::If(BlockType::Empty));
+                self.emit(Instruction::Unreachable);
+                self.emit(Instruction::End);
+                return Ok(());
+            }
+            InstructionData::Load {
+                arg, flags, offset, ..
+            } => {
+                let offset = self.address(arg, offset.into())?;
+                let (load, bytes) = match (opcode, ty) {
+                    (Opcode::Load, types::I8) => (Instruction::I32Load8U as fn(_) -> _, 1),
+                    (Opcode::Load, types::I16) => (Instruction::I32Load16U as fn(_) -> _, 2),
+                    (Opcode::Load, types::I32) => (Instruction::I32Load as fn(_) -> _, 4),
+                    (Opcode::Load, types::I64) => (Instruction::I64Load as fn(_) -> _, 8),
+                    (Opcode::Load, types::F32) => (Instruction::F32Load as fn(_) -> _, 4),
+                    (Opcode::Load, types::F64) => (Instruction::F64Load as fn(_) -> _, 8),
+                    (Opcode::Uload8, types::I64) => (Instruction::I64Load8U as fn(_) -> _, 1),
+                    (Opcode::Uload8, _) => (Instruction::I32Load8U as fn(_) -> _, 1),
+                    (Opcode::Sload8, types::I64) => (Instruction::I64Load8S as fn(_) -> _, 1),
+                    (Opcode::Sload8, _) => (Instruction::I32Load8S as fn(_) -> _, 1),
+                    (Opcode::Uload16, types::I64) => (Instruction::I64Load16U as fn(_) -> _, 2),
+                    (Opcode::Uload16, _) => (Instruction::I32Load16U as fn(_) -> _, 2),
+                    (Opcode::Sload16, types::I64) => (Instruction::I64Load16S as fn(_) -> _, 2),
+                    (Opcode::Sload16, _) => (Instruction::I32Load16S as fn(_) -> _, 2),
+                    (Opcode::Uload32, types::I64) => (Instruction::I64Load32U as fn(_) -> _, 4),
+                    (Opcode::Sload32, types::I64) => (Instruction::I64Load32S as fn(_) -> _, 4),
+                    _ => return Err(unsupported(format_args!("{opcode}.{ty}"))),
+                };
+                self.emit(load(Self::mem_arg(flags, offset, bytes)?));
+                self.narrow(ty);
+            }
+            InstructionData::Store {
+                args: [value, addr],
+                flags,
+                offset,
+                ..
+            } => {
+                let offset = self.address(addr, offset.into())?;
+                self.get(value)?;
+                let (store, bytes) = match (opcode, dfg.value_type(value)) {
+                    (Opcode::Store, types::I8) => (Instruction::I32Store8 as fn(_) -> _, 1),
+                    (Opcode::Store, types::I16) => (Instruction::I32Store16 as fn(_) -> _, 2),
+                    (Opcode::Store, types::I32) => (Instruction::I32Store as fn(_) -> _, 4),
+                    (Opcode::Store, types::I64) => (Instruction::I64Store as fn(_) -> _, 8),
+                    (Opcode::Store, types::F32) => (Instruction::F32Store as fn(_) -> _, 4),
+                    (Opcode::Store, types::F64) => (Instruction::F64Store as fn(_) -> _, 8),
+                    (Opcode::Istore8, types::I64) => (Instruction::I64Store8 as fn(_) -> _, 1),
+                    (Opcode::Istore8, _) => (Instruction::I32Store8 as fn(_) -> _, 1),
+                    (Opcode::Istore16, types::I64) => (Instruction::I64Store16 as fn(_) -> _, 2),
+                    (Opcode::Istore16, _) => (Instruction::I32Store16 as fn(_) -> _, 2),
+                    (Opcode::Istore32, types::I64) => (Instruction::I64Store32 as fn(_) -> _, 4),
+                    (_, value_ty) => return Err(unsupported(format_args!("{opcode}.{value_ty}"))),
+                };
+                self.emit(store(Self::mem_arg(flags, offset, bytes)?));
+                return Ok(());
+            }
+            InstructionData::StackLoad {
+                opcode: Opcode::StackAddr,
+                stack_slot,
+                offset,
+            } => {
+                let frame = self.frame.as_ref().unwrap();
+                let (base, slot_offset) = (frame.base, frame.offsets[stack_slot]);
+                self.emit(Instruction::LocalGet(base));
+                self.pointer_const(i64::from(slot_offset) + i64::from(i32::from(offset)));
+                self.pointer_add();
+            }
+            InstructionData::UnaryGlobalValue { global_value, .. } => {
+                match func.global_values[global_value] {
+                    GlobalValueData::Symbol {
+                        ref name,
+                        offset,
+                        tls: false,
+                        ..
+                    } => {
+                        self.symbol_address(name)?;
+                        if offset.bits() != 0 {
+                            self.pointer_const(offset.bits());
+                            self.pointer_add();
+                        }
+                    }
+                    ref gv => return Err(unsupported(format_args!("global value {gv}"))),
+                }
+            }
+            InstructionData::FuncAddr { func_ref, .. } => {
+                let name = dfg.ext_funcs[func_ref].name.clone();
+                self.symbol_address(&name)?;
+            }
+            InstructionData::Call { func_ref, .. } => {
+                debug_assert_eq!(opcode, Opcode::Call);
+                let callee = self.callee(func_ref)?;
+                for &arg in args {
+                    self.get(arg)?;
+                }
+                self.body.push(Inst::Call(callee));
+                return self.set_results(inst);
+            }
+            InstructionData::CallIndirect { sig_ref, .. } => {
+                debug_assert_eq!(opcode, Opcode::CallIndirect);
+                let ty = FuncType::from_signature(&dfg.signatures[sig_ref])?;
+                for &arg in dfg.inst_variable_args(inst) {
+                    self.get(arg)?;
+                }
+                self.get(dfg.inst_fixed_args(inst)[0])?;
+                self.pointer_to_table_index();
+                self.body.push(Inst::CallIndirect(ty));
+                return self.set_results(inst);
+            }
+            InstructionData::IntCompare {
+                cond, args: [a, b], ..
+            } => {
+                let operand_ty = dfg.value_type(a);
+                self.translate_icmp(cond, a, b, operand_ty)?;
+            }
+            InstructionData::FloatCompare {
+                cond, args: [a, b], ..
+            } => {
+                let operand_ty = dfg.value_type(a);
+                self.translate_fcmp(cond, a, b, operand_ty == types::F64)?;
+            }
+            InstructionData::Ternary {
+                opcode: Opcode::Select | Opcode::SelectSpectreGuard,
+                args: [c, x, y],
+            } => {
+                self.get(x)?;
+                self.get(y)?;
+                self.get_condition(c)?;
+                self.emit(Instruction::Select);
+            }
+            InstructionData::Binary { args: [a, b], .. } => {
+                self.translate_binary(opcode, ty, a, b)?
+            }
+            InstructionData::Unary { arg, .. } | InstructionData::LoadNoOffset { arg, .. } => {
+                self.translate_unary(opcode, ty, arg)?
+            }
+            _ => return Err(unsupported(opcode)),
+        }
+
+        // All remaining instructions have a single result, left on the stack.
+        debug_assert_eq!(dfg.inst_results(inst).len(), 1);
+        self.set(result.unwrap())
+    }
+
+    fn translate_icmp(&mut self, cond: IntCC, a: Value, b: Value, ty: Type) -> CodegenResult<()> {
+        if !ty.is_int() {
+            return Err(unsupported(format_args!("icmp.{ty}")));
+        }
+        let is64 = ty == types::I64;
+        let signed = matches!(
+            cond,
+            IntCC::SignedLessThan
+                | IntCC::SignedLessThanOrEqual
+                | IntCC::SignedGreaterThan
+                | IntCC::SignedGreaterThanOrEqual
+        );
+        if signed {
+            self.get_signed(a)?;
+            self.get_signed(b)?;
+        } else {
+            self.get(a)?;
+            self.get(b)?;
+        }
+        self.emit(match cond {
+            IntCC::Equal => pick!(is64, I32Eq, I64Eq),
+            IntCC::NotEqual => pick!(is64, I32Ne, I64Ne),
+            IntCC::SignedLessThan => pick!(is64, I32LtS, I64LtS),
+            IntCC::SignedLessThanOrEqual => pick!(is64, I32LeS, I64LeS),
+            IntCC::SignedGreaterThan => pick!(is64, I32GtS, I64GtS),
+            IntCC::SignedGreaterThanOrEqual => pick!(is64, I32GeS, I64GeS),
+            IntCC::UnsignedLessThan => pick!(is64, I32LtU, I64LtU),
+            IntCC::UnsignedLessThanOrEqual => pick!(is64, I32LeU, I64LeU),
+            IntCC::UnsignedGreaterThan => pick!(is64, I32GtU, I64GtU),
+            IntCC::UnsignedGreaterThanOrEqual => pick!(is64, I32GeU, I64GeU),
+        });
+        Ok(())
+    }
+
+    fn translate_fcmp(
+        &mut self,
+        cond: FloatCC,
+        a: Value,
+        b: Value,
+        is64: bool,
+    ) -> CodegenResult<()> {
+        let eq = pick!(is64, F32Eq, F64Eq);
+        let ne = pick!(is64, F32Ne, F64Ne);
+        let lt = pick!(is64, F32Lt, F64Lt);
+        let le = pick!(is64, F32Le, F64Le);
+        let gt = pick!(is64, F32Gt, F64Gt);
+        let ge = pick!(is64, F32Ge, F64Ge);
+
+        // Conditions which are a combination of two comparisons.
+        let (first, second, combine, negate) = match cond {
+            FloatCC::Ordered => ((a, a, &eq), (b, b, &eq), Instruction::I32And, false),
+            FloatCC::Unordered => ((a, a, &eq), (b, b, &eq), Instruction::I32And, true),
+            FloatCC::OrderedNotEqual => ((a, b, &lt), (a, b, &gt), Instruction::I32Or, false),
+            FloatCC::UnorderedOrEqual => ((a, b, &lt), (a, b, &gt), Instruction::I32Or, true),
+            _ => {
+                // Conditions which are a single comparison, or the negation of one.
+                let (op, negate) = match cond {
+                    FloatCC::Equal => (eq, false),
+                    FloatCC::NotEqual => (ne, false),
+                    FloatCC::LessThan => (lt, false),
+                    FloatCC::LessThanOrEqual => (le, false),
+                    FloatCC::GreaterThan => (gt, false),
+                    FloatCC::GreaterThanOrEqual => (ge, false),
+                    FloatCC::UnorderedOrLessThan => (ge, true),
+                    FloatCC::UnorderedOrLessThanOrEqual => (gt, true),
+                    FloatCC::UnorderedOrGreaterThan => (le, true),
+                    FloatCC::UnorderedOrGreaterThanOrEqual => (lt, true),
+                    _ => unreachable!(),
+                };
+                self.get(a)?;
+                self.get(b)?;
+                self.emit(op);
+                if negate {
+                    self.emit(Instruction::I32Eqz);
+                }
+                return Ok(());
+            }
+        };
+        for (x, y, op) in [first, second] {
+            self.get(x)?;
+            self.get(y)?;
+            self.emit(op.clone());
+        }
+        self.emit(combine);
+        if negate {
+            self.emit(Instruction::I32Eqz);
+        }
+        Ok(())
+    }
+
+    /// Push the shift amount `amount` for a shift of a value of type `ty`.
+    fn shift_amount(&mut self, ty: Type, amount: Value) -> CodegenResult<()> {
+        self.get(amount)?;
+        let amount_is64 = self.func.dfg.value_type(amount) == types::I64;
+        match (ty == types::I64, amount_is64) {
+            (true, false) => self.emit(Instruction::I64ExtendI32U),
+            (false, true) => self.emit(Instruction::I32WrapI64),
+            _ => {}
+        }
+        // Wasm only masks shift amounts to 32 or 64 bits.
+        if ty.bits() < 32 {
+            self.emit(Instruction::I32Const(ty.bits() as i32 - 1));
+            self.emit(Instruction::I32And);
+        }
+        Ok(())
+    }
+
+    fn translate_binary(
+        &mut self,
+        opcode: Opcode,
+        ty: Type,
+        a: Value,
+        b: Value,
+    ) -> CodegenResult<()> {
+        let is64 = ty == types::I64 || ty == types::F64;
+        if ty.is_float() {
+            self.get(a)?;
+            self.get(b)?;
+            self.emit(match opcode {
+                Opcode::Fadd => pick!(is64, F32Add, F64Add),
+                Opcode::Fsub => pick!(is64, F32Sub, F64Sub),
+                Opcode::Fmul => pick!(is64, F32Mul, F64Mul),
+                Opcode::Fdiv => pick!(is64, F32Div, F64Div),
+                Opcode::Fmin => pick!(is64, F32Min, F64Min),
+                Opcode::Fmax => pick!(is64, F32Max, F64Max),
+                Opcode::Fcopysign => pick!(is64, F32Copysign, F64Copysign),
+                _ => return Err(unsupported(format_args!("{opcode}.{ty}"))),
+            });
+            return Ok(());
+        }
+        if !ty.is_int() {
+            return Err(unsupported(format_args!("{opcode}.{ty}")));
+        }
+
+        let small = ty.bits() < 32;
+        match opcode {
+            Opcode::Iadd
+            | Opcode::Isub
+            | Opcode::Imul
+            | Opcode::Band
+            | Opcode::Bor
+            | Opcode::Bxor => {
+                self.get(a)?;
+                self.get(b)?;
+                self.emit(match opcode {
+                    Opcode::Iadd => pick!(is64, I32Add, I64Add),
+                    Opcode::Isub => pick!(is64, I32Sub, I64Sub),
+                    Opcode::Imul => pick!(is64, I32Mul, I64Mul),
+                    Opcode::Band => pick!(is64, I32And, I64And),
+                    Opcode::Bor => pick!(is64, I32Or, I64Or),
+                    _ => pick!(is64, I32Xor, I64Xor),
+                });
+                self.narrow(ty);
+            }
+            Opcode::Udiv | Opcode::Urem => {
+                self.get(a)?;
+                self.get(b)?;
+                self.emit(match opcode {
+                    Opcode::Udiv => pick!(is64, I32DivU, I64DivU),
+                    _ => pick!(is64, I32RemU, I64RemU),
+                });
+            }
+            Opcode::Sdiv | Opcode::Srem => {
+                self.get_signed(a)?;
+                self.get_signed(b)?;
+                if opcode == Opcode::Sdiv {
+                    self.emit(pick!(is64, I32DivS, I64DivS));
+                    if small {
+                        // Dividing the minimum value by -1 overflows the small type, which traps
+                        // in CLIF but not when computing with 32 bits.
+                        let tmp = self.scratch_local(ValType::I32, 0);
+                        self.emit(Instruction::LocalTee(tmp));
+                        self.emit(Instruction::I32Const(1 << (ty.bits() - 1)));
+                        self.emit(Instruction::I32Eq);
+                        self.emit(Instruction::If(BlockType::Empty));
+                        self.emit(Instruction::Unreachable);
+                        self.emit(Instruction::End);
+                        self.emit(Instruction::LocalGet(tmp));
+                    }
+                } else {
+                    self.emit(pick!(is64, I32RemS, I64RemS));
+                }
+                self.narrow(ty);
+            }
+            Opcode::Ishl => {
+                self.get(a)?;
+                self.shift_amount(ty, b)?;
+                self.emit(pick!(is64, I32Shl, I64Shl));
+                self.narrow(ty);
+            }
+            Opcode::Ushr => {
+                self.get(a)?;
+                self.shift_amount(ty, b)?;
+                self.emit(pick!(is64, I32ShrU, I64ShrU));
+            }
+            Opcode::Sshr => {
+                self.get_signed(a)?;
+                self.shift_amount(ty, b)?;
+                self.emit(pick!(is64, I32ShrS, I64ShrS));
+                self.narrow(ty);
+            }
+            Opcode::Rotl | Opcode::Rotr if !small => {
+                self.get(a)?;
+                self.shift_amount(ty, b)?;
+                self.emit(match opcode {
+                    Opcode::Rotl => pick!(is64, I32Rotl, I64Rotl),
+                    _ => pick!(is64, I32Rotr, I64Rotr),
+                });
+            }
+            Opcode::Rotl | Opcode::Rotr => {
+                // (a << n) | (a >> (bits - n)), or the other way around for rotr.
+                let (first, second) = match opcode {
+                    Opcode::Rotl => (Instruction::I32Shl, Instruction::I32ShrU),
+                    _ => (Instruction::I32ShrU, Instruction::I32Shl),
+                };
+                let amount = self.scratch_local(ValType::I32, 0);
+                self.shift_amount(ty, b)?;
+                self.emit(Instruction::LocalSet(amount));
+                self.get(a)?;
+                self.emit(Instruction::LocalGet(amount));
+                self.emit(first);
+                self.get(a)?;
+                self.emit(Instruction::I32Const(ty.bits() as i32));
+                self.emit(Instruction::LocalGet(amount));
+                self.emit(Instruction::I32Sub);
+                self.emit(second);
+                self.emit(Instruction::I32Or);
+                self.narrow(ty);
+            }
+            Opcode::Smin | Opcode::Smax | Opcode::Umin | Opcode::Umax => {
+                self.get(a)?;
+                self.get(b)?;
+                let compare = match opcode {
+                    Opcode::Smin => pick!(is64, I32LtS, I64LtS),
+                    Opcode::Smax => pick!(is64, I32GtS, I64GtS),
+                    Opcode::Umin => pick!(is64, I32LtU, I64LtU),
+                    _ => pick!(is64, I32GtU, I64GtU),
+                };
+                if matches!(opcode, Opcode::Smin | Opcode::Smax) {
+                    self.get_signed(a)?;
+                    self.get_signed(b)?;
+                } else {
+                    self.get(a)?;
+                    self.get(b)?;
+                }
+                self.emit(compare);
+                self.emit(Instruction::Select);
+            }
+            _ => return Err(unsupported(format_args!("{opcode}.{ty}"))),
+        }
+        Ok(())
+    }
+
+    fn translate_unary(&mut self, opcode: Opcode, ty: Type, arg: Value) -> CodegenResult<()> {
+        let from = self.func.dfg.value_type(arg);
+        let is64 = ty == types::I64 || ty == types::F64;
+        let from64 = from == types::I64 || from == types::F64;
+        match opcode {
+            Opcode::Ineg if ty.is_int() => {
+                self.emit(if is64 {
+                    Instruction::I64Const(0)
+                } else {
+                    Instruction::I32Const(0)
+                });
+                self.get(arg)?;
+                self.emit(pick!(is64, I32Sub, I64Sub));
+                self.narrow(ty);
+            }
+            Opcode::Bnot if ty.is_int() => {
+                self.get(arg)?;
+                self.emit(if is64 {
+                    Instruction::I64Const(-1)
+                } else {
+                    Instruction::I32Const(-1)
+                });
+                self.emit(pick!(is64, I32Xor, I64Xor));
+                self.narrow(ty);
+            }
+            Opcode::Iabs if ty.is_int() => {
+                // select(0 - a, a, a < 0)
+                self.emit(if is64 {
+                    Instruction::I64Const(0)
+                } else {
+                    Instruction::I32Const(0)
+                });
+                self.get_signed(arg)?;
+                self.emit(pick!(is64, I32Sub, I64Sub));
+                self.get(arg)?;
+                self.get_signed(arg)?;
+                self.emit(if is64 {
+                    Instruction::I64Const(0)
+                } else {
+                    Instruction::I32Const(0)
+                });
+                self.emit(pick!(is64, I32LtS, I64LtS));
+                self.emit(Instruction::Select);
+                self.narrow(ty);
+            }
+            Opcode::Clz if ty.is_int() => {
+                self.get(arg)?;
+                self.emit(pick!(is64, I32Clz, I64Clz));
+                if ty.bits() < 32 {
+                    self.emit(Instruction::I32Const(32 - ty.bits() as i32));
+                    self.emit(Instruction::I32Sub);
+                }
+            }
+            Opcode::Ctz if ty.is_int() => {
+                self.get(arg)?;
+                if ty.bits() < 32 {
+                    // Stop counting at the width of the type.
+                    self.emit(Instruction::I32Const(1 << ty.bits()));
+                    self.emit(Instruction::I32Or);
+                }
+                self.emit(pick!(is64, I32Ctz, I64Ctz));
+            }
+            Opcode::Popcnt if ty.is_int() => {
+                self.get(arg)?;
+                self.emit(pick!(is64, I32Popcnt, I64Popcnt));
+            }
+            Opcode::Uextend => {
+                self.get(arg)?;
+                if is64 {
+                    self.emit(Instruction::I64ExtendI32U);
+                }
+            }
+            Opcode::Sextend => {
+                self.get_signed(arg)?;
+                if is64 {
+                    self.emit(Instruction::I64ExtendI32S);
+                } else {
+                    self.narrow(ty);
+                }
+            }
+            Opcode::Ireduce => {
+                self.get(arg)?;
+                if from64 {
+                    self.emit(Instruction::I32WrapI64);
+                }
+                self.narrow(ty);
+            }
+            Opcode::Bitcast => {
+                self.get(arg)?;
+                match (from, ty) {
+                    (types::I32, types::F32) => self.emit(Instruction::F32ReinterpretI32),
+                    (types::F32, types::I32) => self.emit(Instruction::I32ReinterpretF32),
+                    (types::I64, types::F64) => self.emit(Instruction::F64ReinterpretI64),
+                    (types::F64, types::I64) => self.emit(Instruction::I64ReinterpretF64),
+                    _ if from == ty => {}
+                    _ => return Err(unsupported(format_args!("bitcast from {from} to {ty}"))),
+                }
+            }
+            Opcode::Sqrt
+            | Opcode::Fabs
+            | Opcode::Fneg
+            | Opcode::Ceil
+            | Opcode::Floor
+            | Opcode::Trunc
+            | Opcode::Nearest
+                if ty.is_float() =>
+            {
+                self.get(arg)?;
+                self.emit(match opcode {
+                    Opcode::Sqrt => pick!(is64, F32Sqrt, F64Sqrt),
+                    Opcode::Fabs => pick!(is64, F32Abs, F64Abs),
+                    Opcode::Fneg => pick!(is64, F32Neg, F64Neg),
+                    Opcode::Ceil => pick!(is64, F32Ceil, F64Ceil),
+                    Opcode::Floor => pick!(is64, F32Floor, F64Floor),
+                    Opcode::Trunc => pick!(is64, F32Trunc, F64Trunc),
+                    _ => pick!(is64, F32Nearest, F64Nearest),
+                });
+            }
+            Opcode::Fpromote => {
+                self.get(arg)?;
+                self.emit(Instruction::F64PromoteF32);
+            }
+            Opcode::Fdemote => {
+                self.get(arg)?;
+                self.emit(Instruction::F32DemoteF64);
+            }
+            Opcode::FcvtToSint
+            | Opcode::FcvtToUint
+            | Opcode::FcvtToSintSat
+            | Opcode::FcvtToUintSat
+                if ty == types::I32 || ty == types::I64 =>
+            {
+                use Instruction::*;
+                self.get(arg)?;
+                self.emit(match (opcode, is64, from64) {
+                    (Opcode::FcvtToSint, false, false) => I32TruncF32S,
+                    (Opcode::FcvtToSint, false, true) => I32TruncF64S,
+                    (Opcode::FcvtToSint, true, false) => I64TruncF32S,
+                    (Opcode::FcvtToSint, true, true) => I64TruncF64S,
+                    (Opcode::FcvtToUint, false, false) => I32TruncF32U,
+                    (Opcode::FcvtToUint, false, true) => I32TruncF64U,
+                    (Opcode::FcvtToUint, true, false) => I64TruncF32U,
+                    (Opcode::FcvtToUint, true, true) => I64TruncF64U,
+                    (Opcode::FcvtToSintSat, false, false) => I32TruncSatF32S,
+                    (Opcode::FcvtToSintSat, false, true) => I32TruncSatF64S,
+                    (Opcode::FcvtToSintSat, true, false) => I64TruncSatF32S,
+                    (Opcode::FcvtToSintSat, true, true) => I64TruncSatF64S,
+                    (Opcode::FcvtToUintSat, false, false) => I32TruncSatF32U,
+                    (Opcode::FcvtToUintSat, false, true) => I32TruncSatF64U,
+                    (Opcode::FcvtToUintSat, true, false) => I64TruncSatF32U,
+                    (_, _, _) => I64TruncSatF64U,
+                });
+            }
+            Opcode::FcvtFromSint | Opcode::FcvtFromUint if ty.is_float() && from.is_int() => {
+                use Instruction::*;
+                let signed = opcode == Opcode::FcvtFromSint;
+                if signed {
+                    self.get_signed(arg)?;
+                } else {
+                    self.get(arg)?;
+                }
+                self.emit(match (signed, is64, from64) {
+                    (true, false, false) => F32ConvertI32S,
+                    (true, false, true) => F32ConvertI64S,
+                    (true, true, false) => F64ConvertI32S,
+                    (true, true, true) => F64ConvertI64S,
+                    (false, false, false) => F32ConvertI32U,
+                    (false, false, true) => F32ConvertI64U,
+                    (false, true, false) => F64ConvertI32U,
+                    (false, true, true) => F64ConvertI64U,
+                });
+            }
+            _ => return Err(unsupported(format_args!("{opcode}.{ty}"))),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::*;
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_codegen::{CodegenError, Context};
+use cranelift_frontend::*;
+use cranelift_module::*;
+use cranelift_wasm_emit::*;
+
+fn module(triple: &str) -> WasmModule {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("opt_level", "speed").unwrap();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name(triple).unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    WasmModule::new(WasmBuilder::new(isa, default_libcall_names()).unwrap())
+}
+
+fn signature(params: &[Type], returns: &[Type]) -> Signature {
+    Signature {
+        params: params.iter().map(|&ty| AbiParam::new(ty)).collect(),
+        returns: returns.iter().map(|&ty| AbiParam::new(ty)).collect(),
+        call_conv: CallConv::Tail,
+    }
+}
+
+/// Define the function `func_id`, building its body with `build`.
+fn define(
+    module: &mut WasmModule,
+    func_id: FuncId,
+    build: impl FnOnce(&mut WasmModule, &mut FunctionBuilder),
+) -> ModuleResult<()> {
+    let sig = module
+        .declarations()
+        .get_function_decl(func_id)
+        .signature
+        .clone();
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+    build(module, &mut bcx);
+    bcx.seal_all_blocks();
+    bcx.finalize();
+    module.define_function(func_id, &mut ctx)
+}
+
+/// Validate the finished module and return its text format.
+fn finish(module: WasmModule) -> String {
+    let wasm = module.finish().unwrap().emit();
+    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all());
+    if let Err(e) = validator.validate_all(&wasm) {
+        panic!(
+            "invalid module: {e}\n{}",
+            wasmprinter::print_bytes(&wasm).unwrap_or_default()
+        );
+    }
+    wasmprinter::print_bytes(&wasm).unwrap()
+}
+
+/// Define an exported function summing the integers below its argument.
+fn define_sum(module: &mut WasmModule, ty: Type) {
+    let func_id = module
+        .declare_function("sum", Linkage::Export, &signature(&[ty], &[ty]))
+        .unwrap();
+    define(module, func_id, |_, bcx| {
+        let entry = bcx.create_block();
+        let header = bcx.create_block();
+        let body = bcx.create_block();
+        let exit = bcx.create_block();
+        bcx.append_block_params_for_function_params(entry);
+        bcx.append_block_param(header, ty);
+        bcx.append_block_param(header, ty);
+        bcx.append_block_param(exit, ty);
+
+        bcx.switch_to_block(entry);
+        let n = bcx.block_params(entry)[0];
+        let zero = bcx.ins().iconst(ty, 0);
+        bcx.ins().jump(header, &[zero.into(), zero.into()]);
+
+        bcx.switch_to_block(header);
+        let i = bcx.block_params(header)[0];
+        let acc = bcx.block_params(header)[1];
+        let done = bcx.ins().icmp(IntCC::UnsignedGreaterThanOrEqual, i, n);
+        bcx.ins().brif(done, exit, &[acc.into()], body, &[]);
+
+        bcx.switch_to_block(body);
+        let acc = bcx.ins().iadd(acc, i);
+        let i = bcx.ins().iadd_imm(i, 1);
+        bcx.ins().jump(header, &[i.into(), acc.into()]);
+
+        bcx.switch_to_block(exit);
+        let result = bcx.block_params(exit)[0];
+        bcx.ins().return_(&[result]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn loop_memory32() {
+    let mut module = module("pulley32");
+    define_sum(&mut module, types::I32);
+    let text = finish(module);
+    assert!(text.contains("(export \"sum\" (func"), "{text}");
+    assert!(text.contains("(memory (;0;) 17)"), "{text}");
+    assert!(text.contains("loop"), "{text}");
+}
+
+#[test]
+fn loop_memory64() {
+    let mut module = module("pulley64");
+    define_sum(&mut module, types::I64);
+    let text = finish(module);
+    assert!(text.contains("(memory (;0;) i64 17)"), "{text}");
+    assert!(text.contains("loop"), "{text}");
+}
+
+#[test]
+fn small_integers() {
+    let mut module = module("pulley32");
+    let func_id = module
+        .declare_function(
+            "f",
+            Linkage::Export,
+            &signature(&[types::I8, types::I16], &[types::I8]),
+        )
+        .unwrap();
+    define(&mut module, func_id, |_, bcx| {
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let a = bcx.block_params(block)[0];
+        let b = bcx.block_params(block)[1];
+        let b = bcx.ins().ireduce(types::I8, b);
+        let sum = bcx.ins().iadd(a, b);
+        let quotient = bcx.ins().sdiv(sum, a);
+        let shifted = bcx.ins().sshr(quotient, b);
+        let rotated = bcx.ins().rotl(shifted, a);
+        let lt = bcx.ins().icmp(IntCC::SignedLessThan, rotated, a);
+        let lt = bcx.ins().uextend(types::I16, lt);
+        let lt = bcx.ins().ireduce(types::I8, lt);
+        let result = bcx.ins().smax(lt, rotated);
+        bcx.ins().return_(&[result]);
+    })
+    .unwrap();
+    let text = finish(module);
+    assert!(text.contains("i32.extend8_s"), "{text}");
+}
+
+#[test]
+fn switch() {
+    let mut module = module("pulley32");
+    let func_id = module
+        .declare_function(
+            "switch",
+            Linkage::Export,
+            &signature(&[types::I32], &[types::I32]),
+        )
+        .unwrap();
+    define(&mut module, func_id, |_, bcx| {
+        let entry = bcx.create_block();
+        let default = bcx.create_block();
+        let exit = bcx.create_block();
+        bcx.append_block_params_for_function_params(entry);
+        bcx.append_block_param(exit, types::I32);
+
+        bcx.switch_to_block(entry);
+        let index = bcx.block_params(entry)[0];
+        let mut switch = Switch::new();
+        let mut cases = Vec::new();
+        for i in 0..5 {
+            let case = bcx.create_block();
+            switch.set_entry(i, case);
+            cases.push(case);
+        }
+        switch.emit(bcx, index, default);
+
+        for (i, case) in cases.into_iter().enumerate() {
+            bcx.switch_to_block(case);
+            let value = bcx.ins().iconst(types::I32, i64::try_from(i * 10).unwrap());
+            bcx.ins().jump(exit, &[value.into()]);
+        }
+
+        bcx.switch_to_block(default);
+        let value = bcx.ins().iconst(types::I32, -1);
+        bcx.ins().jump(exit, &[value.into()]);
+
+        bcx.switch_to_block(exit);
+        let result = bcx.block_params(exit)[0];
+        bcx.ins().return_(&[result]);
+    })
+    .unwrap();
+    let text = finish(module);
+    assert!(text.contains("br_table"), "{text}");
+}
+
+#[test]
+fn irreducible() {
+    let mut module = module("pulley32");
+    let func_id = module
+        .declare_function(
+            "irreducible",
+            Linkage::Export,
+            &signature(&[types::I32, types::I32], &[types::I32]),
+        )
+        .unwrap();
+    define(&mut module, func_id, |_, bcx| {
+        // Both `a` and `b` can be entered from `entry`, and they branch to each other, so neither
+        // dominates the other.
+        let entry = bcx.create_block();
+        let a = bcx.create_block();
+        let b = bcx.create_block();
+        let exit = bcx.create_block();
+        bcx.append_block_params_for_function_params(entry);
+        bcx.append_block_param(a, types::I32);
+        bcx.append_block_param(b, types::I32);
+
+        bcx.switch_to_block(entry);
+        let cond = bcx.block_params(entry)[0];
+        let n = bcx.block_params(entry)[1];
+        bcx.ins().brif(cond, a, &[n.into()], b, &[n.into()]);
+
+        bcx.switch_to_block(a);
+        let x = bcx.block_params(a)[0];
+        let x = bcx.ins().iadd_imm(x, -1);
+        bcx.ins().brif(x, b, &[x.into()], exit, &[]);
+
+        bcx.switch_to_block(b);
+        let y = bcx.block_params(b)[0];
+        let y = bcx.ins().imul_imm(y, 3);
+        bcx.ins().jump(a, &[y.into()]);
+
+        bcx.switch_to_block(exit);
+        let zero = bcx.ins().iconst(types::I32, 0);
+        bcx.ins().return_(&[zero]);
+    })
+    .unwrap();
+    let text = finish(module);
+    assert!(text.contains("br_table"), "{text}");
+}
+
+#[test]
+fn calls_and_data() {
+    let mut module = module("pulley64");
+    let sig = signature(&[types::I64], &[types::I64]);
+    let imported = module
+        .declare_function("imported", Linkage::Import, &sig)
+        .unwrap();
+    let local = module
+        .declare_function("local", Linkage::Local, &sig)
+        .unwrap();
+    let exported = module
+        .declare_function("exported", Linkage::Export, &sig)
+        .unwrap();
+
+    let table = module
+        .declare_data("table", Linkage::Export, false, false)
+        .unwrap();
+    let mut data = DataDescription::new();
+    data.define(vec![0; 16].into_boxed_slice());
+    let func_ref = module.declare_func_in_data(local, &mut data);
+    data.write_function_addr(0, func_ref);
+    let gv = module.declare_data_in_data(table, &mut data);
+    data.write_data_addr(8, gv, 8);
+    module.define_data(table, &data).unwrap();
+
+    define(&mut module, local, |module, bcx| {
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let callee = module.declare_func_in_func(imported, bcx.func);
+        let call = bcx.ins().call(callee, &[x]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+    })
+    .unwrap();
+
+    define(&mut module, exported, |module, bcx| {
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let gv = module.declare_data_in_func(table, bcx.func);
+        let addr = bcx.ins().global_value(types::I64, gv);
+        let callee = bcx.ins().load(types::I64, MemFlags::trusted(), addr, 0);
+        let sig = bcx.import_signature(sig.clone());
+        bcx.ins().return_call_indirect(sig, callee, &[x]);
+    })
+    .unwrap();
+
+    let text = finish(module);
+    assert!(
+        text.contains("(import \"env\" \"imported\" (func (;0;)"),
+        "{text}"
+    );
+    assert!(text.contains("(export \"exported\" (func 2))"), "{text}");
+    assert!(text.contains("(export \"table\" (global 1))"), "{text}");
+    assert!(text.contains("return_call_indirect"), "{text}");
+    assert!(
+        text.contains("(elem (;0;) (i32.const 1) func 0 1 2)"),
+        "{text}"
+    );
+    // The first word is the table index of `local`, the second the address of the data object
+    // itself plus 8.
+    assert!(
+        text.contains(
+            r#"(data (;0;) (i64.const 16) "\02\00\00\00\00\00\00\00\18\00\00\00\00\00\00\00")"#
+        ),
+        "{text}"
+    );
+}
+
+#[test]
+fn stack_slots() {
+    let mut module = module("pulley32");
+    let func_id = module
+        .declare_function(
+            "f",
+            Linkage::Export,
+            &signature(&[types::I32], &[types::I32]),
+        )
+        .unwrap();
+    define(&mut module, func_id, |_, bcx| {
+        let slot =
+            bcx.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 8, 3));
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        bcx.ins().stack_store(x, slot, 4);
+        let addr = bcx.ins().stack_addr(types::I32, slot, 0);
+        let loaded = bcx.ins().load(types::I32, MemFlags::trusted(), addr, 4);
+        bcx.ins().return_(&[loaded]);
+    })
+    .unwrap();
+    let text = finish(module);
+    assert!(text.contains("global.set 0"), "{text}");
+}
+
+#[test]
+fn libcalls() {
+    let mut module = module("pulley32");
+    let func_id = module
+        .declare_function(
+            "f",
+            Linkage::Export,
+            &signature(&[types::F32], &[types::F32]),
+        )
+        .unwrap();
+    define(&mut module, func_id, |_, bcx| {
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let sig = bcx.import_signature(signature(&[types::F32], &[types::F32]));
+        let callee = bcx.import_function(ExtFuncData {
+            name: ExternalName::LibCall(LibCall::FloorF32),
+            signature: sig,
+            colocated: false,
+        });
+        let call = bcx.ins().call(callee, &[x]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+    })
+    .unwrap();
+    let text = finish(module);
+    assert!(text.contains("(import \"env\" \"floorf\""), "{text}");
+}
+
+#[test]
+fn unsupported() {
+    let mut module = module("pulley64");
+    let func_id = module
+        .declare_function(
+            "f",
+            Linkage::Export,
+            &signature(&[types::I32X4], &[types::I32X4]),
+        )
+        .unwrap();
+    let err = define(&mut module, func_id, |_, bcx| {
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        let x = bcx.block_params(block)[0];
+        let sum = bcx.ins().iadd(x, x);
+        bcx.ins().return_(&[sum]);
+    })
+    .unwrap_err();
+    match err {
+        ModuleError::Compilation(CodegenError::Unsupported(msg)) => {
+            assert!(msg.contains("not supported by the Wasm backend"), "{msg}")
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+#[test]
+fn duplicate_definition() {
+    let mut module = module("pulley32");
+    define_sum(&mut module, types::I32);
+    let func_id = module.get_name("sum").unwrap();
+    let FuncOrDataId::Func(func_id) = func_id else {
+        panic!()
+    };
+    let err = define(&mut module, func_id, |_, bcx| {
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        bcx.ins().trap(TrapCode::unwrap_user(1));
+    })
+    .unwrap_err();
+    assert!(
+        matches!(err, ModuleError::DuplicateDefinition(_)),
+        "{err:?}"
+    );
+}
@@ -188,10 +188,12 @@
 // clang-format off
 // IWYU pragma: begin_exports
 #include <wasmtime/config.h>
+#include <wasmtime/coredump.h>
 #include <wasmtime/engine.h>
 #include <wasmtime/error.h>
 #include <wasmtime/extern.h>
 #include <wasmtime/func.h>
+#include <wasmtime/gc.h>
 #include <wasmtime/global.h>
 #include <wasmtime/instance.h>
 #include <wasmtime/linker.h>
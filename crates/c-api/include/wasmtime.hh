@@ -31,15 +31,18 @@
 #define WASMTIME_HH
 
 #include <wasmtime/config.hh>
+#include <wasmtime/coredump.hh>
 #include <wasmtime/engine.hh>
 #include <wasmtime/error.hh>
 #include <wasmtime/extern.hh>
 #include <wasmtime/func.hh>
+#include <wasmtime/gc.hh>
 #include <wasmtime/global.hh>
 #include <wasmtime/instance.hh>
 #include <wasmtime/linker.hh>
 #include <wasmtime/memory.hh>
 #include <wasmtime/module.hh>
+#include <wasmtime/profiling.hh>
 #include <wasmtime/store.hh>
 #include <wasmtime/table.hh>
 #include <wasmtime/trap.hh>
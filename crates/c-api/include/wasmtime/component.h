@@ -1,3 +1,11 @@
+/**
+ * \file wasmtime/component.h
+ *
+ * Umbrella header for Wasmtime's component model C API. Note that of this
+ * API only functions have C++ wrappers so far, in `wasmtime/component/func.hh`,
+ * so C++ embedders otherwise use these C functions directly.
+ */
+
 #ifndef WASMTIME_COMPONENT_H
 #define WASMTIME_COMPONENT_H
 
@@ -5,6 +13,8 @@
 #include <wasmtime/component/func.h>
 #include <wasmtime/component/instance.h>
 #include <wasmtime/component/linker.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/component/types.h>
 #include <wasmtime/component/val.h>
 
 #endif // WASMTIME_COMPONENT_H
@@ -8,6 +8,10 @@
 #include <wasmtime/error.h>
 #include <wasmtime/store.h>
 
+#ifdef WASMTIME_FEATURE_ASYNC
+#include <wasmtime/async.h>
+#endif
+
 #ifdef WASMTIME_FEATURE_COMPONENT_MODEL
 
 #ifdef __cplusplus
@@ -60,6 +64,38 @@ WASM_API_EXTERN wasmtime_error_t *
 wasmtime_component_func_post_return(const wasmtime_component_func_t *func,
                                     wasmtime_context_t *context);
 
+#ifdef WASMTIME_FEATURE_ASYNC
+
+/**
+ * \brief Same as #wasmtime_component_func_call except for use with stores
+ * configured for async, invoking \p func asynchronously.
+ *
+ * This returns a #wasmtime_call_future_t which must be polled to completion
+ * with #wasmtime_call_future_poll and then deleted. All of the parameters to
+ * this function must be kept alive and unmodified until the future is deleted.
+ * Once the future completes, if \p error_ret is `NULL` then \p results have
+ * been filled in, otherwise \p error_ret is an error owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_call_future_t *wasmtime_component_func_call_async(
+    const wasmtime_component_func_t *func, wasmtime_context_t *context,
+    const wasmtime_component_val_t *args, size_t args_size,
+    wasmtime_component_val_t *results, size_t results_size,
+    wasmtime_error_t **error_ret);
+
+/**
+ * \brief Same as #wasmtime_component_func_post_return except for use after a
+ * #wasmtime_component_func_call_async.
+ *
+ * The returned future follows the same rules as the one returned from
+ * #wasmtime_component_func_call_async.
+ */
+WASM_API_EXTERN wasmtime_call_future_t *
+wasmtime_component_func_post_return_async(const wasmtime_component_func_t *func,
+                                          wasmtime_context_t *context,
+                                          wasmtime_error_t **error_ret);
+
+#endif // WASMTIME_FEATURE_ASYNC
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
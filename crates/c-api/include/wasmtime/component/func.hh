@@ -0,0 +1,184 @@
+/**
+ * \file wasmtime/component/func.hh
+ */
+
+#ifndef WASMTIME_COMPONENT_FUNC_HH
+#define WASMTIME_COMPONENT_FUNC_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#include <memory>
+#include <variant>
+#include <wasmtime/component/func.h>
+#include <wasmtime/error.hh>
+#include <wasmtime/span.hh>
+#include <wasmtime/store.hh>
+
+namespace wasmtime {
+namespace component {
+
+#ifdef WASMTIME_FEATURE_ASYNC
+
+/**
+ * \brief A call into WebAssembly which is executed asynchronously.
+ *
+ * This is returned by `Func::call_async` and `Func::post_return_async` and
+ * must be polled until it completes, at which point `result` returns the
+ * outcome of the call.
+ *
+ * For more information see the documentation of `wasmtime_call_future_t`.
+ */
+class [[nodiscard]] CallFuture {
+  friend class Func;
+
+  struct error_deleter {
+    void operator()(wasmtime_error_t **p) const {
+      if (*p != nullptr) {
+        wasmtime_error_delete(*p);
+      }
+      delete p; // NOLINT
+    }
+  };
+
+  struct deleter {
+    void operator()(wasmtime_call_future_t *p) const {
+      wasmtime_call_future_delete(p);
+    }
+  };
+
+  // The future writes to the error through a pointer, so it's boxed to keep
+  // its address stable when this is moved. It's declared first so that the
+  // future is deleted before it.
+  std::unique_ptr<wasmtime_error_t *, error_deleter> error;
+  std::unique_ptr<wasmtime_call_future_t, deleter> ptr;
+
+  CallFuture() : error(new wasmtime_error_t *(nullptr)) {}
+
+public:
+  /// \brief Executes WebAssembly until the call either completes, returning
+  /// `true`, or yields, returning `false`.
+  bool poll() { return wasmtime_call_future_poll(ptr.get()); }
+
+  /// \brief Returns the outcome of the call once `poll` has returned `true`.
+  Result<std::monostate> result() {
+    if (*error != nullptr) {
+      Error ret(*error);
+      *error = nullptr;
+      return ret;
+    }
+    return std::monostate();
+  }
+
+  /// \brief Polls this future until it completes and then returns its
+  /// `result`.
+  Result<std::monostate> wait() {
+    while (!poll()) {
+    }
+    return result();
+  }
+};
+
+#endif // WASMTIME_FEATURE_ASYNC
+
+/**
+ * \brief A function exported from a component instance.
+ *
+ * Like `wasmtime::Func` this is a handle into a `Store`, and it must only be
+ * used with the store it came from. Component values don't have C++ wrappers
+ * yet, so arguments and results are the C API's `wasmtime_component_val_t`.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/component/struct.Func.html
+ */
+class Func {
+  wasmtime_component_func_t func;
+
+public:
+  /// Creates a new function from the raw underlying C API representation.
+  Func(wasmtime_component_func_t func) : func(func) {}
+
+  /**
+   * \brief Invokes this function with the `args` given, writing its results
+   * to `results`.
+   *
+   * The number of `results` must match the number of results of this
+   * function. Once the results have been processed `post_return` must be
+   * called.
+   *
+   * https://docs.wasmtime.dev/api/wasmtime/component/struct.Func.html#method.call
+   */
+  Result<std::monostate> call(Store::Context cx,
+                              Span<const wasmtime_component_val_t> args,
+                              Span<wasmtime_component_val_t> results) const {
+    auto *error = wasmtime_component_func_call(&func, cx.raw_context(),
+                                               args.data(), args.size(),
+                                               results.data(), results.size());
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /**
+   * \brief Invokes the `post-return` canonical ABI option, which is required
+   * after each successful `call`.
+   *
+   * https://docs.wasmtime.dev/api/wasmtime/component/struct.Func.html#method.post_return
+   */
+  Result<std::monostate> post_return(Store::Context cx) const {
+    auto *error = wasmtime_component_func_post_return(&func, cx.raw_context());
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+#ifdef WASMTIME_FEATURE_ASYNC
+  /**
+   * \brief Same as `call` except for stores configured for async, returning a
+   * future which executes the call.
+   *
+   * This `Func` and the `results` must be kept alive, and no other functions
+   * may be called on the store, until the returned future is deleted. The
+   * `results` are written once the future completes successfully.
+   *
+   * https://docs.wasmtime.dev/api/wasmtime/component/struct.Func.html#method.call_async
+   */
+  CallFuture call_async(Store::Context cx,
+                        Span<const wasmtime_component_val_t> args,
+                        Span<wasmtime_component_val_t> results) const {
+    CallFuture future;
+    future.ptr.reset(wasmtime_component_func_call_async(
+        &func, cx.raw_context(), args.data(), args.size(), results.data(),
+        results.size(), future.error.get()));
+    return future;
+  }
+
+  /**
+   * \brief Same as `post_return` except for use after `call_async`.
+   *
+   * The returned future has the same requirements as the one returned from
+   * `call_async`.
+   *
+   * https://docs.wasmtime.dev/api/wasmtime/component/struct.Func.html#method.post_return_async
+   */
+  CallFuture post_return_async(Store::Context cx) const {
+    CallFuture future;
+    future.ptr.reset(wasmtime_component_func_post_return_async(
+        &func, cx.raw_context(), future.error.get()));
+    return future;
+  }
+#endif // WASMTIME_FEATURE_ASYNC
+
+  /// Returns the raw underlying C API function this is using.
+  const wasmtime_component_func_t &capi() const { return func; }
+};
+
+} // namespace component
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_FUNC_HH
@@ -6,10 +6,15 @@
 #include <wasm.h>
 #include <wasmtime/component/component.h>
 #include <wasmtime/component/instance.h>
+#include <wasmtime/component/resource.h>
 #include <wasmtime/conf.h>
 #include <wasmtime/error.h>
 #include <wasmtime/store.h>
 
+#ifdef WASMTIME_FEATURE_ASYNC
+#include <wasmtime/async.h>
+#endif
+
 #ifdef WASMTIME_FEATURE_COMPONENT_MODEL
 
 #ifdef __cplusplus
@@ -64,6 +69,26 @@ WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instantiate(
     const wasmtime_component_t *component,
     wasmtime_component_instance_t *instance_out);
 
+#ifdef WASMTIME_FEATURE_ASYNC
+
+/**
+ * \brief Same as #wasmtime_component_linker_instantiate except for use with
+ * stores configured for async, instantiating \p component asynchronously.
+ *
+ * This returns a #wasmtime_call_future_t which must be polled to completion
+ * with #wasmtime_call_future_poll and then deleted. All of the parameters to
+ * this function must be kept alive and unmodified until the future is deleted.
+ * Once the future completes, if \p error_ret is `NULL` then \p instance_out
+ * has been filled in, otherwise \p error_ret is an error owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_call_future_t *
+wasmtime_component_linker_instantiate_async(
+    const wasmtime_component_linker_t *linker, wasmtime_context_t *context,
+    const wasmtime_component_t *component,
+    wasmtime_component_instance_t *instance_out, wasmtime_error_t **error_ret);
+
+#endif // WASMTIME_FEATURE_ASYNC
+
 /**
  * \brief Deletes a #wasmtime_component_linker_t created by
  * #wasmtime_component_linker_new
@@ -136,6 +161,34 @@ WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instance_add_func(
     size_t name_len, wasmtime_component_func_callback_t callback, void *data,
     void (*finalizer)());
 
+/// Type of the destructor used in
+/// #wasmtime_component_linker_instance_add_resource
+typedef wasmtime_error_t *(*wasmtime_component_resource_destructor_t)(
+    void *, wasmtime_context_t *, uint32_t);
+
+/**
+ * \brief Define a resource type within this instance.
+ *
+ * The \p destructor is invoked with the `rep` of an owned resource of type
+ * \p ty when a component drops it.
+ *
+ * \param linker_instance the instance to define the resource in
+ * \param name the resource name
+ * \param name_len length of \p name in bytes
+ * \param ty the resource type, typically created with
+ *        #wasmtime_component_resource_type_new_host
+ * \param destructor the destructor for resources of this type, can be `NULL`
+ * \param data host-specific data passed to the destructor, can be `NULL`
+ * \param finalizer optional finalizer for \p data, can be `NULL`
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_instance_add_resource(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len, const wasmtime_component_resource_type_t *ty,
+    wasmtime_component_resource_destructor_t destructor, void *data,
+    void (*finalizer)(void *));
+
 #ifdef WASMTIME_FEATURE_WASI
 
 /**
@@ -0,0 +1,205 @@
+/// \file wasmtime/component/resource.h
+
+#ifndef WASMTIME_COMPONENT_RESOURCE_H
+#define WASMTIME_COMPONENT_RESOURCE_H
+
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_ASYNC
+#include <wasmtime/async.h>
+#endif
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \brief The type of a resource in the component model.
+ *
+ * Resource types are either defined by the host with
+ * #wasmtime_component_resource_type_new_host or defined by a guest component.
+ * Types can be compared with #wasmtime_component_resource_type_equal.
+ */
+typedef struct wasmtime_component_resource_type_t
+    wasmtime_component_resource_type_t;
+
+/**
+ * \brief Creates a new host-defined resource type.
+ *
+ * Host resource types are identified by \p ty, so two types created with the
+ * same \p ty are the same type. The returned type can be used with
+ * #wasmtime_component_linker_instance_add_resource to define it in a linker.
+ *
+ * The returned value is owned by the caller and must be deleted with
+ * #wasmtime_component_resource_type_delete.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_new_host(uint32_t ty);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_clone(
+    const wasmtime_component_resource_type_t *ty);
+
+/// \brief Returns whether \p a and \p b are the same resource type.
+WASM_API_EXTERN bool wasmtime_component_resource_type_equal(
+    const wasmtime_component_resource_type_t *a,
+    const wasmtime_component_resource_type_t *b);
+
+/// \brief Deletes a #wasmtime_component_resource_type_t.
+WASM_API_EXTERN void wasmtime_component_resource_type_delete(
+    wasmtime_component_resource_type_t *ty);
+
+/**
+ * \brief A handle to a resource of any type, owned or borrowed.
+ *
+ * This is the representation of resources within #wasmtime_component_val_t
+ * and is how resources are passed to and from component functions. The
+ * handle refers to an entry in a store's resource table, so it's only valid
+ * for the store that it came from.
+ *
+ * Deleting this value with #wasmtime_component_resource_any_delete frees the
+ * handle itself but does not drop the resource within the store. Owned
+ * resources must be passed back to a component or dropped with
+ * #wasmtime_component_resource_any_drop.
+ */
+typedef struct wasmtime_component_resource_any_t
+    wasmtime_component_resource_any_t;
+
+/**
+ * \brief Returns the type of \p resource.
+ *
+ * The returned value is owned by the caller and must be deleted with
+ * #wasmtime_component_resource_type_delete.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_any_type(
+    const wasmtime_component_resource_any_t *resource);
+
+/// \brief Returns whether \p resource is an owned resource, as opposed to a
+/// borrowed one.
+WASM_API_EXTERN bool wasmtime_component_resource_any_owned(
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Drops \p resource within \p context, running its destructor if it's
+ * owned.
+ *
+ * \return `NULL` on success, otherwise an error such as when the resource has
+ * already been dropped or its destructor failed.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_drop(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource);
+
+#ifdef WASMTIME_FEATURE_ASYNC
+
+/**
+ * \brief Same as #wasmtime_component_resource_any_drop except for use with
+ * stores configured for async, running the destructor asynchronously.
+ *
+ * The returned future must be polled to completion with
+ * #wasmtime_call_future_poll. On failure an error is written to \p error_ret.
+ */
+WASM_API_EXTERN wasmtime_call_future_t *
+wasmtime_component_resource_any_drop_async(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource,
+    wasmtime_error_t **error_ret);
+
+#endif // WASMTIME_FEATURE_ASYNC
+
+/// \brief Creates a new copy of the handle \p resource owned by the caller.
+///
+/// This does not duplicate the resource itself, only the handle to it.
+WASM_API_EXTERN wasmtime_component_resource_any_t *
+wasmtime_component_resource_any_clone(
+    const wasmtime_component_resource_any_t *resource);
+
+/// \brief Deletes a #wasmtime_component_resource_any_t.
+WASM_API_EXTERN void wasmtime_component_resource_any_delete(
+    wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief A resource defined by the host.
+ *
+ * Host resources are identified by a 32-bit `rep` chosen by the host along
+ * with the `ty` of their #wasmtime_component_resource_type_new_host type. They
+ * are converted to and from #wasmtime_component_resource_any_t, which is how
+ * they're passed to components, with #wasmtime_component_resource_host_to_any
+ * and #wasmtime_component_resource_any_to_host.
+ */
+typedef struct wasmtime_component_resource_host_t
+    wasmtime_component_resource_host_t;
+
+/**
+ * \brief Creates a new host resource.
+ *
+ * \param owned whether this is an owned resource or a borrowed one
+ * \param rep the host-defined representation of this resource
+ * \param ty the host-defined type of this resource, matching the argument to
+ *        #wasmtime_component_resource_type_new_host
+ */
+WASM_API_EXTERN wasmtime_component_resource_host_t *
+wasmtime_component_resource_host_new(bool owned, uint32_t rep, uint32_t ty);
+
+/// \brief Returns the `rep` that \p resource was created with.
+WASM_API_EXTERN uint32_t wasmtime_component_resource_host_rep(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Returns the `ty` that \p resource was created with.
+WASM_API_EXTERN uint32_t wasmtime_component_resource_host_type(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Returns whether \p resource is an owned resource.
+WASM_API_EXTERN bool wasmtime_component_resource_host_owned(
+    const wasmtime_component_resource_host_t *resource);
+
+/**
+ * \brief Converts \p resource into a #wasmtime_component_resource_any_t
+ * within \p context.
+ *
+ * On success \p resource_out is filled in with a handle owned by the caller.
+ *
+ * \return `NULL` on success, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_host_to_any(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_host_t *resource,
+    wasmtime_component_resource_any_t **resource_out);
+
+/**
+ * \brief Converts \p resource into a #wasmtime_component_resource_host_t.
+ *
+ * This fails if \p resource isn't a host resource created with
+ * #wasmtime_component_resource_type_new_host. Owned resources are removed from
+ * \p context, so they're not dropped when the store is dropped and their
+ * destructors don't run.
+ *
+ * \return `NULL` on success, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_to_host(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource,
+    wasmtime_component_resource_host_t **resource_out);
+
+/// \brief Creates a new copy of \p resource owned by the caller.
+WASM_API_EXTERN wasmtime_component_resource_host_t *
+wasmtime_component_resource_host_clone(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Deletes a #wasmtime_component_resource_host_t.
+WASM_API_EXTERN void wasmtime_component_resource_host_delete(
+    wasmtime_component_resource_host_t *resource);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_RESOURCE_H
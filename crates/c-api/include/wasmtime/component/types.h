@@ -0,0 +1,440 @@
+/// \file wasmtime/component/types.h
+
+#ifndef WASMTIME_COMPONENT_TYPES_H
+#define WASMTIME_COMPONENT_TYPES_H
+
+#include <wasm.h>
+#include <wasmtime/component/component.h>
+#include <wasmtime/component/func.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \brief The type of a value in the component model.
+ *
+ * Values of this type are owned by the caller of the function that returned
+ * them and must be deleted with #wasmtime_component_valtype_delete. Names
+ * returned from accessors of this type are borrowed from the type and are
+ * valid for as long as the type is alive.
+ */
+typedef struct wasmtime_component_valtype_t wasmtime_component_valtype_t;
+
+/// \brief Discriminant returned by #wasmtime_component_valtype_kind
+typedef uint8_t wasmtime_component_valtype_kind_t;
+
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a bool
+#define WASMTIME_COMPONENT_TYPE_BOOL 0
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s8
+#define WASMTIME_COMPONENT_TYPE_S8 1
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u8
+#define WASMTIME_COMPONENT_TYPE_U8 2
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s16
+#define WASMTIME_COMPONENT_TYPE_S16 3
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u16
+#define WASMTIME_COMPONENT_TYPE_U16 4
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s32
+#define WASMTIME_COMPONENT_TYPE_S32 5
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u32
+#define WASMTIME_COMPONENT_TYPE_U32 6
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s64
+#define WASMTIME_COMPONENT_TYPE_S64 7
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u64
+#define WASMTIME_COMPONENT_TYPE_U64 8
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a f32
+#define WASMTIME_COMPONENT_TYPE_F32 9
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a f64
+#define WASMTIME_COMPONENT_TYPE_F64 10
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a char
+#define WASMTIME_COMPONENT_TYPE_CHAR 11
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a string
+#define WASMTIME_COMPONENT_TYPE_STRING 12
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a list
+#define WASMTIME_COMPONENT_TYPE_LIST 13
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a record
+#define WASMTIME_COMPONENT_TYPE_RECORD 14
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a tuple
+#define WASMTIME_COMPONENT_TYPE_TUPLE 15
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a variant
+#define WASMTIME_COMPONENT_TYPE_VARIANT 16
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an enum
+#define WASMTIME_COMPONENT_TYPE_ENUM 17
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an option
+#define WASMTIME_COMPONENT_TYPE_OPTION 18
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a result
+#define WASMTIME_COMPONENT_TYPE_RESULT 19
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is flags
+#define WASMTIME_COMPONENT_TYPE_FLAGS 20
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an owned resource
+#define WASMTIME_COMPONENT_TYPE_OWN 21
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a borrowed resource
+#define WASMTIME_COMPONENT_TYPE_BORROW 22
+
+/// \brief Returns the kind of \p ty.
+WASM_API_EXTERN wasmtime_component_valtype_kind_t
+wasmtime_component_valtype_kind(const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns whether \p a and \p b are the same type.
+WASM_API_EXTERN bool
+wasmtime_component_valtype_equal(const wasmtime_component_valtype_t *a,
+                                 const wasmtime_component_valtype_t *b);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_clone(const wasmtime_component_valtype_t *ty);
+
+/// \brief Deletes a #wasmtime_component_valtype_t.
+WASM_API_EXTERN void
+wasmtime_component_valtype_delete(wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the element type of a list, or `NULL` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_LIST.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_list_element(
+    const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the number of fields in a record, or 0 if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_RECORD.
+WASM_API_EXTERN size_t wasmtime_component_valtype_record_field_count(
+    const wasmtime_component_valtype_t *ty);
+
+/**
+ * \brief Returns the name and type of the field at \p index of a record.
+ *
+ * \return `true` if \p ty is a #WASMTIME_COMPONENT_TYPE_RECORD and \p index is
+ *         in bounds, in which case the out-parameters are filled in.
+ */
+WASM_API_EXTERN bool wasmtime_component_valtype_record_field_nth(
+    const wasmtime_component_valtype_t *ty, size_t index, const char **name,
+    size_t *name_len, wasmtime_component_valtype_t **ty_out);
+
+/// \brief Returns the number of types in a tuple, or 0 if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_TUPLE.
+WASM_API_EXTERN size_t wasmtime_component_valtype_tuple_type_count(
+    const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the type at \p index of a tuple, or `NULL` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_TUPLE or \p index is out of bounds.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_tuple_type_nth(
+    const wasmtime_component_valtype_t *ty, size_t index);
+
+/// \brief Returns the number of cases in a variant, or 0 if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_VARIANT.
+WASM_API_EXTERN size_t wasmtime_component_valtype_variant_case_count(
+    const wasmtime_component_valtype_t *ty);
+
+/**
+ * \brief Returns the name and payload type of the case at \p index of a
+ * variant.
+ *
+ * \p payload_out is set to `NULL` if the case has no payload.
+ *
+ * \return `true` if \p ty is a #WASMTIME_COMPONENT_TYPE_VARIANT and \p index
+ *         is in bounds, in which case the out-parameters are filled in.
+ */
+WASM_API_EXTERN bool wasmtime_component_valtype_variant_case_nth(
+    const wasmtime_component_valtype_t *ty, size_t index, const char **name,
+    size_t *name_len, wasmtime_component_valtype_t **payload_out);
+
+/// \brief Returns the number of names in an enum, or 0 if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_ENUM.
+WASM_API_EXTERN size_t wasmtime_component_valtype_enum_name_count(
+    const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the name at \p index of an enum, or `false` if \p ty isn't
+/// a #WASMTIME_COMPONENT_TYPE_ENUM or \p index is out of bounds.
+WASM_API_EXTERN bool wasmtime_component_valtype_enum_name_nth(
+    const wasmtime_component_valtype_t *ty, size_t index, const char **name,
+    size_t *name_len);
+
+/// \brief Returns the number of names in flags, or 0 if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_FLAGS.
+WASM_API_EXTERN size_t wasmtime_component_valtype_flags_name_count(
+    const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the name at \p index of flags, or `false` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_FLAGS or \p index is out of bounds.
+WASM_API_EXTERN bool wasmtime_component_valtype_flags_name_nth(
+    const wasmtime_component_valtype_t *ty, size_t index, const char **name,
+    size_t *name_len);
+
+/// \brief Returns the payload type of an option, or `NULL` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_OPTION.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_option_payload(
+    const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the `ok` type of a result, or `NULL` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_RESULT or the result has no `ok` type.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_result_ok(const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the `err` type of a result, or `NULL` if \p ty isn't a
+/// #WASMTIME_COMPONENT_TYPE_RESULT or the result has no `err` type.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_valtype_result_err(const wasmtime_component_valtype_t *ty);
+
+/// \brief Returns the resource type of an `own` or `borrow` handle, or `NULL`
+/// if \p ty is neither #WASMTIME_COMPONENT_TYPE_OWN nor
+/// #WASMTIME_COMPONENT_TYPE_BORROW.
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_valtype_resource_type(
+    const wasmtime_component_valtype_t *ty);
+
+/**
+ * \brief The type of a component function.
+ *
+ * Values of this type are owned by the caller of the function that returned
+ * them and must be deleted with #wasmtime_component_func_type_delete.
+ */
+typedef struct wasmtime_component_func_type_t wasmtime_component_func_type_t;
+
+/// \brief Returns the type of \p func.
+WASM_API_EXTERN wasmtime_component_func_type_t *
+wasmtime_component_func_type(const wasmtime_component_func_t *func,
+                             wasmtime_context_t *context);
+
+/// \brief Returns the number of parameters of \p ty.
+WASM_API_EXTERN size_t wasmtime_component_func_type_param_count(
+    const wasmtime_component_func_type_t *ty);
+
+/**
+ * \brief Returns the name and type of the parameter at \p index of \p ty.
+ *
+ * \return `true` if \p index is in bounds, in which case the out-parameters
+ *         are filled in.
+ */
+WASM_API_EXTERN bool wasmtime_component_func_type_param_nth(
+    const wasmtime_component_func_type_t *ty, size_t index, const char **name,
+    size_t *name_len, wasmtime_component_valtype_t **ty_out);
+
+/// \brief Returns the number of results of \p ty.
+WASM_API_EXTERN size_t wasmtime_component_func_type_result_count(
+    const wasmtime_component_func_type_t *ty);
+
+/// \brief Returns the type of the result at \p index of \p ty, or `NULL` if
+/// \p index is out of bounds.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_func_type_result_nth(
+    const wasmtime_component_func_type_t *ty, size_t index);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_component_func_type_t *
+wasmtime_component_func_type_clone(const wasmtime_component_func_type_t *ty);
+
+/// \brief Deletes a #wasmtime_component_func_type_t.
+WASM_API_EXTERN void
+wasmtime_component_func_type_delete(wasmtime_component_func_type_t *ty);
+
+/// \brief The type of a component, as returned by #wasmtime_component_type.
+typedef struct wasmtime_component_type_t wasmtime_component_type_t;
+
+/// \brief The type of a component instance.
+typedef struct wasmtime_component_instance_type_t
+    wasmtime_component_instance_type_t;
+
+/**
+ * \brief The type of an item imported or exported by a component.
+ *
+ * The kind of the item is returned by #wasmtime_component_item_kind and the
+ * type itself by the accessor for that kind, each of which returns `NULL` or
+ * `false` if the item is a different kind.
+ */
+typedef struct wasmtime_component_item_t wasmtime_component_item_t;
+
+/// \brief Discriminant returned by #wasmtime_component_item_kind
+typedef uint8_t wasmtime_component_item_kind_t;
+
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component function
+#define WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC 0
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a core wasm function
+#define WASMTIME_COMPONENT_ITEM_CORE_FUNC 1
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a core wasm module
+#define WASMTIME_COMPONENT_ITEM_MODULE 2
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component
+#define WASMTIME_COMPONENT_ITEM_COMPONENT 3
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component instance
+#define WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE 4
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component value type
+#define WASMTIME_COMPONENT_ITEM_TYPE 5
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a resource type
+#define WASMTIME_COMPONENT_ITEM_RESOURCE 6
+
+/// \brief Returns the kind of \p item.
+WASM_API_EXTERN wasmtime_component_item_kind_t
+wasmtime_component_item_kind(const wasmtime_component_item_t *item);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC.
+WASM_API_EXTERN wasmtime_component_func_type_t *
+wasmtime_component_item_component_func(const wasmtime_component_item_t *item);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_CORE_FUNC.
+WASM_API_EXTERN wasm_functype_t *
+wasmtime_component_item_core_func(const wasmtime_component_item_t *item);
+
+/// \brief Fills in \p out with the imports of a
+/// #WASMTIME_COMPONENT_ITEM_MODULE.
+WASM_API_EXTERN bool
+wasmtime_component_item_module_imports(const wasmtime_component_item_t *item,
+                                       wasm_importtype_vec_t *out);
+
+/// \brief Fills in \p out with the exports of a
+/// #WASMTIME_COMPONENT_ITEM_MODULE.
+WASM_API_EXTERN bool
+wasmtime_component_item_module_exports(const wasmtime_component_item_t *item,
+                                       wasm_exporttype_vec_t *out);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_COMPONENT.
+WASM_API_EXTERN wasmtime_component_type_t *
+wasmtime_component_item_component(const wasmtime_component_item_t *item);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE.
+WASM_API_EXTERN wasmtime_component_instance_type_t *
+wasmtime_component_item_component_instance(
+    const wasmtime_component_item_t *item);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_TYPE.
+WASM_API_EXTERN wasmtime_component_valtype_t *
+wasmtime_component_item_type(const wasmtime_component_item_t *item);
+
+/// \brief Returns the type of a #WASMTIME_COMPONENT_ITEM_RESOURCE.
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_item_resource(const wasmtime_component_item_t *item);
+
+/// \brief Creates a new copy of \p item owned by the caller.
+WASM_API_EXTERN wasmtime_component_item_t *
+wasmtime_component_item_clone(const wasmtime_component_item_t *item);
+
+/// \brief Deletes a #wasmtime_component_item_t.
+WASM_API_EXTERN void
+wasmtime_component_item_delete(wasmtime_component_item_t *item);
+
+/**
+ * \brief Returns the type of \p component, describing its imports and
+ * exports.
+ *
+ * The returned value is owned by the caller and must be deleted with
+ * #wasmtime_component_type_delete.
+ */
+WASM_API_EXTERN wasmtime_component_type_t *
+wasmtime_component_type(const wasmtime_component_t *component);
+
+/// \brief Returns the number of imports of \p ty.
+WASM_API_EXTERN size_t
+wasmtime_component_type_import_count(const wasmtime_component_type_t *ty);
+
+/// \brief Returns the type of the import named \p name, or `NULL` if there's
+/// no such import.
+WASM_API_EXTERN wasmtime_component_item_t *
+wasmtime_component_type_import_get(const wasmtime_component_type_t *ty,
+                                   const char *name, size_t name_len);
+
+/**
+ * \brief Returns the name and type of the import at \p index.
+ *
+ * The name is borrowed from \p ty and the item is owned by the caller.
+ *
+ * \return `true` if \p index is in bounds, in which case the out-parameters
+ *         are filled in.
+ */
+WASM_API_EXTERN bool
+wasmtime_component_type_import_nth(const wasmtime_component_type_t *ty,
+                                   size_t index, const char **name,
+                                   size_t *name_len,
+                                   wasmtime_component_item_t **item_out);
+
+/// \brief Returns the number of exports of \p ty.
+WASM_API_EXTERN size_t
+wasmtime_component_type_export_count(const wasmtime_component_type_t *ty);
+
+/// \brief Returns the type of the export named \p name, or `NULL` if there's
+/// no such export.
+WASM_API_EXTERN wasmtime_component_item_t *
+wasmtime_component_type_export_get(const wasmtime_component_type_t *ty,
+                                   const char *name, size_t name_len);
+
+/// \brief Same as #wasmtime_component_type_import_nth, but for exports.
+WASM_API_EXTERN bool
+wasmtime_component_type_export_nth(const wasmtime_component_type_t *ty,
+                                   size_t index, const char **name,
+                                   size_t *name_len,
+                                   wasmtime_component_item_t **item_out);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_component_type_t *
+wasmtime_component_type_clone(const wasmtime_component_type_t *ty);
+
+/// \brief Deletes a #wasmtime_component_type_t.
+WASM_API_EXTERN void
+wasmtime_component_type_delete(wasmtime_component_type_t *ty);
+
+/// \brief Returns the number of exports of \p ty.
+WASM_API_EXTERN size_t wasmtime_component_instance_type_export_count(
+    const wasmtime_component_instance_type_t *ty);
+
+/// \brief Returns the type of the export named \p name, or `NULL` if there's
+/// no such export.
+WASM_API_EXTERN wasmtime_component_item_t *
+wasmtime_component_instance_type_export_get(
+    const wasmtime_component_instance_type_t *ty, const char *name,
+    size_t name_len);
+
+/// \brief Same as #wasmtime_component_type_export_nth, but for a component
+/// instance.
+WASM_API_EXTERN bool wasmtime_component_instance_type_export_nth(
+    const wasmtime_component_instance_type_t *ty, size_t index,
+    const char **name, size_t *name_len, wasmtime_component_item_t **item_out);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_component_instance_type_t *
+wasmtime_component_instance_type_clone(
+    const wasmtime_component_instance_type_t *ty);
+
+/// \brief Deletes a #wasmtime_component_instance_type_t.
+WASM_API_EXTERN void wasmtime_component_instance_type_delete(
+    wasmtime_component_instance_type_t *ty);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_TYPES_H
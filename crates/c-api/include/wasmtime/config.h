@@ -444,6 +444,23 @@ WASM_API_EXTERN void wasmtime_config_cranelift_flag_set(wasm_config_t *,
  */
 WASMTIME_CONFIG_PROP(void, macos_use_mach_ports, bool)
 
+#ifdef WASMTIME_FEATURE_COREDUMP
+
+/**
+ * \brief Configures whether a WebAssembly core dump is attached to traps.
+ *
+ * When enabled, traps raised by WebAssembly carry a core dump which can be
+ * retrieved with #wasmtime_trap_coredump or #wasmtime_error_coredump.
+ *
+ * This setting is `false` by default.
+ *
+ * For more information see the Rust documentation at
+ * https://docs.wasmtime.dev/api/wasmtime/struct.Config.html#method.coredump_on_trap
+ */
+WASMTIME_CONFIG_PROP(void, coredump_on_trap, bool)
+
+#endif // WASMTIME_FEATURE_COREDUMP
+
 /**
  * Return the data from a LinearMemory instance.
  *
@@ -759,6 +776,60 @@ WASMTIME_POOLING_ALLOCATION_CONFIG_PROP(max_memory_size, size_t)
  */
 WASMTIME_POOLING_ALLOCATION_CONFIG_PROP(total_gc_heaps, uint32_t)
 
+#ifdef WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+
+/**
+ * \brief Whether or not memory protection keys are used by the pooling
+ * allocator.
+ *
+ * This is a `uint8_t` which holds one of the values from
+ * #wasmtime_mpk_enabled_enum.
+ */
+typedef uint8_t wasmtime_mpk_enabled_t;
+
+/**
+ * \brief Different ways the pooling allocator can use memory protection keys.
+ *
+ * See #wasmtime_pooling_allocation_config_memory_protection_keys_set.
+ */
+enum wasmtime_mpk_enabled_enum { // MpkEnabled
+  /// Use memory protection keys if they're supported by the host.
+  WASMTIME_MPK_ENABLED_AUTO,
+  /// Use memory protection keys, failing if they're not available.
+  WASMTIME_MPK_ENABLED_ENABLE,
+  /// Never use memory protection keys.
+  WASMTIME_MPK_ENABLED_DISABLE,
+};
+
+/**
+ * \brief Configures whether memory protection keys are used to stripe linear
+ * memories in the pool.
+ *
+ * For more information see the Rust documentation at
+ * https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.memory_protection_keys.
+ */
+WASMTIME_POOLING_ALLOCATION_CONFIG_PROP(memory_protection_keys,
+                                        wasmtime_mpk_enabled_t)
+
+/**
+ * \brief The maximum number of memory protection keys to use.
+ *
+ * For more information see the Rust documentation at
+ * https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.max_memory_protection_keys.
+ */
+WASMTIME_POOLING_ALLOCATION_CONFIG_PROP(max_memory_protection_keys, size_t)
+
+/**
+ * \brief Returns whether memory protection keys are available on this host.
+ *
+ * For more information see the Rust documentation at
+ * https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.are_memory_protection_keys_available.
+ */
+WASM_API_EXTERN bool
+wasmtime_pooling_allocation_config_are_memory_protection_keys_available(void);
+
+#endif // WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+
 /**
  * \brief Sets the Wasmtime allocation strategy to use the pooling allocator. It
  * does not take ownership of the pooling allocation configuration object, which
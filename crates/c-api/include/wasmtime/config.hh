@@ -7,6 +7,9 @@
 
 #include <wasmtime/conf.h>
 #include <wasmtime/config.h>
+#ifdef WASMTIME_FEATURE_ASYNC
+#include <wasmtime/async.h>
+#endif
 #include <wasmtime/error.hh>
 #include <wasmtime/types/memory.hh>
 
@@ -42,6 +45,18 @@ enum class ProfilingStrategy {
   Perfmap = WASMTIME_PROFILING_STRATEGY_PERFMAP,
 };
 
+#ifdef WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+/// \brief Values passed to `PoolAllocationConfig::memory_protection_keys`
+enum class MpkEnabled {
+  /// Use memory protection keys if the host supports them
+  Auto = WASMTIME_MPK_ENABLED_AUTO,
+  /// Use memory protection keys, failing if they're unavailable
+  Enable = WASMTIME_MPK_ENABLED_ENABLE,
+  /// Never use memory protection keys
+  Disable = WASMTIME_MPK_ENABLED_DISABLE,
+};
+#endif // WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+
 #ifdef WASMTIME_FEATURE_POOLING_ALLOCATOR
 /**
  * \brief Pool allocation configuration for Wasmtime.
@@ -238,6 +253,33 @@ public:
   void total_gc_heaps(uint32_t count) {
     wasmtime_pooling_allocation_config_total_gc_heaps_set(ptr.get(), count);
   }
+
+#ifdef WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+  /// \brief Configures whether memory protection keys are used to stripe
+  /// linear memories.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.memory_protection_keys.
+  void memory_protection_keys(MpkEnabled enable) {
+    wasmtime_pooling_allocation_config_memory_protection_keys_set(
+        ptr.get(), static_cast<wasmtime_mpk_enabled_t>(enable));
+  }
+
+  /// \brief The maximum number of memory protection keys to use.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.max_memory_protection_keys.
+  void max_memory_protection_keys(size_t max) {
+    wasmtime_pooling_allocation_config_max_memory_protection_keys_set(
+        ptr.get(), max);
+  }
+
+  /// \brief Returns whether memory protection keys are available on this
+  /// host.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocationConfig.html#method.are_memory_protection_keys_available.
+  static bool are_memory_protection_keys_available() {
+    return wasmtime_pooling_allocation_config_are_memory_protection_keys_available();
+  }
+#endif // WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
 };
 #endif // WASMTIME_FEATURE_POOLING_ALLOCATOR
 
@@ -400,6 +442,16 @@ public:
   }
 #endif // WASMTIME_FEATURE_COMPONENT_MODEL
 
+#ifdef WASMTIME_FEATURE_ASYNC
+  /// \brief Configures whether WebAssembly code will be executed
+  /// asynchronously, which the `_async` functions require
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.Config.html#method.async_support
+  void async_support(bool enable) {
+    wasmtime_config_async_support_set(ptr.get(), enable);
+  }
+#endif // WASMTIME_FEATURE_ASYNC
+
 #ifdef WASMTIME_FEATURE_PARALLEL_COMPILATION
   /// \brief Configure whether wasmtime should compile a module using multiple
   /// threads.
@@ -515,6 +567,15 @@ public:
     wasmtime_config_macos_use_mach_ports_set(ptr.get(), enable);
   }
 
+#ifdef WASMTIME_FEATURE_COREDUMP
+  /// \brief Configures whether a core dump is attached to traps.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.Config.html#method.coredump_on_trap
+  void coredump_on_trap(bool enable) {
+    wasmtime_config_coredump_on_trap_set(ptr.get(), enable);
+  }
+#endif // WASMTIME_FEATURE_COREDUMP
+
 #ifdef WASMTIME_FEATURE_CACHE
   /// \brief Loads the default cache configuration present on the system.
   ///
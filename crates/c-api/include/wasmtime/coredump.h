@@ -0,0 +1,90 @@
+/**
+ * \file wasmtime/coredump.h
+ *
+ * \brief API for retrieving WebAssembly core dumps from traps
+ */
+
+#ifndef WASMTIME_COREDUMP_H
+#define WASMTIME_COREDUMP_H
+
+#include <wasm.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+#include <wasmtime/trap.h>
+
+#ifdef WASMTIME_FEATURE_COREDUMP
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \brief A core dump of the WebAssembly state at the time of a trap.
+ *
+ * Core dumps are only captured when #wasmtime_config_coredump_on_trap_set is
+ * enabled. A #wasmtime_coredump_t borrows from the trap or error it was
+ * retrieved from and must not outlive it. It must be deleted with
+ * #wasmtime_coredump_delete.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.WasmCoreDump.html
+ */
+typedef struct wasmtime_coredump wasmtime_coredump_t;
+
+/**
+ * \brief Deletes a core dump.
+ *
+ * This doesn't affect the trap or error it was retrieved from.
+ */
+WASM_API_EXTERN void wasmtime_coredump_delete(wasmtime_coredump_t *coredump);
+
+/**
+ * \brief Returns the core dump attached to a trap, if any.
+ *
+ * Returns `NULL` if the trap has no core dump attached.
+ */
+WASM_API_EXTERN wasmtime_coredump_t *
+wasmtime_trap_coredump(const wasm_trap_t *trap);
+
+/**
+ * \brief Returns the core dump attached to an error, if any.
+ *
+ * Returns `NULL` if the error has no core dump attached.
+ */
+WASM_API_EXTERN wasmtime_coredump_t *
+wasmtime_error_coredump(const wasmtime_error_t *error);
+
+/**
+ * \brief Returns the number of WebAssembly frames captured in the core dump.
+ */
+WASM_API_EXTERN size_t
+wasmtime_coredump_frames_count(const wasmtime_coredump_t *coredump);
+
+/**
+ * \brief Serializes the core dump into the standard core dump binary format.
+ *
+ * \param coredump the core dump being serialized
+ * \param store    the store the trap originated from
+ * \param name     the name recorded in the core dump, UTF-8 encoded
+ * \param name_len length of `name` in bytes
+ * \param out      where the serialized bytes, owned by the caller, are written
+ *
+ * \return Returns an error owned by the caller if `name` isn't valid UTF-8,
+ * `NULL` otherwise.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.WasmCoreDump.html#method.serialize
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_coredump_serialize(const wasmtime_coredump_t *coredump,
+                            wasmtime_context_t *store, const char *name,
+                            size_t name_len, wasm_byte_vec_t *out);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COREDUMP
+
+#endif // WASMTIME_COREDUMP_H
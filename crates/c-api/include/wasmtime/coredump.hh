@@ -0,0 +1,92 @@
+/**
+ * \file wasmtime/coredump.hh
+ */
+
+#ifndef WASMTIME_COREDUMP_HH
+#define WASMTIME_COREDUMP_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COREDUMP
+
+#include <memory>
+#include <optional>
+#include <string_view>
+#include <vector>
+#include <wasmtime/coredump.h>
+#include <wasmtime/error.hh>
+#include <wasmtime/span.hh>
+#include <wasmtime/store.hh>
+#include <wasmtime/trap.hh>
+
+namespace wasmtime {
+
+/**
+ * \brief A core dump of the WebAssembly state at the time of a trap.
+ *
+ * Core dumps are only captured when `Config::coredump_on_trap` is enabled. A
+ * `CoreDump` borrows from the `Trap` or `Error` it was retrieved from and
+ * must not outlive it.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.WasmCoreDump.html
+ */
+class CoreDump {
+  struct deleter {
+    void operator()(wasmtime_coredump_t *p) const {
+      wasmtime_coredump_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_coredump_t, deleter> ptr;
+
+  explicit CoreDump(wasmtime_coredump_t *raw) : ptr(raw) {}
+
+  static std::optional<CoreDump> wrap(wasmtime_coredump_t *raw) {
+    if (raw == nullptr)
+      return std::nullopt;
+    return CoreDump(raw);
+  }
+
+public:
+  /// \brief Returns the core dump attached to `trap`, if any.
+  static std::optional<CoreDump> from(const Trap &trap) {
+    return wrap(wasmtime_trap_coredump(trap.ptr.get()));
+  }
+
+  /// \brief Returns the core dump attached to `error`, if any.
+  static std::optional<CoreDump> from(const Error &error) {
+    return wrap(wasmtime_error_coredump(error.ptr.get()));
+  }
+
+  /// \brief Returns the number of WebAssembly frames in this core dump.
+  size_t frames_count() const {
+    return wasmtime_coredump_frames_count(ptr.get());
+  }
+
+  /// \brief Serializes this core dump into the standard core dump binary
+  /// format, recording `name` as the name of the dump.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.WasmCoreDump.html#method.serialize
+  Result<std::vector<uint8_t>> serialize(Store::Context cx,
+                                         std::string_view name) const {
+    wasm_byte_vec_t bytes;
+    auto *error = wasmtime_coredump_serialize(ptr.get(), cx.raw_context(),
+                                              name.data(), name.size(), &bytes);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    std::vector<uint8_t> ret;
+    // NOLINTNEXTLINE TODO can this be done without triggering lints?
+    Span<uint8_t> raw(reinterpret_cast<uint8_t *>(bytes.data), bytes.size);
+    ret.assign(raw.begin(), raw.end());
+    wasm_byte_vec_delete(&bytes);
+    return ret;
+  }
+};
+
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_COREDUMP
+
+#endif // WASMTIME_COREDUMP_HH
@@ -8,6 +8,7 @@
 #define WASMTIME_ENGINE_H
 
 #include <wasm.h>
+#include <wasmtime/conf.h>
 
 #ifdef __cplusplus
 extern "C" {
@@ -41,6 +42,76 @@ WASM_API_EXTERN void wasmtime_engine_increment_epoch(wasm_engine_t *engine);
  */
 WASM_API_EXTERN bool wasmtime_engine_is_pulley(wasm_engine_t *engine);
 
+#ifdef WASMTIME_FEATURE_POOLING_ALLOCATOR
+
+/**
+ * \brief Metrics about the current usage of an engine's pooling allocator.
+ *
+ * Created with #wasmtime_engine_pooling_allocator_metrics and deleted with
+ * #wasmtime_pooling_allocator_metrics_delete. Each accessor reports the state
+ * of the pool at the time it's called.
+ *
+ * For more information see the Rust documentation at
+ * https://docs.wasmtime.dev/api/wasmtime/struct.PoolingAllocatorMetrics.html
+ */
+typedef struct wasmtime_pooling_allocator_metrics_t
+    wasmtime_pooling_allocator_metrics_t;
+
+/**
+ * \brief Returns metrics for the pooling allocator used by `engine`.
+ *
+ * Returns `NULL` if the engine wasn't configured with
+ * #wasmtime_pooling_allocation_strategy_set. The returned object keeps the
+ * engine alive and must be deleted with
+ * #wasmtime_pooling_allocator_metrics_delete.
+ */
+WASM_API_EXTERN wasmtime_pooling_allocator_metrics_t *
+wasmtime_engine_pooling_allocator_metrics(const wasm_engine_t *engine);
+
+/**
+ * \brief Deletes metrics returned by
+ * #wasmtime_engine_pooling_allocator_metrics.
+ */
+WASM_API_EXTERN void wasmtime_pooling_allocator_metrics_delete(
+    wasmtime_pooling_allocator_metrics_t *metrics);
+
+/**
+ * \brief Returns the number of core WebAssembly instances currently allocated.
+ */
+WASM_API_EXTERN uint64_t wasmtime_pooling_allocator_metrics_core_instances(
+    const wasmtime_pooling_allocator_metrics_t *metrics);
+
+/**
+ * \brief Returns the number of component instances currently allocated.
+ */
+WASM_API_EXTERN uint64_t
+wasmtime_pooling_allocator_metrics_component_instances(
+    const wasmtime_pooling_allocator_metrics_t *metrics);
+
+/**
+ * \brief Returns the number of linear memory slots currently in use.
+ */
+WASM_API_EXTERN size_t wasmtime_pooling_allocator_metrics_memories(
+    const wasmtime_pooling_allocator_metrics_t *metrics);
+
+/**
+ * \brief Returns the number of table slots currently in use.
+ */
+WASM_API_EXTERN size_t wasmtime_pooling_allocator_metrics_tables(
+    const wasmtime_pooling_allocator_metrics_t *metrics);
+
+/**
+ * \brief Returns the number of unused linear memory slots being kept warm
+ * for reuse.
+ *
+ * See #wasmtime_pooling_allocation_config_max_unused_warm_slots_set.
+ */
+WASM_API_EXTERN uint32_t
+wasmtime_pooling_allocator_metrics_unused_warm_memories(
+    const wasmtime_pooling_allocator_metrics_t *metrics);
+
+#endif // WASMTIME_FEATURE_POOLING_ALLOCATOR
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -6,11 +6,61 @@
 #define WASMTIME_ENGINE_HH
 
 #include <memory>
+#include <optional>
 #include <wasmtime/config.hh>
 #include <wasmtime/engine.h>
 
 namespace wasmtime {
 
+#ifdef WASMTIME_FEATURE_POOLING_ALLOCATOR
+/**
+ * \brief Metrics about the current usage of an engine's pooling allocator.
+ *
+ * Created with `Engine::pooling_allocator_metrics`. Each accessor reports the
+ * state of the pool at the time it's called.
+ */
+class PoolingAllocatorMetrics {
+  friend class Engine;
+
+  struct deleter {
+    void operator()(wasmtime_pooling_allocator_metrics_t *p) const {
+      wasmtime_pooling_allocator_metrics_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_pooling_allocator_metrics_t, deleter> ptr;
+
+  explicit PoolingAllocatorMetrics(wasmtime_pooling_allocator_metrics_t *raw)
+      : ptr(raw) {}
+
+public:
+  /// \brief Returns the number of core instances currently allocated.
+  uint64_t core_instances() const {
+    return wasmtime_pooling_allocator_metrics_core_instances(ptr.get());
+  }
+
+  /// \brief Returns the number of component instances currently allocated.
+  uint64_t component_instances() const {
+    return wasmtime_pooling_allocator_metrics_component_instances(ptr.get());
+  }
+
+  /// \brief Returns the number of linear memory slots currently in use.
+  size_t memories() const {
+    return wasmtime_pooling_allocator_metrics_memories(ptr.get());
+  }
+
+  /// \brief Returns the number of table slots currently in use.
+  size_t tables() const {
+    return wasmtime_pooling_allocator_metrics_tables(ptr.get());
+  }
+
+  /// \brief Returns the number of unused memory slots kept warm for reuse.
+  uint32_t unused_warm_memories() const {
+    return wasmtime_pooling_allocator_metrics_unused_warm_memories(ptr.get());
+  }
+};
+#endif // WASMTIME_FEATURE_POOLING_ALLOCATOR
+
 /**
  * \brief Global compilation state in Wasmtime.
  *
@@ -22,6 +72,8 @@ class Engine {
   friend class Store;
   friend class Module;
   friend class Linker;
+  friend class StructType;
+  friend class ArrayType;
 
   struct deleter {
     void operator()(wasm_engine_t *p) const { wasm_engine_delete(p); }
@@ -56,6 +108,21 @@ public:
 
   /// \brief Returns whether this engine is using Pulley for execution.
   void is_pulley() const { wasmtime_engine_is_pulley(ptr.get()); }
+
+#ifdef WASMTIME_FEATURE_POOLING_ALLOCATOR
+  /// \brief Returns metrics for this engine's pooling allocator, or
+  /// `std::nullopt` if it isn't using the pooling allocator.
+  std::optional<PoolingAllocatorMetrics> pooling_allocator_metrics() const {
+    auto *raw = wasmtime_engine_pooling_allocator_metrics(ptr.get());
+    if (raw == nullptr)
+      return std::nullopt;
+    return PoolingAllocatorMetrics(raw);
+  }
+#endif // WASMTIME_FEATURE_POOLING_ALLOCATOR
+
+  /// \brief Returns the raw underlying C API engine, for use with parts of the
+  /// C API which don't have C++ wrappers yet, such as components.
+  wasm_engine_t *capi() const { return ptr.get(); }
 };
 
 } // namespace wasmtime
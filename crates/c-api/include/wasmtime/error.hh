@@ -23,6 +23,8 @@ class Trace;
  * description of the error that occurred.
  */
 class Error {
+  friend class CoreDump;
+
   struct deleter {
     void operator()(wasmtime_error_t *p) const { wasmtime_error_delete(p); }
   };
@@ -0,0 +1,322 @@
+/**
+ * \file wasmtime/gc.h
+ *
+ * APIs for creating and inspecting WebAssembly GC structs and arrays.
+ *
+ * Structs and arrays are represented as #wasmtime_anyref_t values, so they can
+ * be passed to and from WebAssembly like any other `anyref`. The functions
+ * here allocate new GC objects and read or write their contents. Use
+ * #wasmtime_anyref_is_struct and #wasmtime_anyref_is_array to test what an
+ * `anyref` points to, and #wasmtime_anyref_i31_get_u for `i31ref` values.
+ */
+
+#ifndef WASMTIME_GC_H
+#define WASMTIME_GC_H
+
+#include <wasm.h>
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+#include <wasmtime/val.h>
+
+#ifdef WASMTIME_FEATURE_GC
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \brief Discriminant used in #wasmtime_field_type_t::kind
+ *
+ * The values of #wasmtime_valkind_t are all valid storage kinds, meaning a
+ * field of that value type. Reference kinds describe nullable references to
+ * the top of their hierarchy, for example #WASMTIME_ANYREF is `(ref null
+ * any)`. Additionally #WASMTIME_STORAGE_KIND_I8 and
+ * #WASMTIME_STORAGE_KIND_I16 describe packed fields.
+ */
+typedef uint8_t wasmtime_storage_kind_t;
+
+/// \brief Value of #wasmtime_storage_kind_t meaning a packed 8-bit integer
+/// field, read and written as an `i32`.
+#define WASMTIME_STORAGE_KIND_I8 8
+/// \brief Value of #wasmtime_storage_kind_t meaning a packed 16-bit integer
+/// field, read and written as an `i32`.
+#define WASMTIME_STORAGE_KIND_I16 9
+
+/**
+ * \brief The type of a field within a struct, or the elements of an array.
+ */
+typedef struct wasmtime_field_type {
+  /// The kind of value stored in this field.
+  wasmtime_storage_kind_t kind;
+  /// Whether this field can be modified after creation, either #WASM_CONST or
+  /// #WASM_VAR.
+  wasm_mutability_t mutability;
+} wasmtime_field_type_t;
+
+/**
+ * \brief The type of a WebAssembly GC struct.
+ */
+typedef struct wasmtime_struct_type wasmtime_struct_type_t;
+
+/**
+ * \brief Creates a new struct type with the \p nfields fields provided.
+ *
+ * The created type is final and has no supertype.
+ *
+ * \return `NULL` on success and \p ret is filled in with a type owned by the
+ * caller which must be deleted with #wasmtime_struct_type_delete. Otherwise an
+ * error is returned, for example if a field has an unknown kind.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_struct_type_new(const wasm_engine_t *engine,
+                         const wasmtime_field_type_t *fields, size_t nfields,
+                         wasmtime_struct_type_t **ret);
+
+/// \brief Returns the number of fields in \p ty.
+WASM_API_EXTERN size_t
+wasmtime_struct_type_field_count(const wasmtime_struct_type_t *ty);
+
+/**
+ * \brief Returns the type of the field at \p index within \p ty.
+ *
+ * Returns `false` if \p index is out of bounds or if the field is a reference
+ * outside of the `func`, `extern`, and `any` hierarchies. Note that concrete
+ * reference types, such as a reference to a particular struct type, are
+ * reported as the top type of their hierarchy.
+ */
+WASM_API_EXTERN bool
+wasmtime_struct_type_field(const wasmtime_struct_type_t *ty, size_t index,
+                           wasmtime_field_type_t *ret);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_struct_type_t *
+wasmtime_struct_type_clone(const wasmtime_struct_type_t *ty);
+
+/// \brief Deletes a #wasmtime_struct_type_t.
+WASM_API_EXTERN void wasmtime_struct_type_delete(wasmtime_struct_type_t *ty);
+
+/**
+ * \brief The type of a WebAssembly GC array.
+ */
+typedef struct wasmtime_array_type wasmtime_array_type_t;
+
+/**
+ * \brief Creates a new array type whose elements are described by \p field.
+ *
+ * \return `NULL` on success and \p ret is filled in with a type owned by the
+ * caller which must be deleted with #wasmtime_array_type_delete. Otherwise an
+ * error is returned, for example if \p field has an unknown kind.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_array_type_new(const wasm_engine_t *engine,
+                        const wasmtime_field_type_t *field,
+                        wasmtime_array_type_t **ret);
+
+/**
+ * \brief Returns the type of the elements of \p ty.
+ *
+ * Returns `false` under the same conditions as #wasmtime_struct_type_field.
+ */
+WASM_API_EXTERN bool wasmtime_array_type_field(const wasmtime_array_type_t *ty,
+                                               wasmtime_field_type_t *ret);
+
+/// \brief Creates a new copy of \p ty owned by the caller.
+WASM_API_EXTERN wasmtime_array_type_t *
+wasmtime_array_type_clone(const wasmtime_array_type_t *ty);
+
+/// \brief Deletes a #wasmtime_array_type_t.
+WASM_API_EXTERN void wasmtime_array_type_delete(wasmtime_array_type_t *ty);
+
+/**
+ * \brief A struct type which has been registered within a store, ready to
+ * allocate structs of that type.
+ *
+ * Creating this once and reusing it amortizes the cost of allocating many
+ * structs of the same type. It may only be used with the store it was created
+ * within.
+ */
+typedef struct wasmtime_struct_ref_pre wasmtime_struct_ref_pre_t;
+
+/// \brief Creates a new #wasmtime_struct_ref_pre_t for \p ty within \p
+/// context, which must be deleted with #wasmtime_struct_ref_pre_delete.
+WASM_API_EXTERN wasmtime_struct_ref_pre_t *
+wasmtime_struct_ref_pre_new(wasmtime_context_t *context,
+                            const wasmtime_struct_type_t *ty);
+
+/// \brief Deletes a #wasmtime_struct_ref_pre_t.
+WASM_API_EXTERN void
+wasmtime_struct_ref_pre_delete(wasmtime_struct_ref_pre_t *pre);
+
+/**
+ * \brief An array type which has been registered within a store, ready to
+ * allocate arrays of that type.
+ *
+ * This is the array equivalent of #wasmtime_struct_ref_pre_t.
+ */
+typedef struct wasmtime_array_ref_pre wasmtime_array_ref_pre_t;
+
+/// \brief Creates a new #wasmtime_array_ref_pre_t for \p ty within \p
+/// context, which must be deleted with #wasmtime_array_ref_pre_delete.
+WASM_API_EXTERN wasmtime_array_ref_pre_t *
+wasmtime_array_ref_pre_new(wasmtime_context_t *context,
+                           const wasmtime_array_type_t *ty);
+
+/// \brief Deletes a #wasmtime_array_ref_pre_t.
+WASM_API_EXTERN void
+wasmtime_array_ref_pre_delete(wasmtime_array_ref_pre_t *pre);
+
+/// \brief Returns whether \p anyref points to a struct.
+///
+/// Returns `false` for null references.
+WASM_API_EXTERN bool wasmtime_anyref_is_struct(wasmtime_context_t *context,
+                                               const wasmtime_anyref_t *anyref);
+
+/// \brief Returns whether \p anyref points to an array.
+///
+/// Returns `false` for null references.
+WASM_API_EXTERN bool wasmtime_anyref_is_array(wasmtime_context_t *context,
+                                              const wasmtime_anyref_t *anyref);
+
+/**
+ * \brief Allocates a new struct within \p context.
+ *
+ * \param context the store to allocate the struct within
+ * \param pre the type of the struct, created within \p context
+ * \param fields the initial values of the struct's fields
+ * \param nfields the number of values in \p fields, which must match the
+ *        number of fields in the struct type
+ * \param out where to store the created struct
+ *
+ * Each value in \p fields must match the type of its field. Values for packed
+ * fields are given as `i32` and truncated.
+ *
+ * \return `NULL` on success and \p out is filled in with a reference that must
+ * be unrooted with #wasmtime_anyref_unroot. Otherwise an error is returned,
+ * for example if the values don't match the type or the GC heap is out of
+ * memory.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_new(wasmtime_context_t *context,
+                       const wasmtime_struct_ref_pre_t *pre,
+                       const wasmtime_val_t *fields, size_t nfields,
+                       wasmtime_anyref_t *out);
+
+/**
+ * \brief Returns the type of the struct that \p structref points to.
+ *
+ * \return `NULL` on success and \p ret is filled in with a type owned by the
+ * caller. Otherwise an error is returned if \p structref is null or not a
+ * struct.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_type(wasmtime_context_t *context,
+                        const wasmtime_anyref_t *structref,
+                        wasmtime_struct_type_t **ret);
+
+/**
+ * \brief Reads the field at \p index of the struct \p structref.
+ *
+ * Packed fields are zero-extended to an `i32`.
+ *
+ * \return `NULL` on success and \p out is filled in with a value which must be
+ * unrooted with #wasmtime_val_unroot. Otherwise an error is returned if \p
+ * structref is null or not a struct, or if \p index is out of bounds.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_field(wasmtime_context_t *context,
+                         const wasmtime_anyref_t *structref, size_t index,
+                         wasmtime_val_t *out);
+
+/**
+ * \brief Writes \p val to the field at \p index of the struct \p structref.
+ *
+ * \return `NULL` on success. Otherwise an error is returned if \p structref is
+ * null or not a struct, if \p index is out of bounds, if the field is
+ * immutable, or if \p val doesn't match the field's type.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_set_field(wasmtime_context_t *context,
+                             const wasmtime_anyref_t *structref, size_t index,
+                             const wasmtime_val_t *val);
+
+/**
+ * \brief Allocates a new array within \p context with \p len copies of \p
+ * elem.
+ *
+ * \return `NULL` on success and \p out is filled in with a reference that must
+ * be unrooted with #wasmtime_anyref_unroot. Otherwise an error is returned,
+ * for example if \p elem doesn't match the element type or the GC heap is out
+ * of memory.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_new(wasmtime_context_t *context,
+                      const wasmtime_array_ref_pre_t *pre,
+                      const wasmtime_val_t *elem, uint32_t len,
+                      wasmtime_anyref_t *out);
+
+/**
+ * \brief Allocates a new array within \p context containing the \p nelems
+ * values in \p elems.
+ *
+ * Errors are the same as #wasmtime_arrayref_new.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_new_fixed(wasmtime_context_t *context,
+                            const wasmtime_array_ref_pre_t *pre,
+                            const wasmtime_val_t *elems, size_t nelems,
+                            wasmtime_anyref_t *out);
+
+/**
+ * \brief Returns the type of the array that \p arrayref points to.
+ *
+ * \return `NULL` on success and \p ret is filled in with a type owned by the
+ * caller. Otherwise an error is returned if \p arrayref is null or not an
+ * array.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_type(wasmtime_context_t *context,
+                       const wasmtime_anyref_t *arrayref,
+                       wasmtime_array_type_t **ret);
+
+/**
+ * \brief Returns the length of the array \p arrayref in \p len.
+ *
+ * \return `NULL` on success, otherwise an error if \p arrayref is null or not
+ * an array.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_len(wasmtime_context_t *context,
+                      const wasmtime_anyref_t *arrayref, uint32_t *len);
+
+/**
+ * \brief Reads the element at \p index of the array \p arrayref.
+ *
+ * \return `NULL` on success and \p out is filled in with a value which must be
+ * unrooted with #wasmtime_val_unroot. Otherwise an error is returned if \p
+ * arrayref is null or not an array, or if \p index is out of bounds.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_get(wasmtime_context_t *context,
+                      const wasmtime_anyref_t *arrayref, uint32_t index,
+                      wasmtime_val_t *out);
+
+/**
+ * \brief Writes \p val to the element at \p index of the array \p arrayref.
+ *
+ * \return `NULL` on success. Otherwise an error is returned if \p arrayref is
+ * null or not an array, if \p index is out of bounds, if the elements are
+ * immutable, or if \p val doesn't match the element type.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_set(wasmtime_context_t *context,
+                      const wasmtime_anyref_t *arrayref, uint32_t index,
+                      const wasmtime_val_t *val);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_GC
+
+#endif // WASMTIME_GC_H
@@ -0,0 +1,456 @@
+/**
+ * \file wasmtime/gc.hh
+ */
+
+#ifndef WASMTIME_GC_HH
+#define WASMTIME_GC_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_GC
+
+#include <memory>
+#include <optional>
+#include <vector>
+#include <wasmtime/engine.hh>
+#include <wasmtime/error.hh>
+#include <wasmtime/gc.h>
+#include <wasmtime/store.hh>
+#include <wasmtime/val.hh>
+
+namespace wasmtime {
+
+/**
+ * \brief The type of a field within a struct, or the elements of an array.
+ *
+ * Fields either store a WebAssembly value or a packed `i8` or `i16` integer.
+ * Reference fields are nullable references to the top of their hierarchy.
+ */
+class FieldType {
+  wasmtime_field_type_t ty;
+
+  FieldType(wasmtime_storage_kind_t kind, bool mut) : ty{} {
+    ty.kind = kind;
+    ty.mutability = mut ? WASM_VAR : WASM_CONST;
+  }
+
+  static wasmtime_storage_kind_t kind_to_c(ValKind kind) {
+    switch (kind) {
+    case ValKind::I32:
+      return WASMTIME_I32;
+    case ValKind::I64:
+      return WASMTIME_I64;
+    case ValKind::F32:
+      return WASMTIME_F32;
+    case ValKind::F64:
+      return WASMTIME_F64;
+    case ValKind::V128:
+      return WASMTIME_V128;
+    case ValKind::FuncRef:
+      return WASMTIME_FUNCREF;
+    case ValKind::ExternRef:
+      return WASMTIME_EXTERNREF;
+    case ValKind::AnyRef:
+      return WASMTIME_ANYREF;
+    }
+    std::abort();
+  }
+
+public:
+  /// Creates a field type from its C API representation.
+  FieldType(wasmtime_field_type_t ty) : ty(ty) {}
+
+  /// Creates a field which stores values of the given `kind`.
+  FieldType(ValKind kind, bool mut) : FieldType(kind_to_c(kind), mut) {}
+
+  /// Creates a field which stores a packed 8-bit integer.
+  static FieldType i8(bool mut) {
+    return FieldType(WASMTIME_STORAGE_KIND_I8, mut);
+  }
+
+  /// Creates a field which stores a packed 16-bit integer.
+  static FieldType i16(bool mut) {
+    return FieldType(WASMTIME_STORAGE_KIND_I16, mut);
+  }
+
+  /// Returns whether this field can be modified after creation.
+  bool is_mutable() const { return ty.mutability == WASM_VAR; }
+
+  /// Returns whether this field is a packed 8-bit integer.
+  bool is_i8() const { return ty.kind == WASMTIME_STORAGE_KIND_I8; }
+
+  /// Returns whether this field is a packed 16-bit integer.
+  bool is_i16() const { return ty.kind == WASMTIME_STORAGE_KIND_I16; }
+
+  /// Returns the kind of value stored in this field, or `std::nullopt` if
+  /// it's a packed integer.
+  std::optional<ValKind> kind() const {
+    switch (ty.kind) {
+    case WASMTIME_I32:
+      return ValKind::I32;
+    case WASMTIME_I64:
+      return ValKind::I64;
+    case WASMTIME_F32:
+      return ValKind::F32;
+    case WASMTIME_F64:
+      return ValKind::F64;
+    case WASMTIME_V128:
+      return ValKind::V128;
+    case WASMTIME_FUNCREF:
+      return ValKind::FuncRef;
+    case WASMTIME_EXTERNREF:
+      return ValKind::ExternRef;
+    case WASMTIME_ANYREF:
+      return ValKind::AnyRef;
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the raw underlying C API field type.
+  const wasmtime_field_type_t &capi() const { return ty; }
+};
+
+/**
+ * \brief The type of a WebAssembly GC struct.
+ */
+class StructType {
+  friend class StructRefPre;
+
+  struct deleter {
+    void operator()(wasmtime_struct_type_t *p) const {
+      wasmtime_struct_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_struct_type_t, deleter> ptr;
+
+public:
+  /// Takes ownership of a struct type from the C API.
+  explicit StructType(wasmtime_struct_type_t *ptr) : ptr(ptr) {}
+  /// Copies another struct type into this one.
+  StructType(const StructType &other)
+      : ptr(wasmtime_struct_type_clone(other.ptr.get())) {}
+  /// Copies another struct type into this one.
+  StructType &operator=(const StructType &other) {
+    ptr.reset(wasmtime_struct_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~StructType() = default;
+  /// Moves the type information from another struct type into this one.
+  StructType(StructType &&other) = default;
+  /// Moves the type information from another struct type into this one.
+  StructType &operator=(StructType &&other) = default;
+
+  /// Creates a new struct type with the given fields.
+  static Result<StructType> create(const Engine &engine,
+                                   const std::vector<FieldType> &fields) {
+    std::vector<wasmtime_field_type_t> raw;
+    raw.reserve(fields.size());
+    for (const auto &field : fields) {
+      raw.push_back(field.capi());
+    }
+    wasmtime_struct_type_t *ty = nullptr;
+    auto *error = wasmtime_struct_type_new(engine.ptr.get(), raw.data(),
+                                           raw.size(), &ty);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return StructType(ty);
+  }
+
+  /// Returns the number of fields in this struct type.
+  size_t field_count() const {
+    return wasmtime_struct_type_field_count(ptr.get());
+  }
+
+  /// Returns the type of the field at `index`, or `std::nullopt` if it's out
+  /// of bounds or can't be represented as a `FieldType`.
+  std::optional<FieldType> field(size_t index) const {
+    wasmtime_field_type_t ret;
+    if (wasmtime_struct_type_field(ptr.get(), index, &ret)) {
+      return FieldType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the raw underlying C API struct type.
+  const wasmtime_struct_type_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief The type of a WebAssembly GC array.
+ */
+class ArrayType {
+  friend class ArrayRefPre;
+
+  struct deleter {
+    void operator()(wasmtime_array_type_t *p) const {
+      wasmtime_array_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_array_type_t, deleter> ptr;
+
+public:
+  /// Takes ownership of an array type from the C API.
+  explicit ArrayType(wasmtime_array_type_t *ptr) : ptr(ptr) {}
+  /// Copies another array type into this one.
+  ArrayType(const ArrayType &other)
+      : ptr(wasmtime_array_type_clone(other.ptr.get())) {}
+  /// Copies another array type into this one.
+  ArrayType &operator=(const ArrayType &other) {
+    ptr.reset(wasmtime_array_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ArrayType() = default;
+  /// Moves the type information from another array type into this one.
+  ArrayType(ArrayType &&other) = default;
+  /// Moves the type information from another array type into this one.
+  ArrayType &operator=(ArrayType &&other) = default;
+
+  /// Creates a new array type whose elements are described by `field`.
+  static Result<ArrayType> create(const Engine &engine,
+                                  const FieldType &field) {
+    wasmtime_array_type_t *ty = nullptr;
+    auto *error =
+        wasmtime_array_type_new(engine.ptr.get(), &field.capi(), &ty);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayType(ty);
+  }
+
+  /// Returns the type of this array's elements, or `std::nullopt` if it can't
+  /// be represented as a `FieldType`.
+  std::optional<FieldType> field() const {
+    wasmtime_field_type_t ret;
+    if (wasmtime_array_type_field(ptr.get(), &ret)) {
+      return FieldType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the raw underlying C API array type.
+  const wasmtime_array_type_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief A struct type registered within a `Store`, used to allocate new
+ * structs of that type.
+ */
+class StructRefPre {
+  friend class StructRef;
+
+  struct deleter {
+    void operator()(wasmtime_struct_ref_pre_t *p) const {
+      wasmtime_struct_ref_pre_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_struct_ref_pre_t, deleter> ptr;
+
+public:
+  /// Registers `ty` within the store `cx`.
+  StructRefPre(Store::Context cx, const StructType &ty)
+      : ptr(wasmtime_struct_ref_pre_new(cx.ptr, ty.ptr.get())) {}
+};
+
+/**
+ * \brief An array type registered within a `Store`, used to allocate new
+ * arrays of that type.
+ */
+class ArrayRefPre {
+  friend class ArrayRef;
+
+  struct deleter {
+    void operator()(wasmtime_array_ref_pre_t *p) const {
+      wasmtime_array_ref_pre_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_array_ref_pre_t, deleter> ptr;
+
+public:
+  /// Registers `ty` within the store `cx`.
+  ArrayRefPre(Store::Context cx, const ArrayType &ty)
+      : ptr(wasmtime_array_ref_pre_new(cx.ptr, ty.ptr.get())) {}
+};
+
+/**
+ * \brief A reference to a WebAssembly GC struct.
+ *
+ * This is an `AnyRef` which is known to point to a struct. Like `AnyRef` it is
+ * rooted within a `Store` and must be manually unrooted via `unroot`.
+ */
+class StructRef {
+  AnyRef ref;
+
+public:
+  /// Creates a `StructRef` from an `AnyRef` already known to be a struct.
+  explicit StructRef(AnyRef ref) : ref(ref) {}
+
+  /// Returns `ref` as a `StructRef`, or `std::nullopt` if it's not a struct.
+  static std::optional<StructRef> from(Store::Context cx, AnyRef ref) {
+    if (wasmtime_anyref_is_struct(cx.ptr, ref.raw())) {
+      return StructRef(ref);
+    }
+    return std::nullopt;
+  }
+
+  /// Allocates a new struct within `cx` with the given field values.
+  static Result<StructRef> create(Store::Context cx, const StructRefPre &pre,
+                                  const std::vector<Val> &fields) {
+    std::vector<wasmtime_val_t> raw;
+    raw.reserve(fields.size());
+    for (const auto &field : fields) {
+      raw.push_back(field.val);
+    }
+    wasmtime_anyref_t out;
+    auto *error = wasmtime_structref_new(cx.ptr, pre.ptr.get(), raw.data(),
+                                         raw.size(), &out);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return StructRef(AnyRef(out));
+  }
+
+  /// Returns the type of this struct.
+  Result<StructType> type(Store::Context cx) const {
+    wasmtime_struct_type_t *ty = nullptr;
+    auto *error = wasmtime_structref_type(cx.ptr, ref.raw(), &ty);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return StructType(ty);
+  }
+
+  /// Reads the field at `index`.
+  Result<Val> field(Store::Context cx, size_t index) const {
+    Val ret;
+    auto *error = wasmtime_structref_field(cx.ptr, ref.raw(), index, &ret.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::move(ret);
+  }
+
+  /// Writes `val` to the field at `index`.
+  Result<std::monostate> set_field(Store::Context cx, size_t index,
+                                   const Val &val) const {
+    auto *error =
+        wasmtime_structref_set_field(cx.ptr, ref.raw(), index, &val.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /// Returns the underlying `AnyRef` of this struct.
+  const AnyRef &anyref() const { return ref; }
+
+  /// Unroots this value from the context provided.
+  void unroot(Store::Context cx) { ref.unroot(cx); }
+};
+
+/**
+ * \brief A reference to a WebAssembly GC array.
+ *
+ * This is an `AnyRef` which is known to point to an array. Like `AnyRef` it is
+ * rooted within a `Store` and must be manually unrooted via `unroot`.
+ */
+class ArrayRef {
+  AnyRef ref;
+
+public:
+  /// Creates an `ArrayRef` from an `AnyRef` already known to be an array.
+  explicit ArrayRef(AnyRef ref) : ref(ref) {}
+
+  /// Returns `ref` as an `ArrayRef`, or `std::nullopt` if it's not an array.
+  static std::optional<ArrayRef> from(Store::Context cx, AnyRef ref) {
+    if (wasmtime_anyref_is_array(cx.ptr, ref.raw())) {
+      return ArrayRef(ref);
+    }
+    return std::nullopt;
+  }
+
+  /// Allocates a new array within `cx` with `len` copies of `elem`.
+  static Result<ArrayRef> create(Store::Context cx, const ArrayRefPre &pre,
+                                 const Val &elem, uint32_t len) {
+    wasmtime_anyref_t out;
+    auto *error =
+        wasmtime_arrayref_new(cx.ptr, pre.ptr.get(), &elem.val, len, &out);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayRef(AnyRef(out));
+  }
+
+  /// Allocates a new array within `cx` containing `elems`.
+  static Result<ArrayRef> create(Store::Context cx, const ArrayRefPre &pre,
+                                 const std::vector<Val> &elems) {
+    std::vector<wasmtime_val_t> raw;
+    raw.reserve(elems.size());
+    for (const auto &elem : elems) {
+      raw.push_back(elem.val);
+    }
+    wasmtime_anyref_t out;
+    auto *error = wasmtime_arrayref_new_fixed(cx.ptr, pre.ptr.get(),
+                                              raw.data(), raw.size(), &out);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayRef(AnyRef(out));
+  }
+
+  /// Returns the type of this array.
+  Result<ArrayType> type(Store::Context cx) const {
+    wasmtime_array_type_t *ty = nullptr;
+    auto *error = wasmtime_arrayref_type(cx.ptr, ref.raw(), &ty);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayType(ty);
+  }
+
+  /// Returns the number of elements in this array.
+  Result<uint32_t> len(Store::Context cx) const {
+    uint32_t ret = 0;
+    auto *error = wasmtime_arrayref_len(cx.ptr, ref.raw(), &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ret;
+  }
+
+  /// Reads the element at `index`.
+  Result<Val> get(Store::Context cx, uint32_t index) const {
+    Val ret;
+    auto *error = wasmtime_arrayref_get(cx.ptr, ref.raw(), index, &ret.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::move(ret);
+  }
+
+  /// Writes `val` to the element at `index`.
+  Result<std::monostate> set(Store::Context cx, uint32_t index,
+                             const Val &val) const {
+    auto *error = wasmtime_arrayref_set(cx.ptr, ref.raw(), index, &val.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /// Returns the underlying `AnyRef` of this array.
+  const AnyRef &anyref() const { return ref; }
+
+  /// Unroots this value from the context provided.
+  void unroot(Store::Context cx) { ref.unroot(cx); }
+};
+
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_GC
+
+#endif // WASMTIME_GC_HH
@@ -28,6 +28,7 @@ class Module {
   friend class Store;
   friend class Instance;
   friend class Linker;
+  friend class GuestProfiler;
 
   struct deleter {
     void operator()(wasmtime_module_t *p) const { wasmtime_module_delete(p); }
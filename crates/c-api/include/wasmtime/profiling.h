@@ -8,6 +8,7 @@
 #define WASMTIME_PROFILING_H
 
 #include <wasm.h>
+#include <wasmtime/component/component.h>
 #include <wasmtime/conf.h>
 #include <wasmtime/error.h>
 #include <wasmtime/module.h>
@@ -74,6 +75,37 @@ WASM_API_EXTERN /* own */ wasmtime_guestprofiler_t *wasmtime_guestprofiler_new(
     const wasm_name_t *module_name, uint64_t interval_nanos,
     const wasmtime_guestprofiler_modules_t *modules, size_t modules_len);
 
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+/**
+ * \brief Begin profiling a new component guest.
+ *
+ * \param component_name    name recorded in the profile
+ * \param interval_nanos    intended sampling interval in nanoseconds recorded
+ *                          in the profile
+ * \param component         component whose core modules will appear in
+ *                          captured stack traces
+ * \param extra_modules     additional modules and associated names that will
+ *                          appear in captured stack traces, pointer to the
+ *                          first element
+ * \param extra_modules_len count of elements in `extra_modules`
+ *
+ * \return Created profiler that is owned by the caller.
+ *
+ * This function does not take ownership of the arguments.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.GuestProfiler.html#method.new_component
+ */
+WASM_API_EXTERN /* own */ wasmtime_guestprofiler_t *
+wasmtime_guestprofiler_new_component(
+    const wasm_name_t *component_name, uint64_t interval_nanos,
+    const wasmtime_component_t *component,
+    const wasmtime_guestprofiler_modules_t *extra_modules,
+    size_t extra_modules_len);
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
 /**
  * \brief Add a sample to the profile.
  *
@@ -94,6 +126,22 @@ wasmtime_guestprofiler_sample(wasmtime_guestprofiler_t *guestprofiler,
                               const wasmtime_store_t *store,
                               uint64_t delta_nanos);
 
+/**
+ * \brief Add a sample to the profile using a store's context.
+ *
+ * This is the same as #wasmtime_guestprofiler_sample except that it takes a
+ * #wasmtime_context_t, such as the one passed to the callback registered with
+ * #wasmtime_store_epoch_deadline_callback.
+ *
+ * \param guestprofiler the profiler the sample is being added to
+ * \param context       context that is being used to collect the backtraces
+ * \param delta_nanos   CPU time in nanoseconds that was used by this guest
+ *                      since the previous sample
+ */
+WASM_API_EXTERN void wasmtime_guestprofiler_sample_context(
+    wasmtime_guestprofiler_t *guestprofiler, const wasmtime_context_t *context,
+    uint64_t delta_nanos);
+
 /**
  * \brief Writes out the captured profile.
  *
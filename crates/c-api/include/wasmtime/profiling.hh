@@ -0,0 +1,110 @@
+/**
+ * \file wasmtime/profiling.hh
+ */
+
+#ifndef WASMTIME_PROFILING_HH
+#define WASMTIME_PROFILING_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_PROFILING
+
+#include <memory>
+#include <string>
+#include <string_view>
+#include <utility>
+#include <vector>
+#include <wasmtime/error.hh>
+#include <wasmtime/module.hh>
+#include <wasmtime/profiling.h>
+#include <wasmtime/span.hh>
+#include <wasmtime/store.hh>
+
+namespace wasmtime {
+
+/**
+ * \brief Collects basic profiling data for a single WebAssembly guest.
+ *
+ * Call `sample` at regular intervals while the guest is on the stack, for
+ * example from a host function or after an epoch deadline is reached, and then
+ * call `finish` to produce a profile in the Firefox profiler format.
+ *
+ * For more information see the Rust documentation at:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.GuestProfiler.html
+ */
+class GuestProfiler {
+  struct deleter {
+    void operator()(wasmtime_guestprofiler_t *p) const {
+      wasmtime_guestprofiler_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_guestprofiler_t, deleter> ptr;
+
+  static wasm_name_t name_view(std::string_view name) {
+    wasm_name_t ret;
+    ret.size = name.size();
+    // NOLINTNEXTLINE the C API doesn't take ownership or modify the name
+    ret.data = const_cast<wasm_byte_t *>(name.data());
+    return ret;
+  }
+
+public:
+  /// \brief Begins profiling a new guest.
+  ///
+  /// The `modules` are the modules, along with the names recorded for them in
+  /// the profile, which may appear in captured stack traces.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.GuestProfiler.html#method.new
+  GuestProfiler(std::string_view module_name, uint64_t interval_nanos,
+                const std::vector<std::pair<std::string, Module>> &modules) {
+    std::vector<wasm_name_t> names;
+    names.reserve(modules.size());
+    std::vector<wasmtime_guestprofiler_modules_t> raw;
+    raw.reserve(modules.size());
+    for (const auto &[name, module] : modules) {
+      names.push_back(name_view(name));
+      raw.push_back({&names.back(), module.ptr.get()});
+    }
+    wasm_name_t name = name_view(module_name);
+    ptr.reset(wasmtime_guestprofiler_new(&name, interval_nanos, raw.data(),
+                                         raw.size()));
+  }
+
+  /// \brief Adds a sample to the profile using the current stack of `cx`.
+  ///
+  /// The `delta_nanos` is the CPU time used by the guest since the previous
+  /// sample, or zero if that isn't being recorded.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.GuestProfiler.html#method.sample
+  void sample(Store::Context cx, uint64_t delta_nanos = 0) {
+    wasmtime_guestprofiler_sample_context(ptr.get(), cx.raw_context(),
+                                          delta_nanos);
+  }
+
+  /// \brief Finishes profiling and returns the profile in the Firefox
+  /// profiler's JSON format.
+  ///
+  /// This consumes the profiler, which can't be used afterwards.
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.GuestProfiler.html#method.finish
+  Result<std::vector<uint8_t>> finish() && {
+    wasm_byte_vec_t bytes;
+    auto *error = wasmtime_guestprofiler_finish(ptr.release(), &bytes);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    std::vector<uint8_t> ret;
+    // NOLINTNEXTLINE TODO can this be done without triggering lints?
+    Span<uint8_t> raw(reinterpret_cast<uint8_t *>(bytes.data), bytes.size);
+    ret.assign(raw.begin(), raw.end());
+    wasm_byte_vec_delete(&bytes);
+    return ret;
+  }
+};
+
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_PROFILING
+
+#endif // WASMTIME_PROFILING_HH
@@ -66,6 +66,10 @@ public:
     friend class Linker;
     friend class ExternRef;
     friend class AnyRef;
+    friend class StructRefPre;
+    friend class ArrayRefPre;
+    friend class StructRef;
+    friend class ArrayRef;
     friend class Val;
     friend class Store;
     wasmtime_context_t *ptr;
@@ -111,6 +111,7 @@ inline Trace Error::trace() const {
  * frames on the stack.
  */
 class Trap {
+  friend class CoreDump;
   friend class Linker;
   friend class Instance;
   friend class Func;
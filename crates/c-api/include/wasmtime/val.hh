@@ -157,6 +157,8 @@ class Val {
   friend class Global;
   friend class Table;
   friend class Func;
+  friend class StructRef;
+  friend class ArrayRef;
 
   wasmtime_val_t val;
 
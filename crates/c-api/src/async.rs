@@ -191,7 +191,7 @@ unsafe fn c_async_callback_to_rust_fn(
 
 #[repr(transparent)]
 pub struct wasmtime_call_future_t<'a> {
-    underlying: Pin<Box<dyn Future<Output = ()> + 'a>>,
+    pub(crate) underlying: Pin<Box<dyn Future<Output = ()> + 'a>>,
 }
 
 #[unsafe(no_mangle)]
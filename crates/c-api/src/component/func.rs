@@ -2,7 +2,7 @@ use wasmtime::component::{Func, Val};
 
 use crate::{WasmtimeStoreContextMut, wasmtime_error_t};
 
-use super::wasmtime_component_val_t;
+use super::{wasmtime_component_func_type_t, wasmtime_component_val_t};
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_component_func_call(
@@ -37,3 +37,63 @@ pub unsafe extern "C" fn wasmtime_component_func_post_return(
 
     crate::handle_result(result, |_| {})
 }
+
+#[cfg(feature = "async")]
+async fn do_func_call_async(
+    func: &Func,
+    mut context: WasmtimeStoreContextMut<'_>,
+    args: Vec<Val>,
+    c_results: &mut [wasmtime_component_val_t],
+    error_ret: &mut *mut wasmtime_error_t,
+) {
+    let mut results = vec![Val::Bool(false); c_results.len()];
+    match func.call_async(&mut context, &args, &mut results).await {
+        Ok(()) => {
+            for (c_val, rust_val) in std::iter::zip(c_results, results) {
+                *c_val = wasmtime_component_val_t::from(&rust_val);
+            }
+        }
+        Err(err) => *error_ret = Box::into_raw(Box::new(err.into())),
+    }
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "async")]
+pub unsafe extern "C" fn wasmtime_component_func_call_async<'a>(
+    func: &'a Func,
+    context: WasmtimeStoreContextMut<'a>,
+    args: *const wasmtime_component_val_t,
+    args_len: usize,
+    results: *mut wasmtime_component_val_t,
+    results_len: usize,
+    error_ret: &'a mut *mut wasmtime_error_t,
+) -> Box<crate::wasmtime_call_future_t<'a>> {
+    let c_args = unsafe { crate::slice_from_raw_parts(args, args_len) };
+    let results = unsafe { crate::slice_from_raw_parts_mut(results, results_len) };
+    let args = c_args.iter().map(Val::from).collect::<Vec<_>>();
+    let fut = Box::pin(do_func_call_async(func, context, args, results, error_ret));
+    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "async")]
+pub unsafe extern "C" fn wasmtime_component_func_post_return_async<'a>(
+    func: &'a Func,
+    mut context: WasmtimeStoreContextMut<'a>,
+    error_ret: &'a mut *mut wasmtime_error_t,
+) -> Box<crate::wasmtime_call_future_t<'a>> {
+    let fut = Box::pin(async move {
+        if let Err(err) = func.post_return_async(&mut context).await {
+            *error_ret = Box::into_raw(Box::new(err.into()));
+        }
+    });
+    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_func_type(
+    func: &Func,
+    context: WasmtimeStoreContextMut<'_>,
+) -> Box<wasmtime_component_func_type_t> {
+    Box::new(wasmtime_component_func_type_t::from_func(func, context))
+}
@@ -6,7 +6,7 @@ use crate::{
     WasmtimeStoreContextMut, WasmtimeStoreData, wasm_engine_t, wasmtime_error_t, wasmtime_module_t,
 };
 
-use super::{wasmtime_component_t, wasmtime_component_val_t};
+use super::{wasmtime_component_resource_type_t, wasmtime_component_t, wasmtime_component_val_t};
 
 #[repr(transparent)]
 pub struct wasmtime_component_linker_t {
@@ -47,6 +47,28 @@ pub unsafe extern "C" fn wasmtime_component_linker_instantiate(
     crate::handle_result(result, |instance| *instance_out = instance)
 }
 
+#[unsafe(no_mangle)]
+#[cfg(feature = "async")]
+pub unsafe extern "C" fn wasmtime_component_linker_instantiate_async<'a>(
+    linker: &'a wasmtime_component_linker_t,
+    context: WasmtimeStoreContextMut<'a>,
+    component: &'a wasmtime_component_t,
+    instance_out: &'a mut Instance,
+    error_ret: &'a mut *mut wasmtime_error_t,
+) -> Box<crate::wasmtime_call_future_t<'a>> {
+    let fut = Box::pin(async move {
+        match linker
+            .linker
+            .instantiate_async(context, &component.component)
+            .await
+        {
+            Ok(instance) => *instance_out = instance,
+            Err(err) => *error_ret = Box::into_raw(Box::new(err.into())),
+        }
+    });
+    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_component_linker_delete(
     _linker: Box<wasmtime_component_linker_t>,
@@ -152,6 +174,43 @@ pub unsafe extern "C" fn wasmtime_component_linker_instance_add_func(
     crate::handle_result(result, |_| ())
 }
 
+pub type wasmtime_component_resource_destructor_t =
+    extern "C" fn(*mut c_void, WasmtimeStoreContextMut<'_>, u32) -> Option<Box<wasmtime_error_t>>;
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_resource(
+    linker_instance: &mut wasmtime_component_linker_instance_t,
+    name: *const u8,
+    name_len: usize,
+    ty: &wasmtime_component_resource_type_t,
+    destructor: Option<wasmtime_component_resource_destructor_t>,
+    data: *mut c_void,
+    finalizer: Option<extern "C" fn(*mut c_void)>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let Ok(name) = std::str::from_utf8(name) else {
+        return crate::bad_utf8();
+    };
+
+    let foreign = crate::ForeignData { data, finalizer };
+
+    let result = linker_instance
+        .linker_instance
+        .resource(name, ty.ty, move |ctx, rep| {
+            let _ = &foreign;
+
+            let Some(destructor) = destructor else {
+                return Ok(());
+            };
+            match destructor(foreign.data, ctx, rep) {
+                Some(err) => Err((*err).into()),
+                None => Ok(()),
+            }
+        });
+
+    crate::handle_result(result, |_| ())
+}
+
 #[unsafe(no_mangle)]
 #[cfg(feature = "wasi")]
 pub unsafe extern "C" fn wasmtime_component_linker_add_wasip2(
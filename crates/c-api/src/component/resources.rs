@@ -0,0 +1,176 @@
+use wasmtime::component::{ResourceAny, ResourceDynamic, ResourceType};
+
+use crate::{WasmtimeStoreContextMut, wasmtime_error_t};
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_component_resource_type_t {
+    pub(crate) ty: ResourceType,
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_type_new_host(
+    ty: u32,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(wasmtime_component_resource_type_t {
+        ty: ResourceType::host_dynamic(ty),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_type_clone(
+    ty: &wasmtime_component_resource_type_t,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_type_equal(
+    a: &wasmtime_component_resource_type_t,
+    b: &wasmtime_component_resource_type_t,
+) -> bool {
+    a.ty == b.ty
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_type_delete(
+    _ty: Box<wasmtime_component_resource_type_t>,
+) {
+}
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_component_resource_any_t {
+    pub(crate) resource: ResourceAny,
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_type(
+    resource: &wasmtime_component_resource_any_t,
+) -> Box<wasmtime_component_resource_type_t> {
+    Box::new(wasmtime_component_resource_type_t {
+        ty: resource.resource.ty(),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_owned(
+    resource: &wasmtime_component_resource_any_t,
+) -> bool {
+    resource.resource.owned()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_drop(
+    context: WasmtimeStoreContextMut<'_>,
+    resource: &wasmtime_component_resource_any_t,
+) -> Option<Box<wasmtime_error_t>> {
+    crate::handle_result(resource.resource.resource_drop(context), |()| ())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_clone(
+    resource: &wasmtime_component_resource_any_t,
+) -> Box<wasmtime_component_resource_any_t> {
+    Box::new(resource.clone())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_delete(
+    _resource: Option<Box<wasmtime_component_resource_any_t>>,
+) {
+}
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_component_resource_host_t {
+    resource: ResourceDynamic,
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_new(
+    owned: bool,
+    rep: u32,
+    ty: u32,
+) -> Box<wasmtime_component_resource_host_t> {
+    let resource = if owned {
+        ResourceDynamic::new_own(rep, ty)
+    } else {
+        ResourceDynamic::new_borrow(rep, ty)
+    };
+    Box::new(wasmtime_component_resource_host_t { resource })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_rep(
+    resource: &wasmtime_component_resource_host_t,
+) -> u32 {
+    resource.resource.rep()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_type(
+    resource: &wasmtime_component_resource_host_t,
+) -> u32 {
+    resource.resource.ty()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_owned(
+    resource: &wasmtime_component_resource_host_t,
+) -> bool {
+    resource.resource.owned()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_to_any(
+    context: WasmtimeStoreContextMut<'_>,
+    resource: &wasmtime_component_resource_host_t,
+    resource_out: &mut *mut wasmtime_component_resource_any_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = resource.resource.try_into_resource_any(context);
+    crate::handle_result(result, |resource| {
+        *resource_out = Box::into_raw(Box::new(wasmtime_component_resource_any_t { resource }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_any_to_host(
+    context: WasmtimeStoreContextMut<'_>,
+    resource: &wasmtime_component_resource_any_t,
+    resource_out: &mut *mut wasmtime_component_resource_host_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = ResourceDynamic::try_from_resource_any(resource.resource, context);
+    crate::handle_result(result, |resource| {
+        *resource_out = Box::into_raw(Box::new(wasmtime_component_resource_host_t { resource }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_clone(
+    resource: &wasmtime_component_resource_host_t,
+) -> Box<wasmtime_component_resource_host_t> {
+    Box::new(resource.clone())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_resource_host_delete(
+    _resource: Option<Box<wasmtime_component_resource_host_t>>,
+) {
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "async")]
+pub unsafe extern "C" fn wasmtime_component_resource_any_drop_async<'a>(
+    context: WasmtimeStoreContextMut<'a>,
+    resource: &wasmtime_component_resource_any_t,
+    error_ret: &'a mut *mut wasmtime_error_t,
+) -> Box<crate::wasmtime_call_future_t<'a>> {
+    let resource = resource.resource;
+    let fut = Box::pin(async move {
+        if let Err(err) = resource.resource_drop_async::<()>(context).await {
+            *error_ret = Box::into_raw(Box::new(err.into()));
+        }
+    });
+    Box::new(crate::wasmtime_call_future_t { underlying: fut })
+}
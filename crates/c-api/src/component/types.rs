@@ -0,0 +1,713 @@
+use wasmtime::Engine;
+use wasmtime::component::Func;
+use wasmtime::component::types::{self, ComponentItem, Type};
+
+use crate::{
+    CExternType, WasmtimeStoreContextMut, wasm_exporttype_t, wasm_exporttype_vec_t,
+    wasm_functype_t, wasm_importtype_t, wasm_importtype_vec_t,
+};
+
+use super::{wasmtime_component_resource_type_t, wasmtime_component_t};
+
+pub type wasmtime_component_valtype_kind_t = u8;
+
+pub const WASMTIME_COMPONENT_TYPE_BOOL: wasmtime_component_valtype_kind_t = 0;
+pub const WASMTIME_COMPONENT_TYPE_S8: wasmtime_component_valtype_kind_t = 1;
+pub const WASMTIME_COMPONENT_TYPE_U8: wasmtime_component_valtype_kind_t = 2;
+pub const WASMTIME_COMPONENT_TYPE_S16: wasmtime_component_valtype_kind_t = 3;
+pub const WASMTIME_COMPONENT_TYPE_U16: wasmtime_component_valtype_kind_t = 4;
+pub const WASMTIME_COMPONENT_TYPE_S32: wasmtime_component_valtype_kind_t = 5;
+pub const WASMTIME_COMPONENT_TYPE_U32: wasmtime_component_valtype_kind_t = 6;
+pub const WASMTIME_COMPONENT_TYPE_S64: wasmtime_component_valtype_kind_t = 7;
+pub const WASMTIME_COMPONENT_TYPE_U64: wasmtime_component_valtype_kind_t = 8;
+pub const WASMTIME_COMPONENT_TYPE_F32: wasmtime_component_valtype_kind_t = 9;
+pub const WASMTIME_COMPONENT_TYPE_F64: wasmtime_component_valtype_kind_t = 10;
+pub const WASMTIME_COMPONENT_TYPE_CHAR: wasmtime_component_valtype_kind_t = 11;
+pub const WASMTIME_COMPONENT_TYPE_STRING: wasmtime_component_valtype_kind_t = 12;
+pub const WASMTIME_COMPONENT_TYPE_LIST: wasmtime_component_valtype_kind_t = 13;
+pub const WASMTIME_COMPONENT_TYPE_RECORD: wasmtime_component_valtype_kind_t = 14;
+pub const WASMTIME_COMPONENT_TYPE_TUPLE: wasmtime_component_valtype_kind_t = 15;
+pub const WASMTIME_COMPONENT_TYPE_VARIANT: wasmtime_component_valtype_kind_t = 16;
+pub const WASMTIME_COMPONENT_TYPE_ENUM: wasmtime_component_valtype_kind_t = 17;
+pub const WASMTIME_COMPONENT_TYPE_OPTION: wasmtime_component_valtype_kind_t = 18;
+pub const WASMTIME_COMPONENT_TYPE_RESULT: wasmtime_component_valtype_kind_t = 19;
+pub const WASMTIME_COMPONENT_TYPE_FLAGS: wasmtime_component_valtype_kind_t = 20;
+pub const WASMTIME_COMPONENT_TYPE_OWN: wasmtime_component_valtype_kind_t = 21;
+pub const WASMTIME_COMPONENT_TYPE_BORROW: wasmtime_component_valtype_kind_t = 22;
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_component_valtype_t {
+    pub(crate) ty: Type,
+}
+
+impl wasmtime_component_valtype_t {
+    fn new(ty: Type) -> Box<wasmtime_component_valtype_t> {
+        Box::new(wasmtime_component_valtype_t { ty })
+    }
+}
+
+fn write_name(name: &str, name_ptr: &mut *const u8, name_len: &mut usize) {
+    *name_ptr = name.as_ptr();
+    *name_len = name.len();
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_kind(
+    ty: &wasmtime_component_valtype_t,
+) -> wasmtime_component_valtype_kind_t {
+    match ty.ty {
+        Type::Bool => WASMTIME_COMPONENT_TYPE_BOOL,
+        Type::S8 => WASMTIME_COMPONENT_TYPE_S8,
+        Type::U8 => WASMTIME_COMPONENT_TYPE_U8,
+        Type::S16 => WASMTIME_COMPONENT_TYPE_S16,
+        Type::U16 => WASMTIME_COMPONENT_TYPE_U16,
+        Type::S32 => WASMTIME_COMPONENT_TYPE_S32,
+        Type::U32 => WASMTIME_COMPONENT_TYPE_U32,
+        Type::S64 => WASMTIME_COMPONENT_TYPE_S64,
+        Type::U64 => WASMTIME_COMPONENT_TYPE_U64,
+        Type::Float32 => WASMTIME_COMPONENT_TYPE_F32,
+        Type::Float64 => WASMTIME_COMPONENT_TYPE_F64,
+        Type::Char => WASMTIME_COMPONENT_TYPE_CHAR,
+        Type::String => WASMTIME_COMPONENT_TYPE_STRING,
+        Type::List(_) => WASMTIME_COMPONENT_TYPE_LIST,
+        Type::Record(_) => WASMTIME_COMPONENT_TYPE_RECORD,
+        Type::Tuple(_) => WASMTIME_COMPONENT_TYPE_TUPLE,
+        Type::Variant(_) => WASMTIME_COMPONENT_TYPE_VARIANT,
+        Type::Enum(_) => WASMTIME_COMPONENT_TYPE_ENUM,
+        Type::Option(_) => WASMTIME_COMPONENT_TYPE_OPTION,
+        Type::Result(_) => WASMTIME_COMPONENT_TYPE_RESULT,
+        Type::Flags(_) => WASMTIME_COMPONENT_TYPE_FLAGS,
+        Type::Own(_) => WASMTIME_COMPONENT_TYPE_OWN,
+        Type::Borrow(_) => WASMTIME_COMPONENT_TYPE_BORROW,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_equal(
+    a: &wasmtime_component_valtype_t,
+    b: &wasmtime_component_valtype_t,
+) -> bool {
+    a.ty == b.ty
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_clone(
+    ty: &wasmtime_component_valtype_t,
+) -> Box<wasmtime_component_valtype_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_delete(_ty: Box<wasmtime_component_valtype_t>) {}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_list_element(
+    ty: &wasmtime_component_valtype_t,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &ty.ty {
+        Type::List(list) => Some(wasmtime_component_valtype_t::new(list.ty())),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_record_field_count(
+    ty: &wasmtime_component_valtype_t,
+) -> usize {
+    match &ty.ty {
+        Type::Record(record) => record.fields().len(),
+        _ => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_record_field_nth(
+    ty: &wasmtime_component_valtype_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    ty_out: &mut *mut wasmtime_component_valtype_t,
+) -> bool {
+    let Type::Record(record) = &ty.ty else {
+        return false;
+    };
+    match record.fields().nth(index) {
+        Some(field) => {
+            write_name(field.name, name_ptr, name_len);
+            *ty_out = Box::into_raw(wasmtime_component_valtype_t::new(field.ty));
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_tuple_type_count(
+    ty: &wasmtime_component_valtype_t,
+) -> usize {
+    match &ty.ty {
+        Type::Tuple(tuple) => tuple.types().len(),
+        _ => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_tuple_type_nth(
+    ty: &wasmtime_component_valtype_t,
+    index: usize,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &ty.ty {
+        Type::Tuple(tuple) => tuple
+            .types()
+            .nth(index)
+            .map(wasmtime_component_valtype_t::new),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_variant_case_count(
+    ty: &wasmtime_component_valtype_t,
+) -> usize {
+    match &ty.ty {
+        Type::Variant(variant) => variant.cases().len(),
+        _ => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_variant_case_nth(
+    ty: &wasmtime_component_valtype_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    payload_out: &mut *mut wasmtime_component_valtype_t,
+) -> bool {
+    let Type::Variant(variant) = &ty.ty else {
+        return false;
+    };
+    match variant.cases().nth(index) {
+        Some(case) => {
+            write_name(case.name, name_ptr, name_len);
+            *payload_out = match case.ty {
+                Some(ty) => Box::into_raw(wasmtime_component_valtype_t::new(ty)),
+                None => std::ptr::null_mut(),
+            };
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_enum_name_count(
+    ty: &wasmtime_component_valtype_t,
+) -> usize {
+    match &ty.ty {
+        Type::Enum(enum_) => enum_.names().len(),
+        _ => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_enum_name_nth(
+    ty: &wasmtime_component_valtype_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+) -> bool {
+    let Type::Enum(enum_) = &ty.ty else {
+        return false;
+    };
+    match enum_.names().nth(index) {
+        Some(name) => {
+            write_name(name, name_ptr, name_len);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_flags_name_count(
+    ty: &wasmtime_component_valtype_t,
+) -> usize {
+    match &ty.ty {
+        Type::Flags(flags) => flags.names().len(),
+        _ => 0,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_flags_name_nth(
+    ty: &wasmtime_component_valtype_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+) -> bool {
+    let Type::Flags(flags) = &ty.ty else {
+        return false;
+    };
+    match flags.names().nth(index) {
+        Some(name) => {
+            write_name(name, name_ptr, name_len);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_option_payload(
+    ty: &wasmtime_component_valtype_t,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &ty.ty {
+        Type::Option(option) => Some(wasmtime_component_valtype_t::new(option.ty())),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_result_ok(
+    ty: &wasmtime_component_valtype_t,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &ty.ty {
+        Type::Result(result) => result.ok().map(wasmtime_component_valtype_t::new),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_result_err(
+    ty: &wasmtime_component_valtype_t,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &ty.ty {
+        Type::Result(result) => result.err().map(wasmtime_component_valtype_t::new),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_resource_type(
+    ty: &wasmtime_component_valtype_t,
+) -> Option<Box<wasmtime_component_resource_type_t>> {
+    match &ty.ty {
+        Type::Own(ty) | Type::Borrow(ty) => {
+            Some(Box::new(wasmtime_component_resource_type_t { ty: *ty }))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Clone)]
+pub struct wasmtime_component_func_type_t {
+    params: Vec<(String, Type)>,
+    results: Vec<Type>,
+}
+
+impl wasmtime_component_func_type_t {
+    pub(crate) fn from_func(func: &Func, context: WasmtimeStoreContextMut<'_>) -> Self {
+        wasmtime_component_func_type_t {
+            params: func.params(&context).into_vec(),
+            results: func.results(&context).into_vec(),
+        }
+    }
+
+    fn from_type(ty: &types::ComponentFunc) -> Self {
+        wasmtime_component_func_type_t {
+            params: ty
+                .params()
+                .map(|(name, ty)| (name.to_string(), ty))
+                .collect(),
+            results: ty.results().collect(),
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_param_count(
+    ty: &wasmtime_component_func_type_t,
+) -> usize {
+    ty.params.len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_param_nth(
+    ty: &wasmtime_component_func_type_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    ty_out: &mut *mut wasmtime_component_valtype_t,
+) -> bool {
+    match ty.params.get(index) {
+        Some((name, ty)) => {
+            write_name(name, name_ptr, name_len);
+            *ty_out = Box::into_raw(wasmtime_component_valtype_t::new(ty.clone()));
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_result_count(
+    ty: &wasmtime_component_func_type_t,
+) -> usize {
+    ty.results.len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_result_nth(
+    ty: &wasmtime_component_func_type_t,
+    index: usize,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    ty.results
+        .get(index)
+        .map(|ty| wasmtime_component_valtype_t::new(ty.clone()))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_clone(
+    ty: &wasmtime_component_func_type_t,
+) -> Box<wasmtime_component_func_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_delete(_ty: Box<wasmtime_component_func_type_t>) {}
+
+pub type wasmtime_component_item_kind_t = u8;
+
+pub const WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC: wasmtime_component_item_kind_t = 0;
+pub const WASMTIME_COMPONENT_ITEM_CORE_FUNC: wasmtime_component_item_kind_t = 1;
+pub const WASMTIME_COMPONENT_ITEM_MODULE: wasmtime_component_item_kind_t = 2;
+pub const WASMTIME_COMPONENT_ITEM_COMPONENT: wasmtime_component_item_kind_t = 3;
+pub const WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE: wasmtime_component_item_kind_t = 4;
+pub const WASMTIME_COMPONENT_ITEM_TYPE: wasmtime_component_item_kind_t = 5;
+pub const WASMTIME_COMPONENT_ITEM_RESOURCE: wasmtime_component_item_kind_t = 6;
+
+#[derive(Clone)]
+pub struct wasmtime_component_item_t {
+    item: ComponentItem,
+    engine: Engine,
+}
+
+impl wasmtime_component_item_t {
+    fn new(item: ComponentItem, engine: &Engine) -> Box<wasmtime_component_item_t> {
+        Box::new(wasmtime_component_item_t {
+            item,
+            engine: engine.clone(),
+        })
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_kind(
+    item: &wasmtime_component_item_t,
+) -> wasmtime_component_item_kind_t {
+    match item.item {
+        ComponentItem::ComponentFunc(_) => WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC,
+        ComponentItem::CoreFunc(_) => WASMTIME_COMPONENT_ITEM_CORE_FUNC,
+        ComponentItem::Module(_) => WASMTIME_COMPONENT_ITEM_MODULE,
+        ComponentItem::Component(_) => WASMTIME_COMPONENT_ITEM_COMPONENT,
+        ComponentItem::ComponentInstance(_) => WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE,
+        ComponentItem::Type(_) => WASMTIME_COMPONENT_ITEM_TYPE,
+        ComponentItem::Resource(_) => WASMTIME_COMPONENT_ITEM_RESOURCE,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_component_func(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasmtime_component_func_type_t>> {
+    match &item.item {
+        ComponentItem::ComponentFunc(ty) => {
+            Some(Box::new(wasmtime_component_func_type_t::from_type(ty)))
+        }
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_core_func(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasm_functype_t>> {
+    match &item.item {
+        ComponentItem::CoreFunc(ty) => Some(Box::new(wasm_functype_t::new(ty.clone()))),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_module_imports(
+    item: &wasmtime_component_item_t,
+    out: &mut wasm_importtype_vec_t,
+) -> bool {
+    let ComponentItem::Module(module) = &item.item else {
+        return false;
+    };
+    let imports = module
+        .imports(&item.engine)
+        .map(|((module, name), ty)| {
+            Some(Box::new(wasm_importtype_t::new(
+                module.to_owned(),
+                name.to_owned(),
+                CExternType::new(ty),
+            )))
+        })
+        .collect::<Vec<_>>();
+    out.set_buffer(imports);
+    true
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_module_exports(
+    item: &wasmtime_component_item_t,
+    out: &mut wasm_exporttype_vec_t,
+) -> bool {
+    let ComponentItem::Module(module) = &item.item else {
+        return false;
+    };
+    let exports = module
+        .exports(&item.engine)
+        .map(|(name, ty)| {
+            Some(Box::new(wasm_exporttype_t::new(
+                name.to_owned(),
+                CExternType::new(ty),
+            )))
+        })
+        .collect::<Vec<_>>();
+    out.set_buffer(exports);
+    true
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_component(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasmtime_component_type_t>> {
+    match &item.item {
+        ComponentItem::Component(ty) => Some(Box::new(wasmtime_component_type_t {
+            ty: ty.clone(),
+            engine: item.engine.clone(),
+        })),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_component_instance(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasmtime_component_instance_type_t>> {
+    match &item.item {
+        ComponentItem::ComponentInstance(ty) => {
+            Some(Box::new(wasmtime_component_instance_type_t {
+                ty: ty.clone(),
+                engine: item.engine.clone(),
+            }))
+        }
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_type(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasmtime_component_valtype_t>> {
+    match &item.item {
+        ComponentItem::Type(ty) => Some(wasmtime_component_valtype_t::new(ty.clone())),
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_resource(
+    item: &wasmtime_component_item_t,
+) -> Option<Box<wasmtime_component_resource_type_t>> {
+    match &item.item {
+        ComponentItem::Resource(ty) => {
+            Some(Box::new(wasmtime_component_resource_type_t { ty: *ty }))
+        }
+        _ => None,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_clone(
+    item: &wasmtime_component_item_t,
+) -> Box<wasmtime_component_item_t> {
+    Box::new(item.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_delete(_item: Box<wasmtime_component_item_t>) {}
+
+fn item_nth<'a>(
+    mut items: impl Iterator<Item = (&'a str, ComponentItem)>,
+    engine: &Engine,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    item_out: &mut *mut wasmtime_component_item_t,
+) -> bool {
+    match items.nth(index) {
+        Some((name, item)) => {
+            write_name(name, name_ptr, name_len);
+            *item_out = Box::into_raw(wasmtime_component_item_t::new(item, engine));
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Clone)]
+pub struct wasmtime_component_type_t {
+    ty: types::Component,
+    engine: Engine,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type(
+    component: &wasmtime_component_t,
+) -> Box<wasmtime_component_type_t> {
+    Box::new(wasmtime_component_type_t {
+        ty: component.component.component_type(),
+        engine: component.component.engine().clone(),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_import_count(ty: &wasmtime_component_type_t) -> usize {
+    ty.ty.imports(&ty.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_type_import_get(
+    ty: &wasmtime_component_type_t,
+    name: *const u8,
+    name_len: usize,
+) -> Option<Box<wasmtime_component_item_t>> {
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let name = std::str::from_utf8(name).ok()?;
+    let item = ty.ty.get_import(&ty.engine, name)?;
+    Some(wasmtime_component_item_t::new(item, &ty.engine))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_import_nth(
+    ty: &wasmtime_component_type_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    item_out: &mut *mut wasmtime_component_item_t,
+) -> bool {
+    item_nth(
+        ty.ty.imports(&ty.engine),
+        &ty.engine,
+        index,
+        name_ptr,
+        name_len,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_export_count(ty: &wasmtime_component_type_t) -> usize {
+    ty.ty.exports(&ty.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_type_export_get(
+    ty: &wasmtime_component_type_t,
+    name: *const u8,
+    name_len: usize,
+) -> Option<Box<wasmtime_component_item_t>> {
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let name = std::str::from_utf8(name).ok()?;
+    let item = ty.ty.get_export(&ty.engine, name)?;
+    Some(wasmtime_component_item_t::new(item, &ty.engine))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_export_nth(
+    ty: &wasmtime_component_type_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    item_out: &mut *mut wasmtime_component_item_t,
+) -> bool {
+    item_nth(
+        ty.ty.exports(&ty.engine),
+        &ty.engine,
+        index,
+        name_ptr,
+        name_len,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_clone(
+    ty: &wasmtime_component_type_t,
+) -> Box<wasmtime_component_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_delete(_ty: Box<wasmtime_component_type_t>) {}
+
+#[derive(Clone)]
+pub struct wasmtime_component_instance_type_t {
+    ty: types::ComponentInstance,
+    engine: Engine,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_export_count(
+    ty: &wasmtime_component_instance_type_t,
+) -> usize {
+    ty.ty.exports(&ty.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_instance_type_export_get(
+    ty: &wasmtime_component_instance_type_t,
+    name: *const u8,
+    name_len: usize,
+) -> Option<Box<wasmtime_component_item_t>> {
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let name = std::str::from_utf8(name).ok()?;
+    let item = ty.ty.get_export(&ty.engine, name)?;
+    Some(wasmtime_component_item_t::new(item, &ty.engine))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_export_nth(
+    ty: &wasmtime_component_instance_type_t,
+    index: usize,
+    name_ptr: &mut *const u8,
+    name_len: &mut usize,
+    item_out: &mut *mut wasmtime_component_item_t,
+) -> bool {
+    item_nth(
+        ty.ty.exports(&ty.engine),
+        &ty.engine,
+        index,
+        name_ptr,
+        name_len,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_clone(
+    ty: &wasmtime_component_instance_type_t,
+) -> Box<wasmtime_component_instance_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_delete(
+    _ty: Box<wasmtime_component_instance_type_t>,
+) {
+}
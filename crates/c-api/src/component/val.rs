@@ -2,6 +2,8 @@ use wasmtime::component::Val;
 
 use crate::wasm_name_t;
 
+use super::wasmtime_component_resource_any_t;
+
 use std::mem;
 use std::mem::MaybeUninit;
 use std::ptr;
@@ -232,6 +234,7 @@ pub enum wasmtime_component_val_t {
     Option(Option<Box<Self>>),
     Result(wasmtime_component_valresult_t),
     Flags(wasmtime_component_valflags_t),
+    Resource(Box<wasmtime_component_resource_any_t>),
 }
 
 impl Default for wasmtime_component_val_t {
@@ -273,6 +276,7 @@ impl From<&wasmtime_component_val_t> for Val {
             }
             wasmtime_component_val_t::Result(x) => Val::Result(x.into()),
             wasmtime_component_val_t::Flags(x) => Val::Flags(x.into()),
+            wasmtime_component_val_t::Resource(x) => Val::Resource(x.resource),
         }
     }
 }
@@ -306,7 +310,11 @@ impl From<&Val> for wasmtime_component_val_t {
             ),
             Val::Result(x) => wasmtime_component_val_t::Result(x.into()),
             Val::Flags(x) => wasmtime_component_val_t::Flags(x.as_slice().into()),
-            Val::Resource(_resource_any) => todo!(),
+            Val::Resource(x) => {
+                wasmtime_component_val_t::Resource(Box::new(wasmtime_component_resource_any_t {
+                    resource: *x,
+                }))
+            }
         }
     }
 }
@@ -14,6 +14,9 @@ use wasmtime::{
 #[cfg(feature = "pooling-allocator")]
 use wasmtime::PoolingAllocationConfig;
 
+#[cfg(feature = "memory-protection-keys")]
+use wasmtime::MpkEnabled;
+
 #[repr(C)]
 #[derive(Clone)]
 pub struct wasm_config_t {
@@ -37,6 +40,15 @@ pub enum wasmtime_opt_level_t {
     WASMTIME_OPT_LEVEL_SPEED_AND_SIZE,
 }
 
+#[repr(u8)]
+#[derive(Clone)]
+#[cfg(feature = "memory-protection-keys")]
+pub enum wasmtime_mpk_enabled_t {
+    WASMTIME_MPK_ENABLED_AUTO,
+    WASMTIME_MPK_ENABLED_ENABLE,
+    WASMTIME_MPK_ENABLED_DISABLE,
+}
+
 #[repr(u8)]
 #[derive(Clone)]
 pub enum wasmtime_profiling_strategy_t {
@@ -277,6 +289,12 @@ pub extern "C" fn wasmtime_config_macos_use_mach_ports_set(c: &mut wasm_config_t
     c.config.macos_use_mach_ports(enabled);
 }
 
+#[unsafe(no_mangle)]
+#[cfg(feature = "coredump")]
+pub extern "C" fn wasmtime_config_coredump_on_trap_set(c: &mut wasm_config_t, enabled: bool) {
+    c.config.coredump_on_trap(enabled);
+}
+
 #[unsafe(no_mangle)]
 #[cfg(any(feature = "cranelift", feature = "winch"))]
 pub unsafe extern "C" fn wasmtime_config_cranelift_flag_enable(
@@ -663,6 +681,36 @@ pub extern "C" fn wasmtime_pooling_allocation_config_total_gc_heaps_set(
     c.config.total_gc_heaps(count);
 }
 
+#[unsafe(no_mangle)]
+#[cfg(feature = "memory-protection-keys")]
+pub extern "C" fn wasmtime_pooling_allocation_config_memory_protection_keys_set(
+    c: &mut wasmtime_pooling_allocation_config_t,
+    enabled: wasmtime_mpk_enabled_t,
+) {
+    use wasmtime_mpk_enabled_t::*;
+    c.config.memory_protection_keys(match enabled {
+        WASMTIME_MPK_ENABLED_AUTO => MpkEnabled::Auto,
+        WASMTIME_MPK_ENABLED_ENABLE => MpkEnabled::Enable,
+        WASMTIME_MPK_ENABLED_DISABLE => MpkEnabled::Disable,
+    });
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "memory-protection-keys")]
+pub extern "C" fn wasmtime_pooling_allocation_config_max_memory_protection_keys_set(
+    c: &mut wasmtime_pooling_allocation_config_t,
+    max: usize,
+) {
+    c.config.max_memory_protection_keys(max);
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "memory-protection-keys")]
+pub extern "C" fn wasmtime_pooling_allocation_config_are_memory_protection_keys_available() -> bool
+{
+    PoolingAllocationConfig::are_memory_protection_keys_available()
+}
+
 #[unsafe(no_mangle)]
 #[cfg(feature = "pooling-allocator")]
 pub extern "C" fn wasmtime_pooling_allocation_strategy_set(
@@ -0,0 +1,49 @@
+use crate::{
+    WasmtimeStoreContextMut, bad_utf8, to_str, wasm_byte_vec_t, wasm_trap_t, wasmtime_error_t,
+};
+use std::str;
+use wasmtime::WasmCoreDump;
+
+/// A borrowed view of a `WasmCoreDump` attached to a trap or error.
+///
+/// Like `wasm_frame_t` this borrows from the trap or error it was created
+/// from, so it must not outlive it.
+pub struct wasmtime_coredump_t<'a> {
+    coredump: &'a WasmCoreDump,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_coredump_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_trap_coredump(
+    trap: &wasm_trap_t,
+) -> Option<Box<wasmtime_coredump_t<'_>>> {
+    let coredump = trap.error.downcast_ref::<WasmCoreDump>()?;
+    Some(Box::new(wasmtime_coredump_t { coredump }))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_error_coredump(
+    error: &wasmtime_error_t,
+) -> Option<Box<wasmtime_coredump_t<'_>>> {
+    let coredump = error.error.downcast_ref::<WasmCoreDump>()?;
+    Some(Box::new(wasmtime_coredump_t { coredump }))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_coredump_frames_count(coredump: &wasmtime_coredump_t<'_>) -> usize {
+    coredump.coredump.frames().len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_coredump_serialize(
+    coredump: &wasmtime_coredump_t<'_>,
+    store: WasmtimeStoreContextMut<'_>,
+    name: *const u8,
+    name_len: usize,
+    out: &mut wasm_byte_vec_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = to_str!(name, name_len);
+    out.set_buffer(coredump.coredump.serialize(store, name));
+    None
+}
@@ -1,5 +1,7 @@
 use crate::wasm_config_t;
 use wasmtime::Engine;
+#[cfg(feature = "pooling-allocator")]
+use wasmtime::PoolingAllocatorMetrics;
 
 #[repr(C)]
 #[derive(Clone)]
@@ -52,3 +54,60 @@ pub extern "C" fn wasmtime_engine_increment_epoch(engine: &wasm_engine_t) {
 pub extern "C" fn wasmtime_engine_is_pulley(engine: &wasm_engine_t) -> bool {
     engine.engine.is_pulley()
 }
+
+#[cfg(feature = "pooling-allocator")]
+pub struct wasmtime_pooling_allocator_metrics_t {
+    metrics: PoolingAllocatorMetrics,
+}
+
+#[cfg(feature = "pooling-allocator")]
+wasmtime_c_api_macros::declare_own!(wasmtime_pooling_allocator_metrics_t);
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_engine_pooling_allocator_metrics(
+    engine: &wasm_engine_t,
+) -> Option<Box<wasmtime_pooling_allocator_metrics_t>> {
+    let metrics = engine.engine.pooling_allocator_metrics()?;
+    Some(Box::new(wasmtime_pooling_allocator_metrics_t { metrics }))
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_pooling_allocator_metrics_core_instances(
+    metrics: &wasmtime_pooling_allocator_metrics_t,
+) -> u64 {
+    metrics.metrics.core_instances()
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_pooling_allocator_metrics_component_instances(
+    metrics: &wasmtime_pooling_allocator_metrics_t,
+) -> u64 {
+    metrics.metrics.component_instances()
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_pooling_allocator_metrics_memories(
+    metrics: &wasmtime_pooling_allocator_metrics_t,
+) -> usize {
+    metrics.metrics.memories()
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_pooling_allocator_metrics_tables(
+    metrics: &wasmtime_pooling_allocator_metrics_t,
+) -> usize {
+    metrics.metrics.tables()
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "pooling-allocator")]
+pub extern "C" fn wasmtime_pooling_allocator_metrics_unused_warm_memories(
+    metrics: &wasmtime_pooling_allocator_metrics_t,
+) -> u32 {
+    metrics.metrics.unused_warm_memories()
+}
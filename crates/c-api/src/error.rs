@@ -3,7 +3,7 @@ use anyhow::{Error, Result, anyhow};
 
 #[repr(C)]
 pub struct wasmtime_error_t {
-    error: Error,
+    pub(crate) error: Error,
 }
 
 wasmtime_c_api_macros::declare_own!(wasmtime_error_t);
@@ -0,0 +1,409 @@
+use crate::{
+    WASM_CONST, WASM_VAR, WASMTIME_ANYREF, WASMTIME_EXTERNREF, WASMTIME_F32, WASMTIME_F64,
+    WASMTIME_FUNCREF, WASMTIME_I32, WASMTIME_I64, WASMTIME_V128, WasmtimeStoreContextMut,
+    handle_result, wasm_engine_t, wasm_mutability_t, wasmtime_anyref_t, wasmtime_error_t,
+    wasmtime_val_t,
+};
+use anyhow::{Result, anyhow, bail};
+use std::mem::MaybeUninit;
+use wasmtime::{
+    AnyRef, ArrayRef, ArrayRefPre, ArrayType, AsContextMut, FieldType, HeapType, Mutability,
+    RootScope, Rooted, StorageType, StructRef, StructRefPre, StructType, Val, ValType,
+};
+
+pub type wasmtime_storage_kind_t = u8;
+
+pub const WASMTIME_STORAGE_KIND_I8: wasmtime_storage_kind_t = 8;
+pub const WASMTIME_STORAGE_KIND_I16: wasmtime_storage_kind_t = 9;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct wasmtime_field_type_t {
+    pub kind: wasmtime_storage_kind_t,
+    pub mutability: wasm_mutability_t,
+}
+
+impl wasmtime_field_type_t {
+    fn to_wasmtime(&self) -> Result<FieldType> {
+        let mutability = match self.mutability {
+            WASM_CONST => Mutability::Const,
+            WASM_VAR => Mutability::Var,
+            other => bail!("unknown wasm_mutability_t: {other}"),
+        };
+        let storage = match self.kind {
+            WASMTIME_STORAGE_KIND_I8 => StorageType::I8,
+            WASMTIME_STORAGE_KIND_I16 => StorageType::I16,
+            WASMTIME_I32 => StorageType::ValType(ValType::I32),
+            WASMTIME_I64 => StorageType::ValType(ValType::I64),
+            WASMTIME_F32 => StorageType::ValType(ValType::F32),
+            WASMTIME_F64 => StorageType::ValType(ValType::F64),
+            WASMTIME_V128 => StorageType::ValType(ValType::V128),
+            WASMTIME_FUNCREF => StorageType::ValType(ValType::FUNCREF),
+            WASMTIME_EXTERNREF => StorageType::ValType(ValType::EXTERNREF),
+            WASMTIME_ANYREF => StorageType::ValType(ValType::ANYREF),
+            other => bail!("unknown wasmtime_storage_kind_t: {other}"),
+        };
+        Ok(FieldType::new(mutability, storage))
+    }
+
+    /// Converts a field type into its C representation, returning `None` for
+    /// reference types outside of the `func`, `extern`, and `any` hierarchies.
+    ///
+    /// Note that concrete reference types are described by the top type of
+    /// their hierarchy, so this is a lossy conversion.
+    fn from_wasmtime(ty: &FieldType) -> Option<wasmtime_field_type_t> {
+        let kind = match ty.element_type() {
+            StorageType::I8 => WASMTIME_STORAGE_KIND_I8,
+            StorageType::I16 => WASMTIME_STORAGE_KIND_I16,
+            StorageType::ValType(ValType::I32) => WASMTIME_I32,
+            StorageType::ValType(ValType::I64) => WASMTIME_I64,
+            StorageType::ValType(ValType::F32) => WASMTIME_F32,
+            StorageType::ValType(ValType::F64) => WASMTIME_F64,
+            StorageType::ValType(ValType::V128) => WASMTIME_V128,
+            StorageType::ValType(ValType::Ref(r)) => match r.heap_type().top() {
+                HeapType::Func => WASMTIME_FUNCREF,
+                HeapType::Extern => WASMTIME_EXTERNREF,
+                HeapType::Any => WASMTIME_ANYREF,
+                _ => return None,
+            },
+        };
+        let mutability = match ty.mutability() {
+            Mutability::Const => WASM_CONST,
+            Mutability::Var => WASM_VAR,
+        };
+        Some(wasmtime_field_type_t { kind, mutability })
+    }
+}
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_struct_type_t {
+    pub(crate) ty: StructType,
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_struct_type_new(
+    engine: &wasm_engine_t,
+    fields: *const wasmtime_field_type_t,
+    nfields: usize,
+    ret: &mut *mut wasmtime_struct_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let fields = crate::slice_from_raw_parts(fields, nfields);
+    let result = fields
+        .iter()
+        .map(|f| f.to_wasmtime())
+        .collect::<Result<Vec<_>>>()
+        .and_then(|fields| StructType::new(&engine.engine, fields));
+    handle_result(result, |ty| {
+        *ret = Box::into_raw(Box::new(wasmtime_struct_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_field_count(ty: &wasmtime_struct_type_t) -> usize {
+    ty.ty.fields().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_field(
+    ty: &wasmtime_struct_type_t,
+    index: usize,
+    ret: &mut MaybeUninit<wasmtime_field_type_t>,
+) -> bool {
+    match ty
+        .ty
+        .field(index)
+        .and_then(|f| wasmtime_field_type_t::from_wasmtime(&f))
+    {
+        Some(field) => {
+            crate::initialize(ret, field);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_clone(
+    ty: &wasmtime_struct_type_t,
+) -> Box<wasmtime_struct_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_delete(_ty: Box<wasmtime_struct_type_t>) {}
+
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct wasmtime_array_type_t {
+    pub(crate) ty: ArrayType,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_new(
+    engine: &wasm_engine_t,
+    field: &wasmtime_field_type_t,
+    ret: &mut *mut wasmtime_array_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = field
+        .to_wasmtime()
+        .map(|field| ArrayType::new(&engine.engine, field));
+    handle_result(result, |ty| {
+        *ret = Box::into_raw(Box::new(wasmtime_array_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_field(
+    ty: &wasmtime_array_type_t,
+    ret: &mut MaybeUninit<wasmtime_field_type_t>,
+) -> bool {
+    match wasmtime_field_type_t::from_wasmtime(&ty.ty.field_type()) {
+        Some(field) => {
+            crate::initialize(ret, field);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_clone(
+    ty: &wasmtime_array_type_t,
+) -> Box<wasmtime_array_type_t> {
+    Box::new(ty.clone())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_delete(_ty: Box<wasmtime_array_type_t>) {}
+
+#[repr(transparent)]
+pub struct wasmtime_struct_ref_pre_t {
+    pre: StructRefPre,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_ref_pre_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    ty: &wasmtime_struct_type_t,
+) -> Box<wasmtime_struct_ref_pre_t> {
+    Box::new(wasmtime_struct_ref_pre_t {
+        pre: StructRefPre::new(cx, ty.ty.clone()),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_ref_pre_delete(_pre: Box<wasmtime_struct_ref_pre_t>) {}
+
+#[repr(transparent)]
+pub struct wasmtime_array_ref_pre_t {
+    pre: ArrayRefPre,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_ref_pre_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    ty: &wasmtime_array_type_t,
+) -> Box<wasmtime_array_ref_pre_t> {
+    Box::new(wasmtime_array_ref_pre_t {
+        pre: ArrayRefPre::new(cx, ty.ty.clone()),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_ref_pre_delete(_pre: Box<wasmtime_array_ref_pre_t>) {}
+
+unsafe fn to_anyref(
+    scope: &mut RootScope<impl AsContextMut>,
+    anyref: &wasmtime_anyref_t,
+) -> Result<Rooted<AnyRef>> {
+    match anyref.as_wasmtime() {
+        Some(anyref) => Ok(anyref.to_rooted(scope)),
+        None => bail!("null reference"),
+    }
+}
+
+unsafe fn to_structref(
+    scope: &mut RootScope<impl AsContextMut>,
+    anyref: &wasmtime_anyref_t,
+) -> Result<Rooted<StructRef>> {
+    to_anyref(scope, anyref)?
+        .as_struct(&scope)?
+        .ok_or_else(|| anyhow!("anyref is not a struct"))
+}
+
+unsafe fn to_arrayref(
+    scope: &mut RootScope<impl AsContextMut>,
+    anyref: &wasmtime_anyref_t,
+) -> Result<Rooted<ArrayRef>> {
+    to_anyref(scope, anyref)?
+        .as_array(&scope)?
+        .ok_or_else(|| anyhow!("anyref is not an array"))
+}
+
+unsafe fn to_vals(scope: &mut RootScope<impl AsContextMut>, vals: &[wasmtime_val_t]) -> Vec<Val> {
+    vals.iter().map(|v| v.to_val(scope)).collect()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_is_struct(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+) -> bool {
+    match anyref.and_then(|a| a.as_wasmtime()) {
+        Some(anyref) => anyref
+            .is_struct(&cx)
+            .expect("ManuallyRooted always in scope"),
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_is_array(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+) -> bool {
+    match anyref.and_then(|a| a.as_wasmtime()) {
+        Some(anyref) => anyref
+            .is_array(&cx)
+            .expect("ManuallyRooted always in scope"),
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    pre: &wasmtime_struct_ref_pre_t,
+    fields: *const wasmtime_val_t,
+    nfields: usize,
+    out: &mut MaybeUninit<wasmtime_anyref_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let fields = to_vals(&mut scope, crate::slice_from_raw_parts(fields, nfields));
+    let result = StructRef::new(&mut scope, &pre.pre, &fields)
+        .and_then(|s| s.to_anyref().to_manually_rooted(&mut scope));
+    handle_result(result, |anyref| crate::initialize(out, Some(anyref).into()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_type(
+    cx: WasmtimeStoreContextMut<'_>,
+    structref: &wasmtime_anyref_t,
+    ret: &mut *mut wasmtime_struct_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = to_structref(&mut scope, structref).and_then(|s| s.ty(&scope));
+    handle_result(result, |ty| {
+        *ret = Box::into_raw(Box::new(wasmtime_struct_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_field(
+    cx: WasmtimeStoreContextMut<'_>,
+    structref: &wasmtime_anyref_t,
+    index: usize,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = to_structref(&mut scope, structref).and_then(|s| s.field(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_set_field(
+    cx: WasmtimeStoreContextMut<'_>,
+    structref: &wasmtime_anyref_t,
+    index: usize,
+    val: &wasmtime_val_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let val = val.to_val(&mut scope);
+    let result =
+        to_structref(&mut scope, structref).and_then(|s| s.set_field(&mut scope, index, val));
+    handle_result(result, |()| {})
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    pre: &wasmtime_array_ref_pre_t,
+    elem: &wasmtime_val_t,
+    len: u32,
+    out: &mut MaybeUninit<wasmtime_anyref_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let elem = elem.to_val(&mut scope);
+    let result = ArrayRef::new(&mut scope, &pre.pre, &elem, len)
+        .and_then(|a| a.to_anyref().to_manually_rooted(&mut scope));
+    handle_result(result, |anyref| crate::initialize(out, Some(anyref).into()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_new_fixed(
+    cx: WasmtimeStoreContextMut<'_>,
+    pre: &wasmtime_array_ref_pre_t,
+    elems: *const wasmtime_val_t,
+    nelems: usize,
+    out: &mut MaybeUninit<wasmtime_anyref_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let elems = to_vals(&mut scope, crate::slice_from_raw_parts(elems, nelems));
+    let result = ArrayRef::new_fixed(&mut scope, &pre.pre, &elems)
+        .and_then(|a| a.to_anyref().to_manually_rooted(&mut scope));
+    handle_result(result, |anyref| crate::initialize(out, Some(anyref).into()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_type(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: &wasmtime_anyref_t,
+    ret: &mut *mut wasmtime_array_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = to_arrayref(&mut scope, arrayref).and_then(|a| a.ty(&scope));
+    handle_result(result, |ty| {
+        *ret = Box::into_raw(Box::new(wasmtime_array_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_len(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: &wasmtime_anyref_t,
+    len: &mut MaybeUninit<u32>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = to_arrayref(&mut scope, arrayref).and_then(|a| a.len(&scope));
+    handle_result(result, |l| crate::initialize(len, l))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_get(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: &wasmtime_anyref_t,
+    index: u32,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = to_arrayref(&mut scope, arrayref).and_then(|a| a.get(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_set(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: &wasmtime_anyref_t,
+    index: u32,
+    val: &wasmtime_val_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let val = val.to_val(&mut scope);
+    let result = to_arrayref(&mut scope, arrayref).and_then(|a| a.set(&mut scope, index, val));
+    handle_result(result, |()| {})
+}
@@ -61,6 +61,16 @@ mod r#async;
 #[cfg(feature = "async")]
 pub use crate::r#async::*;
 
+#[cfg(feature = "coredump")]
+mod coredump;
+#[cfg(feature = "coredump")]
+pub use crate::coredump::*;
+
+#[cfg(feature = "gc")]
+mod gc;
+#[cfg(feature = "gc")]
+pub use crate::gc::*;
+
 #[cfg(feature = "wasi")]
 mod wasi;
 #[cfg(feature = "wasi")]
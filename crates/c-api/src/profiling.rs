@@ -1,8 +1,10 @@
-use crate::{wasm_byte_vec_t, wasm_name_t, wasmtime_error_t, wasmtime_module_t, wasmtime_store_t};
-use std::slice;
+use crate::{
+    WasmtimeStoreContext, wasm_byte_vec_t, wasm_name_t, wasmtime_error_t, wasmtime_module_t,
+    wasmtime_store_t,
+};
 use std::str::from_utf8;
 use std::time::Duration;
-use wasmtime::GuestProfiler;
+use wasmtime::{GuestProfiler, Module};
 
 pub struct wasmtime_guestprofiler_t {
     guest_profiler: GuestProfiler,
@@ -24,7 +26,38 @@ pub unsafe extern "C" fn wasmtime_guestprofiler_new(
     modules_len: usize,
 ) -> Box<wasmtime_guestprofiler_t> {
     let module_name = from_utf8(&module_name.as_slice()).expect("not valid utf-8");
-    let list = slice::from_raw_parts(modules, modules_len)
+    let list = profiled_modules(modules, modules_len);
+    Box::new(wasmtime_guestprofiler_t {
+        guest_profiler: GuestProfiler::new(module_name, Duration::from_nanos(interval_nanos), list),
+    })
+}
+
+#[unsafe(no_mangle)]
+#[cfg(feature = "component-model")]
+pub unsafe extern "C" fn wasmtime_guestprofiler_new_component(
+    component_name: &wasm_name_t,
+    interval_nanos: u64,
+    component: &crate::wasmtime_component_t,
+    extra_modules: *const wasmtime_guestprofiler_modules_t,
+    extra_modules_len: usize,
+) -> Box<wasmtime_guestprofiler_t> {
+    let component_name = from_utf8(component_name.as_slice()).expect("not valid utf-8");
+    let list = profiled_modules(extra_modules, extra_modules_len);
+    Box::new(wasmtime_guestprofiler_t {
+        guest_profiler: GuestProfiler::new_component(
+            component_name,
+            Duration::from_nanos(interval_nanos),
+            component.component.clone(),
+            list,
+        ),
+    })
+}
+
+unsafe fn profiled_modules(
+    modules: *const wasmtime_guestprofiler_modules_t,
+    modules_len: usize,
+) -> Vec<(String, Module)> {
+    crate::slice_from_raw_parts(modules, modules_len)
         .iter()
         .map(|entry| {
             (
@@ -34,10 +67,7 @@ pub unsafe extern "C" fn wasmtime_guestprofiler_new(
                 entry.module.module.clone(),
             )
         })
-        .collect::<Vec<_>>();
-    Box::new(wasmtime_guestprofiler_t {
-        guest_profiler: GuestProfiler::new(module_name, Duration::from_nanos(interval_nanos), list),
-    })
+        .collect()
 }
 
 #[unsafe(no_mangle)]
@@ -51,6 +81,17 @@ pub extern "C" fn wasmtime_guestprofiler_sample(
         .sample(&store.store, Duration::from_nanos(delta_nanos));
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_guestprofiler_sample_context(
+    guestprofiler: &mut wasmtime_guestprofiler_t,
+    context: WasmtimeStoreContext<'_>,
+    delta_nanos: u64,
+) {
+    guestprofiler
+        .guest_profiler
+        .sample(context, Duration::from_nanos(delta_nanos));
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn wasmtime_guestprofiler_finish(
     guestprofiler: Box<wasmtime_guestprofiler_t>,
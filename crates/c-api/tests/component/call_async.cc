@@ -0,0 +1,182 @@
+#include "utils.h"
+
+#include <array>
+#include <gtest/gtest.h>
+#include <wasmtime.h>
+#include <wasmtime.hh>
+#include <wasmtime/component/func.hh>
+
+TEST(component, call_async) {
+  static constexpr auto component_text = std::string_view{
+      R"END(
+(component
+    (core module $m
+        (func (export "f") (param $x i32) (param $y i32) (result i32)
+            (local.get $x)
+            (local.get $y)
+            (i32.add)
+        )
+    )
+    (core instance $i (instantiate $m))
+    (func $f (param "x" u32) (param "y" u32) (result u32) (canon lift (core func $i "f")))
+    (export "f" (func $f))
+)
+      )END",
+  };
+  const auto config = wasm_config_new();
+  wasmtime_config_async_support_set(config, true);
+  const auto engine = wasm_engine_new_with_config(config);
+  EXPECT_NE(engine, nullptr);
+
+  const auto store = wasmtime_store_new(engine, nullptr, nullptr);
+  const auto context = wasmtime_store_context(store);
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine, reinterpret_cast<const uint8_t *>(component_text.data()),
+      component_text.size(), &component);
+  CHECK_ERR(err);
+
+  const auto f =
+      wasmtime_component_get_export_index(component, nullptr, "f", 1);
+  EXPECT_NE(f, nullptr);
+
+  const auto linker = wasmtime_component_linker_new(engine);
+
+  wasmtime_component_instance_t instance = {};
+  auto future = wasmtime_component_linker_instantiate_async(
+      linker, context, component, &instance, &err);
+  while (!wasmtime_call_future_poll(future)) {
+  }
+  wasmtime_call_future_delete(future);
+  CHECK_ERR(err);
+
+  wasmtime_component_func_t func = {};
+  const auto found =
+      wasmtime_component_instance_get_func(&instance, context, f, &func);
+  EXPECT_TRUE(found);
+
+  // The function type can be inspected before calling it.
+  const auto func_ty = wasmtime_component_func_type(&func, context);
+  EXPECT_EQ(wasmtime_component_func_type_param_count(func_ty), 2);
+  EXPECT_EQ(wasmtime_component_func_type_result_count(func_ty), 1);
+  wasmtime_component_func_type_delete(func_ty);
+
+  auto params = std::array<wasmtime_component_val_t, 2>{
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_U32,
+          .of = {.u32 = 34},
+      },
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_U32,
+          .of = {.u32 = 35},
+      },
+  };
+
+  auto results = std::array<wasmtime_component_val_t, 1>{};
+
+  future = wasmtime_component_func_call_async(&func, context, params.data(),
+                                              params.size(), results.data(),
+                                              results.size(), &err);
+  while (!wasmtime_call_future_poll(future)) {
+  }
+  wasmtime_call_future_delete(future);
+  CHECK_ERR(err);
+
+  future = wasmtime_component_func_post_return_async(&func, context, &err);
+  while (!wasmtime_call_future_poll(future)) {
+  }
+  wasmtime_call_future_delete(future);
+  CHECK_ERR(err);
+
+  EXPECT_EQ(results[0].kind, WASMTIME_COMPONENT_U32);
+  EXPECT_EQ(results[0].of.u32, 69);
+
+  wasmtime_component_export_index_delete(f);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_delete(component);
+
+  wasmtime_store_delete(store);
+  wasm_engine_delete(engine);
+}
+
+TEST(component, call_async_wrapper) {
+  static constexpr auto component_text = std::string_view{
+      R"END(
+(component
+    (core module $m
+        (func (export "f") (param $x i32) (param $y i32) (result i32)
+            (local.get $x)
+            (local.get $y)
+            (i32.add)
+        )
+    )
+    (core instance $i (instantiate $m))
+    (func $f (param "x" u32) (param "y" u32) (result u32) (canon lift (core func $i "f")))
+    (export "f" (func $f))
+)
+      )END",
+  };
+  wasmtime::Config config;
+  config.async_support(true);
+  wasmtime::Engine engine(std::move(config));
+  wasmtime::Store store(engine);
+  const auto context = store.context().raw_context();
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine.capi(), reinterpret_cast<const uint8_t *>(component_text.data()),
+      component_text.size(), &component);
+  CHECK_ERR(err);
+
+  const auto f =
+      wasmtime_component_get_export_index(component, nullptr, "f", 1);
+  EXPECT_NE(f, nullptr);
+
+  const auto linker = wasmtime_component_linker_new(engine.capi());
+
+  wasmtime_component_instance_t instance = {};
+  auto future = wasmtime_component_linker_instantiate_async(
+      linker, context, component, &instance, &err);
+  while (!wasmtime_call_future_poll(future)) {
+  }
+  wasmtime_call_future_delete(future);
+  CHECK_ERR(err);
+
+  wasmtime_component_func_t raw_func = {};
+  const auto found =
+      wasmtime_component_instance_get_func(&instance, context, f, &raw_func);
+  EXPECT_TRUE(found);
+  wasmtime::component::Func func(raw_func);
+
+  auto params = std::array<wasmtime_component_val_t, 2>{
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_U32,
+          .of = {.u32 = 34},
+      },
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_U32,
+          .of = {.u32 = 35},
+      },
+  };
+  auto results = std::array<wasmtime_component_val_t, 1>{};
+
+  func.call_async(store, params, results).wait().unwrap();
+  func.post_return_async(store).wait().unwrap();
+
+  EXPECT_EQ(results[0].kind, WASMTIME_COMPONENT_U32);
+  EXPECT_EQ(results[0].of.u32, 69);
+
+  // Passing the wrong number of results is reported once the call completes.
+  auto no_results = std::array<wasmtime_component_val_t, 0>{};
+  auto call = func.call_async(store, params, no_results);
+  while (!call.poll()) {
+  }
+  auto result = call.result();
+  EXPECT_FALSE(result);
+  EXPECT_NE(result.err().message(), "");
+
+  wasmtime_component_export_index_delete(f);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_delete(component);
+}
@@ -0,0 +1,180 @@
+#include "utils.h"
+
+#include <array>
+#include <gtest/gtest.h>
+#include <wasmtime.h>
+
+static constexpr auto resource_component_text = std::string_view{
+    R"END(
+(component
+    (import "t" (type $t (sub resource)))
+
+    (core func $drop (canon resource.drop $t))
+
+    (core module $m
+        (import "" "drop" (func $drop (param i32)))
+        (func (export "pass") (param i32) (result i32)
+            (local.get 0))
+        (func (export "drop") (param i32)
+            (call $drop (local.get 0)))
+    )
+    (core instance $i (instantiate $m
+        (with "" (instance (export "drop" (func $drop))))
+    ))
+
+    (func (export "pass") (param "x" (own $t)) (result (own $t))
+        (canon lift (core func $i "pass")))
+    (func (export "drop") (param "x" (own $t))
+        (canon lift (core func $i "drop")))
+)
+    )END",
+};
+
+static wasmtime_error_t *record_drop(void *data, wasmtime_context_t *context,
+                                     uint32_t rep) {
+  *static_cast<uint32_t *>(data) = rep;
+  return nullptr;
+}
+
+TEST(component, resource_type) {
+  const auto a = wasmtime_component_resource_type_new_host(1);
+  const auto b = wasmtime_component_resource_type_new_host(1);
+  const auto c = wasmtime_component_resource_type_new_host(2);
+
+  EXPECT_TRUE(wasmtime_component_resource_type_equal(a, b));
+  EXPECT_FALSE(wasmtime_component_resource_type_equal(a, c));
+
+  const auto d = wasmtime_component_resource_type_clone(c);
+  EXPECT_TRUE(wasmtime_component_resource_type_equal(c, d));
+
+  wasmtime_component_resource_type_delete(a);
+  wasmtime_component_resource_type_delete(b);
+  wasmtime_component_resource_type_delete(c);
+  wasmtime_component_resource_type_delete(d);
+}
+
+TEST(component, resources) {
+  const auto engine = wasm_engine_new();
+  EXPECT_NE(engine, nullptr);
+
+  const auto store = wasmtime_store_new(engine, nullptr, nullptr);
+  const auto context = wasmtime_store_context(store);
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine, reinterpret_cast<const uint8_t *>(resource_component_text.data()),
+      resource_component_text.size(), &component);
+  CHECK_ERR(err);
+
+  uint32_t dropped = 0;
+  const auto linker = wasmtime_component_linker_new(engine);
+  const auto root = wasmtime_component_linker_root(linker);
+  const auto ty = wasmtime_component_resource_type_new_host(1);
+  err = wasmtime_component_linker_instance_add_resource(
+      root, "t", 1, ty, record_drop, &dropped, nullptr);
+  CHECK_ERR(err);
+  wasmtime_component_linker_instance_delete(root);
+
+  wasmtime_component_instance_t instance = {};
+  err = wasmtime_component_linker_instantiate(linker, context, component,
+                                              &instance);
+  CHECK_ERR(err);
+
+  const auto pass_idx =
+      wasmtime_component_get_export_index(component, nullptr, "pass", 4);
+  const auto drop_idx =
+      wasmtime_component_get_export_index(component, nullptr, "drop", 4);
+  wasmtime_component_func_t pass = {};
+  wasmtime_component_func_t drop = {};
+  EXPECT_TRUE(
+      wasmtime_component_instance_get_func(&instance, context, pass_idx, &pass));
+  EXPECT_TRUE(
+      wasmtime_component_instance_get_func(&instance, context, drop_idx, &drop));
+
+  // Round-trip a host resource through the guest.
+  const auto host = wasmtime_component_resource_host_new(true, 100, 1);
+  wasmtime_component_resource_any_t *any = nullptr;
+  err = wasmtime_component_resource_host_to_any(context, host, &any);
+  CHECK_ERR(err);
+  wasmtime_component_resource_host_delete(host);
+
+  const auto any_ty = wasmtime_component_resource_any_type(any);
+  EXPECT_TRUE(wasmtime_component_resource_type_equal(any_ty, ty));
+  EXPECT_TRUE(wasmtime_component_resource_any_owned(any));
+  wasmtime_component_resource_type_delete(any_ty);
+
+  auto params = std::array<wasmtime_component_val_t, 1>{
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_RESOURCE,
+          .of = {.resource = any},
+      },
+  };
+  auto results = std::array<wasmtime_component_val_t, 1>{};
+  err = wasmtime_component_func_call(&pass, context, params.data(),
+                                     params.size(), results.data(),
+                                     results.size());
+  CHECK_ERR(err);
+  err = wasmtime_component_func_post_return(&pass, context);
+  CHECK_ERR(err);
+  wasmtime_component_val_delete(&params[0]);
+
+  EXPECT_EQ(results[0].kind, WASMTIME_COMPONENT_RESOURCE);
+  wasmtime_component_resource_host_t *returned = nullptr;
+  err = wasmtime_component_resource_any_to_host(
+      context, results[0].of.resource, &returned);
+  CHECK_ERR(err);
+  wasmtime_component_val_delete(&results[0]);
+  EXPECT_EQ(wasmtime_component_resource_host_rep(returned), 100);
+  EXPECT_EQ(wasmtime_component_resource_host_type(returned), 1);
+  EXPECT_TRUE(wasmtime_component_resource_host_owned(returned));
+
+  // Dropping the resource in the guest runs the host destructor.
+  err = wasmtime_component_resource_host_to_any(context, returned, &any);
+  CHECK_ERR(err);
+  wasmtime_component_resource_host_delete(returned);
+  params[0] = wasmtime_component_val_t{
+      .kind = WASMTIME_COMPONENT_RESOURCE,
+      .of = {.resource = any},
+  };
+  err = wasmtime_component_func_call(&drop, context, params.data(),
+                                     params.size(), results.data(), 0);
+  CHECK_ERR(err);
+  err = wasmtime_component_func_post_return(&drop, context);
+  CHECK_ERR(err);
+  wasmtime_component_val_delete(&params[0]);
+  EXPECT_EQ(dropped, 100);
+
+  // Dropping a resource from the host also runs the destructor.
+  const auto other = wasmtime_component_resource_host_new(true, 200, 1);
+  err = wasmtime_component_resource_host_to_any(context, other, &any);
+  CHECK_ERR(err);
+  wasmtime_component_resource_host_delete(other);
+  err = wasmtime_component_resource_any_drop(context, any);
+  CHECK_ERR(err);
+  wasmtime_component_resource_any_delete(any);
+
+  // A resource of the wrong type can't be passed in.
+  const auto wrong = wasmtime_component_resource_host_new(true, 300, 2);
+  err = wasmtime_component_resource_host_to_any(context, wrong, &any);
+  CHECK_ERR(err);
+  wasmtime_component_resource_host_delete(wrong);
+  params[0] = wasmtime_component_val_t{
+      .kind = WASMTIME_COMPONENT_RESOURCE,
+      .of = {.resource = any},
+  };
+  err = wasmtime_component_func_call(&pass, context, params.data(),
+                                     params.size(), results.data(),
+                                     results.size());
+  EXPECT_NE(err, nullptr);
+  wasmtime_error_delete(err);
+  wasmtime_component_val_delete(&params[0]);
+
+  wasmtime_component_resource_type_delete(ty);
+  wasmtime_component_export_index_delete(pass_idx);
+  wasmtime_component_export_index_delete(drop_idx);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_delete(component);
+
+  wasmtime_store_delete(store);
+  wasm_engine_delete(engine);
+}
@@ -0,0 +1,191 @@
+#include "utils.h"
+
+#include <gtest/gtest.h>
+#include <string>
+#include <wasmtime.h>
+
+static std::string item_name(const char *name, size_t len) {
+  return std::string(name, len);
+}
+
+TEST(component, types) {
+  static constexpr auto component_text = std::string_view{
+      R"END(
+(component
+    (import "host" (instance
+        (export "f" (func (param "x" string) (result u32)))
+    ))
+    (type $rdef (record (field "a" u8) (field "b" (list string))))
+    (import "r" (type $r (eq $rdef)))
+    (type $v (variant (case "none") (case "some" s64)))
+    (type $e (enum "x" "y" "z"))
+    (type $fl (flags "read" "write"))
+    (export "v" (type $v))
+    (export "e" (type $e))
+    (export "fl" (type $fl))
+    (import "g" (func
+        (param "a" $r)
+        (param "b" (tuple bool char))
+        (param "c" (option f32))
+        (result (result f64 (error u16)))))
+)
+      )END",
+  };
+  const auto engine = wasm_engine_new();
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine, reinterpret_cast<const uint8_t *>(component_text.data()),
+      component_text.size(), &component);
+  CHECK_ERR(err);
+
+  const auto ty = wasmtime_component_type(component);
+  EXPECT_EQ(wasmtime_component_type_import_count(ty), 2);
+  EXPECT_EQ(wasmtime_component_type_export_count(ty), 3);
+
+  // Imports
+  const char *name = nullptr;
+  size_t name_len = 0;
+  wasmtime_component_item_t *item = nullptr;
+  EXPECT_TRUE(
+      wasmtime_component_type_import_nth(ty, 0, &name, &name_len, &item));
+  EXPECT_EQ(item_name(name, name_len), "host");
+  EXPECT_EQ(wasmtime_component_item_kind(item),
+            WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE);
+  EXPECT_EQ(wasmtime_component_item_component_func(item), nullptr);
+  EXPECT_FALSE(
+      wasmtime_component_type_import_nth(ty, 2, &name, &name_len, &item));
+
+  const auto instance_ty = wasmtime_component_item_component_instance(item);
+  wasmtime_component_item_delete(item);
+  EXPECT_EQ(wasmtime_component_instance_type_export_count(instance_ty), 1);
+  item = wasmtime_component_instance_type_export_get(instance_ty, "f", 1);
+  EXPECT_EQ(wasmtime_component_item_kind(item),
+            WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC);
+  auto func_ty = wasmtime_component_item_component_func(item);
+  wasmtime_component_item_delete(item);
+  wasmtime_component_instance_type_delete(instance_ty);
+
+  wasmtime_component_valtype_t *valty = nullptr;
+  EXPECT_EQ(wasmtime_component_func_type_param_count(func_ty), 1);
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func_ty, 0, &name,
+                                                     &name_len, &valty));
+  EXPECT_EQ(item_name(name, name_len), "x");
+  EXPECT_EQ(wasmtime_component_valtype_kind(valty),
+            WASMTIME_COMPONENT_TYPE_STRING);
+  wasmtime_component_valtype_delete(valty);
+  EXPECT_EQ(wasmtime_component_func_type_result_count(func_ty), 1);
+  valty = wasmtime_component_func_type_result_nth(func_ty, 0);
+  EXPECT_EQ(wasmtime_component_valtype_kind(valty),
+            WASMTIME_COMPONENT_TYPE_U32);
+  wasmtime_component_valtype_delete(valty);
+  EXPECT_EQ(wasmtime_component_func_type_result_nth(func_ty, 1), nullptr);
+  wasmtime_component_func_type_delete(func_ty);
+
+  // Exported types
+  EXPECT_EQ(wasmtime_component_type_export_get(ty, "missing", 7), nullptr);
+
+  item = wasmtime_component_type_export_get(ty, "v", 1);
+  EXPECT_EQ(wasmtime_component_item_kind(item), WASMTIME_COMPONENT_ITEM_TYPE);
+  valty = wasmtime_component_item_type(item);
+  wasmtime_component_item_delete(item);
+  EXPECT_EQ(wasmtime_component_valtype_kind(valty),
+            WASMTIME_COMPONENT_TYPE_VARIANT);
+  EXPECT_EQ(wasmtime_component_valtype_variant_case_count(valty), 2);
+  wasmtime_component_valtype_t *payload = nullptr;
+  EXPECT_TRUE(wasmtime_component_valtype_variant_case_nth(
+      valty, 0, &name, &name_len, &payload));
+  EXPECT_EQ(item_name(name, name_len), "none");
+  EXPECT_EQ(payload, nullptr);
+  EXPECT_TRUE(wasmtime_component_valtype_variant_case_nth(
+      valty, 1, &name, &name_len, &payload));
+  EXPECT_EQ(item_name(name, name_len), "some");
+  EXPECT_EQ(wasmtime_component_valtype_kind(payload),
+            WASMTIME_COMPONENT_TYPE_S64);
+  wasmtime_component_valtype_delete(payload);
+  wasmtime_component_valtype_delete(valty);
+
+  item = wasmtime_component_type_export_get(ty, "e", 1);
+  valty = wasmtime_component_item_type(item);
+  wasmtime_component_item_delete(item);
+  EXPECT_EQ(wasmtime_component_valtype_enum_name_count(valty), 3);
+  EXPECT_TRUE(
+      wasmtime_component_valtype_enum_name_nth(valty, 2, &name, &name_len));
+  EXPECT_EQ(item_name(name, name_len), "z");
+  EXPECT_FALSE(
+      wasmtime_component_valtype_enum_name_nth(valty, 3, &name, &name_len));
+  wasmtime_component_valtype_delete(valty);
+
+  item = wasmtime_component_type_export_get(ty, "fl", 2);
+  valty = wasmtime_component_item_type(item);
+  wasmtime_component_item_delete(item);
+  EXPECT_EQ(wasmtime_component_valtype_flags_name_count(valty), 2);
+  EXPECT_TRUE(
+      wasmtime_component_valtype_flags_name_nth(valty, 1, &name, &name_len));
+  EXPECT_EQ(item_name(name, name_len), "write");
+  wasmtime_component_valtype_delete(valty);
+
+  // Imported function
+  item = wasmtime_component_type_import_get(ty, "g", 1);
+  func_ty = wasmtime_component_item_component_func(item);
+  wasmtime_component_item_delete(item);
+  EXPECT_EQ(wasmtime_component_func_type_param_count(func_ty), 3);
+
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func_ty, 0, &name,
+                                                     &name_len, &valty));
+  EXPECT_EQ(item_name(name, name_len), "a");
+  EXPECT_EQ(wasmtime_component_valtype_kind(valty),
+            WASMTIME_COMPONENT_TYPE_RECORD);
+  EXPECT_EQ(wasmtime_component_valtype_record_field_count(valty), 2);
+  wasmtime_component_valtype_t *field = nullptr;
+  EXPECT_TRUE(wasmtime_component_valtype_record_field_nth(valty, 1, &name,
+                                                          &name_len, &field));
+  EXPECT_EQ(item_name(name, name_len), "b");
+  EXPECT_EQ(wasmtime_component_valtype_kind(field),
+            WASMTIME_COMPONENT_TYPE_LIST);
+  const auto element = wasmtime_component_valtype_list_element(field);
+  EXPECT_EQ(wasmtime_component_valtype_kind(element),
+            WASMTIME_COMPONENT_TYPE_STRING);
+  wasmtime_component_valtype_delete(element);
+  wasmtime_component_valtype_delete(field);
+  EXPECT_EQ(wasmtime_component_valtype_list_element(valty), nullptr);
+  wasmtime_component_valtype_delete(valty);
+
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func_ty, 1, &name,
+                                                     &name_len, &valty));
+  EXPECT_EQ(wasmtime_component_valtype_tuple_type_count(valty), 2);
+  const auto second = wasmtime_component_valtype_tuple_type_nth(valty, 1);
+  EXPECT_EQ(wasmtime_component_valtype_kind(second),
+            WASMTIME_COMPONENT_TYPE_CHAR);
+  wasmtime_component_valtype_delete(second);
+  wasmtime_component_valtype_delete(valty);
+
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func_ty, 2, &name,
+                                                     &name_len, &valty));
+  payload = wasmtime_component_valtype_option_payload(valty);
+  EXPECT_EQ(wasmtime_component_valtype_kind(payload),
+            WASMTIME_COMPONENT_TYPE_F32);
+  wasmtime_component_valtype_delete(payload);
+  wasmtime_component_valtype_delete(valty);
+
+  valty = wasmtime_component_func_type_result_nth(func_ty, 0);
+  EXPECT_EQ(wasmtime_component_valtype_kind(valty),
+            WASMTIME_COMPONENT_TYPE_RESULT);
+  const auto ok = wasmtime_component_valtype_result_ok(valty);
+  const auto error = wasmtime_component_valtype_result_err(valty);
+  EXPECT_EQ(wasmtime_component_valtype_kind(ok), WASMTIME_COMPONENT_TYPE_F64);
+  EXPECT_EQ(wasmtime_component_valtype_kind(error),
+            WASMTIME_COMPONENT_TYPE_U16);
+  EXPECT_FALSE(wasmtime_component_valtype_equal(ok, error));
+  const auto ok2 = wasmtime_component_valtype_clone(ok);
+  EXPECT_TRUE(wasmtime_component_valtype_equal(ok, ok2));
+  wasmtime_component_valtype_delete(ok2);
+  wasmtime_component_valtype_delete(ok);
+  wasmtime_component_valtype_delete(error);
+  wasmtime_component_valtype_delete(valty);
+  wasmtime_component_func_type_delete(func_ty);
+
+  wasmtime_component_type_delete(ty);
+  wasmtime_component_delete(component);
+  wasm_engine_delete(engine);
+}
@@ -26,6 +26,11 @@ TEST(PoolAllocationConfig, Smoke) {
   config.max_memories_per_module(18);
   config.max_memory_size(19);
   config.total_gc_heaps(20);
+#ifdef WASMTIME_FEATURE_MEMORY_PROTECTION_KEYS
+  config.memory_protection_keys(MpkEnabled::Disable);
+  config.max_memory_protection_keys(21);
+  PoolAllocationConfig::are_memory_protection_keys_available();
+#endif
 
   PoolAllocationConfig config2 = std::move(config);
   PoolAllocationConfig config3(std::move(config));
@@ -63,6 +68,7 @@ TEST(Config, Smoke) {
   config.memory_init_cow(false);
   config.native_unwind_info(false);
   config.macos_use_mach_ports(false);
+  config.coredump_on_trap(false);
   config.cranelift_flag_enable("foo");
   config.cranelift_flag_set("foo", "bar");
   EXPECT_TRUE(config.cache_load_default());
@@ -0,0 +1,53 @@
+#include <wasmtime/coredump.hh>
+
+#include <gtest/gtest.h>
+#include <wasmtime.hh>
+
+using namespace wasmtime;
+
+static Func trapping_func(Store &store, Engine &engine) {
+  Module m = Module::compile(engine, R"(
+    (module
+      (func $inner unreachable)
+      (func (export "") call $inner)
+    )
+  )")
+                 .unwrap();
+  Instance i = Instance::create(store, m, {}).unwrap();
+  return std::get<Func>(*i.get(store, ""));
+}
+
+TEST(CoreDump, Disabled) {
+  Engine engine;
+  Store store(engine);
+  Func func = trapping_func(store, engine);
+  auto trap = std::get<Trap>(func.call(store, {}).err().data);
+  EXPECT_FALSE(CoreDump::from(trap));
+}
+
+TEST(CoreDump, OnTrap) {
+  Config config;
+  config.coredump_on_trap(true);
+  Engine engine(std::move(config));
+  Store store(engine);
+  Func func = trapping_func(store, engine);
+  auto trap = std::get<Trap>(func.call(store, {}).err().data);
+
+  std::optional<CoreDump> dump = CoreDump::from(trap);
+  ASSERT_TRUE(dump);
+  EXPECT_EQ(dump->frames_count(), 2);
+
+  std::vector<uint8_t> bytes = dump->serialize(store, "test").unwrap();
+  ASSERT_GE(bytes.size(), 8);
+  EXPECT_EQ(bytes[0], 0);
+  EXPECT_EQ(bytes[1], 'a');
+  EXPECT_EQ(bytes[2], 's');
+  EXPECT_EQ(bytes[3], 'm');
+
+  EXPECT_FALSE(dump->serialize(store, std::string_view("\xff", 1)));
+}
+
+TEST(CoreDump, HostError) {
+  Error error("not a trap");
+  EXPECT_FALSE(CoreDump::from(error));
+}
@@ -1,6 +1,7 @@
 #include <wasmtime/engine.hh>
 
 #include <gtest/gtest.h>
+#include <wasmtime.hh>
 
 using namespace wasmtime;
 
@@ -13,3 +14,36 @@ TEST(Engine, Simple) {
   engine2 = Engine();
   engine.is_pulley();
 }
+
+TEST(Engine, PoolingAllocatorMetrics) {
+  EXPECT_FALSE(Engine().pooling_allocator_metrics());
+
+  PoolAllocationConfig pooling;
+  pooling.total_core_instances(2);
+  pooling.total_memories(2);
+  pooling.total_tables(2);
+  pooling.max_memory_size(1 << 16);
+  Config config;
+  config.pooling_allocation_strategy(pooling);
+  Engine engine(std::move(config));
+
+  auto metrics = engine.pooling_allocator_metrics();
+  ASSERT_TRUE(metrics);
+  EXPECT_EQ(metrics->core_instances(), 0);
+  EXPECT_EQ(metrics->memories(), 0);
+
+  Module m = Module::compile(engine, "(module (memory 1) (table 1 funcref))")
+                 .unwrap();
+  {
+    Store store(engine);
+    Instance::create(store, m, {}).unwrap();
+    EXPECT_EQ(metrics->core_instances(), 1);
+    EXPECT_EQ(metrics->component_instances(), 0);
+    EXPECT_EQ(metrics->memories(), 1);
+    EXPECT_EQ(metrics->tables(), 1);
+  }
+  EXPECT_EQ(metrics->core_instances(), 0);
+  EXPECT_EQ(metrics->memories(), 0);
+  EXPECT_EQ(metrics->tables(), 0);
+  EXPECT_EQ(metrics->unused_warm_memories(), 1);
+}
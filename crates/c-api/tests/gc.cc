@@ -0,0 +1,122 @@
+#include <wasmtime/gc.hh>
+
+#include <gtest/gtest.h>
+#include <wasmtime.hh>
+
+using namespace wasmtime;
+
+static Engine gc_engine() {
+  Config config;
+  config.wasm_function_references(true);
+  config.wasm_gc(true);
+  return Engine(std::move(config));
+}
+
+TEST(GC, StructType) {
+  Engine engine = gc_engine();
+  StructType ty =
+      StructType::create(engine, {FieldType(ValKind::I32, true),
+                                  FieldType::i8(false),
+                                  FieldType(ValKind::AnyRef, true)})
+          .unwrap();
+  EXPECT_EQ(ty.field_count(), 3);
+  EXPECT_EQ(ty.field(0)->kind(), ValKind::I32);
+  EXPECT_TRUE(ty.field(0)->is_mutable());
+  EXPECT_TRUE(ty.field(1)->is_i8());
+  EXPECT_EQ(ty.field(1)->kind(), std::nullopt);
+  EXPECT_FALSE(ty.field(1)->is_mutable());
+  EXPECT_EQ(ty.field(2)->kind(), ValKind::AnyRef);
+  EXPECT_EQ(ty.field(3), std::nullopt);
+
+  wasmtime_field_type_t bad = {100, WASM_CONST};
+  EXPECT_FALSE(StructType::create(engine, {FieldType(bad)}));
+}
+
+TEST(GC, StructRef) {
+  Engine engine = gc_engine();
+  Store store(engine);
+  StructType ty = StructType::create(engine, {FieldType(ValKind::I32, true),
+                                              FieldType::i16(false)})
+                      .unwrap();
+  StructRefPre pre(store, ty);
+
+  EXPECT_FALSE(StructRef::create(store, pre, {int32_t(1)}));
+  EXPECT_FALSE(StructRef::create(store, pre, {int64_t(1), int32_t(2)}));
+
+  StructRef s =
+      StructRef::create(store, pre, {int32_t(1), int32_t(-1)}).unwrap();
+  EXPECT_EQ(s.field(store, 0).unwrap().i32(), 1);
+  EXPECT_EQ(s.field(store, 1).unwrap().i32(), 0xffff);
+  EXPECT_FALSE(s.field(store, 2));
+
+  s.set_field(store, 0, int32_t(42)).unwrap();
+  EXPECT_EQ(s.field(store, 0).unwrap().i32(), 42);
+  EXPECT_FALSE(s.set_field(store, 1, int32_t(3)));
+  EXPECT_FALSE(s.set_field(store, 0, int64_t(3)));
+
+  EXPECT_EQ(s.type(store).unwrap().field_count(), 2);
+
+  AnyRef any = s.anyref();
+  EXPECT_TRUE(StructRef::from(store, any));
+  EXPECT_FALSE(ArrayRef::from(store, any));
+  EXPECT_FALSE(StructRef::from(store, AnyRef::i31(store, 1)));
+  s.unroot(store);
+}
+
+TEST(GC, ArrayRef) {
+  Engine engine = gc_engine();
+  Store store(engine);
+  ArrayType ty = ArrayType::create(engine, FieldType::i8(true)).unwrap();
+  EXPECT_TRUE(ty.field()->is_i8());
+  ArrayRefPre pre(store, ty);
+
+  ArrayRef a = ArrayRef::create(store, pre, int32_t(7), 3).unwrap();
+  EXPECT_EQ(a.len(store).unwrap(), 3);
+  EXPECT_EQ(a.get(store, 2).unwrap().i32(), 7);
+  a.set(store, 2, int32_t(0x1ff)).unwrap();
+  EXPECT_EQ(a.get(store, 2).unwrap().i32(), 0xff);
+  EXPECT_FALSE(a.get(store, 3));
+  EXPECT_FALSE(a.set(store, 3, int32_t(0)));
+  EXPECT_TRUE(a.type(store).unwrap().field()->is_mutable());
+  a.unroot(store);
+
+  ArrayRef b = ArrayRef::create(store, pre, {int32_t(1), int32_t(2)}).unwrap();
+  EXPECT_EQ(b.len(store).unwrap(), 2);
+  EXPECT_EQ(b.get(store, 1).unwrap().i32(), 2);
+  EXPECT_TRUE(ArrayRef::from(store, b.anyref()));
+  EXPECT_FALSE(StructRef::from(store, b.anyref()));
+  b.unroot(store);
+}
+
+TEST(GC, Interop) {
+  Engine engine = gc_engine();
+  Module module = Module::compile(engine, R"(
+    (module
+      (type $s (struct (field (mut i32))))
+      (func (export "get") (param anyref) (result i32)
+        (struct.get $s 0 (ref.cast (ref $s) (local.get 0))))
+      (func (export "make") (param i32) (result anyref)
+        (struct.new $s (local.get 0)))
+    )
+  )")
+                      .unwrap();
+  Store store(engine);
+  Instance instance = Instance::create(store, module, {}).unwrap();
+  Func get = std::get<Func>(*instance.get(store, "get"));
+  Func make = std::get<Func>(*instance.get(store, "make"));
+
+  StructType ty =
+      StructType::create(engine, {FieldType(ValKind::I32, true)}).unwrap();
+  StructRefPre pre(store, ty);
+  StructRef s = StructRef::create(store, pre, {int32_t(5)}).unwrap();
+  auto results = get.call(store, {s.anyref()}).unwrap();
+  EXPECT_EQ(results[0].i32(), 5);
+  s.unroot(store);
+
+  results = make.call(store, {int32_t(9)}).unwrap();
+  std::optional<StructRef> made =
+      StructRef::from(store, *results[0].anyref(store));
+  EXPECT_TRUE(made);
+  EXPECT_EQ(made->field(store, 0).unwrap().i32(), 9);
+  made->unroot(store);
+}
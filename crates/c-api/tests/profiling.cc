@@ -0,0 +1,37 @@
+#include <wasmtime/profiling.hh>
+
+#include <gtest/gtest.h>
+#include <wasmtime.hh>
+
+using namespace wasmtime;
+
+TEST(GuestProfiler, Sample) {
+  Engine engine;
+  Module m = Module::compile(engine, R"(
+    (module
+      (import "" "sample" (func $sample))
+      (func $run (export "run") call $sample)
+    )
+  )")
+                 .unwrap();
+  Store store(engine);
+
+  GuestProfiler profiler("test", 1000000, {{"guest", m}});
+  size_t samples = 0;
+  Func sample = Func::wrap(store, [&](Caller caller) {
+    profiler.sample(caller, 10);
+    samples++;
+  });
+  Instance i = Instance::create(store, m, {sample}).unwrap();
+  Func run = std::get<Func>(*i.get(store, "run"));
+  run.call(store, {}).unwrap();
+  run.call(store, {}).unwrap();
+  profiler.sample(store);
+  EXPECT_EQ(samples, 2);
+
+  std::vector<uint8_t> profile = std::move(profiler).finish().unwrap();
+  std::string json(profile.begin(), profile.end());
+  EXPECT_EQ(json.front(), '{');
+  EXPECT_NE(json.find("\"guest\""), std::string::npos);
+  EXPECT_NE(json.find("\"test\""), std::string::npos);
+}
@@ -233,6 +233,21 @@ wasmtime_option_group! {
         pub parallel_compilation: Option<bool>,
         /// Whether to enable proof-carrying code (PCC)-based validation.
         pub pcc: Option<bool>,
+        /// Whether to inline small functions into their callers within the
+        /// same module.
+        pub inlining: Option<bool>,
+        /// Whether accesses to linear memory may reuse or widen the bounds
+        /// checks of earlier accesses to the same index.
+        pub bounds_check_merging: Option<bool>,
+        /// Whether to hoist invariant loads out of loops, unroll small loops
+        /// and strength-reduce induction variables.
+        pub loop_optimizations: Option<bool>,
+        /// Whether to vectorize scalar memory accesses to adjacent addresses,
+        /// and the arithmetic on them, into SIMD instructions.
+        pub slp_vectorization: Option<bool>,
+        /// Whether compiled code counts the executions of functions and
+        /// branches to collect a profile for profile-guided optimization.
+        pub profile_instrumentation: Option<bool>,
         /// Controls whether native unwind information is present in compiled
         /// object files.
         pub native_unwind_info: Option<bool>,
@@ -719,6 +734,31 @@ impl CommonOptions {
             enable => config.cranelift_pcc(enable),
             true => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.inlining]
+            enable => config.compiler_inlining(enable),
+            true => err,
+        }
+        match_feature! {
+            ["cranelift" : self.codegen.bounds_check_merging]
+            enable => config.cranelift_bounds_check_merging(enable),
+            true => err,
+        }
+        match_feature! {
+            ["cranelift" : self.codegen.loop_optimizations]
+            enable => config.cranelift_loop_optimizations(enable),
+            true => err,
+        }
+        match_feature! {
+            ["cranelift" : self.codegen.slp_vectorization]
+            enable => config.cranelift_slp_vectorization(enable),
+            true => err,
+        }
+        match_feature! {
+            ["cranelift" : self.codegen.profile_instrumentation]
+            enable => config.profile_instrumentation(enable),
+            true => err,
+        }
 
         self.enable_wasm_features(&mut config)?;
 
@@ -1119,6 +1159,7 @@ mod tests {
         // Regalloc algorithm
         for (regalloc_value, expected) in [
             ("\"backtracking\"", Some(RegallocAlgorithm::Backtracking)),
+            ("\"single-pass\"", Some(RegallocAlgorithm::SinglePass)),
             ("\"hello\"", None), // should fail
             ("3", None),         // should fail
             ("true", None),      // should fail
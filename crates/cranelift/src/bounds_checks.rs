@@ -31,6 +31,7 @@ use cranelift_codegen::{
     ir::{Expr, Fact},
 };
 use cranelift_frontend::FunctionBuilder;
+use std::collections::HashMap;
 use wasmtime_environ::Unsigned;
 
 /// The kind of bounds check to perform when accessing a Wasm linear memory or
@@ -227,26 +228,41 @@ fn bounds_check_field_access(
                         compare_kind: IntCC,
                         lhs: ir::Value,
                         lhs_off: Option<i64>,
-                        rhs: ir::Value,
-                        rhs_off: Option<i64>| {
+                        rhs: ir::Value| {
         let result = builder.ins().icmp(compare_kind, lhs, rhs);
         if pcc {
-            // Name the original value as a def of the SSA value;
-            // if the value was extended, name that as well with a
-            // dynamic range, overwriting the basic full-range
-            // fact that we previously put on the uextend.
-            builder.func.dfg.facts[orig_index] = Some(Fact::Def { value: orig_index });
+            // Name the original value as a def of the SSA value, or
+            // by its value if it is a constant; if the value was
+            // extended, name that as well with a dynamic range,
+            // overwriting the basic full-range fact that we
+            // previously put on the uextend.
+            let index_expr = match constant_index(&builder.func, orig_index)
+                .and_then(|k| i64::try_from(k).ok())
+            {
+                Some(k) => {
+                    let index_bits = u16::try_from(heap.index_type().bits()).unwrap();
+                    builder.func.dfg.facts[orig_index] =
+                        Some(Fact::constant(index_bits, k.unsigned()));
+                    Expr::constant(k)
+                }
+                None => {
+                    builder.func.dfg.facts[orig_index] = Some(Fact::Def { value: orig_index });
+                    Expr::value(orig_index)
+                }
+            };
+            let exact = |expr: Expr| Fact::DynamicRange {
+                bit_width: pointer_bit_width,
+                min: expr.clone(),
+                max: expr,
+            };
             if index != orig_index {
-                builder.func.dfg.facts[index] = Some(Fact::value(pointer_bit_width, orig_index));
+                builder.func.dfg.facts[index] = Some(exact(index_expr.clone()));
             }
 
             // Create a fact on the LHS that is a "trivial symbolic
             // fact": v1 has range v1+LHS_off..=v1+LHS_off
-            builder.func.dfg.facts[lhs] = Some(Fact::value_offset(
-                pointer_bit_width,
-                orig_index,
-                lhs_off.unwrap(),
-            ));
+            let lhs_expr = Expr::offset(&index_expr, lhs_off.unwrap()).unwrap();
+            builder.func.dfg.facts[lhs] = Some(exact(lhs_expr.clone()));
             // If the RHS is a symbolic value (v1 or gv1), we can
             // emit a Compare fact.
             if let Some(rhs) = builder.func.dfg.facts[rhs]
@@ -255,8 +271,8 @@ fn bounds_check_field_access(
             {
                 builder.func.dfg.facts[result] = Some(Fact::Compare {
                     kind: compare_kind,
-                    lhs: Expr::offset(&Expr::value(orig_index), lhs_off.unwrap()).unwrap(),
-                    rhs: Expr::offset(rhs, rhs_off.unwrap()).unwrap(),
+                    lhs: lhs_expr.clone(),
+                    rhs: rhs.clone(),
                 });
             }
             // Likewise, if the RHS is a constant, we can emit a
@@ -267,8 +283,8 @@ fn bounds_check_field_access(
             {
                 builder.func.dfg.facts[result] = Some(Fact::Compare {
                     kind: compare_kind,
-                    lhs: Expr::offset(&Expr::value(orig_index), lhs_off.unwrap()).unwrap(),
-                    rhs: Expr::constant((k as i64).checked_add(rhs_off.unwrap()).unwrap()),
+                    lhs: lhs_expr,
+                    rhs: Expr::constant(k as i64),
                 });
             }
         }
@@ -371,7 +387,22 @@ fn bounds_check_field_access(
 
     // Special case when the `index` is a constant and statically known to be
     // in-bounds on this memory, no bounds checks necessary.
-    if statically_in_bounds {
+    //
+    // PCC can't prove this for a dynamic memory type, whose bound is only
+    // known symbolically, so in that case fall through to an explicit check.
+    let pcc_dynamic_memory = heap.pcc_memory_type.is_some_and(|ty| {
+        matches!(
+            builder.func.memory_types[ty],
+            ir::MemoryTypeData::DynamicMemory { .. }
+        )
+    });
+    if statically_in_bounds && !pcc_dynamic_memory {
+        if pcc {
+            let k = constant_index(&builder.func, orig_index).unwrap();
+            let index_bits = u16::try_from(heap.index_type().bits()).unwrap();
+            builder.func.dfg.facts[orig_index] = Some(Fact::constant(index_bits, k));
+            builder.func.dfg.facts[index] = Some(Fact::constant(pointer_bit_width, k));
+        }
         return Reachable(compute_addr(
             &mut builder.cursor(),
             heap,
@@ -382,6 +413,45 @@ fn bounds_check_field_access(
         ));
     }
 
+    // Special case for when an earlier access dominating this one checked the
+    // same index against a range that covers this access, or that can be
+    // widened to. See `BoundsCheckCache` for details.
+    let merge_checks = env.merge_bounds_checks() && trap == ir::TrapCode::HEAP_OUT_OF_BOUNDS;
+    if merge_checks {
+        let requires_access = !matches!(oob_behavior, OobBehavior::ExplicitTrap);
+        let may_widen = !env.is_pulley();
+        if let Some(oob) = env.bounds_checks.covering_check(
+            builder,
+            heap,
+            orig_index,
+            offset_and_size,
+            requires_access,
+            may_widen,
+        ) {
+            let addr = compute_addr(
+                &mut builder.cursor(),
+                heap,
+                env.pointer_type(),
+                index,
+                offset,
+                None,
+            );
+            // Without Spectre mitigations, an out-of-bounds index already
+            // trapped at the check, or at the access to null it selected.
+            // With them, this access must not speculatively go out of bounds
+            // either.
+            return Reachable(match oob_behavior {
+                OobBehavior::ConditionallyLoadFromZero {
+                    select_spectre_guard: true,
+                } => {
+                    let null = builder.ins().iconst(env.pointer_type(), 0);
+                    builder.ins().select_spectre_guard(oob, null, addr)
+                }
+                _ => addr,
+            });
+        }
+    }
+
     // Special case for when we can rely on virtual memory, the minimum
     // byte size of this memory fits within the memory reservation, and
     // memory isn't allowed to move. In this situation we know that
@@ -418,9 +488,8 @@ fn bounds_check_field_access(
             index,
             Some(0),
             adjusted_bound_value,
-            Some(0),
         );
-        return Reachable(explicit_check_oob_condition_and_compute_addr(
+        let addr = explicit_check_oob_condition_and_compute_addr(
             env,
             builder,
             heap,
@@ -431,7 +500,29 @@ fn bounds_check_field_access(
             AddrPcc::static32(heap.pcc_memory_type, memory_reservation),
             oob,
             trap,
-        ));
+        );
+        if merge_checks {
+            env.bounds_checks.record(
+                builder,
+                heap,
+                orig_index,
+                CheckedBounds {
+                    oob,
+                    addr,
+                    covered: offset_and_size,
+                    widen: Some(Widen {
+                        iconst: builder
+                            .func
+                            .dfg
+                            .value_def(adjusted_bound_value)
+                            .unwrap_inst(),
+                        reservation: Some(memory_reservation),
+                        max: memory_reservation,
+                    }),
+                },
+            );
+        }
+        return Reachable(addr);
     }
 
     // Special case for when `offset + access_size == 1`:
@@ -452,9 +543,8 @@ fn bounds_check_field_access(
             index,
             Some(0),
             bound,
-            Some(0),
         );
-        return Reachable(explicit_check_oob_condition_and_compute_addr(
+        let addr = explicit_check_oob_condition_and_compute_addr(
             env,
             builder,
             heap,
@@ -465,7 +555,21 @@ fn bounds_check_field_access(
             AddrPcc::dynamic(heap.pcc_memory_type, bound_gv),
             oob,
             trap,
-        ));
+        );
+        if merge_checks {
+            env.bounds_checks.record(
+                builder,
+                heap,
+                orig_index,
+                CheckedBounds {
+                    oob,
+                    addr,
+                    covered: 1,
+                    widen: None,
+                },
+            );
+        }
+        return Reachable(addr);
     }
 
     // Special case for when we know that there are enough guard
@@ -495,15 +599,8 @@ fn bounds_check_field_access(
     // will all emit the same `index > bound` check, which we can GVN.
     if can_use_virtual_memory && offset_and_size <= memory_guard_size {
         let bound = get_dynamic_heap_bound(builder, env, heap);
-        let oob = make_compare(
-            builder,
-            IntCC::UnsignedGreaterThan,
-            index,
-            Some(0),
-            bound,
-            Some(0),
-        );
-        return Reachable(explicit_check_oob_condition_and_compute_addr(
+        let oob = make_compare(builder, IntCC::UnsignedGreaterThan, index, Some(0), bound);
+        let addr = explicit_check_oob_condition_and_compute_addr(
             env,
             builder,
             heap,
@@ -514,7 +611,21 @@ fn bounds_check_field_access(
             AddrPcc::dynamic(heap.pcc_memory_type, bound_gv),
             oob,
             trap,
-        ));
+        );
+        if merge_checks {
+            env.bounds_checks.record(
+                builder,
+                heap,
+                orig_index,
+                CheckedBounds {
+                    oob,
+                    addr,
+                    covered: memory_guard_size,
+                    widen: None,
+                },
+            );
+        }
+        return Reachable(addr);
     }
 
     // Special case for when `offset + access_size <= min_size`.
@@ -546,9 +657,8 @@ fn bounds_check_field_access(
             index,
             Some(0),
             adjusted_bound,
-            Some(adjustment),
         );
-        return Reachable(explicit_check_oob_condition_and_compute_addr(
+        let addr = explicit_check_oob_condition_and_compute_addr(
             env,
             builder,
             heap,
@@ -559,7 +669,25 @@ fn bounds_check_field_access(
             AddrPcc::dynamic(heap.pcc_memory_type, bound_gv),
             oob,
             trap,
-        ));
+        );
+        if merge_checks {
+            env.bounds_checks.record(
+                builder,
+                heap,
+                orig_index,
+                CheckedBounds {
+                    oob,
+                    addr,
+                    covered: offset_and_size,
+                    widen: Some(Widen {
+                        iconst: builder.func.dfg.value_def(adjustment_value).unwrap_inst(),
+                        reservation: None,
+                        max: heap.memory.minimum_byte_size().unwrap_or(u64::MAX),
+                    }),
+                },
+            );
+        }
+        return Reachable(addr);
     }
 
     // General case for dynamic bounds checks:
@@ -591,9 +719,8 @@ fn bounds_check_field_access(
         adjusted_index,
         i64::try_from(offset_and_size).ok(),
         bound,
-        Some(0),
     );
-    Reachable(explicit_check_oob_condition_and_compute_addr(
+    let addr = explicit_check_oob_condition_and_compute_addr(
         env,
         builder,
         heap,
@@ -604,7 +731,25 @@ fn bounds_check_field_access(
         AddrPcc::dynamic(heap.pcc_memory_type, bound_gv),
         oob,
         trap,
-    ))
+    );
+    if merge_checks {
+        env.bounds_checks.record(
+            builder,
+            heap,
+            orig_index,
+            CheckedBounds {
+                oob,
+                addr,
+                covered: offset_and_size,
+                widen: Some(Widen {
+                    iconst: builder.func.dfg.value_def(access_size_val).unwrap_inst(),
+                    reservation: None,
+                    max: u64::MAX,
+                }),
+            },
+        );
+    }
+    Reachable(addr)
 }
 
 /// Get the bound of a dynamic heap as an `ir::Value`.
@@ -866,6 +1011,16 @@ fn compute_addr(
                     max: idx,
                     nullable: false,
                 });
+            } else if let Some(k) = pos.func.dfg.facts[index]
+                .as_ref()
+                .and_then(|f| f.as_const(u16::try_from(addr_ty.bits()).unwrap()))
+            {
+                pos.func.dfg.facts[base_and_index] = Some(Fact::Mem {
+                    ty,
+                    min_offset: k,
+                    max_offset: k,
+                    nullable: false,
+                });
             } else {
                 pos.func.dfg.facts[base_and_index] = Some(Fact::Mem {
                     ty,
@@ -911,6 +1066,19 @@ fn compute_addr(
                         max: Expr::offset(idx, i64::from(offset)).unwrap(),
                         nullable: false,
                     });
+                } else if let Some(k) = pos.func.dfg.facts[index]
+                    .as_ref()
+                    .and_then(|f| f.as_const(u16::try_from(addr_ty.bits()).unwrap()))
+                {
+                    // Can't overflow: the index was checked to be statically
+                    // in bounds together with this offset.
+                    let k = k + u64::from(offset);
+                    pos.func.dfg.facts[result] = Some(Fact::Mem {
+                        ty,
+                        min_offset: k,
+                        max_offset: k,
+                        nullable: false,
+                    });
                 } else {
                     pos.func.dfg.facts[result] = Some(Fact::Mem {
                         ty,
@@ -949,21 +1117,288 @@ fn statically_in_bounds(
     index: ir::Value,
     offset_and_size: u64,
 ) -> bool {
-    func.dfg
-        .value_def(index)
-        .inst()
-        .and_then(|i| {
-            let imm = match func.dfg.insts[i] {
-                ir::InstructionData::UnaryImm {
-                    opcode: ir::Opcode::Iconst,
-                    imm,
-                } => imm,
-                _ => return None,
-            };
-            let ty = func.dfg.value_type(index);
-            let index = imm.zero_extend_from_width(ty.bits()).bits().unsigned();
+    constant_index(func, index)
+        .and_then(|index| {
             let final_addr = index.checked_add(offset_and_size)?;
             Some(final_addr <= heap.memory.minimum_byte_size().unwrap_or(u64::MAX))
         })
         .unwrap_or(false)
 }
+
+/// Returns the value of `index` if it is defined by an `iconst`, zero-extended
+/// from its type's width.
+fn constant_index(func: &ir::Function, index: ir::Value) -> Option<u64> {
+    let i = func.dfg.value_def(index).inst()?;
+    let imm = match func.dfg.insts[i] {
+        ir::InstructionData::UnaryImm {
+            opcode: ir::Opcode::Iconst,
+            imm,
+        } => imm,
+        _ => return None,
+    };
+    let ty = func.dfg.value_type(index);
+    Some(imm.zero_extend_from_width(ty.bits()).bits().unsigned())
+}
+
+/// Explicit bounds checks emitted so far, which later accesses to the same
+/// heap with the same index may reuse instead of emitting their own.
+///
+/// A check that `index + offset_and_size <= bound` covers every access with
+/// the same index and a smaller `offset_and_size` that it dominates: memories
+/// never shrink, so the bound can only have grown since the check, even if
+/// `memory.grow` was called in between. A check is reused in a later block
+/// when the block it was emitted in dominates that one, which is only known
+/// once all the predecessors of the blocks in between have been declared.
+///
+/// An access that isn't covered can still widen the earlier check to cover
+/// it, when the check is in a form whose constant can be adjusted and nothing
+/// between them could tell that an out-of-bounds trap happened early: only
+/// pure instructions, loads, other heap out-of-bounds traps, and branches
+/// that all lead to the access without looping. This merges the checks of a
+/// series of loads at increasing offsets from a common base, such as the
+/// fields of a struct or the elements of an unrolled loop, into a single one.
+#[derive(Default)]
+pub(crate) struct BoundsCheckCache {
+    checks: HashMap<(ir::GlobalValue, ir::Value), (ir::Block, CheckedBounds)>,
+}
+
+/// An explicit bounds check of some index.
+struct CheckedBounds {
+    /// The condition that is true when the index is out of bounds.
+    oob: ir::Value,
+    /// The address computed for the access the check was emitted for.
+    addr: ir::Value,
+    /// The largest `offset + access_size` that the check covers.
+    covered: u64,
+    /// How to widen the check, if its form allows it.
+    widen: Option<Widen>,
+}
+
+/// The constant that a check's covered range depends on.
+struct Widen {
+    /// The `iconst` defining the constant, used by nothing but the check.
+    iconst: ir::Inst,
+    /// The constant is `reservation - offset_and_size` if this is set, and
+    /// `offset_and_size` otherwise.
+    reservation: Option<u64>,
+    /// The largest `offset_and_size` the check's form supports.
+    max: u64,
+}
+
+/// The largest number of blocks between a check and a later access reusing
+/// it, which bounds the work done for each access.
+const MAX_BLOCKS_BETWEEN: usize = 16;
+
+impl BoundsCheckCache {
+    /// Find an earlier check of `index` in a block dominating the current one
+    /// that covers an access of `offset_and_size` bytes, widening it if need
+    /// be and `may_widen`. Returns the check's out-of-bounds condition.
+    ///
+    /// When `requires_access`, the check doesn't trap by itself but through
+    /// the access it selects a null address for, which must then have already
+    /// been emitted.
+    fn covering_check(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        heap: &HeapData,
+        index: ir::Value,
+        offset_and_size: u64,
+        requires_access: bool,
+        may_widen: bool,
+    ) -> Option<ir::Value> {
+        let block = builder.current_block()?;
+        let (check_block, check) = self.checks.get_mut(&(heap.base, index))?;
+        let check_block = *check_block;
+        let between = blocks_between(builder, check_block, block)?;
+
+        let (accessed, mut reorderable) = scan_after_check(builder.func, check_block, check);
+        if requires_access && !accessed {
+            return None;
+        }
+
+        if offset_and_size > check.covered {
+            if check_block != block {
+                reorderable =
+                    reorderable && trap_may_move_past(builder.func, check_block, &between, block);
+            }
+            let widen = check
+                .widen
+                .as_ref()
+                .filter(|widen| may_widen && reorderable && offset_and_size <= widen.max)?;
+            let imm = match widen.reservation {
+                Some(reservation) => reservation - offset_and_size,
+                None => offset_and_size,
+            };
+            builder.func.dfg.insts[widen.iconst] = ir::InstructionData::UnaryImm {
+                opcode: ir::Opcode::Iconst,
+                imm: ir::immediates::Imm64::new(imm as i64),
+            };
+            check.covered = offset_and_size;
+        }
+        Some(check.oob)
+    }
+
+    /// Record the check just emitted in the current block for an access at
+    /// `index`.
+    fn record(
+        &mut self,
+        builder: &FunctionBuilder,
+        heap: &HeapData,
+        index: ir::Value,
+        check: CheckedBounds,
+    ) {
+        if let Some(block) = builder.current_block() {
+            self.checks.insert((heap.base, index), (block, check));
+        }
+    }
+}
+
+/// If `dom` dominates `block`, returns the other blocks on the paths from
+/// `dom` to `block`, at most `MAX_BLOCKS_BETWEEN` of them.
+///
+/// This walks the predecessors back from `block`, which must all have been
+/// declared: a block that isn't sealed yet, such as the header of a loop
+/// whose body is being translated, could still gain a predecessor that
+/// `dom` doesn't dominate.
+fn blocks_between(
+    builder: &FunctionBuilder,
+    dom: ir::Block,
+    block: ir::Block,
+) -> Option<Vec<ir::Block>> {
+    let mut between = Vec::new();
+    let mut stack = vec![block];
+    while let Some(b) = stack.pop() {
+        if b == dom {
+            continue;
+        }
+        // The entry block and unreachable blocks have no predecessors, and
+        // nothing else dominates them.
+        let preds = builder.sealed_predecessors(b)?;
+        if preds.is_empty() {
+            return None;
+        }
+        for &branch in preds {
+            let pred = builder.func.layout.inst_block(branch)?;
+            if pred != dom && pred != block && !between.contains(&pred) {
+                if between.len() == MAX_BLOCKS_BETWEEN {
+                    return None;
+                }
+                between.push(pred);
+                stack.push(pred);
+            }
+        }
+    }
+    Some(between)
+}
+
+/// Scan the instructions of `block` following the computation of `check`'s
+/// condition: returns whether one of them accesses memory at the check's
+/// address, and whether a heap out-of-bounds trap may be moved before all of
+/// them but the branch ending `block`, if any.
+fn scan_after_check(func: &ir::Function, block: ir::Block, check: &CheckedBounds) -> (bool, bool) {
+    let def = func.dfg.value_def(check.oob).unwrap_inst();
+    let (mut next, mut reorderable) = if func.layout.inst_block(def) == Some(block) {
+        (func.layout.next_inst(def), true)
+    } else {
+        // The check branched to a trap and we are in its continuation: there
+        // are instructions we don't scan in between.
+        (func.layout.first_inst(block), false)
+    };
+    let mut accessed = false;
+    while let Some(inst) = next {
+        next = func.layout.next_inst(inst);
+        let data = &func.dfg.insts[inst];
+        let opcode = data.opcode();
+        if (opcode.can_load() || opcode.can_store())
+            && data
+                .memflags()
+                .is_some_and(|flags| flags.trap_code().is_some())
+            && func.dfg.inst_args(inst).contains(&check.addr)
+        {
+            accessed = true;
+        }
+        reorderable &= is_plain_branch(opcode) || may_follow_heap_trap(data);
+    }
+    (accessed, reorderable)
+}
+
+/// Can a heap out-of-bounds trap at the end of `check_block` be moved to the
+/// start of `block`, which it dominates with `between` on the paths between
+/// them, without that being observable?
+///
+/// Every path leaving `check_block` must reach `block`, without looping, and
+/// all the instructions on the way must allow a heap trap to move before
+/// them.
+fn trap_may_move_past(
+    func: &ir::Function,
+    check_block: ir::Block,
+    between: &[ir::Block],
+    block: ir::Block,
+) -> bool {
+    let successors = |b: ir::Block| {
+        func.layout
+            .last_inst(b)
+            .into_iter()
+            .flat_map(|inst| {
+                func.dfg.insts[inst]
+                    .branch_destination(&func.dfg.jump_tables, &func.dfg.exception_tables)
+            })
+            .map(|dest| dest.block(&func.dfg.value_lists))
+    };
+
+    let leads_to_block = |b| successors(b).all(|succ| succ == block || between.contains(&succ));
+    if !leads_to_block(check_block) || !between.iter().all(|&b| leads_to_block(b)) {
+        return false;
+    }
+
+    let reorderable = |b| {
+        func.layout.block_insts(b).all(|inst| {
+            let data = &func.dfg.insts[inst];
+            is_plain_branch(data.opcode()) || may_follow_heap_trap(data)
+        })
+    };
+    if !between.iter().all(|&b| reorderable(b)) || !reorderable(block) {
+        return false;
+    }
+
+    // Check that the blocks in between don't form a loop by repeatedly
+    // removing one that none of the remaining ones branches to.
+    let mut remaining = between.to_vec();
+    while let Some(i) = remaining
+        .iter()
+        .position(|&b| !remaining.iter().any(|&p| successors(p).any(|s| s == b)))
+    {
+        remaining.swap_remove(i);
+    }
+    remaining.is_empty()
+}
+
+/// Is `opcode` a branch that only transfers control to other blocks?
+fn is_plain_branch(opcode: ir::Opcode) -> bool {
+    matches!(
+        opcode,
+        ir::Opcode::Jump | ir::Opcode::Brif | ir::Opcode::BrTable
+    )
+}
+
+/// Can a heap out-of-bounds trap be moved before `data` without that being
+/// observable?
+fn may_follow_heap_trap(data: &ir::InstructionData) -> bool {
+    let opcode = data.opcode();
+    if opcode.can_store()
+        || opcode.is_call()
+        || opcode.is_branch()
+        || opcode.is_terminator()
+        || opcode.other_side_effects()
+    {
+        return false;
+    }
+    let trap_code = match *data {
+        ir::InstructionData::Load { flags, .. } => flags.trap_code(),
+        ir::InstructionData::CondTrap { code, .. }
+        | ir::InstructionData::IntAddTrap { code, .. } => Some(code),
+        _ if opcode.can_load() || opcode.can_trap() => return false,
+        _ => None,
+    };
+    trap_code.is_none_or(|code| code == ir::TrapCode::HEAP_OUT_OF_BOUNDS)
+}
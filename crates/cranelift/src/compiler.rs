@@ -13,6 +13,7 @@ use cranelift_codegen::isa::{
     unwind::{UnwindInfo, UnwindInfoKind},
 };
 use cranelift_codegen::print_errors::pretty_error;
+use cranelift_codegen::settings::OptLevel;
 use cranelift_codegen::{CompiledCode, Context};
 use cranelift_entity::PrimaryMap;
 use cranelift_frontend::FunctionBuilder;
@@ -35,6 +36,7 @@ use wasmtime_environ::{
 
 #[cfg(feature = "component-model")]
 mod component;
+mod inline;
 
 struct IncrementalCacheContext {
     #[cfg(feature = "incremental-cache")]
@@ -211,6 +213,7 @@ impl wasmtime_environ::Compiler for Compiler {
         }
 
         let mut func_env = FuncEnvironment::new(self, translation, types, wasm_func_ty);
+        func_env.set_profiled_function(module.defined_func_index(func_index).unwrap());
 
         // The `stack_limit` global value below is the implementation of stack
         // overflow checks in Wasmtime.
@@ -276,6 +279,15 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
+        if self.tunables.inlining
+            && !self.tunables.generate_native_debuginfo
+            && isa.flags().opt_level() != OptLevel::None
+        {
+            let inliner =
+                inline::ModuleInliner::new(self, translation, types, &context.func, func_index)?;
+            context.set_inliner(inliner);
+        }
+
         let func = compiler.finish_with_info(Some((&body, &self.tunables)), symbol)?;
 
         let timing = cranelift_codegen::timing::take_current();
@@ -285,6 +297,7 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: Box::new(func),
             needs_gc_heap: func_env.needs_gc_heap(),
+            profile_counters: func_env.num_profile_counters(),
         })
     }
 
@@ -364,6 +377,7 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: Box::new(compiler.finish(symbol)?),
             needs_gc_heap: false,
+            profile_counters: 0,
         })
     }
 
@@ -438,6 +452,7 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: Box::new(compiler.finish(&symbol)?),
             needs_gc_heap: false,
+            profile_counters: 0,
         })
     }
 
@@ -659,6 +674,7 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: Box::new(compiler.finish(&symbol)?),
             needs_gc_heap: false,
+            profile_counters: 0,
         })
     }
 
@@ -1632,6 +1632,7 @@ impl ComponentCompiler for Compiler {
             Ok(CompiledFunctionBody {
                 code: Box::new(compiler.finish(&symbol)?),
                 needs_gc_heap: false,
+                profile_counters: 0,
             })
         };
         Ok(AllCallFunc {
@@ -0,0 +1,200 @@
+//! Inlining of calls between functions defined in the same module.
+//!
+//! Each function is compiled independently, so the bodies of the functions it
+//! calls are translated to CLIF here, alongside the caller, and handed to
+//! Cranelift's inliner which decides which calls to actually inline.
+//!
+//! When the module has a profile, functions that it never saw executed are
+//! not inlined, which leaves room to inline larger functions that were.
+
+use crate::compiler::Compiler;
+use crate::func_environ::FuncEnvironment;
+use crate::translate::FuncTranslator;
+use crate::wasm_call_signature;
+use cranelift_codegen::inline::{Inline, InlineCommand, InlineHeuristics};
+use cranelift_codegen::ir::{self, ExternalName, UserExternalName, UserFuncName};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::mem;
+use wasmparser::FuncToValidate;
+use wasmtime_environ::{
+    FuncIndex, FunctionBodyData, ModuleTranslation, ModuleTypesBuilder, WasmResult,
+};
+
+/// The largest Wasm function body, in bytes, that is translated to be
+/// considered for inlining. Cranelift's heuristics make the final decision,
+/// this just avoids translating functions that are certainly too large.
+const MAX_INLINE_BODY_SIZE: usize = 512;
+
+/// How many times larger the callees that are inlined can be when the module
+/// has a profile.
+const PROFILED_SIZE_FACTOR: usize = 4;
+
+/// Provides Cranelift's inliner with the bodies of the Wasm functions called,
+/// directly or through other inlined functions, by the function being
+/// compiled.
+pub(super) struct ModuleInliner {
+    callees: HashMap<FuncIndex, ir::Function>,
+    heuristics: InlineHeuristics,
+}
+
+impl ModuleInliner {
+    /// Translate the small functions called by `caller`, the function
+    /// `caller_index` of `translation`, as deep as the heuristics allow.
+    ///
+    /// Fails if one of those functions fails to translate.
+    pub(super) fn new(
+        compiler: &Compiler,
+        translation: &ModuleTranslation<'_>,
+        types: &ModuleTypesBuilder,
+        caller: &ir::Function,
+        caller_index: FuncIndex,
+    ) -> WasmResult<Self> {
+        let mut heuristics = InlineHeuristics::default();
+        let mut max_body_size = MAX_INLINE_BODY_SIZE;
+        if translation.profile.is_some() {
+            heuristics.max_callee_size *= PROFILED_SIZE_FACTOR;
+            max_body_size *= PROFILED_SIZE_FACTOR;
+        }
+
+        let mut callees = HashMap::new();
+        let mut frontier = direct_callees(caller);
+        for _ in 0..heuristics.max_depth {
+            for index in mem::take(&mut frontier) {
+                if index == caller_index
+                    || callees.contains_key(&index)
+                    || !was_executed(translation, index)
+                {
+                    continue;
+                }
+                let Some(func) =
+                    translate_callee(compiler, translation, types, index, max_body_size)?
+                else {
+                    continue;
+                };
+                frontier.extend(direct_callees(&func));
+                callees.insert(index, func);
+            }
+        }
+        Ok(Self {
+            callees,
+            heuristics,
+        })
+    }
+}
+
+impl Inline for ModuleInliner {
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        _call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        _call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        match wasm_func_index(caller, callee).and_then(|index| self.callees.get(&index)) {
+            Some(func) => InlineCommand::Inline {
+                callee: Cow::Borrowed(func),
+                visit_callee: true,
+            },
+            None => InlineCommand::KeepCall,
+        }
+    }
+
+    fn heuristics(&self) -> InlineHeuristics {
+        self.heuristics
+    }
+}
+
+/// Whether the profile of `translation`, if any, saw the function `index`
+/// executed.
+fn was_executed(translation: &ModuleTranslation<'_>, index: FuncIndex) -> bool {
+    let Some(profile) = &translation.profile else {
+        return true;
+    };
+    translation
+        .module
+        .defined_func_index(index)
+        .and_then(|index| profile.func(index))
+        .is_none_or(|func| func.entries() > 0)
+}
+
+/// The Wasm function referenced by `func_ref` in `func`, if any.
+fn wasm_func_index(func: &ir::Function, func_ref: ir::FuncRef) -> Option<FuncIndex> {
+    match func.dfg.ext_funcs[func_ref].name {
+        ExternalName::User(name) => {
+            let name = &func.params.user_named_funcs()[name];
+            if name.namespace == crate::NS_WASM_FUNC {
+                Some(FuncIndex::from_u32(name.index))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The Wasm functions directly called by `func`.
+fn direct_callees(func: &ir::Function) -> Vec<FuncIndex> {
+    let mut callees = Vec::new();
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            if let ir::InstructionData::Call {
+                opcode: ir::Opcode::Call,
+                func_ref,
+                ..
+            } = func.dfg.insts[inst]
+            {
+                callees.extend(wasm_func_index(func, func_ref));
+            }
+        }
+    }
+    callees
+}
+
+/// Translate the function `index` of `translation` to CLIF, if it is a
+/// function defined in the module whose body is at most `max_body_size` bytes.
+fn translate_callee(
+    compiler: &Compiler,
+    translation: &ModuleTranslation<'_>,
+    types: &ModuleTypesBuilder,
+    index: FuncIndex,
+    max_body_size: usize,
+) -> WasmResult<Option<ir::Function>> {
+    let Some(def_index) = translation.module.defined_func_index(index) else {
+        return Ok(None);
+    };
+    let Some(FunctionBodyData { body, validator }) =
+        translation.inlining_function_bodies.get(def_index)
+    else {
+        return Ok(None);
+    };
+    if body.range().len() > max_body_size {
+        return Ok(None);
+    }
+
+    let sig = translation.module.functions[index]
+        .signature
+        .unwrap_module_type_index();
+    let wasm_func_ty = types[sig].unwrap_func();
+    let mut func = ir::Function::with_name_signature(
+        UserFuncName::User(UserExternalName {
+            namespace: crate::NS_WASM_FUNC,
+            index: index.as_u32(),
+        }),
+        wasm_call_signature(compiler.isa(), wasm_func_ty, compiler.tunables()),
+    );
+
+    // The callee is only ever inlined, so unlike a function compiled on its
+    // own it needs no stack limit check: the caller's covers it.
+    let mut func_env = FuncEnvironment::new(compiler, translation, types, wasm_func_ty);
+    func_env.set_profiled_function(def_index);
+    let mut validator = FuncToValidate {
+        resources: &validator.resources,
+        index: validator.index,
+        ty: validator.ty,
+        features: validator.features,
+    }
+    .into_validator(Default::default());
+    FuncTranslator::new().translate_body(&mut validator, body.clone(), &mut func, &mut func_env)?;
+    Ok(Some(func))
+}
@@ -1,5 +1,7 @@
 mod gc;
+mod profile;
 
+use crate::bounds_checks::BoundsCheckCache;
 use crate::compiler::Compiler;
 use crate::translate::{
     FuncTranslationState, GlobalVariable, Heap, HeapData, StructFieldsVec, TableData, TableSize,
@@ -14,6 +16,7 @@ use cranelift_codegen::ir::types::*;
 use cranelift_codegen::ir::{self, types};
 use cranelift_codegen::ir::{ArgumentPurpose, ConstantData, Function, InstBuilder, MemFlags};
 use cranelift_codegen::isa::{TargetFrontendConfig, TargetIsa};
+use cranelift_codegen::settings::OptLevel;
 use cranelift_entity::packed_option::ReservedValue;
 use cranelift_entity::{EntityRef, PrimaryMap, SecondaryMap};
 use cranelift_frontend::Variable;
@@ -83,6 +86,7 @@ macro_rules! declare_function_signatures {
     )*) => {
         $(impl BuiltinFunctions {
             $( #[$attr] )*
+            #[allow(dead_code, reason = "some builtins are only called by Winch-generated code")]
             pub(crate) fn $name(&mut self, func: &mut Function) -> ir::FuncRef {
                 self.load_builtin(func, BuiltinFunctionIndex::$name())
             }
@@ -174,6 +178,13 @@ pub struct FuncEnvironment<'module_environment> {
     /// always present even if this is a "leaf" function, as we have to call
     /// into the host to trap when signal handlers are disabled.
     pub(crate) stack_limit_at_function_entry: Option<ir::GlobalValue>,
+
+    /// Explicit bounds checks emitted so far, for later accesses to reuse.
+    pub(crate) bounds_checks: BoundsCheckCache,
+
+    /// Execution counting, or the profile guiding the translation, of the
+    /// function.
+    profiling: profile::Profiling<'module_environment>,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -228,6 +239,10 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             translation,
 
             stack_limit_at_function_entry: None,
+
+            bounds_checks: BoundsCheckCache::default(),
+
+            profiling: profile::Profiling::new(translation.profile.as_ref()),
         }
     }
 
@@ -2477,10 +2492,7 @@ impl FuncEnvironment<'_> {
     ) -> (Option<Fact>, Option<ir::MemoryType>) {
         // If we have a declared maximum, we can make this a "static" heap, which is
         // allocated up front and never moved.
-        let host_page_size_log2 = self.target_config().page_size_align_log2;
-        let (base_fact, memory_type) = if !memory
-            .can_elide_bounds_check(self.tunables, host_page_size_log2)
-        {
+        let (base_fact, memory_type) = if !self.memory_is_statically_bounded(&memory) {
             if let Some(ptr_memtype) = ptr_memtype {
                 // Create a memtype representing the untyped memory region.
                 let data_mt = func.create_memory_type(ir::MemoryTypeData::DynamicMemory {
@@ -3183,6 +3195,8 @@ impl FuncEnvironment<'_> {
             self.epoch_function_entry(builder);
         }
 
+        self.profile_function_entry(builder);
+
         #[cfg(feature = "wmemcheck")]
         if self.compiler.wmemcheck {
             let func_name = self.current_func_name(builder);
@@ -3204,6 +3218,7 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_function_exit(builder);
         }
+        self.profile_function_exit();
         Ok(())
     }
 
@@ -3776,13 +3791,49 @@ impl FuncEnvironment<'_> {
         self.tunables.signals_based_traps || self.is_pulley()
     }
 
+    /// Returns whether accesses to linear memory may reuse or widen the
+    /// bounds checks of earlier accesses with the same index.
+    ///
+    /// This is an optimization enabled with `Tunables::bounds_check_merging`,
+    /// and isn't done with proof-carrying code, which can't follow checks from
+    /// one access to another.
+    pub fn merge_bounds_checks(&self) -> bool {
+        self.tunables.bounds_check_merging
+            && self.isa.flags().opt_level() != OptLevel::None
+            && !self.proof_carrying_code()
+    }
+
     /// Returns whether loads from the null address are allowed as signals of
     /// whether to trap or not.
     pub fn load_from_zero_allowed(&self) -> bool {
         // Pulley allows loads-from-zero and otherwise this is only allowed with
-        // traps + spectre mitigations.
+        // traps + spectre mitigations. Proof-carrying code also wants this
+        // since it can reason about a `select` of the address but not about a
+        // `trapnz` guarding its use.
         self.is_pulley()
-            || (self.clif_memory_traps_enabled() && self.heap_access_spectre_mitigation())
+            || (self.clif_memory_traps_enabled()
+                && (self.heap_access_spectre_mitigation() || self.proof_carrying_code()))
+    }
+
+    /// Returns whether accesses to `memory` are bounds-checked against its
+    /// static `memory_reservation` rather than its dynamic current length.
+    ///
+    /// This mirrors the cases in `bounds_checks.rs` which never load the
+    /// memory's length, and determines which kind of PCC memory type the
+    /// memory gets.
+    fn memory_is_statically_bounded(&self, memory: &Memory) -> bool {
+        let host_page_size_log2 = self.target_config().page_size_align_log2;
+        let can_use_virtual_memory = memory
+            .can_use_virtual_memory(self.tunables, host_page_size_log2)
+            && self.clif_memory_traps_enabled();
+        let can_elide_bounds_check = memory
+            .can_elide_bounds_check(self.tunables, host_page_size_log2)
+            && self.clif_memory_traps_enabled();
+        can_elide_bounds_check
+            || (can_use_virtual_memory
+                && memory.minimum_byte_size().unwrap_or(u64::MAX)
+                    <= self.tunables.memory_reservation
+                && !memory.memory_may_move(self.tunables))
     }
 
     /// Returns whether translation is happening for Pulley bytecode.
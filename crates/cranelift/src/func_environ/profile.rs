@@ -0,0 +1,165 @@
+//! Profile-guided optimization of Wasm functions.
+//!
+//! With `Module::profile_instrumentation` each defined function counts its
+//! entries and the executions of its `if` and `br_if` branches into the
+//! counters that the `VMContext` points to, laid out as described by
+//! `FunctionProfile`. Branches are numbered in the order they are translated,
+//! which is the same every time a function is translated, so the counts that
+//! a `ModuleProfile` collected in a previous run can be matched with the
+//! branches when compiling the module again and used to move the blocks that
+//! are rarely executed out of the hot path.
+
+use crate::func_environ::FuncEnvironment;
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{self, InstBuilder, MemFlags};
+use cranelift_frontend::FunctionBuilder;
+use wasmtime_environ::{DefinedFuncIndex, FunctionProfile, ModuleProfile};
+
+/// A successor of a branch is cold when less than one in this many executions
+/// of the branch went to it.
+const COLD_BRANCH_RATIO: u64 = 100;
+
+/// The profiling state of the function being translated.
+pub(crate) struct Profiling<'a> {
+    /// The profile of the module, if its compilation is profile-guided.
+    module: Option<&'a ModuleProfile>,
+    /// The defined function being translated, if profiling applies to it.
+    func: Option<DefinedFuncIndex>,
+    /// The pointer to the counters of the function, if it counts its
+    /// executions.
+    counters: Option<ir::Value>,
+    /// The number of branches translated so far.
+    branches: u32,
+}
+
+/// Which successors of a conditional branch were rarely executed according
+/// to the profile.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct BranchHint {
+    /// The successor executed when the condition is non-zero.
+    pub taken_is_cold: bool,
+    /// The successor executed when the condition is zero.
+    pub not_taken_is_cold: bool,
+}
+
+impl<'a> Profiling<'a> {
+    pub(crate) fn new(module: Option<&'a ModuleProfile>) -> Self {
+        Self {
+            module,
+            func: None,
+            counters: None,
+            branches: 0,
+        }
+    }
+
+    fn func_profile(&self) -> Option<&'a FunctionProfile> {
+        self.module?.func(self.func?)
+    }
+}
+
+impl FuncEnvironment<'_> {
+    /// Declare that the function being translated is the defined function
+    /// `index`, so that it counts its executions or uses its profile, as
+    /// configured.
+    pub(crate) fn set_profiled_function(&mut self, index: DefinedFuncIndex) {
+        self.profiling.func = Some(index);
+    }
+
+    /// The number of execution counters of the function translated.
+    pub(crate) fn num_profile_counters(&self) -> u32 {
+        if self.profiling.counters.is_some() {
+            FunctionProfile::num_counters(self.profiling.branches)
+        } else {
+            0
+        }
+    }
+
+    pub(super) fn profile_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let Some(index) = self.profiling.func else {
+            return;
+        };
+        if !self.module.profile_instrumentation {
+            return;
+        }
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let offset = i32::try_from(self.offsets.vmctx_profile_counters(index)).unwrap();
+        let counters = builder.ins().load(
+            self.pointer_type(),
+            MemFlags::trusted().with_readonly(),
+            vmctx,
+            offset,
+        );
+        self.profiling.counters = Some(counters);
+        let one = builder.ins().iconst(ir::types::I64, 1);
+        self.profile_increment(builder, FunctionProfile::ENTRY_COUNTER, one);
+    }
+
+    pub(super) fn profile_function_exit(&mut self) {
+        let Some(profile) = self.profiling.func_profile() else {
+            return;
+        };
+        let expected = FunctionProfile::num_counters(self.profiling.branches);
+        if profile.counters().len() != usize::try_from(expected).unwrap() {
+            log::warn!(
+                "profile of {:?} does not match its code, its counts were likely \
+                 collected from a different module",
+                self.profiling.func.unwrap(),
+            );
+        }
+    }
+
+    /// Count an execution of the next conditional branch of the function,
+    /// whose condition is `cond`, and return which of its successors are cold
+    /// according to the profile.
+    pub(crate) fn profile_branch(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        cond: ir::Value,
+    ) -> BranchHint {
+        if self.profiling.func.is_none() {
+            return BranchHint::default();
+        }
+        let index = self.profiling.branches;
+        self.profiling.branches += 1;
+
+        if self.profiling.counters.is_some() {
+            let counter = FunctionProfile::branch_counter(index);
+            let one = builder.ins().iconst(ir::types::I64, 1);
+            self.profile_increment(builder, counter, one);
+            let taken = builder.ins().icmp_imm(IntCC::NotEqual, cond, 0);
+            let taken = builder.ins().uextend(ir::types::I64, taken);
+            self.profile_increment(builder, counter + 1, taken);
+        }
+
+        match self
+            .profiling
+            .func_profile()
+            .and_then(|profile| profile.branch(index))
+        {
+            Some(branch) if branch.executed > 0 => BranchHint {
+                taken_is_cold: branch.taken.saturating_mul(COLD_BRANCH_RATIO) < branch.executed,
+                not_taken_is_cold: branch.not_taken().saturating_mul(COLD_BRANCH_RATIO)
+                    < branch.executed,
+            },
+            _ => BranchHint::default(),
+        }
+    }
+
+    /// Add `amount`, an `i64`, to the counter `index` of the function.
+    fn profile_increment(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        index: u32,
+        amount: ir::Value,
+    ) {
+        let counters = self.profiling.counters.unwrap();
+        let offset = i32::try_from(index * 8).unwrap();
+        let count = builder
+            .ins()
+            .load(ir::types::I64, MemFlags::trusted(), counters, offset);
+        let count = builder.ins().iadd(count, amount);
+        builder
+            .ins()
+            .store(MemFlags::trusted(), count, counters, offset);
+    }
+}
@@ -300,8 +300,12 @@ pub fn translate_operator(
         }
         Operator::If { blockty } => {
             let val = state.pop1();
+            let hint = environ.profile_branch(builder, val);
 
             let next_block = builder.create_block();
+            if hint.taken_is_cold {
+                builder.set_cold_block(next_block);
+            }
             let (params, results) = blocktype_params_results(validator, *blockty)?;
             let (destination, else_data) = if params.clone().eq(results.clone()) {
                 // It is possible there is no `else` block, so we will only
@@ -324,6 +328,7 @@ pub fn translate_operator(
                     ElseData::NoElse {
                         branch_inst,
                         placeholder: destination,
+                        is_cold: hint.not_taken_is_cold,
                     },
                 )
             } else {
@@ -331,6 +336,9 @@ pub fn translate_operator(
                 // so we eagerly allocate the `else` block here.
                 let destination = block_with_params(builder, results.clone(), environ)?;
                 let else_block = block_with_params(builder, params.clone(), environ)?;
+                if hint.not_taken_is_cold {
+                    builder.set_cold_block(else_block);
+                }
                 canonicalise_brif(
                     builder,
                     val,
@@ -387,12 +395,16 @@ pub fn translate_operator(
                             ElseData::NoElse {
                                 branch_inst,
                                 placeholder,
+                                is_cold,
                             } => {
                                 let (params, _results) =
                                     blocktype_params_results(validator, blocktype)?;
                                 debug_assert_eq!(params.len(), num_return_values);
                                 let else_block =
                                     block_with_params(builder, params.clone(), environ)?;
+                                if is_cold {
+                                    builder.set_cold_block(else_block);
+                                }
                                 canonicalise_then_jump(
                                     builder,
                                     destination,
@@ -504,7 +516,9 @@ pub fn translate_operator(
             state.popn(return_count);
             state.reachable = false;
         }
-        Operator::BrIf { relative_depth } => translate_br_if(*relative_depth, builder, state),
+        Operator::BrIf { relative_depth } => {
+            translate_br_if(*relative_depth, builder, state, environ)
+        }
         Operator::BrTable { targets } => {
             let default = targets.default();
             let mut min_depth = default;
@@ -3085,6 +3099,7 @@ fn translate_unreachable_operator(
                 ElseData::NoElse {
                     branch_inst: ir::Inst::reserved_value(),
                     placeholder: ir::Block::reserved_value(),
+                    is_cold: false,
                 },
                 0,
                 0,
@@ -3115,10 +3130,14 @@ fn translate_unreachable_operator(
                             ElseData::NoElse {
                                 branch_inst,
                                 placeholder,
+                                is_cold,
                             } => {
                                 let (params, _results) =
                                     blocktype_params_results(validator, blocktype)?;
                                 let else_block = block_with_params(builder, params, environ)?;
+                                if is_cold {
+                                    builder.set_cold_block(else_block);
+                                }
                                 let frame = state.control_stack.last().unwrap();
                                 frame.truncate_value_stack_to_else_params(&mut state.stack);
 
@@ -3734,10 +3753,15 @@ fn translate_br_if(
     relative_depth: u32,
     builder: &mut FunctionBuilder,
     state: &mut FuncTranslationState,
+    environ: &mut FuncEnvironment<'_>,
 ) {
     let val = state.pop1();
+    let hint = environ.profile_branch(builder, val);
     let (br_destination, inputs) = translate_br_if_args(relative_depth, state);
     let next_block = builder.create_block();
+    if hint.not_taken_is_cold {
+        builder.set_cold_block(next_block);
+    }
     canonicalise_brif(builder, val, br_destination, inputs, next_block, &[]);
 
     builder.seal_block(next_block); // The only predecessor is the current block.
@@ -27,6 +27,10 @@ pub enum ElseData {
 
         /// The placeholder block we're replacing.
         placeholder: Block,
+
+        /// Whether the `else` block, if we need one, is rarely executed
+        /// according to the profile of the function.
+        is_cold: bool,
     },
 
     /// We have already allocated an `else` block.
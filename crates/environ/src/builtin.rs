@@ -39,6 +39,9 @@ macro_rules! foreach_builtin_function {
             memory_atomic_wait64(vmctx: vmctx, memory: u32, addr: u64, expected: u64, timeout: u64) -> u64;
             // Invoked when fuel has run out while executing a function.
             out_of_gas(vmctx: vmctx) -> bool;
+            // Invoked every `Tunables::tier_up_threshold` calls of a function
+            // of a module which tiers up.
+            tier_up(vmctx: vmctx) -> bool;
             // Invoked when we reach a new epoch.
             #[cfg(target_has_atomic = "64")]
             new_epoch(vmctx: vmctx) -> u64;
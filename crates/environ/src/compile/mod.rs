@@ -184,6 +184,9 @@ pub struct CompiledFunctionBody {
     /// Whether the compiled function needs a GC heap to run; that is, whether
     /// it reads a struct field, allocates, an array, or etc...
     pub needs_gc_heap: bool,
+    /// The number of execution counters of the function, if it was compiled
+    /// with `Tunables::profile_instrumentation`.
+    pub profile_counters: u32,
 }
 
 /// An implementation of a compiler which can compile WebAssembly functions to
@@ -300,6 +303,7 @@ pub trait Compiler: Send + Sync {
             Aarch64(_) => (Architecture::Aarch64, 0),
             S390x => (Architecture::S390x, 0),
             Riscv64(_) => (Architecture::Riscv64, 0),
+            LoongArch64 => (Architecture::LoongArch64, 0),
             // XXX: the `object` crate won't successfully build an object
             // with relocations and such if it doesn't know the
             // architecture, so just pretend we are riscv64. Yolo!
@@ -6,9 +6,10 @@ use crate::prelude::*;
 use crate::{
     ConstExpr, ConstOp, DataIndex, DefinedFuncIndex, ElemIndex, EngineOrModuleTypeIndex,
     EntityIndex, EntityType, FuncIndex, GlobalIndex, IndexType, InitMemory, MemoryIndex,
-    ModuleInternedTypeIndex, ModuleTypesBuilder, PrimaryMap, SizeOverflow, StaticMemoryInitializer,
-    TableIndex, TableInitialValue, Tag, TagIndex, Tunables, TypeConvert, TypeIndex, Unsigned,
-    WasmError, WasmHeapTopType, WasmHeapType, WasmResult, WasmValType, WasmparserTypeConverter,
+    ModuleInternedTypeIndex, ModuleProfile, ModuleTypesBuilder, PrimaryMap, SizeOverflow,
+    StaticMemoryInitializer, TableIndex, TableInitialValue, Tag, TagIndex, Tunables, TypeConvert,
+    TypeIndex, Unsigned, WasmError, WasmHeapTopType, WasmHeapType, WasmResult, WasmValType,
+    WasmparserTypeConverter,
 };
 use anyhow::{Result, bail};
 use cranelift_entity::packed_option::ReservedValue;
@@ -20,7 +21,7 @@ use std::sync::Arc;
 use wasmparser::{
     CustomSectionReader, DataKind, ElementItems, ElementKind, Encoding, ExternalKind,
     FuncToValidate, FunctionBody, KnownCustom, NameSectionReader, Naming, Parser, Payload, TypeRef,
-    Validator, ValidatorResources, types::Types,
+    ValidPayload, Validator, ValidatorResources, types::Types,
 };
 
 /// Object containing the standalone environment information.
@@ -54,6 +55,12 @@ pub struct ModuleTranslation<'data> {
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// A second set of references to the function bodies, with their own
+    /// validators, used to translate callees when inlining. Only populated
+    /// when `Tunables::inlining` is enabled, because `function_body_inputs`
+    /// is consumed when compiling the module's functions.
+    pub inlining_function_bodies: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
@@ -98,6 +105,10 @@ pub struct ModuleTranslation<'data> {
     /// The type information of the current module made available at the end of the
     /// validation process.
     types: Option<Types>,
+
+    /// The execution counts of a previous run of this module, if available, to
+    /// guide its compilation.
+    pub profile: Option<ModuleProfile>,
 }
 
 impl<'data> ModuleTranslation<'data> {
@@ -190,13 +201,51 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
     ) -> Result<ModuleTranslation<'data>> {
         self.result.wasm = data;
 
+        self.result.module.profile_instrumentation = self.tunables.profile_instrumentation;
+        self.result.module.tier_up = self.tunables.tier_up_threshold != 0;
+
+        if self.tunables.inlining {
+            self.result.inlining_function_bodies =
+                self.inlining_function_bodies(parser.clone(), data)?;
+        }
+
         for payload in parser.parse_all(data) {
             self.translate_payload(payload?)?;
         }
 
+        // Calls to functions of a module which tiers up go through their
+        // `VMFuncRef`s, which must exist for all of them.
+        if self.result.module.tier_up {
+            for index in self.result.module.num_imported_funcs..self.result.module.functions.len() {
+                self.flag_func_escaped(FuncIndex::from_u32(index as u32));
+            }
+        }
+
         Ok(self.result)
     }
 
+    /// Validate the module in `data` a second time, with a fresh validator,
+    /// to get a second validator for each function body.
+    ///
+    /// Function validators can't be duplicated, and the ones in
+    /// `function_body_inputs` are handed off to the compilation of each
+    /// function, so this provides the ones used to translate the same function
+    /// bodies again when inlining them into their callers.
+    fn inlining_function_bodies(
+        &self,
+        parser: Parser,
+        data: &'data [u8],
+    ) -> Result<PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>> {
+        let mut validator = Validator::new_with_features(*self.validator.features());
+        let mut bodies = PrimaryMap::new();
+        for payload in parser.parse_all(data) {
+            if let ValidPayload::Func(validator, body) = validator.payload(&payload?)? {
+                bodies.push(FunctionBodyData { validator, body });
+            }
+        }
+        Ok(bodies)
+    }
+
     fn translate_payload(&mut self, payload: Payload<'data>) -> Result<()> {
         match payload {
             Payload::Version {
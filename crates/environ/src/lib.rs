@@ -30,6 +30,7 @@ mod module;
 mod module_artifacts;
 mod module_types;
 pub mod obj;
+mod profile;
 mod ref_bits;
 mod scopevec;
 mod stack_map;
@@ -49,6 +50,7 @@ pub use crate::hostcall::*;
 pub use crate::module::*;
 pub use crate::module_artifacts::*;
 pub use crate::module_types::*;
+pub use crate::profile::*;
 pub use crate::ref_bits::*;
 pub use crate::scopevec::ScopeVec;
 pub use crate::stack_map::*;
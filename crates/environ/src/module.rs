@@ -339,6 +339,19 @@ pub struct Module {
     /// Does this module need a GC heap to run?
     pub needs_gc_heap: bool,
 
+    /// Do the functions defined in this module count their executions? See
+    /// [`Tunables::profile_instrumentation`](crate::Tunables::profile_instrumentation).
+    pub profile_instrumentation: bool,
+
+    /// Do the functions defined in this module count their calls to trigger
+    /// tiering up? See
+    /// [`Tunables::tier_up_threshold`](crate::Tunables::tier_up_threshold).
+    ///
+    /// All defined functions of such a module escape, and calls between them
+    /// go through their `VMFuncRef`, so the runtime can redirect them to
+    /// optimized code.
+    pub tier_up: bool,
+
     /// Number of functions that "escape" from this module may need to have a
     /// `VMFuncRef` constructed for them.
     ///
@@ -626,6 +639,8 @@ impl TypeTrace for Module {
             num_imported_tags: _,
             num_escaped_funcs: _,
             needs_gc_heap: _,
+            profile_instrumentation: _,
+            tier_up: _,
             functions,
             tables,
             memories: _,
@@ -676,6 +691,8 @@ impl TypeTrace for Module {
             num_imported_tags: _,
             num_escaped_funcs: _,
             needs_gc_heap: _,
+            profile_instrumentation: _,
+            tier_up: _,
             functions,
             tables,
             memories: _,
@@ -18,6 +18,9 @@ pub struct CompiledFunctionInfo {
     pub wasm_func_loc: FunctionLoc,
     /// A trampoline for array callers (e.g. `Func::new`) calling into this function (if needed).
     pub array_to_wasm_trampoline: Option<FunctionLoc>,
+    /// The number of execution counters of this function, see
+    /// [`FunctionProfile`](crate::FunctionProfile).
+    pub profile_counters: u32,
 }
 
 /// Description of where a function is located in the text section of a
@@ -0,0 +1,164 @@
+//! Execution counts collected for profile-guided optimization.
+//!
+//! A module compiled with [`Tunables::profile_instrumentation`] counts, in
+//! each of its defined functions, how often the function was entered and,
+//! for each conditional branch (`if` and `br_if`) in the order they appear in
+//! the function body, how often the branch was executed and how often it was
+//! taken. These counts are saved as a [`ModuleProfile`] which is then used to
+//! guide a later compilation of the same module.
+//!
+//! [`Tunables::profile_instrumentation`]: crate::Tunables::profile_instrumentation
+
+use crate::prelude::*;
+use crate::{DefinedFuncIndex, PrimaryMap};
+use anyhow::{Result, bail};
+use serde_derive::{Deserialize, Serialize};
+
+/// The execution counts of the functions defined in a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleProfile {
+    funcs: PrimaryMap<DefinedFuncIndex, FunctionProfile>,
+}
+
+/// The execution counts of a single function.
+///
+/// The counters of a function are laid out as:
+///
+/// * the number of times the function was entered, followed by
+/// * for each branch, the number of times it was executed and the number of
+///   times it was taken.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionProfile {
+    counters: Vec<u64>,
+}
+
+/// The execution counts of a conditional branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BranchProfile {
+    /// The number of times the branch was executed.
+    pub executed: u64,
+    /// The number of times the branch was taken: the condition of an `if` or
+    /// `br_if` was non-zero.
+    pub taken: u64,
+}
+
+impl BranchProfile {
+    /// The number of times the branch was not taken.
+    pub fn not_taken(&self) -> u64 {
+        self.executed.saturating_sub(self.taken)
+    }
+}
+
+/// The bytes that start a serialized [`ModuleProfile`].
+const MAGIC: &[u8] = b"\0wasmtime-profile";
+
+/// The version of the serialized format, which comes after [`MAGIC`].
+const VERSION: u32 = 1;
+
+impl ModuleProfile {
+    /// Creates a profile from the counts of each function.
+    pub fn new(funcs: PrimaryMap<DefinedFuncIndex, FunctionProfile>) -> Self {
+        Self { funcs }
+    }
+
+    /// The counts of the defined function `index`, if the profile has them.
+    pub fn func(&self, index: DefinedFuncIndex) -> Option<&FunctionProfile> {
+        self.funcs.get(index)
+    }
+
+    /// The number of functions in this profile.
+    pub fn num_funcs(&self) -> usize {
+        self.funcs.len()
+    }
+
+    /// Serializes this profile to bytes that [`ModuleProfile::from_bytes`]
+    /// accepts.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        postcard::to_extend(self, bytes).unwrap()
+    }
+
+    /// Deserializes a profile serialized with [`ModuleProfile::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let Some(bytes) = bytes.strip_prefix(MAGIC) else {
+            bail!("not a wasmtime profile");
+        };
+        let Some((version, bytes)) = bytes.split_first_chunk() else {
+            bail!("truncated wasmtime profile");
+        };
+        let version = u32::from_le_bytes(*version);
+        if version != VERSION {
+            bail!("unsupported wasmtime profile version {version}, expected {VERSION}");
+        }
+        Ok(postcard::from_bytes(bytes)?)
+    }
+}
+
+impl FunctionProfile {
+    /// The index of the counter of the entries of a function.
+    pub const ENTRY_COUNTER: u32 = 0;
+
+    /// Creates the profile of a function from its counters.
+    pub fn new(counters: Vec<u64>) -> Self {
+        Self { counters }
+    }
+
+    /// The index of the counter of executions of the branch `index` of a
+    /// function. The times it was taken are counted by the next counter.
+    pub fn branch_counter(index: u32) -> u32 {
+        1 + 2 * index
+    }
+
+    /// The number of counters of a function with `branches` branches.
+    pub fn num_counters(branches: u32) -> u32 {
+        Self::branch_counter(branches)
+    }
+
+    /// The number of times the function was entered.
+    pub fn entries(&self) -> u64 {
+        self.counters.first().copied().unwrap_or(0)
+    }
+
+    /// The counts of the branch `index` of the function, if the profile has
+    /// them.
+    pub fn branch(&self, index: u32) -> Option<BranchProfile> {
+        let counter = usize::try_from(Self::branch_counter(index)).ok()?;
+        match self.counters.get(counter..counter + 2)? {
+            &[executed, taken] => Some(BranchProfile { executed, taken }),
+            _ => None,
+        }
+    }
+
+    /// The counters of the function.
+    pub fn counters(&self) -> &[u64] {
+        &self.counters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut funcs = PrimaryMap::new();
+        funcs.push(FunctionProfile::new(vec![10, 10, 3]));
+        funcs.push(FunctionProfile::new(vec![0]));
+        let profile = ModuleProfile::new(funcs);
+        let bytes = profile.to_bytes();
+        assert_eq!(ModuleProfile::from_bytes(&bytes).unwrap(), profile);
+
+        let func = profile.func(DefinedFuncIndex::from_u32(0)).unwrap();
+        assert_eq!(func.entries(), 10);
+        let branch = func.branch(0).unwrap();
+        assert_eq!(
+            (branch.executed, branch.taken, branch.not_taken()),
+            (10, 3, 7)
+        );
+        assert!(func.branch(1).is_none());
+
+        assert!(ModuleProfile::from_bytes(b"garbage").is_err());
+        assert!(ModuleProfile::from_bytes(&bytes[..MAGIC.len() + 2]).is_err());
+    }
+}
@@ -121,6 +121,25 @@ define_tunables! {
 
         /// Whether CoW images might be used to initialize linear memories.
         pub memory_init_cow: bool,
+
+        /// Whether small functions are inlined into their callers within the
+        /// same module.
+        pub inlining: bool,
+
+        /// Whether accesses to linear memory may reuse or widen the bounds
+        /// checks of earlier accesses to the same index.
+        pub bounds_check_merging: bool,
+
+        /// Whether Wasm functions count their entries and the executions of
+        /// their branches, to collect a `ModuleProfile` for profile-guided
+        /// optimization.
+        pub profile_instrumentation: bool,
+
+        /// When nonzero, Wasm functions count their calls and invoke the
+        /// `tier_up` builtin once every `tier_up_threshold` calls so that the
+        /// runtime can switch hot code to the optimizing compiler. Must be a
+        /// power of two.
+        pub tier_up_threshold: u32,
     }
 
     pub struct ConfigTunables {
@@ -191,6 +210,10 @@ impl Tunables {
             winch_callable: false,
             signals_based_traps: false,
             memory_init_cow: true,
+            inlining: false,
+            bounds_check_merging: false,
+            profile_instrumentation: false,
+            tier_up_threshold: 0,
         }
     }
 
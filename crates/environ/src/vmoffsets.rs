@@ -31,11 +31,13 @@
 //      globals: [VMGlobalDefinition; module.num_defined_globals],
 //      tags: [VMTagDefinition; module.num_defined_tags],
 //      func_refs: [VMFuncRef; module.num_escaped_funcs],
+//      profile_counters: [*mut u64; module.num_profiled_funcs],
 // }
 
 use crate::{
-    DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex, FuncIndex,
-    FuncRefIndex, GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex, TagIndex,
+    DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex,
+    FuncIndex, FuncRefIndex, GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex,
+    TagIndex,
 };
 use cranelift_entity::packed_option::ReservedValue;
 
@@ -83,6 +85,9 @@ pub struct VMOffsets<P> {
     /// The number of escaped functions in the module, the size of the func_refs
     /// array.
     pub num_escaped_funcs: u32,
+    /// The number of functions with execution counters in the module, the size
+    /// of the profile_counters array.
+    pub num_profiled_funcs: u32,
 
     // precalculated offsets of various member fields
     imported_functions: u32,
@@ -96,6 +101,7 @@ pub struct VMOffsets<P> {
     defined_globals: u32,
     defined_tags: u32,
     defined_func_refs: u32,
+    profile_counters: u32,
     size: u32,
 }
 
@@ -472,6 +478,9 @@ pub struct VMOffsetsFields<P> {
     /// The number of escaped functions in the module, the size of the function
     /// references array.
     pub num_escaped_funcs: u32,
+    /// The number of functions with execution counters in the module, the size
+    /// of the profile counters array.
+    pub num_profiled_funcs: u32,
 }
 
 impl<P: PtrSize> VMOffsets<P> {
@@ -498,6 +507,11 @@ impl<P: PtrSize> VMOffsets<P> {
             num_defined_globals: cast_to_u32(module.globals.len() - module.num_imported_globals),
             num_defined_tags: cast_to_u32(module.tags.len() - module.num_imported_tags),
             num_escaped_funcs: cast_to_u32(module.num_escaped_funcs),
+            num_profiled_funcs: if module.profile_instrumentation || module.tier_up {
+                cast_to_u32(module.functions.len() - module.num_imported_funcs)
+            } else {
+                0
+            },
         })
     }
 
@@ -529,6 +543,7 @@ impl<P: PtrSize> VMOffsets<P> {
                     num_defined_tags: _,
                     num_owned_memories: _,
                     num_escaped_funcs: _,
+                    num_profiled_funcs: _,
 
                     // used as the initial size below
                     size,
@@ -557,6 +572,7 @@ impl<P: PtrSize> VMOffsets<P> {
         }
 
         calculate_sizes! {
+            profile_counters: "profile counters",
             defined_func_refs: "module functions",
             defined_tags: "defined tags",
             defined_globals: "defined globals",
@@ -587,6 +603,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             num_defined_globals: fields.num_defined_globals,
             num_defined_tags: fields.num_defined_tags,
             num_escaped_funcs: fields.num_escaped_funcs,
+            num_profiled_funcs: fields.num_profiled_funcs,
             imported_functions: 0,
             imported_tables: 0,
             imported_memories: 0,
@@ -598,6 +615,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             defined_globals: 0,
             defined_tags: 0,
             defined_func_refs: 0,
+            profile_counters: 0,
             size: 0,
         };
 
@@ -656,6 +674,8 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
                 ret.num_escaped_funcs,
                 ret.ptr.size_of_vm_func_ref(),
             ),
+            size(profile_counters)
+                = cmul(ret.num_profiled_funcs, ret.ptr.size()),
         }
 
         ret.size = next_field_offset;
@@ -862,6 +882,12 @@ impl<P: PtrSize> VMOffsets<P> {
         self.defined_func_refs
     }
 
+    /// The offset of the `profile_counters` array.
+    #[inline]
+    pub fn vmctx_profile_counters_begin(&self) -> u32 {
+        self.profile_counters
+    }
+
     /// Return the size of the `VMContext` allocation.
     #[inline]
     pub fn size_of_vmctx(&self) -> u32 {
@@ -954,6 +980,24 @@ impl<P: PtrSize> VMOffsets<P> {
         self.vmctx_func_refs_begin() + index.as_u32() * u32::from(self.ptr.size_of_vm_func_ref())
     }
 
+    /// Return the offset to the pointer to the execution counters of the
+    /// defined function `index`.
+    #[inline]
+    pub fn vmctx_profile_counters(&self, index: DefinedFuncIndex) -> u32 {
+        assert!(index.as_u32() < self.num_profiled_funcs);
+        self.vmctx_profile_counters_begin() + index.as_u32() * u32::from(self.ptr.size())
+    }
+
+    /// Return the offset to the 32-bit call counter of the defined function
+    /// `index` in a module which tiers up.
+    ///
+    /// Such modules don't have execution counters, so each instance keeps
+    /// the call counters of its functions in place of the pointers to them.
+    #[inline]
+    pub fn vmctx_tier_up_counter(&self, index: DefinedFuncIndex) -> u32 {
+        self.vmctx_profile_counters(index)
+    }
+
     /// Return the offset to the `wasm_call` field in `*const VMFunctionBody` index `index`.
     #[inline]
     pub fn vmctx_vmfunction_import_wasm_call(&self, index: FuncIndex) -> u32 {
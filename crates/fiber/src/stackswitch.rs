@@ -24,6 +24,9 @@ cfg_if::cfg_if! {
     } else if #[cfg(target_arch = "riscv64")]  {
         mod riscv64;
         pub(crate) use supported::*;
+    } else if #[cfg(target_arch = "loongarch64")] {
+        mod loongarch64;
+        pub(crate) use supported::*;
     } else {
         // No support for this platform. Don't fail compilation though and
         // instead defer the error to happen at runtime when a fiber is created.
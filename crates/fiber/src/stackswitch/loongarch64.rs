@@ -0,0 +1,139 @@
+// A WORD OF CAUTION
+//
+// This entire file basically needs to be kept in sync with itself. It's not
+// really possible to modify just one bit of this file without understanding
+// all the other bits. Documentation tries to reference various bits here and
+// there but try to make sure to read over everything before tweaking things!
+
+use wasmtime_asm_macros::asm_func;
+
+// fn(top_of_stack(a0): *mut u8)
+asm_func!(
+    wasmtime_versioned_export_macros::versioned_stringify_ident!(wasmtime_fiber_switch),
+    "
+      // We're switching to arbitrary code somewhere else, so pessimistically
+      // assume that all callee-save register are clobbered. This means we need
+      // to save/restore all of them.
+      //
+      // Note that this order for saving is important since we use CFI directives
+      // below to point to where all the saved registers are.
+      st.d $ra,$sp,-0x8
+      st.d $fp,$sp,-0x10
+      st.d $s0,$sp,-0x18
+      st.d $s1,$sp,-0x20
+      st.d $s2,$sp,-0x28
+      st.d $s3,$sp,-0x30
+      st.d $s4,$sp,-0x38
+      st.d $s5,$sp,-0x40
+      st.d $s6,$sp,-0x48
+      st.d $s7,$sp,-0x50
+      st.d $s8,$sp,-0x58
+      fst.d $fs0,$sp,-0x60
+      fst.d $fs1,$sp,-0x68
+      fst.d $fs2,$sp,-0x70
+      fst.d $fs3,$sp,-0x78
+      fst.d $fs4,$sp,-0x80
+      fst.d $fs5,$sp,-0x88
+      fst.d $fs6,$sp,-0x90
+      fst.d $fs7,$sp,-0x98
+      addi.d $sp,$sp,-0xa0
+
+      ld.d $t0,$a0,-0x10
+      st.d $sp,$a0,-0x10
+
+      // Swap stacks and restore all our callee-saved registers
+      move $sp,$t0
+
+      fld.d $fs7,$sp,0x8
+      fld.d $fs6,$sp,0x10
+      fld.d $fs5,$sp,0x18
+      fld.d $fs4,$sp,0x20
+      fld.d $fs3,$sp,0x28
+      fld.d $fs2,$sp,0x30
+      fld.d $fs1,$sp,0x38
+      fld.d $fs0,$sp,0x40
+      ld.d $s8,$sp,0x48
+      ld.d $s7,$sp,0x50
+      ld.d $s6,$sp,0x58
+      ld.d $s5,$sp,0x60
+      ld.d $s4,$sp,0x68
+      ld.d $s3,$sp,0x70
+      ld.d $s2,$sp,0x78
+      ld.d $s1,$sp,0x80
+      ld.d $s0,$sp,0x88
+      ld.d $fp,$sp,0x90
+      ld.d $ra,$sp,0x98
+      addi.d $sp,$sp,0xa0
+      jr $ra
+  ",
+);
+
+// fn(
+//    top_of_stack(a0): *mut u8,
+//    entry_point(a1): extern fn(*mut u8, *mut u8),
+//    entry_arg0(a2): *mut u8,
+// )
+#[rustfmt::skip]
+asm_func!(
+    wasmtime_versioned_export_macros::versioned_stringify_ident!(wasmtime_fiber_init),
+    "
+      la.local $t0,{}
+      st.d $t0,$a0,-0x18  // ra,first should be wasmtime_fiber_start.
+      st.d $a0,$a0,-0x20  // fp pointer.
+      st.d $a1,$a0,-0x28  // entry_point will load to s0.
+      st.d $a2,$a0,-0x30  // entry_arg0 will load to s1.
+
+      //
+      addi.d $t0,$a0,-0xb0
+      st.d $t0,$a0,-0x10
+      jr $ra
+    ",
+    sym super::wasmtime_fiber_start,
+);
+
+asm_func!(
+    wasmtime_versioned_export_macros::versioned_stringify_ident!(wasmtime_fiber_start),
+    "
+    .cfi_startproc simple
+    .cfi_def_cfa_offset 0
+
+
+    .cfi_escape 0x0f, /* DW_CFA_def_cfa_expression */ \
+      5,             /* the byte length of this expression */ \
+      0x53,          /* DW_OP_reg3 (sp) */ \
+      0x06,          /* DW_OP_deref */ \
+      0x08, 0xa0 ,   /* DW_OP_const1u 0xa0 */ \
+      0x22           /* DW_OP_plus */
+
+
+      // DWARF register numbers: ra is 1, fp is 22, s0-s8 are 23-31 and
+      // fs0-fs7 are 56-63.
+      .cfi_rel_offset 1,-0x8
+      .cfi_rel_offset 22,-0x10
+      .cfi_rel_offset 23,-0x18
+      .cfi_rel_offset 24,-0x20
+      .cfi_rel_offset 25,-0x28
+      .cfi_rel_offset 26,-0x30
+      .cfi_rel_offset 27,-0x38
+      .cfi_rel_offset 28,-0x40
+      .cfi_rel_offset 29,-0x48
+      .cfi_rel_offset 30,-0x50
+      .cfi_rel_offset 31,-0x58
+      .cfi_rel_offset 56,-0x60
+      .cfi_rel_offset 57,-0x68
+      .cfi_rel_offset 58,-0x70
+      .cfi_rel_offset 59,-0x78
+      .cfi_rel_offset 60,-0x80
+      .cfi_rel_offset 61,-0x88
+      .cfi_rel_offset 62,-0x90
+      .cfi_rel_offset 63,-0x98
+
+      move $a0,$s1
+      move $a1,$fp
+      jirl $ra,$s0,0
+      // .4byte 0 will cause panic.
+      // for safety just like x86_64.rs.
+      .4byte 0
+      .cfi_endproc
+  ",
+);
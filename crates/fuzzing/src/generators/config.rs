@@ -145,6 +145,7 @@ impl Config {
 
             hogs_memory: _,
             nan_canonicalization: _,
+            bounds_check_merging: _,
             gc_types: _,
             stack_switching: _,
             spec_test: _,
@@ -357,6 +358,7 @@ impl Config {
             }
 
             cfg.codegen.pcc = Some(pcc);
+            cfg.codegen.bounds_check_merging = Some(self.wasmtime.bounds_check_merging);
 
             // Eager init is currently only supported on Cranelift, not Winch.
             cfg.opts.table_lazy_init = Some(self.wasmtime.table_lazy_init);
@@ -569,6 +571,9 @@ pub struct WasmtimeConfig {
     /// Whether or not fuzzing should enable PCC.
     pcc: bool,
 
+    /// Whether or not accesses to linear memory may share bounds checks.
+    bounds_check_merging: bool,
+
     /// Configuration for whether wasm is invoked in an async fashion and how
     /// it's cooperatively time-sliced.
     pub async_config: AsyncConfig,
@@ -812,13 +817,7 @@ impl RegallocAlgorithm {
     fn to_wasmtime(&self) -> wasmtime::RegallocAlgorithm {
         match self {
             RegallocAlgorithm::Backtracking => wasmtime::RegallocAlgorithm::Backtracking,
-            // Note: we have disabled `single_pass` for now because of
-            // its limitations w.r.t. exception handling
-            // (https://github.com/bytecodealliance/regalloc2/issues/217). To
-            // avoid breaking all existing fuzzbugs by changing the
-            // `arbitrary` mappings, we keep the `RegallocAlgorithm`
-            // enum as it is and remap here to `Backtracking`.
-            RegallocAlgorithm::SinglePass => wasmtime::RegallocAlgorithm::Backtracking,
+            RegallocAlgorithm::SinglePass => wasmtime::RegallocAlgorithm::SinglePass,
         }
     }
 }
@@ -144,6 +144,16 @@ fn riscv_flush_icache(start: u64, end: u64) -> Result<()> {
     }
 }
 
+#[cfg(all(target_arch = "loongarch64", target_os = "linux"))]
+fn loongarch_flush_icache() {
+    // LoongArch keeps the instruction and data caches coherent in hardware,
+    // matching what Linux's own `flush_icache_range` assumes, so all that's
+    // needed is an `ibar` to discard any stale instructions in the pipeline.
+    unsafe {
+        core::arch::asm!("ibar 0", options(nostack, preserves_flags));
+    }
+}
+
 pub(crate) use details::*;
 
 /// See docs on [crate::clear_cache] for a description of what this function is trying to do.
@@ -157,5 +167,7 @@ pub(crate) fn clear_cache(_ptr: *const c_void, _len: usize) -> Result<()> {
     // See: https://github.com/bytecodealliance/wasmtime/issues/3310
     #[cfg(all(target_arch = "riscv64", target_os = "linux"))]
     riscv_flush_icache(_ptr as u64, (_ptr as u64) + (_len as u64))?;
+    #[cfg(all(target_arch = "loongarch64", target_os = "linux"))]
+    loongarch_flush_icache();
     Ok(())
 }
@@ -43,6 +43,7 @@ pub fn apply_test_config(config: &mut Config, test_config: &wast::TestConfig) {
         component_model_error_context,
         component_model_gc,
         nan_canonicalization,
+        bounds_check_merging,
         simd,
         exceptions,
         legacy_exceptions,
@@ -70,6 +71,7 @@ pub fn apply_test_config(config: &mut Config, test_config: &wast::TestConfig) {
     let component_model_error_context = component_model_error_context.unwrap_or(false);
     let component_model_gc = component_model_gc.unwrap_or(false);
     let nan_canonicalization = nan_canonicalization.unwrap_or(false);
+    let bounds_check_merging = bounds_check_merging.unwrap_or(false);
     let relaxed_simd = relaxed_simd.unwrap_or(false);
     let legacy_exceptions = legacy_exceptions.unwrap_or(false);
     let stack_switching = stack_switching.unwrap_or(false);
@@ -106,7 +108,8 @@ pub fn apply_test_config(config: &mut Config, test_config: &wast::TestConfig) {
         .wasm_component_model_gc(component_model_gc)
         .wasm_exceptions(exceptions)
         .wasm_stack_switching(stack_switching)
-        .cranelift_nan_canonicalization(nan_canonicalization);
+        .cranelift_nan_canonicalization(nan_canonicalization)
+        .cranelift_bounds_check_merging(bounds_check_merging);
     #[expect(deprecated, reason = "forwarding legacy-exceptions")]
     config.wasm_legacy_exceptions(legacy_exceptions);
 }
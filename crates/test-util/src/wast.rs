@@ -241,6 +241,7 @@ macro_rules! foreach_config_option {
             wide_arithmetic
             hogs_memory
             nan_canonicalization
+            bounds_check_merging
             component_model_async
             component_model_async_builtins
             component_model_async_stackful
@@ -342,7 +343,17 @@ impl Compiler {
     /// `Config::compiler_panicking_wasm_features`.
     pub fn should_fail(&self, config: &TestConfig) -> bool {
         match self {
-            Compiler::CraneliftNative => config.legacy_exceptions(),
+            Compiler::CraneliftNative => {
+                if config.legacy_exceptions() {
+                    return true;
+                }
+
+                if cfg!(target_arch = "loongarch64") {
+                    return config.simd() || config.relaxed_simd();
+                }
+
+                false
+            }
 
             Compiler::Winch => {
                 let unsupported_base = config.gc()
@@ -370,9 +381,7 @@ impl Compiler {
                 false
             }
 
-            Compiler::CraneliftPulley => {
-                config.threads() || config.legacy_exceptions() || config.stack_switching()
-            }
+            Compiler::CraneliftPulley => config.legacy_exceptions() || config.stack_switching(),
         }
     }
 
@@ -385,6 +394,7 @@ impl Compiler {
                     || cfg!(target_arch = "aarch64")
                     || cfg!(target_arch = "riscv64")
                     || cfg!(target_arch = "s390x")
+                    || cfg!(target_arch = "loongarch64")
             }
             Compiler::Winch => cfg!(target_arch = "x86_64") || cfg!(target_arch = "aarch64"),
             Compiler::CraneliftPulley => true,
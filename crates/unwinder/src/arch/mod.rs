@@ -20,6 +20,9 @@ cfg_if::cfg_if! {
     } else if #[cfg(target_arch = "riscv64")] {
         mod riscv64;
         use riscv64 as imp;
+    } else if #[cfg(target_arch = "loongarch64")] {
+        mod loongarch64;
+        use loongarch64 as imp;
     }
 }
 
@@ -31,7 +34,8 @@ cfg_if::cfg_if! {
         target_arch = "x86_64",
         target_arch = "aarch64",
         target_arch = "s390x",
-        target_arch = "riscv64"
+        target_arch = "riscv64",
+        target_arch = "loongarch64"
     ))] {
         /// Get the current stack pointer (at the time this function is
         /// executing). This may be used to check, e.g., approximate space
@@ -14,7 +14,7 @@ fn main() {
     // Determine if the current host architecture is supported by Cranelift
     // meaning that we might be executing native code.
     let has_host_compiler_backend = match std::env::var("CARGO_CFG_TARGET_ARCH").unwrap().as_str() {
-        "x86_64" | "riscv64" | "s390x" | "aarch64" => true,
+        "x86_64" | "riscv64" | "s390x" | "aarch64" | "loongarch64" => true,
         _ => false,
     };
 
@@ -29,6 +29,7 @@ use crate::prelude::*;
 use std::{
     any::Any,
     borrow::Cow,
+    cmp::Reverse,
     collections::{BTreeMap, BTreeSet, btree_map},
     mem,
 };
@@ -39,8 +40,8 @@ use wasmtime_environ::component::Translator;
 use wasmtime_environ::{
     BuiltinFunctionIndex, CompiledFunctionInfo, CompiledModuleInfo, Compiler, DefinedFuncIndex,
     FilePos, FinishedObject, FunctionBodyData, ModuleEnvironment, ModuleInternedTypeIndex,
-    ModuleTranslation, ModuleTypes, ModuleTypesBuilder, ObjectKind, PrimaryMap, RelocationTarget,
-    StaticModuleIndex,
+    ModuleProfile, ModuleTranslation, ModuleTypes, ModuleTypesBuilder, ObjectKind, PrimaryMap,
+    RelocationTarget, StaticModuleIndex,
 };
 
 mod code_builder;
@@ -48,6 +49,8 @@ pub use self::code_builder::{CodeBuilder, CodeHint, HashedEngineCompileEnv};
 
 #[cfg(feature = "runtime")]
 mod runtime;
+#[cfg(feature = "runtime")]
+pub(crate) use self::runtime::compile_tier_up;
 
 /// Converts an input binary-encoded WebAssembly module to compilation
 /// artifacts and type information.
@@ -65,6 +68,42 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     engine: &Engine,
     wasm: &[u8],
     dwarf_package: Option<&[u8]>,
+    profile_data: Option<&[u8]>,
+    obj_state: &T::State,
+) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
+    build_module_artifacts(
+        engine,
+        engine.compiler(),
+        wasm,
+        dwarf_package,
+        profile_data,
+        obj_state,
+    )
+}
+
+/// Same as [`build_artifacts`], except that the module is compiled with the
+/// optimizing compiler used for tiered compilation.
+///
+/// Returns an error if tiered compilation is not enabled for `engine`.
+#[cfg(feature = "runtime")]
+pub(crate) fn build_tier_up_artifacts<T: FinishedObject>(
+    engine: &Engine,
+    wasm: &[u8],
+    profile_data: Option<&[u8]>,
+    obj_state: &T::State,
+) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
+    let compiler = engine
+        .tier_up_compiler()
+        .ok_or_else(|| anyhow!("tiered compilation is not enabled for this engine"))?;
+    build_module_artifacts(engine, compiler, wasm, None, profile_data, obj_state)
+}
+
+fn build_module_artifacts<T: FinishedObject>(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    wasm: &[u8],
+    dwarf_package: Option<&[u8]>,
+    profile_data: Option<&[u8]>,
     obj_state: &T::State,
 ) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
     let tunables = engine.tunables();
@@ -80,10 +119,24 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     let mut translation = ModuleEnvironment::new(tunables, &mut validator, &mut types)
         .translate(parser, wasm)
         .context("failed to parse WebAssembly module")?;
+    if let Some(profile_data) = profile_data {
+        let profile =
+            ModuleProfile::from_bytes(profile_data).context("failed to parse profile data")?;
+        let num_defined_funcs =
+            translation.module.functions.len() - translation.module.num_imported_funcs;
+        if profile.num_funcs() != num_defined_funcs {
+            bail!(
+                "profile data has {} functions but the module defines {num_defined_funcs}, \
+                 it was likely collected from a different module",
+                profile.num_funcs(),
+            );
+        }
+        translation.profile = Some(profile);
+    }
     let functions = mem::take(&mut translation.function_body_inputs);
 
     let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
-    let unlinked_compile_outputs = compile_inputs.compile(engine)?;
+    let unlinked_compile_outputs = compile_inputs.compile(engine, compiler)?;
     let PreLinkOutput {
         needs_gc_heap,
         compiled_funcs,
@@ -93,7 +146,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
 
     // Emplace all compiled functions into the object file with any other
     // sections associated with code as well.
-    let mut object = engine.compiler().object(ObjectKind::Module)?;
+    let mut object = compiler.object(ObjectKind::Module)?;
     // Insert `Engine` and type-level information into the compiled
     // artifact so if this module is deserialized later it contains all
     // information necessary.
@@ -110,6 +163,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
         &types,
         object,
         engine,
+        compiler,
         compiled_funcs,
         std::iter::once(translation).collect(),
         dwarf_package,
@@ -135,6 +189,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
     engine: &Engine,
     binary: &[u8],
     _dwarf_package: Option<&[u8]>,
+    _profile_data: Option<&[u8]>,
     obj_state: &T::State,
 ) -> Result<(T, Option<wasmtime_environ::component::ComponentArtifacts>)> {
     use wasmtime_environ::ScopeVec;
@@ -158,11 +213,15 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
         &types,
         &component,
         module_translations.iter_mut().map(|(i, translation)| {
+            // Executions are only counted for core modules, not for the
+            // modules within a component, and only core modules tier up.
+            translation.module.profile_instrumentation = false;
+            translation.module.tier_up = false;
             let functions = mem::take(&mut translation.function_body_inputs);
             (i, &*translation, functions)
         }),
     );
-    let unlinked_compile_outputs = compile_inputs.compile(&engine)?;
+    let unlinked_compile_outputs = compile_inputs.compile(&engine, compiler)?;
 
     let PreLinkOutput {
         needs_gc_heap,
@@ -181,6 +240,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
         types.module_types_builder(),
         object,
         engine,
+        compiler,
         compiled_funcs,
         module_translations,
         None, // TODO: Support dwarf packages for components.
@@ -463,6 +523,14 @@ impl<'a> CompileInputs<'a> {
         >,
     ) {
         for (module, translation, functions) in translations {
+            let mut functions: Vec<_> = functions.into_iter().collect();
+            // Lay out the functions that were entered most often according to
+            // the profile first so that the hot code is packed together.
+            if let Some(profile) = &translation.profile {
+                functions.sort_by_key(|(index, _)| {
+                    Reverse(profile.func(*index).map_or(0, |f| f.entries()))
+                });
+            }
             for (def_func_index, func_body) in functions {
                 self.push_input(move |compiler| {
                     let func_index = translation.module.func_index(def_func_index);
@@ -554,9 +622,7 @@ impl<'a> CompileInputs<'a> {
 
     /// Compile these `CompileInput`s (maybe in parallel) and return the
     /// resulting `UnlinkedCompileOutput`s.
-    fn compile(self, engine: &Engine) -> Result<UnlinkedCompileOutputs> {
-        let compiler = engine.compiler();
-
+    fn compile(self, engine: &Engine, compiler: &dyn Compiler) -> Result<UnlinkedCompileOutputs> {
         if self.inputs.len() > 0 && cfg!(miri) {
             bail!(
                 "\
@@ -582,7 +648,7 @@ the use case.
         // wasmtime-builtin functions are necessary. If so those need to be
         // collected and then those trampolines additionally need to be
         // compiled.
-        compile_required_builtins(engine, &mut raw_outputs)?;
+        compile_required_builtins(engine, compiler, &mut raw_outputs)?;
 
         // Bucket the outputs by kind.
         let mut outputs: BTreeMap<u32, Vec<CompileOutput>> = BTreeMap::new();
@@ -594,8 +660,11 @@ the use case.
     }
 }
 
-fn compile_required_builtins(engine: &Engine, raw_outputs: &mut Vec<CompileOutput>) -> Result<()> {
-    let compiler = engine.compiler();
+fn compile_required_builtins(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    raw_outputs: &mut Vec<CompileOutput>,
+) -> Result<()> {
     let mut builtins = HashSet::new();
     let mut new_inputs: Vec<CompileInput<'_>> = Vec::new();
 
@@ -658,6 +727,11 @@ impl UnlinkedCompileOutputs {
             let index = match output.function {
                 CompiledFunction::Function(f) => {
                     needs_gc_heap |= f.needs_gc_heap;
+                    if output.key.kind() == CompileKey::WASM_FUNCTION_KIND {
+                        indices
+                            .profile_counters
+                            .insert(output.key, f.profile_counters);
+                    }
                     let index = compiled_funcs.len();
                     compiled_funcs.push((output.symbol, f.code));
                     CompiledFunction::Function(index)
@@ -729,6 +803,9 @@ struct FunctionIndices {
     // A map of wasm functions and where they're located in the original file.
     start_srclocs: HashMap<CompileKey, FilePos>,
 
+    // The number of profile counters of each wasm function.
+    profile_counters: HashMap<CompileKey, u32>,
+
     // The index of each compiled function, bucketed by compile key kind.
     indices: BTreeMap<u32, BTreeMap<CompileKey, CompiledFunction<usize>>>,
 }
@@ -741,6 +818,7 @@ impl FunctionIndices {
         types: &ModuleTypesBuilder,
         mut obj: object::write::Object<'static>,
         engine: &'a Engine,
+        compiler: &dyn Compiler,
         compiled_funcs: Vec<(String, Box<dyn Any + Send>)>,
         translations: PrimaryMap<StaticModuleIndex, ModuleTranslation<'_>>,
         dwarf_package_bytes: Option<&[u8]>,
@@ -750,7 +828,6 @@ impl FunctionIndices {
         // The result is a vector parallel to `compiled_funcs` where
         // `symbol_ids_and_locs[i]` is the symbol ID and function location of
         // `compiled_funcs[i]`.
-        let compiler = engine.compiler();
         let tunables = engine.tunables();
         let symbol_ids_and_locs = compiler.append_code(
             &mut obj,
@@ -871,6 +948,7 @@ impl FunctionIndices {
                             let wasm_func_index = wasm_func_index.unwrap_function();
                             let wasm_func_loc = symbol_ids_and_locs[wasm_func_index].1;
                             let start_srcloc = self.start_srclocs.remove(&key).unwrap();
+                            let profile_counters = self.profile_counters.remove(&key).unwrap_or(0);
 
                             let array_to_wasm_trampoline = array_to_wasm_trampolines
                                 .remove(&CompileKey::array_to_wasm_trampoline(
@@ -883,6 +961,7 @@ impl FunctionIndices {
                                 start_srcloc,
                                 wasm_func_loc,
                                 array_to_wasm_trampoline,
+                                profile_counters,
                             }
                         })
                         .collect();
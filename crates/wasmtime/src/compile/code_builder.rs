@@ -40,6 +40,7 @@ pub struct CodeBuilder<'a> {
     wasm_path: Option<Cow<'a, Path>>,
     dwarf_package: Option<Cow<'a, [u8]>>,
     dwarf_package_path: Option<Cow<'a, Path>>,
+    profile_data: Option<Cow<'a, [u8]>>,
 }
 
 /// Return value of [`CodeBuilder::hint`]
@@ -60,6 +61,7 @@ impl<'a> CodeBuilder<'a> {
             wasm_path: None,
             dwarf_package: None,
             dwarf_package_path: None,
+            profile_data: None,
         }
     }
 
@@ -236,6 +238,60 @@ impl<'a> CodeBuilder<'a> {
         Ok(self)
     }
 
+    /// Configures the profile that guides the compilation of the module.
+    ///
+    /// The `profile_bytes` must have been returned by
+    /// [`Module::profile_data`](crate::Module::profile_data) for a module
+    /// compiled from the same WebAssembly bytes with
+    /// [`Config::profile_instrumentation`](crate::Config::profile_instrumentation)
+    /// enabled. The compiler then lays out the functions that were executed
+    /// most often first, moves the branches that were rarely taken out of the
+    /// hot path, and inlines the calls made most often more eagerly.
+    ///
+    /// With [`Config::tiered_compilation`](crate::Config::tiered_compilation)
+    /// the calls recorded in the profile also count toward tiering up, so
+    /// functions which were hot in the profile tier up sooner, and the profile
+    /// guides the recompilation with Cranelift in the same way.
+    ///
+    /// Profiles are currently only used when compiling modules, not
+    /// components.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile has already been set via
+    /// [`CodeBuilder::profile_data`] or [`CodeBuilder::profile_data_file`].
+    /// Compilation will fail if the profile can't be parsed or does not match
+    /// the module.
+    pub fn profile_data(&mut self, profile_bytes: &'a [u8]) -> Result<&mut Self> {
+        if self.profile_data.is_some() {
+            bail!("cannot call `profile_data` or `profile_data_file` twice");
+        }
+        self.profile_data = Some(profile_bytes.into());
+        Ok(self)
+    }
+
+    /// Same as [`CodeBuilder::profile_data`] except that the profile is read
+    /// from `file`.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors returned by [`CodeBuilder::profile_data`]
+    /// this returns an error if `file` cannot be read.
+    pub fn profile_data_file(&mut self, file: &Path) -> Result<&mut Self> {
+        if self.profile_data.is_some() {
+            bail!("cannot call `profile_data` or `profile_data_file` twice");
+        }
+        let profile_data = std::fs::read(file)
+            .with_context(|| format!("failed to read profile data file: {}", file.display()))?;
+        self.profile_data = Some(profile_data.into());
+        Ok(self)
+    }
+
+    /// Gets the profile guiding the compilation.
+    pub(super) fn get_profile_data(&self) -> Option<&[u8]> {
+        self.profile_data.as_deref()
+    }
+
     /// Returns a hint, if possible, of what the provided bytes are.
     ///
     /// This method can be use to detect what the previously supplied bytes to
@@ -274,7 +330,14 @@ impl<'a> CodeBuilder<'a> {
     pub fn compile_module_serialized(&self) -> Result<Vec<u8>> {
         let wasm = self.get_wasm()?;
         let dwarf_package = self.get_dwarf_package();
-        let (v, _) = super::build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), &())?;
+        let profile_data = self.get_profile_data();
+        let (v, _) = super::build_artifacts(
+            self.engine,
+            &wasm,
+            dwarf_package.as_deref(),
+            profile_data,
+            &(),
+        )?;
         Ok(v)
     }
 
@@ -284,7 +347,7 @@ impl<'a> CodeBuilder<'a> {
     #[cfg(feature = "component-model")]
     pub fn compile_component_serialized(&self) -> Result<Vec<u8>> {
         let bytes = self.get_wasm()?;
-        let (v, _) = super::build_component_artifacts(self.engine, &bytes, None, &())?;
+        let (v, _) = super::build_component_artifacts(self.engine, &bytes, None, None, &())?;
         Ok(v)
     }
 }
@@ -14,12 +14,14 @@ impl<'a> CodeBuilder<'a> {
             &Engine,
             &[u8],
             Option<&[u8]>,
+            Option<&[u8]>,
             &S,
         ) -> Result<(MmapVecWrapper, Option<T>)>,
         state: &S,
     ) -> Result<(Arc<CodeMemory>, Option<T>)> {
         let wasm = self.get_wasm()?;
         let dwarf_package = self.get_dwarf_package();
+        let profile_data = self.get_profile_data();
 
         self.engine
             .check_compatible_with_native_host()
@@ -31,6 +33,7 @@ impl<'a> CodeBuilder<'a> {
                 crate::compile::HashedEngineCompileEnv(self.engine),
                 &wasm,
                 &dwarf_package,
+                &profile_data,
                 // Don't hash this as it's just its own "pure" function pointer.
                 NotHashed(build_artifacts),
                 // Don't hash the FinishedObject state: this contains
@@ -38,36 +41,37 @@ impl<'a> CodeBuilder<'a> {
                 // not impact the compilation result itself.
                 NotHashed(state),
             );
-            let (code, info_and_types) =
-                wasmtime_cache::ModuleCacheEntry::new("wasmtime", self.engine.cache())
-                    .get_data_raw(
-                        &state,
-                        // Cache miss, compute the actual artifacts
-                        |(engine, wasm, dwarf_package, build_artifacts, state)| -> Result<_> {
-                            let (mmap, info) = (build_artifacts.0)(
-                                engine.0,
-                                wasm,
-                                dwarf_package.as_deref(),
-                                state.0,
-                            )?;
-                            let code = publish_mmap(engine.0, mmap.0)?;
-                            Ok((code, info))
-                        },
-                        // Implementation of how to serialize artifacts
-                        |(_engine, _wasm, _, _, _), (code, _info_and_types)| {
-                            Some(code.mmap().to_vec())
-                        },
-                        // Cache hit, deserialize the provided artifacts
-                        |(engine, wasm, _, _, _), serialized_bytes| {
-                            let kind = if wasmparser::Parser::is_component(&wasm) {
-                                wasmtime_environ::ObjectKind::Component
-                            } else {
-                                wasmtime_environ::ObjectKind::Module
-                            };
-                            let code = engine.0.load_code_bytes(&serialized_bytes, kind).ok()?;
-                            Some((code, None))
-                        },
+            let (code, info_and_types) = wasmtime_cache::ModuleCacheEntry::new(
+                "wasmtime",
+                self.engine.cache(),
+            )
+            .get_data_raw(
+                &state,
+                // Cache miss, compute the actual artifacts
+                |(engine, wasm, dwarf_package, profile_data, build_artifacts, state)| -> Result<_> {
+                    let (mmap, info) = (build_artifacts.0)(
+                        engine.0,
+                        wasm,
+                        dwarf_package.as_deref(),
+                        profile_data.as_deref(),
+                        state.0,
                     )?;
+                    let code = publish_mmap(engine.0, mmap.0)?;
+                    Ok((code, info))
+                },
+                // Implementation of how to serialize artifacts
+                |(_engine, _wasm, _, _, _, _), (code, _info_and_types)| Some(code.mmap().to_vec()),
+                // Cache hit, deserialize the provided artifacts
+                |(engine, wasm, _, _, _, _), serialized_bytes| {
+                    let kind = if wasmparser::Parser::is_component(&wasm) {
+                        wasmtime_environ::ObjectKind::Component
+                    } else {
+                        wasmtime_environ::ObjectKind::Module
+                    };
+                    let code = engine.0.load_code_bytes(&serialized_bytes, kind).ok()?;
+                    Some((code, None))
+                },
+            )?;
             return Ok((code, info_and_types));
 
             struct NotHashed<T>(T);
@@ -79,8 +83,13 @@ impl<'a> CodeBuilder<'a> {
 
         #[cfg(not(feature = "cache"))]
         {
-            let (mmap, info_and_types) =
-                build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), state)?;
+            let (mmap, info_and_types) = build_artifacts(
+                self.engine,
+                &wasm,
+                dwarf_package.as_deref(),
+                profile_data,
+                state,
+            )?;
             let code = publish_mmap(self.engine, mmap.0)?;
             return Ok((code, info_and_types));
         }
@@ -95,7 +104,11 @@ impl<'a> CodeBuilder<'a> {
         let custom_alignment = self.custom_alignment();
         let (code, info_and_types) =
             self.compile_cached(super::build_artifacts, &custom_alignment)?;
-        Module::from_parts(self.engine, code, info_and_types)
+        let module = Module::from_parts(self.engine, code, info_and_types)?;
+        if self.engine.tier_up_compiler().is_some() {
+            return module.with_tier_up_source(&self.get_wasm()?, self.get_profile_data());
+        }
+        Ok(module)
     }
 
     /// Same as [`CodeBuilder::compile_module`] except that it compiles a
@@ -119,6 +132,29 @@ impl<'a> CodeBuilder<'a> {
     }
 }
 
+/// Compiles `wasm` with the optimizing compiler of `engine` for tiered
+/// compilation.
+pub(crate) fn compile_tier_up(
+    engine: &Engine,
+    wasm: &[u8],
+    profile_data: Option<&[u8]>,
+) -> Result<Module> {
+    let custom_alignment = CustomAlignment {
+        alignment: engine
+            .custom_code_memory()
+            .map(|c| c.required_alignment())
+            .unwrap_or(1),
+    };
+    let (mmap, info_and_types) = super::build_tier_up_artifacts::<MmapVecWrapper>(
+        engine,
+        wasm,
+        profile_data,
+        &custom_alignment,
+    )?;
+    let code = publish_mmap(engine, mmap.0)?;
+    Module::from_parts(engine, code, info_and_types)
+}
+
 fn publish_mmap(engine: &Engine, mmap: MmapVec) -> Result<Arc<CodeMemory>> {
     let mut code = CodeMemory::new(engine, mmap)?;
     code.publish()?;
@@ -144,7 +144,7 @@ pub struct Config {
     pub(crate) enabled_features: WasmFeatures,
     /// Same as `enabled_features`, but for those that are explicitly disabled.
     pub(crate) disabled_features: WasmFeatures,
-    pub(crate) wasm_backtrace: bool,
+    pub(crate) wasm_backtrace: Option<bool>,
     pub(crate) wasm_backtrace_details_env_used: bool,
     pub(crate) native_unwind_info: Option<bool>,
     #[cfg(any(feature = "async", feature = "stack-switching"))]
@@ -176,8 +176,14 @@ struct CompilerConfig {
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<std::path::PathBuf>,
     wmemcheck: bool,
+    tiered_compilation: bool,
 }
 
+/// How many calls of a function it takes for its baseline code to start the
+/// tier-up of its module when [`Config::tiered_compilation`] is enabled.
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+const DEFAULT_TIER_UP_THRESHOLD: u32 = 1 << 12;
+
 #[cfg(any(feature = "cranelift", feature = "winch"))]
 impl CompilerConfig {
     fn new() -> Self {
@@ -189,6 +195,7 @@ impl CompilerConfig {
             cache_store: None,
             clif_dir: None,
             wmemcheck: false,
+            tiered_compilation: false,
         }
     }
 
@@ -247,7 +254,7 @@ impl Config {
             // 1` forces this), or at least it passed when this change was
             // committed.
             max_wasm_stack: 512 * 1024,
-            wasm_backtrace: true,
+            wasm_backtrace: None,
             wasm_backtrace_details_env_used: false,
             native_unwind_info: None,
             enabled_features: WasmFeatures::empty(),
@@ -463,14 +470,21 @@ impl Config {
     /// When this option is disabled then this context is never applied to
     /// errors coming out of wasm.
     ///
-    /// This option is `true` by default.
+    /// This option is `true` by default, unless [`Config::compiler_inlining`]
+    /// is enabled.
     ///
     /// [`WasmBacktrace`]: crate::WasmBacktrace
     pub fn wasm_backtrace(&mut self, enable: bool) -> &mut Self {
-        self.wasm_backtrace = enable;
+        self.wasm_backtrace = Some(enable);
         self
     }
 
+    /// Whether backtraces are collected, see [`Config::wasm_backtrace`].
+    pub(crate) fn wasm_backtrace_enabled(&self) -> bool {
+        self.wasm_backtrace
+            .unwrap_or(self.tunables.inlining != Some(true))
+    }
+
     /// Configures whether backtraces in `Trap` will parse debug info in the wasm file to
     /// have filename/line number information.
     ///
@@ -1214,6 +1228,63 @@ impl Config {
         self
     }
 
+    /// Configures whether modules are compiled in two tiers: first with a
+    /// baseline compiler and then, once they're hot, with an optimizing
+    /// compiler.
+    ///
+    /// When enabled, [`Module::new`](crate::Module::new) and friends compile
+    /// the module with the baseline compiler configured via
+    /// [`Config::strategy`], which must be [`Strategy::Winch`]. This yields a
+    /// module which is available for instantiation quickly. Each instance
+    /// counts the calls of its functions, and once one of them has been called
+    /// a few thousand times the module is recompiled with Cranelift on a
+    /// background thread. Each engine has a single such thread, started on
+    /// first use, which recompiles hot modules one at a time. Execution
+    /// continues in the baseline code in the meantime. Once the optimized code
+    /// is available, instances of the module switch to it the next time one of
+    /// their hot functions is called: the `VMFuncRef`s of the instance's
+    /// functions, which calls within the instance go through, are redirected
+    /// to the optimized code. Instances created afterwards use the optimized
+    /// code from the start. [`Module::tier_up`](crate::Module::tier_up) can
+    /// also be used to recompile a module eagerly.
+    ///
+    /// When a module is compiled with a profile from
+    /// [`CodeBuilder::profile_data`](crate::CodeBuilder::profile_data), the
+    /// calls recorded in the profile count toward tiering up: functions which
+    /// were called often in the profile tier up after fewer calls, or on their
+    /// first one. The profile also guides the optimized compilation.
+    ///
+    /// Tiered compilation currently has these limitations:
+    ///
+    /// * The whole module is recompiled and switched to at once, including
+    ///   functions which were never called, rather than individual hot
+    ///   functions.
+    /// * Code of the optimized tier uses Winch's calling convention so that
+    ///   the two tiers can call each other, which makes calls in optimized
+    ///   code somewhat slower than with [`Strategy::Cranelift`].
+    /// * Calls into an instance from other instances through function imports
+    ///   keep using the baseline code of that instance, since imports are
+    ///   resolved at instantiation time.
+    /// * Modules within components are compiled with the baseline compiler
+    ///   only and never tier up.
+    ///
+    /// Note that both tiers are compiled with the same set of compiler
+    /// settings. Settings which only apply to Cranelift, such as
+    /// [`Config::cranelift_opt_level`], only take effect in the optimized
+    /// tier.
+    ///
+    /// This option requires both the `cranelift` and `winch` features of this
+    /// crate to be enabled. It is not supported when compiling to Pulley: an
+    /// engine compiles for a single target, so Pulley bytecode can't serve as
+    /// the baseline tier for native code.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(all(feature = "cranelift", feature = "winch"))]
+    pub fn tiered_compilation(&mut self, enable: bool) -> &mut Self {
+        self.compiler_config.tiered_compilation = enable;
+        self
+    }
+
     /// Configures which garbage collector will be used for Wasm modules.
     ///
     /// This method can be used to configure which garbage collector
@@ -1296,6 +1367,7 @@ impl Config {
     pub fn cranelift_regalloc_algorithm(&mut self, algo: RegallocAlgorithm) -> &mut Self {
         let val = match algo {
             RegallocAlgorithm::Backtracking => "backtracking",
+            RegallocAlgorithm::SinglePass => "single_pass",
         };
         self.compiler_config
             .settings
@@ -1325,6 +1397,51 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift should optimize loops: hoist loads that
+    /// aren't written to in a loop out of it, fully unroll loops with a small
+    /// constant trip count, and strength-reduce multiplications by induction
+    /// variables.
+    ///
+    /// These only take effect with [`OptLevel::Speed`] or
+    /// [`OptLevel::SpeedAndSize`], and unrolling only with the former. They
+    /// are off by default since they increase compile time while the
+    /// workloads that benefit from them are usually already optimized this
+    /// way by the producer of the WebAssembly module.
+    ///
+    /// The default value for this is `false`
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_loop_optimizations(&mut self, enable: bool) -> &mut Self {
+        let val = if enable { "true" } else { "false" };
+        self.compiler_config
+            .settings
+            .insert("enable_loop_optimizations".to_string(), val.to_string());
+        self
+    }
+
+    /// Configures whether Cranelift should vectorize groups of scalar memory
+    /// accesses to adjacent addresses, and the arithmetic computing the
+    /// stored values, into SIMD instructions.
+    ///
+    /// This can speed up WebAssembly that was compiled without the SIMD
+    /// proposal but operates on arrays of numbers. It only takes effect on
+    /// x86_64 and aarch64, and with an optimization level other than
+    /// [`OptLevel::None`].
+    ///
+    /// Note that a vectorized group of memory accesses traps as a whole: if
+    /// one of the scalar accesses it replaces is out of bounds, the others
+    /// may or may not have happened when the trap is raised, while they all
+    /// would have happened, in order, up to the trapping one otherwise.
+    ///
+    /// The default value for this is `false`
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_slp_vectorization(&mut self, enable: bool) -> &mut Self {
+        let val = if enable { "true" } else { "false" };
+        self.compiler_config
+            .settings
+            .insert("enable_slp_vectorization".to_string(), val.to_string());
+        self
+    }
+
     /// Controls whether proof-carrying code (PCC) is used to validate
     /// lowering of Wasm sandbox checks.
     ///
@@ -1347,6 +1464,70 @@ impl Config {
         self
     }
 
+    /// Controls whether small WebAssembly functions are inlined into their
+    /// callers within the same module.
+    ///
+    /// When enabled, direct calls to small functions defined in the same
+    /// module are replaced with a copy of the callee's body, avoiding the
+    /// overhead of the call. This increases compile time and may increase
+    /// code size. Inlining is only performed when optimizations are enabled
+    /// and native debug information is not being generated.
+    ///
+    /// The frames of inlined functions aren't recorded, so enabling inlining
+    /// also disables [`Config::wasm_backtrace`] by default. Explicitly
+    /// enabling both is an error.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn compiler_inlining(&mut self, inlining: bool) -> &mut Self {
+        self.tunables.inlining = Some(inlining);
+        self
+    }
+
+    /// Controls whether accesses to linear memory reuse the bounds checks of
+    /// earlier accesses with the same index.
+    ///
+    /// When enabled, an access whose bounds are covered by a check of the same
+    /// index in a dominating block doesn't need a check of its own, and a check
+    /// may be widened to also cover later accesses at larger offsets. This
+    /// only applies to explicit bounds checks, so it has no effect on memories
+    /// whose accesses are all covered by guard pages, and it's not performed
+    /// when [`Config::cranelift_pcc`] is enabled.
+    ///
+    /// This option is experimental and requires the Cranelift compiler.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_bounds_check_merging(&mut self, enable: bool) -> &mut Self {
+        self.tunables.bounds_check_merging = Some(enable);
+        self
+    }
+
+    /// Configures whether compiled WebAssembly counts its executions to
+    /// collect a profile for profile-guided optimization.
+    ///
+    /// When enabled, each function defined by a module counts how often it is
+    /// entered and how often each of its conditional branches is executed and
+    /// taken. The counts are shared by all instances of a module and can be
+    /// retrieved with [`Module::profile_data`](crate::Module::profile_data)
+    /// after running a representative workload. Passing them to
+    /// [`CodeBuilder::profile_data`](crate::CodeBuilder::profile_data) when
+    /// compiling the module again then guides the optimizations of that
+    /// compilation.
+    ///
+    /// Counting executions slows down the compiled code, so this is intended
+    /// for a separate training run and not for production. Counts are only
+    /// collected for core modules, not for the modules within components.
+    ///
+    /// This option requires the Cranelift compiler.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn profile_instrumentation(&mut self, enable: bool) -> &mut Self {
+        self.tunables.profile_instrumentation = Some(enable);
+        self
+    }
+
     /// Allows setting a Cranelift boolean flag or preset. This allows
     /// fine-tuning of Cranelift settings.
     ///
@@ -2052,12 +2233,14 @@ impl Config {
             None | Some(Strategy::Cranelift) => {
                 let mut unsupported = WasmFeatures::empty();
 
-                // Pulley at this time fundamentally doesn't support the
-                // `threads` proposal, notably shared memory, because Rust can't
-                // safely implement loads/stores in the face of shared memory.
-                // Stack switching is not implemented, either.
+                // Pulley doesn't implement stack switching. Switching the
+                // interpreter's own state isn't enough: the runtime's
+                // continuation stacks only exist for native x64 Unix and
+                // would need a Pulley backend which starts, resumes and
+                // unwinds continuations on interpreter stacks. Note that the
+                // `threads` proposal is supported through Pulley's atomic
+                // instructions.
                 if self.compiler_target().is_pulley() {
-                    unsupported |= WasmFeatures::THREADS;
                     unsupported |= WasmFeatures::STACK_SWITCHING;
                 }
 
@@ -2080,6 +2263,13 @@ impl Config {
                         unsupported |= WasmFeatures::STACK_SWITCHING;
                     }
                 }
+
+                // The LoongArch backend doesn't implement LSX/LASX lowerings
+                // yet, so it can't compile any vector code.
+                if let Architecture::LoongArch64 = self.compiler_target().architecture {
+                    unsupported |= WasmFeatures::SIMD;
+                    unsupported |= WasmFeatures::RELAXED_SIMD;
+                }
                 unsupported
             }
             Some(Strategy::Winch) => {
@@ -2211,6 +2401,21 @@ impl Config {
         if self.wmemcheck {
             bail!("wmemcheck (memory checker) was requested but is not enabled in this build");
         }
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        if self.compiler_config.tiered_compilation {
+            if self.compiler_config.strategy != Some(Strategy::Winch) {
+                bail!("tiered compilation requires Winch as the baseline compilation strategy");
+            }
+            if self.compiler_target().is_pulley() {
+                bail!("tiered compilation is not supported when targeting Pulley");
+            }
+        }
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        if self.tunables.profile_instrumentation == Some(true)
+            && self.compiler_config.strategy == Some(Strategy::Winch)
+        {
+            bail!("profile instrumentation requires the Cranelift compiler");
+        }
 
         let mut tunables = Tunables::default_for_target(&self.compiler_target())?;
 
@@ -2241,10 +2446,18 @@ impl Config {
 
         self.tunables.configure(&mut tunables);
 
+        // Inlined functions would be missing from the frames of backtraces.
+        if tunables.inlining && self.wasm_backtrace == Some(true) {
+            bail!("`compiler_inlining` cannot be enabled when `wasm_backtrace` is enabled");
+        }
+
         // If we're going to compile with winch, we must use the winch calling convention.
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
             tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+            if self.compiler_config.tiered_compilation && tunables.tier_up_threshold == 0 {
+                tunables.tier_up_threshold = DEFAULT_TIER_UP_THRESHOLD;
+            }
         }
 
         tunables.collector = if features.gc_types() {
@@ -2516,6 +2729,29 @@ impl Config {
         Ok((self, compiler.build()?))
     }
 
+    /// Builds the optimizing compiler used for the second tier when
+    /// [`Config::tiered_compilation`] is enabled.
+    ///
+    /// Returns `None` if tiered compilation is disabled. The returned compiler
+    /// uses Cranelift with the same settings as the baseline compiler,
+    /// including Winch's calling convention for Wasm functions, so that the
+    /// optimized functions can be called from baseline code of the same
+    /// instance.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn build_tier_up_compiler(
+        &self,
+        tunables: &Tunables,
+        features: WasmFeatures,
+    ) -> Result<Option<Box<dyn wasmtime_environ::Compiler>>> {
+        if !self.compiler_config.tiered_compilation {
+            return Ok(None);
+        }
+        let mut config = self.clone();
+        config.compiler_config.strategy = Some(Strategy::Cranelift);
+        let (_, compiler) = config.build_compiler(tunables, features)?;
+        Ok(Some(compiler))
+    }
+
     /// Internal setting for whether adapter modules for components will have
     /// extra WebAssembly instructions inserted performing more debug checks
     /// then are necessary.
@@ -2874,6 +3110,19 @@ pub enum RegallocAlgorithm {
     /// results in better register utilization, producing fewer spills
     /// and moves, but can cause super-linear compile runtime.
     Backtracking,
+    /// Generates acceptable code very quickly.
+    ///
+    /// This algorithm performs a single pass through the code,
+    /// guaranteed to work in linear time. (Note that the rest of
+    /// Cranelift is not necessarily guaranteed to run in linear time,
+    /// however.) It cannot undo earlier decisions, however, and it
+    /// cannot foresee constraints or issues that may occur further
+    /// ahead in the code, so the code may have more spills and moves as
+    /// a result.
+    ///
+    /// Functions which make use of exception handling are always
+    /// allocated with the backtracking algorithm.
+    SinglePass,
 }
 
 /// Select which profiling technique to support.
@@ -3536,6 +3785,22 @@ fn detect_host_feature(feature: &str) -> Option<bool> {
         };
     }
 
+    // There is no stable is_loongarch_feature_detected macro for every
+    // extension yet, so for now we use getauxval from the libc crate directly.
+    #[cfg(all(target_arch = "loongarch64", target_os = "linux"))]
+    {
+        let v = unsafe { libc::getauxval(libc::AT_HWCAP) };
+        const HWCAP_LOONGARCH_LSX: libc::c_ulong = 1 << 4;
+        const HWCAP_LOONGARCH_LASX: libc::c_ulong = 1 << 5;
+
+        return match feature {
+            "lsx" => Some((v & HWCAP_LOONGARCH_LSX) != 0),
+            "lasx" => Some((v & HWCAP_LOONGARCH_LASX) != 0),
+
+            _ => None,
+        };
+    }
+
     #[cfg(target_arch = "riscv64")]
     {
         return match feature {
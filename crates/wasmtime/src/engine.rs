@@ -18,6 +18,8 @@ use wasmparser::WasmFeatures;
 use wasmtime_environ::{FlagValue, ObjectKind, TripleExt, Tunables};
 
 mod serialization;
+#[cfg(all(feature = "runtime", any(feature = "cranelift", feature = "winch")))]
+mod tier_up;
 
 /// An `Engine` which is a global context for compilation and management of wasm
 /// modules.
@@ -52,6 +54,14 @@ struct EngineInner {
     tunables: Tunables,
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     compiler: Box<dyn wasmtime_environ::Compiler>,
+    /// The optimizing compiler used to tier up modules, if
+    /// `Config::tiered_compilation` is enabled.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    tier_up_compiler: Option<Box<dyn wasmtime_environ::Compiler>>,
+    /// The thread which tiers up hot modules in the background, present if
+    /// `tier_up_compiler` is.
+    #[cfg(all(feature = "runtime", any(feature = "cranelift", feature = "winch")))]
+    tier_up_worker: Option<tier_up::TierUpWorker>,
     #[cfg(feature = "runtime")]
     allocator: Box<dyn crate::runtime::vm::InstanceAllocator + Send + Sync>,
     #[cfg(feature = "runtime")]
@@ -116,11 +126,19 @@ impl Engine {
 
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         let (config, compiler) = config.build_compiler(&tunables, features)?;
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        let tier_up_compiler = config.build_tier_up_compiler(&tunables, features)?;
 
         Ok(Engine {
             inner: Arc::new(EngineInner {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 compiler,
+                #[cfg(all(feature = "runtime", any(feature = "cranelift", feature = "winch")))]
+                tier_up_worker: tier_up_compiler
+                    .as_ref()
+                    .map(|_| tier_up::TierUpWorker::new()),
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                tier_up_compiler,
                 #[cfg(feature = "runtime")]
                 allocator: {
                     let allocator = config.build_allocator(&tunables)?;
@@ -425,6 +443,8 @@ impl Engine {
             | "tls_model" // wasmtime doesn't use tls right now
             | "opt_level" // opt level doesn't change semantics
             | "enable_alias_analysis" // alias analysis-based opts don't change semantics
+            | "enable_loop_optimizations" // loop opts don't change semantics
+            | "enable_slp_vectorization" // only changes which accesses precede a trap
             | "probestack_size_log2" // probestack above asserted disabled
             | "regalloc" // shouldn't change semantics
             | "enable_incremental_compilation_cache_checks" // shouldn't change semantics
@@ -510,6 +530,10 @@ impl Engine {
             "has_vxrs_ext2" => "vxrs_ext2",
             "has_mie2" => "mie2",
 
+            // loongarch64 features to detect
+            "has_lsx" => "lsx",
+            "has_lasx" => "lasx",
+
             // x64 features to detect
             "has_cmpxchg16b" => "cmpxchg16b",
             "has_sse3" => "sse3",
@@ -589,6 +613,19 @@ impl Engine {
         &*self.inner.compiler
     }
 
+    /// Returns the optimizing compiler used to tier up modules, if tiered
+    /// compilation is enabled.
+    pub(crate) fn tier_up_compiler(&self) -> Option<&dyn wasmtime_environ::Compiler> {
+        self.inner.tier_up_compiler.as_deref()
+    }
+
+    /// Returns the thread which tiers up hot modules in the background, if
+    /// tiered compilation is enabled.
+    #[cfg(all(feature = "runtime", any(feature = "cranelift", feature = "winch")))]
+    pub(crate) fn tier_up_worker(&self) -> Option<&tier_up::TierUpWorker> {
+        self.inner.tier_up_worker.as_ref()
+    }
+
     /// Ahead-of-time (AOT) compiles a WebAssembly module.
     ///
     /// The `bytes` provided must be in one of two formats:
@@ -684,6 +721,13 @@ impl Engine {
         crate::runtime::vm::tls_eager_initialize();
     }
 
+    /// Returns metrics about this engine's pooling instance allocator, or
+    /// `None` if this engine isn't configured to use the pooling allocator.
+    #[cfg(feature = "pooling-allocator")]
+    pub fn pooling_allocator_metrics(&self) -> Option<crate::PoolingAllocatorMetrics> {
+        crate::PoolingAllocatorMetrics::new(self)
+    }
+
     pub(crate) fn allocator(&self) -> &dyn crate::runtime::vm::InstanceAllocator {
         self.inner.allocator.as_ref()
     }
@@ -299,6 +299,19 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // Inlining changes the compiled code but not its interface with
+            // the runtime.
+            inlining: _,
+            bounds_check_merging: _,
+
+            // Whether a module counts executions is recorded in the module
+            // itself, which is all the runtime needs to allocate its counters.
+            profile_instrumentation: _,
+
+            // Likewise whether a module tiers up is recorded in the module,
+            // and the threshold is baked into its code.
+            tier_up_threshold: _,
         } = self.tunables;
 
         Self::check_collector(collector, other.collector)?;
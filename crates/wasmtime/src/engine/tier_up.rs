@@ -0,0 +1,108 @@
+//! Background compilation of the optimized tier of modules when
+//! `Config::tiered_compilation` is enabled.
+
+use crate::Module;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// The worker thread of an `Engine` which tiers up its hot modules.
+///
+/// Modules are queued by `Module::tier_up_in_background` and recompiled one
+/// at a time, in the order they became hot, so an engine never has more than
+/// one tier-up compilation in flight. Note that each compilation may still
+/// compile functions in parallel if the `parallel-compilation` feature is
+/// enabled.
+///
+/// The thread is started when the first module is queued, and it's shut down
+/// and joined when the engine is dropped. Queued modules keep their engine
+/// alive, so the queue is always empty by then.
+pub(crate) struct TierUpWorker {
+    shared: Arc<Shared>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Module>,
+    shutdown: bool,
+}
+
+impl TierUpWorker {
+    pub(crate) fn new() -> TierUpWorker {
+        TierUpWorker {
+            shared: Arc::default(),
+            thread: Mutex::new(None),
+        }
+    }
+
+    /// Queues `module` to be tiered up on the worker thread, starting the
+    /// thread if it isn't running yet.
+    pub(crate) fn submit(&self, module: Module) {
+        let mut thread = self.thread.lock().unwrap();
+        if thread.is_none() {
+            let shared = self.shared.clone();
+            match thread::Builder::new()
+                .name("wasmtime-tier-up".into())
+                .spawn(move || shared.run())
+            {
+                Ok(handle) => *thread = Some(handle),
+                Err(e) => {
+                    log::warn!("failed to start the tier-up thread: {e}");
+                    return;
+                }
+            }
+        }
+        drop(thread);
+
+        self.shared.state.lock().unwrap().queue.push_back(module);
+        self.shared.cond.notify_one();
+    }
+}
+
+impl Shared {
+    fn run(&self) {
+        loop {
+            let module = {
+                let mut state = self.state.lock().unwrap();
+                loop {
+                    if let Some(module) = state.queue.pop_front() {
+                        break module;
+                    }
+                    if state.shutdown {
+                        return;
+                    }
+                    state = self.cond.wait(state).unwrap();
+                }
+            };
+            if let Err(e) = module.tier_up() {
+                log::warn!("failed to tier up module {:?}: {e:?}", module.name());
+            }
+            // Note that this may drop the last reference to the engine, which
+            // shuts this thread down.
+            drop(module);
+        }
+    }
+}
+
+impl Drop for TierUpWorker {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.cond.notify_all();
+        let Some(handle) = self.thread.get_mut().unwrap().take() else {
+            return;
+        };
+        // The engine may be dropped by the worker thread itself once it's done
+        // with the last module referencing it, in which case the thread exits
+        // on its own right after.
+        if handle.thread().id() != thread::current().id() {
+            let _ = handle.join();
+        }
+    }
+}
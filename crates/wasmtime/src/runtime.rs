@@ -93,7 +93,7 @@ pub use values::*;
 pub(crate) use uninhabited::*;
 
 #[cfg(feature = "pooling-allocator")]
-pub use vm::PoolConcurrencyLimitError;
+pub use vm::{PoolConcurrencyLimitError, PoolingAllocatorMetrics};
 
 #[cfg(feature = "profiling")]
 mod profiling;
@@ -105,6 +105,11 @@ pub(crate) mod stack;
 #[cfg(feature = "async")]
 pub use stack::*;
 
+#[cfg(feature = "debug-pulley")]
+mod pulley_debugger;
+#[cfg(feature = "debug-pulley")]
+pub use pulley_debugger::*;
+
 #[cfg(feature = "coredump")]
 mod coredump;
 #[cfg(feature = "coredump")]
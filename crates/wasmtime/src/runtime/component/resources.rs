@@ -58,6 +58,25 @@ impl ResourceType {
         }
     }
 
+    /// Creates a new host resource type identified by `payload`.
+    ///
+    /// This is similar to [`ResourceType::host`] except that host resource
+    /// types are distinguished by a runtime value rather than a Rust type,
+    /// which is useful when resource types aren't known until runtime, such
+    /// as when they're defined by a language other than Rust. Two types
+    /// created with this function are the same if they have the same
+    /// `payload`, and they are never the same as a type created with
+    /// [`ResourceType::host`].
+    ///
+    /// A resource type of type `ResourceType::host_dynamic(payload)` will match
+    /// the type of the value produced by `ResourceDynamic::new_{own,borrow}`
+    /// with the same `payload`.
+    pub fn host_dynamic(payload: u32) -> ResourceType {
+        ResourceType {
+            kind: ResourceTypeKind::HostDynamic(payload),
+        }
+    }
+
     pub(crate) fn guest(
         store: StoreId,
         instance: &ComponentInstance,
@@ -85,6 +104,7 @@ impl ResourceType {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum ResourceTypeKind {
     Host(TypeId),
+    HostDynamic(u32),
     Guest {
         store: StoreId,
         // For now this is the `*mut ComponentInstance` pointer within the store
@@ -791,31 +811,8 @@ where
     ///
     /// This function will panic if `resource` does not belong to the `store`
     /// specified.
-    pub fn try_from_resource_any(
-        resource: ResourceAny,
-        mut store: impl AsContextMut,
-    ) -> Result<Self> {
-        let store = store.as_context_mut();
-        let mut tables = HostResourceTables::new_host(store.0);
-        let ResourceAny { idx, ty, owned } = resource;
-        ensure!(ty == ResourceType::host::<T>(), "resource type mismatch");
-        let (state, rep) = if owned {
-            let rep = tables.host_resource_lift_own(idx)?;
-            (AtomicResourceState::NOT_IN_TABLE, rep)
-        } else {
-            // For borrowed handles, first acquire the `rep` via lifting the
-            // borrow. Afterwards though remove any dynamic state associated
-            // with this borrow. `Resource<T>` doesn't participate in dynamic
-            // state tracking and it's assumed embedders know what they're
-            // doing, so the drop call will clear out that a borrow is active
-            //
-            // Note that the result of `drop` should always be `None` as it's a
-            // borrowed handle, so assert so.
-            let rep = tables.host_resource_lift_borrow(idx)?;
-            let res = tables.host_resource_drop(idx)?;
-            assert!(res.is_none());
-            (AtomicResourceState::BORROW, rep)
-        };
+    pub fn try_from_resource_any(resource: ResourceAny, store: impl AsContextMut) -> Result<Self> {
+        let (state, rep) = resource.into_host(store, ResourceType::host::<T>())?;
         Ok(Resource {
             state,
             rep,
@@ -905,6 +902,108 @@ impl<T> fmt::Debug for Resource<T> {
     }
 }
 
+/// A host-defined resource whose type is determined at runtime.
+///
+/// This is similar to [`Resource`] except that its type is
+/// [`ResourceType::host_dynamic`] with a payload chosen at runtime rather than
+/// [`ResourceType::host`] with a Rust type. This is useful for embedders which
+/// define resource types dynamically, such as bindings to other languages.
+///
+/// Unlike [`Resource`] this type cannot be passed directly to typed component
+/// functions. Instead it's converted to and from [`ResourceAny`] with
+/// [`ResourceDynamic::try_into_resource_any`] and
+/// [`ResourceDynamic::try_from_resource_any`], and [`ResourceAny`] is then used
+/// with [`Val::Resource`](crate::component::Val::Resource).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ResourceDynamic {
+    rep: u32,
+    ty: u32,
+    owned: bool,
+}
+
+impl ResourceDynamic {
+    /// Creates a new owned resource with the `rep` specified and of type
+    /// `ResourceType::host_dynamic(ty)`.
+    pub fn new_own(rep: u32, ty: u32) -> ResourceDynamic {
+        ResourceDynamic {
+            rep,
+            ty,
+            owned: true,
+        }
+    }
+
+    /// Creates a new borrowed resource with the `rep` specified and of type
+    /// `ResourceType::host_dynamic(ty)`.
+    ///
+    /// See [`Resource::new_borrow`] for more information about borrowed host
+    /// resources.
+    pub fn new_borrow(rep: u32, ty: u32) -> ResourceDynamic {
+        ResourceDynamic {
+            rep,
+            ty,
+            owned: false,
+        }
+    }
+
+    /// Returns the underlying 32-bit representation used to originally create
+    /// this resource.
+    pub fn rep(&self) -> u32 {
+        self.rep
+    }
+
+    /// Returns the payload of this resource's type, as passed to
+    /// [`ResourceType::host_dynamic`].
+    pub fn ty(&self) -> u32 {
+        self.ty
+    }
+
+    /// Returns whether this is an owned resource or not.
+    pub fn owned(&self) -> bool {
+        self.owned
+    }
+
+    /// Attempts to convert a [`ResourceAny`] into [`ResourceDynamic`].
+    ///
+    /// This method will check that `resource` has a type created with
+    /// [`ResourceType::host_dynamic`] and then convert it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `resource` is not a dynamic host
+    /// resource. This function may also return an error if `resource` is no
+    /// longer valid, for example it was previously converted.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `resource` does not belong to the `store`
+    /// specified.
+    pub fn try_from_resource_any(resource: ResourceAny, store: impl AsContextMut) -> Result<Self> {
+        let ty = match resource.ty.kind {
+            ResourceTypeKind::HostDynamic(ty) => ty,
+            _ => bail!("resource type mismatch"),
+        };
+        let (state, rep) = resource.into_host(store, ResourceType::host_dynamic(ty))?;
+        Ok(ResourceDynamic {
+            rep,
+            ty,
+            owned: state.get() != ResourceState::Borrow,
+        })
+    }
+
+    /// Converts this resource into a [`ResourceAny`] placed in `store`.
+    ///
+    /// Like [`ResourceAny::try_from_resource`] the returned value has no
+    /// destructor attached to it.
+    pub fn try_into_resource_any(self, store: impl AsContextMut) -> Result<ResourceAny> {
+        let state = if self.owned {
+            ResourceState::NotInTable
+        } else {
+            ResourceState::Borrow
+        };
+        ResourceAny::from_host(store, ResourceType::host_dynamic(self.ty), self.rep, state)
+    }
+}
+
 /// Representation of a resource in the component model, either a guest-defined
 /// or a host-defined resource.
 ///
@@ -954,13 +1053,22 @@ impl ResourceAny {
     /// such as when it's already lowered into a component.
     pub fn try_from_resource<T: 'static>(
         resource: Resource<T>,
-        mut store: impl AsContextMut,
+        store: impl AsContextMut,
     ) -> Result<Self> {
         let Resource { rep, state, .. } = resource;
+        Self::from_host(store, ResourceType::host::<T>(), rep, state.get())
+    }
+
+    fn from_host(
+        mut store: impl AsContextMut,
+        ty: ResourceType,
+        rep: u32,
+        state: ResourceState,
+    ) -> Result<Self> {
         let store = store.as_context_mut();
 
         let mut tables = HostResourceTables::new_host(store.0);
-        let (idx, owned) = match state.get() {
+        let (idx, owned) = match state {
             ResourceState::Borrow => (tables.host_resource_lower_borrow(rep)?, false),
             ResourceState::NotInTable => {
                 let idx = tables.host_resource_lower_own(rep, None, None)?;
@@ -969,11 +1077,41 @@ impl ResourceAny {
             ResourceState::Taken => bail!("host resource already consumed"),
             ResourceState::Index(idx) => (idx, true),
         };
-        Ok(Self {
+        Ok(Self { idx, ty, owned })
+    }
+
+    /// Removes this resource from the host's table, returning its `rep` and
+    /// the state of the host resource it should now be represented with.
+    fn into_host(
+        self,
+        mut store: impl AsContextMut,
+        ty: ResourceType,
+    ) -> Result<(AtomicResourceState, u32)> {
+        let store = store.as_context_mut();
+        let mut tables = HostResourceTables::new_host(store.0);
+        let ResourceAny {
             idx,
-            ty: ResourceType::host::<T>(),
+            ty: actual,
             owned,
-        })
+        } = self;
+        ensure!(actual == ty, "resource type mismatch");
+        if owned {
+            let rep = tables.host_resource_lift_own(idx)?;
+            Ok((AtomicResourceState::NOT_IN_TABLE, rep))
+        } else {
+            // For borrowed handles, first acquire the `rep` via lifting the
+            // borrow. Afterwards though remove any dynamic state associated
+            // with this borrow. Host resources don't participate in dynamic
+            // state tracking and it's assumed embedders know what they're
+            // doing, so the drop call will clear out that a borrow is active
+            //
+            // Note that the result of `drop` should always be `None` as it's a
+            // borrowed handle, so assert so.
+            let rep = tables.host_resource_lift_borrow(idx)?;
+            let res = tables.host_resource_drop(idx)?;
+            assert!(res.is_none());
+            Ok((AtomicResourceState::BORROW, rep))
+        }
     }
 
     /// See [`Resource::try_from_resource_any`]
@@ -261,6 +261,12 @@ impl Instance {
         if !Engine::same(store.engine(), module.engine()) {
             bail!("cross-`Engine` instantiation is not currently supported");
         }
+
+        // If this module has been tiered up then instantiate the optimized
+        // code instead. Both tiers share the same types and layout so the
+        // already type-checked `imports` remain valid.
+        let module = module.latest_tier();
+
         store.bump_resource_counts(module)?;
 
         // Allocate the GC heap, if necessary.
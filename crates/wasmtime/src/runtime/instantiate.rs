@@ -17,6 +17,10 @@ use wasmtime_environ::{
 pub struct CompiledModule {
     module: Arc<Module>,
     funcs: PrimaryMap<DefinedFuncIndex, CompiledFunctionInfo>,
+    /// The defined functions sorted by their location in the text section,
+    /// present only if that's not the order of their indices, as when they
+    /// were laid out according to a profile.
+    funcs_by_loc: Option<Box<[DefinedFuncIndex]>>,
     wasm_to_array_trampolines: Vec<(ModuleInternedTypeIndex, FunctionLoc)>,
     meta: Metadata,
     code_memory: Arc<CodeMemory>,
@@ -47,9 +51,21 @@ impl CompiledModule {
         info: CompiledModuleInfo,
         profiler: &dyn ProfilingAgent,
     ) -> Result<Self> {
+        let funcs_by_loc = if info
+            .funcs
+            .values()
+            .is_sorted_by_key(|f| f.wasm_func_loc.start)
+        {
+            None
+        } else {
+            let mut funcs_by_loc = info.funcs.keys().collect::<Vec<_>>();
+            funcs_by_loc.sort_by_key(|i| info.funcs[*i].wasm_func_loc.start);
+            Some(funcs_by_loc.into())
+        };
         let mut ret = Self {
             module: Arc::new(info.module),
             funcs: info.funcs,
+            funcs_by_loc,
             wasm_to_array_trampolines: info.wasm_to_array_trampolines,
             code_memory,
             meta: info.meta,
@@ -173,6 +189,15 @@ impl CompiledModule {
         &self.text()[loc.start as usize..][..loc.length as usize]
     }
 
+    /// Returns the defined function laid out first in the text section, if
+    /// this module defines any functions.
+    pub fn first_func_in_text(&self) -> Option<DefinedFuncIndex> {
+        match &self.funcs_by_loc {
+            Some(funcs_by_loc) => funcs_by_loc.first().copied(),
+            None => self.funcs.keys().next(),
+        }
+    }
+
     /// Lookups a defined function by a program counter value.
     ///
     /// Returns the defined function index and the relative address of
@@ -180,21 +205,30 @@ impl CompiledModule {
     pub fn func_by_text_offset(&self, text_offset: usize) -> Option<(DefinedFuncIndex, u32)> {
         let text_offset = u32::try_from(text_offset).unwrap();
 
-        let index = match self.funcs.binary_search_values_by_key(&text_offset, |e| {
+        let end = |e: &CompiledFunctionInfo| {
             debug_assert!(e.wasm_func_loc.length > 0);
             // Return the inclusive "end" of the function
             e.wasm_func_loc.start + e.wasm_func_loc.length - 1
-        }) {
-            Ok(k) => {
-                // Exact match, pc is at the end of this function
-                k
-            }
-            Err(k) => {
-                // Not an exact match, k is where `pc` would be "inserted"
-                // Since we key based on the end, function `k` might contain `pc`,
-                // so we'll validate on the range check below
-                k
+        };
+        let index = match &self.funcs_by_loc {
+            // The functions aren't laid out in index order so search them in
+            // the order they're laid out instead.
+            Some(funcs_by_loc) => {
+                let i = funcs_by_loc.partition_point(|i| end(&self.funcs[*i]) < text_offset);
+                *funcs_by_loc.get(i)?
             }
+            None => match self.funcs.binary_search_values_by_key(&text_offset, end) {
+                Ok(k) => {
+                    // Exact match, pc is at the end of this function
+                    k
+                }
+                Err(k) => {
+                    // Not an exact match, k is where `pc` would be "inserted"
+                    // Since we key based on the end, function `k` might contain `pc`,
+                    // so we'll validate on the range check below
+                    k
+                }
+            },
         };
 
         let CompiledFunctionInfo { wasm_func_loc, .. } = self.funcs.get(index)?;
@@ -217,6 +251,12 @@ impl CompiledModule {
             .wasm_func_loc
     }
 
+    /// Returns the number of profile counters of the function `index`, which
+    /// is zero unless the module was compiled with profile instrumentation.
+    pub fn func_profile_counters(&self, index: DefinedFuncIndex) -> u32 {
+        self.funcs[index].profile_counters
+    }
+
     /// Returns the original binary offset in the file that `index` was defined
     /// at.
     pub fn func_start_srcloc(&self, index: DefinedFuncIndex) -> FilePos {
@@ -15,12 +15,13 @@ use alloc::sync::Arc;
 use core::fmt;
 use core::ops::Range;
 use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 #[cfg(feature = "std")]
 use std::{fs::File, path::Path};
 use wasmparser::{Parser, ValidPayload, Validator};
 use wasmtime_environ::{
-    CompiledModuleInfo, EntityIndex, HostPtr, ModuleTypes, ObjectKind, TypeTrace, VMOffsets,
-    VMSharedTypeIndex,
+    CompiledModuleInfo, DefinedFuncIndex, EntityIndex, FunctionProfile, HostPtr, ModuleProfile,
+    ModuleTypes, ObjectKind, PrimaryMap, TypeTrace, VMOffsets, VMSharedTypeIndex,
 };
 mod registry;
 
@@ -157,6 +158,36 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// The execution counters of each defined function, shared by all
+    /// instances of this module, present only if this module was compiled
+    /// with `Config::profile_instrumentation` enabled.
+    profile_counters: Option<PrimaryMap<DefinedFuncIndex, Box<[AtomicU64]>>>,
+
+    /// State used to recompile this module with the optimizing compiler,
+    /// present only if this module was compiled with
+    /// `Config::tiered_compilation` enabled.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    tier_up: Option<TierUp>,
+}
+
+/// State for the optimized tier of a module compiled with tiered compilation.
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+struct TierUp {
+    /// The original wasm binary, retained for recompilation.
+    wasm: Box<[u8]>,
+    /// The profile that guided the compilation, if any, which also guides
+    /// the recompilation.
+    profile_data: Option<Box<[u8]>>,
+    /// The value that the call counter of each defined function starts from
+    /// in new instances, derived from the profile. Empty without a profile,
+    /// in which case all counters start from zero.
+    counter_seeds: PrimaryMap<DefinedFuncIndex, u32>,
+    /// The module recompiled with the optimizing compiler, once available.
+    optimized: OnceLock<Module>,
+    /// Whether the recompilation has been queued on the engine's tier-up
+    /// thread.
+    started: AtomicBool,
 }
 
 impl fmt::Debug for Module {
@@ -531,6 +562,22 @@ impl Module {
 
         let _ = serializable;
 
+        let profile_counters = if module.module().profile_instrumentation {
+            Some(
+                module
+                    .module()
+                    .defined_func_indices()
+                    .map(|i| {
+                        (0..module.func_profile_counters(i))
+                            .map(|_| AtomicU64::new(0))
+                            .collect()
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
         Ok(Self {
             inner: Arc::new(ModuleInner {
                 engine: engine.clone(),
@@ -540,10 +587,165 @@ impl Module {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 serializable,
                 offsets,
+                profile_counters,
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                tier_up: None,
             }),
         })
     }
 
+    /// Records the original `wasm` binary of a freshly compiled module so it
+    /// can later be recompiled by [`Module::tier_up`].
+    ///
+    /// The calls that the profile saw, if any, count toward tiering up: the
+    /// call counter of each function starts from the number of times it was
+    /// entered in the profile, so functions which were hot then tier up
+    /// sooner, up to on their first call.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn with_tier_up_source(
+        mut self,
+        wasm: &[u8],
+        profile_data: Option<&[u8]>,
+    ) -> Result<Self> {
+        let mut counter_seeds = PrimaryMap::new();
+        if let Some(profile_data) = profile_data {
+            let profile = ModuleProfile::from_bytes(profile_data)?;
+            let max_seed = self.engine().tunables().tier_up_threshold.saturating_sub(1);
+            for index in self.env_module().defined_func_indices() {
+                let entries = profile.func(index).map_or(0, |f| f.entries());
+                counter_seeds.push(u32::try_from(entries).unwrap_or(u32::MAX).min(max_seed));
+            }
+        }
+        let inner = Arc::get_mut(&mut self.inner).expect("module should not be shared yet");
+        inner.tier_up = Some(TierUp {
+            wasm: wasm.into(),
+            profile_data: profile_data.map(|p| p.into()),
+            counter_seeds,
+            optimized: OnceLock::new(),
+            started: AtomicBool::new(false),
+        });
+        Ok(self)
+    }
+
+    /// Returns the value that the call counter of the defined function
+    /// `index` starts from in new instances of this module.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn tier_up_counter_seed(&self, index: DefinedFuncIndex) -> u32 {
+        self.inner
+            .tier_up
+            .as_ref()
+            .and_then(|t| t.counter_seeds.get(index).copied())
+            .unwrap_or(0)
+    }
+
+    /// Returns the optimized tier of this module if it's available, or
+    /// otherwise queues its compilation on the engine's tier-up thread unless
+    /// that has already happened.
+    ///
+    /// Returns `None` if this module can't be tiered up or if the optimized
+    /// tier isn't available yet.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn tier_up_in_background(&self) -> Option<&Module> {
+        let tier_up = self.inner.tier_up.as_ref()?;
+        if let Some(optimized) = tier_up.optimized.get() {
+            return Some(optimized);
+        }
+        if !tier_up.started.swap(true, Ordering::Relaxed) {
+            if let Some(worker) = self.engine().tier_up_worker() {
+                worker.submit(self.clone());
+            }
+        }
+        None
+    }
+
+    /// Recompiles this module with the optimizing compiler configured through
+    /// [`Config::tiered_compilation`](crate::Config::tiered_compilation).
+    ///
+    /// Modules compiled with tiered compilation are recompiled automatically
+    /// on a background thread of their engine once their code is hot. This
+    /// method can be used to recompile a module eagerly instead, and it blocks
+    /// the current thread until compilation has finished. Once this method
+    /// returns successfully all future instantiations of this module will use
+    /// the optimized code. Existing instances switch to the optimized code the
+    /// next time the baseline code of one of their hot functions is executed.
+    ///
+    /// Calling this method again after a successful tier-up is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this module wasn't compiled with tiered compilation
+    /// enabled, for example because it was deserialized, or if compilation with
+    /// the optimizing compiler fails.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn tier_up(&self) -> Result<()> {
+        let tier_up =
+            self.inner.tier_up.as_ref().ok_or_else(|| {
+                anyhow!("module was not compiled with tiered compilation enabled")
+            })?;
+        tier_up.optimized.get_or_try_init(|| {
+            crate::compile::compile_tier_up(
+                self.engine(),
+                &tier_up.wasm,
+                tier_up.profile_data.as_deref(),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns the execution counts collected by all instances of this module
+    /// so far, serialized for
+    /// [`CodeBuilder::profile_data`](crate::CodeBuilder::profile_data).
+    ///
+    /// The counts are collected only if this module was compiled with
+    /// [`Config::profile_instrumentation`](crate::Config::profile_instrumentation)
+    /// enabled, otherwise `None` is returned.
+    pub fn profile_data(&self) -> Option<Vec<u8>> {
+        if !self.env_module().profile_instrumentation {
+            return None;
+        }
+        let counters = self.inner.profile_counters.as_ref()?;
+        let profile = ModuleProfile::new(
+            counters
+                .values()
+                .map(|counters| {
+                    FunctionProfile::new(
+                        counters.iter().map(|c| c.load(Ordering::Relaxed)).collect(),
+                    )
+                })
+                .collect(),
+        );
+        Some(profile.to_bytes())
+    }
+
+    /// Returns the pointers to the execution counters of each defined
+    /// function, if this module counts its executions.
+    pub(crate) fn profile_counter_ptrs(&self) -> Option<impl Iterator<Item = NonNull<u64>> + '_> {
+        let counters = self.inner.profile_counters.as_ref()?;
+        Some(counters.values().map(|c| NonNull::from(&**c).cast::<u64>()))
+    }
+
+    /// Returns whether the optimized tier of this module is available,
+    /// either because [`Module::tier_up`] was called or because the module
+    /// was tiered up in the background.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn is_tiered_up(&self) -> bool {
+        self.inner
+            .tier_up
+            .as_ref()
+            .is_some_and(|t| t.optimized.get().is_some())
+    }
+
+    /// Returns the module that new instances of this module should be created
+    /// from: the optimized module if a tier-up has completed, or otherwise
+    /// this module itself.
+    pub(crate) fn latest_tier(&self) -> &Module {
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        if let Some(optimized) = self.inner.tier_up.as_ref().and_then(|t| t.optimized.get()) {
+            return optimized;
+        }
+        self
+    }
+
     /// Validates `binary` input data as a WebAssembly binary given the
     /// configuration in `engine`.
     ///
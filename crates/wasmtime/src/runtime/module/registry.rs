@@ -212,8 +212,9 @@ impl ModuleRegistry {
 
 impl LoadedCode {
     fn push_module(&mut self, module: &Module) {
-        let func = match module.compiled_module().finished_functions().next() {
-            Some((_, func)) => func,
+        let compiled_module = module.compiled_module();
+        let func = match compiled_module.first_func_in_text() {
+            Some(index) => compiled_module.finished_function(index),
             // There are no compiled functions in this module so there's no
             // need to push onto `self.modules` which is only used for frame
             // information lookup for a trap which only symbolicates defined
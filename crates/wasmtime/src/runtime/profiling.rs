@@ -123,10 +123,11 @@ impl GuestProfiler {
                 let text_range = {
                     // Assumption: within text, the code for a given module is packed linearly and
                     // is non-overlapping; if this is violated, it should be safe but might result
-                    // in incorrect profiling results.
-                    let start =
-                        compiled.finished_functions().next()?.1.as_ptr_range().start as usize;
-                    let end = compiled.finished_functions().last()?.1.as_ptr_range().end as usize;
+                    // in incorrect profiling results. Functions aren't necessarily laid out
+                    // in index order, for example with profile-guided optimization.
+                    let funcs = || compiled.finished_functions().map(|(_, f)| f.as_ptr_range());
+                    let start = funcs().map(|r| r.start as usize).min()?;
+                    let end = funcs().map(|r| r.end as usize).max()?;
                     start..end
                 };
 
@@ -0,0 +1,214 @@
+//! Debugging support for stores which execute WebAssembly with the Pulley
+//! interpreter.
+
+use crate::Module;
+use crate::prelude::*;
+use core::ptr::NonNull;
+use pulley_interpreter::interp::{DebugStop, Debugger, MachineState, StopReason};
+use pulley_interpreter::{FReg, Reg, XReg};
+
+/// Breakpoints, single-stepping and a stop hook for a [`Store`](crate::Store)
+/// which executes WebAssembly with the Pulley interpreter.
+///
+/// This is acquired with [`Store::pulley_debugger`](crate::Store::pulley_debugger)
+/// or, while stopped, [`PulleyStop::debugger`].
+///
+/// Pulley executes the bytecode in a [`Module`]'s [`Module::text`] section
+/// directly, so breakpoints are set on offsets within that section. The
+/// offsets of each function are available through [`Module::functions`] and
+/// offsets can be mapped to the original WebAssembly with
+/// [`Module::address_map`].
+///
+/// The hook is invoked before executing an instruction with a breakpoint, or
+/// before every instruction while single-stepping. Execution continues once
+/// the hook returns. The hook is never invoked when the store does not have a
+/// hook installed.
+///
+/// This API is only available when the `debug-pulley` Cargo feature is
+/// enabled.
+pub struct PulleyDebugger<'a> {
+    debugger: &'a mut Debugger,
+}
+
+impl<'a> PulleyDebugger<'a> {
+    pub(crate) fn new(debugger: &'a mut Debugger) -> Self {
+        PulleyDebugger { debugger }
+    }
+
+    /// Installs `hook` to be invoked each time execution stops, replacing any
+    /// previously installed hook.
+    pub fn set_hook(&mut self, mut hook: impl FnMut(&mut PulleyStop<'_>) + Send + 'static) {
+        self.debugger
+            .set_hook(Some(Box::new(move |stop: &mut DebugStop<'_>| {
+                hook(&mut PulleyStop::new(stop))
+            })));
+    }
+
+    /// Removes the hook installed with [`PulleyDebugger::set_hook`], if any,
+    /// so execution no longer stops.
+    pub fn clear_hook(&mut self) {
+        self.debugger.set_hook(None);
+    }
+
+    /// Enables or disables stopping before every instruction.
+    pub fn set_single_step(&mut self, enable: bool) {
+        self.debugger.set_single_step(enable);
+    }
+
+    /// Returns whether single-stepping is enabled.
+    pub fn single_step(&self) -> bool {
+        self.debugger.single_step()
+    }
+
+    /// Adds a breakpoint on the instruction at `text_offset` within
+    /// `module`'s [`Module::text`] section.
+    ///
+    /// The offset must be the start of an instruction for the breakpoint to
+    /// be hit. Returns `false` if the breakpoint was already set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text_offset` is out of bounds of `module`'s text.
+    pub fn add_breakpoint(&mut self, module: &Module, text_offset: usize) -> Result<bool> {
+        let pc = breakpoint_pc(module, text_offset)?;
+        Ok(self.debugger.add_breakpoint(pc))
+    }
+
+    /// Removes the breakpoint on the instruction at `text_offset` within
+    /// `module`'s [`Module::text`] section.
+    ///
+    /// Returns `false` if no breakpoint was set there.
+    pub fn remove_breakpoint(&mut self, module: &Module, text_offset: usize) -> bool {
+        match breakpoint_pc(module, text_offset) {
+            Ok(pc) => self.debugger.remove_breakpoint(pc),
+            Err(_) => false,
+        }
+    }
+
+    /// Removes all breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.clear_breakpoints();
+    }
+}
+
+fn breakpoint_pc(module: &Module, text_offset: usize) -> Result<NonNull<u8>> {
+    let text = module.text();
+    match text.get(text_offset) {
+        Some(byte) => Ok(NonNull::from(byte)),
+        None => bail!(
+            "text offset {text_offset:#x} is out of bounds of module text of size {:#x}",
+            text.len()
+        ),
+    }
+}
+
+/// Why execution stopped and invoked the hook installed with
+/// [`PulleyDebugger::set_hook`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PulleyStopReason {
+    /// Single-stepping is enabled.
+    Step,
+    /// The next instruction has a breakpoint.
+    Breakpoint,
+}
+
+/// The state of the Pulley interpreter while stopped before an instruction,
+/// passed to the hook installed with [`PulleyDebugger::set_hook`].
+pub struct PulleyStop<'a> {
+    state: &'a mut MachineState,
+    pc: NonNull<u8>,
+    reason: PulleyStopReason,
+}
+
+impl<'a> PulleyStop<'a> {
+    fn new(stop: &'a mut DebugStop<'_>) -> Self {
+        let pc = stop.pc();
+        let reason = match stop.reason() {
+            StopReason::Step => PulleyStopReason::Step,
+            StopReason::Breakpoint => PulleyStopReason::Breakpoint,
+        };
+        PulleyStop {
+            state: stop.state_mut(),
+            pc,
+            reason,
+        }
+    }
+
+    /// Returns why execution stopped.
+    pub fn reason(&self) -> PulleyStopReason {
+        self.reason
+    }
+
+    /// Returns the address of the instruction about to execute.
+    pub fn pc(&self) -> usize {
+        self.pc.as_ptr() as usize
+    }
+
+    /// Returns the offset of the instruction about to execute within
+    /// `module`'s [`Module::text`] section, or `None` if it's not within
+    /// `module`.
+    pub fn text_offset(&self, module: &Module) -> Option<usize> {
+        let text = module.text().as_ptr_range();
+        let pc = self.pc.as_ptr().cast_const();
+        if text.contains(&pc) {
+            Some(self.pc() - text.start as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of the `x` register `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a valid `x` register, that is 32 or greater.
+    pub fn x_reg(&self, index: u8) -> u64 {
+        self.state[XReg::new(index).expect("invalid x register")].get_u64()
+    }
+
+    /// Returns the value of the `f` register `index` as raw bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a valid `f` register, that is 32 or greater.
+    pub fn f_reg(&self, index: u8) -> u64 {
+        self.state[FReg::new(index).expect("invalid f register")]
+            .get_f64()
+            .to_bits()
+    }
+
+    /// Returns the value of the `v` register `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a valid `v` register, that is 32 or greater.
+    #[cfg(not(pulley_disable_interp_simd))]
+    pub fn v_reg(&self, index: u8) -> u128 {
+        self.state[pulley_interpreter::VReg::new(index).expect("invalid v register")].get_u128()
+    }
+
+    /// Returns the value of the stack pointer.
+    pub fn sp(&self) -> usize {
+        self.state[XReg::sp].get_ptr::<u8>() as usize
+    }
+
+    /// Returns the value of the frame pointer.
+    pub fn fp(&self) -> usize {
+        self.state.fp() as usize
+    }
+
+    /// Returns the value of the link register.
+    pub fn lr(&self) -> usize {
+        self.state.lr() as usize
+    }
+
+    /// Returns the debugger, for example to add breakpoints or change
+    /// single-stepping.
+    ///
+    /// The hook currently executing is reinstalled once it returns unless
+    /// [`PulleyDebugger::set_hook`] or [`PulleyDebugger::clear_hook`] is
+    /// called.
+    pub fn debugger(&mut self) -> PulleyDebugger<'_> {
+        PulleyDebugger::new(self.state.debugger_mut())
+    }
+}
@@ -975,6 +975,20 @@ impl<T> Store<T> {
     ) {
         self.inner.epoch_deadline_callback(Box::new(callback));
     }
+
+    /// Returns the debugger for this store's Pulley interpreter, used to set
+    /// breakpoints, single-step and inspect registers.
+    ///
+    /// Returns `None` if this store's [`Engine`] does not execute WebAssembly
+    /// with Pulley.
+    ///
+    /// See [`PulleyDebugger`](crate::PulleyDebugger) for more information.
+    /// This method is only available when the `debug-pulley` Cargo feature is
+    /// enabled.
+    #[cfg(feature = "debug-pulley")]
+    pub fn pulley_debugger(&mut self) -> Option<crate::PulleyDebugger<'_>> {
+        self.inner.pulley_debugger()
+    }
 }
 
 impl<'a, T> StoreContext<'a, T> {
@@ -1068,6 +1082,14 @@ impl<'a, T> StoreContextMut<'a, T> {
     pub fn epoch_deadline_trap(&mut self) {
         self.0.epoch_deadline_trap();
     }
+
+    /// Returns the debugger for this store's Pulley interpreter.
+    ///
+    /// For more information see [`Store::pulley_debugger`].
+    #[cfg(feature = "debug-pulley")]
+    pub fn pulley_debugger(&mut self) -> Option<crate::PulleyDebugger<'_>> {
+        self.0.pulley_debugger()
+    }
 }
 
 impl<T> StoreInner<T> {
@@ -1975,6 +1997,24 @@ at https://bytecodealliance.org/security.
     #[cfg(feature = "async")]
     pub(crate) fn swap_executor(&mut self, executor: &mut Executor) {
         mem::swap(&mut self.executor, executor);
+
+        // The debugger belongs to the store rather than any one interpreter,
+        // so it follows the store onto the executor it's now using.
+        #[cfg(feature = "debug-pulley")]
+        if let (Executor::Interpreter(new), Executor::Interpreter(old)) =
+            (&mut self.executor, executor)
+        {
+            mem::swap(new.debugger_mut(), old.debugger_mut());
+        }
+    }
+
+    #[cfg(feature = "debug-pulley")]
+    pub(crate) fn pulley_debugger(&mut self) -> Option<crate::PulleyDebugger<'_>> {
+        match &mut self.executor {
+            Executor::Interpreter(i) => Some(crate::PulleyDebugger::new(i.debugger_mut())),
+            #[cfg(has_host_compiler_backend)]
+            Executor::Native => None,
+        }
     }
 
     pub(crate) fn unwinder(&self) -> &'static dyn Unwind {
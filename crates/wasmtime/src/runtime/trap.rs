@@ -238,7 +238,7 @@ impl WasmBacktrace {
     /// ```
     pub fn capture(store: impl AsContext) -> WasmBacktrace {
         let store = store.as_context();
-        if store.engine().config().wasm_backtrace {
+        if store.engine().config().wasm_backtrace_enabled() {
             Self::force_capture(store)
         } else {
             WasmBacktrace {
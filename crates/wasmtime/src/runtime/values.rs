@@ -1032,6 +1032,7 @@ mod tests {
             || cfg!(target_arch = "aarch64")
             || cfg!(target_arch = "s390x")
             || cfg!(target_arch = "riscv64")
+            || cfg!(target_arch = "loongarch64")
             || cfg!(target_arch = "arm")
         {
             24
@@ -1051,6 +1052,7 @@ mod tests {
             || cfg!(target_arch = "aarch64")
             || cfg!(target_arch = "s390x")
             || cfg!(target_arch = "riscv64")
+            || cfg!(target_arch = "loongarch64")
             || cfg!(target_arch = "arm")
         {
             24
@@ -1216,6 +1216,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_profiled_funcs: 0,
         });
 
         assert_eq!(
@@ -1246,6 +1247,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_profiled_funcs: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_next() as usize,
@@ -1275,6 +1277,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_profiled_funcs: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_end() as usize,
@@ -214,6 +214,14 @@ pub struct Instance {
     /// functions, lazy initialization state, etc.
     runtime_info: ModuleRuntimeInfo,
 
+    /// The optimized tier of the module, once this instance has switched its
+    /// functions over to it with `switch_to_tier`. Only the code pointers of
+    /// this instance's `VMFuncRef`s come from it: all other metadata is still
+    /// that of `runtime_info`, which baseline frames of this instance that
+    /// are still on the stack rely on.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    optimized_tier: Option<ModuleRuntimeInfo>,
+
     /// WebAssembly linear memory data.
     ///
     /// This is where all runtime information about defined linear memories in
@@ -280,6 +288,8 @@ impl Instance {
         let mut ret = OwnedInstance::new(Instance {
             id: req.id,
             runtime_info: req.runtime_info.clone(),
+            #[cfg(any(feature = "cranelift", feature = "winch"))]
+            optimized_tier: None,
             memories,
             tables,
             dropped_elements,
@@ -345,7 +355,7 @@ impl Instance {
         self.runtime_info.env_module()
     }
 
-    #[cfg(feature = "gc")]
+    #[cfg(any(feature = "gc", feature = "cranelift", feature = "winch"))]
     pub(crate) fn runtime_module(&self) -> Option<&crate::Module> {
         match &self.runtime_info {
             ModuleRuntimeInfo::Module(m) => Some(m),
@@ -365,6 +375,64 @@ impl Instance {
         self.runtime_info.offsets()
     }
 
+    /// Switches the functions of this instance over to the code of
+    /// `optimized`, the optimized tier of the module it was instantiated from.
+    ///
+    /// Both tiers are compiled from the same wasm with the same tunables, so
+    /// they share the layout of the `VMContext` and the engine-level types.
+    /// This only redirects the `VMFuncRef`s of the defined functions of this
+    /// instance, which all escape in modules which tier up and which calls
+    /// within the instance go through, to the optimized code. Everything else
+    /// about this instance, such as its runtime info, stays the same, so
+    /// baseline frames of this instance which are still on the stack keep
+    /// running as before.
+    ///
+    /// Does nothing if this instance has already switched.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn switch_to_tier(mut self: Pin<&mut Self>, optimized: crate::Module) {
+        if self.optimized_tier.is_some() {
+            return;
+        }
+        let optimized = ModuleRuntimeInfo::Module(optimized);
+        assert!(self.env_module().tier_up && optimized.env_module().tier_up);
+        assert_eq!(
+            self.offsets().size_of_vmctx(),
+            optimized.offsets().size_of_vmctx()
+        );
+        assert_eq!(
+            self.offsets().vmctx_func_refs_begin(),
+            optimized.offsets().vmctx_func_refs_begin()
+        );
+        assert_eq!(self.runtime_info.type_ids(), optimized.type_ids());
+
+        // SAFETY: `optimized_tier` isn't structurally pinned.
+        unsafe {
+            self.as_mut().get_unchecked_mut().optimized_tier = Some(optimized);
+        }
+
+        let module = self.env_module().clone();
+        for index in module.num_imported_funcs..module.functions.len() {
+            self.as_mut().get_func_ref(FuncIndex::new(index));
+        }
+    }
+
+    /// Returns whether this instance has switched its functions over to the
+    /// optimized tier of its module.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) fn is_tiered_up(&self) -> bool {
+        self.optimized_tier.is_some()
+    }
+
+    /// Returns the runtime info that the code of the `VMFuncRef`s of this
+    /// instance's defined functions comes from.
+    fn func_ref_code(&self) -> &ModuleRuntimeInfo {
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        if let Some(optimized) = &self.optimized_tier {
+            return optimized;
+        }
+        &self.runtime_info
+    }
+
     /// Return the indexed `VMFunctionImport`.
     fn imported_function(&self, index: FuncIndex) -> &VMFunctionImport {
         unsafe { self.vmctx_plus_offset(self.offsets().vmctx_vmfunction_import(index)) }
@@ -827,13 +895,13 @@ impl Instance {
         into: *mut VMFuncRef,
     ) {
         let func_ref = if let Some(def_index) = self.env_module().defined_func_index(index) {
+            let code = self.func_ref_code();
             VMFuncRef {
-                array_call: self
-                    .runtime_info
+                array_call: code
                     .array_to_wasm_trampoline(def_index)
                     .expect("should have array-to-Wasm trampoline for escaping function")
                     .into(),
-                wasm_call: Some(self.runtime_info.function(def_index).into()),
+                wasm_call: Some(code.function(def_index).into()),
                 vmctx: VMOpaqueContext::from_vmcontext(self.vmctx()).into(),
                 type_index,
             }
@@ -1453,6 +1521,42 @@ impl Instance {
             ));
             ptr = ptr.add(1);
         }
+
+        // The funcrefs of the defined functions of a module which tiers up
+        // are read directly by its code to call them, so unlike other
+        // funcrefs they're initialized eagerly.
+        if module.tier_up {
+            for index in module.num_imported_funcs..module.functions.len() {
+                self.as_mut().get_func_ref(FuncIndex::new(index));
+            }
+        }
+
+        // Point each defined function at its execution counters, if the
+        // module counts them.
+        if let ModuleRuntimeInfo::Module(m) = &self.runtime_info {
+            if let Some(counters) = m.profile_counter_ptrs() {
+                let mut ptr = self.vmctx_plus_offset_raw(offsets.vmctx_profile_counters_begin());
+                for counters in counters {
+                    ptr.write(VmPtr::from(counters));
+                    ptr = ptr.add(1);
+                }
+            }
+        }
+
+        // Functions of a module which tiers up count their calls in each
+        // instance, starting from zero or from the calls seen by the module's
+        // profile.
+        if module.tier_up {
+            for index in module.defined_func_indices() {
+                let seed = match &self.runtime_info {
+                    #[cfg(any(feature = "cranelift", feature = "winch"))]
+                    ModuleRuntimeInfo::Module(m) => m.tier_up_counter_seed(index),
+                    _ => 0,
+                };
+                self.vmctx_plus_offset_raw::<u32>(offsets.vmctx_tier_up_counter(index))
+                    .write(seed);
+            }
+        }
     }
 
     /// Attempts to convert from the host `addr` specified to a WebAssembly
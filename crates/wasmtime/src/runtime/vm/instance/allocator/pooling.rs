@@ -21,6 +21,7 @@
 mod decommit_queue;
 mod index_allocator;
 mod memory_pool;
+mod metrics;
 mod table_pool;
 
 #[cfg(feature = "gc")]
@@ -42,6 +43,7 @@ cfg_if::cfg_if! {
 
 use self::decommit_queue::DecommitQueue;
 use self::memory_pool::MemoryPool;
+pub use self::metrics::PoolingAllocatorMetrics;
 use self::table_pool::TablePool;
 use super::{
     InstanceAllocationRequest, InstanceAllocatorImpl, MemoryAllocationIndex, TableAllocationIndex,
@@ -499,6 +501,10 @@ impl PoolingInstanceAllocator {
 }
 
 unsafe impl InstanceAllocatorImpl for PoolingInstanceAllocator {
+    fn as_pooling(&self) -> Option<&PoolingInstanceAllocator> {
+        Some(self)
+    }
+
     #[cfg(feature = "component-model")]
     fn validate_component_impl<'a>(
         &self,
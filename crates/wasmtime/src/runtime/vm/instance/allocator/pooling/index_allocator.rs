@@ -36,6 +36,10 @@ impl SimpleIndexAllocator {
         self.0.is_empty()
     }
 
+    pub fn num_used_slots(&self) -> usize {
+        self.0.num_used_slots()
+    }
+
     pub fn alloc(&self) -> Option<SlotId> {
         self.0.alloc(None)
     }
@@ -185,6 +189,22 @@ impl ModuleAffinityIndexAllocator {
             .any(|s| matches!(s, SlotState::Used(_)))
     }
 
+    /// How many slots are in use right now?
+    pub fn num_used_slots(&self) -> usize {
+        let inner = self.0.lock().unwrap();
+        inner
+            .slot_state
+            .iter()
+            .filter(|s| matches!(s, SlotState::Used(_)))
+            .count()
+    }
+
+    /// How many slots are unused but still warm with a previous allocation's
+    /// state?
+    pub fn num_unused_warm_slots(&self) -> u32 {
+        self.0.lock().unwrap().unused_warm_slots
+    }
+
     /// Allocate a new index from this allocator optionally using `id` as an
     /// affinity request if the allocation strategy supports it.
     ///
@@ -315,6 +315,22 @@ impl MemoryPool {
         self.stripes.iter().all(|s| s.allocator.is_empty())
     }
 
+    /// How many memory slots are in use right now?
+    pub fn num_used_slots(&self) -> usize {
+        self.stripes
+            .iter()
+            .map(|s| s.allocator.num_used_slots())
+            .sum()
+    }
+
+    /// How many memory slots are unused but still warm?
+    pub fn num_unused_warm_slots(&self) -> u32 {
+        self.stripes
+            .iter()
+            .map(|s| s.allocator.num_unused_warm_slots())
+            .sum()
+    }
+
     /// Allocate a single memory for the given instance allocation request.
     pub fn allocate(
         &self,
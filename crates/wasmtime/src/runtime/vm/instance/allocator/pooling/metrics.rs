@@ -0,0 +1,59 @@
+use super::PoolingInstanceAllocator;
+use crate::Engine;
+use core::sync::atomic::Ordering;
+
+/// Metrics about the current usage of an engine's pooling instance allocator.
+///
+/// This is created with [`Engine::pooling_allocator_metrics`] and reflects the
+/// state of the pool at the time each method is called. It's intended for
+/// monitoring how close an embedding is to the limits configured with
+/// [`PoolingAllocationConfig`](crate::PoolingAllocationConfig).
+#[derive(Clone)]
+pub struct PoolingAllocatorMetrics {
+    engine: Engine,
+}
+
+impl PoolingAllocatorMetrics {
+    pub(crate) fn new(engine: &Engine) -> Option<Self> {
+        engine.allocator().as_pooling()?;
+        Some(PoolingAllocatorMetrics {
+            engine: engine.clone(),
+        })
+    }
+
+    fn allocator(&self) -> &PoolingInstanceAllocator {
+        self.engine
+            .allocator()
+            .as_pooling()
+            .expect("checked in `new`")
+    }
+
+    /// Returns the number of core WebAssembly instances currently allocated.
+    pub fn core_instances(&self) -> u64 {
+        self.allocator().live_core_instances.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of component instances currently allocated.
+    pub fn component_instances(&self) -> u64 {
+        self.allocator()
+            .live_component_instances
+            .load(Ordering::Acquire)
+    }
+
+    /// Returns the number of linear memory slots currently in use.
+    pub fn memories(&self) -> usize {
+        self.allocator().memories.num_used_slots()
+    }
+
+    /// Returns the number of table slots currently in use.
+    pub fn tables(&self) -> usize {
+        self.allocator().tables.num_used_slots()
+    }
+
+    /// Returns the number of linear memory slots which are unused but are
+    /// being kept warm for reuse, as configured with
+    /// [`PoolingAllocationConfig::max_unused_warm_slots`](crate::PoolingAllocationConfig::max_unused_warm_slots).
+    pub fn unused_warm_memories(&self) -> u32 {
+        self.allocator().memories.num_unused_warm_slots()
+    }
+}
@@ -97,6 +97,11 @@ impl TablePool {
         self.index_allocator.is_empty()
     }
 
+    /// How many table slots are in use right now?
+    pub fn num_used_slots(&self) -> usize {
+        self.index_allocator.num_used_slots()
+    }
+
     /// Get the base pointer of the given table allocation.
     fn get(&self, table_index: TableAllocationIndex) -> *mut u8 {
         assert!(table_index.index() < self.max_total_tables);
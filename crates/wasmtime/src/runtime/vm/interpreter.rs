@@ -72,6 +72,16 @@ impl Interpreter {
         unsafe { self.pulley.get().as_ref() }
     }
 
+    /// Returns the debugger of this interpreter's `Vm`.
+    #[cfg(feature = "debug-pulley")]
+    pub fn debugger_mut(&mut self) -> &mut pulley_interpreter::interp::Debugger {
+        // SAFETY: like `as_interpreter_ref` the `&mut self` here guarantees
+        // exclusive access to the `Vm`.
+        unsafe { self.pulley.get().as_mut() }
+            .state_mut()
+            .debugger_mut()
+    }
+
     /// Get an implementation of `Unwind` used to walk the Pulley stack.
     pub fn unwinder(&self) -> &'static dyn Unwind {
         &UnwindPulley
@@ -1145,6 +1145,35 @@ fn out_of_gas(store: &mut dyn VMStore, _instance: Pin<&mut Instance>) -> Result<
     store.out_of_gas()
 }
 
+// Hook for when a function of a module which tiers up has been called
+// `Tunables::tier_up_threshold` more times.
+fn tier_up(store: &mut dyn VMStore, instance: Pin<&mut Instance>) -> Result<()> {
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    {
+        if instance.is_tiered_up() {
+            return Ok(());
+        }
+        let Some(optimized) = instance
+            .runtime_module()
+            .and_then(|m| m.tier_up_in_background())
+            .cloned()
+        else {
+            return Ok(());
+        };
+        log::debug!("switching instance to the optimized tier");
+        // Keep the optimized code registered with the store so traps and
+        // backtraces within it can be resolved.
+        store
+            .store_opaque_mut()
+            .modules_mut()
+            .register_module(&optimized);
+        instance.switch_to_tier(optimized);
+    }
+    #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+    let _ = (store, instance);
+    Ok(())
+}
+
 // Hook for when an instance observes that the epoch has changed.
 #[cfg(target_has_atomic = "64")]
 fn new_epoch(store: &mut dyn VMStore, _instance: Pin<&mut Instance>) -> Result<NextEpoch> {
@@ -510,7 +510,7 @@ mod call_thread_state {
                 jmp_buf: Cell::new(ptr::null()),
                 #[cfg(all(has_native_signals))]
                 signal_handler: store.signal_handler(),
-                capture_backtrace: store.engine().config().wasm_backtrace,
+                capture_backtrace: store.engine().config().wasm_backtrace_enabled(),
                 #[cfg(feature = "coredump")]
                 capture_coredump: store.engine().config().coredump_on_trap,
                 vm_store_context: store.vm_store_context_ptr(),
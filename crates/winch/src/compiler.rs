@@ -97,6 +97,7 @@ impl wasmtime_environ::Compiler for Compiler {
         types: &ModuleTypesBuilder,
         _symbol: &str,
     ) -> Result<CompiledFunctionBody, CompileError> {
+        let def_index = index;
         let index = translation.module.func_index(index);
         let sig = translation.module.functions[index]
             .signature
@@ -110,6 +111,7 @@ impl wasmtime_environ::Compiler for Compiler {
         let func = self
             .isa
             .compile_function(
+                def_index,
                 ty,
                 &body,
                 translation,
@@ -137,6 +139,8 @@ impl wasmtime_environ::Compiler for Compiler {
             code: Box::new(func),
             // TODO: Winch doesn't support GC objects and stack maps and all that yet.
             needs_gc_heap: false,
+            // Calls are counted in the `VMContext` of each instance instead.
+            profile_counters: 0,
         })
     }
 
@@ -0,0 +1,131 @@
+//! Benchmarks comparing each superinstruction from
+//! `for_each_superinstruction!` against the sequence of instructions it fuses.
+//!
+//! Both are run in a loop and, before timing them, the number of instruction
+//! dispatches each loop performs is printed to show how many dispatches
+//! fusing removes. Criterion's throughput for each benchmark is the number of
+//! loop iterations, so the two are directly comparable.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use pulley_interpreter::decode::{Decoder, OpVisitor, SafeBytecodeStream};
+use pulley_interpreter::interp::{DoneReason, Vm};
+use pulley_interpreter::op::MaterializeOpsVisitor;
+use pulley_interpreter::*;
+use std::hint::black_box;
+use std::ptr::NonNull;
+
+criterion_main!(benches);
+criterion_group!(benches, bench_superinstructions);
+
+const ITERS: u64 = 10_000;
+
+fn x(n: u8) -> XReg {
+    XReg::new(n).unwrap()
+}
+
+/// Picks the value of the `n`th field of a superinstruction.
+trait Field {
+    fn nth(n: u8) -> Self;
+}
+
+impl Field for XReg {
+    // Combine the loop index in `x2` into the result accumulated in `x0`.
+    fn nth(n: u8) -> XReg {
+        if n == 1 { x(2) } else { x(0) }
+    }
+}
+
+impl Field for U6 {
+    fn nth(_: u8) -> U6 {
+        U6::new(2).unwrap()
+    }
+}
+
+/// Encodes a loop which runs `body` `ITERS` times and then returns.
+///
+/// The loop counter is kept in `x1` and the loop index is kept in `x2`.
+fn encode_loop(body: &[u8]) -> Vec<u8> {
+    let mut bytecode = body.to_vec();
+    encode::xsub32_u8(&mut bytecode, x(1), x(1), 1);
+    encode::xadd32_u8(&mut bytecode, x(2), x(2), 1);
+    let offset = -i32::try_from(bytecode.len()).unwrap();
+    encode::br_if_xneq32_i8(&mut bytecode, x(1), 0, PcRelOffset::from(offset));
+    encode::ret(&mut bytecode);
+    bytecode
+}
+
+/// Returns how many instructions are dispatched when running a loop built by
+/// `encode_loop`.
+fn dispatches(bytecode: &[u8]) -> u64 {
+    let mut visitor = MaterializeOpsVisitor::new(SafeBytecodeStream::new(bytecode));
+    let mut decoder = Decoder::new();
+    let mut ops = 0;
+    while !visitor.bytecode().as_slice().is_empty() {
+        decoder.decode_one(&mut visitor).unwrap();
+        ops += 1;
+    }
+    // Everything but the final `ret` runs once per iteration.
+    (ops - 1) * ITERS + 1
+}
+
+fn run(vm: &mut Vm, bytecode: &[u8]) -> u64 {
+    vm.state_mut()[x(0)].set_u64(0);
+    vm.state_mut()[x(1)].set_u64(ITERS);
+    vm.state_mut()[x(2)].set_u64(0);
+    match unsafe { vm.call(NonNull::from(bytecode).cast(), &[], []) } {
+        DoneReason::ReturnToHost(_) => {}
+        _ => unreachable!(),
+    }
+    vm.state_mut()[x(0)].get_u64()
+}
+
+fn bench(c: &mut Criterion, name: &str, sequence: &[u8], fused: &[u8]) {
+    let sequence = encode_loop(sequence);
+    let fused = encode_loop(fused);
+
+    let mut vm = Vm::new();
+    assert_eq!(run(&mut vm, &sequence), run(&mut vm, &fused));
+
+    let before = dispatches(&sequence);
+    let after = dispatches(&fused);
+    println!(
+        "{name}: {after} dispatches instead of {before} ({:.1}% fewer)",
+        (before - after) as f64 / before as f64 * 100.0
+    );
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(ITERS));
+    group.bench_function("sequence", |b| {
+        b.iter(|| run(&mut vm, black_box(&sequence)))
+    });
+    group.bench_function("fused", |b| b.iter(|| run(&mut vm, black_box(&fused))));
+    group.finish();
+}
+
+macro_rules! bench_superinstructions {
+    (
+        $(
+            $( #[$attr:meta] )*
+            $snake_name:ident = $name:ident { $( $field:ident : $field_ty:ident ),* }
+                => [ $( $op:ident ( $($arg:tt)* ); )* ]
+                lower [ $($lower:tt)* ];
+        )*
+    ) => {
+        fn bench_superinstructions(c: &mut Criterion) {
+            $(
+                let mut n = 0;
+                let mut next = || {
+                    n += 1;
+                    n - 1
+                };
+                $( let $field = <$field_ty as Field>::nth(next()); )*
+                let mut fused = vec![];
+                encode::$snake_name(&mut fused, $( $field ),*);
+                let mut sequence = vec![];
+                $( encode::$op(&mut sequence, $($arg)*); )*
+                bench(c, stringify!($snake_name), &sequence, &fused);
+            )*
+        }
+    };
+}
+for_each_superinstruction!(bench_superinstructions);
@@ -0,0 +1,193 @@
+//! Example program which finds the hottest sequences of opcodes in a pulley
+//! profile, to help pick candidates for new superinstructions.
+//!
+//! Profiles are recorded the same way as for the `profiler-html` example:
+//!
+//! ```text
+//! $ cargo build --release --features profile-pulley
+//! $ ./target/release/wasmtime run --profile pulley --target pulley64 \
+//!   your_wasm_file.wasm
+//! ```
+//!
+//! The resulting `pulley-$pid.data` file is then fed to this program:
+//!
+//! ```text
+//! $ cargo run -p pulley-interpreter --example profiler-sequences --all-features \
+//!     ./pulley-$pid.data
+//! ```
+//!
+//! This prints sequences of opcodes which execute one after another without
+//! any intervening control flow, sorted by an estimate of how many
+//! instruction dispatches would be removed by fusing the sequence into a
+//! single superinstruction. The number of times a sequence executed is
+//! estimated as the smallest number of samples taken on any one of its
+//! instructions, and fusing a sequence of `n` instructions removes `n - 1`
+//! dispatches each time it executes.
+//!
+//! A sequence worth fusing is added as a new entry in
+//! `for_each_superinstruction!`, listing the ops it fuses and the Cranelift
+//! lowering rules which should select it. The op, its interpreter
+//! implementation and its lowering are all generated from that entry, and the
+//! `superinstructions` benchmark then shows how many dispatches it saves:
+//!
+//! ```text
+//! $ cargo bench -p pulley-interpreter --features interp --bench superinstructions
+//! ```
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use pulley_interpreter::decode::{BytecodeStream, Decoder, OpVisitor, SafeBytecodeStream};
+use pulley_interpreter::op::{MaterializeOpsVisitor, Op};
+use pulley_interpreter::profile::{Event, decode};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct ProfilerSequences {
+    /// The profile data to load which was generated by a `--profile pulley` run
+    /// of Wasmtime previously.
+    profile: PathBuf,
+
+    /// The maximum length of opcode sequences to consider.
+    #[clap(long, default_value = "3")]
+    max_len: usize,
+
+    /// The number of sequences to print.
+    #[clap(long, default_value = "20")]
+    top: usize,
+}
+
+struct Function<'a> {
+    addr: u64,
+    body: &'a [u8],
+    instructions: BTreeMap<u32, u64>,
+}
+
+#[derive(Default)]
+struct Sequence {
+    /// Estimated number of times this sequence executed, in samples.
+    hits: u64,
+    /// Number of distinct locations in the bytecode this sequence occurs at.
+    sites: u32,
+}
+
+fn main() -> Result<()> {
+    let args = ProfilerSequences::parse();
+    if args.max_len < 2 {
+        bail!("`--max-len` must be at least 2");
+    }
+    let profile = std::fs::read(&args.profile)
+        .with_context(|| format!("failed to read {:?}", args.profile))?;
+
+    let mut functions = BTreeMap::new();
+    let mut total = 0;
+    for event in decode(&profile) {
+        match event? {
+            Event::Function(addr, _name, body) => {
+                functions.insert(
+                    addr,
+                    Function {
+                        addr,
+                        body,
+                        instructions: BTreeMap::new(),
+                    },
+                );
+            }
+            Event::Samples(samples) => {
+                for sample in samples {
+                    let addr = sample.0;
+                    let (_, function) = functions.range_mut(..=addr).next_back().unwrap();
+                    total += 1;
+                    *function
+                        .instructions
+                        .entry(u32::try_from(addr - function.addr).unwrap())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    if total == 0 {
+        bail!("no samples found in profile");
+    }
+
+    let mut sequences = HashMap::<Vec<&'static str>, Sequence>::new();
+    for func in functions.values() {
+        let ops = decode_ops(func.body)?;
+        for start in 0..ops.len() {
+            let mut hits = u64::MAX;
+            for (i, (offset, op)) in ops[start..].iter().enumerate().take(args.max_len) {
+                hits = hits.min(func.instructions.get(offset).copied().unwrap_or(0));
+                if hits == 0 {
+                    break;
+                }
+                if i > 0 {
+                    let names = ops[start..=start + i]
+                        .iter()
+                        .map(|(_, op)| op.name())
+                        .collect();
+                    let sequence = sequences.entry(names).or_default();
+                    sequence.hits += hits;
+                    sequence.sites += 1;
+                }
+                if is_control_flow(op) {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut sequences = sequences
+        .into_iter()
+        .map(|(names, seq)| {
+            let saved = seq.hits * (names.len() as u64 - 1);
+            (saved, names, seq)
+        })
+        .collect::<Vec<_>>();
+    sequences.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    println!("{total} samples");
+    println!(" saved%   hits%  sites  sequence");
+    for (saved, names, seq) in sequences.iter().take(args.top) {
+        println!(
+            "{:6.02}% {:6.02}% {:6}  {}",
+            (*saved as f64) / (total as f64) * 100.0,
+            (seq.hits as f64) / (total as f64) * 100.0,
+            seq.sites,
+            names.join(" ; "),
+        );
+    }
+
+    Ok(())
+}
+
+/// Decodes all instructions in `body`, returning each one along with its
+/// offset.
+fn decode_ops(body: &[u8]) -> Result<Vec<(u32, Op)>> {
+    let mut visitor = MaterializeOpsVisitor::new(SafeBytecodeStream::new(body));
+    let mut decoder = Decoder::new();
+    let mut ops = Vec::new();
+    while !visitor.bytecode().as_slice().is_empty() {
+        let offset = u32::try_from(visitor.bytecode().position())?;
+        let op = decoder.decode_one(&mut visitor)?;
+
+        // Branch tables are followed by their table of targets, which aren't
+        // instructions, so skip over them.
+        if let Op::BrTable32(table) = op {
+            for _ in 0..table.amt {
+                visitor.bytecode().read::<4>()?;
+            }
+        }
+        ops.push((offset, op));
+    }
+    Ok(ops)
+}
+
+/// Whether `op` may transfer control somewhere other than the next
+/// instruction, in which case it can only be the last instruction in a fused
+/// sequence.
+fn is_control_flow(op: &Op) -> bool {
+    let name = op.name();
+    ["br", "jump", "xjump", "call", "ret", "return", "trap"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
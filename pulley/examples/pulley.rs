@@ -0,0 +1,194 @@
+//! Example program for experimenting with hand-written Pulley bytecode.
+//!
+//! Programs are written in the syntax of Pulley's disassembly, which is
+//! documented in the `pulley_interpreter::asm` module. For example, given a
+//! file `add.pulley` containing:
+//!
+//! ```text
+//! xadd64 x0, x0, x1
+//! ret
+//! ```
+//!
+//! the program can be executed with:
+//!
+//! ```text
+//! $ cargo run -p pulley-interpreter --example pulley --all-features -- \
+//!     run add.pulley 1 2 --ret x
+//! 3
+//! ```
+//!
+//! Arguments are passed in registers according to Pulley's ABI. Each argument
+//! is either an integer, passed in an `x` register, or is prefixed with the
+//! type of register to pass it in: `x:-1`, `f32:1.5`, `f64:2.5`, or `v:0x10`.
+//! The types of the values returned in registers are listed with `--ret`.
+//!
+//! Programs can also be assembled to, or disassembled from, raw bytecode with
+//! the `asm` and `disas` subcommands.
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::Parser;
+use pulley_interpreter::interp::{DoneReason, FRegVal, RegType, VRegVal, Val, Vm, XRegVal};
+use pulley_interpreter::{asm, decode::Decoder, disas::Disassembler};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+
+#[derive(Parser)]
+enum Pulley {
+    /// Execute a function and print the values that it returns.
+    Run(Run),
+    /// Assemble a program into raw bytecode.
+    Asm {
+        /// The program to assemble.
+        input: PathBuf,
+        /// Where to write the bytecode.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Disassemble raw bytecode into a program.
+    Disas {
+        /// The bytecode to disassemble.
+        input: PathBuf,
+        /// Whether to print the offset and bytes of each instruction.
+        #[arg(long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Parser)]
+struct Run {
+    /// The program to execute, starting at its first instruction.
+    program: PathBuf,
+
+    /// The arguments to pass to the function, such as `5`, `x:5`, `f32:1.5`,
+    /// `f64:1.5`, or `v:0x10`.
+    #[arg(allow_negative_numbers = true)]
+    args: Vec<String>,
+
+    /// The type of each value returned: `x`, `f32`, `f64`, or `v`.
+    #[arg(long = "ret")]
+    rets: Vec<String>,
+
+    /// Treat the program as raw bytecode rather than text to assemble.
+    #[arg(long)]
+    binary: bool,
+
+    /// Allocate this many zeroed bytes of memory and pass their address as the
+    /// first argument.
+    #[arg(long, value_name = "BYTES")]
+    memory: Option<usize>,
+
+    /// The size, in bytes, of the interpreter's stack.
+    #[arg(long, value_name = "BYTES")]
+    stack_size: Option<usize>,
+}
+
+fn main() -> Result<()> {
+    match Pulley::parse() {
+        Pulley::Run(run) => run.execute(),
+        Pulley::Asm { input, output } => {
+            let bytecode = assemble(&input)?;
+            std::fs::write(&output, bytecode)
+                .with_context(|| format!("failed to write {output:?}"))?;
+            Ok(())
+        }
+        Pulley::Disas { input, verbose } => {
+            let bytecode =
+                std::fs::read(&input).with_context(|| format!("failed to read {input:?}"))?;
+            let mut disas = Disassembler::new(&bytecode);
+            disas.offsets(verbose).hexdump(verbose);
+            Decoder::decode_all(&mut disas).context("failed to decode bytecode")?;
+            print!("{}", disas.disas());
+            Ok(())
+        }
+    }
+}
+
+fn assemble(path: &Path) -> Result<Vec<u8>> {
+    let source =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+    asm::assemble(&source).map_err(|e| anyhow!("{}: {e}", path.display()))
+}
+
+impl Run {
+    fn execute(&self) -> Result<()> {
+        let bytecode = if self.binary {
+            std::fs::read(&self.program)
+                .with_context(|| format!("failed to read {:?}", self.program))?
+        } else {
+            assemble(&self.program)?
+        };
+        if bytecode.is_empty() {
+            bail!("program is empty");
+        }
+
+        let mut memory = vec![0u8; self.memory.unwrap_or(0)];
+        let mut args = Vec::new();
+        if self.memory.is_some() {
+            args.push(Val::from(memory.as_mut_ptr()));
+        }
+        for arg in &self.args {
+            args.push(parse_arg(arg).with_context(|| format!("invalid argument `{arg}`"))?);
+        }
+        let rets = self
+            .rets
+            .iter()
+            .map(|ret| match ret.as_str() {
+                "x" => Ok(RegType::XReg),
+                "f32" | "f64" => Ok(RegType::FReg),
+                "v" => Ok(RegType::VReg),
+                _ => bail!("invalid return type `{ret}`"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut vm = match self.stack_size {
+            Some(size) => Vm::with_stack(size),
+            None => Vm::new(),
+        };
+        let func = NonNull::from(&bytecode[..]).cast::<u8>();
+        // SAFETY: there's no way to guarantee that arbitrary bytecode only
+        // accesses valid memory. It's up to the author of the program to only
+        // access the stack and the memory passed in.
+        match unsafe { vm.call(func, &args, rets) } {
+            DoneReason::ReturnToHost(vals) => {
+                for (val, ty) in vals.zip(&self.rets) {
+                    match (val, ty.as_str()) {
+                        (Val::XReg(x), _) => println!("{}", x.get_i64()),
+                        (Val::FReg(f), "f32") => println!("{}", f.get_f32()),
+                        (Val::FReg(f), _) => println!("{}", f.get_f64()),
+                        (Val::VReg(v), _) => println!("{:#x}", v.get_u128()),
+                    }
+                }
+                Ok(())
+            }
+            DoneReason::Trap { pc, kind } => {
+                let offset = pc.as_ptr() as usize - func.as_ptr() as usize;
+                match kind {
+                    Some(kind) => bail!("trap at offset {offset:#x}: {kind:?}"),
+                    None => bail!("trap at offset {offset:#x}"),
+                }
+            }
+            DoneReason::CallIndirectHost { id, .. } => {
+                bail!("host calls are not supported: `call_indirect_host {id}`")
+            }
+        }
+    }
+}
+
+fn parse_arg(arg: &str) -> Result<Val> {
+    let (ty, value) = arg.split_once(':').unwrap_or(("x", arg));
+    Ok(match ty {
+        "x" => XRegVal::new_i64(match value.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16)? as i64,
+            None => value.parse()?,
+        })
+        .into(),
+        "f32" => FRegVal::new_f32(value.parse()?).into(),
+        "f64" => FRegVal::new_f64(value.parse()?).into(),
+        "v" => VRegVal::new_u128(match value.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16)?,
+            None => value.parse()?,
+        })
+        .into(),
+        _ => bail!("unknown register type `{ty}`"),
+    })
+}
@@ -1,5 +1,7 @@
 use pulley_interpreter::{
+    asm,
     decode::{Decoder, SafeBytecodeStream},
+    disas::Disassembler,
     op::{MaterializeOpsVisitor, Op},
 };
 
@@ -25,4 +27,18 @@ pub fn roundtrip(ops: Vec<Op>) {
         decoded, ops,
         "`decode(encode(ops))` should be equal to the original `ops`"
     );
+
+    // `br_table32` entries aren't ops, so they can't be disassembled from a
+    // list of arbitrary ops.
+    if ops.iter().any(|op| matches!(op, Op::BrTable32(_))) {
+        return;
+    }
+    let mut disas = Disassembler::new(&encoded);
+    Decoder::decode_all(&mut disas).expect("should disassemble okay");
+    log::trace!("disassembly:\n{}", disas.disas());
+    let assembled = asm::assemble(disas.disas()).expect("should assemble okay");
+    assert_eq!(
+        assembled, encoded,
+        "`assemble(disas(encode(ops)))` should be equal to `encode(ops)`"
+    );
 }
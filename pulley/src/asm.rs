@@ -0,0 +1,528 @@
+//! A textual assembler for Pulley bytecode.
+//!
+//! The syntax accepted is the syntax printed by
+//! [`Disassembler`](crate::disas::Disassembler), so disassembly can be
+//! assembled back into the bytecode it came from. Each line holds one
+//! instruction, a mnemonic followed by comma-separated operands:
+//!
+//! ```text
+//! xconst8 x0, 10
+//! xadd32 x0, x0, x1
+//! xload32le_o32 x2, x0, 8
+//! ret
+//! ```
+//!
+//! Additionally:
+//!
+//! * Comments start with `//` or `;` and extend to the end of the line.
+//! * The offsets and hexdump which the disassembler prints by default at the
+//!   start of each line are ignored.
+//! * A line containing only `name:` defines a label. Branch offsets may be
+//!   written as a label's name instead of a number, in which case the offset
+//!   to that label is filled in.
+//! * Numeric branch offsets are relative to the start of the instruction, as
+//!   in the disassembly.
+//! * The `br_table32` instruction must be followed by one line per table entry
+//!   containing just a branch offset or label. Each entry's offset is relative
+//!   to the start of the entry.
+
+use crate::encode::Encode;
+use crate::imms::*;
+use crate::op::*;
+use crate::regs::*;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use cranelift_bitset::ScalarBitSet;
+
+/// Either an `Ok(T)` or an `Err(AsmError)`.
+pub type Result<T, E = AsmError> = core::result::Result<T, E>;
+
+/// An error when assembling Pulley bytecode from text.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AsmError {
+    line: usize,
+    message: String,
+}
+
+impl AsmError {
+    /// The 1-based line number of the source text where this error occurred.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// A description of this error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Debug for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsmError {}
+
+/// Assemble the given source text into Pulley bytecode.
+///
+/// See the [module documentation](self) for the syntax accepted.
+pub fn assemble(source: &str) -> Result<Vec<u8>> {
+    // Every instruction has a fixed size regardless of the value of its
+    // operands, so a first pass with all labels at offset zero determines
+    // where each label is, and a second pass then fills in label offsets.
+    let labels = Assembler::new(None).run(source)?.labels;
+    Ok(Assembler::new(Some(&labels)).run(source)?.bytecode)
+}
+
+struct Assembler<'a> {
+    /// Labels defined by a previous pass, or `None` if this is the first pass.
+    resolved: Option<&'a BTreeMap<String, usize>>,
+    labels: BTreeMap<String, usize>,
+    bytecode: Vec<u8>,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(resolved: Option<&'a BTreeMap<String, usize>>) -> Self {
+        Assembler {
+            resolved,
+            labels: BTreeMap::new(),
+            bytecode: Vec::new(),
+        }
+    }
+
+    fn run(mut self, source: &str) -> Result<Self> {
+        let mut lines = source.lines().enumerate();
+        while let Some((i, line)) = lines.next() {
+            let err = |message: String| AsmError {
+                line: i + 1,
+                message,
+            };
+            let tokens = tokenize(line).map_err(err)?;
+            let tokens = strip_offset_and_hexdump(&tokens);
+            match tokens {
+                [] => {}
+                [Token::Word(name), Token::Colon] => {
+                    if self
+                        .labels
+                        .insert(name.to_string(), self.bytecode.len())
+                        .is_some()
+                    {
+                        return Err(err(format!("label `{name}` is defined twice")));
+                    }
+                }
+                [Token::Word(mnemonic), operands @ ..] => {
+                    let position = self.bytecode.len();
+                    let mut parser = Parser {
+                        tokens: operands,
+                        position,
+                        labels: self.resolved,
+                    };
+                    let op = parser.op(mnemonic).map_err(err)?;
+                    parser.finish().map_err(err)?;
+                    op.encode(&mut self.bytecode);
+
+                    if let Op::BrTable32(table) = op {
+                        for _ in 0..table.amt {
+                            let (i, line) = lines
+                                .by_ref()
+                                .find(|(_, line)| !matches!(tokenize(line).as_deref(), Ok([])))
+                                .ok_or_else(
+                                    || err("missing entries of `br_table32`".to_string()),
+                                )?;
+                            let err = |message: String| AsmError {
+                                line: i + 1,
+                                message,
+                            };
+                            let tokens = tokenize(line).map_err(err)?;
+                            let mut parser = Parser {
+                                tokens: strip_offset_and_hexdump(&tokens),
+                                position: self.bytecode.len(),
+                                labels: self.resolved,
+                            };
+                            let offset = PcRelOffset::parse(&mut parser).map_err(err)?;
+                            parser.finish().map_err(err)?;
+                            offset.encode(&mut self.bytecode);
+                        }
+                    }
+                }
+                _ => return Err(err("expected an instruction or label".to_string())),
+            }
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Token<'a> {
+    /// A mnemonic, register, number, or label.
+    Word(&'a str),
+    Comma,
+    Colon,
+    Star,
+    Plus,
+    LBracket,
+    RBracket,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "`{w}`"),
+            Token::Comma => write!(f, "`,`"),
+            Token::Colon => write!(f, "`:`"),
+            Token::Star => write!(f, "`*`"),
+            Token::Plus => write!(f, "`+`"),
+            Token::LBracket => write!(f, "`[`"),
+            Token::RBracket => write!(f, "`]`"),
+        }
+    }
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token<'_>>, String> {
+    let line = match (line.find("//"), line.find(';')) {
+        (Some(a), Some(b)) => &line[..a.min(b)],
+        (Some(a), None) | (None, Some(a)) => &line[..a],
+        (None, None) => line,
+    };
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let token = match c {
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '*' => Token::Star,
+            '+' => Token::Plus,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            c if is_word_char(c) => {
+                let end = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+                let (word, remaining) = rest.split_at(end);
+                tokens.push(Token::Word(word));
+                rest = remaining.trim_start();
+                continue;
+            }
+            c => return Err(format!("unexpected character `{c}`")),
+        };
+        tokens.push(token);
+        rest = rest[c.len_utf8()..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Removes the `offset: ` and hexdump of bytes that the disassembler prints
+/// before each instruction by default.
+fn strip_offset_and_hexdump<'a, 'b>(tokens: &'b [Token<'a>]) -> &'b [Token<'a>] {
+    let is_hex = |w: &str| !w.is_empty() && w.chars().all(|c| c.is_ascii_hexdigit());
+    let mut tokens = match tokens {
+        [Token::Word(offset), Token::Colon, rest @ ..] if is_hex(offset) && !rest.is_empty() => {
+            rest
+        }
+        _ => tokens,
+    };
+    while let [Token::Word(byte), rest @ ..] = tokens {
+        if byte.len() != 2 || !is_hex(byte) || rest.is_empty() {
+            break;
+        }
+        tokens = rest;
+    }
+    tokens
+}
+
+struct Parser<'a, 'b> {
+    tokens: &'b [Token<'a>],
+    /// The offset of the instruction, or table entry, being parsed.
+    position: usize,
+    labels: Option<&'b BTreeMap<String, usize>>,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn next(&mut self) -> Result<Token<'a>, String> {
+        let (first, rest) = self
+            .tokens
+            .split_first()
+            .ok_or_else(|| "unexpected end of line".to_string())?;
+        self.tokens = rest;
+        Ok(*first)
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.first().copied()
+    }
+
+    fn expect(&mut self, expected: Token<'_>) -> Result<(), String> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("expected {expected}, found {token}")),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<&'a str, String> {
+        match self.next()? {
+            Token::Word(w) => Ok(w),
+            token => Err(format!("expected {what}, found {token}")),
+        }
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(format!("unexpected {token} after instruction")),
+        }
+    }
+
+    fn op(&mut self, mnemonic: &str) -> Result<Op, String> {
+        macro_rules! parse_op {
+            (
+                $(
+                    $( #[$attr:meta] )*
+                    $snake_name:ident = $name:ident $( {
+                        $(
+                            $( #[$field_attr:meta] )*
+                            $field:ident : $field_ty:ty
+                        ),*
+                    } )? ;
+                )*
+            ) => {
+                $(
+                    if mnemonic == stringify!($snake_name) {
+                        let mut _first = true;
+                        $($(
+                            if !_first {
+                                self.expect(Token::Comma)?;
+                            }
+                            _first = false;
+                            let $field = <$field_ty as Parse>::parse(self)?;
+                        )*)?
+                        return Ok($name { $($($field),*)? }.into());
+                    }
+                )*
+            };
+        }
+        for_each_op!(parse_op);
+        for_each_extended_op!(parse_op);
+        Err(format!("unknown instruction `{mnemonic}`"))
+    }
+}
+
+/// Anything inside an instruction that can be parsed: registers, immediates,
+/// etc...
+trait Parse: Sized {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String>;
+}
+
+macro_rules! impl_parse_reg {
+    ($($reg:ident)*) => {$(
+        impl Parse for $reg {
+            fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+                let word = parser.word("a register")?;
+                (0..u8::MAX)
+                    .map_while($reg::new)
+                    .find(|reg| reg.to_string() == word)
+                    .ok_or_else(|| {
+                        format!(concat!("invalid `", stringify!($reg), "` register `{}`"), word)
+                    })
+            }
+        }
+    )*};
+}
+
+impl_parse_reg!(XReg FReg VReg);
+
+/// Parses a possibly-negative integer written in decimal or with a `0x`,
+/// `0o`, or `0b` prefix.
+fn parse_int(word: &str) -> Option<i128> {
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+    let digits = digits.replace('_', "");
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        u128::from_str_radix(hex, 16).ok()?
+    } else if let Some(oct) = digits.strip_prefix("0o") {
+        u128::from_str_radix(oct, 8).ok()?
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        u128::from_str_radix(bin, 2).ok()?
+    } else {
+        digits.parse::<u128>().ok()?
+    };
+    if negative {
+        0i128.checked_sub_unsigned(value)
+    } else {
+        i128::try_from(value).ok()
+    }
+}
+
+macro_rules! impl_parse_int {
+    ($($int:ident)*) => {$(
+        impl Parse for $int {
+            fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+                let word = parser.word("an integer")?;
+                parse_int(word)
+                    .and_then(|i| $int::try_from(i).ok())
+                    .ok_or_else(|| {
+                        format!(concat!("invalid `", stringify!($int), "` immediate `{}`"), word)
+                    })
+            }
+        }
+    )*};
+}
+
+impl_parse_int!(i8 i16 i32 i64 u8 u16 u32 u64);
+
+impl Parse for i128 {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let word = parser.word("an integer")?;
+        parse_int(word).ok_or_else(|| format!("invalid `i128` immediate `{word}`"))
+    }
+}
+
+impl Parse for u128 {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let word = parser.word("an integer")?;
+        let digits = word.replace('_', "");
+        let value = match digits.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => digits.parse().ok(),
+        };
+        value.ok_or_else(|| format!("invalid `u128` immediate `{word}`"))
+    }
+}
+
+impl Parse for U6 {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let word = parser.word("an integer")?;
+        parse_int(word)
+            .and_then(|i| u8::try_from(i).ok())
+            .and_then(U6::new)
+            .ok_or_else(|| format!("invalid `U6` immediate `{word}`"))
+    }
+}
+
+impl Parse for PcRelOffset {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let word = parser.word("a branch offset or label")?;
+        if let Some(offset) = parse_int(word) {
+            return i32::try_from(offset)
+                .map(PcRelOffset::from)
+                .map_err(|_| format!("branch offset `{word}` out of range"));
+        }
+        let target = match parser.labels {
+            // Labels aren't known yet on the first pass.
+            None => return Ok(PcRelOffset::from(0)),
+            Some(labels) => labels
+                .get(word)
+                .ok_or_else(|| format!("unknown label `{word}`"))?,
+        };
+        let offset = *target as i64 - parser.position as i64;
+        i32::try_from(offset)
+            .map(PcRelOffset::from)
+            .map_err(|_| format!("label `{word}` is out of range"))
+    }
+}
+
+impl<D: Parse, S1: Parse, S2: Parse> Parse for BinaryOperands<D, S1, S2> {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let dst = D::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let src1 = S1::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let src2 = S2::parse(parser)?;
+        Ok(BinaryOperands { dst, src1, src2 })
+    }
+}
+
+impl<R: Reg + Parse> Parse for UpperRegSet<R> {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let mut bitset = ScalarBitSet::<u16>::new();
+        if parser.peek().is_none() {
+            return Ok(bitset.into());
+        }
+        loop {
+            let reg = R::parse(parser)?;
+            let index = reg
+                .to_u8()
+                .checked_sub(16)
+                .ok_or_else(|| format!("register `{reg}` cannot be in a register set"))?;
+            bitset.insert(index);
+            if parser.peek().is_none() {
+                return Ok(bitset.into());
+            }
+            parser.expect(Token::Comma)?;
+        }
+    }
+}
+
+impl Parse for AddrO32 {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let addr = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let offset = i32::parse(parser)?;
+        Ok(AddrO32 { addr, offset })
+    }
+}
+
+impl Parse for AddrZ {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let addr = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let offset = i32::parse(parser)?;
+        Ok(AddrZ { addr, offset })
+    }
+}
+
+impl Parse for AddrG32 {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let host_heap_base = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let host_heap_bound = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let wasm_addr = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let offset = u16::parse(parser)?;
+        Ok(AddrG32 {
+            host_heap_base,
+            host_heap_bound,
+            wasm_addr,
+            offset,
+        })
+    }
+}
+
+impl Parse for AddrG32Bne {
+    fn parse(parser: &mut Parser<'_, '_>) -> Result<Self, String> {
+        let host_heap_base = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        parser.expect(Token::Star)?;
+        parser.expect(Token::LBracket)?;
+        let host_heap_bound_addr = XReg::parse(parser)?;
+        parser.expect(Token::Plus)?;
+        let host_heap_bound_offset = u8::parse(parser)?;
+        parser.expect(Token::RBracket)?;
+        parser.expect(Token::Comma)?;
+        let wasm_addr = XReg::parse(parser)?;
+        parser.expect(Token::Comma)?;
+        let offset = u8::parse(parser)?;
+        Ok(AddrG32Bne {
+            host_heap_base,
+            host_heap_bound_addr,
+            host_heap_bound_offset,
+            wasm_addr,
+            offset,
+        })
+    }
+}
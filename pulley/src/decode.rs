@@ -431,6 +431,15 @@ impl<D: Reg, S1: Reg, S2: Reg> Decode for BinaryOperands<D, S1, S2> {
     }
 }
 
+impl Decode for U6 {
+    fn decode<T>(bytecode: &mut T) -> Result<Self, T::Error>
+    where
+        T: BytecodeStream,
+    {
+        u8::decode(bytecode).map(|bits| U6::new(bits & 0b111111).unwrap())
+    }
+}
+
 impl<D: Reg, S1: Reg> Decode for BinaryOperands<D, S1, U6> {
     fn decode<T>(bytecode: &mut T) -> Result<Self, T::Error>
     where
@@ -377,6 +377,11 @@ impl<'a> OpVisitor for Disassembler<'a> {
             for _ in 0..12_usize.saturating_sub(size) {
                 write!(&mut self.disas, "   ").unwrap();
             }
+            // Always separate the hexdump from the instruction, even when the
+            // hexdump doesn't fit in the padding.
+            if size >= 12 {
+                self.disas.push(' ');
+            }
         }
         self.disas.push_str(&self.temp);
         self.temp.clear();
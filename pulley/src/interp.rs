@@ -15,7 +15,14 @@ use core::ptr::NonNull;
 use pulley_macros::interp_disable_if_cfg;
 use wasmtime_math::{WasmFloat, f32_cvt_to_int_bounds, f64_cvt_to_int_bounds};
 
+use atomic::{AtomicInt, AtomicRmwOp};
+#[cfg(feature = "debug")]
+pub use debugger::{DebugHook, DebugStop, Debugger, StopReason};
+
+mod atomic;
 mod debug;
+#[cfg(feature = "debug")]
+mod debugger;
 #[cfg(all(not(pulley_tail_calls), not(pulley_assume_llvm_makes_tail_calls)))]
 mod match_loop;
 #[cfg(any(pulley_tail_calls, pulley_assume_llvm_makes_tail_calls))]
@@ -750,6 +757,8 @@ pub struct MachineState {
     lr: *mut u8,
     stack: Stack,
     done_reason: Option<DoneReason<()>>,
+    #[cfg(feature = "debug")]
+    debugger: Debugger,
 }
 
 unsafe impl Send for MachineState {}
@@ -822,6 +831,8 @@ impl fmt::Debug for MachineState {
             done_reason: _,
             fp: _,
             lr: _,
+            #[cfg(feature = "debug")]
+                debugger: _,
         } = self;
 
         struct RegMap<'a, R>(&'a [R], fn(u8) -> alloc::string::String);
@@ -896,6 +907,16 @@ index_reg!(VReg, VRegVal, v_regs);
 const HOST_RETURN_ADDR: *mut u8 = usize::MAX as *mut u8;
 
 impl MachineState {
+    /// Returns the current `fp` register value.
+    pub fn fp(&self) -> *mut u8 {
+        self.fp
+    }
+
+    /// Returns the current `lr` register value.
+    pub fn lr(&self) -> *mut u8 {
+        self.lr
+    }
+
     fn with_stack(stack_size: usize) -> Self {
         let mut state = Self {
             x_regs: [Default::default(); XReg::RANGE.end as usize],
@@ -904,6 +925,8 @@ impl MachineState {
             v_regs: Default::default(),
             stack: Stack::new(stack_size),
             done_reason: None,
+            #[cfg(feature = "debug")]
+            debugger: Debugger::default(),
             fp: HOST_RETURN_ADDR,
             lr: HOST_RETURN_ADDR,
         };
@@ -951,6 +974,7 @@ mod done {
     }
 
     /// Stored within `DoneReason::Trap`.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     #[expect(missing_docs, reason = "self-describing variants")]
     pub enum TrapKind {
         DivideByZero,
@@ -1133,6 +1157,74 @@ impl Interpreter<'_> {
         unsafe { addr.store_ne::<T, I>(self, val) }
     }
 
+    /// Calculates the host address of an atomic access of `T` at `addr`.
+    ///
+    /// Traps if `addr` is null or if the host doesn't support atomic
+    /// operations of this width.
+    ///
+    /// # Unsafety
+    ///
+    /// Same as `load_ne`, and additionally the resulting address must be
+    /// naturally aligned for `T`.
+    #[must_use]
+    unsafe fn atomic_addr<T: AtomicInt, I: Encode>(
+        &mut self,
+        addr: AddrZ,
+    ) -> ControlFlow<Done, *mut T> {
+        if !T::ENABLED {
+            self.done_trap_kind::<I>(Some(TrapKind::DisabledOpcode))?;
+            unreachable!();
+        }
+        let ptr = unsafe { addr.addr::<T, I>(self)? };
+        debug_assert!(ptr.is_aligned());
+        ControlFlow::Continue(ptr)
+    }
+
+    /// Atomically loads a `T` from `addr`, see `atomic_addr`.
+    #[must_use]
+    unsafe fn atomic_load<T: AtomicInt, I: Encode>(&mut self, addr: AddrZ) -> ControlFlow<Done, T> {
+        let ptr = unsafe { self.atomic_addr::<T, I>(addr)? };
+        ControlFlow::Continue(unsafe { T::load(ptr) })
+    }
+
+    /// Atomically stores `val` to `addr`, see `atomic_addr`.
+    #[must_use]
+    unsafe fn atomic_store<T: AtomicInt, I: Encode>(
+        &mut self,
+        addr: AddrZ,
+        val: T,
+    ) -> ControlFlow<Done> {
+        let ptr = unsafe { self.atomic_addr::<T, I>(addr)? };
+        unsafe { T::store(ptr, val) };
+        ControlFlow::Continue(())
+    }
+
+    /// Atomically applies `op` to `addr`, returning the previous value, see
+    /// `atomic_addr`.
+    #[must_use]
+    unsafe fn atomic_rmw<T: AtomicInt, I: Encode>(
+        &mut self,
+        addr: AddrZ,
+        op: AtomicRmwOp,
+        val: T,
+    ) -> ControlFlow<Done, T> {
+        let ptr = unsafe { self.atomic_addr::<T, I>(addr)? };
+        ControlFlow::Continue(unsafe { T::rmw(ptr, op, val) })
+    }
+
+    /// Atomically compares-and-swaps `addr`, returning the previous value, see
+    /// `atomic_addr`.
+    #[must_use]
+    unsafe fn atomic_cas<T: AtomicInt, I: Encode>(
+        &mut self,
+        addr: AddrZ,
+        expected: T,
+        replacement: T,
+    ) -> ControlFlow<Done, T> {
+        let ptr = unsafe { self.atomic_addr::<T, I>(addr)? };
+        ControlFlow::Continue(unsafe { T::cas(ptr, expected, replacement) })
+    }
+
     fn check_xnn_from_f32<I: Encode>(
         &mut self,
         val: f32,
@@ -1190,20 +1282,25 @@ trait AddressingMode: Sized {
 
     /// Loads a value of `T` from this address, using native-endian byte order.
     ///
+    /// The address may be in memory shared with other threads, so this is
+    /// done with relaxed atomic accesses, see the `atomic` module.
+    ///
     /// For more information see [`Interpreter::load_ne`].
     #[must_use]
     unsafe fn load_ne<T, I: Encode>(self, i: &mut Interpreter<'_>) -> ControlFlow<Done, T> {
-        let ret = unsafe { self.addr::<T, I>(i)?.read_unaligned() };
+        let ret = unsafe { atomic::load_relaxed(self.addr::<T, I>(i)?) };
         ControlFlow::Continue(ret)
     }
 
     /// Stores a `val` to this address, using native-endian byte order.
     ///
+    /// Like `load_ne` this is done with relaxed atomic accesses.
+    ///
     /// For more information see [`Interpreter::store_ne`].
     #[must_use]
     unsafe fn store_ne<T, I: Encode>(self, i: &mut Interpreter<'_>, val: T) -> ControlFlow<Done> {
         unsafe {
-            self.addr::<T, I>(i)?.write_unaligned(val);
+            atomic::store_relaxed(self.addr::<T, I>(i)?, val);
         }
         ControlFlow::Continue(())
     }
@@ -1329,7 +1426,50 @@ macro_rules! br_if_imm {
     )*};
 }
 
+macro_rules! xatomic_rmw {
+    ($(
+        fn $snake:ident(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = $camel:ident / $op:ident / $ty:ident;
+    )*) => {$(
+        #[allow(trivial_numeric_casts, reason = "macro-generated code")]
+        fn $snake(&mut self, dst: XReg, addr: AddrZ, src: XReg) -> ControlFlow<Done> {
+            let val = self.state[src].get_u64() as $ty;
+            let result = unsafe {
+                self.atomic_rmw::<$ty, crate::$camel>(addr, AtomicRmwOp::$op, val)?
+            };
+            self.state[dst].set_u64(result.into());
+            ControlFlow::Continue(())
+        }
+    )*};
+}
+
+/// Implements each superinstruction by running the sequence of ops it fuses.
+///
+/// `spilltmp0` holds the sequence's intermediate results, and like all of
+/// `x16` and up it's callee-saved, so its value is restored afterwards.
+macro_rules! superinstruction_handlers {
+    (
+        $(
+            $( #[$attr:meta] )*
+            $snake_name:ident = $name:ident { $( $field:ident : $field_ty:ident ),* }
+                => [ $( $op:ident ( $($arg:tt)* ); )* ]
+                lower [ $($lower:tt)* ];
+        )*
+    ) => {
+        $(
+            fn $snake_name(&mut self, $( $field: $field_ty ),*) -> ControlFlow<Done> {
+                let spilltmp0 = self.state[XReg::spilltmp0];
+                $( self.$op($($arg)*)?; )*
+                self.state[XReg::spilltmp0] = spilltmp0;
+                ControlFlow::Continue(())
+            }
+        )*
+    };
+}
+
 impl OpVisitor for Interpreter<'_> {
+    for_each_superinstruction!(superinstruction_handlers);
+
     type BytecodeStream = UnsafeBytecodeStream;
     type Return = ControlFlow<Done>;
 
@@ -2865,6 +3005,218 @@ impl ExtendedOpVisitor for Interpreter<'_> {
         ControlFlow::Continue(())
     }
 
+    fn fence(&mut self) -> ControlFlow<Done> {
+        core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_load8_z(&mut self, dst: XReg, addr: AddrZ) -> ControlFlow<Done> {
+        let result = unsafe { self.atomic_load::<u8, crate::XAtomicLoad8Z>(addr)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_load16le_z(&mut self, dst: XReg, addr: AddrZ) -> ControlFlow<Done> {
+        let result = unsafe { self.atomic_load::<u16, crate::XAtomicLoad16LeZ>(addr)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_load32le_z(&mut self, dst: XReg, addr: AddrZ) -> ControlFlow<Done> {
+        let result = unsafe { self.atomic_load::<u32, crate::XAtomicLoad32LeZ>(addr)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_load64le_z(&mut self, dst: XReg, addr: AddrZ) -> ControlFlow<Done> {
+        let result = unsafe { self.atomic_load::<u64, crate::XAtomicLoad64LeZ>(addr)? };
+        self.state[dst].set_u64(result);
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_store8_z(&mut self, addr: AddrZ, src: XReg) -> ControlFlow<Done> {
+        let val = self.state[src].get_u32() as u8;
+        unsafe {
+            self.atomic_store::<u8, crate::XAtomicStore8Z>(addr, val)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_store16le_z(&mut self, addr: AddrZ, src: XReg) -> ControlFlow<Done> {
+        let val = self.state[src].get_u32() as u16;
+        unsafe {
+            self.atomic_store::<u16, crate::XAtomicStore16LeZ>(addr, val)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_store32le_z(&mut self, addr: AddrZ, src: XReg) -> ControlFlow<Done> {
+        let val = self.state[src].get_u32();
+        unsafe {
+            self.atomic_store::<u32, crate::XAtomicStore32LeZ>(addr, val)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_store64le_z(&mut self, addr: AddrZ, src: XReg) -> ControlFlow<Done> {
+        let val = self.state[src].get_u64();
+        unsafe {
+            self.atomic_store::<u64, crate::XAtomicStore64LeZ>(addr, val)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    xatomic_rmw! {
+        fn xatomic_add8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAdd8Z / Add / u8;
+        fn xatomic_add16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAdd16LeZ / Add / u16;
+        fn xatomic_add32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAdd32LeZ / Add / u32;
+        fn xatomic_add64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAdd64LeZ / Add / u64;
+        fn xatomic_sub8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSub8Z / Sub / u8;
+        fn xatomic_sub16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSub16LeZ / Sub / u16;
+        fn xatomic_sub32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSub32LeZ / Sub / u32;
+        fn xatomic_sub64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSub64LeZ / Sub / u64;
+        fn xatomic_and8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAnd8Z / And / u8;
+        fn xatomic_and16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAnd16LeZ / And / u16;
+        fn xatomic_and32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAnd32LeZ / And / u32;
+        fn xatomic_and64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicAnd64LeZ / And / u64;
+        fn xatomic_or8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicOr8Z / Or / u8;
+        fn xatomic_or16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicOr16LeZ / Or / u16;
+        fn xatomic_or32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicOr32LeZ / Or / u32;
+        fn xatomic_or64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicOr64LeZ / Or / u64;
+        fn xatomic_xor8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXor8Z / Xor / u8;
+        fn xatomic_xor16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXor16LeZ / Xor / u16;
+        fn xatomic_xor32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXor32LeZ / Xor / u32;
+        fn xatomic_xor64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXor64LeZ / Xor / u64;
+        fn xatomic_nand8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicNand8Z / Nand / u8;
+        fn xatomic_nand16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicNand16LeZ / Nand / u16;
+        fn xatomic_nand32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicNand32LeZ / Nand / u32;
+        fn xatomic_nand64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicNand64LeZ / Nand / u64;
+        fn xatomic_umin8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmin8Z / Umin / u8;
+        fn xatomic_umin16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmin16LeZ / Umin / u16;
+        fn xatomic_umin32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmin32LeZ / Umin / u32;
+        fn xatomic_umin64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmin64LeZ / Umin / u64;
+        fn xatomic_umax8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmax8Z / Umax / u8;
+        fn xatomic_umax16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmax16LeZ / Umax / u16;
+        fn xatomic_umax32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmax32LeZ / Umax / u32;
+        fn xatomic_umax64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicUmax64LeZ / Umax / u64;
+        fn xatomic_smin8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmin8Z / Smin / u8;
+        fn xatomic_smin16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmin16LeZ / Smin / u16;
+        fn xatomic_smin32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmin32LeZ / Smin / u32;
+        fn xatomic_smin64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmin64LeZ / Smin / u64;
+        fn xatomic_smax8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmax8Z / Smax / u8;
+        fn xatomic_smax16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmax16LeZ / Smax / u16;
+        fn xatomic_smax32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmax32LeZ / Smax / u32;
+        fn xatomic_smax64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicSmax64LeZ / Smax / u64;
+        fn xatomic_xchg8_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXchg8Z / Xchg / u8;
+        fn xatomic_xchg16le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXchg16LeZ / Xchg / u16;
+        fn xatomic_xchg32le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXchg32LeZ / Xchg / u32;
+        fn xatomic_xchg64le_z(&mut self, dst: XReg, addr: AddrZ, src: XReg)
+            = XAtomicXchg64LeZ / Xchg / u64;
+    }
+
+    fn xatomic_cas8_z(
+        &mut self,
+        dst: XReg,
+        addr: AddrZ,
+        expected: XReg,
+        replacement: XReg,
+    ) -> ControlFlow<Done> {
+        let expected = self.state[expected].get_u32() as u8;
+        let replacement = self.state[replacement].get_u32() as u8;
+        let result =
+            unsafe { self.atomic_cas::<u8, crate::XAtomicCas8Z>(addr, expected, replacement)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_cas16le_z(
+        &mut self,
+        dst: XReg,
+        addr: AddrZ,
+        expected: XReg,
+        replacement: XReg,
+    ) -> ControlFlow<Done> {
+        let expected = self.state[expected].get_u32() as u16;
+        let replacement = self.state[replacement].get_u32() as u16;
+        let result =
+            unsafe { self.atomic_cas::<u16, crate::XAtomicCas16LeZ>(addr, expected, replacement)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_cas32le_z(
+        &mut self,
+        dst: XReg,
+        addr: AddrZ,
+        expected: XReg,
+        replacement: XReg,
+    ) -> ControlFlow<Done> {
+        let expected = self.state[expected].get_u32();
+        let replacement = self.state[replacement].get_u32();
+        let result =
+            unsafe { self.atomic_cas::<u32, crate::XAtomicCas32LeZ>(addr, expected, replacement)? };
+        self.state[dst].set_u64(result.into());
+        ControlFlow::Continue(())
+    }
+
+    fn xatomic_cas64le_z(
+        &mut self,
+        dst: XReg,
+        addr: AddrZ,
+        expected: XReg,
+        replacement: XReg,
+    ) -> ControlFlow<Done> {
+        let expected = self.state[expected].get_u64();
+        let replacement = self.state[replacement].get_u64();
+        let result =
+            unsafe { self.atomic_cas::<u64, crate::XAtomicCas64LeZ>(addr, expected, replacement)? };
+        self.state[dst].set_u64(result);
+        ControlFlow::Continue(())
+    }
+
     fn xadd32_uoverflow_trap(&mut self, operands: BinaryOperands<XReg>) -> ControlFlow<Done> {
         let a = self.state[operands.src1].get_u32();
         let b = self.state[operands.src2].get_u32();
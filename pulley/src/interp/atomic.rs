@@ -0,0 +1,343 @@
+//! Support for Pulley's atomic memory instructions.
+//!
+//! Memory that is shared between threads, such as a wasm shared linear
+//! memory, may be concurrently modified by other threads while the interpreter
+//! is executing. Pulley's atomic instructions are therefore implemented with
+//! the atomic types of `core::sync::atomic` which are sound to use in the face
+//! of racing accesses. Pulley's memory is always little-endian so values are
+//! byte-swapped as necessary on big-endian hosts.
+//!
+//! Pulley's regular loads and stores may race with accesses from other
+//! threads just the same, which would be undefined behavior if they were
+//! plain reads and writes, so they're made of relaxed atomic accesses as wide
+//! as their alignment allows instead. On the hosts Pulley typically runs on
+//! these compile to the same instructions as plain ones.
+
+use core::mem::{MaybeUninit, size_of};
+use core::sync::atomic::Ordering::{Relaxed, SeqCst};
+
+/// Read-modify-write operations supported by [`AtomicInt::rmw`].
+#[derive(Copy, Clone)]
+pub(super) enum AtomicRmwOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Nand,
+    Umin,
+    Umax,
+    Smin,
+    Smax,
+    Xchg,
+}
+
+/// An integer type that Pulley's atomic instructions operate on.
+///
+/// All methods operate on little-endian values in memory and take and return
+/// values in native byte order.
+pub(super) trait AtomicInt: Copy {
+    /// Whether the host supports atomic operations of this width natively.
+    ///
+    /// If this is `false` then no other methods may be called and the
+    /// instruction should instead trap with `TrapKind::DisabledOpcode`.
+    const ENABLED: bool;
+
+    /// Atomically loads the value at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid and naturally aligned.
+    unsafe fn load(ptr: *mut Self) -> Self;
+
+    /// Atomically stores `val` to `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AtomicInt::load`].
+    unsafe fn store(ptr: *mut Self, val: Self);
+
+    /// Atomically applies `op` with `val` to the value at `ptr`, returning the
+    /// previous value.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AtomicInt::load`].
+    unsafe fn rmw(ptr: *mut Self, op: AtomicRmwOp, val: Self) -> Self;
+
+    /// Atomically replaces the value at `ptr` with `replacement` if it's equal
+    /// to `expected`, returning the previous value.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AtomicInt::load`].
+    unsafe fn cas(ptr: *mut Self, expected: Self, replacement: Self) -> Self;
+}
+
+macro_rules! atomic_int {
+    ($($int:ident/$signed:ident => $atomic:ident/$satomic:ident if $width:tt;)*) => {$(
+        #[cfg(target_has_atomic = $width)]
+        impl AtomicInt for $int {
+            const ENABLED: bool = true;
+
+            unsafe fn load(ptr: *mut Self) -> Self {
+                let atomic = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                $int::from_le(atomic.load(SeqCst))
+            }
+
+            unsafe fn store(ptr: *mut Self, val: Self) {
+                let atomic = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                atomic.store(val.to_le(), SeqCst);
+            }
+
+            unsafe fn rmw(ptr: *mut Self, op: AtomicRmwOp, val: Self) -> Self {
+                let atomic = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                let little = cfg!(target_endian = "little");
+                let prev = match op {
+                    AtomicRmwOp::Add if little => atomic.fetch_add(val, SeqCst),
+                    AtomicRmwOp::Sub if little => atomic.fetch_sub(val, SeqCst),
+                    AtomicRmwOp::Umin if little => atomic.fetch_min(val, SeqCst),
+                    AtomicRmwOp::Umax if little => atomic.fetch_max(val, SeqCst),
+                    AtomicRmwOp::Smin if little => {
+                        let atomic = unsafe { core::sync::atomic::$satomic::from_ptr(ptr.cast()) };
+                        atomic.fetch_min(val as $signed, SeqCst) as $int
+                    }
+                    AtomicRmwOp::Smax if little => {
+                        let atomic = unsafe { core::sync::atomic::$satomic::from_ptr(ptr.cast()) };
+                        atomic.fetch_max(val as $signed, SeqCst) as $int
+                    }
+                    // Arithmetic and comparisons don't commute with byte swaps
+                    // so on big-endian hosts fall back to a compare-and-swap
+                    // loop.
+                    AtomicRmwOp::Add
+                    | AtomicRmwOp::Sub
+                    | AtomicRmwOp::Umin
+                    | AtomicRmwOp::Umax
+                    | AtomicRmwOp::Smin
+                    | AtomicRmwOp::Smax => atomic
+                        .fetch_update(SeqCst, SeqCst, |x| {
+                            let x = $int::from_le(x);
+                            let new = match op {
+                                AtomicRmwOp::Add => x.wrapping_add(val),
+                                AtomicRmwOp::Sub => x.wrapping_sub(val),
+                                AtomicRmwOp::Umin => x.min(val),
+                                AtomicRmwOp::Umax => x.max(val),
+                                AtomicRmwOp::Smin => (x as $signed).min(val as $signed) as $int,
+                                AtomicRmwOp::Smax => (x as $signed).max(val as $signed) as $int,
+                                _ => unreachable!(),
+                            };
+                            Some(new.to_le())
+                        })
+                        .unwrap(),
+                    AtomicRmwOp::And => atomic.fetch_and(val.to_le(), SeqCst),
+                    AtomicRmwOp::Or => atomic.fetch_or(val.to_le(), SeqCst),
+                    AtomicRmwOp::Xor => atomic.fetch_xor(val.to_le(), SeqCst),
+                    AtomicRmwOp::Nand => atomic.fetch_nand(val.to_le(), SeqCst),
+                    AtomicRmwOp::Xchg => atomic.swap(val.to_le(), SeqCst),
+                };
+                $int::from_le(prev)
+            }
+
+            unsafe fn cas(ptr: *mut Self, expected: Self, replacement: Self) -> Self {
+                let atomic = unsafe { core::sync::atomic::$atomic::from_ptr(ptr) };
+                let prev = atomic.compare_exchange(
+                    expected.to_le(),
+                    replacement.to_le(),
+                    SeqCst,
+                    SeqCst,
+                );
+                $int::from_le(prev.unwrap_or_else(|x| x))
+            }
+        }
+
+        #[cfg(not(target_has_atomic = $width))]
+        impl AtomicInt for $int {
+            const ENABLED: bool = false;
+
+            unsafe fn load(_ptr: *mut Self) -> Self {
+                unreachable!()
+            }
+
+            unsafe fn store(_ptr: *mut Self, _val: Self) {
+                unreachable!()
+            }
+
+            unsafe fn rmw(_ptr: *mut Self, _op: AtomicRmwOp, _val: Self) -> Self {
+                unreachable!()
+            }
+
+            unsafe fn cas(_ptr: *mut Self, _expected: Self, _replacement: Self) -> Self {
+                unreachable!()
+            }
+        }
+    )*};
+}
+
+atomic_int! {
+    u8/i8 => AtomicU8/AtomicI8 if "8";
+    u16/i16 => AtomicU16/AtomicI16 if "16";
+    u32/i32 => AtomicU32/AtomicI32 if "32";
+    u64/i64 => AtomicU64/AtomicI64 if "64";
+}
+
+/// The width of the relaxed atomic accesses used to load or store `size`
+/// bytes at `addr`: the widest one the host supports that divides both, or
+/// `None` if the host has no atomics at all.
+#[inline]
+fn relaxed_width(addr: usize, size: usize) -> Option<usize> {
+    let widths: &[usize] = &[
+        #[cfg(target_has_atomic = "64")]
+        8,
+        #[cfg(target_has_atomic = "32")]
+        4,
+        #[cfg(target_has_atomic = "16")]
+        2,
+        #[cfg(target_has_atomic = "8")]
+        1,
+    ];
+    widths.iter().copied().find(|w| (addr | size) % w == 0)
+}
+
+/// Invokes `$access!($int, $atomic)` for the integer and atomic types of
+/// `$width` bytes, or evaluates `$fallback` if the host has no atomics of
+/// that width.
+macro_rules! with_relaxed_width {
+    ($width:expr, $access:ident, $fallback:expr) => {
+        match $width {
+            #[cfg(target_has_atomic = "64")]
+            Some(8) => $access!(u64, AtomicU64),
+            #[cfg(target_has_atomic = "32")]
+            Some(4) => $access!(u32, AtomicU32),
+            #[cfg(target_has_atomic = "16")]
+            Some(2) => $access!(u16, AtomicU16),
+            #[cfg(target_has_atomic = "8")]
+            Some(1) => $access!(u8, AtomicU8),
+            _ => $fallback,
+        }
+    };
+}
+
+/// Loads a `T` from `ptr` with relaxed atomic accesses.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `T`, but needn't be aligned.
+#[inline]
+pub(super) unsafe fn load_relaxed<T>(ptr: *const T) -> T {
+    let src = ptr.cast::<u8>().cast_mut();
+    let mut val = MaybeUninit::<T>::uninit();
+    let dst = val.as_mut_ptr().cast::<u8>();
+    macro_rules! load {
+        ($int:ident, $atomic:ident) => {
+            for offset in (0..size_of::<T>()).step_by(size_of::<$int>()) {
+                unsafe {
+                    let atomic =
+                        core::sync::atomic::$atomic::from_ptr(src.add(offset).cast::<$int>());
+                    dst.add(offset)
+                        .cast::<$int>()
+                        .write_unaligned(atomic.load(Relaxed));
+                }
+            }
+        };
+    }
+    with_relaxed_width!(
+        relaxed_width(src as usize, size_of::<T>()),
+        load,
+        return unsafe { ptr.read_unaligned() }
+    );
+    unsafe { val.assume_init() }
+}
+
+/// Stores `val` to `ptr` with relaxed atomic accesses.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `T`, but needn't be aligned.
+#[inline]
+pub(super) unsafe fn store_relaxed<T>(ptr: *mut T, val: T) {
+    let dst = ptr.cast::<u8>();
+    let val = MaybeUninit::new(val);
+    let src = val.as_ptr().cast::<u8>();
+    macro_rules! store {
+        ($int:ident, $atomic:ident) => {
+            for offset in (0..size_of::<T>()).step_by(size_of::<$int>()) {
+                unsafe {
+                    let atomic =
+                        core::sync::atomic::$atomic::from_ptr(dst.add(offset).cast::<$int>());
+                    atomic.store(src.add(offset).cast::<$int>().read_unaligned(), Relaxed);
+                }
+            }
+        };
+    }
+    with_relaxed_width!(relaxed_width(dst as usize, size_of::<T>()), store, unsafe {
+        ptr.write_unaligned(val.assume_init())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rmw_returns_previous_value() {
+        let mut x = 5u32.to_le();
+        unsafe {
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Add, 3), 5);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Sub, 10), 8);
+            assert_eq!(u32::load(&mut x), u32::MAX - 1);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::And, 0xff), u32::MAX - 1);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Or, 0x100), 0xfe);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Xor, 0x1), 0x1fe);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Nand, 0x3), 0x1ff);
+            assert_eq!(u32::rmw(&mut x, AtomicRmwOp::Xchg, 7), !0x3);
+            assert_eq!(u32::load(&mut x), 7);
+        }
+    }
+
+    #[test]
+    fn cas() {
+        let mut x = 0u64;
+        unsafe {
+            assert_eq!(u64::cas(&mut x, 1, 2), 0);
+            assert_eq!(u64::load(&mut x), 0);
+            assert_eq!(u64::cas(&mut x, 0, 2), 0);
+            assert_eq!(u64::load(&mut x), 2);
+            u64::store(&mut x, 3);
+        }
+        assert_eq!(u64::from_le(x), 3);
+    }
+
+    #[test]
+    fn relaxed_accesses_of_any_alignment() {
+        let mut bytes = [0u8; 32];
+        for offset in 0..16 {
+            let ptr = bytes[offset..].as_mut_ptr();
+            unsafe {
+                store_relaxed(ptr.cast::<u64>(), 0x0102030405060708u64);
+                assert_eq!(load_relaxed(ptr.cast::<u64>()), 0x0102030405060708);
+                store_relaxed(ptr.cast::<u16>(), 0xabcd);
+                assert_eq!(load_relaxed(ptr.cast::<u16>()), 0xabcd);
+                store_relaxed(ptr.cast::<[u8; 3]>(), [1, 2, 3]);
+                assert_eq!(load_relaxed(ptr.cast::<[u8; 3]>()), [1, 2, 3]);
+                let wide = 0x0f0e0d0c0b0a09080706050403020100u128;
+                store_relaxed(ptr.cast::<u128>(), wide);
+                assert_eq!(load_relaxed(ptr.cast::<u128>()), wide);
+                assert_eq!(ptr.cast::<u128>().read_unaligned(), wide);
+            }
+        }
+    }
+
+    #[test]
+    fn narrow_values_wrap() {
+        let mut x = 0xffu8;
+        unsafe {
+            assert_eq!(u8::rmw(&mut x, AtomicRmwOp::Add, 1), 0xff);
+            assert_eq!(u8::load(&mut x), 0);
+            assert_eq!(u8::rmw(&mut x, AtomicRmwOp::Smin, 0x80), 0);
+            assert_eq!(u8::rmw(&mut x, AtomicRmwOp::Umin, 0x7f), 0x80);
+            assert_eq!(u8::rmw(&mut x, AtomicRmwOp::Smax, 0xff), 0x7f);
+            assert_eq!(u8::rmw(&mut x, AtomicRmwOp::Umax, 0xff), 0x7f);
+            assert_eq!(u8::load(&mut x), 0xff);
+        }
+    }
+}
@@ -85,6 +85,11 @@ impl<'a> OpVisitor for Debug<'a> {
 
     fn before_visit(&mut self) {
         self.0.record_executing_pc_for_profiling();
+        #[cfg(feature = "debug")]
+        {
+            let pc = self.0.pc.as_ptr().as_ptr();
+            self.0.state.maybe_stop_for_debugger(pc);
+        }
         if !DEBUG {
             return;
         }
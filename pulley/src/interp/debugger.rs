@@ -0,0 +1,191 @@
+//! Support for single-stepping and breakpoints in the Pulley interpreter.
+//!
+//! A [`Debugger`] is owned by each [`Vm`](super::Vm) when the `debug` feature
+//! is enabled. It holds a set of breakpoints, a single-stepping flag, and a
+//! hook to invoke whenever the interpreter stops. The interpreter checks the
+//! debugger before executing each instruction and, when it stops, invokes the
+//! hook with a [`DebugStop`] through which machine state may be inspected or
+//! modified. Execution resumes once the hook returns.
+
+use super::MachineState;
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use core::fmt;
+use core::ptr::NonNull;
+
+/// A callback invoked each time the interpreter stops before an instruction.
+pub type DebugHook = Box<dyn FnMut(&mut DebugStop<'_>) + Send>;
+
+/// Breakpoints, single-stepping state, and the hook to invoke when stopping.
+#[derive(Default)]
+pub struct Debugger {
+    hook: Option<DebugHook>,
+    /// Whether `set_hook` was called while the hook was running.
+    hook_replaced: bool,
+    breakpoints: BTreeSet<usize>,
+    single_step: bool,
+}
+
+/// The reason that the interpreter stopped and invoked the [`DebugHook`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// Single-stepping is enabled and the interpreter is about to execute the
+    /// next instruction.
+    Step,
+    /// The interpreter is about to execute an instruction with a breakpoint.
+    Breakpoint,
+}
+
+impl Debugger {
+    /// Sets the hook to invoke when the interpreter stops, returning the
+    /// previous hook, if any.
+    ///
+    /// Without a hook the interpreter never stops, even if single-stepping is
+    /// enabled or breakpoints are set.
+    pub fn set_hook(&mut self, hook: Option<DebugHook>) -> Option<DebugHook> {
+        self.hook_replaced = true;
+        core::mem::replace(&mut self.hook, hook)
+    }
+
+    /// Returns whether a hook is installed.
+    pub fn has_hook(&self) -> bool {
+        self.hook.is_some()
+    }
+
+    /// Enables or disables stopping before every instruction.
+    pub fn set_single_step(&mut self, enable: bool) {
+        self.single_step = enable;
+    }
+
+    /// Returns whether single-stepping is enabled.
+    pub fn single_step(&self) -> bool {
+        self.single_step
+    }
+
+    /// Adds a breakpoint on the instruction at `pc`.
+    ///
+    /// The `pc` must point to the start of an instruction for the breakpoint
+    /// to ever be hit. Returns `false` if the breakpoint was already set.
+    pub fn add_breakpoint(&mut self, pc: NonNull<u8>) -> bool {
+        self.breakpoints.insert(pc.as_ptr() as usize)
+    }
+
+    /// Removes the breakpoint on the instruction at `pc`.
+    ///
+    /// Returns `false` if no breakpoint was set at `pc`.
+    pub fn remove_breakpoint(&mut self, pc: NonNull<u8>) -> bool {
+        self.breakpoints.remove(&(pc.as_ptr() as usize))
+    }
+
+    /// Removes all breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Returns the addresses of all breakpoints, in ascending order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = NonNull<u8>> + '_ {
+        self.breakpoints
+            .iter()
+            .map(|pc| NonNull::new(*pc as *mut u8).unwrap())
+    }
+
+    /// Returns why the interpreter should stop before executing the
+    /// instruction at `pc`, if at all.
+    #[inline]
+    fn should_stop(&self, pc: *mut u8) -> Option<StopReason> {
+        self.hook.as_ref()?;
+        if self.single_step {
+            Some(StopReason::Step)
+        } else if !self.breakpoints.is_empty() && self.breakpoints.contains(&(pc as usize)) {
+            Some(StopReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for Debugger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debugger")
+            .field("hook", &self.hook.as_ref().map(|_| ()))
+            .field("breakpoints", &self.breakpoints)
+            .field("single_step", &self.single_step)
+            .finish()
+    }
+}
+
+/// The state of the interpreter when stopped before an instruction, passed to
+/// the [`DebugHook`].
+pub struct DebugStop<'a> {
+    state: &'a mut MachineState,
+    pc: NonNull<u8>,
+    reason: StopReason,
+}
+
+impl DebugStop<'_> {
+    /// Returns the address of the instruction that is about to execute.
+    pub fn pc(&self) -> NonNull<u8> {
+        self.pc
+    }
+
+    /// Returns why the interpreter stopped.
+    pub fn reason(&self) -> StopReason {
+        self.reason
+    }
+
+    /// Returns the machine state, for example to read registers.
+    pub fn state(&self) -> &MachineState {
+        self.state
+    }
+
+    /// Returns the machine state, for example to modify registers.
+    pub fn state_mut(&mut self) -> &mut MachineState {
+        self.state
+    }
+
+    /// Returns the debugger, for example to add breakpoints or change
+    /// single-stepping.
+    ///
+    /// The hook currently executing is not installed in the returned debugger
+    /// while it runs. If [`Debugger::set_hook`] is called then the hook
+    /// currently executing is dropped once it returns rather than being
+    /// reinstalled.
+    pub fn debugger(&mut self) -> &mut Debugger {
+        &mut self.state.debugger
+    }
+}
+
+impl MachineState {
+    /// Returns this machine's debugger.
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Returns this machine's debugger, for example to add breakpoints or
+    /// install a hook.
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    /// Invokes the debug hook if the instruction at `pc` is a place to stop.
+    #[inline]
+    pub(super) fn maybe_stop_for_debugger(&mut self, pc: *mut u8) {
+        if let Some(reason) = self.debugger.should_stop(pc) {
+            self.stop_for_debugger(NonNull::new(pc).unwrap(), reason);
+        }
+    }
+
+    #[cold]
+    fn stop_for_debugger(&mut self, pc: NonNull<u8>, reason: StopReason) {
+        let mut hook = self.debugger.hook.take().unwrap();
+        self.debugger.hook_replaced = false;
+        hook(&mut DebugStop {
+            state: self,
+            pc,
+            reason,
+        });
+        if !self.debugger.hook_replaced {
+            self.debugger.hook = Some(hook);
+        }
+    }
+}
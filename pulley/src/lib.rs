@@ -10,7 +10,7 @@
 #[macro_use]
 extern crate std;
 
-#[cfg(feature = "decode")]
+#[cfg(any(feature = "decode", feature = "asm"))]
 extern crate alloc;
 
 /// Calls the given macro with each opcode.
@@ -82,10 +82,14 @@ extern crate alloc;
 ///
 /// More guidelines might get added here over time, and if you have any
 /// questions feel free to raise them and we can try to add them here as well!
+///
+/// The ops listed by [`for_each_superinstruction!`] are passed to the macro
+/// after all of the ops listed here.
 #[macro_export]
 macro_rules! for_each_op {
     ( $macro:ident ) => {
-        $macro! {
+        $crate::for_each_superinstruction! {
+            @call [$crate::for_each_superinstruction] [@ops $macro {
             /// Transfer control the address in the `lr` register.
             ret = Ret;
 
@@ -611,8 +615,96 @@ macro_rules! for_each_op {
             xselect32 = XSelect32 { dst: XReg, cond: XReg, if_nonzero: XReg, if_zero: XReg };
             /// `dst = low32(cond) ? if_nonzero : if_zero`
             xselect64 = XSelect64 { dst: XReg, cond: XReg, if_nonzero: XReg, if_zero: XReg };
+            }]
+        }
+    };
+}
+
+/// Calls the given macro with each superinstruction.
+///
+/// A superinstruction is a single op which does the same thing as a sequence
+/// of other ops, saving the cost of dispatching all but one of them. The
+/// `profiler-sequences` example finds candidate sequences in Pulley profiles.
+///
+/// Each superinstruction is defined here just once, and everything else about
+/// it is generated from its definition:
+///
+/// * The op itself is included at the end of [`for_each_op!`].
+/// * The sequence after `=>` lists the ops it's equivalent to, each written
+///   as a call with the same arguments as that op's `OpVisitor` method and
+///   `encode` function. The interpreter implements the superinstruction by
+///   running this sequence. Intermediate results are kept in
+///   `XReg::spilltmp0`, which Cranelift never allocates, so it must not be
+///   used for any of the fields. Unlike the sequence, the superinstruction
+///   leaves `spilltmp0` unmodified.
+/// * Each `lower` entry is a Cranelift lowering rule selecting the
+///   superinstruction, given as its priority, the pattern matched by `lower`
+///   and then any extra clauses of the rule. The rule passes the
+///   superinstruction's non-`dst` fields, in order, to its constructor, so the
+///   pattern must bind them.
+///
+/// Fields of superinstructions are limited to types which are a single
+/// identifier, such as `XReg` or `U6`.
+#[macro_export]
+macro_rules! for_each_superinstruction {
+    // Implementation detail of `for_each_op!`, which passes its ops here to
+    // have the superinstructions appended to them.
+    (
+        @ops $macro:ident
+        { $($ops:tt)* }
+        $(
+            $( #[$attr:meta] )*
+            $snake_name:ident = $name:ident { $( $field:ident : $field_ty:ident ),* }
+                => [ $($sequence:tt)* ]
+                lower [ $($lower:tt)* ];
+        )*
+    ) => {
+        $macro! {
+            $($ops)*
+            $(
+                $( #[$attr] )*
+                $snake_name = $name { $( $field : $field_ty ),* };
+            )*
         }
     };
+
+    ( @call [ $($callback:tt)* ] [ $($prefix:tt)* ] ) => {
+        $($callback)*! {
+            $($prefix)*
+
+            /// `low32(dst) = (low32(src1) << shift) + low32(src2)`
+            ///
+            /// Frequently seen in profiles when computing addresses of array
+            /// elements.
+            xshladd32 = Xshladd32 { dst: XReg, src1: XReg, src2: XReg, shift: U6 }
+                => [
+                    xshl32_u6(BinaryOperands::new(XReg::spilltmp0, src1, shift));
+                    xadd32(BinaryOperands::new(dst, XReg::spilltmp0, src2));
+                ]
+                lower [
+                    2 => "(has_type $I32 (iadd (ishl src1 amt) src2))",
+                        "(if-let shift (u6_shift_from_iconst amt))";
+                    3 => "(has_type $I32 (iadd src2 (ishl src1 amt)))",
+                        "(if-let shift (u6_shift_from_iconst amt))";
+                ];
+            /// `dst = (src1 << shift) + src2`
+            xshladd64 = Xshladd64 { dst: XReg, src1: XReg, src2: XReg, shift: U6 }
+                => [
+                    xshl64_u6(BinaryOperands::new(XReg::spilltmp0, src1, shift));
+                    xadd64(BinaryOperands::new(dst, XReg::spilltmp0, src2));
+                ]
+                lower [
+                    2 => "(has_type $I64 (iadd (ishl src1 amt) src2))",
+                        "(if-let shift (u6_shift_from_iconst amt))";
+                    3 => "(has_type $I64 (iadd src2 (ishl src1 amt)))",
+                        "(if-let shift (u6_shift_from_iconst amt))";
+                ];
+        }
+    };
+
+    ( $macro:ident ) => {
+        $crate::for_each_superinstruction! { @call [$macro] [] }
+    };
 }
 
 /// Calls the given macro with each extended opcode.
@@ -677,6 +769,132 @@ macro_rules! for_each_extended_op {
             /// dst = if src == 0 { 0 } else { -1 }
             xbmask64 = Xbmask64 { dst: XReg, src: XReg };
 
+            // Atomic memory operations. These all use the "z" addressing mode
+            // and are sequentially consistent. The address must be naturally
+            // aligned for the width of the access.
+
+            /// Sequentially consistent memory fence.
+            fence = Fence;
+
+            /// `dst = zext(atomic_load(*addr))`
+            xatomic_load8_z = XAtomicLoad8Z { dst: XReg, addr: AddrZ };
+            /// `dst = zext(atomic_load(*addr))`
+            xatomic_load16le_z = XAtomicLoad16LeZ { dst: XReg, addr: AddrZ };
+            /// `dst = zext(atomic_load(*addr))`
+            xatomic_load32le_z = XAtomicLoad32LeZ { dst: XReg, addr: AddrZ };
+            /// `dst = atomic_load(*addr)`
+            xatomic_load64le_z = XAtomicLoad64LeZ { dst: XReg, addr: AddrZ };
+            /// `atomic_store(*addr, low8(src))`
+            xatomic_store8_z = XAtomicStore8Z { addr: AddrZ, src: XReg };
+            /// `atomic_store(*addr, low16(src))`
+            xatomic_store16le_z = XAtomicStore16LeZ { addr: AddrZ, src: XReg };
+            /// `atomic_store(*addr, low32(src))`
+            xatomic_store32le_z = XAtomicStore32LeZ { addr: AddrZ, src: XReg };
+            /// `atomic_store(*addr, src)`
+            xatomic_store64le_z = XAtomicStore64LeZ { addr: AddrZ, src: XReg };
+
+            /// `dst = zext(*addr); *addr += low8(src)` (atomically)
+            xatomic_add8_z = XAtomicAdd8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr += low16(src)` (atomically)
+            xatomic_add16le_z = XAtomicAdd16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr += low32(src)` (atomically)
+            xatomic_add32le_z = XAtomicAdd32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr += src` (atomically)
+            xatomic_add64le_z = XAtomicAdd64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr -= low8(src)` (atomically)
+            xatomic_sub8_z = XAtomicSub8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr -= low16(src)` (atomically)
+            xatomic_sub16le_z = XAtomicSub16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr -= low32(src)` (atomically)
+            xatomic_sub32le_z = XAtomicSub32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr -= src` (atomically)
+            xatomic_sub64le_z = XAtomicSub64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr &= low8(src)` (atomically)
+            xatomic_and8_z = XAtomicAnd8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr &= low16(src)` (atomically)
+            xatomic_and16le_z = XAtomicAnd16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr &= low32(src)` (atomically)
+            xatomic_and32le_z = XAtomicAnd32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr &= src` (atomically)
+            xatomic_and64le_z = XAtomicAnd64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr |= low8(src)` (atomically)
+            xatomic_or8_z = XAtomicOr8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr |= low16(src)` (atomically)
+            xatomic_or16le_z = XAtomicOr16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr |= low32(src)` (atomically)
+            xatomic_or32le_z = XAtomicOr32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr |= src` (atomically)
+            xatomic_or64le_z = XAtomicOr64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr ^= low8(src)` (atomically)
+            xatomic_xor8_z = XAtomicXor8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr ^= low16(src)` (atomically)
+            xatomic_xor16le_z = XAtomicXor16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr ^= low32(src)` (atomically)
+            xatomic_xor32le_z = XAtomicXor32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr ^= src` (atomically)
+            xatomic_xor64le_z = XAtomicXor64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = !(*addr & low8(src))` (atomically)
+            xatomic_nand8_z = XAtomicNand8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = !(*addr & low16(src))` (atomically)
+            xatomic_nand16le_z = XAtomicNand16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = !(*addr & low32(src))` (atomically)
+            xatomic_nand32le_z = XAtomicNand32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = !(*addr & src)` (atomically)
+            xatomic_nand64le_z = XAtomicNand64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umin(*addr, low8(src))` (atomically)
+            xatomic_umin8_z = XAtomicUmin8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umin(*addr, low16(src))` (atomically)
+            xatomic_umin16le_z = XAtomicUmin16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umin(*addr, low32(src))` (atomically)
+            xatomic_umin32le_z = XAtomicUmin32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = umin(*addr, src)` (atomically)
+            xatomic_umin64le_z = XAtomicUmin64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umax(*addr, low8(src))` (atomically)
+            xatomic_umax8_z = XAtomicUmax8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umax(*addr, low16(src))` (atomically)
+            xatomic_umax16le_z = XAtomicUmax16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = umax(*addr, low32(src))` (atomically)
+            xatomic_umax32le_z = XAtomicUmax32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = umax(*addr, src)` (atomically)
+            xatomic_umax64le_z = XAtomicUmax64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smin(*addr, low8(src))` (atomically)
+            xatomic_smin8_z = XAtomicSmin8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smin(*addr, low16(src))` (atomically)
+            xatomic_smin16le_z = XAtomicSmin16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smin(*addr, low32(src))` (atomically)
+            xatomic_smin32le_z = XAtomicSmin32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = smin(*addr, src)` (atomically)
+            xatomic_smin64le_z = XAtomicSmin64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smax(*addr, low8(src))` (atomically)
+            xatomic_smax8_z = XAtomicSmax8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smax(*addr, low16(src))` (atomically)
+            xatomic_smax16le_z = XAtomicSmax16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = smax(*addr, low32(src))` (atomically)
+            xatomic_smax32le_z = XAtomicSmax32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = smax(*addr, src)` (atomically)
+            xatomic_smax64le_z = XAtomicSmax64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = low8(src)` (atomically)
+            xatomic_xchg8_z = XAtomicXchg8Z { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = low16(src)` (atomically)
+            xatomic_xchg16le_z = XAtomicXchg16LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = zext(*addr); *addr = low32(src)` (atomically)
+            xatomic_xchg32le_z = XAtomicXchg32LeZ { dst: XReg, addr: AddrZ, src: XReg };
+            /// `dst = *addr; *addr = src` (atomically)
+            xatomic_xchg64le_z = XAtomicXchg64LeZ { dst: XReg, addr: AddrZ, src: XReg };
+
+            /// `dst = zext(*addr); if low8(dst) == low8(expected) { *addr =
+            /// low8(replacement) }` (atomically)
+            xatomic_cas8_z = XAtomicCas8Z { dst: XReg, addr: AddrZ, expected: XReg, replacement: XReg };
+            /// `dst = zext(*addr); if low16(dst) == low16(expected) { *addr =
+            /// low16(replacement) }` (atomically)
+            xatomic_cas16le_z = XAtomicCas16LeZ { dst: XReg, addr: AddrZ, expected: XReg, replacement: XReg };
+            /// `dst = zext(*addr); if low32(dst) == low32(expected) { *addr =
+            /// low32(replacement) }` (atomically)
+            xatomic_cas32le_z = XAtomicCas32LeZ { dst: XReg, addr: AddrZ, expected: XReg, replacement: XReg };
+            /// `dst = *addr; if dst == expected { *addr = replacement }`
+            /// (atomically)
+            xatomic_cas64le_z = XAtomicCas64LeZ { dst: XReg, addr: AddrZ, expected: XReg, replacement: XReg };
+
             // Big-endian loads/stores of X-registers using the "o32"
             // addressing mode
 
@@ -1380,6 +1598,8 @@ macro_rules! for_each_extended_op {
     };
 }
 
+#[cfg(feature = "asm")]
+pub mod asm;
 #[cfg(feature = "decode")]
 pub mod decode;
 #[cfg(feature = "disas")]
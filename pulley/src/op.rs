@@ -28,6 +28,18 @@ macro_rules! define_op {
             ExtendedOp(ExtendedOp),
         }
 
+        impl Op {
+            /// Returns the name of this op's opcode, e.g. `xadd32`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$name(_) => stringify!($snake_name),
+                    )*
+                    Self::ExtendedOp(op) => op.name(),
+                }
+            }
+        }
+
         $(
             $( #[$attr] )*
             #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -78,6 +90,17 @@ macro_rules! define_extended_op {
             )*
         }
 
+        impl ExtendedOp {
+            /// Returns the name of this op's opcode, e.g. `trap`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$name(_) => stringify!($snake_name),
+                    )*
+                }
+            }
+        }
+
         $(
             $( #[$attr] )*
             #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -0,0 +1,197 @@
+//! Assembler tests.
+
+use pulley_interpreter::encode::Encode;
+use pulley_interpreter::*;
+
+fn encoded(ops: &[Op]) -> Vec<u8> {
+    let mut encoded = vec![];
+    for op in ops {
+        op.encode(&mut encoded);
+    }
+    encoded
+}
+
+fn disassemble(bytecode: &[u8], offsets: bool, hexdump: bool) -> String {
+    let mut disas = disas::Disassembler::new(bytecode);
+    disas.offsets(offsets).hexdump(hexdump);
+    decode::Decoder::decode_all(&mut disas).expect("decoding should succeed");
+    disas.disas().to_string()
+}
+
+#[track_caller]
+fn assert_round_trip(bytecode: &[u8]) {
+    for (offsets, hexdump) in [(true, true), (true, false), (false, false)] {
+        let text = disassemble(bytecode, offsets, hexdump);
+        eprintln!("=== disassembly ===\n{text}");
+        let assembled = asm::assemble(&text).expect("assembling should succeed");
+        assert_eq!(bytecode, assembled);
+    }
+}
+
+#[test]
+fn simple() {
+    let assembled = asm::assemble(
+        "
+            push_frame
+            xadd32 x0, x0, x1  // x0 += x1
+            pop_frame
+            ret
+        ",
+    )
+    .unwrap();
+    let expected = encoded(&[
+        Op::PushFrame(PushFrame {}),
+        Op::Xadd32(Xadd32 {
+            operands: BinaryOperands {
+                dst: XReg::x0,
+                src1: XReg::x0,
+                src2: XReg::x1,
+            },
+        }),
+        Op::PopFrame(PopFrame {}),
+        Op::Ret(Ret {}),
+    ]);
+    assert_eq!(assembled, expected);
+}
+
+#[test]
+fn round_trip() {
+    let mut bytecode = encoded(&[
+        Op::PushFrameSave(PushFrameSave {
+            amt: 16,
+            regs: UpperRegSet::from_bitset(0b101.into()),
+        }),
+        Op::PushFrameSave(PushFrameSave {
+            amt: 0,
+            regs: UpperRegSet::default(),
+        }),
+        Op::Xconst8(Xconst8 {
+            dst: XReg::x0,
+            imm: -128,
+        }),
+        Op::Xconst64(Xconst64 {
+            dst: XReg::x1,
+            imm: i64::MIN,
+        }),
+        Op::from(FConst64 {
+            dst: FReg::f3,
+            bits: 1.5f64.to_bits(),
+        }),
+        Op::from(Vconst128 {
+            dst: VReg::v31,
+            imm: u128::MAX,
+        }),
+        Op::Xshladd64(Xshladd64 {
+            dst: XReg::x2,
+            src1: XReg::x3,
+            src2: XReg::x4,
+            shift: U6::new(33).unwrap(),
+        }),
+        Op::XLoad32LeG32Bne(XLoad32LeG32Bne {
+            dst: XReg::x5,
+            addr: AddrG32Bne {
+                host_heap_base: XReg::x6,
+                host_heap_bound_addr: XReg::x7,
+                host_heap_bound_offset: 8,
+                wasm_addr: XReg::x9,
+                offset: 10,
+            },
+        }),
+        Op::XLoad32LeO32(XLoad32LeO32 {
+            dst: XReg::sp,
+            addr: AddrO32 {
+                addr: XReg::spilltmp0,
+                offset: -4,
+            },
+        }),
+        Op::BrIfXult32U8(BrIfXult32U8 {
+            a: XReg::x0,
+            b: 255,
+            offset: PcRelOffset::from(-3),
+        }),
+        Op::BrTable32(BrTable32 {
+            idx: XReg::x0,
+            amt: 2,
+        }),
+    ]);
+    PcRelOffset::from(8).encode(&mut bytecode);
+    PcRelOffset::from(-12).encode(&mut bytecode);
+    Op::Ret(Ret {}).encode(&mut bytecode);
+
+    assert_round_trip(&bytecode);
+}
+
+#[test]
+fn labels() {
+    let assembled = asm::assemble(
+        "
+            xconst8 x0, 0
+        loop:
+            xadd32_u8 x0, x0, 1
+            br_if_xult32_u8 x0, 10, loop
+            jump done
+            br_table32 x0, 2
+                loop
+                done
+        done:
+            ret
+        ",
+    )
+    .unwrap();
+    let mut expected = encoded(&[
+        Op::Xconst8(Xconst8 {
+            dst: XReg::x0,
+            imm: 0,
+        }),
+        Op::Xadd32U8(Xadd32U8 {
+            dst: XReg::x0,
+            src1: XReg::x0,
+            src2: 1,
+        }),
+        Op::BrIfXult32U8(BrIfXult32U8 {
+            a: XReg::x0,
+            b: 10,
+            offset: PcRelOffset::from(-4),
+        }),
+        Op::Jump(Jump {
+            offset: PcRelOffset::from(19),
+        }),
+        Op::BrTable32(BrTable32 {
+            idx: XReg::x0,
+            amt: 2,
+        }),
+    ]);
+    PcRelOffset::from(-22).encode(&mut expected);
+    PcRelOffset::from(4).encode(&mut expected);
+    Op::Ret(Ret {}).encode(&mut expected);
+    assert_eq!(assembled, expected);
+}
+
+#[track_caller]
+fn assert_error(source: &str, line: usize, message: &str) {
+    let err = asm::assemble(source).unwrap_err();
+    assert_eq!(err.line(), line, "wrong line for error: {err}");
+    assert!(
+        err.message().contains(message),
+        "expected `{message}` in error: {err}"
+    );
+}
+
+#[test]
+fn errors() {
+    assert_error("ret\nbogus x0", 2, "unknown instruction `bogus`");
+    assert_error("ret\n\nxconst8 x0, 128", 3, "invalid `i8` immediate `128`");
+    assert_error("xconst8 x32, 1", 1, "invalid `XReg` register `x32`");
+    assert_error("xconst8 x0 1", 1, "expected `,`, found `1`");
+    assert_error("xconst8 x0,", 1, "unexpected end of line");
+    assert_error("ret x0", 1, "unexpected `x0` after instruction");
+    assert_error("jump nowhere", 1, "unknown label `nowhere`");
+    assert_error("a:\nret\na:", 3, "label `a` is defined twice");
+    assert_error(
+        "br_table32 x0, 2\n0x8",
+        1,
+        "missing entries of `br_table32`",
+    );
+    assert_error("push_frame_save 0, x15", 1, "cannot be in a register set");
+    assert_error("xconst8 x0, $1", 1, "unexpected character `$`");
+}
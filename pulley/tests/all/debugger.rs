@@ -0,0 +1,133 @@
+//! Tests for single-stepping and breakpoints in the interpreter.
+
+use pulley_interpreter::interp::{DebugStop, DoneReason, StopReason, Vm};
+use pulley_interpreter::*;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+
+fn x(x: u8) -> XReg {
+    XReg::new(x).unwrap()
+}
+
+/// Encodes `ops`, returning the bytecode and the offset of each instruction.
+fn encoded(ops: &[Op]) -> (Vec<u8>, Vec<usize>) {
+    let mut encoded = vec![];
+    let mut offsets = vec![];
+    for op in ops {
+        offsets.push(encoded.len());
+        op.encode(&mut encoded);
+    }
+    (encoded, offsets)
+}
+
+/// `x0 = 1; x0 = x0 + x0; x0 = x0 + x0; ret`
+fn program() -> (Vec<u8>, Vec<usize>) {
+    let double = Op::Xadd64(Xadd64 {
+        operands: BinaryOperands {
+            dst: x(0),
+            src1: x(0),
+            src2: x(0),
+        },
+    });
+    encoded(&[
+        Op::Xconst8(Xconst8 { dst: x(0), imm: 1 }),
+        double,
+        double,
+        Op::Ret(Ret {}),
+    ])
+}
+
+fn run(vm: &mut Vm, bytecode: &[u8]) {
+    match unsafe { vm.call(NonNull::from(bytecode).cast(), &[], []) } {
+        DoneReason::ReturnToHost(_) => {}
+        _ => panic!("unexpected trap"),
+    }
+}
+
+/// Installs a hook on `vm` which records the offset of each stop, the reason
+/// for stopping, and the value of `x0` at that point.
+fn record_stops(vm: &mut Vm, bytecode: &[u8]) -> Arc<Mutex<Vec<(usize, StopReason, u64)>>> {
+    let stops = Arc::new(Mutex::new(Vec::new()));
+    let base = bytecode.as_ptr() as usize;
+    let hook_stops = stops.clone();
+    vm.state_mut()
+        .debugger_mut()
+        .set_hook(Some(Box::new(move |stop: &mut DebugStop<'_>| {
+            let offset = stop.pc().as_ptr() as usize - base;
+            let x0 = stop.state()[x(0)].get_u64();
+            hook_stops.lock().unwrap().push((offset, stop.reason(), x0));
+        })));
+    stops
+}
+
+#[test]
+fn single_step() {
+    let (bytecode, offsets) = program();
+    let mut vm = Vm::new();
+    let stops = record_stops(&mut vm, &bytecode);
+
+    // Without single-stepping or breakpoints the hook never runs.
+    run(&mut vm, &bytecode);
+    assert!(stops.lock().unwrap().is_empty());
+
+    vm.state_mut().debugger_mut().set_single_step(true);
+    run(&mut vm, &bytecode);
+    assert_eq!(vm.state()[x(0)].get_u64(), 4);
+    assert_eq!(
+        *stops.lock().unwrap(),
+        [
+            (offsets[0], StopReason::Step, 4),
+            (offsets[1], StopReason::Step, 1),
+            (offsets[2], StopReason::Step, 2),
+            (offsets[3], StopReason::Step, 4),
+        ]
+    );
+}
+
+#[test]
+fn breakpoints() {
+    let (bytecode, offsets) = program();
+    let mut vm = Vm::new();
+    let stops = record_stops(&mut vm, &bytecode);
+
+    let at = |offset: usize| NonNull::from(&bytecode[offset]);
+    let debugger = vm.state_mut().debugger_mut();
+    assert!(debugger.add_breakpoint(at(offsets[2])));
+    assert!(!debugger.add_breakpoint(at(offsets[2])));
+    assert!(debugger.add_breakpoint(at(offsets[3])));
+    assert!(debugger.remove_breakpoint(at(offsets[3])));
+    assert_eq!(debugger.breakpoints().collect::<Vec<_>>(), [at(offsets[2])]);
+
+    run(&mut vm, &bytecode);
+    assert_eq!(
+        *stops.lock().unwrap(),
+        [(offsets[2], StopReason::Breakpoint, 2)]
+    );
+}
+
+#[test]
+fn hook_modifies_state() {
+    let (bytecode, offsets) = program();
+    let mut vm = Vm::new();
+    let at = |offset: usize| NonNull::from(&bytecode[offset]);
+    let last = at(offsets[3]).as_ptr() as usize;
+
+    // At the first breakpoint overwrite `x0` and move the breakpoint to the
+    // `ret`, and at the `ret` uninstall the hook.
+    let debugger = vm.state_mut().debugger_mut();
+    debugger.add_breakpoint(at(offsets[1]));
+    debugger.set_hook(Some(Box::new(move |stop: &mut DebugStop<'_>| {
+        if stop.pc().as_ptr() as usize == last {
+            stop.debugger().set_hook(None);
+            return;
+        }
+        stop.state_mut()[x(0)].set_u64(10);
+        stop.debugger().clear_breakpoints();
+        stop.debugger()
+            .add_breakpoint(NonNull::new(last as *mut u8).unwrap());
+    })));
+
+    run(&mut vm, &bytecode);
+    assert_eq!(vm.state()[x(0)].get_u64(), 40);
+    assert!(!vm.state().debugger().has_hook());
+}
@@ -177,6 +177,135 @@ fn xadd64() {
     }
 }
 
+#[test]
+fn xshladd32() {
+    for (expected, a, b, shift) in [
+        (42u64 | 0x1234567800000000, 10u64, 2u64, 2u8),
+        (0x1234567800000000, 1, u32::MAX as _, 0),
+        (0x1234567800000007, 0x80000001, 5, 33),
+    ] {
+        unsafe {
+            assert_one(
+                [(x(0), 0x1234567812345678), (x(1), a), (x(2), b)],
+                Xshladd32 {
+                    dst: x(0),
+                    src1: x(1),
+                    src2: x(2),
+                    shift: U6::new(shift).unwrap(),
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+}
+
+#[test]
+fn xshladd64() {
+    for (expected, a, b, shift) in [
+        (42u64, 5u64, 2u64, 3u8),
+        (0, 1, u64::MAX, 0),
+        (1, 1 << 1, 1, 63),
+    ] {
+        unsafe {
+            assert_one(
+                [(x(0), 0x1234567812345678), (x(1), a), (x(2), b)],
+                Xshladd64 {
+                    dst: x(0),
+                    src1: x(1),
+                    src2: x(2),
+                    shift: U6::new(shift).unwrap(),
+                },
+                x(0),
+                expected,
+            );
+        }
+    }
+}
+
+/// Picks the `n`th of a few interesting values for a superinstruction's field.
+trait SuperinstructionField {
+    fn nth(n: usize) -> Self;
+}
+
+impl SuperinstructionField for XReg {
+    fn nth(n: usize) -> XReg {
+        x(u8::try_from(n % 4).unwrap())
+    }
+}
+
+impl SuperinstructionField for U6 {
+    fn nth(n: usize) -> U6 {
+        U6::new([0, 1, 3, 31, 32, 63][n % 6]).unwrap()
+    }
+}
+
+macro_rules! assert_superinstructions_match_sequences {
+    (
+        $(
+            $( #[$attr:meta] )*
+            $snake_name:ident = $name:ident { $( $field:ident : $field_ty:ident ),* }
+                => [ $( $op:ident ( $($arg:tt)* ); )* ]
+                lower [ $($lower:tt)* ];
+        )*
+    ) => {
+        $(
+            for i in 0..100 {
+                let mut n = i;
+                let mut next = || {
+                    n = n / 7 + i;
+                    n
+                };
+                $( let $field = <$field_ty as SuperinstructionField>::nth(next()); )*
+                let mut fused = vec![];
+                encode::$snake_name(&mut fused, $( $field ),*);
+                encode::ret(&mut fused);
+                let mut sequence = vec![];
+                $( encode::$op(&mut sequence, $($arg)*); )*
+                encode::ret(&mut sequence);
+                assert_same_x_regs(stringify!($snake_name), &fused, &sequence);
+            }
+        )*
+    };
+}
+
+/// Runs a superinstruction and its sequence from the same initial state and
+/// asserts that they leave the `x` registers with the same values, except
+/// that the superinstruction preserves the `spilltmp0` which the sequence
+/// uses for intermediate results.
+fn assert_same_x_regs(name: &str, fused: &[u8], sequence: &[u8]) {
+    let initial = |reg: XReg| 0x0123_4567_89ab_cdef_u64.rotate_left(u32::from(reg.to_u8()) * 5);
+    // Each `Vm` has its own stack, so `sp` always differs.
+    let regs = || (0..32).map(x).filter(|reg| *reg != XReg::sp);
+    let run = |bytecode: &[u8]| {
+        let mut vm = Vm::new();
+        for reg in regs() {
+            vm.state_mut()[reg].set_u64(initial(reg));
+        }
+        match unsafe { vm.call(NonNull::from(bytecode).cast(), &[], []) } {
+            DoneReason::ReturnToHost(_) => {}
+            _ => panic!("{name} should return"),
+        }
+        regs()
+            .map(|reg| vm.state_mut()[reg].get_u64())
+            .collect::<Vec<_>>()
+    };
+    let fused = run(fused);
+    let sequence = run(sequence);
+    for ((reg, fused), sequence) in regs().zip(fused).zip(sequence) {
+        if reg == XReg::spilltmp0 {
+            assert_eq!(fused, initial(reg), "{name} should preserve {reg}");
+        } else {
+            assert_eq!(fused, sequence, "{name} differs from its sequence in {reg}");
+        }
+    }
+}
+
+#[test]
+fn superinstructions_match_sequences() {
+    for_each_superinstruction!(assert_superinstructions_match_sequences);
+}
+
 #[test]
 fn xeq64() {
     for (expected, a, b) in [
@@ -1,9 +1,15 @@
+#[cfg(all(feature = "asm", feature = "disas"))]
+mod asm;
+
 #[cfg(all(feature = "disas", feature = "encode"))]
 mod disas;
 
 #[cfg(feature = "interp")]
 mod interp;
 
+#[cfg(all(feature = "interp", feature = "debug"))]
+mod debugger;
+
 // Test the property relied on by `crates/cranelift/src/obj.rs` when filling in
 // the `PulleyHostcall` relocation.
 #[test]
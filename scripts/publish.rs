@@ -40,6 +40,7 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "cranelift-frontend",
     "cranelift-native",
     "cranelift-object",
+    "cranelift-wasm-emit",
     "cranelift-interpreter",
     "wasmtime-internal-jit-icache-coherence",
     // Wasmtime unwinder, used by both `cranelift-jit` (optionally) and filetests, and by Wasmtime.
@@ -126,6 +127,7 @@ const PUBLIC_CRATES: &[&str] = &[
     "cranelift-frontend",
     "cranelift-native",
     "cranelift-object",
+    "cranelift-wasm-emit",
     "cranelift-interpreter",
     "cranelift",
     "cranelift-jit",
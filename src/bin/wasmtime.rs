@@ -74,6 +74,10 @@ enum Subcommand {
     #[cfg(feature = "serve")]
     Serve(wasmtime_cli::commands::ServeCommand),
 
+    /// Interactively calls the exports of a WebAssembly component.
+    #[cfg(feature = "repl")]
+    Repl(wasmtime_cli::commands::ReplCommand),
+
     /// Displays available Cranelift settings for a target.
     #[cfg(feature = "cranelift")]
     Settings(wasmtime_cli::commands::SettingsCommand),
@@ -115,6 +119,9 @@ impl Wasmtime {
             #[cfg(feature = "serve")]
             Subcommand::Serve(c) => c.execute(),
 
+            #[cfg(feature = "repl")]
+            Subcommand::Repl(c) => c.execute(),
+
             #[cfg(feature = "cranelift")]
             Subcommand::Settings(c) => c.execute(),
 
@@ -39,3 +39,8 @@ pub use self::settings::*;
 mod objdump;
 #[cfg(feature = "objdump")]
 pub use self::objdump::*;
+
+#[cfg(feature = "repl")]
+mod repl;
+#[cfg(feature = "repl")]
+pub use self::repl::*;
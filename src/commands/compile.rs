@@ -43,6 +43,11 @@ pub struct CompileCommand {
     #[arg(long = "emit-clif", value_name = "PATH")]
     pub emit_clif: Option<PathBuf>,
 
+    /// The path of a profile collected with `wasmtime run --profile=pgo` to
+    /// guide the optimizations of the module.
+    #[arg(long = "profile-data", value_name = "PATH")]
+    pub profile_data: Option<PathBuf>,
+
     /// The path of the WebAssembly to compile
     #[arg(index = 1, value_name = "MODULE")]
     pub module: PathBuf,
@@ -81,6 +86,9 @@ impl CompileCommand {
 
         let mut code = CodeBuilder::new(&engine);
         code.wasm_binary_or_text_file(&self.module)?;
+        if let Some(path) = &self.profile_data {
+            code.profile_data_file(path)?;
+        }
 
         let output = self.output.take().unwrap_or_else(|| {
             let mut output: PathBuf = self.module.file_name().unwrap().into();
@@ -90,7 +98,12 @@ impl CompileCommand {
 
         let output_bytes = match code.hint() {
             #[cfg(feature = "component-model")]
-            Some(CodeHint::Component) => code.compile_component_serialized()?,
+            Some(CodeHint::Component) => {
+                if self.profile_data.is_some() {
+                    bail!("'--profile-data' is not supported for components");
+                }
+                code.compile_component_serialized()?
+            }
             #[cfg(not(feature = "component-model"))]
             Some(CodeHint::Component) => {
                 bail!("component model support was disabled at compile time")
@@ -142,6 +155,98 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_profile_data_compile() -> Result<()> {
+        const WAT: &str = r#"
+            (module
+                (func $abs (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    i32.lt_s
+                    if (result i32)
+                        i32.const 0
+                        local.get 0
+                        i32.sub
+                    else
+                        local.get 0
+                    end)
+                (func (export "f") (param i32) (result i32)
+                    local.get 0
+                    call $abs))
+        "#;
+        let (mut input, input_path) = NamedTempFile::new()?.into_parts();
+        input.write_all(WAT.as_bytes())?;
+        drop(input);
+
+        // Collect a profile of the module.
+        let mut config = wasmtime::Config::new();
+        config.profile_instrumentation(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, WAT)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+        for i in 0..1000 {
+            assert_eq!(f.call(&mut store, i)?, i);
+        }
+        let (mut profile, profile_path) = NamedTempFile::new()?.into_parts();
+        profile.write_all(&module.profile_data().unwrap())?;
+        drop(profile);
+
+        let output_path = NamedTempFile::new()?.into_temp_path();
+
+        let command = CompileCommand::try_parse_from(vec![
+            "compile",
+            "-Dlogging=n",
+            "--profile-data",
+            profile_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            input_path.to_str().unwrap(),
+        ])?;
+
+        command.execute()?;
+
+        let engine = Engine::default();
+        let contents = std::fs::read(output_path)?;
+        let module = unsafe { Module::deserialize(&engine, contents)? };
+        assert!(module.profile_data().is_none());
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+        assert_eq!(f.call(&mut store, -1234)?, 1234);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_profile_data_compile() -> Result<()> {
+        let (mut input, input_path) = NamedTempFile::new()?.into_parts();
+        input.write_all("(module)".as_bytes())?;
+        drop(input);
+        let (mut profile, profile_path) = NamedTempFile::new()?.into_parts();
+        profile.write_all(b"not a profile")?;
+        drop(profile);
+
+        let output_path = NamedTempFile::new()?.into_temp_path();
+
+        let command = CompileCommand::try_parse_from(vec![
+            "compile",
+            "-Dlogging=n",
+            "--profile-data",
+            profile_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            input_path.to_str().unwrap(),
+        ])?;
+
+        let err = command.execute().unwrap_err();
+        assert_eq!(err.to_string(), "failed to parse profile data");
+        assert_eq!(err.root_cause().to_string(), "not a wasmtime profile");
+
+        Ok(())
+    }
+
     #[cfg(target_arch = "x86_64")]
     #[test]
     fn test_x64_flags_compile() -> Result<()> {
@@ -354,6 +354,9 @@ impl ObjdumpCommand {
                     "riscv64"
                 }
             }
+            Architecture::LoongArch64 => {
+                bail!("disassembling loongarch64 code is not supported as capstone lacks LoongArch")
+            }
             other => bail!("unknown architecture {other:?}"),
         };
         let builder =
@@ -0,0 +1,761 @@
+//! The module that implements the `wasmtime repl` command.
+
+use crate::common::{Profile, RunCommon, RunTarget};
+use anyhow::{Context as _, Result, anyhow, bail};
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::io::{BufRead, IsTerminal, Write as _};
+use std::path::PathBuf;
+use wasmtime::component::types::{self, ComponentItem};
+use wasmtime::component::wasm_wave::ast::{Node, NodeType};
+use wasmtime::component::wasm_wave::untyped::{UntypedFuncCall, UntypedValue};
+use wasmtime::component::wasm_wave::wasm::DisplayValue;
+use wasmtime::component::{
+    Component, ComponentExportIndex, Func, Instance, Linker, ResourceTable, ResourceType, Type, Val,
+};
+use wasmtime::{Engine, Store, StoreLimits};
+use wasmtime_wasi::p2::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+const HELP: &str = "\
+Commands:
+  <func>(<args...>)          call an exported function with WAVE-encoded arguments
+  let <name> = <func>(...)   call a function and store its result in a variable
+  <name>                     print the value of a variable
+  .exports                   list exported functions and their signatures
+  .vars                      list variables
+  .drop <name>               drop a variable, running the destructor of a resource
+  .help                      print this message
+  .quit                      exit the REPL
+
+Variables may be used in place of argument values, e.g. `[method]counter.get(c)`.";
+
+struct Host {
+    table: ResourceTable,
+    ctx: WasiCtx,
+    limits: StoreLimits,
+}
+
+impl IoView for Host {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+impl WasiView for Host {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.ctx
+    }
+}
+
+/// Interactively calls the exports of a WebAssembly component
+#[derive(Parser)]
+pub struct ReplCommand {
+    #[command(flatten)]
+    run: RunCommon,
+
+    /// The WebAssembly component to instantiate.
+    #[arg(value_name = "WASM", required = true)]
+    component: PathBuf,
+}
+
+impl ReplCommand {
+    /// Instantiates the component and starts reading commands from stdin
+    pub fn execute(mut self) -> Result<()> {
+        self.run.common.init_logging()?;
+
+        let mut config = self.run.common.config(None)?;
+        config.wasm_component_model(true);
+
+        if self.run.common.wasm.timeout.is_some() {
+            bail!("the `timeout` option is not supported by `wasmtime repl`");
+        }
+        match self.run.profile {
+            Some(Profile::Native(s)) => {
+                config.profiler(s);
+            }
+            Some(Profile::Guest { .. }) | Some(Profile::Pgo { .. }) => {
+                bail!("only native profiling strategies are supported by `wasmtime repl`");
+            }
+            None => {}
+        }
+
+        let engine = Engine::new(&config)?;
+        let component = match self.run.load_module(&engine, &self.component)? {
+            RunTarget::Core(_) => bail!("the repl command requires a component"),
+            RunTarget::Component(c) => c,
+        };
+
+        let mut linker = Linker::new(&engine);
+        // The REPL's store is synchronous, so mirror the options of
+        // `RunCommon::compute_wasi_features` for the sync bindings.
+        let wasi = &self.run.common.wasi;
+        let mut link_options = wasmtime_wasi::p2::bindings::sync::LinkOptions::default();
+        link_options.cli_exit_with_code(wasi.cli_exit_with_code.unwrap_or(false));
+        link_options.network_error_code(wasi.network_error_code.unwrap_or(false));
+        wasmtime_wasi::p2::add_to_linker_with_options_sync(&mut linker, &link_options)?;
+
+        let mut builder = WasiCtxBuilder::new();
+        builder.inherit_stdout().inherit_stderr();
+        self.run.configure_wasip2(&mut builder)?;
+        let host = Host {
+            table: ResourceTable::new(),
+            ctx: builder.build(),
+            limits: StoreLimits::default(),
+        };
+        let mut store = Store::new(&engine, host);
+        store.data_mut().limits = self.run.store_limits();
+        store.limiter(|t| &mut t.limits);
+        if let Some(fuel) = self.run.common.wasm.fuel {
+            store.set_fuel(fuel)?;
+        }
+
+        let instance = linker.instantiate(&mut store, &component)?;
+        let mut repl = Repl::new(store, &component, instance);
+        repl.run()
+    }
+}
+
+/// An exported function, along with the path of names leading to it.
+struct Export {
+    path: Vec<String>,
+    func: Func,
+    ty: types::ComponentFunc,
+}
+
+impl Export {
+    fn name(&self) -> &str {
+        self.path.last().expect("export has at least one name")
+    }
+}
+
+/// Everything the REPL knows about the exports of the instance.
+#[derive(Default)]
+struct Exports {
+    funcs: Vec<Export>,
+    /// Exported types, used to print named types in signatures.
+    types: Vec<(Type, String)>,
+    /// Exported resource types, both as seen in the component's type and as
+    /// instantiated, used to name resource types and values.
+    resources: Vec<(ResourceType, String)>,
+}
+
+struct Repl {
+    store: Store<Host>,
+    exports: Exports,
+    vars: BTreeMap<String, Val>,
+}
+
+impl Repl {
+    fn new(mut store: Store<Host>, component: &Component, instance: Instance) -> Repl {
+        let mut exports = Exports::default();
+        collect_exports(
+            &mut store,
+            instance,
+            ComponentItem::Component(component.component_type()),
+            None,
+            Vec::new(),
+            &mut exports,
+        );
+        Repl {
+            store,
+            exports,
+            vars: BTreeMap::new(),
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let interactive = std::io::stdin().is_terminal();
+        let mut lines = std::io::stdin().lock().lines();
+        loop {
+            if interactive {
+                print!("> ");
+                std::io::stdout().flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = line?;
+            let line = line.trim();
+            match line {
+                "" => continue,
+                ".quit" | ".exit" => break,
+                _ => {}
+            }
+            if let Err(e) = self.eval(line) {
+                eprintln!("error: {e:?}");
+            }
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, line: &str) -> Result<()> {
+        if let Some(command) = line.strip_prefix('.') {
+            let (command, arg) = match command.split_once(char::is_whitespace) {
+                Some((command, arg)) => (command, arg.trim()),
+                None => (command, ""),
+            };
+            match (command, arg) {
+                ("help", "") => println!("{HELP}"),
+                ("exports", "") => self.print_exports(),
+                ("vars", "") => {
+                    for (name, val) in &self.vars {
+                        println!("{name} = {}", self.display_val(val));
+                    }
+                }
+                ("drop", name) if !name.is_empty() => {
+                    let val = self
+                        .vars
+                        .remove(name)
+                        .ok_or_else(|| anyhow!("no variable named `{name}`"))?;
+                    self.drop_resources(val)?;
+                }
+                _ => bail!("unknown command `{line}`, try `.help`"),
+            }
+            return Ok(());
+        }
+
+        if let Some(rest) = line.strip_prefix("let ") {
+            let (name, call) = rest
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected `let <name> = <func>(<args...>)`"))?;
+            let name = name.trim();
+            if !is_label(name) {
+                bail!("`{name}` is not a valid variable name");
+            }
+            if self.vars.contains_key(name) {
+                bail!("variable `{name}` is already defined, `.drop` it first");
+            }
+            let mut results = self.call(call.trim())?;
+            let val = match results.len() {
+                0 => bail!("function has no results to store in `{name}`"),
+                1 => results.pop().unwrap(),
+                _ => Val::Tuple(results),
+            };
+            println!("{name} = {}", self.display_val(&val));
+            self.vars.insert(name.to_string(), val);
+            return Ok(());
+        }
+
+        if let Some(val) = self.vars.get(line) {
+            println!("{}", self.display_val(val));
+            return Ok(());
+        }
+
+        let results = self.call(line)?;
+        match results.as_slice() {
+            [] => {}
+            [val] => println!("{}", self.display_val(val)),
+            vals => {
+                let vals = vals.iter().map(|v| self.display_val(v)).collect::<Vec<_>>();
+                println!("({})", vals.join(", "));
+            }
+        }
+        Ok(())
+    }
+
+    fn find_func(&self, name: &str) -> Result<Func> {
+        let mut matches = self
+            .exports
+            .funcs
+            .iter()
+            .filter(|f| f.name() == name || f.path.join("#") == name);
+        let Some(export) = matches.next() else {
+            bail!("no export named `{name}` in component");
+        };
+        if matches.next().is_some() {
+            bail!("multiple exports named `{name}`, use the full path shown by `.exports`");
+        }
+        Ok(export.func)
+    }
+
+    /// Parses `call` as a WAVE function call and invokes it, returning its
+    /// results.
+    fn call(&mut self, call: &str) -> Result<Vec<Val>> {
+        // Function names such as `[method]counter.get` aren't valid WAVE
+        // labels, so the name is looked up separately and replaced with a
+        // placeholder of the same length before the arguments are parsed to
+        // keep the positions in parse errors accurate.
+        let Some(i) = call.find('(') else {
+            bail!("expected a function call like `{call}(...)`");
+        };
+        let name = call[..i].trim();
+        let func = self.find_func(name)?;
+        let src = format!("{}{}", "f".repeat(i), &call[i..]);
+        let untyped = UntypedFuncCall::parse(&src)
+            .with_context(|| format!("failed to parse call to `{name}`"))?;
+
+        let param_types = func
+            .params(&self.store)
+            .iter()
+            .map(|(_, ty)| ty.clone())
+            .collect::<Vec<_>>();
+        let nodes = match untyped.params_node() {
+            Some(node) => node.as_tuple()?.collect(),
+            None => Vec::new(),
+        };
+        if nodes.len() > param_types.len() {
+            bail!(
+                "too many arguments to `{name}`: expected {}, got {}",
+                param_types.len(),
+                nodes.len()
+            );
+        }
+        let mut params = Vec::with_capacity(param_types.len());
+        for (node, ty) in nodes.iter().zip(&param_types) {
+            let param = self
+                .node_to_val(node, ty, &src)
+                .with_context(|| format!("while interpreting arguments to `{name}`"))?;
+            params.push(param);
+        }
+        // As with `wasmtime run --invoke`, omitted trailing parameters are only
+        // allowed for `option` types and default to `none`.
+        for ty in &param_types[params.len()..] {
+            match ty {
+                Type::Option(_) => params.push(Val::Option(None)),
+                _ => bail!(
+                    "missing required argument(s) to `{name}`: expected {}, got {}",
+                    param_types.len(),
+                    params.len(),
+                ),
+            }
+        }
+
+        let mut results = vec![Val::Bool(false); func.results(&self.store).len()];
+        func.call(&mut self.store, &params, &mut results)?;
+        func.post_return(&mut self.store)?;
+        Ok(results)
+    }
+
+    /// Converts `node` into a value of type `ty`, substituting the value of a
+    /// variable wherever its name appears in place of a value.
+    fn node_to_val(&self, node: &Node, ty: &Type, src: &str) -> Result<Val> {
+        if node.ty() == NodeType::Label {
+            let label = &src[node.span()];
+            if let Some(val) = self.vars.get(label.strip_prefix('%').unwrap_or(label)) {
+                return Ok(val.clone());
+            }
+        }
+        if !contains_resource(ty) {
+            return Ok(node.to_wasm_value(ty, src)?);
+        }
+        let some = |node: Option<&Node>, ty: Option<Type>| -> Result<Option<Box<Val>>> {
+            match (node, ty) {
+                (Some(node), Some(ty)) => Ok(Some(Box::new(self.node_to_val(node, &ty, src)?))),
+                (None, None) => Ok(None),
+                _ => bail!(
+                    "unexpected payload in `{}`",
+                    &src[node.map_or(0..0, |n| n.span())]
+                ),
+            }
+        };
+        Ok(match ty {
+            Type::Own(_) | Type::Borrow(_) => match node.ty() {
+                NodeType::Label => bail!("no variable named `{}`", &src[node.span()]),
+                _ => bail!(
+                    "expected a variable holding a resource, found `{}`",
+                    &src[node.span()]
+                ),
+            },
+            Type::List(list) => Val::List(
+                node.as_list()?
+                    .map(|n| self.node_to_val(n, &list.ty(), src))
+                    .collect::<Result<_>>()?,
+            ),
+            Type::Tuple(tuple) => {
+                let nodes = node.as_tuple()?;
+                if nodes.len() != tuple.types().len() {
+                    bail!("expected a tuple of {} values", tuple.types().len());
+                }
+                Val::Tuple(
+                    nodes
+                        .zip(tuple.types())
+                        .map(|(n, ty)| self.node_to_val(n, &ty, src))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            Type::Record(record) => {
+                let mut given = node.as_record(src)?.collect::<BTreeMap<_, _>>();
+                let mut fields = Vec::new();
+                for field in record.fields() {
+                    let val = match (given.remove(field.name), &field.ty) {
+                        (Some(n), ty) => self.node_to_val(n, ty, src)?,
+                        (None, Type::Option(_)) => Val::Option(None),
+                        (None, _) => bail!("missing record field `{}`", field.name),
+                    };
+                    fields.push((field.name.to_string(), val));
+                }
+                if let Some(name) = given.keys().next() {
+                    bail!("unknown record field `{name}`");
+                }
+                Val::Record(fields)
+            }
+            Type::Variant(variant) => {
+                let (name, payload) = node.as_variant(src)?;
+                let case = variant
+                    .cases()
+                    .find(|c| c.name == name)
+                    .ok_or_else(|| anyhow!("unknown variant case `{name}`"))?;
+                Val::Variant(name.to_string(), some(payload, case.ty)?)
+            }
+            Type::Option(option) => Val::Option(match node.as_option()? {
+                Some(n) => Some(Box::new(self.node_to_val(n, &option.ty(), src)?)),
+                None => None,
+            }),
+            Type::Result(result) => Val::Result(match node.as_result()? {
+                Ok(payload) => Ok(some(payload, result.ok())?),
+                Err(payload) => Err(some(payload, result.err())?),
+            }),
+            _ => unreachable!("type without resources: {ty:?}"),
+        })
+    }
+
+    /// Drops every resource contained within `val`.
+    fn drop_resources(&mut self, val: Val) -> Result<()> {
+        match val {
+            Val::Resource(resource) => resource.resource_drop(&mut self.store)?,
+            Val::List(vals) | Val::Tuple(vals) => {
+                for val in vals {
+                    self.drop_resources(val)?;
+                }
+            }
+            Val::Record(fields) => {
+                for (_, val) in fields {
+                    self.drop_resources(val)?;
+                }
+            }
+            Val::Variant(_, Some(val))
+            | Val::Option(Some(val))
+            | Val::Result(Ok(Some(val)))
+            | Val::Result(Err(Some(val))) => self.drop_resources(*val)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn print_exports(&self) {
+        for (ty, name) in &self.exports.types {
+            let kind = match ty {
+                Type::Record(_) => "record",
+                Type::Variant(_) => "variant",
+                Type::Enum(_) => "enum",
+                Type::Flags(_) => "flags",
+                _ => {
+                    println!("type {name} = {}", self.display_type(ty));
+                    continue;
+                }
+            };
+            let mut def = String::new();
+            self.fmt_type_structure(&mut def, ty).unwrap();
+            let def = def.strip_prefix(kind).unwrap();
+            println!("{kind} {name}{def}");
+        }
+        let mut resources = Vec::new();
+        for (_, name) in &self.exports.resources {
+            if !resources.contains(&name) {
+                println!("resource {name}");
+                resources.push(name);
+            }
+        }
+        for export in &self.exports.funcs {
+            let mut sig = String::new();
+            for (i, (name, ty)) in export.ty.params().enumerate() {
+                if i > 0 {
+                    sig.push_str(", ");
+                }
+                write!(sig, "{name}: {}", self.display_type(&ty)).unwrap();
+            }
+            let mut line = format!("{}: func({sig})", export.path.join("#"));
+            let results = export.ty.results().collect::<Vec<_>>();
+            match &results[..] {
+                [] => {}
+                [ty] => write!(line, " -> {}", self.display_type(ty)).unwrap(),
+                tys => {
+                    let tys = tys.iter().map(|t| self.display_type(t)).collect::<Vec<_>>();
+                    write!(line, " -> ({})", tys.join(", ")).unwrap();
+                }
+            }
+            println!("{line}");
+        }
+    }
+
+    fn resource_name(&self, ty: &ResourceType) -> &str {
+        self.exports
+            .resources
+            .iter()
+            .find(|(t, _)| t == ty)
+            .map_or("resource", |(_, name)| name)
+    }
+
+    fn display_type(&self, ty: &Type) -> String {
+        let mut s = String::new();
+        self.fmt_type(&mut s, ty).unwrap();
+        s
+    }
+
+    /// Writes `ty` in WIT syntax, using the names of exported types.
+    fn fmt_type(&self, f: &mut String, ty: &Type) -> fmt::Result {
+        if let Type::Record(_) | Type::Variant(_) | Type::Enum(_) | Type::Flags(_) = ty {
+            if let Some((_, name)) = self.exports.types.iter().find(|(t, _)| t == ty) {
+                f.push_str(name);
+                return Ok(());
+            }
+        }
+        self.fmt_type_structure(f, ty)
+    }
+
+    /// Writes the structure of `ty`, without looking up its own name.
+    fn fmt_type_structure(&self, f: &mut String, ty: &Type) -> fmt::Result {
+        let list = |f: &mut String, tys: &mut dyn Iterator<Item = Type>| -> fmt::Result {
+            for (i, ty) in tys.enumerate() {
+                if i > 0 {
+                    f.push_str(", ");
+                }
+                self.fmt_type(f, &ty)?;
+            }
+            Ok(())
+        };
+        match ty {
+            Type::List(l) => {
+                f.push_str("list<");
+                self.fmt_type(f, &l.ty())?;
+                f.push('>');
+            }
+            Type::Tuple(t) => {
+                f.push_str("tuple<");
+                list(f, &mut t.types())?;
+                f.push('>');
+            }
+            Type::Option(o) => {
+                f.push_str("option<");
+                self.fmt_type(f, &o.ty())?;
+                f.push('>');
+            }
+            Type::Result(r) => {
+                f.push_str("result");
+                match (r.ok(), r.err()) {
+                    (None, None) => {}
+                    (Some(ok), None) => {
+                        f.push('<');
+                        self.fmt_type(f, &ok)?;
+                        f.push('>');
+                    }
+                    (ok, Some(err)) => {
+                        f.push('<');
+                        match ok {
+                            Some(ok) => self.fmt_type(f, &ok)?,
+                            None => f.push('_'),
+                        }
+                        f.push_str(", ");
+                        self.fmt_type(f, &err)?;
+                        f.push('>');
+                    }
+                }
+            }
+            Type::Record(r) => {
+                f.push_str("record { ");
+                for (i, field) in r.fields().enumerate() {
+                    if i > 0 {
+                        f.push_str(", ");
+                    }
+                    write!(f, "{}: ", field.name)?;
+                    self.fmt_type(f, &field.ty)?;
+                }
+                f.push_str(" }");
+            }
+            Type::Variant(v) => {
+                f.push_str("variant { ");
+                for (i, case) in v.cases().enumerate() {
+                    if i > 0 {
+                        f.push_str(", ");
+                    }
+                    f.push_str(case.name);
+                    if let Some(ty) = &case.ty {
+                        f.push('(');
+                        self.fmt_type(f, ty)?;
+                        f.push(')');
+                    }
+                }
+                f.push_str(" }");
+            }
+            Type::Enum(e) => write!(f, "enum {{ {} }}", e.names().collect::<Vec<_>>().join(", "))?,
+            Type::Flags(fl) => write!(
+                f,
+                "flags {{ {} }}",
+                fl.names().collect::<Vec<_>>().join(", ")
+            )?,
+            Type::Own(r) => f.push_str(self.resource_name(r)),
+            Type::Borrow(r) => write!(f, "borrow<{}>", self.resource_name(r))?,
+            Type::Bool => f.push_str("bool"),
+            Type::S8 => f.push_str("s8"),
+            Type::U8 => f.push_str("u8"),
+            Type::S16 => f.push_str("s16"),
+            Type::U16 => f.push_str("u16"),
+            Type::S32 => f.push_str("s32"),
+            Type::U32 => f.push_str("u32"),
+            Type::S64 => f.push_str("s64"),
+            Type::U64 => f.push_str("u64"),
+            Type::Float32 => f.push_str("f32"),
+            Type::Float64 => f.push_str("f64"),
+            Type::Char => f.push_str("char"),
+            Type::String => f.push_str("string"),
+        }
+        Ok(())
+    }
+
+    fn display_val(&self, val: &Val) -> String {
+        let mut s = String::new();
+        self.fmt_val(&mut s, val).unwrap();
+        s
+    }
+
+    /// Writes `val` in WAVE syntax, except that resources, which WAVE can't
+    /// represent, are written as `<name>` after their resource type.
+    fn fmt_val(&self, f: &mut String, val: &Val) -> fmt::Result {
+        let list = |f: &mut String, vals: &[Val]| -> fmt::Result {
+            for (i, val) in vals.iter().enumerate() {
+                if i > 0 {
+                    f.push_str(", ");
+                }
+                self.fmt_val(f, val)?;
+            }
+            Ok(())
+        };
+        let payload = |f: &mut String, name: &str, val: &Option<Box<Val>>| -> fmt::Result {
+            f.push_str(name);
+            if let Some(val) = val {
+                f.push('(');
+                self.fmt_val(f, val)?;
+                f.push(')');
+            }
+            Ok(())
+        };
+        match val {
+            Val::Resource(r) => {
+                let kind = if r.owned() { "" } else { "borrow " };
+                write!(f, "<{kind}{}>", self.resource_name(&r.ty()))
+            }
+            val if !val_contains_resource(val) => write!(f, "{}", DisplayValue(val)),
+            Val::List(vals) => {
+                f.push('[');
+                list(f, vals)?;
+                f.push(']');
+                Ok(())
+            }
+            Val::Tuple(vals) => {
+                f.push('(');
+                list(f, vals)?;
+                f.push(')');
+                Ok(())
+            }
+            Val::Record(fields) => {
+                f.push('{');
+                for (i, (name, val)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.push_str(", ");
+                    }
+                    write!(f, "{name}: ")?;
+                    self.fmt_val(f, val)?;
+                }
+                f.push('}');
+                Ok(())
+            }
+            Val::Variant(name, val) => payload(f, name, val),
+            Val::Option(val) => match val {
+                Some(_) => payload(f, "some", val),
+                None => payload(f, "none", val),
+            },
+            Val::Result(Ok(val)) => payload(f, "ok", val),
+            Val::Result(Err(val)) => payload(f, "err", val),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Recursively finds the functions and types exported from `item`.
+fn collect_exports(
+    store: &mut Store<Host>,
+    instance: Instance,
+    item: ComponentItem,
+    index: Option<ComponentExportIndex>,
+    path: Vec<String>,
+    exports: &mut Exports,
+) {
+    let items: Vec<(String, ComponentItem)> = match &item {
+        ComponentItem::Component(c) => c
+            .exports(store.engine())
+            .map(|(name, item)| (name.to_string(), item))
+            .collect(),
+        ComponentItem::ComponentInstance(i) => i
+            .exports(store.engine())
+            .map(|(name, item)| (name.to_string(), item))
+            .collect(),
+        _ => return,
+    };
+    for (name, item) in items {
+        let index = instance
+            .get_export_index(&mut *store, index.as_ref(), &name)
+            .expect("export exists");
+        let mut path = path.clone();
+        path.push(name);
+        match item {
+            ComponentItem::ComponentFunc(ty) => {
+                let func = instance
+                    .get_func(&mut *store, &index)
+                    .expect("found export index");
+                exports.funcs.push(Export { path, func, ty });
+            }
+            ComponentItem::Type(ty) => {
+                exports.types.push((ty, path.pop().unwrap()));
+            }
+            ComponentItem::Resource(ty) => {
+                let name = path.pop().unwrap();
+                if let Some(ty) = instance.get_resource(&mut *store, &index) {
+                    exports.resources.push((ty, name.clone()));
+                }
+                exports.resources.push((ty, name));
+            }
+            ComponentItem::Component(_) | ComponentItem::ComponentInstance(_) => {
+                collect_exports(store, instance, item, Some(index), path, exports);
+            }
+            ComponentItem::CoreFunc(_) | ComponentItem::Module(_) => {}
+        }
+    }
+}
+
+/// Returns whether `name` would be parsed by WAVE as a plain label, and can
+/// therefore be used to refer to a variable.
+fn is_label(name: &str) -> bool {
+    UntypedValue::parse(name).is_ok_and(|v| v.node().ty() == NodeType::Label)
+        && !name.starts_with('%')
+}
+
+fn contains_resource(ty: &Type) -> bool {
+    match ty {
+        Type::Own(_) | Type::Borrow(_) => true,
+        Type::List(l) => contains_resource(&l.ty()),
+        Type::Tuple(t) => t.types().any(|t| contains_resource(&t)),
+        Type::Record(r) => r.fields().any(|f| contains_resource(&f.ty)),
+        Type::Variant(v) => v
+            .cases()
+            .any(|c| c.ty.as_ref().is_some_and(contains_resource)),
+        Type::Option(o) => contains_resource(&o.ty()),
+        Type::Result(r) => {
+            r.ok().as_ref().is_some_and(contains_resource)
+                || r.err().as_ref().is_some_and(contains_resource)
+        }
+        _ => false,
+    }
+}
+
+fn val_contains_resource(val: &Val) -> bool {
+    match val {
+        Val::Resource(_) => true,
+        Val::List(vals) | Val::Tuple(vals) => vals.iter().any(val_contains_resource),
+        Val::Record(fields) => fields.iter().any(|(_, v)| val_contains_resource(v)),
+        Val::Variant(_, Some(v))
+        | Val::Option(Some(v))
+        | Val::Result(Ok(Some(v)))
+        | Val::Result(Err(Some(v))) => val_contains_resource(v),
+        _ => false,
+    }
+}
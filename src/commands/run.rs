@@ -106,6 +106,12 @@ impl RunCommand {
                 // Further configured down below as well.
                 config.epoch_interruption(true);
             }
+            Some(Profile::Pgo { .. }) => {
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                config.profile_instrumentation(true);
+                #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+                bail!("support for the `pgo` profiling strategy disabled at compile time");
+            }
             None => {}
         }
 
@@ -116,6 +122,11 @@ impl RunCommand {
             .run
             .load_module(&engine, self.module_and_args[0].as_ref())?;
 
+        #[cfg(feature = "component-model")]
+        if let (Some(Profile::Pgo { .. }), RunTarget::Component(_)) = (&self.run.profile, &main) {
+            bail!("the `pgo` profiling strategy is not supported for components");
+        }
+
         // Validate coredump-on-trap argument
         if let Some(path) = &self.run.common.debug.coredump {
             if path.contains("%") {
@@ -235,6 +246,16 @@ impl RunCommand {
             .await
         });
 
+        // Write out the execution counts whether or not the module exited
+        // successfully, a trap or `proc_exit` still ends a training run.
+        if let (Some(Profile::Pgo { path }), RunTarget::Core(m)) = (&self.run.profile, &main) {
+            if let Some(profile) = m.profile_data() {
+                std::fs::write(path, profile)
+                    .with_context(|| format!("failed to write profile to `{path}`"))?;
+                eprintln!("Profile written to: {path}");
+            }
+        }
+
         // Load the main wasm module.
         match result.unwrap_or_else(|elapsed| {
             Err(anyhow::Error::from(wasmtime::Trap::Interrupt))
@@ -536,7 +557,7 @@ impl RunCommand {
         linker: &mut wasmtime::component::Linker<Host>,
     ) -> Result<()> {
         use wasmtime::component::{
-            Val,
+            Type, Val,
             types::ComponentItem,
             wasm_wave::{
                 untyped::UntypedFuncCall,
@@ -565,9 +586,23 @@ impl RunCommand {
         let (params, result_len, export) = match &matches[0] {
             (names, ComponentItem::ComponentFunc(func)) => {
                 let param_types = WasmFunc::params(func).collect::<Vec<_>>();
-                let params = untyped_call.to_wasm_params(&param_types).with_context(|| {
+                let mut params = untyped_call.to_wasm_params(&param_types).with_context(|| {
                     format!("while interpreting parameters in invoke \"{invoke}\"")
                 })?;
+                // `wasm-wave` doesn't look at the parameter types at all for
+                // an empty argument list like `f()`, so apply its rule for
+                // omitted trailing parameters here: they're only allowed for
+                // `option` types and default to `none`.
+                let given = params.len();
+                for ty in &param_types[given..] {
+                    match ty {
+                        Type::Option(_) => params.push(Val::Option(None)),
+                        _ => bail!(
+                            "missing required param(s) in invoke \"{invoke}\": expected {}, got {given}",
+                            param_types.len(),
+                        ),
+                    }
+                }
                 let mut export = None;
                 for name in names {
                     let ix = component
@@ -354,6 +354,9 @@ impl ServeCommand {
             Some(Profile::Guest { .. }) => {
                 config.epoch_interruption(true);
             }
+            Some(Profile::Pgo { .. }) => {
+                bail!("the `pgo` profiling strategy is not supported by `wasmtime serve`");
+            }
             None => {}
         }
 
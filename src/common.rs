@@ -52,7 +52,8 @@ pub struct RunCommon {
     #[arg(long = "allow-precompiled")]
     pub allow_precompiled: bool,
 
-    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest)
+    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest,
+    /// pgo)
     ///
     /// The perfmap, jitdump, and vtune profiling strategies integrate Wasmtime
     /// with external profilers such as `perf`. The guest profiling strategy
@@ -60,6 +61,12 @@ pub struct RunCommon {
     /// `wasmtime-guest-profile.json` by default which can be viewed at
     /// https://profiler.firefox.com/.
     ///
+    /// The pgo profiling strategy counts the executions of functions and
+    /// branches of a core module and writes them to `wasmtime-pgo.profdata`
+    /// by default, or to the path given as `--profile=pgo,path`. The profile
+    /// can then guide the optimizations of the module with
+    /// `wasmtime compile --profile-data`.
+    ///
     /// The `guest` option can be additionally configured as:
     ///
     ///     --profile=guest[,path[,interval]]
@@ -348,6 +355,7 @@ impl RunCommon {
 pub enum Profile {
     Native(wasmtime::ProfilingStrategy),
     Guest { path: String, interval: Duration },
+    Pgo { path: String },
 }
 
 impl Profile {
@@ -371,6 +379,12 @@ impl Profile {
                 path: path.to_string(),
                 interval: WasmtimeOptionValue::parse(Some(dur))?,
             }),
+            ["pgo"] => Ok(Profile::Pgo {
+                path: "wasmtime-pgo.profdata".to_string(),
+            }),
+            ["pgo", path] => Ok(Profile::Pgo {
+                path: path.to_string(),
+            }),
             _ => bail!("unknown profiling strategy: {s}"),
         }
     }
@@ -430,6 +430,70 @@ fn run_cwasm() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn run_pgo_profile_then_compile() -> Result<()> {
+    let td = TempDir::new()?;
+    let profdata = td.path().join("simple.profdata");
+    let cwasm = td.path().join("simple.cwasm");
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            &format!("--profile=pgo,{}", profdata.display()),
+            "--invoke",
+            "simple",
+            "tests/all/cli_tests/simple.wat",
+            "4",
+        ],
+        None,
+    )?;
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(output.stdout, b"4\n");
+
+    let profile = wasmtime_environ::ModuleProfile::from_bytes(&std::fs::read(&profdata)?)?;
+    assert_eq!(profile.num_funcs(), 5);
+    let simple = wasmtime_environ::DefinedFuncIndex::from_u32(0);
+    assert_eq!(profile.func(simple).unwrap().entries(), 1);
+
+    let stdout = run_wasmtime(&[
+        "compile",
+        "--profile-data",
+        profdata.to_str().unwrap(),
+        "tests/all/cli_tests/simple.wat",
+        "-o",
+        cwasm.to_str().unwrap(),
+    ])?;
+    assert_eq!(stdout, "");
+    let stdout = run_wasmtime(&[
+        "run",
+        "--allow-precompiled",
+        "--invoke",
+        "simple",
+        cwasm.to_str().unwrap(),
+        "4",
+    ])?;
+    assert_eq!(stdout, "4\n");
+
+    // The profile doesn't match a different module.
+    let output = run_wasmtime_for_output(
+        &[
+            "compile",
+            "--profile-data",
+            profdata.to_str().unwrap(),
+            "tests/all/cli_tests/empty-module.wat",
+            "-o",
+            cwasm.to_str().unwrap(),
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("profile data has 5 functions but the module defines 0"),
+        "{output:?}"
+    );
+    Ok(())
+}
+
 #[cfg(unix)]
 #[test]
 fn hello_wasi_snapshot0_from_stdin() -> Result<()> {
@@ -837,6 +901,122 @@ fn run_basic_component() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn invoke_component_wave() -> Result<()> {
+    let path = "tests/all/cli_tests/component-wave.wat";
+    let invoke = |call: &str| run_wasmtime(&["run", "-Ccache=n", "--invoke", call, path]);
+
+    assert_eq!(
+        invoke(r#"process({name: "x", tags: ["a"]})"#)?,
+        "{name-len: 1, tag-count: 1}\n"
+    );
+    assert_eq!(
+        invoke(r#"tags({tags: ["a", "bc"], name: "x"})"#)?,
+        "[\"a\", \"bc\"]\n"
+    );
+    assert_eq!(
+        invoke(r#"tag-count({name: "x", tags: ["a", "b"]})"#)?,
+        "2\n"
+    );
+    assert_eq!(
+        invoke(r#"tag-count({name: "x", tags: ["a", "b"]}, some(1))"#)?,
+        "1\n"
+    );
+    assert_eq!(invoke("maybe()")?, "none\n");
+    assert_eq!(invoke("maybe(some(3))")?, "some(3)\n");
+    assert_eq!(invoke("classify(0)")?, "zero\n");
+    assert_eq!(invoke("classify(5)")?, "small(5)\n");
+    assert_eq!(invoke("classify(50)")?, "big(\"big\")\n");
+
+    for (call, error) in [
+        ("process()", "missing required param(s)"),
+        ("tags({name: 1})", "while interpreting parameters"),
+        ("classify(1, 2)", "more param(s) than expected"),
+    ] {
+        let output = run_wasmtime_for_output(&["run", "-Ccache=n", "--invoke", call, path], None)?;
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(error), "bad stderr for {call}: {stderr}");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn repl_component() -> Result<()> {
+    let repl = |path: &str, input: &str| -> Result<Output> {
+        let mut child = get_wasmtime_command()?
+            .args(["repl", "-Ccache=n", path])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(input.as_bytes())?;
+        Ok(child.wait_with_output()?)
+    };
+
+    let output = repl(
+        "tests/all/cli_tests/component-wave.wat",
+        ".exports\n\
+         let s = process({name: \"xy\", tags: [\"a\"]})\n\
+         s\n\
+         maybe()\n\
+         classify(1, 2)\n",
+    )?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("record input { name: string, tags: list<string> }\n"));
+    assert!(stdout.contains("tag-count: func(input: input, limit: option<u32>) -> u32\n"));
+    assert!(stdout.ends_with(
+        "s = {name-len: 2, tag-count: 1}\n\
+         {name-len: 2, tag-count: 1}\n\
+         none\n"
+    ));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("too many arguments to `classify`"),
+        "{stderr}"
+    );
+
+    // Resources stay alive in variables between calls.
+    let output = repl(
+        "tests/all/cli_tests/component-repl.wat",
+        ".exports\n\
+         let c = [constructor]counter(5)\n\
+         [method]counter.increment(c)\n\
+         [method]counter.get(c)\n\
+         let d = [constructor]counter(10)\n\
+         total([c, d])\n\
+         try-new(500)\n\
+         .drop d\n\
+         total([d])\n\
+         .vars\n",
+    )?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        "resource counter\n\
+         [constructor]counter: func(init: u32) -> counter\n\
+         [method]counter.increment: func(self: borrow<counter>)\n\
+         [method]counter.get: func(self: borrow<counter>) -> u32\n\
+         try-new: func(init: u32) -> result<counter, string>\n\
+         total: func(counters: list<borrow<counter>>) -> u32\n\
+         c = <counter>\n\
+         6\n\
+         d = <counter>\n\
+         16\n\
+         err(\"too big\")\n\
+         c = <counter>\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no variable named `d`"), "{stderr}");
+
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(not(feature = "component-model"), ignore)]
 fn run_precompiled_component() -> Result<()> {
@@ -0,0 +1,85 @@
+(component
+  (type $r (resource (rep i32)))
+  (core func $new (canon resource.new $r))
+
+  (core module $m
+    (import "" "new" (func $new (param i32) (result i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 200) "too big")
+    (global $next (mut i32) (i32.const 16))
+    (global $bump (mut i32) (i32.const 1024))
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $bump) (i32.const 7))
+          (i32.const -8)))
+      (global.set $bump (i32.add (local.get $ret) (local.get 3)))
+      local.get $ret)
+
+    ;; The representation of a counter is the address of its count.
+    (func $new-counter (export "new-counter") (param i32) (result i32)
+      (local $slot i32)
+      (local.set $slot (global.get $next))
+      (global.set $next (i32.add (global.get $next) (i32.const 4)))
+      (i32.store (local.get $slot) (local.get 0))
+      (call $new (local.get $slot)))
+
+    ;; (u32) -> result<counter, string>
+    (func (export "try-new") (param i32) (result i32)
+      (if (i32.gt_u (local.get 0) (i32.const 100))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 1))
+          (i32.store (i32.const 4) (i32.const 200))
+          (i32.store (i32.const 8) (i32.const 7))
+          (return (i32.const 0))))
+      (i32.store8 (i32.const 0) (i32.const 0))
+      (i32.store (i32.const 4) (call $new-counter (local.get 0)))
+      i32.const 0)
+
+    (func (export "increment") (param i32)
+      (i32.store (local.get 0) (i32.add (i32.load (local.get 0)) (i32.const 1))))
+
+    (func (export "get") (param i32) (result i32)
+      (i32.load (local.get 0)))
+
+    ;; (list<borrow<counter>>) -> u32
+    (func (export "total") (param $ptr i32) (param $len i32) (result i32)
+      (local $sum i32)
+      (block $done
+        (loop $l
+          (br_if $done (i32.eqz (local.get $len)))
+          (local.set $sum
+            (i32.add (local.get $sum) (i32.load (i32.load (local.get $ptr)))))
+          (local.set $ptr (i32.add (local.get $ptr) (i32.const 4)))
+          (local.set $len (i32.sub (local.get $len) (i32.const 1)))
+          (br $l)))
+      local.get $sum)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance (export "new" (func $new))))))
+
+  (export $counter "counter" (type $r))
+
+  (func $ctor (param "init" u32) (result (own $counter))
+    (canon lift (core func $i "new-counter")))
+  (export "[constructor]counter" (func $ctor))
+
+  (func $increment (param "self" (borrow $counter))
+    (canon lift (core func $i "increment")))
+  (export "[method]counter.increment" (func $increment))
+
+  (func $get (param "self" (borrow $counter)) (result u32)
+    (canon lift (core func $i "get")))
+  (export "[method]counter.get" (func $get))
+
+  (func $try-new (param "init" u32) (result (result (own $counter) (error string)))
+    (canon lift (core func $i "try-new") (memory $i "memory")))
+  (export "try-new" (func $try-new))
+
+  (func $total (param "counters" (list (borrow $counter))) (result u32)
+    (canon lift (core func $i "total") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "total" (func $total))
+)
@@ -0,0 +1,92 @@
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (data (i32.const 100) "big")
+    (global $bump (mut i32) (i32.const 1024))
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ret i32)
+      (local.set $ret
+        (i32.and
+          (i32.add (global.get $bump) (i32.const 7))
+          (i32.const -8)))
+      (global.set $bump (i32.add (local.get $ret) (local.get 3)))
+      local.get $ret)
+
+    ;; (input) -> summary
+    (func (export "process") (param i32 i32 i32 i32) (result i32)
+      (i32.store (i32.const 0) (local.get 1))
+      (i32.store (i32.const 4) (local.get 3))
+      i32.const 0)
+
+    ;; (input) -> list<string>
+    (func (export "tags") (param i32 i32 i32 i32) (result i32)
+      (i32.store (i32.const 8) (local.get 2))
+      (i32.store (i32.const 12) (local.get 3))
+      i32.const 8)
+
+    ;; (input, option<u32>) -> u32
+    (func (export "tag-count") (param i32 i32 i32 i32 i32 i32) (result i32)
+      (if (i32.and
+            (local.get 4)
+            (i32.lt_u (local.get 5) (local.get 3)))
+        (then (return (local.get 5))))
+      local.get 3)
+
+    ;; (option<u32>) -> option<u32>
+    (func (export "maybe") (param i32 i32) (result i32)
+      (i32.store8 (i32.const 32) (local.get 0))
+      (i32.store (i32.const 36) (local.get 1))
+      i32.const 32)
+
+    ;; (u32) -> shape
+    (func (export "classify") (param i32) (result i32)
+      (if (i32.eqz (local.get 0))
+        (then
+          (i32.store8 (i32.const 16) (i32.const 0))
+          (return (i32.const 16))))
+      (if (i32.lt_u (local.get 0) (i32.const 10))
+        (then
+          (i32.store8 (i32.const 16) (i32.const 1))
+          (i32.store (i32.const 20) (local.get 0))
+          (return (i32.const 16))))
+      (i32.store8 (i32.const 16) (i32.const 2))
+      (i32.store (i32.const 20) (i32.const 100))
+      (i32.store (i32.const 24) (i32.const 3))
+      i32.const 16)
+  )
+  (core instance $i (instantiate $m))
+
+  (type $input' (record (field "name" string) (field "tags" (list string))))
+  (export $input "input" (type $input'))
+  (type $summary' (record (field "name-len" u32) (field "tag-count" u32)))
+  (export $summary "summary" (type $summary'))
+  (type $shape' (variant (case "zero") (case "small" u32) (case "big" string)))
+  (export $shape "shape" (type $shape'))
+
+  (func $process (param "input" $input) (result $summary)
+    (canon lift (core func $i "process") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "process" (func $process))
+
+  (func $tags (param "input" $input) (result (list string))
+    (canon lift (core func $i "tags") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "tags" (func $tags))
+
+  (func $tag-count (param "input" $input) (param "limit" (option u32))
+    (result u32)
+    (canon lift (core func $i "tag-count") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "tag-count" (func $tag-count))
+
+  (func $maybe (param "n" (option u32)) (result (option u32))
+    (canon lift (core func $i "maybe") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "maybe" (func $maybe))
+
+  (func $classify (param "n" u32) (result $shape)
+    (canon lift (core func $i "classify") (memory $i "memory")
+      (realloc (func $i "realloc"))))
+  (export "classify" (func $classify))
+)
@@ -688,6 +688,89 @@ fn dynamic_val() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn host_dynamic() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "t" (type $t (sub resource)))
+
+                (core func $drop (canon resource.drop $t))
+
+                (core module $m
+                    (import "" "drop" (func $drop (param i32)))
+                    (func (export "pass") (param i32) (result i32)
+                        (local.get 0))
+                    (func (export "drop") (param i32)
+                        (call $drop (local.get 0)))
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance (export "drop" (func $drop))))
+                ))
+
+                (func (export "pass") (param "x" (own $t)) (result (own $t))
+                    (canon lift (core func $i "pass")))
+                (func (export "drop") (param "x" (own $t))
+                    (canon lift (core func $i "drop")))
+            )
+        "#,
+    )?;
+
+    struct MyType;
+    assert_eq!(ResourceType::host_dynamic(1), ResourceType::host_dynamic(1));
+    assert!(ResourceType::host_dynamic(1) != ResourceType::host_dynamic(2));
+    assert!(ResourceType::host_dynamic(1) != ResourceType::host::<MyType>());
+
+    let mut store = Store::new(&engine, None);
+    let mut linker = Linker::<Option<u32>>::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host_dynamic(1), |mut cx, rep| {
+            assert!(cx.data().is_none());
+            *cx.data_mut() = Some(rep);
+            Ok(())
+        })?;
+    let i = linker.instantiate(&mut store, &c)?;
+    let pass = i.get_func(&mut store, "pass").unwrap();
+    let drop = i.get_func(&mut store, "drop").unwrap();
+
+    // Round-trip through the guest.
+    let mut results = [Val::Bool(false)];
+    let t = ResourceDynamic::new_own(100, 1).try_into_resource_any(&mut store)?;
+    assert_eq!(t.ty(), ResourceType::host_dynamic(1));
+    pass.call(&mut store, &[Val::Resource(t)], &mut results)?;
+    pass.post_return(&mut store)?;
+    let t = match &results[0] {
+        Val::Resource(t) => *t,
+        _ => unreachable!(),
+    };
+    assert!(t.try_into_resource::<MyType>(&mut store).is_err());
+    let t = ResourceDynamic::try_from_resource_any(t, &mut store)?;
+    assert_eq!(t.rep(), 100);
+    assert_eq!(t.ty(), 1);
+    assert!(t.owned());
+
+    // Dropping the resource in the guest runs the host destructor.
+    let t = t.try_into_resource_any(&mut store)?;
+    drop.call(&mut store, &[Val::Resource(t)], &mut [])?;
+    drop.post_return(&mut store)?;
+    assert_eq!(*store.data(), Some(100));
+
+    // A resource of the wrong dynamic type can't be passed in.
+    let wrong = ResourceDynamic::new_own(100, 2).try_into_resource_any(&mut store)?;
+    let err = pass
+        .call(&mut store, &[Val::Resource(wrong)], &mut results)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("mismatched resource types"),
+        "{err}"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn cannot_reenter_during_import() -> Result<()> {
     let engine = super::engine();
@@ -0,0 +1,457 @@
+//! Tests running modules produced by the Cranelift Wasm backend.
+
+use cranelift_codegen::Context;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::*;
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::*;
+use cranelift_module::*;
+use cranelift_wasm_emit::{WasmBuilder, WasmModule};
+use wasmtime::{Config, Engine, Instance, Linker, Result, Store};
+
+fn wasm_module(triple: &str) -> WasmModule {
+    let mut flag_builder = settings::builder();
+    flag_builder.set("opt_level", "speed").unwrap();
+    let isa = cranelift_codegen::isa::lookup_by_name(triple)
+        .unwrap()
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    WasmModule::new(WasmBuilder::new(isa, default_libcall_names()).unwrap())
+}
+
+fn signature(params: &[Type], returns: &[Type]) -> Signature {
+    Signature {
+        params: params.iter().map(|&ty| AbiParam::new(ty)).collect(),
+        returns: returns.iter().map(|&ty| AbiParam::new(ty)).collect(),
+        call_conv: CallConv::Tail,
+    }
+}
+
+fn define(
+    module: &mut WasmModule,
+    name: &str,
+    linkage: Linkage,
+    sig: Signature,
+    build: impl FnOnce(&mut WasmModule, &mut FunctionBuilder),
+) -> FuncId {
+    let func_id = module.declare_function(name, linkage, &sig).unwrap();
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    let mut func_ctx = FunctionBuilderContext::new();
+    let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+    build(module, &mut bcx);
+    bcx.seal_all_blocks();
+    bcx.finalize();
+    module.define_function(func_id, &mut ctx).unwrap();
+    func_id
+}
+
+fn linker() -> Result<Linker<()>> {
+    let mut config = Config::new();
+    config.wasm_memory64(true);
+    config.wasm_tail_call(true);
+    Ok(Linker::new(&Engine::new(&config)?))
+}
+
+fn instantiate(module: WasmModule, linker: &Linker<()>) -> Result<(Store<()>, Instance)> {
+    let wasm = module.finish()?.emit();
+    let engine = linker.engine();
+    let module = wasmtime::Module::new(engine, &wasm)?;
+    let mut store = Store::new(engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    Ok((store, instance))
+}
+
+fn define_sum(module: &mut WasmModule, ty: Type) {
+    define(
+        module,
+        "sum",
+        Linkage::Export,
+        signature(&[ty], &[ty]),
+        |_, bcx| {
+            let entry = bcx.create_block();
+            let header = bcx.create_block();
+            let body = bcx.create_block();
+            let exit = bcx.create_block();
+            bcx.append_block_params_for_function_params(entry);
+            bcx.append_block_param(header, ty);
+            bcx.append_block_param(header, ty);
+            bcx.append_block_param(exit, ty);
+
+            bcx.switch_to_block(entry);
+            let n = bcx.block_params(entry)[0];
+            let zero = bcx.ins().iconst(ty, 0);
+            bcx.ins().jump(header, &[zero.into(), zero.into()]);
+
+            bcx.switch_to_block(header);
+            let i = bcx.block_params(header)[0];
+            let acc = bcx.block_params(header)[1];
+            let done = bcx.ins().icmp(IntCC::UnsignedGreaterThanOrEqual, i, n);
+            bcx.ins().brif(done, exit, &[acc.into()], body, &[]);
+
+            bcx.switch_to_block(body);
+            let acc = bcx.ins().iadd(acc, i);
+            let i = bcx.ins().iadd_imm(i, 1);
+            bcx.ins().jump(header, &[i.into(), acc.into()]);
+
+            bcx.switch_to_block(exit);
+            let result = bcx.block_params(exit)[0];
+            bcx.ins().return_(&[result]);
+        },
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn loops() -> Result<()> {
+    let mut module = wasm_module("pulley32");
+    define_sum(&mut module, types::I32);
+    let (mut store, instance) = instantiate(module, &linker()?)?;
+    let sum = instance.get_typed_func::<i32, i32>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, 0)?, 0);
+    assert_eq!(sum.call(&mut store, 10)?, 45);
+
+    let mut module = wasm_module("pulley64");
+    define_sum(&mut module, types::I64);
+    let (mut store, instance) = instantiate(module, &linker()?)?;
+    let sum = instance.get_typed_func::<i64, i64>(&mut store, "sum")?;
+    assert_eq!(sum.call(&mut store, 100_000)?, 4_999_950_000);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn switch_and_irreducible() -> Result<()> {
+    let mut module = wasm_module("pulley32");
+    define(
+        &mut module,
+        "switch",
+        Linkage::Export,
+        signature(&[types::I32], &[types::I32]),
+        |_, bcx| {
+            let entry = bcx.create_block();
+            let default = bcx.create_block();
+            let exit = bcx.create_block();
+            bcx.append_block_params_for_function_params(entry);
+            bcx.append_block_param(exit, types::I32);
+
+            bcx.switch_to_block(entry);
+            let index = bcx.block_params(entry)[0];
+            let mut switch = Switch::new();
+            let mut cases = Vec::new();
+            for i in [0, 1, 2, 3, 7, 100] {
+                let case = bcx.create_block();
+                switch.set_entry(i, case);
+                cases.push((i, case));
+            }
+            switch.emit(bcx, index, default);
+
+            for (i, case) in cases {
+                bcx.switch_to_block(case);
+                let value = bcx.ins().iconst(types::I32, i64::try_from(i * 10).unwrap());
+                bcx.ins().jump(exit, &[value.into()]);
+            }
+
+            bcx.switch_to_block(default);
+            let value = bcx.ins().iconst(types::I32, -1);
+            bcx.ins().jump(exit, &[value.into()]);
+
+            bcx.switch_to_block(exit);
+            let result = bcx.block_params(exit)[0];
+            bcx.ins().return_(&[result]);
+        },
+    );
+    define(
+        &mut module,
+        "irreducible",
+        Linkage::Export,
+        signature(&[types::I32, types::I32], &[types::I32]),
+        |_, bcx| {
+            let entry = bcx.create_block();
+            let a = bcx.create_block();
+            let b = bcx.create_block();
+            let exit = bcx.create_block();
+            bcx.append_block_params_for_function_params(entry);
+            bcx.append_block_param(a, types::I32);
+            bcx.append_block_param(a, types::I32);
+            bcx.append_block_param(b, types::I32);
+            bcx.append_block_param(b, types::I32);
+            bcx.append_block_param(exit, types::I32);
+
+            // Count down from `n`, adding 1 in `a` and 2 in `b`, alternating between the two.
+            bcx.switch_to_block(entry);
+            let cond = bcx.block_params(entry)[0];
+            let n = bcx.block_params(entry)[1];
+            let zero = bcx.ins().iconst(types::I32, 0);
+            bcx.ins().brif(
+                cond,
+                a,
+                &[n.into(), zero.into()],
+                b,
+                &[n.into(), zero.into()],
+            );
+
+            bcx.switch_to_block(a);
+            let n = bcx.block_params(a)[0];
+            let acc = bcx.block_params(a)[1];
+            let acc = bcx.ins().iadd_imm(acc, 1);
+            let n = bcx.ins().iadd_imm(n, -1);
+            bcx.ins()
+                .brif(n, b, &[n.into(), acc.into()], exit, &[acc.into()]);
+
+            bcx.switch_to_block(b);
+            let n = bcx.block_params(b)[0];
+            let acc = bcx.block_params(b)[1];
+            let acc = bcx.ins().iadd_imm(acc, 2);
+            let n = bcx.ins().iadd_imm(n, -1);
+            bcx.ins()
+                .brif(n, a, &[n.into(), acc.into()], exit, &[acc.into()]);
+
+            bcx.switch_to_block(exit);
+            let result = bcx.block_params(exit)[0];
+            bcx.ins().return_(&[result]);
+        },
+    );
+
+    let (mut store, instance) = instantiate(module, &linker()?)?;
+    let switch = instance.get_typed_func::<i32, i32>(&mut store, "switch")?;
+    for i in -2..120 {
+        let expected = match i {
+            0 | 1 | 2 | 3 | 7 | 100 => i * 10,
+            _ => -1,
+        };
+        assert_eq!(switch.call(&mut store, i)?, expected, "switch({i})");
+    }
+
+    let irreducible = instance.get_typed_func::<(i32, i32), i32>(&mut store, "irreducible")?;
+    assert_eq!(irreducible.call(&mut store, (1, 1))?, 1);
+    assert_eq!(irreducible.call(&mut store, (0, 1))?, 2);
+    assert_eq!(irreducible.call(&mut store, (1, 4))?, 6);
+    assert_eq!(irreducible.call(&mut store, (0, 5))?, 8);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn small_integers() -> Result<()> {
+    let mut module = wasm_module("pulley32");
+    for (name, op) in [
+        ("add", Opcode::Iadd),
+        ("sdiv", Opcode::Sdiv),
+        ("srem", Opcode::Srem),
+        ("udiv", Opcode::Udiv),
+        ("sshr", Opcode::Sshr),
+        ("ushr", Opcode::Ushr),
+        ("ishl", Opcode::Ishl),
+        ("rotl", Opcode::Rotl),
+        ("rotr", Opcode::Rotr),
+        ("smin", Opcode::Smin),
+    ] {
+        define(
+            &mut module,
+            name,
+            Linkage::Export,
+            signature(&[types::I8, types::I8], &[types::I8]),
+            |_, bcx| {
+                let block = bcx.create_block();
+                bcx.append_block_params_for_function_params(block);
+                bcx.switch_to_block(block);
+                let a = bcx.block_params(block)[0];
+                let b = bcx.block_params(block)[1];
+                let (inst, dfg) = bcx.ins().Binary(op, types::I8, a, b);
+                let result = dfg.first_result(inst);
+                bcx.ins().return_(&[result]);
+            },
+        );
+    }
+    define(
+        &mut module,
+        "clz",
+        Linkage::Export,
+        signature(&[types::I16], &[types::I16]),
+        |_, bcx| {
+            let block = bcx.create_block();
+            bcx.append_block_params_for_function_params(block);
+            bcx.switch_to_block(block);
+            let a = bcx.block_params(block)[0];
+            let result = bcx.ins().clz(a);
+            bcx.ins().return_(&[result]);
+        },
+    );
+
+    let (mut store, instance) = instantiate(module, &linker()?)?;
+    let mut call = |name: &str, a: i8, b: i8| -> Result<i8> {
+        let f = instance.get_typed_func::<(i32, i32), i32>(&mut store, name)?;
+        // Set the upper bits of the arguments, which must be ignored.
+        let result = f.call(&mut store, (i32::from(a) | !0xff, i32::from(b) | 0x100))?;
+        assert_eq!(result & !0xff, 0, "{name}({a}, {b}) = {result:#x}");
+        Ok(result as i8)
+    };
+    assert_eq!(call("add", 100, 100)?, 100i8.wrapping_add(100));
+    assert_eq!(call("sdiv", -100, 7)?, -100 / 7);
+    assert_eq!(call("srem", -100, 7)?, -100 % 7);
+    assert_eq!(call("udiv", -100, 7)?, (156u8 / 7) as i8);
+    assert_eq!(call("sshr", -100, 10)?, -100 >> 2);
+    assert_eq!(call("ushr", -100, 2)?, (156u8 >> 2) as i8);
+    assert_eq!(call("ishl", 0x41, 1)?, 0x41i8 << 1);
+    assert_eq!(call("rotl", 0x41, 9)?, 0x41u8.rotate_left(1) as i8);
+    assert_eq!(call("rotr", 0x41, 3)?, 0x41u8.rotate_right(3) as i8);
+    assert_eq!(call("smin", -100, 7)?, -100);
+    assert!(call("sdiv", i8::MIN, -1).is_err());
+    assert!(call("sdiv", 1, 0).is_err());
+
+    let clz = instance.get_typed_func::<i32, i32>(&mut store, "clz")?;
+    assert_eq!(clz.call(&mut store, 0x0100)?, 7);
+    assert_eq!(clz.call(&mut store, 0x1_0000)?, 16);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn float_compare() -> Result<()> {
+    let conditions = [
+        FloatCC::Ordered,
+        FloatCC::Unordered,
+        FloatCC::Equal,
+        FloatCC::NotEqual,
+        FloatCC::OrderedNotEqual,
+        FloatCC::UnorderedOrEqual,
+        FloatCC::LessThan,
+        FloatCC::LessThanOrEqual,
+        FloatCC::GreaterThan,
+        FloatCC::GreaterThanOrEqual,
+        FloatCC::UnorderedOrLessThan,
+        FloatCC::UnorderedOrLessThanOrEqual,
+        FloatCC::UnorderedOrGreaterThan,
+        FloatCC::UnorderedOrGreaterThanOrEqual,
+    ];
+    let mut module = wasm_module("pulley64");
+    for cond in conditions {
+        define(
+            &mut module,
+            &cond.to_string(),
+            Linkage::Export,
+            signature(&[types::F64, types::F64], &[types::I8]),
+            |_, bcx| {
+                let block = bcx.create_block();
+                bcx.append_block_params_for_function_params(block);
+                bcx.switch_to_block(block);
+                let a = bcx.block_params(block)[0];
+                let b = bcx.block_params(block)[1];
+                let result = bcx.ins().fcmp(cond, a, b);
+                bcx.ins().return_(&[result]);
+            },
+        );
+    }
+
+    let (mut store, instance) = instantiate(module, &linker()?)?;
+    let values = [0.0, 1.0, -1.0, f64::NAN];
+    for cond in conditions {
+        let f = instance.get_typed_func::<(f64, f64), i32>(&mut store, &cond.to_string())?;
+        for a in values {
+            for b in values {
+                let unordered = a.is_nan() || b.is_nan();
+                let expected = match cond {
+                    FloatCC::Ordered => !unordered,
+                    FloatCC::Unordered => unordered,
+                    FloatCC::Equal => a == b,
+                    FloatCC::NotEqual => a != b,
+                    FloatCC::OrderedNotEqual => !unordered && a != b,
+                    FloatCC::UnorderedOrEqual => unordered || a == b,
+                    FloatCC::LessThan => a < b,
+                    FloatCC::LessThanOrEqual => a <= b,
+                    FloatCC::GreaterThan => a > b,
+                    FloatCC::GreaterThanOrEqual => a >= b,
+                    FloatCC::UnorderedOrLessThan => unordered || a < b,
+                    FloatCC::UnorderedOrLessThanOrEqual => unordered || a <= b,
+                    FloatCC::UnorderedOrGreaterThan => unordered || a > b,
+                    FloatCC::UnorderedOrGreaterThanOrEqual => unordered || a >= b,
+                };
+                assert_eq!(
+                    f.call(&mut store, (a, b))?,
+                    i32::from(expected),
+                    "fcmp {cond} {a}, {b}"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn calls_data_and_stack() -> Result<()> {
+    let mut module = wasm_module("pulley64");
+    let sig = signature(&[types::I64], &[types::I64]);
+    let host = module
+        .declare_function("host", Linkage::Import, &sig)
+        .unwrap();
+    let double = define(
+        &mut module,
+        "double",
+        Linkage::Local,
+        sig.clone(),
+        |module, bcx| {
+            let block = bcx.create_block();
+            bcx.append_block_params_for_function_params(block);
+            bcx.switch_to_block(block);
+            let x = bcx.block_params(block)[0];
+            let x = bcx.ins().iadd(x, x);
+            let callee = module.declare_func_in_func(host, bcx.func);
+            let call = bcx.ins().call(callee, &[x]);
+            let result = bcx.inst_results(call)[0];
+            bcx.ins().return_(&[result]);
+        },
+    );
+
+    // A data object holding a pointer to `double` and the value 5.
+    let table = module
+        .declare_data("table", Linkage::Local, false, false)
+        .unwrap();
+    let mut data = DataDescription::new();
+    let mut contents = vec![0; 16];
+    contents[8] = 5;
+    data.define(contents.into_boxed_slice());
+    let func_ref = module.declare_func_in_data(double, &mut data);
+    data.write_function_addr(0, func_ref);
+    module.define_data(table, &data).unwrap();
+
+    define(
+        &mut module,
+        "run",
+        Linkage::Export,
+        sig.clone(),
+        |module, bcx| {
+            let slot =
+                bcx.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 16, 3));
+            let block = bcx.create_block();
+            bcx.append_block_params_for_function_params(block);
+            bcx.switch_to_block(block);
+            let x = bcx.block_params(block)[0];
+            bcx.ins().stack_store(x, slot, 8);
+
+            let gv = module.declare_data_in_func(table, bcx.func);
+            let addr = bcx.ins().global_value(types::I64, gv);
+            let five = bcx.ins().load(types::I64, MemFlags::trusted(), addr, 8);
+            let x = bcx.ins().stack_load(types::I64, slot, 8);
+            let x = bcx.ins().iadd(x, five);
+
+            let callee = bcx.ins().load(types::I64, MemFlags::trusted(), addr, 0);
+            let sig = bcx.import_signature(sig.clone());
+            bcx.ins().return_call_indirect(sig, callee, &[x]);
+        },
+    );
+
+    let mut linker = linker()?;
+    linker.func_wrap("env", "host", |x: i64| x + 1)?;
+    let (mut store, instance) = instantiate(module, &linker)?;
+    let run = instance.get_typed_func::<i64, i64>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 10)?, 31);
+
+    // The stack pointer is restored, so calling repeatedly doesn't overflow the stack.
+    for i in 0..100_000 {
+        assert_eq!(run.call(&mut store, i)?, (i + 5) * 2 + 1);
+    }
+    Ok(())
+}
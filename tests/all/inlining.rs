@@ -0,0 +1,153 @@
+#![cfg(not(miri))]
+
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (memory 1)
+        (global $counter (mut i32) (i32.const 0))
+
+        (func $add (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add)
+        (func $max (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            local.get 0
+            local.get 1
+            i32.gt_s
+            select)
+        (func $bump (result i32)
+            global.get $counter
+            i32.const 1
+            i32.add
+            global.set $counter
+            global.get $counter)
+        (func $load (param i32) (result i32)
+            local.get 0
+            i32.load)
+        (func $div (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.div_s)
+        (func $fib (param i32) (result i32)
+            local.get 0
+            i32.const 2
+            i32.lt_u
+            if (result i32)
+                local.get 0
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                call $fib
+                local.get 0
+                i32.const 2
+                i32.sub
+                call $fib
+                call $add
+            end)
+
+        (func (export "sum_max") (param i32 i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            call $max
+            local.get 2
+            call $max
+            local.get 0
+            call $add)
+        (func (export "bump_twice") (result i32)
+            call $bump
+            call $bump
+            call $add)
+        (func (export "store_load") (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.store
+            local.get 0
+            call $load)
+        (func $call_div (export "div") (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            call $div)
+        (func (export "fib") (param i32) (result i32)
+            local.get 0
+            call $fib)
+    )
+"#;
+
+fn instantiate(inlining: bool) -> Result<(Store<()>, Instance)> {
+    let mut config = Config::new();
+    config.compiler_inlining(inlining);
+    config.cranelift_opt_level(OptLevel::Speed);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Ok((store, instance))
+}
+
+#[test]
+fn inlined_calls_match_uninlined_calls() -> Result<()> {
+    for inlining in [false, true] {
+        let (mut store, instance) = instantiate(inlining)?;
+        let sum_max = instance.get_typed_func::<(i32, i32, i32), i32>(&mut store, "sum_max")?;
+        assert_eq!(sum_max.call(&mut store, (1, 5, 3))?, 6);
+        assert_eq!(sum_max.call(&mut store, (-4, -8, -6))?, -8);
+
+        let bump_twice = instance.get_typed_func::<(), i32>(&mut store, "bump_twice")?;
+        assert_eq!(bump_twice.call(&mut store, ())?, 3);
+        assert_eq!(bump_twice.call(&mut store, ())?, 7);
+
+        let store_load = instance.get_typed_func::<(i32, i32), i32>(&mut store, "store_load")?;
+        assert_eq!(store_load.call(&mut store, (16, 42))?, 42);
+        let err = store_load.call(&mut store, (65536, 1)).unwrap_err();
+        assert_eq!(err.downcast::<Trap>()?, Trap::MemoryOutOfBounds);
+
+        let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+        assert_eq!(fib.call(&mut store, 20)?, 6765);
+    }
+    Ok(())
+}
+
+#[test]
+fn traps_in_inlined_callees() -> Result<()> {
+    let (mut store, instance) = instantiate(true)?;
+    let div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div")?;
+    assert_eq!(div.call(&mut store, (7, 2))?, 3);
+    let err = div.call(&mut store, (7, 0)).unwrap_err();
+    // Inlined frames aren't recorded, so backtraces are off by default.
+    assert!(err.downcast_ref::<WasmBacktrace>().is_none());
+    assert_eq!(err.downcast::<Trap>()?, Trap::IntegerDivisionByZero);
+    Ok(())
+}
+
+#[test]
+fn backtraces_include_callees_without_inlining() -> Result<()> {
+    let (mut store, instance) = instantiate(false)?;
+    let div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "div")?;
+    let err = div.call(&mut store, (7, 0)).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    let names = trace
+        .frames()
+        .iter()
+        .map(|frame| frame.func_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [Some("div"), Some("call_div")]);
+    assert_eq!(err.downcast::<Trap>()?, Trap::IntegerDivisionByZero);
+    Ok(())
+}
+
+#[test]
+fn inlining_and_backtraces_are_exclusive() {
+    let mut config = Config::new();
+    config.compiler_inlining(true);
+    config.wasm_backtrace(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(
+        err.to_string()
+            .contains("`compiler_inlining` cannot be enabled when `wasm_backtrace` is enabled"),
+        "{err}"
+    );
+}
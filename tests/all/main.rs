@@ -9,6 +9,7 @@ mod cli_tests;
 mod code_too_large;
 mod component_model;
 mod coredump;
+mod cranelift_wasm_emit;
 mod custom_code_memory;
 mod debug;
 mod defaults;
@@ -24,6 +25,7 @@ mod i31ref;
 mod iloop;
 mod import_calling_export;
 mod import_indexes;
+mod inlining;
 mod instance;
 mod invoke_func_via_table;
 mod limits;
@@ -34,6 +36,7 @@ mod module;
 mod module_serialize;
 mod name;
 mod noextern;
+mod pgo;
 mod piped_tests;
 mod pooling_allocator;
 mod pulley;
@@ -46,6 +49,7 @@ mod table;
 #[cfg(all(feature = "stack-switching", unix, target_arch = "x86_64"))]
 mod tags;
 mod threads;
+mod tiered_compilation;
 mod traps;
 mod types;
 mod wait_notify;
@@ -0,0 +1,155 @@
+use wasmtime::*;
+use wasmtime_environ::{DefinedFuncIndex, ModuleProfile};
+
+/// A module whose `classify` function takes its branch only for zero.
+const CLASSIFY: &str = r#"
+    (module
+        (func (export "classify") (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if
+                i32.const -1
+                return
+            end
+            local.get 0
+            i32.const 1
+            i32.add
+        )
+    )
+"#;
+
+/// Run `classify` on `0..n` in a module compiled with instrumentation, and
+/// return the collected profile.
+fn collect_profile(wat: &str, n: i32) -> Result<Vec<u8>> {
+    let mut config = Config::new();
+    config.profile_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let classify = instance.get_typed_func::<i32, i32>(&mut store, "classify")?;
+    for i in 0..n {
+        classify.call(&mut store, i)?;
+    }
+    Ok(module.profile_data().unwrap())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn profile_data_counts_executions() -> Result<()> {
+    let profile = ModuleProfile::from_bytes(&collect_profile(CLASSIFY, 1000)?)?;
+    assert_eq!(profile.num_funcs(), 1);
+    let func = profile.func(DefinedFuncIndex::from_u32(0)).unwrap();
+    assert_eq!(func.entries(), 1000);
+    let branch = func.branch(0).unwrap();
+    assert_eq!(branch.executed, 1000);
+    assert_eq!(branch.taken, 1);
+    assert!(func.branch(1).is_none());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn profile_data_is_shared_by_instances() -> Result<()> {
+    let mut config = Config::new();
+    config.profile_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, CLASSIFY)?;
+    for _ in 0..2 {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let classify = instance.get_typed_func::<i32, i32>(&mut store, "classify")?;
+        classify.call(&mut store, 1)?;
+    }
+    let profile = ModuleProfile::from_bytes(&module.profile_data().unwrap())?;
+    let func = profile.func(DefinedFuncIndex::from_u32(0)).unwrap();
+    assert_eq!(func.entries(), 2);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn profile_data_requires_instrumentation() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, CLASSIFY)?;
+    assert!(module.profile_data().is_none());
+    Ok(())
+}
+
+/// Count the blocks marked cold in the CLIF emitted when compiling
+/// `CLASSIFY` with `profile`.
+fn cold_blocks(profile: Option<&[u8]>) -> Result<usize> {
+    let clif_dir = tempfile::TempDir::new()?;
+    let mut config = Config::new();
+    config.emit_clif(clif_dir.path());
+    let engine = Engine::new(&config)?;
+    let mut code = CodeBuilder::new(&engine);
+    code.wasm_binary_or_text(CLASSIFY.as_bytes(), None)?;
+    if let Some(profile) = profile {
+        code.profile_data(profile)?;
+    }
+    code.compile_module()?;
+
+    let mut cold = 0;
+    for entry in std::fs::read_dir(clif_dir.path())? {
+        let clif = std::fs::read_to_string(entry?.path())?;
+        cold += clif
+            .lines()
+            .filter(|l| l.trim_end().ends_with(" cold:"))
+            .count();
+    }
+    Ok(cold)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn profile_data_marks_rarely_executed_blocks_cold() -> Result<()> {
+    let profile = collect_profile(CLASSIFY, 1000)?;
+    let without_profile = cold_blocks(None)?;
+    let with_profile = cold_blocks(Some(&profile))?;
+    assert!(
+        with_profile > without_profile,
+        "expected the `if` body to be cold: {with_profile} cold blocks with the \
+         profile and {without_profile} without"
+    );
+
+    // A branch taken half of the time isn't cold.
+    let mut config = Config::new();
+    config.profile_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, CLASSIFY)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let classify = instance.get_typed_func::<i32, i32>(&mut store, "classify")?;
+    for i in 0..100 {
+        classify.call(&mut store, i % 2)?;
+    }
+    let profile = module.profile_data().unwrap();
+    assert_eq!(cold_blocks(Some(&profile))?, without_profile);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn profile_data_of_another_module_is_rejected() -> Result<()> {
+    let profile = collect_profile(CLASSIFY, 10)?;
+    let engine = Engine::default();
+    let mut code = CodeBuilder::new(&engine);
+    code.wasm_binary_or_text(b"(module (func) (func))", None)?
+        .profile_data(&profile)?;
+    let err = code.compile_module().unwrap_err();
+    assert!(
+        format!("{err:?}").contains("profile data has 1 functions but the module defines 2"),
+        "unexpected error: {err:?}"
+    );
+
+    let mut code = CodeBuilder::new(&engine);
+    code.wasm_binary_or_text(CLASSIFY.as_bytes(), None)?
+        .profile_data(b"not a profile")?;
+    let err = code.compile_module().unwrap_err();
+    assert!(
+        format!("{err:?}").contains("failed to parse profile data"),
+        "unexpected error: {err:?}"
+    );
+    Ok(())
+}
@@ -19,6 +19,37 @@ fn successful_instantiation() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn metrics() -> Result<()> {
+    let pool = crate::small_pool_config();
+    let mut config = Config::new();
+    config.allocation_strategy(pool);
+    config.memory_guard_size(0);
+    config.memory_reservation(1 << 16);
+
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (memory 1) (table 10 funcref))"#)?;
+    let metrics = engine.pooling_allocator_metrics().unwrap();
+    assert_eq!(metrics.core_instances(), 0);
+    assert_eq!(metrics.memories(), 0);
+    assert_eq!(metrics.tables(), 0);
+
+    let mut store = Store::new(&engine, ());
+    Instance::new(&mut store, &module, &[])?;
+    assert_eq!(metrics.core_instances(), 1);
+    assert_eq!(metrics.component_instances(), 0);
+    assert_eq!(metrics.memories(), 1);
+    assert_eq!(metrics.tables(), 1);
+
+    drop(store);
+    assert_eq!(metrics.core_instances(), 0);
+    assert_eq!(metrics.memories(), 0);
+    assert_eq!(metrics.tables(), 0);
+
+    assert!(Engine::default().pooling_allocator_metrics().is_none());
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn memory_limit() -> Result<()> {
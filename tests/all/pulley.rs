@@ -1,7 +1,8 @@
 use anyhow::Result;
 use wasmtime::component::{self, Component};
 use wasmtime::{
-    Caller, Config, Engine, Func, FuncType, Instance, Module, Store, Trap, Val, ValType,
+    Caller, Config, Engine, Func, FuncType, Instance, MemoryType, Module, SharedMemory, Store,
+    Trap, Val, ValType,
 };
 use wasmtime_environ::TripleExt;
 
@@ -196,6 +197,54 @@ fn pulley_provenance_test() -> Result<()> {
     Ok(())
 }
 
+/// Same as `pulley_provenance_test`, but for threads racing with regular
+/// loads and stores on a shared memory, which the interpreter must implement
+/// with atomics to not have data races.
+#[test]
+#[cfg_attr(miri, ignore)]
+#[cfg(target_pointer_width = "64")]
+fn pulley_provenance_test_threads() -> Result<()> {
+    const THREADS: i32 = 4;
+    const ITERS: i32 = 32;
+
+    let mut config = provenance_test_config();
+    config.wasm_threads(true);
+    let engine = Engine::new(&config)?;
+    let module = if cfg!(miri) {
+        unsafe {
+            Module::deserialize_file(&engine, "./tests/all/pulley_provenance_test_threads.cwasm")?
+        }
+    } else {
+        Module::from_file(&engine, "./tests/all/pulley_provenance_test_threads.wat")?
+    };
+    let memory = SharedMemory::new(&engine, MemoryType::shared(1, 1))?;
+
+    let threads = (0..THREADS)
+        .map(|id| {
+            let engine = engine.clone();
+            let module = module.clone();
+            let memory = memory.clone();
+            std::thread::spawn(move || -> Result<()> {
+                let mut store = Store::new(&engine, ());
+                let instance = Instance::new(&mut store, &module, &[memory.into()])?;
+                let run = instance.get_typed_func::<(i32, i32), ()>(&mut store, "run")?;
+                run.call(&mut store, (id, ITERS))
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[memory.into()])?;
+    let check = instance.get_typed_func::<(i32, i32), i32>(&mut store, "check")?;
+    for id in 0..THREADS {
+        assert_eq!(check.call(&mut store, (id, ITERS))?, THREADS * ITERS);
+    }
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn pulley_provenance_test_components() -> Result<()> {
@@ -401,3 +450,149 @@ fn enabling_debug_info_doesnt_break_anything() -> Result<()> {
     assert!(Module::from_file(&engine, "./tests/all/cli_tests/greeter_command.wat").is_err());
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "debug-pulley")]
+#[cfg_attr(miri, ignore)]
+fn debugger_breakpoints_and_single_step() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+    use wasmtime::PulleyStopReason;
+
+    let engine = Engine::new(&pulley_config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+            )
+        "#,
+    )?;
+    let add = module.functions().next().unwrap();
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+
+    let stops = Arc::new(Mutex::new(Vec::new()));
+    let mut debugger = store.pulley_debugger().unwrap();
+    {
+        let module = module.clone();
+        let stops = stops.clone();
+        debugger.set_hook(move |stop| {
+            let offset = stop.text_offset(&module).unwrap();
+            stops.lock().unwrap().push((stop.reason(), offset));
+        });
+    }
+    assert!(debugger.add_breakpoint(&module, add.offset)?);
+    assert!(!debugger.add_breakpoint(&module, add.offset)?);
+    assert!(
+        debugger
+            .add_breakpoint(&module, module.text().len())
+            .is_err()
+    );
+
+    // The breakpoint at the start of the function is hit once per call.
+    assert_eq!(func.call(&mut store, (1, 2))?, 3);
+    assert_eq!(func.call(&mut store, (3, 4))?, 7);
+    assert_eq!(
+        *stops.lock().unwrap(),
+        [
+            (PulleyStopReason::Breakpoint, add.offset),
+            (PulleyStopReason::Breakpoint, add.offset),
+        ]
+    );
+    stops.lock().unwrap().clear();
+
+    // With single-stepping every instruction of the function, and of the
+    // trampoline calling it, stops.
+    let mut debugger = store.pulley_debugger().unwrap();
+    assert!(debugger.remove_breakpoint(&module, add.offset));
+    debugger.set_single_step(true);
+    assert_eq!(func.call(&mut store, (5, 6))?, 11);
+    {
+        let stops = stops.lock().unwrap();
+        assert!(stops.len() > 2);
+        assert!(
+            stops
+                .iter()
+                .all(|(reason, _)| *reason == PulleyStopReason::Step)
+        );
+        let in_add = |offset: usize| (add.offset..add.offset + add.len).contains(&offset);
+        assert!(stops.iter().any(|(_, offset)| in_add(*offset)));
+        assert!(stops.iter().any(|(_, offset)| !in_add(*offset)));
+    }
+    stops.lock().unwrap().clear();
+
+    // Once the hook is removed execution no longer stops.
+    let mut debugger = store.pulley_debugger().unwrap();
+    debugger.clear_hook();
+    assert_eq!(func.call(&mut store, (7, 8))?, 15);
+    assert!(stops.lock().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "debug-pulley")]
+#[cfg_attr(miri, ignore)]
+fn debugger_reads_registers() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    let engine = Engine::new(&pulley_config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "f") (param i64) (result i64)
+                    local.get 0)
+            )
+        "#,
+    )?;
+    let f = module.functions().next().unwrap();
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = instance.get_typed_func::<i64, i64>(&mut store, "f")?;
+
+    // Arguments are passed in `x2` and onwards after the callee and caller
+    // vmctx pointers, so record all of the `x` registers at function entry.
+    let regs = Arc::new(Mutex::new(Vec::new()));
+    let mut debugger = store.pulley_debugger().unwrap();
+    debugger.add_breakpoint(&module, f.offset)?;
+    {
+        let regs = regs.clone();
+        debugger.set_hook(move |stop| {
+            let mut regs = regs.lock().unwrap();
+            regs.extend((0..32).map(|i| stop.x_reg(i)));
+            assert_ne!(stop.sp(), 0);
+            assert_ne!(stop.lr(), 0);
+        });
+    }
+
+    assert_eq!(
+        func.call(&mut store, 0x1234_5678_9abc_def0)?,
+        0x1234_5678_9abc_def0
+    );
+    assert!(regs.lock().unwrap().contains(&0x1234_5678_9abc_def0));
+    Ok(())
+}
+
+#[test]
+#[cfg(all(feature = "debug-pulley", not(miri)))]
+fn debugger_is_only_available_with_pulley() -> Result<()> {
+    let engine = Engine::new(&pulley_config())?;
+    assert!(Store::new(&engine, ()).pulley_debugger().is_some());
+
+    if cfg!(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "s390x"
+    )) {
+        let engine = Engine::default();
+        assert!(Store::new(&engine, ()).pulley_debugger().is_none());
+    }
+    Ok(())
+}
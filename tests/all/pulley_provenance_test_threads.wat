@@ -0,0 +1,54 @@
+;; This file is run as part of `pulley_provenance_test_threads` in
+;; `tests/all/pulley.rs`. Like `pulley_provenance_test.wat` it's precompiled
+;; outside of miri, which then checks that threads racing with regular loads
+;; and stores on a shared memory isn't undefined behavior in the interpreter.
+(module
+  (import "" "memory" (memory 1 1 shared))
+
+  ;; Thread `$id` stores `$n` values to its own region of the memory, with
+  ;; both aligned and unaligned stores, while racing with the other threads to
+  ;; store and load bytes of a common region. Each iteration is counted in the
+  ;; atomic counter at address 0.
+  (func (export "run") (param $id i32) (param $n i32)
+    (local $i i32)
+    (local $own i32)
+    (local.set $own (i32.add (i32.const 1024) (i32.mul (local.get $id) (i32.const 1024))))
+    (loop $l
+      ;; Racing unaligned and byte accesses to the common region.
+      (i32.store8 (i32.add (i32.const 64) (local.get $i)) (local.get $id))
+      (drop (i32.load (i32.add (i32.const 63) (local.get $i))))
+      (drop (i64.load (i32.add (i32.const 61) (local.get $i))))
+      (i32.store16 offset=65 (local.get $i) (local.get $id))
+
+      ;; Accesses to this thread's own region.
+      (i64.store
+        (i32.add (local.get $own) (i32.mul (local.get $i) (i32.const 8)))
+        (i64.extend_i32_u (local.get $i)))
+      (i32.store offset=513
+        (i32.add (local.get $own) (i32.mul (local.get $i) (i32.const 4)))
+        (local.get $i))
+
+      (drop (i32.atomic.rmw.add (i32.const 0) (i32.const 1)))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $l (i32.lt_u (local.get $i) (local.get $n)))))
+
+  ;; Checks that thread `$id` stored all of its `$n` values, returning the
+  ;; value of the atomic counter or -1 on a mismatch.
+  (func (export "check") (param $id i32) (param $n i32) (result i32)
+    (local $i i32)
+    (local $own i32)
+    (local.set $own (i32.add (i32.const 1024) (i32.mul (local.get $id) (i32.const 1024))))
+    (loop $l
+      (if (i64.ne
+            (i64.load (i32.add (local.get $own) (i32.mul (local.get $i) (i32.const 8))))
+            (i64.extend_i32_u (local.get $i)))
+        (then (return (i32.const -1))))
+      (if (i32.ne
+            (i32.load offset=513
+              (i32.add (local.get $own) (i32.mul (local.get $i) (i32.const 4))))
+            (local.get $i))
+        (then (return (i32.const -1))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $l (i32.lt_u (local.get $i) (local.get $n))))
+    (i32.atomic.load (i32.const 0)))
+)
@@ -0,0 +1,510 @@
+#![cfg(all(not(miri), any(target_arch = "x86_64", target_arch = "aarch64")))]
+
+use wasmtime::*;
+
+const WAT: &str = r#"
+    (module
+        (memory 1)
+        (func $fib (export "fib") (param i32) (result i32)
+            local.get 0
+            i32.const 2
+            i32.lt_u
+            if (result i32)
+                local.get 0
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                call $fib
+                local.get 0
+                i32.const 2
+                i32.sub
+                call $fib
+                i32.add
+            end
+        )
+        (func (export "trap") unreachable)
+        (func (export "store") (param i32 i32)
+            local.get 0
+            local.get 1
+            i32.store)
+        (func (export "load") (param i32) (result i32)
+            local.get 0
+            i32.load)
+    )
+"#;
+
+fn tiered_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    config.tiered_compilation(true);
+    Engine::new(&config)
+}
+
+fn call_fib(engine: &Engine, module: &Module) -> Result<i32> {
+    let mut store = Store::new(engine, ());
+    let instance = Instance::new(&mut store, module, &[])?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    fib.call(&mut store, 20)
+}
+
+#[test]
+fn tier_up_is_used_by_new_instances() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    assert!(!module.is_tiered_up());
+    assert_eq!(call_fib(&engine, &module)?, 6765);
+
+    module.tier_up()?;
+    assert!(module.is_tiered_up());
+    assert_eq!(call_fib(&engine, &module)?, 6765);
+
+    // Tiering up again is a no-op.
+    module.tier_up()?;
+    assert!(module.is_tiered_up());
+    Ok(())
+}
+
+#[test]
+fn instances_keep_their_tier() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+
+    let baseline = Instance::new(&mut store, &module, &[])?;
+    module.tier_up()?;
+    let optimized = Instance::new(&mut store, &module, &[])?;
+
+    for instance in [baseline, optimized] {
+        let store_fn = instance.get_typed_func::<(i32, i32), ()>(&mut store, "store")?;
+        let load_fn = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+        store_fn.call(&mut store, (8, 42))?;
+        assert_eq!(load_fn.call(&mut store, 8)?, 42);
+        assert!(load_fn.call(&mut store, 65536).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn traps_in_tiered_up_code_have_backtraces() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    module.tier_up()?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    let err = trap.call(&mut store, ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::UnreachableCodeReached)
+    );
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert_eq!(trace.frames().len(), 1);
+    assert_eq!(trace.frames()[0].func_index(), 1);
+    Ok(())
+}
+
+#[test]
+fn tier_up_on_another_thread() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+
+    std::thread::scope(|s| {
+        let handle = s.spawn(|| module.tier_up());
+        assert_eq!(call_fib(&engine, &module)?, 6765);
+        handle.join().unwrap()
+    })?;
+
+    assert!(module.is_tiered_up());
+    assert_eq!(call_fib(&engine, &module)?, 6765);
+    Ok(())
+}
+
+/// Calls `fib` on `instance` until its module has been tiered up in the
+/// background.
+fn call_until_tiered_up(store: &mut Store<()>, instance: Instance, module: &Module) -> Result<()> {
+    let fib = instance.get_typed_func::<i32, i32>(&mut *store, "fib")?;
+    let start = std::time::Instant::now();
+    while !module.is_tiered_up() {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(60),
+            "module wasn't tiered up in the background"
+        );
+        assert_eq!(fib.call(&mut *store, 20)?, 6765);
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    Ok(())
+}
+
+#[test]
+fn hot_module_tiers_up_in_background() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    call_until_tiered_up(&mut store, instance, &module)?;
+
+    // New instances start out in the optimized tier.
+    assert_eq!(call_fib(&engine, &module)?, 6765);
+    Ok(())
+}
+
+#[test]
+fn hot_modules_tier_up_one_after_another() -> Result<()> {
+    let engine = tiered_engine()?;
+    let modules = (0..4)
+        .map(|_| Module::new(&engine, WAT))
+        .collect::<Result<Vec<_>>>()?;
+    let mut store = Store::new(&engine, ());
+    for module in &modules {
+        let instance = Instance::new(&mut store, module, &[])?;
+        call_until_tiered_up(&mut store, instance, module)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn engine_can_be_dropped_with_hot_modules() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    assert_eq!(call_fib(&engine, &module)?, 6765);
+    // Queued modules keep the engine alive until they've been tiered up, so
+    // this returns immediately and the engine is dropped later on.
+    drop((engine, module));
+    Ok(())
+}
+
+#[test]
+fn running_instance_switches_to_optimized_code() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let store_fn = instance.get_typed_func::<(i32, i32), ()>(&mut store, "store")?;
+    let load_fn = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    store_fn.call(&mut store, (8, 42))?;
+
+    call_until_tiered_up(&mut store, instance, &module)?;
+    // The next hot call switches the instance over to the optimized code.
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    assert_eq!(fib.call(&mut store, 20)?, 6765);
+
+    // The state of the instance carries over to the optimized code.
+    assert_eq!(load_fn.call(&mut store, 8)?, 42);
+    assert!(load_fn.call(&mut store, 65536).is_err());
+
+    // Traps now come from the optimized module, which unlike the baseline
+    // module can't be tiered up any further.
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    let err = trap.call(&mut store, ()).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert_eq!(trace.frames().len(), 1);
+    assert_eq!(trace.frames()[0].func_index(), 1);
+    assert!(module.is_tiered_up());
+    assert!(!trace.frames()[0].module().is_tiered_up());
+    Ok(())
+}
+
+#[test]
+fn whole_module_tiers_up_at_once() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    call_until_tiered_up(&mut store, instance, &module)?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    fib.call(&mut store, 20)?;
+
+    // Only `fib` was hot, but `trap`, which was never called before, now
+    // runs optimized code as well.
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    let err = trap.call(&mut store, ()).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert!(!trace.frames()[0].module().is_tiered_up());
+    Ok(())
+}
+
+#[test]
+fn tiers_share_a_calling_convention() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "host" "switch" (func $switch))
+                (func $many (export "many")
+                    (param i32 i64 f32 f64 i32 i64 f32 f64 i32 i64)
+                    (result i64 f64 i32)
+                    local.get 1
+                    local.get 5
+                    i64.add
+                    local.get 9
+                    i64.add
+                    local.get 3
+                    local.get 7
+                    f64.add
+                    local.get 2
+                    f64.promote_f32
+                    f64.add
+                    local.get 0
+                    local.get 4
+                    i32.add
+                    local.get 8
+                    i32.add)
+                (func (export "run") (result i64 f64 i32)
+                    call $switch
+                    ;; Baseline code calling optimized code with many
+                    ;; parameters and results.
+                    i32.const 1
+                    i64.const 2
+                    f32.const 3
+                    f64.const 4
+                    i32.const 5
+                    i64.const 6
+                    f32.const 7
+                    f64.const 8
+                    i32.const 9
+                    i64.const 10
+                    call $many)
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    let switch = module.clone();
+    linker.func_wrap("host", "switch", move |mut caller: Caller<'_, ()>| {
+        switch.tier_up()?;
+        let many = caller.get_export("many").unwrap().into_func().unwrap();
+        let many = many
+            .typed::<(i32, i64, f32, f64, i32, i64, f32, f64, i32, i64), (i64, f64, i32)>(
+                &caller,
+            )?;
+        for _ in 0..5000 {
+            many.call(&mut caller, (1, 2, 3.0, 4.0, 5, 6, 7.0, 8.0, 9, 10))?;
+        }
+        Ok(())
+    })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<(), (i64, f64, i32)>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, (18, 15.0, 15));
+    // Optimized code calling optimized code.
+    assert_eq!(run.call(&mut store, ())?, (18, 15.0, 15));
+    Ok(())
+}
+
+#[test]
+fn imports_keep_baseline_code() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let importer = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "m" "trap" (func $trap))
+                (func (export "call-trap") call $trap))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let trap = instance.get_func(&mut store, "trap").unwrap();
+    let importer = Instance::new(&mut store, &importer, &[trap.into()])?;
+
+    call_until_tiered_up(&mut store, instance, &module)?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    fib.call(&mut store, 20)?;
+
+    // The export now runs optimized code...
+    let err = trap
+        .typed::<(), ()>(&store)?
+        .call(&mut store, ())
+        .unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert!(!trace.frames()[0].module().is_tiered_up());
+
+    // ... but the import resolved before the switch still runs baseline code.
+    let call_trap = importer.get_typed_func::<(), ()>(&mut store, "call-trap")?;
+    let err = call_trap.call(&mut store, ()).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert!(trace.frames()[0].module().is_tiered_up());
+    Ok(())
+}
+
+#[test]
+fn modules_in_components_do_not_tier_up() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let engine = tiered_engine()?;
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (core module $m (func (export "trap") unreachable))
+                (core instance $i (instantiate $m))
+                (func (export "trap") (canon lift (core func $i "trap")))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let trap = instance.get_typed_func::<(), ()>(&mut store, "trap")?;
+    let err = trap.call(&mut store, ()).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    let module = trace.frames()[0].module();
+    assert!(!module.is_tiered_up());
+    assert!(module.tier_up().is_err());
+    Ok(())
+}
+
+#[test]
+fn tiered_compilation_requires_winch() -> Result<()> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Cranelift);
+    config.tiered_compilation(true);
+    let err = Engine::new(&config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "tiered compilation requires Winch as the baseline compilation strategy"
+    );
+    Ok(())
+}
+
+#[test]
+fn tier_up_without_tiered_compilation() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, WAT)?;
+    assert!(module.tier_up().is_err());
+    assert!(!module.is_tiered_up());
+
+    let engine = tiered_engine()?;
+    let module = Module::new(&engine, WAT)?;
+    let serialized = module.serialize()?;
+    let module = unsafe { Module::deserialize(&engine, &serialized)? };
+    assert!(module.tier_up().is_err());
+    Ok(())
+}
+
+#[test]
+fn switch_with_baseline_frames_on_the_stack() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "host" "switch" (func $switch))
+                (global $count (mut i32) (i32.const 0))
+                (table 1 funcref)
+                (elem (i32.const 0) $inc)
+                (func $inc
+                    global.get $count
+                    i32.const 1
+                    i32.add
+                    global.set $count)
+                (func (export "hot") (param i32)
+                    loop
+                        call $inc
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.tee 0
+                        br_if 0
+                    end)
+                (func $trap (export "trap") unreachable)
+                (func (export "run") (param i32) (result i32)
+                    call $inc
+                    call $switch
+                    ;; This frame is still running baseline code while the
+                    ;; instance's functions have switched to optimized code.
+                    call $inc
+                    i32.const 0
+                    call_indirect
+                    local.get 0
+                    if
+                        call $trap
+                    end
+                    global.get $count)
+            )
+        "#,
+    )?;
+    let mut linker = Linker::new(&engine);
+    let switch = module.clone();
+    linker.func_wrap("host", "switch", move |mut caller: Caller<'_, ()>| {
+        switch.tier_up()?;
+        let hot = caller.get_export("hot").unwrap().into_func().unwrap();
+        hot.typed::<i32, ()>(&caller)?.call(&mut caller, 5000)?;
+        Ok(())
+    })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &module)?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+
+    // The trap happens in optimized code called from the baseline frame of
+    // `run` which started before the switch.
+    let err = run.call(&mut store, 1).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::UnreachableCodeReached)
+    );
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    let frames = trace
+        .frames()
+        .iter()
+        .map(|f| (f.func_index(), f.module().is_tiered_up()))
+        .collect::<Vec<_>>();
+    assert_eq!(frames, [(3, false), (4, true)]);
+
+    // `run` itself is now optimized, and the state of the instance carried
+    // over: both calls incremented the counter 5003 times.
+    assert_eq!(run.call(&mut store, 0)?, 10006);
+    Ok(())
+}
+
+#[test]
+fn profile_data_seeds_tier_up() -> Result<()> {
+    // Collect a profile in which `load` was called often.
+    let mut config = Config::new();
+    config.profile_instrumentation(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, WAT)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    for _ in 0..5000 {
+        load.call(&mut store, 0)?;
+    }
+    let profile = module.profile_data().unwrap();
+
+    let engine = tiered_engine()?;
+    let call_load_once = |module: &Module| -> Result<()> {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, module, &[])?;
+        let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+        assert_eq!(load.call(&mut store, 0)?, 0);
+        Ok(())
+    };
+
+    // Without the profile a single call is far from enough to tier up.
+    let module = Module::new(&engine, WAT)?;
+    call_load_once(&module)?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(!module.is_tiered_up());
+
+    // With it the calls of the profile count as well, so the first call
+    // already tiers up the module.
+    let module = CodeBuilder::new(&engine)
+        .wasm_binary_or_text(WAT.as_bytes(), None)?
+        .profile_data(&profile)?
+        .compile_module()?;
+    call_load_once(&module)?;
+    let start = std::time::Instant::now();
+    while !module.is_tiered_up() {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(60),
+            "module wasn't tiered up in the background"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    Ok(())
+}
@@ -0,0 +1,367 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = [
+;;!   "-Ccranelift-enable-heap-access-spectre-mitigation=false",
+;;!   "-Ostatic-memory-maximum-size=0",
+;;!   "-Odynamic-memory-guard-size=0",
+;;!   "-Cloop-optimizations",
+;;!   "-Cbounds-check-merging",
+;;! ]
+
+(module
+  (memory 1)
+
+  ;; The first bounds check is widened to cover the later accesses.
+  (func (export "increasing") (param i32) (result i32 i32 i32)
+    local.get 0
+    i32.load offset=0
+    local.get 0
+    i32.load offset=4
+    local.get 0
+    i32.load offset=8
+  )
+
+  ;; The first bounds check already covers the later accesses.
+  (func (export "decreasing") (param i32) (result i32 i32 i32)
+    local.get 0
+    i32.load offset=8
+    local.get 0
+    i32.load offset=4
+    local.get 0
+    i32.load offset=0
+  )
+
+  ;; A store may not happen before a wider bounds check traps, so each of
+  ;; these is checked on its own.
+  (func (export "stores") (param i32 i32)
+    local.get 0
+    local.get 1
+    i32.store offset=0
+    local.get 0
+    local.get 1
+    i32.store offset=4
+  )
+
+  ;; The bounds check of loop-invariant addresses, and the loads from them, are
+  ;; hoisted out of the loop.
+  (func (export "loop") (param i32 i32) (result i32)
+    (local i32)
+    loop
+      local.get 0
+      i32.load offset=0
+      local.get 0
+      i32.load offset=4
+      i32.add
+      local.get 2
+      i32.add
+      local.set 2
+      local.get 1
+      i32.const 1
+      i32.sub
+      local.tee 1
+      br_if 0
+    end
+    local.get 2
+  )
+
+  ;; The check of the first access dominates the block after the `if`, and
+  ;; every path to it is free of side effects, so it is widened to cover the
+  ;; second access as well.
+  (func (export "loop-with-if") (param i32 i32) (result i32)
+    (local i32)
+    loop
+      local.get 0
+      i32.load offset=0
+      local.get 2
+      i32.add
+      local.set 2
+      local.get 1
+      i32.const 1
+      i32.and
+      if
+        local.get 2
+        i32.const 1
+        i32.add
+        local.set 2
+      end
+      local.get 0
+      i32.load offset=4
+      local.get 2
+      i32.add
+      local.set 2
+      local.get 1
+      i32.const 1
+      i32.sub
+      local.tee 1
+      br_if 0
+    end
+    local.get 2
+  )
+
+  ;; A call on one of the paths between the accesses may have side effects,
+  ;; so the first check can't be widened to cover the last access. It still
+  ;; covers the second one, even if the call grew the memory.
+  (func (export "call-between") (param i32 i32) (result i32 i32 i32)
+    local.get 0
+    i32.load offset=4
+    local.get 1
+    if
+      call $f
+    end
+    local.get 0
+    i32.load offset=0
+    local.get 0
+    i32.load offset=8
+  )
+
+  (func $f)
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32, i32, i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @008a                               v7 = load.i64 notrap aligned v0+64
+;; @008a                               v6 = uextend.i64 v2
+;; @008a                               v8 = iconst.i64 12
+;; @008a                               v9 = isub v7, v8  ; v8 = 12
+;; @008a                               v10 = icmp ugt v6, v9
+;; @008a                               trapnz v10, heap_oob
+;; @008a                               v11 = load.i64 notrap aligned can_move checked v0+56
+;; @008a                               v12 = iadd v11, v6
+;; @008a                               v13 = load.i32 little heap v12
+;; @008f                               v17 = iconst.i64 4
+;; @008f                               v18 = iadd v12, v17  ; v17 = 4
+;; @008f                               v19 = load.i32 little heap v18
+;; @0094                               v23 = iconst.i64 8
+;; @0094                               v24 = iadd v12, v23  ; v23 = 8
+;; @0094                               v25 = load.i32 little heap v24
+;; @0097                               jump block1
+;;
+;;                                 block1:
+;; @0097                               return v13, v19, v25
+;; }
+;;
+;; function u0:1(i64 vmctx, i64, i32) -> i32, i32, i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @009c                               v7 = load.i64 notrap aligned v0+64
+;; @009c                               v6 = uextend.i64 v2
+;; @009c                               v8 = iconst.i64 12
+;; @009c                               v9 = isub v7, v8  ; v8 = 12
+;; @009c                               v10 = icmp ugt v6, v9
+;; @009c                               trapnz v10, heap_oob
+;; @009c                               v11 = load.i64 notrap aligned can_move checked v0+56
+;; @009c                               v12 = iadd v11, v6
+;; @009c                               v13 = iconst.i64 8
+;; @009c                               v14 = iadd v12, v13  ; v13 = 8
+;; @009c                               v15 = load.i32 little heap v14
+;; @00a1                               v19 = iconst.i64 4
+;; @00a1                               v20 = iadd v12, v19  ; v19 = 4
+;; @00a1                               v21 = load.i32 little heap v20
+;; @00a6                               v25 = load.i32 little heap v12
+;; @00a9                               jump block1
+;;
+;;                                 block1:
+;; @00a9                               return v15, v21, v25
+;; }
+;;
+;; function u0:2(i64 vmctx, i64, i32, i32) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @00b0                               v5 = load.i64 notrap aligned v0+64
+;; @00b0                               v4 = uextend.i64 v2
+;; @00b0                               v6 = iconst.i64 4
+;; @00b0                               v7 = isub v5, v6  ; v6 = 4
+;; @00b0                               v8 = icmp ugt v4, v7
+;; @00b0                               trapnz v8, heap_oob
+;; @00b0                               v9 = load.i64 notrap aligned can_move checked v0+56
+;; @00b0                               v10 = iadd v9, v4
+;; @00b0                               store little heap v3, v10
+;; @00b7                               v13 = iconst.i64 8
+;; @00b7                               v14 = isub v5, v13  ; v13 = 8
+;; @00b7                               v15 = icmp ugt v4, v14
+;; @00b7                               trapnz v15, heap_oob
+;; @00b7                               v19 = iadd v10, v6  ; v6 = 4
+;; @00b7                               store little heap v3, v19
+;; @00ba                               jump block1
+;;
+;;                                 block1:
+;; @00ba                               return
+;; }
+;;
+;; function u0:3(i64 vmctx, i64, i32, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @00c3                               v8 = load.i64 notrap aligned v0+64
+;; @00c3                               v7 = uextend.i64 v2
+;; @00c3                               v9 = iconst.i64 8
+;; @00c3                               v10 = isub v8, v9  ; v9 = 8
+;; @00c3                               v11 = icmp ugt v7, v10
+;; @00c3                               trapnz v11, heap_oob
+;; @00c3                               v12 = load.i64 notrap aligned can_move checked v0+56
+;; @00c3                               v13 = iadd v12, v7
+;; @00c3                               v14 = load.i32 little heap v13
+;; @00c8                               v18 = iconst.i64 4
+;; @00c8                               v19 = iadd v13, v18  ; v18 = 4
+;; @00c8                               v20 = load.i32 little heap v19
+;; @00bd                               v5 = iconst.i32 0
+;; @00d3                               v25 = iconst.i32 1
+;; @00cb                               v21 = iadd v14, v20
+;; @00bf                               jump block2(v5, v3)  ; v5 = 0
+;;
+;;                                 block2(v22: i32, v24: i32):
+;;                                     v30 = iconst.i32 1
+;;                                     v31 = isub v24, v30  ; v30 = 1
+;; @00ce                               v23 = iadd.i32 v21, v22
+;; @00d8                               brif v31, block2(v23, v31), block4
+;;
+;;                                 block4:
+;; @00da                               jump block3
+;;
+;;                                 block3:
+;; @00dd                               jump block1
+;;
+;;                                 block1:
+;; @00dd                               return v23
+;; }
+;;
+;; function u0:4(i64 vmctx, i64, i32, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @00e6                               v8 = load.i64 notrap aligned v0+64
+;; @00e6                               v7 = uextend.i64 v2
+;; @00e6                               v9 = iconst.i64 8
+;; @00e6                               v10 = isub v8, v9  ; v9 = 8
+;; @00e6                               v11 = icmp ugt v7, v10
+;; @00e6                               trapnz v11, heap_oob
+;; @00e6                               v12 = load.i64 notrap aligned can_move checked v0+56
+;; @00e6                               v13 = iadd v12, v7
+;; @00e6                               v14 = load.i32 little heap v13
+;; @00e0                               v5 = iconst.i32 0
+;; @00f0                               v18 = iconst.i32 1
+;; @00ff                               v26 = iconst.i64 4
+;; @00ff                               v27 = iadd v13, v26  ; v26 = 4
+;; @00e2                               jump block2(v5, v3)  ; v5 = 0
+;;
+;;                                 block2(v15: i32, v17: i32):
+;;                                     v37 = iconst.i32 1
+;;                                     v38 = band v17, v37  ; v37 = 1
+;; @00eb                               v16 = iadd.i32 v14, v15
+;; @00f3                               brif v38, block4, block5(v16)
+;;
+;;                                 block4:
+;;                                     v39 = iconst.i32 1
+;;                                     v40 = iadd.i32 v16, v39  ; v39 = 1
+;; @00fc                               jump block5(v40)
+;;
+;;                                 block5(v29: i32):
+;;                                     v41 = iadd.i64 v13, v26  ; v26 = 4
+;; @00ff                               v28 = load.i32 little heap v41
+;;                                     v42 = iconst.i32 1
+;;                                     v43 = isub.i32 v17, v42  ; v42 = 1
+;; @0104                               v30 = iadd v28, v29
+;; @010e                               brif v43, block2(v30, v43), block6
+;;
+;;                                 block6:
+;; @0110                               jump block3
+;;
+;;                                 block3:
+;; @0113                               jump block1
+;;
+;;                                 block1:
+;; @0113                               return v30
+;; }
+;;
+;; function u0:5(i64 vmctx, i64, i32, i32) -> i32, i32, i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned can_move checked gv3+56
+;;     sig0 = (i64 vmctx, i64) tail
+;;     fn0 = colocated u0:6 sig0
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @0118                               v8 = load.i64 notrap aligned v0+64
+;; @0118                               v7 = uextend.i64 v2
+;; @0118                               v9 = iconst.i64 8
+;; @0118                               v10 = isub v8, v9  ; v9 = 8
+;; @0118                               v11 = icmp ugt v7, v10
+;; @0118                               trapnz v11, heap_oob
+;; @0118                               v12 = load.i64 notrap aligned can_move checked v0+56
+;; @0118                               v13 = iadd v12, v7
+;; @0118                               v14 = iconst.i64 4
+;; @0118                               v15 = iadd v13, v14  ; v14 = 4
+;; @0118                               v16 = load.i32 little heap v15
+;; @011d                               brif v3, block2, block3
+;;
+;;                                 block2:
+;; @011f                               call fn0(v0, v0)
+;; @0121                               jump block3
+;;
+;;                                 block3:
+;; @0124                               v19 = load.i64 notrap aligned can_move checked v0+56
+;; @0124                               v20 = iadd v19, v7
+;; @0124                               v21 = load.i32 little heap v20
+;; @0129                               v23 = load.i64 notrap aligned v0+64
+;; @0129                               v24 = iconst.i64 12
+;; @0129                               v25 = isub v23, v24  ; v24 = 12
+;; @0129                               v26 = icmp.i64 ugt v7, v25
+;; @0129                               trapnz v26, heap_oob
+;;                                     v37 = iconst.i64 8
+;;                                     v38 = iadd v20, v37  ; v37 = 8
+;; @0129                               v31 = load.i32 little heap v38
+;; @012c                               jump block1
+;;
+;;                                 block1:
+;; @012c                               return v16, v21, v31
+;; }
+;;
+;; function u0:6(i64 vmctx, i64) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64):
+;; @012f                               jump block1
+;;
+;;                                 block1:
+;; @012f                               return
+;; }
@@ -28,7 +28,7 @@
 ;;     sig0 = (i64 vmctx, i32, i32) -> i32 tail
 ;;     sig1 = (i64 vmctx, i64) tail
 ;;     sig2 = (i64 vmctx, i64) tail
-;;     fn0 = colocated u1:36 sig0
+;;     fn0 = colocated u1:37 sig0
 ;;     fn1 = u0:0 sig1
 ;;     fn2 = u0:1 sig2
 ;;     stack_limit = gv2
@@ -0,0 +1,66 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = "-Cloop-optimizations -Omemory-reservation=0x100000000 -Omemory-guard-size=0x80000000"
+
+;; With loop optimizations enabled, the load of the memory base is hoisted out
+;; of the loop and the four iterations of the loop are unrolled.
+
+(module
+  (memory 1)
+  (func (export "sum4") (param i32) (result i32)
+    (local $i i32) (local $sum i32)
+    (loop $l
+      (local.set $sum
+        (i32.add
+          (local.get $sum)
+          (i32.load (i32.add (local.get 0) (i32.mul (local.get $i) (i32.const 4))))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $l (i32.lt_u (local.get $i) (i32.const 4))))
+    (local.get $sum))
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @002a                               jump block2
+;;
+;;                                 block2:
+;; @0036                               v12 = load.i64 notrap aligned readonly can_move checked v0+56
+;; @0036                               v24 = uextend.i64 v2
+;; @0036                               v25 = iadd v12, v24
+;; @0036                               v26 = load.i32 little heap v25
+;; @0032                               v8 = iconst.i32 4
+;; @0035                               v32 = iadd.i32 v2, v8  ; v8 = 4
+;; @0036                               v33 = uextend.i64 v32
+;; @0036                               v34 = iadd v12, v33
+;; @0036                               v35 = load.i32 little heap v34
+;;                                     v91 = iconst.i32 8
+;; @0035                               v41 = iadd.i32 v2, v91  ; v91 = 8
+;; @0036                               v42 = uextend.i64 v41
+;; @0036                               v43 = iadd v12, v42
+;; @0036                               v44 = load.i32 little heap v43
+;;                                     v118 = iconst.i32 12
+;; @0035                               v10 = iadd.i32 v2, v118  ; v118 = 12
+;; @0036                               v11 = uextend.i64 v10
+;; @0036                               v13 = iadd v12, v11
+;; @0036                               v14 = load.i32 little heap v13
+;; @0048                               jump block4
+;;
+;;                                 block4:
+;; @004a                               jump block3
+;;
+;;                                 block3:
+;; @004d                               jump block1
+;;
+;;                                 block1:
+;; @0039                               v36 = iadd.i32 v26, v35
+;;                                     v126 = iadd.i32 v44, v14
+;;                                     v127 = iadd v36, v126
+;; @004d                               return v127
+;; }
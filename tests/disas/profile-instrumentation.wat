@@ -0,0 +1,70 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = [ "-Cprofile-instrumentation=y" ]
+
+(module
+  (func (param i32 i32) (result i32)
+    (block
+      (br_if 0 (local.get 1))
+      (return (i32.const 0)))
+    local.get 0
+    if (result i32)
+      i32.const 1
+    else
+      i32.const 2
+    end))
+;; function u0:0(i64 vmctx, i64, i32, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @0019                               v6 = load.i64 notrap aligned readonly v0+48
+;; @0019                               v8 = load.i64 notrap aligned v6
+;; @0019                               v7 = iconst.i64 1
+;; @0019                               v9 = iadd v8, v7  ; v7 = 1
+;; @0019                               store notrap aligned v9, v6
+;; @001e                               v11 = load.i64 notrap aligned v6+8
+;; @001e                               v12 = iadd v11, v7  ; v7 = 1
+;; @001e                               store notrap aligned v12, v6+8
+;; @001e                               v15 = load.i64 notrap aligned v6+16
+;;                                     v29 = iconst.i32 0
+;; @001e                               v13 = icmp ne v3, v29  ; v29 = 0
+;; @001e                               v14 = uextend.i64 v13
+;; @001e                               v16 = iadd v15, v14
+;; @001e                               store notrap aligned v16, v6+16
+;; @001e                               brif v3, block2, block3
+;;
+;;                                 block3:
+;;                                     v30 = iconst.i32 0
+;; @0022                               return v30  ; v30 = 0
+;;
+;;                                 block2:
+;; @0026                               v19 = load.i64 notrap aligned v6+24
+;;                                     v31 = iconst.i64 1
+;;                                     v32 = iadd v19, v31  ; v31 = 1
+;; @0026                               store notrap aligned v32, v6+24
+;; @0026                               v23 = load.i64 notrap aligned v6+32
+;;                                     v33 = iconst.i32 0
+;;                                     v34 = icmp.i32 ne v2, v33  ; v33 = 0
+;; @0026                               v22 = uextend.i64 v34
+;; @0026                               v24 = iadd v23, v22
+;; @0026                               store notrap aligned v24, v6+32
+;; @0026                               brif.i32 v2, block4, block6
+;;
+;;                                 block4:
+;; @0028                               v26 = iconst.i32 1
+;; @002a                               jump block5(v26)  ; v26 = 1
+;;
+;;                                 block6:
+;; @002b                               v27 = iconst.i32 2
+;; @002d                               jump block5(v27)  ; v27 = 2
+;;
+;;                                 block5(v25: i32):
+;; @002e                               jump block1(v25)
+;;
+;;                                 block1(v4: i32):
+;; @002e                               return v4
+;; }
@@ -0,0 +1,116 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = [ "-Cslp-vectorization=y" ]
+
+(module
+  (memory 1)
+
+  ;; Scales four adjacent `i32`s in place.
+  (func (export "scale") (param $p i32) (param $k i32)
+    (i32.store offset=0 (local.get $p)
+      (i32.mul (i32.load offset=0 (local.get $p)) (local.get $k)))
+    (i32.store offset=4 (local.get $p)
+      (i32.mul (i32.load offset=4 (local.get $p)) (local.get $k)))
+    (i32.store offset=8 (local.get $p)
+      (i32.mul (i32.load offset=8 (local.get $p)) (local.get $k)))
+    (i32.store offset=12 (local.get $p)
+      (i32.mul (i32.load offset=12 (local.get $p)) (local.get $k)))
+  )
+
+  ;; The loads of `$a` would move before the stores to `$c`, which may
+  ;; overlap them.
+  (func (export "add_may_alias") (param $a i32) (param $c i32)
+    (f64.store offset=0 (local.get $c)
+      (f64.add (f64.load offset=0 (local.get $a)) (f64.const 1)))
+    (f64.store offset=8 (local.get $c)
+      (f64.add (f64.load offset=8 (local.get $a)) (f64.const 1)))
+  )
+
+  ;; All loads come first, so the stores may overlap them.
+  (func (export "add") (param $a i32) (param $c i32)
+    (local $x f64) (local $y f64)
+    (local.set $x (f64.load offset=0 (local.get $a)))
+    (local.set $y (f64.load offset=8 (local.get $a)))
+    (f64.store offset=0 (local.get $c) (f64.add (local.get $x) (f64.const 1)))
+    (f64.store offset=8 (local.get $c) (f64.add (local.get $y) (f64.const 2)))
+  )
+)
+;; function u0:0(i64 vmctx, i64, i32, i32) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @0046                               v5 = load.i64 notrap aligned readonly can_move checked v0+56
+;; @0046                               v4 = uextend.i64 v2
+;; @0046                               v6 = iadd v5, v4
+;; @0073                               v56 = load.i32x4 little heap v6
+;; @0073                               v57 = splat.i32x4 v3
+;; @0073                               v58 = imul v56, v57
+;; @0073                               store little heap v58, v6
+;; @0076                               jump block1
+;;
+;;                                 block1:
+;; @0076                               return
+;; }
+;;
+;; function u0:1(i64 vmctx, i64, i32, i32) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+56
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @007d                               v5 = load.i64 notrap aligned readonly can_move checked v0+56
+;; @007d                               v4 = uextend.i64 v2
+;; @007d                               v6 = iadd v5, v4
+;; @007d                               v7 = load.f64 little heap v6
+;; @0080                               v8 = f64const 0x1.0000000000000p0
+;; @0089                               v9 = fadd v7, v8  ; v8 = 0x1.0000000000000p0
+;; @008a                               v10 = uextend.i64 v3
+;; @008a                               v12 = iadd v5, v10
+;; @008a                               store little heap v9, v12
+;; @0091                               v16 = iconst.i64 8
+;; @0091                               v17 = iadd v6, v16  ; v16 = 8
+;; @0091                               v18 = load.f64 little heap v17
+;; @009d                               v20 = fadd v18, v8  ; v8 = 0x1.0000000000000p0
+;; @009e                               v25 = iadd v12, v16  ; v16 = 8
+;; @009e                               store little heap v20, v25
+;; @00a1                               jump block1
+;;
+;;                                 block1:
+;; @00a1                               return
+;; }
+;;
+;; function u0:2(i64 vmctx, i64, i32, i32) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+64
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+56
+;;     const0 = 0x40000000000000003ff0000000000000
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
+;; @00a8                               v6 = load.i64 notrap aligned readonly can_move checked v0+56
+;; @00a8                               v5 = uextend.i64 v2
+;; @00a8                               v7 = iadd v6, v5
+;; @00c2                               v17 = uextend.i64 v3
+;; @00c2                               v19 = iadd v6, v17
+;; @00d3                               v31 = load.f64x2 little heap v7
+;; @00d3                               v32 = vconst.f64x2 const0
+;; @00d3                               v33 = fadd v31, v32  ; v32 = const0
+;; @00d3                               store little heap v33, v19
+;; @00d6                               jump block1
+;;
+;;                                 block1:
+;; @00d6                               return
+;; }
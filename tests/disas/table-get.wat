@@ -26,7 +26,7 @@
 ;;     gv7 = load.i64 notrap aligned readonly can_move gv6+24
 ;;     gv8 = load.i64 notrap aligned gv6+32
 ;;     sig0 = (i64 vmctx, i32) -> i64 tail
-;;     fn0 = colocated u1:27 sig0
+;;     fn0 = colocated u1:28 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64):
@@ -119,7 +119,7 @@
 ;;     gv7 = load.i64 notrap aligned readonly can_move gv6+24
 ;;     gv8 = load.i64 notrap aligned gv6+32
 ;;     sig0 = (i64 vmctx, i32) -> i64 tail
-;;     fn0 = colocated u1:27 sig0
+;;     fn0 = colocated u1:28 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32):
@@ -27,7 +27,7 @@
 ;;     gv7 = load.i64 notrap aligned readonly can_move gv6+24
 ;;     gv8 = load.i64 notrap aligned gv6+32
 ;;     sig0 = (i64 vmctx, i32) tail
-;;     fn0 = colocated u1:25 sig0
+;;     fn0 = colocated u1:26 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32):
@@ -126,7 +126,7 @@
 ;;     gv7 = load.i64 notrap aligned readonly can_move gv6+24
 ;;     gv8 = load.i64 notrap aligned gv6+32
 ;;     sig0 = (i64 vmctx, i32) tail
-;;     fn0 = colocated u1:25 sig0
+;;     fn0 = colocated u1:26 sig0
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i32, v3: i32):
@@ -0,0 +1,89 @@
+;;! bounds_check_merging = true
+
+;; Bounds checks that are merged across blocks must still trap exactly when
+;; the accesses they replace would have.
+
+(module
+  (memory 1)
+  (global $calls (mut i32) (i32.const 0))
+  (func $f (global.set $calls (i32.add (global.get $calls) (i32.const 1))))
+  (func (export "calls") (result i32) global.get $calls)
+
+  ;; Loads at offsets 0 and 4 on both sides of an `if`.
+  (func (export "if-between") (param i32 i32) (result i32)
+    (local i32)
+    local.get 0
+    i32.load offset=0
+    local.set 2
+    local.get 1
+    if
+      local.get 2
+      i32.const 1
+      i32.add
+      local.set 2
+    end
+    local.get 0
+    i32.load offset=4
+    local.get 2
+    i32.add
+  )
+
+  ;; A call between the loads must happen before the second one traps.
+  (func (export "call-between") (param i32 i32) (result i32)
+    local.get 0
+    i32.load offset=0
+    local.get 1
+    if
+      call $f
+    end
+    local.get 0
+    i32.load offset=4
+    i32.add
+  )
+
+  ;; A store between the loads must happen before the second one traps.
+  (func (export "store-between") (param i32 i32) (result i32)
+    local.get 0
+    i32.load offset=0
+    local.get 1
+    if
+      i32.const 0
+      i32.const 42
+      i32.store
+    end
+    local.get 0
+    i32.load offset=4
+    i32.add
+  )
+
+  ;; A branch out between the loads means the second one may not execute.
+  (func (export "return-between") (param i32 i32) (result i32)
+    local.get 0
+    i32.load offset=0
+    local.get 1
+    if
+      i32.const -1
+      return
+    end
+    local.get 0
+    i32.load offset=4
+    i32.add
+  )
+
+  (func (export "load") (param i32) (result i32) local.get 0 i32.load)
+)
+
+(assert_return (invoke "if-between" (i32.const 0) (i32.const 1)) (i32.const 1))
+(assert_return (invoke "if-between" (i32.const 65528) (i32.const 0)) (i32.const 0))
+(assert_trap (invoke "if-between" (i32.const 65532) (i32.const 1)) "out of bounds")
+
+(assert_trap (invoke "call-between" (i32.const 65532) (i32.const 1)) "out of bounds")
+(assert_return (invoke "calls") (i32.const 1))
+(assert_trap (invoke "call-between" (i32.const 65536) (i32.const 1)) "out of bounds")
+(assert_return (invoke "calls") (i32.const 1))
+
+(assert_trap (invoke "store-between" (i32.const 65532) (i32.const 1)) "out of bounds")
+(assert_return (invoke "load" (i32.const 0)) (i32.const 42))
+
+(assert_return (invoke "return-between" (i32.const 65532) (i32.const 1)) (i32.const -1))
+(assert_trap (invoke "return-between" (i32.const 65532) (i32.const 0)) "out of bounds")
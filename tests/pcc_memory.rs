@@ -45,59 +45,108 @@ mod pcc_memory_tests {
         r#"
   local.get 0
   i64.load offset=0x10000
+  drop
+    "#,
+        r#"
+  local.get 0
+  i32.const 1
+  i32.store8
+    "#,
+        r#"
+  local.get 0
+  i64.const 1
+  i64.store offset=0x10000
+    "#,
+        r#"
+  i32.const 0x1000
+  i32.load offset=0x10
   drop
     "#,
     ];
 
-    #[test]
-    #[cfg_attr(miri, ignore)]
-    fn test_build() {
-        let _ = env_logger::try_init();
-        const KIB: u64 = 1024;
-        const MIB: u64 = 1024 * KIB;
-        const GIB: u64 = 1024 * MIB;
+    /// Targets whose backends have a PCC checker.
+    const TARGETS: &'static [&'static str] =
+        &["x86_64", "aarch64", "riscv64gc", "s390x", "pulley64"];
+
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    const GIB: u64 = 1024 * MIB;
 
+    fn bodies(memory_bits: u32) -> Vec<String> {
+        let memory = if memory_bits == 64 { "i64" } else { "i32" };
         let mut bodies = vec![];
+        let snippets = TESTS
+            .iter()
+            .map(|s| s.replace("i32.const 0x1000", &format!("{memory}.const 0x1000")))
+            .collect::<Vec<_>>();
         for (mem_min, mem_max) in [(1, 1), (10, 20)] {
-            for &snippet in TESTS {
+            for snippet in &snippets {
                 bodies.push(format!(
-                    "(module (memory {mem_min} {mem_max}) (func (param i32) {snippet}))"
+                    "(module (memory {memory} {mem_min} {mem_max}) (func (param {memory}) {snippet}))"
                 ));
             }
-            let all_snippets = TESTS
-                .iter()
-                .map(|s| s.to_owned())
-                .collect::<Vec<_>>()
-                .join("\n");
+            let all_snippets = snippets.join("\n");
             bodies.push(format!(
-                "(module (memory {mem_min} {mem_max}) (func (param i32) {all_snippets}))"
+                "(module (memory {memory} {mem_min} {mem_max}) (func (param {memory}) {all_snippets}))"
             ));
         }
+        bodies
+    }
 
-        for test in &bodies {
-            for memory_reservation in [4 * GIB] {
-                for guard_size in [2 * GIB] {
-                    for enable_spectre in [true /* not yet supported by PCC: false */] {
-                        for _memory_bits in [32 /* not yet supported by PCC: 64 */] {
-                            log::trace!("test:\n{}\n", test);
-                            log::trace!("static {:x} guard {:x}", memory_reservation, guard_size);
-                            let mut cfg = Config::new();
-                            cfg.memory_reservation(memory_reservation);
-                            cfg.memory_guard_size(guard_size);
-                            cfg.cranelift_pcc(true);
-                            unsafe {
-                                cfg.cranelift_flag_set(
-                                    "enable_heap_access_spectre_mitigation",
-                                    &enable_spectre.to_string(),
-                                );
-                            }
-                            // TODO: substitute memory32/memory64 into
-                            // test module.
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_build() {
+        let _ = env_logger::try_init();
+
+        for &target in TARGETS {
+            for memory_bits in [32, 64] {
+                let bodies = bodies(memory_bits);
+                for (memory_reservation, guard_size) in [
+                    // Static memory with bounds checks elided.
+                    (4 * GIB, 2 * GIB),
+                    // Static memory with explicit bounds checks.
+                    (4 * GIB, 0),
+                    // Dynamic memory, with and without a guard region.
+                    (0, 64 * KIB),
+                    (0, 0),
+                ] {
+                    for enable_spectre in [true, false] {
+                        // Pulley doesn't support Spectre mitigations since it
+                        // can't rely on signals-based traps.
+                        if enable_spectre && target.starts_with("pulley") {
+                            continue;
+                        }
+                        log::trace!(
+                            "target {target} memory{memory_bits} reservation {memory_reservation:#x} \
+                             guard {guard_size:#x} spectre {enable_spectre}"
+                        );
+                        let mut cfg = Config::new();
+                        cfg.target(target).unwrap();
+                        cfg.wasm_memory64(true);
+                        cfg.memory_reservation(memory_reservation);
+                        cfg.memory_guard_size(guard_size);
+                        cfg.cranelift_pcc(true);
+                        unsafe {
+                            cfg.cranelift_flag_set(
+                                "enable_heap_access_spectre_mitigation",
+                                &enable_spectre.to_string(),
+                            );
+                        }
 
-                            let engine = Engine::new(&cfg).unwrap();
+                        let engine = Engine::new(&cfg).unwrap();
 
-                            let _module = Module::new(&engine, test)
-                                .expect("compilation with PCC should succeed");
+                        for test in &bodies {
+                            log::trace!("test:\n{test}\n");
+                            engine
+                                .precompile_module(test.as_bytes())
+                                .unwrap_or_else(|e| {
+                                    panic!(
+                                        "compilation with PCC should succeed for {target} \
+                                     memory{memory_bits} reservation {memory_reservation:#x} \
+                                     guard {guard_size:#x} spectre {enable_spectre}:\n\
+                                     {test}\n{e:?}"
+                                    )
+                                });
                         }
                     }
                 }
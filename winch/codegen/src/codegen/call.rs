@@ -70,7 +70,7 @@ use crate::{
     stack::Val,
 };
 use anyhow::{Result, ensure};
-use wasmtime_environ::{FuncIndex, PtrSize, VMOffsets};
+use wasmtime_environ::{FuncIndex, FuncRefIndex, PtrSize, VMOffsets};
 
 /// All the information needed to emit a function call.
 #[derive(Copy, Clone)]
@@ -145,6 +145,11 @@ impl FnCall {
             Callee::FuncRef(_) => {
                 Self::lower_funcref(env.callee_sig::<M::ABI>(callee)?, ptr, context, masm)
             }
+            Callee::Local(i) if env.translation.module.tier_up => {
+                let func_ref = env.translation.module.functions[*i].func_ref;
+                let sig = env.callee_sig::<M::ABI>(callee)?;
+                Self::lower_local_func_ref(func_ref, sig, context, masm, vmoffsets)
+            }
             Callee::Local(i) => Ok(Self::lower_local(env, *i)),
             Callee::Import(i) => {
                 let sig = env.callee_sig::<M::ABI>(callee)?;
@@ -178,6 +183,31 @@ impl FnCall {
         )
     }
 
+    /// Lowers a local function of a module which tiers up by loading its
+    /// address from its `VMFuncRef`, which the runtime redirects to the
+    /// optimized code of the function once it's available.
+    fn lower_local_func_ref<M: MacroAssembler, P: PtrSize>(
+        func_ref: FuncRefIndex,
+        sig: &ABISig,
+        context: &mut CodeGenContext<Emission>,
+        masm: &mut M,
+        vmoffsets: &VMOffsets<P>,
+    ) -> Result<(CalleeKind, ContextArgs)> {
+        let callee =
+            context.without::<Result<Reg>, M, _>(&sig.regs, masm, |context, masm| {
+                context.any_gpr(masm)
+            })??;
+        let callee_body_offset =
+            vmoffsets.vmctx_func_ref(func_ref) + u32::from(vmoffsets.ptr.vm_func_ref_wasm_call());
+        let callee_addr = masm.address_at_vmctx(callee_body_offset)?;
+        masm.load_ptr(callee_addr, writable!(callee))?;
+
+        Ok((
+            CalleeKind::indirect(callee),
+            ContextArgs::pinned_callee_and_caller_vmctx(),
+        ))
+    }
+
     /// Lowers a function import by loading its address to the next available
     /// register.
     fn lower_import<M: MacroAssembler, P: PtrSize>(
@@ -22,8 +22,8 @@ use wasmparser::{
 };
 use wasmtime_cranelift::{TRAP_BAD_SIGNATURE, TRAP_HEAP_MISALIGNED, TRAP_TABLE_OUT_OF_BOUNDS};
 use wasmtime_environ::{
-    FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex, WasmHeapType,
-    WasmValType,
+    DefinedFuncIndex, FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables,
+    TypeIndex, WasmHeapType, WasmValType,
 };
 
 mod context;
@@ -115,6 +115,9 @@ where
 
     /// Local counter to track fuel consumption.
     pub fuel_consumed: i64,
+
+    /// The index of the function being compiled.
+    pub func_index: DefinedFuncIndex,
     phase: PhantomData<P>,
 }
 
@@ -128,6 +131,7 @@ where
         context: CodeGenContext<'a, Prologue>,
         env: FuncEnv<'a, 'translation, 'data, M::Ptr>,
         sig: ABISig,
+        func_index: DefinedFuncIndex,
     ) -> CodeGen<'a, 'translation, 'data, M, Prologue> {
         Self {
             sig,
@@ -139,6 +143,7 @@ where
             control_frames: Default::default(),
             // Empty functions should consume at least 1 fuel unit.
             fuel_consumed: 1,
+            func_index,
             phase: PhantomData,
         }
     }
@@ -199,6 +204,7 @@ where
             source_location: self.source_location,
             control_frames: self.control_frames,
             fuel_consumed: self.fuel_consumed,
+            func_index: self.func_index,
             phase: PhantomData,
         })
     }
@@ -319,6 +325,8 @@ where
 
         self.maybe_emit_epoch_check()?;
 
+        self.maybe_emit_tier_up_check()?;
+
         // Once we have emitted the epilogue and reserved stack space for the locals, we push the
         // base control flow block.
         self.control_frames.push(ControlStackFrame::block(
@@ -1231,6 +1239,83 @@ where
         Ok(())
     }
 
+    /// Checks if the module tiers up and emits a series of instructions that
+    /// count the calls of the current function, calling the `tier_up` builtin
+    /// once every `tier_up_threshold` calls.
+    ///
+    /// The call counter lives in the `VMContext` of the instance, which only
+    /// runs on one thread at a time, so it's updated with a plain load and
+    /// store.
+    pub fn maybe_emit_tier_up_check(&mut self) -> Result<()> {
+        if !self.env.translation.module.tier_up {
+            return Ok(());
+        }
+        let threshold = self.tunables.tier_up_threshold;
+        ensure!(
+            threshold.is_power_of_two(),
+            "tier-up threshold must be a power of two"
+        );
+
+        let cont = self.masm.get_label()?;
+        let tier_up = self.env.builtins.tier_up::<M::ABI, M::Ptr>()?;
+
+        let count_reg = self.context.without::<Result<Reg>, M, _>(
+            &tier_up.sig().regs,
+            self.masm,
+            |cx, masm| cx.any_gpr(masm),
+        )??;
+
+        // Increment the call counter of this function. The counter wraps
+        // around, which is fine since only its low bits are checked.
+        let counter_offset = self.env.vmoffsets.vmctx_tier_up_counter(self.func_index);
+        self.masm.load(
+            self.masm.address_at_vmctx(counter_offset)?,
+            writable!(count_reg),
+            OperandSize::S32,
+        )?;
+        self.masm.add(
+            writable!(count_reg),
+            count_reg,
+            RegImm::i32(1),
+            OperandSize::S32,
+        )?;
+        self.masm.store(
+            count_reg.into(),
+            self.masm.address_at_vmctx(counter_offset)?,
+            OperandSize::S32,
+        )?;
+        self.masm.and(
+            writable!(count_reg),
+            count_reg,
+            RegImm::i32(i32::try_from(threshold - 1)?),
+            OperandSize::S32,
+        )?;
+
+        // Spill locals and registers to avoid conflicts at the control flow
+        // merge below.
+        self.context.spill(self.masm)?;
+        self.masm.branch(
+            IntCmpKind::Ne,
+            count_reg,
+            RegImm::i32(0),
+            cont,
+            OperandSize::S32,
+        )?;
+        // Threshold reached branch.
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(tier_up.clone()),
+        )?;
+        self.context.pop_and_free(self.masm)?;
+
+        self.masm.bind(cont)?;
+
+        self.context.free_reg(count_reg);
+        Ok(())
+    }
+
     fn emit_load_epoch_deadline_and_counter(
         &mut self,
         epoch_deadline_reg: Reg,
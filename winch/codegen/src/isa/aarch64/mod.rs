@@ -17,7 +17,9 @@ use masm::MacroAssembler as Aarch64Masm;
 use target_lexicon::Triple;
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType,
+};
 
 mod abi;
 mod address;
@@ -82,6 +84,7 @@ impl TargetIsa for Aarch64 {
 
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
@@ -111,7 +114,7 @@ impl TargetIsa for Aarch64 {
         let frame = Frame::new::<abi::Aarch64ABI>(&abi_sig, &defined_locals)?;
         let regalloc = RegAlloc::from(gpr_bit_set(), fpr_bit_set());
         let codegen_context = CodeGenContext::new(regalloc, stack, frame, &vmoffsets);
-        let codegen = CodeGen::new(tunables, &mut masm, codegen_context, env, abi_sig);
+        let codegen = CodeGen::new(tunables, &mut masm, codegen_context, env, abi_sig, index);
 
         let mut body_codegen = codegen.emit_prologue()?;
         body_codegen.emit(body, validator)?;
@@ -16,7 +16,9 @@ use cranelift_codegen::{MachTextSectionBuilder, TextSectionBuilder};
 use target_lexicon::Triple;
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, VMOffsets, WasmFuncType,
+};
 
 use self::regs::{fpr_bit_set, gpr_bit_set};
 
@@ -85,6 +87,7 @@ impl TargetIsa for X64 {
 
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
@@ -120,7 +123,7 @@ impl TargetIsa for X64 {
         let frame = Frame::new::<abi::X64ABI>(&abi_sig, &defined_locals)?;
         let regalloc = RegAlloc::from(gpr_bit_set(), fpr_bit_set());
         let codegen_context = CodeGenContext::new(regalloc, stack, frame, &vmoffsets);
-        let codegen = CodeGen::new(tunables, &mut masm, codegen_context, env, abi_sig);
+        let codegen = CodeGen::new(tunables, &mut masm, codegen_context, env, abi_sig, index);
 
         let mut body_codegen = codegen.emit_prologue()?;
 